@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer, Burn};
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer, Burn};
+use oracle::MeterReading;
+use registry::{MeterAccount, MeterStatus};
 
 declare_id!("2CVWTnckn5TXUWXdZoZE6LydiQJGMYHVVPipkoy1LVqr");
 
@@ -13,12 +15,17 @@ pub mod energy_token {
     }
     
     /// Initialize the energy token program
-    pub fn initialize_token(ctx: Context<InitializeToken>) -> Result<()> {
+    ///
+    /// The mint's authority must already be the `token_info` PDA, so tokens can only be
+    /// minted against oracle readings. `mint_cap` bounds the circulating supply.
+    pub fn initialize_token(ctx: Context<InitializeToken>, mint_cap: u64) -> Result<()> {
         let token_info = &mut ctx.accounts.token_info;
         token_info.authority = ctx.accounts.authority.key();
         token_info.mint = ctx.accounts.mint.key();
         token_info.total_supply = 0;
         token_info.created_at = Clock::get()?.unix_timestamp;
+        token_info.mint_cap = mint_cap;
+        token_info.bump = ctx.bumps.token_info;
         
         msg!("Token initialized with authority: {}", token_info.authority);
         
@@ -43,6 +50,79 @@ pub mod energy_token {
         Ok(())
     }
     
+    /// Mint 1 token per kWh of a reading's net generation to the meter's owner
+    ///
+    /// The reading must be an oracle `MeterReading` for an active registry meter. Each
+    /// reading mints once; readings with no net generation are rejected.
+    pub fn mint_from_reading(ctx: Context<MintFromReading>) -> Result<()> {
+        let meter_reading = &ctx.accounts.meter_reading;
+        let amount = meter_reading
+            .energy_produced
+            .saturating_sub(meter_reading.energy_consumed);
+        require!(amount > 0, ErrorCode::NoNetGeneration);
+        require!(
+            ctx.accounts.meter_account.status == MeterStatus::Active,
+            ErrorCode::InvalidMeter
+        );
+        
+        let token_info = &mut ctx.accounts.token_info;
+        let total_supply = token_info
+            .total_supply
+            .checked_add(amount)
+            .ok_or(ErrorCode::MintCapExceeded)?;
+        require!(total_supply <= token_info.mint_cap, ErrorCode::MintCapExceeded);
+        
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                    authority: token_info.to_account_info(),
+                },
+                &[&[b"token_info", &[token_info.bump]]],
+            ),
+            amount,
+        )?;
+        token_info.total_supply = total_supply;
+        
+        let clock = Clock::get()?;
+        let generation_mint = &mut ctx.accounts.generation_mint;
+        generation_mint.meter_reading = meter_reading.key();
+        generation_mint.recipient = ctx.accounts.meter_account.owner;
+        generation_mint.amount = amount;
+        generation_mint.minted_at = clock.unix_timestamp;
+        
+        emit!(GenerationMinted {
+            meter_reading: meter_reading.key(),
+            meter_id: meter_reading.meter_id.clone(),
+            recipient: generation_mint.recipient,
+            amount,
+            total_supply,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Minted {} tokens for meter {}", amount, meter_reading.meter_id);
+        
+        Ok(())
+    }
+    
+    /// Update the cap on circulating supply (admin only)
+    pub fn update_mint_cap(ctx: Context<UpdateMintCap>, mint_cap: u64) -> Result<()> {
+        let token_info = &mut ctx.accounts.token_info;
+        let old_mint_cap = token_info.mint_cap;
+        token_info.mint_cap = mint_cap;
+        
+        emit!(MintCapUpdated {
+            authority: ctx.accounts.authority.key(),
+            old_mint_cap,
+            new_mint_cap: mint_cap,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
     /// Transfer energy tokens between accounts
     pub fn transfer_tokens(
         ctx: Context<TransferTokens>,
@@ -106,6 +186,7 @@ pub struct InitializeToken<'info> {
     )]
     pub token_info: Account<'info, TokenInfo>,
     
+    #[account(mint::authority = token_info)]
     pub mint: Account<'info, Mint>,
     
     #[account(mut)]
@@ -114,6 +195,55 @@ pub struct InitializeToken<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct MintFromReading<'info> {
+    #[account(mut, seeds = [b"token_info"], bump = token_info.bump)]
+    pub token_info: Account<'info, TokenInfo>,
+    
+    #[account(mut, address = token_info.mint)]
+    pub mint: Account<'info, Mint>,
+    
+    pub meter_reading: Account<'info, MeterReading>,
+    
+    #[account(
+        seeds = [b"meter", meter_reading.meter_id.as_bytes()],
+        bump,
+        seeds::program = registry::ID
+    )]
+    pub meter_account: Account<'info, MeterAccount>,
+    
+    /// Marks the reading as minted
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + GenerationMint::INIT_SPACE,
+        seeds = [b"generation_mint", meter_reading.key().as_ref()],
+        bump
+    )]
+    pub generation_mint: Account<'info, GenerationMint>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = meter_account.owner
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMintCap<'info> {
+    #[account(mut, seeds = [b"token_info"], bump = token_info.bump, has_one = authority @ ErrorCode::UnauthorizedAuthority)]
+    pub token_info: Account<'info, TokenInfo>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct AddRecValidator<'info> {
     #[account(mut, has_one = authority @ ErrorCode::UnauthorizedAuthority)]
@@ -137,10 +267,10 @@ pub struct TransferTokens<'info> {
 
 #[derive(Accounts)]
 pub struct BurnTokens<'info> {
-    #[account(mut)]
+    #[account(mut, seeds = [b"token_info"], bump = token_info.bump)]
     pub token_info: Account<'info, TokenInfo>,
     
-    #[account(mut)]
+    #[account(mut, address = token_info.mint)]
     pub mint: Account<'info, Mint>,
     
     #[account(mut)]
@@ -159,6 +289,38 @@ pub struct TokenInfo {
     pub mint: Pubkey,
     pub total_supply: u64,
     pub created_at: i64,
+    /// Maximum circulating supply, in kWh
+    pub mint_cap: u64,
+    pub bump: u8,
+}
+
+/// Tokens minted for one oracle meter reading
+#[account]
+#[derive(InitSpace)]
+pub struct GenerationMint {
+    pub meter_reading: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub minted_at: i64,
+}
+
+// Events
+#[event]
+pub struct GenerationMinted {
+    pub meter_reading: Pubkey,
+    pub meter_id: String,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub total_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MintCapUpdated {
+    pub authority: Pubkey,
+    pub old_mint_cap: u64,
+    pub new_mint_cap: u64,
+    pub timestamp: i64,
 }
 
 // Errors
@@ -170,4 +332,8 @@ pub enum ErrorCode {
     InvalidMeter,
     #[msg("Insufficient token balance")]
     InsufficientBalance,
+    #[msg("Reading has no net generation to mint")]
+    NoNetGeneration,
+    #[msg("Minting would exceed the mint cap")]
+    MintCapExceeded,
 }
\ No newline at end of file
@@ -1,7 +1,29 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_2022::{self, spl_token_2022::instruction::AuthorityType, Token2022};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use oracle::MeterReading;
+use registry::{MeterAccount, MeterStatus};
 
 declare_id!("Dy8JFn95L1E7NoUkXbFQtW1kGR7Ja21CkNcirNgv4ghe");
 
+/// Seed of the trading program PDA that signs `lock_erc_for_trade` and `release_erc`
+pub const ERC_LOCK_AUTHORITY_SEED: &[u8] = b"erc_lock_authority";
+
+/// Staking program whose bonded delegates may validate certificates for trading
+pub const STAKING_PROGRAM_ID: Pubkey = pubkey!("8yJntc7AgDFWX1pzuqgZxQV1QM7ugYBCpMmWW9ZT6daM");
+
+/// Seed, followed by the delegate key, of the staking program PDA that signs
+/// `validate_erc_as_delegate`
+pub const VALIDATOR_AUTHORITY_SEED: &[u8] = b"validator_authority";
+
+/// Domain separator prefixed to every meter-signed issuance payload
+pub const METER_ATTESTATION_DOMAIN: &[u8] = b"gridtokenx:erc_issuance:v1";
+
 #[program]
 pub mod governance {
     use super::*;
@@ -13,8 +35,11 @@ pub mod governance {
         
         poa_config.authority = ctx.accounts.authority.key();
         poa_config.authority_name = "University Engineering Department".to_string();
-        poa_config.contact_info = "engineering_erc@utcc.ac.th".to_string();
-        poa_config.emergency_paused = false;
+        poa_config.contact_email = "engineering_erc@utcc.ac.th".to_string();
+        poa_config.contact_phone = String::new();
+        poa_config.website = String::new();
+        poa_config.office_location = String::new();
+        poa_config.pause_flags = 0;
         poa_config.emergency_timestamp = None;
         poa_config.emergency_reason = None;
         poa_config.created_at = clock.unix_timestamp;
@@ -23,12 +48,17 @@ pub mod governance {
         poa_config.max_erc_amount = 1_000_000; // 1M kWh max per ERC
         poa_config.total_ercs_issued = 0;
         poa_config.total_ercs_validated = 0;
-        poa_config.version = 1;
+        poa_config.version = PoAConfig::CURRENT_VERSION;
         poa_config.delegation_enabled = false;
         poa_config.oracle_authority = None;
         poa_config.min_energy_amount = 100; // 100 kWh minimum
         poa_config.erc_validity_period = 31_536_000; // 1 year in seconds
         poa_config.maintenance_mode = false;
+        poa_config.erc_issuance_fee = 0;
+        poa_config.council_mode = false;
+        poa_config.max_pause_duration = PoAConfig::DEFAULT_MAX_PAUSE_DURATION;
+        poa_config.trading_program = None;
+        poa_config.reset_issuance_rate_limit(clock.unix_timestamp);
         
         emit!(PoAInitialized {
             authority: ctx.accounts.authority.key(),
@@ -42,17 +72,30 @@ pub mod governance {
     }
 
     /// Emergency pause functionality - Engineering Department only
+    ///
+    /// Pauses ERC issuance and validation; configuration updates stay available so
+    /// administrative fixes can still be applied. Use `set_pause_flags` for finer control.
+    /// Stays available to the authority in council mode so incidents can be contained quickly.
+    /// Pauses lapse after `max_pause_duration` so a lost authority key cannot halt the system.
     pub fn emergency_pause(ctx: Context<EmergencyControl>) -> Result<()> {
         let poa_config = &mut ctx.accounts.poa_config;
+        let clock = Clock::get()?;
         
-        require!(!poa_config.emergency_paused, GovernanceError::AlreadyPaused);
+        require!(
+            !poa_config.is_paused(PoAConfig::EMERGENCY_PAUSE_FLAGS, clock.unix_timestamp),
+            GovernanceError::AlreadyPaused
+        );
         
-        poa_config.emergency_paused = true;
-        poa_config.emergency_timestamp = Some(Clock::get()?.unix_timestamp);
+        // A lapsed pause is replaced, restarting the pause window
+        let active_flags = poa_config.active_pause_flags(clock.unix_timestamp);
+        if active_flags == 0 {
+            poa_config.emergency_timestamp = Some(clock.unix_timestamp);
+        }
+        poa_config.pause_flags = active_flags | PoAConfig::EMERGENCY_PAUSE_FLAGS;
         
         emit!(EmergencyPauseActivated {
             authority: ctx.accounts.authority.key(),
-            timestamp: Clock::get()?.unix_timestamp,
+            timestamp: clock.unix_timestamp,
         });
         
         msg!("Emergency pause activated by Engineering Department");
@@ -63,9 +106,10 @@ pub mod governance {
     pub fn emergency_unpause(ctx: Context<EmergencyControl>) -> Result<()> {
         let poa_config = &mut ctx.accounts.poa_config;
         
-        require!(poa_config.emergency_paused, GovernanceError::NotPaused);
+        require!(!poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
+        require!(poa_config.pause_flags != 0, GovernanceError::NotPaused);
         
-        poa_config.emergency_paused = false;
+        poa_config.pause_flags = 0;
         poa_config.emergency_timestamp = None;
         
         emit!(EmergencyPauseDeactivated {
@@ -77,107 +121,559 @@ pub mod governance {
         Ok(())
     }
 
+    /// Set pause flags as a bitmask (see `PoAConfig::PAUSE_*`) - Engineering Department only
+    pub fn set_pause_flags(ctx: Context<EmergencyControl>, pause_flags: u8) -> Result<()> {
+        let poa_config = &mut ctx.accounts.poa_config;
+        
+        require!(!poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
+        
+        apply_pause_flags(poa_config, pause_flags, ctx.accounts.authority.key(), &Clock::get()?)
+    }
+
+    /// Clear a pause that outlived `max_pause_duration` - permissionless crank
+    ///
+    /// Lapsed pauses are already ignored by every pause check; this records the expiry on chain.
+    pub fn expire_pause(ctx: Context<ExpirePause>) -> Result<()> {
+        let poa_config = &mut ctx.accounts.poa_config;
+        let clock = Clock::get()?;
+        
+        require!(poa_config.pause_flags != 0, GovernanceError::NotPaused);
+        require!(poa_config.pause_lapsed(clock.unix_timestamp), GovernanceError::PauseNotExpired);
+        
+        let paused_at = poa_config.emergency_timestamp.unwrap_or_default();
+        poa_config.pause_flags = 0;
+        poa_config.emergency_timestamp = None;
+        poa_config.last_updated = clock.unix_timestamp;
+        
+        emit!(EmergencyPauseExpired {
+            paused_at,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Emergency pause from {} expired", paused_at);
+        Ok(())
+    }
+
+    /// Set how long a pause lasts before it lapses; 0 disables expiry - Engineering Department only
+    pub fn update_max_pause_duration(
+        ctx: Context<UpdateGovernanceConfig>,
+        max_pause_duration: i64,
+    ) -> Result<()> {
+        let poa_config = &mut ctx.accounts.poa_config;
+        
+        require!(!poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
+        
+        apply_max_pause_duration(poa_config, max_pause_duration, ctx.accounts.authority.key(), &Clock::get()?)
+    }
+
+    /// Set the sliding-window issuance caps; 0 disables a cap - Engineering Department only
+    ///
+    /// Bounds how much an authority key can issue before anyone notices, and is the override
+    /// for legitimate bulk issuance.
+    pub fn update_issuance_rate_limit(
+        ctx: Context<UpdateGovernanceConfig>,
+        issuance_window: i64,
+        max_window_ercs: u64,
+        max_window_energy: u64,
+    ) -> Result<()> {
+        let poa_config = &mut ctx.accounts.poa_config;
+        
+        require!(!poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
+        
+        apply_issuance_rate_limit(
+            poa_config,
+            issuance_window,
+            max_window_ercs,
+            max_window_energy,
+            ctx.accounts.authority.key(),
+            &Clock::get()?,
+        )
+    }
+
+    /// Set the trading program allowed to lock certificates via CPI; `None` disables locking
+    /// - Engineering Department only
+    pub fn set_trading_program(
+        ctx: Context<UpdateGovernanceConfig>,
+        trading_program: Option<Pubkey>,
+    ) -> Result<()> {
+        let poa_config = &mut ctx.accounts.poa_config;
+        
+        require!(!poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
+        
+        apply_trading_program(poa_config, trading_program, ctx.accounts.authority.key(), &Clock::get()?)
+    }
+
     /// Issue ERC (Energy Renewable Certificate) - Engineering Department only
-    pub fn issue_erc(
-        ctx: Context<IssueErc>,
+    ///
+    /// Oracle meter reading PDAs backing the certificate are passed as remaining accounts, each
+    /// followed by its writable `ReadingClaim` PDA. They are required when an oracle authority
+    /// is configured.
+    ///
+    /// `period` is the current UTC month as `YYYYMM`, selecting the month index PDA.
+    ///
+    /// When `meter_account` is supplied, the meter's registered device key must have signed
+    /// `meter_attestation_message` in an Ed25519 program instruction placed immediately
+    /// before this one, and the certificate records the meter as its attesting hardware.
+    pub fn issue_erc<'info>(
+        ctx: Context<'_, '_, 'info, 'info, IssueErc<'info>>,
         certificate_id: String,
         energy_amount: u64,
-        renewable_source: String,
+        renewable_source: RenewableSource,
         validation_data: String,
+        period: u32,
     ) -> Result<()> {
         let poa_config = &mut ctx.accounts.poa_config;
         let erc_certificate = &mut ctx.accounts.erc_certificate;
         let clock = Clock::get()?;
         
-        require!(!poa_config.emergency_paused, GovernanceError::SystemPaused);
+        require!(!poa_config.is_paused(PoAConfig::PAUSE_ISSUANCE, clock.unix_timestamp), GovernanceError::IssuancePaused);
         require!(!poa_config.maintenance_mode, GovernanceError::MaintenanceMode);
         require!(poa_config.erc_validation_enabled, GovernanceError::ErcValidationDisabled);
         require!(energy_amount >= poa_config.min_energy_amount, GovernanceError::BelowMinimumEnergy);
         require!(energy_amount <= poa_config.max_erc_amount, GovernanceError::ExceedsMaximumEnergy);
-        require!(certificate_id.len() <= 64, GovernanceError::CertificateIdTooLong);
-        require!(renewable_source.len() <= 64, GovernanceError::SourceNameTooLong);
+        require!(certificate_id.len() <= ErcCertificate::MAX_CERTIFICATE_ID_LEN, GovernanceError::CertificateIdTooLong);
+        require!(validation_data.len() <= ErcCertificate::MAX_VALIDATION_DATA_LEN, GovernanceError::ValidationDataTooLong);
+        renewable_source.validate()?;
+        require!(period == month_period(clock.unix_timestamp), GovernanceError::InvalidIndexPeriod);
+        poa_config.record_issuance(energy_amount, clock.unix_timestamp)?;
+        
+        let source_readings = claim_source_readings(
+            ctx.remaining_accounts,
+            energy_amount,
+            poa_config.oracle_authority,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        
+        let attested_by = match &ctx.accounts.meter_account {
+            Some(meter_account) => {
+                require!(meter_account.status == MeterStatus::Active, GovernanceError::MeterNotActive);
+                let instructions = ctx
+                    .accounts
+                    .instructions
+                    .as_ref()
+                    .ok_or(GovernanceError::MissingMeterSignature)?;
+                let message = meter_attestation_message(&certificate_id, energy_amount, &renewable_source, period);
+                verify_ed25519_instruction(
+                    instructions,
+                    &meter_account.meter_pubkey,
+                    &message,
+                    GovernanceError::MissingMeterSignature,
+                    GovernanceError::InvalidMeterSignature,
+                )?;
+                Some(meter_account.key())
+            }
+            None => None,
+        };
         
+        erc_certificate.renewable_source = renewable_source.clone();
         erc_certificate.certificate_id = certificate_id.clone();
         erc_certificate.authority = ctx.accounts.authority.key();
         erc_certificate.energy_amount = energy_amount;
-        erc_certificate.renewable_source = renewable_source.clone();
         erc_certificate.validation_data = validation_data;
         erc_certificate.issued_at = clock.unix_timestamp;
         erc_certificate.status = ErcStatus::Valid;
         erc_certificate.validated_for_trading = false;
         erc_certificate.expires_at = Some(clock.unix_timestamp + poa_config.erc_validity_period);
+        erc_certificate.source_readings = source_readings;
+        erc_certificate.trade_lock = None;
+        erc_certificate.extensions = Vec::new();
+        erc_certificate.attested_by = attested_by;
+        erc_certificate.challenge = None;
+        erc_certificate.validated_by = None;
+        
+        let fee = poa_config.erc_issuance_fee;
+        if fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+            
+            let treasury = &mut ctx.accounts.treasury;
+            treasury.total_collected = treasury
+                .total_collected
+                .checked_add(fee)
+                .ok_or(GovernanceError::ArithmeticOverflow)?;
+        }
         
         // Update statistics
         poa_config.total_ercs_issued = poa_config.total_ercs_issued.saturating_add(1);
         poa_config.last_updated = clock.unix_timestamp;
         
+        let source_index = &mut ctx.accounts.source_index;
+        if source_index.certificate_count == 0 {
+            source_index.key = ErcIndexKey::Source(renewable_source.clone());
+            source_index.bump = ctx.bumps.source_index;
+        }
+        source_index.record(&certificate_id, energy_amount, clock.unix_timestamp)?;
+        
+        let month_index = &mut ctx.accounts.month_index;
+        if month_index.certificate_count == 0 {
+            month_index.key = ErcIndexKey::Month(period);
+            month_index.bump = ctx.bumps.month_index;
+        }
+        month_index.record(&certificate_id, energy_amount, clock.unix_timestamp)?;
+        
+        emit_status_change(erc_certificate, None, "issued", clock.unix_timestamp);
         emit!(ErcIssued {
             certificate_id,
             authority: ctx.accounts.authority.key(),
             energy_amount,
             renewable_source,
+            fee,
+            attested_by,
             timestamp: clock.unix_timestamp,
         });
         
         msg!("ERC issued by Engineering Department: {} kWh from {} (ID: {})", 
-             energy_amount, erc_certificate.renewable_source, erc_certificate.certificate_id);
+             energy_amount, erc_certificate.renewable_source.name(), erc_certificate.certificate_id);
         Ok(())
     }
 
     /// Validate ERC for trading - Engineering Department only
+    ///
+    /// Mints the certificate to `recipient` as a non-fungible Token-2022 token whose metadata
+    /// pointer references the certificate PDA. The PoA config stays the mint's permanent
+    /// delegate so the token can be burned on revocation.
     pub fn validate_erc_for_trading(ctx: Context<ValidateErc>) -> Result<()> {
-        let poa_config = &mut ctx.accounts.poa_config;
+        let accounts = ctx.accounts;
+        validate_certificate(
+            &mut accounts.poa_config,
+            ctx.bumps.poa_config,
+            &mut accounts.erc_certificate,
+            &accounts.nft_mint,
+            &accounts.recipient,
+            &accounts.recipient_token_account,
+            &accounts.token_program,
+            accounts.authority.key(),
+            None,
+        )
+    }
+
+    /// Validate ERC for trading on behalf of a bonded delegate - CPI from the staking program only
+    ///
+    /// Requires delegation to be enabled. The staking program checks the delegate's bond and
+    /// signs with the delegate's `VALIDATOR_AUTHORITY_SEED` PDA; the certificate records the
+    /// delegate in `validated_by`, so the bond can be slashed if the certificate is revoked.
+    pub fn validate_erc_as_delegate(ctx: Context<DelegatedValidateErc>) -> Result<()> {
+        let accounts = ctx.accounts;
+        require!(accounts.poa_config.delegation_enabled, GovernanceError::DelegationDisabled);
+        
+        let delegate = accounts.delegate.key();
+        let (expected, _) = Pubkey::find_program_address(
+            &[VALIDATOR_AUTHORITY_SEED, delegate.as_ref()],
+            &STAKING_PROGRAM_ID,
+        );
+        require_keys_eq!(accounts.validator_authority.key(), expected, GovernanceError::UnauthorizedValidator);
+        
+        validate_certificate(
+            &mut accounts.poa_config,
+            ctx.bumps.poa_config,
+            &mut accounts.erc_certificate,
+            &accounts.nft_mint,
+            &accounts.recipient,
+            &accounts.recipient_token_account,
+            &accounts.token_program,
+            delegate,
+            Some(delegate),
+        )
+    }
+
+    /// Retire a trading-validated ERC - certificate token holder only
+    ///
+    /// Burns the holder's certificate token, permanently claiming the renewable energy attribute.
+    pub fn retire_erc(ctx: Context<RetireErc>) -> Result<()> {
+        let erc_certificate = &mut ctx.accounts.erc_certificate;
+        let clock = Clock::get()?;
+        
+        require!(!ctx.accounts.poa_config.maintenance_mode, GovernanceError::MaintenanceMode);
+        require!(erc_certificate.status == ErcStatus::Valid, GovernanceError::InvalidErcStatus);
+        require!(erc_certificate.validated_for_trading, GovernanceError::NotValidatedForTrading);
+        require!(!erc_certificate.is_expired(clock.unix_timestamp), GovernanceError::ErcExpired);
+        require!(erc_certificate.trade_lock.is_none(), GovernanceError::ErcLocked);
+        
+        token_2022::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token_2022::Burn {
+                    mint: ctx.accounts.nft_mint.to_account_info(),
+                    from: ctx.accounts.holder_token_account.to_account_info(),
+                    authority: ctx.accounts.holder.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+        
+        erc_certificate.status = ErcStatus::Retired;
+        
+        emit_status_change(erc_certificate, Some(ErcStatus::Valid), "retired", clock.unix_timestamp);
+        emit!(ErcRetired {
+            certificate_id: erc_certificate.certificate_id.clone(),
+            holder: ctx.accounts.holder.key(),
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("ERC retired by holder {} (ID: {})", ctx.accounts.holder.key(), erc_certificate.certificate_id);
+        Ok(())
+    }
+
+    /// Record that a valid certificate has passed its expiry - permissionless
+    pub fn expire_erc(ctx: Context<ExpireErc>) -> Result<()> {
+        let erc_certificate = &mut ctx.accounts.erc_certificate;
+        let clock = Clock::get()?;
+        
+        require!(erc_certificate.status == ErcStatus::Valid, GovernanceError::InvalidErcStatus);
+        require!(erc_certificate.is_expired(clock.unix_timestamp), GovernanceError::ErcNotExpired);
+        
+        erc_certificate.status = ErcStatus::Expired;
+        
+        emit_status_change(erc_certificate, Some(ErcStatus::Valid), "expired", clock.unix_timestamp);
+        
+        msg!("ERC expired (ID: {})", erc_certificate.certificate_id);
+        Ok(())
+    }
+
+    /// Reserve a certificate for a trade - CPI from the configured trading program only
+    ///
+    /// The trading program signs with its `ERC_LOCK_AUTHORITY_SEED` PDA. A locked certificate
+    /// cannot be locked for another trade or retired until `release_erc`.
+    pub fn lock_erc_for_trade(ctx: Context<TradeErcLock>, trade: Pubkey) -> Result<()> {
+        let trading_program = verify_trading_authority(&ctx.accounts.poa_config, &ctx.accounts.trading_authority)?;
+        let erc_certificate = &mut ctx.accounts.erc_certificate;
+        let clock = Clock::get()?;
+        
+        require!(!ctx.accounts.poa_config.maintenance_mode, GovernanceError::MaintenanceMode);
+        require!(erc_certificate.status == ErcStatus::Valid, GovernanceError::InvalidErcStatus);
+        require!(erc_certificate.validated_for_trading, GovernanceError::NotValidatedForTrading);
+        require!(!erc_certificate.is_expired(clock.unix_timestamp), GovernanceError::ErcExpired);
+        require!(erc_certificate.trade_lock.is_none(), GovernanceError::ErcLocked);
+        
+        erc_certificate.trade_lock = Some(ErcTradeLock {
+            trade,
+            locked_at: clock.unix_timestamp,
+        });
+        
+        emit!(ErcLockedForTrade {
+            certificate_id: erc_certificate.certificate_id.clone(),
+            trade,
+            trading_program,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("ERC {} locked for trade {}", erc_certificate.certificate_id, trade);
+        Ok(())
+    }
+
+    /// Release a certificate locked for `trade` - CPI from the configured trading program only
+    pub fn release_erc(ctx: Context<TradeErcLock>, trade: Pubkey) -> Result<()> {
+        let trading_program = verify_trading_authority(&ctx.accounts.poa_config, &ctx.accounts.trading_authority)?;
+        let erc_certificate = &mut ctx.accounts.erc_certificate;
+        let clock = Clock::get()?;
+        
+        let lock = erc_certificate.trade_lock.as_ref().ok_or(GovernanceError::ErcNotLocked)?;
+        require_keys_eq!(lock.trade, trade, GovernanceError::TradeMismatch);
+        erc_certificate.trade_lock = None;
+        
+        emit!(ErcReleased {
+            certificate_id: erc_certificate.certificate_id.clone(),
+            trade,
+            trading_program,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("ERC {} released from trade {}", erc_certificate.certificate_id, trade);
+        Ok(())
+    }
+
+    /// Push a valid certificate's expiry forward - Engineering Department only
+    ///
+    /// For certificates awaiting slow external audits. Must be called before expiry, each
+    /// extension is at most one validity period and a certificate can be extended at most
+    /// `ErcCertificate::MAX_EXTENSIONS` times. Certificates in the pre-migration layout have
+    /// to go through `migrate_erc_certificate` first.
+    pub fn extend_erc_validity(ctx: Context<ExtendErcValidity>, extension: i64) -> Result<()> {
+        let poa_config = &ctx.accounts.poa_config;
         let erc_certificate = &mut ctx.accounts.erc_certificate;
         let clock = Clock::get()?;
         
-        require!(!poa_config.emergency_paused, GovernanceError::SystemPaused);
+        require!(!poa_config.is_paused(PoAConfig::PAUSE_ISSUANCE, clock.unix_timestamp), GovernanceError::IssuancePaused);
         require!(!poa_config.maintenance_mode, GovernanceError::MaintenanceMode);
         require!(erc_certificate.status == ErcStatus::Valid, GovernanceError::InvalidErcStatus);
-        require!(!erc_certificate.validated_for_trading, GovernanceError::AlreadyValidated);
+        require!(!erc_certificate.is_expired(clock.unix_timestamp), GovernanceError::ErcExpired);
+        require!(
+            extension > 0 && extension <= poa_config.erc_validity_period,
+            GovernanceError::InvalidExtension
+        );
+        require!(
+            erc_certificate.extensions.len() < ErcCertificate::MAX_EXTENSIONS,
+            GovernanceError::TooManyExtensions
+        );
         
-        // Check expiration
-        if let Some(expires_at) = erc_certificate.expires_at {
-            require!(clock.unix_timestamp < expires_at, GovernanceError::ErcExpired);
-        }
+        let previous_expires_at = erc_certificate.expires_at.ok_or(GovernanceError::InvalidExtension)?;
+        let new_expires_at = previous_expires_at
+            .checked_add(extension)
+            .ok_or(GovernanceError::ArithmeticOverflow)?;
+        erc_certificate.expires_at = Some(new_expires_at);
+        erc_certificate.extensions.push(ErcExtension {
+            previous_expires_at,
+            new_expires_at,
+            extended_at: clock.unix_timestamp,
+        });
+        
+        emit!(ErcValidityExtended {
+            certificate_id: erc_certificate.certificate_id.clone(),
+            authority: ctx.accounts.authority.key(),
+            previous_expires_at,
+            new_expires_at,
+            extension_count: erc_certificate.extensions.len() as u8,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("ERC validity extended to {} (ID: {})", new_expires_at, erc_certificate.certificate_id);
+        Ok(())
+    }
+
+    /// Revoke an ERC - Engineering Department only
+    ///
+    /// Once the certificate has been minted, its token is burned from the holder through the
+    /// mint's permanent delegate, so the holder token account and token program must be passed.
+    pub fn revoke_erc(ctx: Context<RevokeErc>, reason: String) -> Result<()> {
+        require!(!ctx.accounts.poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
         
-        erc_certificate.validated_for_trading = true;
-        erc_certificate.trading_validated_at = Some(clock.unix_timestamp);
+        let token_accounts = CertificateTokenAccounts {
+            nft_mint: ctx.accounts.nft_mint.to_account_info(),
+            holder_token_account: ctx.accounts.holder_token_account.as_ref().map(|a| a.to_account_info()),
+            token_program: ctx.accounts.token_program.as_ref().map(|p| p.to_account_info()),
+            poa_config: ctx.accounts.poa_config.to_account_info(),
+            poa_config_bump: ctx.bumps.poa_config,
+        };
         
-        // Update statistics
-        poa_config.total_ercs_validated = poa_config.total_ercs_validated.saturating_add(1);
-        poa_config.last_updated = clock.unix_timestamp;
+        revoke_certificate(
+            &mut ctx.accounts.erc_certificate,
+            &token_accounts,
+            ctx.accounts.authority.key(),
+            reason,
+            &Clock::get()?,
+        )
+    }
+
+    /// Put a certificate under dispute - Engineering Department only
+    ///
+    /// A challenged certificate cannot be validated, locked for a trade, retired or extended
+    /// until `resolve_challenge`. `dispute_id` links the challenge to the off-chain dispute.
+    /// Certificates in the pre-migration layout have to go through `migrate_erc_certificate`
+    /// first.
+    pub fn challenge_erc(ctx: Context<ChallengeErc>, dispute_id: [u8; 16], reason: String) -> Result<()> {
+        let erc_certificate = &mut ctx.accounts.erc_certificate;
+        let clock = Clock::get()?;
         
-        emit!(ErcValidatedForTrading {
+        require!(reason.len() <= CouncilAction::MAX_REASON_LEN, GovernanceError::ReasonTooLong);
+        require!(
+            matches!(
+                erc_certificate.status,
+                ErcStatus::Valid | ErcStatus::Pending | ErcStatus::Expired
+            ),
+            GovernanceError::InvalidErcStatus
+        );
+        require!(erc_certificate.trade_lock.is_none(), GovernanceError::ErcLocked);
+        
+        let previous_status = erc_certificate.status.clone();
+        erc_certificate.challenge = Some(ErcChallenge {
+            dispute_id,
+            previous_status: previous_status.clone(),
+            challenged_at: clock.unix_timestamp,
+        });
+        erc_certificate.status = ErcStatus::Challenged;
+        
+        emit_status_change(erc_certificate, Some(previous_status), "challenged", clock.unix_timestamp);
+        emit!(ErcChallenged {
             certificate_id: erc_certificate.certificate_id.clone(),
+            dispute_id,
             authority: ctx.accounts.authority.key(),
+            reason,
             timestamp: clock.unix_timestamp,
         });
         
-        msg!("ERC validated for trading by Engineering Department (ID: {})", erc_certificate.certificate_id);
+        msg!("ERC challenged (ID: {})", erc_certificate.certificate_id);
         Ok(())
     }
 
+    /// Resolve the dispute over a challenged certificate - Engineering Department only
+    ///
+    /// A dismissed challenge restores the status the certificate had before it. An upheld one
+    /// revokes the certificate, burning its token like `revoke_erc`.
+    pub fn resolve_challenge(
+        ctx: Context<RevokeErc>,
+        dispute_id: [u8; 16],
+        upheld: bool,
+        resolution: String,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(resolution.len() <= CouncilAction::MAX_REASON_LEN, GovernanceError::ReasonTooLong);
+        require!(
+            !upheld || !ctx.accounts.poa_config.council_mode,
+            GovernanceError::CouncilApprovalRequired
+        );
+        
+        let erc_certificate = &mut ctx.accounts.erc_certificate;
+        require!(erc_certificate.status == ErcStatus::Challenged, GovernanceError::ErcNotChallenged);
+        let challenge = erc_certificate.challenge.take().ok_or(GovernanceError::ErcNotChallenged)?;
+        require!(challenge.dispute_id == dispute_id, GovernanceError::DisputeMismatch);
+        erc_certificate.status = challenge.previous_status;
+        
+        emit!(ErcChallengeResolved {
+            certificate_id: erc_certificate.certificate_id.clone(),
+            dispute_id,
+            authority: ctx.accounts.authority.key(),
+            upheld,
+            resolution: resolution.clone(),
+            timestamp: clock.unix_timestamp,
+        });
+        
+        if !upheld {
+            emit_status_change(erc_certificate, Some(ErcStatus::Challenged), "challenge_dismissed", clock.unix_timestamp);
+            msg!("ERC challenge dismissed (ID: {})", erc_certificate.certificate_id);
+            return Ok(());
+        }
+        
+        let token_accounts = CertificateTokenAccounts {
+            nft_mint: ctx.accounts.nft_mint.to_account_info(),
+            holder_token_account: ctx.accounts.holder_token_account.as_ref().map(|a| a.to_account_info()),
+            token_program: ctx.accounts.token_program.as_ref().map(|p| p.to_account_info()),
+            poa_config: ctx.accounts.poa_config.to_account_info(),
+            poa_config_bump: ctx.bumps.poa_config,
+        };
+        
+        revoke_certificate(
+            &mut ctx.accounts.erc_certificate,
+            &token_accounts,
+            ctx.accounts.authority.key(),
+            resolution,
+            &clock,
+        )
+    }
+
     /// Update governance configuration - Engineering Department only
     pub fn update_governance_config(
         ctx: Context<UpdateGovernanceConfig>,
         erc_validation_enabled: bool,
     ) -> Result<()> {
         let poa_config = &mut ctx.accounts.poa_config;
-        let clock = Clock::get()?;
         
-        let old_enabled = poa_config.erc_validation_enabled;
-        poa_config.erc_validation_enabled = erc_validation_enabled;
-        poa_config.last_updated = clock.unix_timestamp;
+        require!(!poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
         
-        emit!(GovernanceConfigUpdated {
-            authority: ctx.accounts.authority.key(),
+        apply_erc_validation(
+            poa_config,
             erc_validation_enabled,
-            old_enabled,
-            timestamp: clock.unix_timestamp,
-        });
-        
-        msg!("Governance configuration updated - ERC validation: {}", erc_validation_enabled);
-        Ok(())
+            ctx.accounts.authority.key(),
+            &Clock::get()?,
+        )
     }
 
     /// Set maintenance mode - Engineering Department only
@@ -186,19 +682,15 @@ pub mod governance {
         maintenance_enabled: bool,
     ) -> Result<()> {
         let poa_config = &mut ctx.accounts.poa_config;
-        let clock = Clock::get()?;
         
-        poa_config.maintenance_mode = maintenance_enabled;
-        poa_config.last_updated = clock.unix_timestamp;
+        require!(!poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
         
-        emit!(MaintenanceModeUpdated {
-            authority: ctx.accounts.authority.key(),
+        apply_maintenance_mode(
+            poa_config,
             maintenance_enabled,
-            timestamp: clock.unix_timestamp,
-        });
-        
-        msg!("Maintenance mode {}", if maintenance_enabled { "enabled" } else { "disabled" });
-        Ok(())
+            ctx.accounts.authority.key(),
+            &Clock::get()?,
+        )
     }
 
     /// Update ERC limits - Engineering Department only
@@ -209,98 +701,1348 @@ pub mod governance {
         erc_validity_period: i64,
     ) -> Result<()> {
         let poa_config = &mut ctx.accounts.poa_config;
-        let clock = Clock::get()?;
         
-        require!(min_energy_amount > 0, GovernanceError::InvalidMinimumEnergy);
-        require!(max_erc_amount > min_energy_amount, GovernanceError::InvalidMaximumEnergy);
-        require!(erc_validity_period > 0, GovernanceError::InvalidValidityPeriod);
+        require!(!poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
+        
+        apply_erc_limits(
+            poa_config,
+            min_energy_amount,
+            max_erc_amount,
+            erc_validity_period,
+            ctx.accounts.authority.key(),
+            &Clock::get()?,
+        )
+    }
+
+    /// Update the authority profile shown to certificate holders - Engineering Department only
+    pub fn update_authority_profile(
+        ctx: Context<UpdateAuthorityProfile>,
+        profile: AuthorityProfile,
+    ) -> Result<()> {
+        let poa_config = &mut ctx.accounts.poa_config;
+        let clock = Clock::get()?;
         
-        let old_min = poa_config.min_energy_amount;
-        let old_max = poa_config.max_erc_amount;
-        let old_validity = poa_config.erc_validity_period;
+        require!(!poa_config.is_paused(PoAConfig::PAUSE_CONFIG, clock.unix_timestamp), GovernanceError::ConfigUpdatesPaused);
+        profile.validate()?;
         
-        poa_config.min_energy_amount = min_energy_amount;
-        poa_config.max_erc_amount = max_erc_amount;
-        poa_config.erc_validity_period = erc_validity_period;
+        let old_profile = poa_config.profile();
+        poa_config.set_profile(profile.clone());
         poa_config.last_updated = clock.unix_timestamp;
         
-        emit!(ErcLimitsUpdated {
+        emit!(AuthorityProfileUpdated {
             authority: ctx.accounts.authority.key(),
-            old_min,
-            new_min: min_energy_amount,
-            old_max,
-            new_max: max_erc_amount,
-            old_validity,
-            new_validity: erc_validity_period,
+            old_profile,
+            new_profile: profile,
             timestamp: clock.unix_timestamp,
         });
         
-        msg!("ERC limits updated - Min: {} kWh, Max: {} kWh, Validity: {} seconds", 
-             min_energy_amount, max_erc_amount, erc_validity_period);
+        msg!("Authority profile updated");
+        Ok(())
+    }
+
+    /// Set the oracle authority whose meter readings back ERC issuance - Engineering Department only
+    ///
+    /// Passing `None` disables oracle validation of ERC issuance.
+    pub fn update_oracle_authority(
+        ctx: Context<UpdateGovernanceConfig>,
+        oracle_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        let poa_config = &mut ctx.accounts.poa_config;
+        
+        require!(!poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
+        
+        apply_oracle_authority(
+            poa_config,
+            oracle_authority,
+            ctx.accounts.authority.key(),
+            &Clock::get()?,
+        )
+    }
+
+    /// Create the treasury PDA that collects ERC issuance fees - Engineering Department only
+    pub fn initialize_treasury(ctx: Context<InitializeTreasury>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let clock = Clock::get()?;
+        
+        treasury.total_collected = 0;
+        treasury.total_withdrawn = 0;
+        treasury.created_at = clock.unix_timestamp;
+        treasury.bump = ctx.bumps.treasury;
+        
+        msg!("Governance treasury initialized");
         Ok(())
     }
 
-    /// Update authority contact info - Engineering Department only
-    pub fn update_authority_info(
+    /// Set the lamport fee charged on ERC issuance - Engineering Department only
+    pub fn update_erc_issuance_fee(
         ctx: Context<UpdateGovernanceConfig>,
-        contact_info: String,
+        erc_issuance_fee: u64,
     ) -> Result<()> {
         let poa_config = &mut ctx.accounts.poa_config;
+        
+        require!(!poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
+        
+        apply_erc_issuance_fee(
+            poa_config,
+            erc_issuance_fee,
+            ctx.accounts.authority.key(),
+            &Clock::get()?,
+        )
+    }
+
+    /// Withdraw collected fees from the treasury - Engineering Department only
+    ///
+    /// The treasury always keeps enough lamports to stay rent exempt.
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
+        
+        withdraw_from_treasury(
+            &mut ctx.accounts.treasury,
+            &ctx.accounts.recipient.to_account_info(),
+            amount,
+            ctx.accounts.authority.key(),
+            &Clock::get()?,
+        )
+    }
+
+    /// Migrate the PoA config account to the current layout - Engineering Department only
+    ///
+    /// Grows the account to the current size, fills fields introduced since its version with
+    /// defaults and bumps `version`. Accounts already at the current version are rejected.
+    pub fn migrate_poa_config(ctx: Context<MigratePoaConfig>) -> Result<()> {
+        let config_info = ctx.accounts.poa_config.to_account_info();
         let clock = Clock::get()?;
         
-        require!(contact_info.len() <= 128, GovernanceError::ContactInfoTooLong);
+        // The stored layout may predate the current struct, so check the header by hand
+        // before touching the account
+        {
+            let data = config_info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 + 32 && data[..8] == *PoAConfig::DISCRIMINATOR,
+                GovernanceError::InvalidPoAConfig
+            );
+            require!(
+                data[8..40] == ctx.accounts.authority.key().to_bytes(),
+                GovernanceError::UnauthorizedAuthority
+            );
+        }
+        
+        // New fields are appended, so zeroed bytes deserialize as empty values
+        oracle::grow_account(
+            &config_info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            8 + PoAConfig::INIT_SPACE,
+        )?;
+        
+        let mut poa_config = PoAConfig::try_deserialize(&mut &config_info.try_borrow_data()?[..])?;
+        require!(
+            poa_config.version < PoAConfig::CURRENT_VERSION,
+            GovernanceError::AlreadyMigrated
+        );
         
-        let old_contact = poa_config.contact_info.clone();
-        poa_config.contact_info = contact_info.clone();
+        let old_version = poa_config.version;
+        if old_version < 2 {
+            // Version 1 stored a single emergency pause bool where `pause_flags` now lives
+            if poa_config.pause_flags != 0 {
+                poa_config.pause_flags = PoAConfig::EMERGENCY_PAUSE_FLAGS;
+            }
+        }
+        if old_version < 3 {
+            poa_config.erc_issuance_fee = 0;
+        }
+        if old_version < 4 {
+            poa_config.council_mode = false;
+        }
+        if old_version < 5 {
+            poa_config.max_pause_duration = PoAConfig::DEFAULT_MAX_PAUSE_DURATION;
+        }
+        if old_version < 6 {
+            poa_config.trading_program = None;
+        }
+        if old_version < 7 {
+            poa_config.reset_issuance_rate_limit(clock.unix_timestamp);
+        }
+        if old_version < 8 {
+            // `contact_email` keeps the old `contact_info` slot, which always held an email
+            poa_config.contact_phone = String::new();
+            poa_config.website = String::new();
+            poa_config.office_location = String::new();
+        }
+        poa_config.version = PoAConfig::CURRENT_VERSION;
         poa_config.last_updated = clock.unix_timestamp;
         
-        emit!(AuthorityInfoUpdated {
+        poa_config.try_serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+        
+        emit!(PoAConfigMigrated {
             authority: ctx.accounts.authority.key(),
-            old_contact,
-            new_contact: contact_info,
+            old_version,
+            new_version: PoAConfig::CURRENT_VERSION,
             timestamp: clock.unix_timestamp,
         });
         
-        msg!("Authority contact information updated");
+        msg!("PoA config migrated from version {} to {}", old_version, PoAConfig::CURRENT_VERSION);
         Ok(())
     }
 
-    /// Get governance statistics
-    pub fn get_governance_stats(ctx: Context<GetGovernanceStats>) -> Result<GovernanceStats> {
-        let poa_config = &ctx.accounts.poa_config;
+    /// Migrate a certificate issued before the current layout - Engineering Department only
+    ///
+    /// Certificates stored with a free-text renewable source after `certificate_id` are grown
+    /// to the current size and rewritten with the source first, parsed from its name, and with
+    /// no provenance, trade lock, extensions, attestation, challenge or delegate validator.
+    /// Certificates already in the current layout are rejected.
+    pub fn migrate_erc_certificate(ctx: Context<MigrateErcCertificate>, certificate_id: String) -> Result<()> {
+        let certificate_info = ctx.accounts.erc_certificate.to_account_info();
+        let clock = Clock::get()?;
         
-        Ok(GovernanceStats {
-            total_ercs_issued: poa_config.total_ercs_issued,
-            total_ercs_validated: poa_config.total_ercs_validated,
-            erc_validation_enabled: poa_config.erc_validation_enabled,
-            emergency_paused: poa_config.emergency_paused,
-            maintenance_mode: poa_config.maintenance_mode,
-            min_energy_amount: poa_config.min_energy_amount,
-            max_erc_amount: poa_config.max_erc_amount,
-            erc_validity_period: poa_config.erc_validity_period,
-            created_at: poa_config.created_at,
-            last_updated: poa_config.last_updated,
-        })
+        let new_size = 8 + ErcCertificate::INIT_SPACE;
+        require!(certificate_info.data_len() < new_size, GovernanceError::CertificateAlreadyMigrated);
+        let legacy = {
+            let data = certificate_info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == *ErcCertificate::DISCRIMINATOR,
+                GovernanceError::InvalidErcCertificate
+            );
+            LegacyErcCertificate::deserialize(&mut &data[8..])
+                .map_err(|_| error!(GovernanceError::InvalidErcCertificate))?
+        };
+        require!(legacy.certificate_id == certificate_id, GovernanceError::InvalidErcCertificate);
+        let renewable_source = RenewableSource::from_name(&legacy.renewable_source);
+        renewable_source.validate()?;
+        
+        oracle::grow_account(
+            &certificate_info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_size,
+        )?;
+        
+        let erc_certificate = ErcCertificate {
+            renewable_source,
+            certificate_id: legacy.certificate_id,
+            authority: legacy.authority,
+            energy_amount: legacy.energy_amount,
+            validation_data: legacy.validation_data,
+            issued_at: legacy.issued_at,
+            expires_at: legacy.expires_at,
+            status: legacy.status,
+            validated_for_trading: legacy.validated_for_trading,
+            trading_validated_at: legacy.trading_validated_at,
+            source_readings: Vec::new(),
+            trade_lock: None,
+            extensions: Vec::new(),
+            attested_by: None,
+            challenge: None,
+            validated_by: None,
+        };
+        erc_certificate.try_serialize(&mut &mut certificate_info.try_borrow_mut_data()?[..])?;
+        
+        emit!(ErcCertificateMigrated {
+            certificate_id: erc_certificate.certificate_id.clone(),
+            renewable_source: erc_certificate.renewable_source.clone(),
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("ERC certificate {} migrated to the current layout", erc_certificate.certificate_id);
+        Ok(())
     }
-}
 
-// Account structures for single authority PoA
-#[derive(Accounts)]
-pub struct InitializePoa<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + PoAConfig::LEN,
-        seeds = [b"poa_config"],
-        bump
-    )]
-    pub poa_config: Account<'info, PoAConfig>,
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+    /// Hand sensitive operations to an M-of-N council - Engineering Department only
+    ///
+    /// Once enabled, configuration and fee updates, unpausing, pause flag changes, revocations
+    /// and treasury withdrawals only go through `propose_action`/`approve_action`/`execute_action`.
+    pub fn initialize_council(
+        ctx: Context<InitializeCouncil>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let poa_config = &mut ctx.accounts.poa_config;
+        let council = &mut ctx.accounts.council;
+        let clock = Clock::get()?;
+        
+        Council::validate_members(&members, threshold)?;
+        
+        council.members = members.clone();
+        council.threshold = threshold;
+        council.proposal_count = 0;
+        council.created_at = clock.unix_timestamp;
+        council.bump = ctx.bumps.council;
+        
+        poa_config.council_mode = true;
+        poa_config.last_updated = clock.unix_timestamp;
+        
+        emit!(CouncilUpdated {
+            actor: ctx.accounts.authority.key(),
+            members,
+            threshold,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Council mode enabled with a {} of {} threshold", threshold, council.members.len());
+        Ok(())
+    }
 
-#[derive(Accounts)]
+    /// Propose a sensitive operation - council members only
+    ///
+    /// The proposer's approval is recorded with the proposal.
+    pub fn propose_action(ctx: Context<ProposeAction>, action: CouncilAction) -> Result<()> {
+        let council = &mut ctx.accounts.council;
+        let proposal = &mut ctx.accounts.proposal;
+        let proposer = ctx.accounts.proposer.key();
+        let clock = Clock::get()?;
+        
+        require!(council.is_member(&proposer), GovernanceError::NotCouncilMember);
+        action.validate()?;
+        
+        proposal.id = council.proposal_count;
+        proposal.proposer = proposer;
+        proposal.action = action;
+        proposal.approvals = vec![proposer];
+        proposal.executed = false;
+        proposal.created_at = clock.unix_timestamp;
+        proposal.expires_at = clock.unix_timestamp + CouncilProposal::LIFETIME;
+        proposal.bump = ctx.bumps.proposal;
+        
+        council.proposal_count = council
+            .proposal_count
+            .checked_add(1)
+            .ok_or(GovernanceError::ArithmeticOverflow)?;
+        
+        emit!(CouncilActionProposed {
+            proposal_id: proposal.id,
+            proposer,
+            expires_at: proposal.expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Council proposal {} created by {}", proposal.id, proposer);
+        Ok(())
+    }
+
+    /// Approve a pending proposal - council members only
+    pub fn approve_action(ctx: Context<ApproveAction>) -> Result<()> {
+        let council = &ctx.accounts.council;
+        let proposal = &mut ctx.accounts.proposal;
+        let member = ctx.accounts.member.key();
+        let clock = Clock::get()?;
+        
+        require!(council.is_member(&member), GovernanceError::NotCouncilMember);
+        proposal.require_pending(clock.unix_timestamp)?;
+        require!(!proposal.approvals.contains(&member), GovernanceError::AlreadyApproved);
+        
+        proposal.approvals.push(member);
+        
+        emit!(CouncilActionApproved {
+            proposal_id: proposal.id,
+            member,
+            approvals: council.count_approvals(&proposal.approvals),
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Council proposal {} approved by {}", proposal.id, member);
+        Ok(())
+    }
+
+    /// Execute a proposal that reached the council threshold - council members only
+    ///
+    /// Only approvals from current members count. Revocations need the certificate and, once
+    /// minted, the accounts required to burn its token; treasury withdrawals need the treasury
+    /// and the proposal's recipient.
+    pub fn execute_action(ctx: Context<ExecuteAction>) -> Result<()> {
+        let member = ctx.accounts.member.key();
+        let clock = Clock::get()?;
+        
+        let council = &ctx.accounts.council;
+        let proposal = &ctx.accounts.proposal;
+        require!(council.is_member(&member), GovernanceError::NotCouncilMember);
+        proposal.require_pending(clock.unix_timestamp)?;
+        require!(
+            council.count_approvals(&proposal.approvals) >= council.threshold,
+            GovernanceError::InsufficientApprovals
+        );
+        
+        match proposal.action.clone() {
+            CouncilAction::SetPauseFlags { pause_flags } => {
+                apply_pause_flags(&mut ctx.accounts.poa_config, pause_flags, member, &clock)?;
+            }
+            CouncilAction::UpdateErcLimits {
+                min_energy_amount,
+                max_erc_amount,
+                erc_validity_period,
+            } => {
+                apply_erc_limits(
+                    &mut ctx.accounts.poa_config,
+                    min_energy_amount,
+                    max_erc_amount,
+                    erc_validity_period,
+                    member,
+                    &clock,
+                )?;
+            }
+            CouncilAction::RevokeErc { certificate_id, reason } => {
+                let erc_certificate = ctx
+                    .accounts
+                    .erc_certificate
+                    .as_mut()
+                    .ok_or(GovernanceError::CertificateRequired)?;
+                require!(erc_certificate.certificate_id == certificate_id, GovernanceError::CertificateRequired);
+                
+                let nft_mint = ctx
+                    .accounts
+                    .nft_mint
+                    .as_ref()
+                    .ok_or(GovernanceError::CertificateRequired)?;
+                let (expected_mint, _) = Pubkey::find_program_address(
+                    &[b"erc_mint", erc_certificate.key().as_ref()],
+                    &crate::ID,
+                );
+                require_keys_eq!(nft_mint.key(), expected_mint, GovernanceError::InvalidCertificateMint);
+                
+                let token_accounts = CertificateTokenAccounts {
+                    nft_mint: nft_mint.to_account_info(),
+                    holder_token_account: ctx.accounts.holder_token_account.as_ref().map(|a| a.to_account_info()),
+                    token_program: ctx.accounts.token_program.as_ref().map(|p| p.to_account_info()),
+                    poa_config: ctx.accounts.poa_config.to_account_info(),
+                    poa_config_bump: ctx.bumps.poa_config,
+                };
+                revoke_certificate(erc_certificate, &token_accounts, member, reason, &clock)?;
+            }
+            CouncilAction::UpdateCouncil { members, threshold } => {
+                let council = &mut ctx.accounts.council;
+                council.members = members.clone();
+                council.threshold = threshold;
+                
+                emit!(CouncilUpdated {
+                    actor: member,
+                    members,
+                    threshold,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            CouncilAction::SetMaxPauseDuration { max_pause_duration } => {
+                apply_max_pause_duration(&mut ctx.accounts.poa_config, max_pause_duration, member, &clock)?;
+            }
+            CouncilAction::SetTradingProgram { trading_program } => {
+                apply_trading_program(&mut ctx.accounts.poa_config, trading_program, member, &clock)?;
+            }
+            CouncilAction::UpdateIssuanceRateLimit {
+                issuance_window,
+                max_window_ercs,
+                max_window_energy,
+            } => {
+                apply_issuance_rate_limit(
+                    &mut ctx.accounts.poa_config,
+                    issuance_window,
+                    max_window_ercs,
+                    max_window_energy,
+                    member,
+                    &clock,
+                )?;
+            }
+            CouncilAction::SetErcValidation { erc_validation_enabled } => {
+                apply_erc_validation(&mut ctx.accounts.poa_config, erc_validation_enabled, member, &clock)?;
+            }
+            CouncilAction::SetMaintenanceMode { maintenance_enabled } => {
+                apply_maintenance_mode(&mut ctx.accounts.poa_config, maintenance_enabled, member, &clock)?;
+            }
+            CouncilAction::SetOracleAuthority { oracle_authority } => {
+                apply_oracle_authority(&mut ctx.accounts.poa_config, oracle_authority, member, &clock)?;
+            }
+            CouncilAction::SetErcIssuanceFee { erc_issuance_fee } => {
+                apply_erc_issuance_fee(&mut ctx.accounts.poa_config, erc_issuance_fee, member, &clock)?;
+            }
+            CouncilAction::WithdrawTreasury { recipient, amount } => {
+                let recipient_account = ctx
+                    .accounts
+                    .recipient
+                    .as_ref()
+                    .ok_or(GovernanceError::TreasuryAccountsRequired)?;
+                require_keys_eq!(recipient_account.key(), recipient, GovernanceError::TreasuryAccountsRequired);
+                let recipient_info = recipient_account.to_account_info();
+                let treasury = ctx
+                    .accounts
+                    .treasury
+                    .as_mut()
+                    .ok_or(GovernanceError::TreasuryAccountsRequired)?;
+                withdraw_from_treasury(treasury, &recipient_info, amount, member, &clock)?;
+            }
+        }
+        
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.executed = true;
+        
+        emit!(CouncilActionExecuted {
+            proposal_id: proposal.id,
+            executor: member,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Council proposal {} executed by {}", proposal.id, member);
+        Ok(())
+    }
+
+    /// Create the registry tracking stakeholders and parameter proposals - Engineering Department only
+    pub fn initialize_stakeholder_registry(ctx: Context<InitializeStakeholderRegistry>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let clock = Clock::get()?;
+        
+        registry.stakeholder_count = 0;
+        registry.proposal_count = 0;
+        registry.created_at = clock.unix_timestamp;
+        registry.bump = ctx.bumps.registry;
+        
+        msg!("Stakeholder registry initialized");
+        Ok(())
+    }
+
+    /// Register a campus stakeholder key allowed to vote on proposals - Engineering Department only
+    pub fn register_stakeholder(
+        ctx: Context<RegisterStakeholder>,
+        stakeholder_key: Pubkey,
+        name: String,
+    ) -> Result<()> {
+        require!(name.len() <= Stakeholder::MAX_NAME_LEN, GovernanceError::StakeholderNameTooLong);
+        
+        let registry = &mut ctx.accounts.registry;
+        let record = &mut ctx.accounts.stakeholder;
+        let clock = Clock::get()?;
+        
+        record.key = stakeholder_key;
+        record.name = name.clone();
+        record.registered_at = clock.unix_timestamp;
+        record.bump = ctx.bumps.stakeholder;
+        
+        registry.stakeholder_count = registry
+            .stakeholder_count
+            .checked_add(1)
+            .ok_or(GovernanceError::ArithmeticOverflow)?;
+        
+        emit!(StakeholderRegistered {
+            stakeholder: stakeholder_key,
+            name,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Stakeholder {} registered", stakeholder_key);
+        Ok(())
+    }
+
+    /// Remove a stakeholder; votes already cast still count - Engineering Department only
+    pub fn remove_stakeholder(ctx: Context<RemoveStakeholder>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let stakeholder = ctx.accounts.stakeholder.key;
+        
+        registry.stakeholder_count = registry.stakeholder_count.saturating_sub(1);
+        
+        emit!(StakeholderRemoved {
+            stakeholder,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Stakeholder {} removed", stakeholder);
+        Ok(())
+    }
+
+    /// Put a parameter change to a stakeholder vote - Engineering Department only
+    ///
+    /// Voting stays open for `Proposal::VOTING_PERIOD`; the change is enacted afterwards
+    /// with `execute_proposal` if more stakeholders voted for it than against and turnout
+    /// reached `Proposal::QUORUM_BPS` of the stakeholders registered now.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        change: ParameterChange,
+        description: String,
+    ) -> Result<()> {
+        require!(description.len() <= Proposal::MAX_DESCRIPTION_LEN, GovernanceError::DescriptionTooLong);
+        change.validate()?;
+        
+        let registry = &mut ctx.accounts.registry;
+        let proposal = &mut ctx.accounts.proposal;
+        let clock = Clock::get()?;
+        
+        proposal.id = registry.proposal_count;
+        proposal.proposer = ctx.accounts.authority.key();
+        proposal.change = change;
+        proposal.description = description;
+        proposal.votes_for = 0;
+        proposal.votes_against = 0;
+        proposal.eligible_voters = registry.stakeholder_count;
+        proposal.created_at = clock.unix_timestamp;
+        proposal.voting_ends_at = clock.unix_timestamp + Proposal::VOTING_PERIOD;
+        proposal.executed = false;
+        proposal.bump = ctx.bumps.proposal;
+        
+        registry.proposal_count = registry
+            .proposal_count
+            .checked_add(1)
+            .ok_or(GovernanceError::ArithmeticOverflow)?;
+        
+        emit!(ProposalCreated {
+            proposal_id: proposal.id,
+            proposer: proposal.proposer,
+            voting_ends_at: proposal.voting_ends_at,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Proposal {} open for voting until {}", proposal.id, proposal.voting_ends_at);
+        Ok(())
+    }
+
+    /// Vote on an open proposal - registered stakeholders only, once per proposal
+    pub fn cast_vote(ctx: Context<CastVote>, approve: bool) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let vote = &mut ctx.accounts.vote;
+        let voter = ctx.accounts.voter.key();
+        let clock = Clock::get()?;
+        
+        require!(clock.unix_timestamp < proposal.voting_ends_at, GovernanceError::VotingClosed);
+        
+        if approve {
+            proposal.votes_for = proposal.votes_for.checked_add(1).ok_or(GovernanceError::ArithmeticOverflow)?;
+        } else {
+            proposal.votes_against = proposal
+                .votes_against
+                .checked_add(1)
+                .ok_or(GovernanceError::ArithmeticOverflow)?;
+        }
+        
+        vote.proposal = proposal.key();
+        vote.voter = voter;
+        vote.approve = approve;
+        vote.timestamp = clock.unix_timestamp;
+        vote.bump = ctx.bumps.vote;
+        
+        emit!(StakeholderVoteCast {
+            proposal_id: proposal.id,
+            voter,
+            approve,
+            votes_for: proposal.votes_for,
+            votes_against: proposal.votes_against,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Stakeholder {} voted {} proposal {}", voter, if approve { "for" } else { "against" }, proposal.id);
+        Ok(())
+    }
+
+    /// Enact a proposal once voting has closed with quorum and a majority in favour - Engineering Department only
+    ///
+    /// In council mode parameter changes go through the council instead.
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        let poa_config = &mut ctx.accounts.poa_config;
+        let proposal = &mut ctx.accounts.proposal;
+        let authority = ctx.accounts.authority.key();
+        let clock = Clock::get()?;
+        
+        require!(!poa_config.council_mode, GovernanceError::CouncilApprovalRequired);
+        require!(!proposal.executed, GovernanceError::ProposalAlreadyExecuted);
+        require!(clock.unix_timestamp >= proposal.voting_ends_at, GovernanceError::VotingStillOpen);
+        require!(proposal.has_quorum(), GovernanceError::QuorumNotReached);
+        require!(proposal.votes_for > proposal.votes_against, GovernanceError::ProposalRejected);
+        
+        match proposal.change.clone() {
+            ParameterChange::ErcLimits {
+                min_energy_amount,
+                max_erc_amount,
+                erc_validity_period,
+            } => {
+                apply_erc_limits(
+                    poa_config,
+                    min_energy_amount,
+                    max_erc_amount,
+                    erc_validity_period,
+                    authority,
+                    &clock,
+                )?;
+            }
+            ParameterChange::ErcIssuanceFee { erc_issuance_fee } => {
+                apply_erc_issuance_fee(poa_config, erc_issuance_fee, authority, &clock)?;
+            }
+        }
+        
+        proposal.executed = true;
+        
+        emit!(ProposalExecuted {
+            proposal_id: proposal.id,
+            executor: authority,
+            votes_for: proposal.votes_for,
+            votes_against: proposal.votes_against,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Proposal {} executed", proposal.id);
+        Ok(())
+    }
+
+    /// Get a certificate's effective status at the current time
+    ///
+    /// Expiry is only written to the account once someone calls `expire_erc`, so clients
+    /// should use this rather than comparing `expires_at` themselves.
+    pub fn get_erc_status(ctx: Context<GetErcStatus>) -> Result<ErcStatusView> {
+        Ok(ctx.accounts.erc_certificate.status_view(Clock::get()?.unix_timestamp))
+    }
+
+    /// Get governance statistics
+    pub fn get_governance_stats(ctx: Context<GetGovernanceStats>) -> Result<GovernanceStats> {
+        let poa_config = &ctx.accounts.poa_config;
+        let active_flags = poa_config.active_pause_flags(Clock::get()?.unix_timestamp);
+        
+        Ok(GovernanceStats {
+            total_ercs_issued: poa_config.total_ercs_issued,
+            total_ercs_validated: poa_config.total_ercs_validated,
+            erc_validation_enabled: poa_config.erc_validation_enabled,
+            emergency_paused: active_flags != 0,
+            pause_flags: active_flags,
+            maintenance_mode: poa_config.maintenance_mode,
+            min_energy_amount: poa_config.min_energy_amount,
+            max_erc_amount: poa_config.max_erc_amount,
+            erc_validity_period: poa_config.erc_validity_period,
+            erc_issuance_fee: poa_config.erc_issuance_fee,
+            council_mode: poa_config.council_mode,
+            max_pause_duration: poa_config.max_pause_duration,
+            issuance_window: poa_config.issuance_window,
+            max_window_ercs: poa_config.max_window_ercs,
+            max_window_energy: poa_config.max_window_energy,
+            created_at: poa_config.created_at,
+            last_updated: poa_config.last_updated,
+        })
+    }
+}
+
+/// UTC calendar month of `timestamp` as `YYYYMM`
+fn month_period(timestamp: i64) -> u32 {
+    // Civil-from-days conversion (Howard Hinnant's algorithm)
+    let days = timestamp.div_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year * 100 + month) as u32
+}
+
+/// Mint a valid certificate's token to `recipient` and mark it validated for trading
+///
+/// `validator` is the signer validating the certificate; `delegate` is set when it is a
+/// bonded delegate rather than the authority.
+#[allow(clippy::too_many_arguments)]
+fn validate_certificate<'info>(
+    poa_config: &mut Account<'info, PoAConfig>,
+    poa_config_bump: u8,
+    erc_certificate: &mut Account<'info, ErcCertificate>,
+    nft_mint: &InterfaceAccount<'info, Mint>,
+    recipient: &UncheckedAccount<'info>,
+    recipient_token_account: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Program<'info, Token2022>,
+    validator: Pubkey,
+    delegate: Option<Pubkey>,
+) -> Result<()> {
+    let poa_config_info = poa_config.to_account_info();
+    let clock = Clock::get()?;
+    
+    require!(!poa_config.is_paused(PoAConfig::PAUSE_VALIDATION, clock.unix_timestamp), GovernanceError::ValidationPaused);
+    require!(!poa_config.maintenance_mode, GovernanceError::MaintenanceMode);
+    require!(erc_certificate.status == ErcStatus::Valid, GovernanceError::InvalidErcStatus);
+    require!(!erc_certificate.validated_for_trading, GovernanceError::AlreadyValidated);
+    
+    // Check expiration
+    require!(!erc_certificate.is_expired(clock.unix_timestamp), GovernanceError::ErcExpired);
+    
+    erc_certificate.validated_for_trading = true;
+    erc_certificate.trading_validated_at = Some(clock.unix_timestamp);
+    erc_certificate.validated_by = delegate;
+    
+    // Update statistics
+    poa_config.total_ercs_validated = poa_config.total_ercs_validated.saturating_add(1);
+    poa_config.last_updated = clock.unix_timestamp;
+    
+    let signer_seeds: &[&[&[u8]]] = &[&[b"poa_config", &[poa_config_bump]]];
+    token_2022::mint_to(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token_2022::MintTo {
+                mint: nft_mint.to_account_info(),
+                to: recipient_token_account.to_account_info(),
+                authority: poa_config_info.clone(),
+            },
+            signer_seeds,
+        ),
+        1,
+    )?;
+    // Without a mint authority the supply can never exceed the single certificate token
+    token_2022::set_authority(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token_2022::SetAuthority {
+                current_authority: poa_config_info,
+                account_or_mint: nft_mint.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+    
+    emit_status_change(erc_certificate, Some(ErcStatus::Valid), "validated_for_trading", clock.unix_timestamp);
+    emit!(ErcValidatedForTrading {
+        certificate_id: erc_certificate.certificate_id.clone(),
+        authority: validator,
+        nft_mint: nft_mint.key(),
+        recipient: recipient.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("ERC validated for trading by {} (ID: {})", validator, erc_certificate.certificate_id);
+    Ok(())
+}
+
+/// Verify the oracle meter readings backing an ERC, claim their energy and return their addresses
+///
+/// `reading_accounts` holds each reading followed by its writable `ReadingClaim` PDA, which is
+/// created on a reading's first use. Every reading must be a `MeterReading` owned by the oracle
+/// program and, when an oracle authority is configured, submitted by it. Readings are required
+/// in that case. The certificate amount is claimed from the readings in order, so a reading's
+/// generation backs at most its own kWh across all certificates; a reading with nothing left
+/// to claim, or that the amount is covered without, is rejected.
+fn claim_source_readings<'info>(
+    reading_accounts: &'info [AccountInfo<'info>],
+    energy_amount: u64,
+    oracle_authority: Option<Pubkey>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<Vec<Pubkey>> {
+    require!(reading_accounts.len() % 2 == 0, GovernanceError::InvalidReadingClaim);
+    require!(
+        reading_accounts.len() / 2 <= ErcCertificate::MAX_SOURCE_READINGS,
+        GovernanceError::TooManySourceReadings
+    );
+    
+    if reading_accounts.is_empty() {
+        require!(oracle_authority.is_none(), GovernanceError::SourceReadingsRequired);
+        return Ok(Vec::new());
+    }
+    
+    let mut source_readings = Vec::with_capacity(reading_accounts.len() / 2);
+    let mut unclaimed = energy_amount;
+    
+    for accounts in reading_accounts.chunks_exact(2) {
+        let (account_info, claim_info) = (&accounts[0], &accounts[1]);
+        require!(
+            !source_readings.contains(account_info.key),
+            GovernanceError::DuplicateSourceReading
+        );
+        
+        let reading = Account::<MeterReading>::try_from(account_info)
+            .map_err(|_| error!(GovernanceError::InvalidSourceReading))?;
+        
+        if let Some(oracle_authority) = oracle_authority {
+            require_keys_eq!(reading.submitter, oracle_authority, GovernanceError::InvalidSourceReading);
+        }
+        
+        let mut claim = load_reading_claim(account_info.key(), claim_info, payer, system_program)?;
+        unclaimed -= claim.claim(reading.energy_produced, unclaimed)?;
+        claim.try_serialize(&mut &mut claim_info.try_borrow_mut_data()?[..])?;
+        
+        source_readings.push(account_info.key());
+    }
+    
+    require!(unclaimed == 0, GovernanceError::InsufficientSourceGeneration);
+    
+    Ok(source_readings)
+}
+
+/// Load the claim PDA of `reading`, creating it on the reading's first use
+fn load_reading_claim<'info>(
+    reading: Pubkey,
+    claim_info: &'info AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<ReadingClaim> {
+    let (claim_key, bump) = Pubkey::find_program_address(&[b"reading_claim", reading.as_ref()], &crate::ID);
+    require_keys_eq!(claim_info.key(), claim_key, GovernanceError::InvalidReadingClaim);
+    
+    if claim_info.owner == &crate::ID {
+        return ReadingClaim::try_deserialize(&mut &claim_info.try_borrow_data()?[..]);
+    }
+    
+    oracle::create_pda_account(
+        system_program,
+        payer,
+        claim_info,
+        8 + ReadingClaim::INIT_SPACE,
+        &crate::ID,
+        &[b"reading_claim", reading.as_ref(), &[bump]],
+    )?;
+    Ok(ReadingClaim {
+        reading,
+        claimed_energy: 0,
+        certificate_count: 0,
+        bump,
+    })
+}
+
+/// Replace the pause bitmask on behalf of the authority or an executed council proposal
+fn apply_pause_flags(poa_config: &mut PoAConfig, pause_flags: u8, actor: Pubkey, clock: &Clock) -> Result<()> {
+    require!(pause_flags & !PoAConfig::PAUSE_ALL == 0, GovernanceError::InvalidPauseFlags);
+    
+    let old_flags = poa_config.active_pause_flags(clock.unix_timestamp);
+    poa_config.pause_flags = pause_flags;
+    poa_config.emergency_timestamp = match (old_flags, pause_flags) {
+        (_, 0) => None,
+        (0, _) => Some(clock.unix_timestamp),
+        _ => poa_config.emergency_timestamp,
+    };
+    poa_config.last_updated = clock.unix_timestamp;
+    
+    emit!(PauseFlagsUpdated {
+        authority: actor,
+        old_flags,
+        new_flags: pause_flags,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Pause flags updated from {:#05b} to {:#05b}", old_flags, pause_flags);
+    Ok(())
+}
+
+/// Set the pause lifetime on behalf of the authority or an executed council proposal
+fn apply_max_pause_duration(
+    poa_config: &mut PoAConfig,
+    max_pause_duration: i64,
+    actor: Pubkey,
+    clock: &Clock,
+) -> Result<()> {
+    require!(!poa_config.is_paused(PoAConfig::PAUSE_CONFIG, clock.unix_timestamp), GovernanceError::ConfigUpdatesPaused);
+    require!(max_pause_duration >= 0, GovernanceError::InvalidPauseDuration);
+    
+    let old_duration = poa_config.max_pause_duration;
+    poa_config.max_pause_duration = max_pause_duration;
+    poa_config.last_updated = clock.unix_timestamp;
+    
+    emit!(MaxPauseDurationUpdated {
+        authority: actor,
+        old_duration,
+        new_duration: max_pause_duration,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Max pause duration updated to {} seconds", max_pause_duration);
+    Ok(())
+}
+
+/// Set the issuance caps on behalf of the authority or an executed council proposal
+///
+/// Changing the window length starts a new window, carrying the current counts over as the
+/// previous window so the change cannot be used to shed recent issuance.
+fn apply_issuance_rate_limit(
+    poa_config: &mut PoAConfig,
+    issuance_window: i64,
+    max_window_ercs: u64,
+    max_window_energy: u64,
+    actor: Pubkey,
+    clock: &Clock,
+) -> Result<()> {
+    require!(!poa_config.is_paused(PoAConfig::PAUSE_CONFIG, clock.unix_timestamp), GovernanceError::ConfigUpdatesPaused);
+    require!(issuance_window > 0, GovernanceError::InvalidIssuanceWindow);
+    
+    if issuance_window != poa_config.issuance_window {
+        poa_config.roll_issuance_window(clock.unix_timestamp);
+        poa_config.previous_window_ercs = poa_config.window_ercs;
+        poa_config.previous_window_energy = poa_config.window_energy;
+        poa_config.window_ercs = 0;
+        poa_config.window_energy = 0;
+        poa_config.window_started_at = clock.unix_timestamp;
+    }
+    poa_config.issuance_window = issuance_window;
+    poa_config.max_window_ercs = max_window_ercs;
+    poa_config.max_window_energy = max_window_energy;
+    poa_config.last_updated = clock.unix_timestamp;
+    
+    emit!(IssuanceRateLimitUpdated {
+        authority: actor,
+        issuance_window,
+        max_window_ercs,
+        max_window_energy,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Issuance rate limit updated - {} ERCs / {} kWh per {} seconds",
+         max_window_ercs, max_window_energy, issuance_window);
+    Ok(())
+}
+
+/// Set the certificate-locking trading program on behalf of the authority or an executed council proposal
+fn apply_trading_program(
+    poa_config: &mut PoAConfig,
+    trading_program: Option<Pubkey>,
+    actor: Pubkey,
+    clock: &Clock,
+) -> Result<()> {
+    require!(!poa_config.is_paused(PoAConfig::PAUSE_CONFIG, clock.unix_timestamp), GovernanceError::ConfigUpdatesPaused);
+    
+    let old_program = poa_config.trading_program;
+    poa_config.trading_program = trading_program;
+    poa_config.last_updated = clock.unix_timestamp;
+    
+    emit!(TradingProgramUpdated {
+        authority: actor,
+        old_program,
+        new_program: trading_program,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Trading program updated to {:?}", trading_program);
+    Ok(())
+}
+
+/// Check that `trading_authority` is the configured trading program's lock authority PDA
+fn verify_trading_authority(poa_config: &PoAConfig, trading_authority: &Signer) -> Result<Pubkey> {
+    let trading_program = poa_config.trading_program.ok_or(GovernanceError::TradingProgramNotSet)?;
+    let (expected, _) = Pubkey::find_program_address(&[ERC_LOCK_AUTHORITY_SEED], &trading_program);
+    require_keys_eq!(trading_authority.key(), expected, GovernanceError::UnauthorizedTradingProgram);
+    Ok(trading_program)
+}
+
+/// Update ERC issuance limits on behalf of the authority or an executed council or stakeholder proposal
+fn apply_erc_limits(
+    poa_config: &mut PoAConfig,
+    min_energy_amount: u64,
+    max_erc_amount: u64,
+    erc_validity_period: i64,
+    actor: Pubkey,
+    clock: &Clock,
+) -> Result<()> {
+    require!(!poa_config.is_paused(PoAConfig::PAUSE_CONFIG, clock.unix_timestamp), GovernanceError::ConfigUpdatesPaused);
+    validate_erc_limits(min_energy_amount, max_erc_amount, erc_validity_period)?;
+    
+    let old_min = poa_config.min_energy_amount;
+    let old_max = poa_config.max_erc_amount;
+    let old_validity = poa_config.erc_validity_period;
+    
+    poa_config.min_energy_amount = min_energy_amount;
+    poa_config.max_erc_amount = max_erc_amount;
+    poa_config.erc_validity_period = erc_validity_period;
+    poa_config.last_updated = clock.unix_timestamp;
+    
+    emit!(ErcLimitsUpdated {
+        authority: actor,
+        old_min,
+        new_min: min_energy_amount,
+        old_max,
+        new_max: max_erc_amount,
+        old_validity,
+        new_validity: erc_validity_period,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("ERC limits updated - Min: {} kWh, Max: {} kWh, Validity: {} seconds", 
+         min_energy_amount, max_erc_amount, erc_validity_period);
+    Ok(())
+}
+
+fn validate_erc_limits(min_energy_amount: u64, max_erc_amount: u64, erc_validity_period: i64) -> Result<()> {
+    require!(min_energy_amount > 0, GovernanceError::InvalidMinimumEnergy);
+    require!(max_erc_amount > min_energy_amount, GovernanceError::InvalidMaximumEnergy);
+    require!(erc_validity_period > 0, GovernanceError::InvalidValidityPeriod);
+    Ok(())
+}
+
+/// Set the ERC issuance fee on behalf of the authority or an executed stakeholder proposal
+fn apply_erc_issuance_fee(poa_config: &mut PoAConfig, erc_issuance_fee: u64, actor: Pubkey, clock: &Clock) -> Result<()> {
+    require!(!poa_config.is_paused(PoAConfig::PAUSE_CONFIG, clock.unix_timestamp), GovernanceError::ConfigUpdatesPaused);
+    
+    let old_fee = poa_config.erc_issuance_fee;
+    poa_config.erc_issuance_fee = erc_issuance_fee;
+    poa_config.last_updated = clock.unix_timestamp;
+    
+    emit!(ErcIssuanceFeeUpdated {
+        authority: actor,
+        old_fee,
+        new_fee: erc_issuance_fee,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("ERC issuance fee updated to {} lamports", erc_issuance_fee);
+    Ok(())
+}
+
+/// Toggle ERC validation on behalf of the authority or an executed council proposal
+fn apply_erc_validation(poa_config: &mut PoAConfig, erc_validation_enabled: bool, actor: Pubkey, clock: &Clock) -> Result<()> {
+    require!(!poa_config.is_paused(PoAConfig::PAUSE_CONFIG, clock.unix_timestamp), GovernanceError::ConfigUpdatesPaused);
+    
+    let old_enabled = poa_config.erc_validation_enabled;
+    poa_config.erc_validation_enabled = erc_validation_enabled;
+    poa_config.last_updated = clock.unix_timestamp;
+    
+    emit!(GovernanceConfigUpdated {
+        authority: actor,
+        erc_validation_enabled,
+        old_enabled,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Governance configuration updated - ERC validation: {}", erc_validation_enabled);
+    Ok(())
+}
+
+/// Toggle maintenance mode on behalf of the authority or an executed council proposal
+fn apply_maintenance_mode(poa_config: &mut PoAConfig, maintenance_enabled: bool, actor: Pubkey, clock: &Clock) -> Result<()> {
+    require!(!poa_config.is_paused(PoAConfig::PAUSE_CONFIG, clock.unix_timestamp), GovernanceError::ConfigUpdatesPaused);
+    
+    poa_config.maintenance_mode = maintenance_enabled;
+    poa_config.last_updated = clock.unix_timestamp;
+    
+    emit!(MaintenanceModeUpdated {
+        authority: actor,
+        maintenance_enabled,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Maintenance mode {}", if maintenance_enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Set the oracle authority on behalf of the authority or an executed council proposal
+fn apply_oracle_authority(
+    poa_config: &mut PoAConfig,
+    oracle_authority: Option<Pubkey>,
+    actor: Pubkey,
+    clock: &Clock,
+) -> Result<()> {
+    require!(!poa_config.is_paused(PoAConfig::PAUSE_CONFIG, clock.unix_timestamp), GovernanceError::ConfigUpdatesPaused);
+    
+    let old_oracle_authority = poa_config.oracle_authority;
+    poa_config.oracle_authority = oracle_authority;
+    poa_config.last_updated = clock.unix_timestamp;
+    
+    emit!(OracleAuthorityUpdated {
+        authority: actor,
+        old_oracle_authority,
+        new_oracle_authority: oracle_authority,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Oracle validation {}", if oracle_authority.is_some() { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Pay `amount` out of the treasury on behalf of the authority or an executed council proposal
+///
+/// The treasury always keeps enough lamports to stay rent exempt.
+fn withdraw_from_treasury<'info>(
+    treasury: &mut Account<'info, Treasury>,
+    recipient: &AccountInfo<'info>,
+    amount: u64,
+    actor: Pubkey,
+    clock: &Clock,
+) -> Result<()> {
+    require!(amount > 0, GovernanceError::InvalidWithdrawalAmount);
+    
+    let treasury_info = treasury.to_account_info();
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(treasury_info.data_len());
+    let available = treasury_info.lamports().saturating_sub(rent_exempt_minimum);
+    require!(amount <= available, GovernanceError::InsufficientTreasuryBalance);
+    
+    // The treasury is owned by this program, so lamports move without a system transfer
+    treasury_info.sub_lamports(amount)?;
+    recipient.add_lamports(amount)?;
+    
+    treasury.total_withdrawn = treasury
+        .total_withdrawn
+        .checked_add(amount)
+        .ok_or(GovernanceError::ArithmeticOverflow)?;
+    
+    emit!(TreasuryWithdrawn {
+        authority: actor,
+        recipient: recipient.key(),
+        amount,
+        remaining: available - amount,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Withdrew {} lamports from treasury", amount);
+    Ok(())
+}
+
+/// Accounts needed to burn a certificate token through the mint's permanent delegate
+struct CertificateTokenAccounts<'info> {
+    /// Certificate mint PDA; empty until the certificate is validated for trading
+    nft_mint: AccountInfo<'info>,
+    holder_token_account: Option<AccountInfo<'info>>,
+    token_program: Option<AccountInfo<'info>>,
+    /// Permanent delegate of every certificate mint
+    poa_config: AccountInfo<'info>,
+    poa_config_bump: u8,
+}
+
+impl CertificateTokenAccounts<'_> {
+    /// Burn the outstanding certificate token, if one was minted
+    fn burn_outstanding(&self) -> Result<()> {
+        if self.nft_mint.data_is_empty() {
+            return Ok(());
+        }
+        
+        require_keys_eq!(*self.nft_mint.owner, token_2022::ID, GovernanceError::InvalidCertificateMint);
+        let supply = Mint::try_deserialize(&mut &self.nft_mint.try_borrow_data()?[..])?.supply;
+        if supply == 0 {
+            return Ok(());
+        }
+        
+        let holder_token_account = self
+            .holder_token_account
+            .clone()
+            .ok_or(GovernanceError::CertificateTokenAccountRequired)?;
+        let token_program = self
+            .token_program
+            .clone()
+            .ok_or(GovernanceError::CertificateTokenAccountRequired)?;
+        let signer_seeds: &[&[&[u8]]] = &[&[b"poa_config", &[self.poa_config_bump]]];
+        
+        token_2022::burn(
+            CpiContext::new_with_signer(
+                token_program,
+                token_2022::Burn {
+                    mint: self.nft_mint.clone(),
+                    from: holder_token_account,
+                    authority: self.poa_config.clone(),
+                },
+                signer_seeds,
+            ),
+            supply,
+        )
+    }
+}
+
+/// Revoke a certificate on behalf of the authority or an executed council proposal
+fn revoke_certificate(
+    erc_certificate: &mut ErcCertificate,
+    token_accounts: &CertificateTokenAccounts,
+    actor: Pubkey,
+    reason: String,
+    clock: &Clock,
+) -> Result<()> {
+    require!(reason.len() <= CouncilAction::MAX_REASON_LEN, GovernanceError::ReasonTooLong);
+    require!(
+        matches!(
+            erc_certificate.status,
+            ErcStatus::Valid | ErcStatus::Pending | ErcStatus::Expired
+        ),
+        GovernanceError::InvalidErcStatus
+    );
+    
+    token_accounts.burn_outstanding()?;
+    let old_status = erc_certificate.status.clone();
+    erc_certificate.status = ErcStatus::Revoked;
+    
+    emit_status_change(erc_certificate, Some(old_status), &reason, clock.unix_timestamp);
+    emit!(ErcRevoked {
+        certificate_id: erc_certificate.certificate_id.clone(),
+        authority: actor,
+        reason,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("ERC revoked (ID: {})", erc_certificate.certificate_id);
+    Ok(())
+}
+
+/// Emit the lifecycle event shared by every certificate status transition
+fn emit_status_change(erc_certificate: &ErcCertificate, old_status: Option<ErcStatus>, reason: &str, timestamp: i64) {
+    emit!(ErcStatusChanged {
+        certificate_id: erc_certificate.certificate_id.clone(),
+        old_status,
+        new_status: erc_certificate.status.clone(),
+        reason: reason.to_string(),
+        timestamp,
+    });
+}
+
+/// Bytes a meter signs to attest the generation behind a certificate
+pub fn meter_attestation_message(
+    certificate_id: &str,
+    energy_amount: u64,
+    renewable_source: &RenewableSource,
+    period: u32,
+) -> Vec<u8> {
+    let source = renewable_source.index_seed();
+    let mut message = Vec::with_capacity(
+        METER_ATTESTATION_DOMAIN.len() + 4 + certificate_id.len() + 8 + source.len() + 4,
+    );
+    message.extend_from_slice(METER_ATTESTATION_DOMAIN);
+    message.extend_from_slice(&(certificate_id.len() as u32).to_le_bytes());
+    message.extend_from_slice(certificate_id.as_bytes());
+    message.extend_from_slice(&energy_amount.to_le_bytes());
+    message.extend_from_slice(&source);
+    message.extend_from_slice(&period.to_le_bytes());
+    message
+}
+
+/// Require the previous instruction to be an Ed25519 program check of `message` by `signer`
+///
+/// Fails with `missing` without such an instruction and with `invalid` when it checks another
+/// key or message. The trading program verifies channel states with it too.
+pub fn verify_ed25519_instruction<E: Into<Error> + Copy>(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+    missing: E,
+    invalid: E,
+) -> Result<()> {
+    let current = load_current_index_checked(instructions)? as usize;
+    if current == 0 {
+        return Err(missing.into());
+    }
+    
+    let instruction = load_instruction_at_checked(current - 1, instructions)?;
+    if instruction.program_id != ed25519_program::ID {
+        return Err(missing.into());
+    }
+    
+    // Layout: signature count, padding, then one 14-byte offsets record per signature
+    let data = &instruction.data;
+    if data.len() < 16 || data[0] != 1 {
+        return Err(invalid.into());
+    }
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    
+    // Signature, key and message must live in the Ed25519 instruction itself
+    if read_u16(4) != u16::MAX || read_u16(8) != u16::MAX || read_u16(14) != u16::MAX {
+        return Err(invalid.into());
+    }
+    
+    let key_offset = read_u16(6) as usize;
+    let message_offset = read_u16(10) as usize;
+    let message_len = read_u16(12) as usize;
+    let signed_key = data.get(key_offset..key_offset + 32);
+    let signed_message = data.get(message_offset..message_offset + message_len);
+    
+    if signed_key != Some(signer.as_ref()) || signed_message != Some(message) {
+        return Err(invalid.into());
+    }
+    Ok(())
+}
+
+// Account structures for single authority PoA
+#[derive(Accounts)]
+pub struct InitializePoa<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PoAConfig::INIT_SPACE,
+        seeds = [b"poa_config"],
+        bump
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
 pub struct EmergencyControl<'info> {
     #[account(
         mut,
@@ -313,29 +2055,515 @@ pub struct EmergencyControl<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(certificate_id: String)]
-pub struct IssueErc<'info> {
+pub struct TradeErcLock<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        mut,
+        seeds = [b"erc_certificate", erc_certificate.certificate_id.as_bytes()],
+        bump
+    )]
+    pub erc_certificate: Account<'info, ErcCertificate>,
+    /// Lock authority PDA of the configured trading program, signed through `invoke_signed`
+    pub trading_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExpirePause<'info> {
+    #[account(
+        mut,
+        seeds = [b"poa_config"],
+        bump
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTreasury<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Treasury::LEN,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePoaConfig<'info> {
+    /// CHECK: may hold an older `PoAConfig` layout that does not deserialize as the current one;
+    /// the discriminator and authority are checked in the instruction
+    #[account(
+        mut,
+        seeds = [b"poa_config"],
+        bump,
+        owner = crate::ID
+    )]
+    pub poa_config: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(certificate_id: String)]
+pub struct MigrateErcCertificate<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    /// CHECK: holds the pre-migration certificate layout, which does not deserialize as the
+    /// current one; the discriminator and certificate ID are checked in the instruction
+    #[account(
+        mut,
+        seeds = [b"erc_certificate", certificate_id.as_bytes()],
+        bump,
+        owner = crate::ID
+    )]
+    pub erc_certificate: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    certificate_id: String,
+    energy_amount: u64,
+    renewable_source: RenewableSource,
+    validation_data: String,
+    period: u32
+)]
+pub struct IssueErc<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ErcCertificate::INIT_SPACE,
+        seeds = [b"erc_certificate", certificate_id.as_bytes()],
+        bump
+    )]
+    pub erc_certificate: Account<'info, ErcCertificate>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    /// Certificates issued from this renewable source
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ErcIndex::LEN,
+        seeds = [b"erc_source_index", renewable_source.index_seed().as_slice()],
+        bump
+    )]
+    pub source_index: Account<'info, ErcIndex>,
+    /// Certificates issued in the current UTC month
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ErcIndex::LEN,
+        seeds = [b"erc_month_index", period.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub month_index: Account<'info, ErcIndex>,
+    /// Registered meter whose device key signed the generation payload
+    pub meter_account: Option<Account<'info, MeterAccount>>,
+    /// CHECK: Instructions sysvar, used to find the meter's Ed25519 signature check
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ValidateErc<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        mut,
+        seeds = [b"erc_certificate", erc_certificate.certificate_id.as_bytes()],
+        bump
+    )]
+    pub erc_certificate: Account<'info, ErcCertificate>,
+    /// Non-fungible certificate token; the metadata pointer references the certificate PDA
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"erc_mint", erc_certificate.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = poa_config,
+        mint::token_program = token_program,
+        extensions::metadata_pointer::authority = poa_config,
+        extensions::metadata_pointer::metadata_address = erc_certificate,
+        extensions::permanent_delegate::delegate = poa_config,
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: Any wallet may receive the certificate token
+    pub recipient: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = nft_mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program,
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DelegatedValidateErc<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        mut,
+        seeds = [b"erc_certificate", erc_certificate.certificate_id.as_bytes()],
+        bump
+    )]
+    pub erc_certificate: Account<'info, ErcCertificate>,
+    /// Non-fungible certificate token; the metadata pointer references the certificate PDA
+    #[account(
+        init,
+        payer = delegate,
+        seeds = [b"erc_mint", erc_certificate.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = poa_config,
+        mint::token_program = token_program,
+        extensions::metadata_pointer::authority = poa_config,
+        extensions::metadata_pointer::metadata_address = erc_certificate,
+        extensions::permanent_delegate::delegate = poa_config,
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: Any wallet may receive the certificate token
+    pub recipient: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = delegate,
+        associated_token::mint = nft_mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program,
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// Staking program PDA of `delegate`, signed through `invoke_signed`
+    pub validator_authority: Signer<'info>,
+    #[account(mut)]
+    pub delegate: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RetireErc<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        mut,
+        seeds = [b"erc_certificate", erc_certificate.certificate_id.as_bytes()],
+        bump
+    )]
+    pub erc_certificate: Account<'info, ErcCertificate>,
+    #[account(
+        mut,
+        seeds = [b"erc_mint", erc_certificate.key().as_ref()],
+        bump,
+        mint::token_program = token_program,
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = holder,
+        token::token_program = token_program,
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub holder: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendErcValidity<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        mut,
+        seeds = [b"erc_certificate", erc_certificate.certificate_id.as_bytes()],
+        bump
+    )]
+    pub erc_certificate: Account<'info, ErcCertificate>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireErc<'info> {
+    #[account(
+        mut,
+        seeds = [b"erc_certificate", erc_certificate.certificate_id.as_bytes()],
+        bump
+    )]
+    pub erc_certificate: Account<'info, ErcCertificate>,
+}
+
+#[derive(Accounts)]
+pub struct ChallengeErc<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        mut,
+        seeds = [b"erc_certificate", erc_certificate.certificate_id.as_bytes()],
+        bump
+    )]
+    pub erc_certificate: Account<'info, ErcCertificate>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeErc<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        mut,
+        seeds = [b"erc_certificate", erc_certificate.certificate_id.as_bytes()],
+        bump
+    )]
+    pub erc_certificate: Account<'info, ErcCertificate>,
+    /// CHECK: Certificate mint PDA; empty until the certificate is validated for trading
+    #[account(
+        mut,
+        seeds = [b"erc_mint", erc_certificate.key().as_ref()],
+        bump
+    )]
+    pub nft_mint: UncheckedAccount<'info>,
+    /// CHECK: Validated by the token program during the burn
+    #[account(mut)]
+    pub holder_token_account: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Program<'info, Token2022>>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCouncil<'info> {
+    #[account(
+        mut,
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Council::LEN,
+        seeds = [b"council"],
+        bump
+    )]
+    pub council: Account<'info, Council>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"council"],
+        bump = council.bump
+    )]
+    pub council: Account<'info, Council>,
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + CouncilProposal::LEN,
+        seeds = [b"council_proposal", council.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, CouncilProposal>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveAction<'info> {
+    #[account(
+        seeds = [b"council"],
+        bump = council.bump
+    )]
+    pub council: Account<'info, Council>,
+    #[account(
+        mut,
+        seeds = [b"council_proposal", proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, CouncilProposal>,
+    pub member: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"poa_config"],
+        bump
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        mut,
+        seeds = [b"council"],
+        bump = council.bump
+    )]
+    pub council: Account<'info, Council>,
+    #[account(
+        mut,
+        seeds = [b"council_proposal", proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, CouncilProposal>,
+    /// Certificate targeted by a revocation proposal
+    #[account(mut)]
+    pub erc_certificate: Option<Account<'info, ErcCertificate>>,
+    /// CHECK: Checked against the certificate mint PDA for revocations
+    #[account(mut)]
+    pub nft_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK: Validated by the token program during the burn
+    #[account(mut)]
+    pub holder_token_account: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Program<'info, Token2022>>,
+    /// Treasury drawn on by a withdrawal proposal
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+    /// Recipient of a withdrawal proposal
+    #[account(mut)]
+    pub recipient: Option<SystemAccount<'info>>,
+    pub member: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeStakeholderRegistry<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + StakeholderRegistry::LEN,
+        seeds = [b"stakeholder_registry"],
+        bump
+    )]
+    pub registry: Account<'info, StakeholderRegistry>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(stakeholder_key: Pubkey)]
+pub struct RegisterStakeholder<'info> {
     #[account(
         seeds = [b"poa_config"],
         bump,
         has_one = authority @ GovernanceError::UnauthorizedAuthority
     )]
     pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        mut,
+        seeds = [b"stakeholder_registry"],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, StakeholderRegistry>,
     #[account(
         init,
         payer = authority,
-        space = 8 + ErcCertificate::LEN,
-        seeds = [b"erc_certificate", certificate_id.as_bytes()],
+        space = 8 + Stakeholder::LEN,
+        seeds = [b"stakeholder", stakeholder_key.as_ref()],
         bump
     )]
-    pub erc_certificate: Account<'info, ErcCertificate>,
+    pub stakeholder: Account<'info, Stakeholder>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ValidateErc<'info> {
+pub struct RemoveStakeholder<'info> {
     #[account(
         seeds = [b"poa_config"],
         bump,
@@ -344,10 +2572,91 @@ pub struct ValidateErc<'info> {
     pub poa_config: Account<'info, PoAConfig>,
     #[account(
         mut,
-        seeds = [b"erc_certificate", erc_certificate.certificate_id.as_bytes()],
+        seeds = [b"stakeholder_registry"],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, StakeholderRegistry>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"stakeholder", stakeholder.key.as_ref()],
+        bump = stakeholder.bump
+    )]
+    pub stakeholder: Account<'info, Stakeholder>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        mut,
+        seeds = [b"stakeholder_registry"],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, StakeholderRegistry>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Proposal::LEN,
+        seeds = [b"proposal", registry.proposal_count.to_le_bytes().as_ref()],
         bump
     )]
-    pub erc_certificate: Account<'info, ErcCertificate>,
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    /// Registration of the voter; only registered stakeholders can vote
+    #[account(
+        seeds = [b"stakeholder", voter.key().as_ref()],
+        bump = stakeholder.bump
+    )]
+    pub stakeholder: Account<'info, Stakeholder>,
+    /// One vote record per stakeholder and proposal
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + ProposalVote::LEN,
+        seeds = [b"proposal_vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote: Account<'info, ProposalVote>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
     pub authority: Signer<'info>,
 }
 
@@ -363,6 +2672,33 @@ pub struct UpdateGovernanceConfig<'info> {
     pub authority: Signer<'info>,
 }
 
+/// Reallocates the config to the current layout, so profile fields can grow up to their limits
+#[derive(Accounts)]
+pub struct UpdateAuthorityProfile<'info> {
+    #[account(
+        mut,
+        seeds = [b"poa_config"],
+        bump,
+        has_one = authority @ GovernanceError::UnauthorizedAuthority,
+        realloc = 8 + PoAConfig::INIT_SPACE,
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetErcStatus<'info> {
+    #[account(
+        seeds = [b"erc_certificate", erc_certificate.certificate_id.as_bytes()],
+        bump
+    )]
+    pub erc_certificate: Account<'info, ErcCertificate>,
+}
+
 #[derive(Accounts)]
 pub struct GetGovernanceStats<'info> {
     #[account(
@@ -374,78 +2710,553 @@ pub struct GetGovernanceStats<'info> {
 
 // Data structures for single authority PoA
 #[account]
+#[derive(InitSpace)]
 pub struct PoAConfig {
     /// Single authority - Engineering Department
     pub authority: Pubkey,
     /// Authority name for identification
+    #[max_len(60)]
     pub authority_name: String,
-    /// Department contact information
-    pub contact_info: String,
-    /// Emergency pause status
-    pub emergency_paused: bool,
+    /// Department contact email
+    #[max_len(124)]
+    pub contact_email: String,
+    /// Pause flags bitmask (`PAUSE_ISSUANCE`, `PAUSE_VALIDATION`, `PAUSE_CONFIG`)
+    pub pause_flags: u8,
     /// Emergency pause timestamp
     pub emergency_timestamp: Option<i64>,
     /// Emergency pause reason
+    #[max_len(128)]
     pub emergency_reason: Option<String>,
     /// When governance was initialized
     pub created_at: i64,
-    /// Last updated timestamp
-    pub last_updated: i64,
-    /// Whether ERC validation is enabled
-    pub erc_validation_enabled: bool,
-    /// Maximum ERC amount that can be issued per transaction
-    pub max_erc_amount: u64,
-    /// Total ERCs issued
-    pub total_ercs_issued: u64,
-    /// Total ERCs validated for trading
-    pub total_ercs_validated: u64,
-    /// Governance version for upgrades
-    pub version: u8,
-    /// Whether the authority can delegate ERC validation
-    pub delegation_enabled: bool,
-    /// Oracle authority for AMI data validation
-    pub oracle_authority: Option<Pubkey>,
-    /// Minimum energy amount for ERC issuance (kWh)
-    pub min_energy_amount: u64,
-    /// ERC certificate validity period (seconds)
-    pub erc_validity_period: i64,
-    /// System maintenance mode
-    pub maintenance_mode: bool,
+    /// Last updated timestamp
+    pub last_updated: i64,
+    /// Whether ERC validation is enabled
+    pub erc_validation_enabled: bool,
+    /// Maximum ERC amount that can be issued per transaction
+    pub max_erc_amount: u64,
+    /// Total ERCs issued
+    pub total_ercs_issued: u64,
+    /// Total ERCs validated for trading
+    pub total_ercs_validated: u64,
+    /// Governance version for upgrades
+    pub version: u8,
+    /// Whether the authority can delegate ERC validation
+    pub delegation_enabled: bool,
+    /// Oracle authority for AMI data validation; when set, ERCs must be backed by its meter readings
+    pub oracle_authority: Option<Pubkey>,
+    /// Minimum energy amount for ERC issuance (kWh)
+    pub min_energy_amount: u64,
+    /// ERC certificate validity period (seconds)
+    pub erc_validity_period: i64,
+    /// System maintenance mode
+    pub maintenance_mode: bool,
+    /// Lamports charged to the issuer per ERC, collected in the treasury
+    pub erc_issuance_fee: u64,
+    /// Whether configuration changes, pauses, revocations and treasury withdrawals require M-of-N council approval
+    pub council_mode: bool,
+    /// Seconds after `emergency_timestamp` at which a pause lapses; 0 never lapses
+    pub max_pause_duration: i64,
+    /// Program whose lock authority PDA may lock certificates for trades
+    pub trading_program: Option<Pubkey>,
+    /// Length of the issuance rate-limit window (seconds)
+    pub issuance_window: i64,
+    /// Certificates that may be issued per trailing window; 0 is unlimited
+    pub max_window_ercs: u64,
+    /// Energy (kWh) that may be certified per trailing window; 0 is unlimited
+    pub max_window_energy: u64,
+    /// Start of the current issuance window
+    pub window_started_at: i64,
+    /// Certificates issued in the current window
+    pub window_ercs: u64,
+    /// Energy certified in the current window
+    pub window_energy: u64,
+    /// Certificates issued in the window before the current one
+    pub previous_window_ercs: u64,
+    /// Energy certified in the window before the current one
+    pub previous_window_energy: u64,
+    /// Department contact phone number
+    #[max_len(32)]
+    pub contact_phone: String,
+    /// Department website
+    #[max_len(128)]
+    pub website: String,
+    /// Department office address
+    #[max_len(128)]
+    pub office_location: String,
+}
+
+// `init` and `realloc` can grow an account by at most 10 KiB per instruction
+const _: () = assert!(8 + PoAConfig::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+
+impl PoAConfig {
+    /// Layout version written by `initialize_poa` and `migrate_poa_config`
+    pub const CURRENT_VERSION: u8 = 8;
+
+    /// Blocks ERC issuance
+    pub const PAUSE_ISSUANCE: u8 = 1 << 0;
+    /// Blocks ERC validation for trading
+    pub const PAUSE_VALIDATION: u8 = 1 << 1;
+    /// Blocks governance configuration updates
+    pub const PAUSE_CONFIG: u8 = 1 << 2;
+    pub const PAUSE_ALL: u8 = Self::PAUSE_ISSUANCE | Self::PAUSE_VALIDATION | Self::PAUSE_CONFIG;
+    /// Flags set by `emergency_pause`
+    pub const EMERGENCY_PAUSE_FLAGS: u8 = Self::PAUSE_ISSUANCE | Self::PAUSE_VALIDATION;
+
+    /// Pause lifetime set by `initialize_poa` and `migrate_poa_config`
+    pub const DEFAULT_MAX_PAUSE_DURATION: i64 = 7 * 24 * 60 * 60;
+
+    pub fn profile(&self) -> AuthorityProfile {
+        AuthorityProfile {
+            name: self.authority_name.clone(),
+            email: self.contact_email.clone(),
+            phone: self.contact_phone.clone(),
+            website: self.website.clone(),
+            office_location: self.office_location.clone(),
+        }
+    }
+
+    fn set_profile(&mut self, profile: AuthorityProfile) {
+        self.authority_name = profile.name;
+        self.contact_email = profile.email;
+        self.contact_phone = profile.phone;
+        self.website = profile.website;
+        self.office_location = profile.office_location;
+    }
+
+    /// Issuance rate limits set by `initialize_poa` and `migrate_poa_config`
+    pub const DEFAULT_ISSUANCE_WINDOW: i64 = 24 * 60 * 60;
+    pub const DEFAULT_MAX_WINDOW_ERCS: u64 = 1_000;
+    pub const DEFAULT_MAX_WINDOW_ENERGY: u64 = 10_000_000;
+
+    /// Restore the default issuance caps and start an empty window at `now`
+    pub fn reset_issuance_rate_limit(&mut self, now: i64) {
+        self.issuance_window = Self::DEFAULT_ISSUANCE_WINDOW;
+        self.max_window_ercs = Self::DEFAULT_MAX_WINDOW_ERCS;
+        self.max_window_energy = Self::DEFAULT_MAX_WINDOW_ENERGY;
+        self.window_started_at = now;
+        self.window_ercs = 0;
+        self.window_energy = 0;
+        self.previous_window_ercs = 0;
+        self.previous_window_energy = 0;
+    }
+
+    /// Advance to the issuance window containing `now`
+    fn roll_issuance_window(&mut self, now: i64) {
+        let elapsed = now.saturating_sub(self.window_started_at);
+        if self.issuance_window <= 0 || elapsed < self.issuance_window {
+            return;
+        }
+        
+        if elapsed < 2 * self.issuance_window {
+            self.previous_window_ercs = self.window_ercs;
+            self.previous_window_energy = self.window_energy;
+        } else {
+            self.previous_window_ercs = 0;
+            self.previous_window_energy = 0;
+        }
+        self.window_ercs = 0;
+        self.window_energy = 0;
+        self.window_started_at = now - elapsed % self.issuance_window;
+    }
+
+    /// Count an issuance against the trailing window, failing if it would exceed either cap
+    ///
+    /// Sliding-window counter: the previous window's totals are weighted by the share of it
+    /// still inside the trailing `issuance_window` seconds.
+    pub fn record_issuance(&mut self, energy_amount: u64, now: i64) -> Result<()> {
+        self.roll_issuance_window(now);
+        
+        let window = self.issuance_window.max(1) as u128;
+        let overlap = window - (now.saturating_sub(self.window_started_at).max(0) as u128).min(window);
+        let trailing = |previous: u64, current: u64| (previous as u128 * overlap / window) + current as u128;
+        
+        let ercs = trailing(self.previous_window_ercs, self.window_ercs) + 1;
+        require!(
+            self.max_window_ercs == 0 || ercs <= self.max_window_ercs as u128,
+            GovernanceError::IssuanceRateLimitExceeded
+        );
+        let energy = trailing(self.previous_window_energy, self.window_energy) + energy_amount as u128;
+        require!(
+            self.max_window_energy == 0 || energy <= self.max_window_energy as u128,
+            GovernanceError::IssuanceRateLimitExceeded
+        );
+        
+        self.window_ercs = self.window_ercs.saturating_add(1);
+        self.window_energy = self.window_energy.saturating_add(energy_amount);
+        Ok(())
+    }
+
+    /// Whether the current pause has outlived `max_pause_duration`
+    pub fn pause_lapsed(&self, now: i64) -> bool {
+        match self.emergency_timestamp {
+            Some(paused_at) if self.max_pause_duration > 0 => {
+                now >= paused_at.saturating_add(self.max_pause_duration)
+            }
+            _ => false,
+        }
+    }
+
+    /// Pause flags still in effect at `now`
+    pub fn active_pause_flags(&self, now: i64) -> u8 {
+        if self.pause_lapsed(now) {
+            0
+        } else {
+            self.pause_flags
+        }
+    }
+
+    /// Whether every operation in `flags` is paused
+    pub fn is_paused(&self, flags: u8, now: i64) -> bool {
+        self.active_pause_flags(now) & flags == flags
+    }
+}
+
+/// Public contact details of the issuing authority
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AuthorityProfile {
+    pub name: String,
+    pub email: String,
+    pub phone: String,
+    pub website: String,
+    pub office_location: String,
+}
+
+impl AuthorityProfile {
+    /// Byte limits; name and email fit the original `authority_name`/`contact_info` slots
+    pub const MAX_NAME_LEN: usize = 60;
+    pub const MAX_EMAIL_LEN: usize = 124;
+    pub const MAX_PHONE_LEN: usize = 32;
+    pub const MAX_WEBSITE_LEN: usize = 128;
+    pub const MAX_OFFICE_LEN: usize = 128;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(!self.name.is_empty() && self.name.len() <= Self::MAX_NAME_LEN, GovernanceError::InvalidAuthorityName);
+        require!(self.email.len() <= Self::MAX_EMAIL_LEN, GovernanceError::ContactInfoTooLong);
+        require!(self.phone.len() <= Self::MAX_PHONE_LEN, GovernanceError::ContactInfoTooLong);
+        require!(self.website.len() <= Self::MAX_WEBSITE_LEN, GovernanceError::ContactInfoTooLong);
+        require!(self.office_location.len() <= Self::MAX_OFFICE_LEN, GovernanceError::ContactInfoTooLong);
+        Ok(())
+    }
+}
+
+/// Program-owned PDA holding ERC issuance fees
+#[account]
+pub struct Treasury {
+    /// Total lamports collected from issuance fees
+    pub total_collected: u64,
+    /// Total lamports withdrawn by the authority
+    pub total_withdrawn: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Treasury {
+    pub const LEN: usize = 8 + 8 + 8 + 1;
+}
+
+/// M-of-N council gating sensitive operations once council mode is enabled
+#[account]
+pub struct Council {
+    pub members: Vec<Pubkey>,
+    /// Approvals required to execute a proposal
+    pub threshold: u8,
+    /// Proposals created so far; seeds the next proposal PDA
+    pub proposal_count: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Council {
+    pub const MAX_MEMBERS: usize = 5;
+
+    pub const LEN: usize = (4 + 32 * Self::MAX_MEMBERS) + 1 + 8 + 8 + 1;
+
+    pub fn validate_members(members: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(
+            !members.is_empty() && members.len() <= Self::MAX_MEMBERS,
+            GovernanceError::InvalidCouncilMembers
+        );
+        for (i, member) in members.iter().enumerate() {
+            require!(!members[..i].contains(member), GovernanceError::InvalidCouncilMembers);
+        }
+        require!(
+            threshold > 0 && threshold as usize <= members.len(),
+            GovernanceError::InvalidCouncilThreshold
+        );
+        Ok(())
+    }
+
+    pub fn is_member(&self, key: &Pubkey) -> bool {
+        self.members.contains(key)
+    }
+
+    /// Approvals from keys that are still council members
+    pub fn count_approvals(&self, approvals: &[Pubkey]) -> u8 {
+        approvals.iter().filter(|key| self.is_member(key)).count() as u8
+    }
+}
+
+/// Largest of `lens`, for sizing accounts that hold one of several layouts
+const fn max_len(lens: &[usize]) -> usize {
+    let mut max = 0;
+    let mut i = 0;
+    while i < lens.len() {
+        if lens[i] > max {
+            max = lens[i];
+        }
+        i += 1;
+    }
+    max
+}
+
+/// Operations that require council approval in council mode
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum CouncilAction {
+    SetPauseFlags {
+        pause_flags: u8,
+    },
+    UpdateErcLimits {
+        min_energy_amount: u64,
+        max_erc_amount: u64,
+        erc_validity_period: i64,
+    },
+    RevokeErc {
+        certificate_id: String,
+        reason: String,
+    },
+    UpdateCouncil {
+        members: Vec<Pubkey>,
+        threshold: u8,
+    },
+    SetMaxPauseDuration {
+        max_pause_duration: i64,
+    },
+    SetTradingProgram {
+        trading_program: Option<Pubkey>,
+    },
+    UpdateIssuanceRateLimit {
+        issuance_window: i64,
+        max_window_ercs: u64,
+        max_window_energy: u64,
+    },
+    SetErcValidation {
+        erc_validation_enabled: bool,
+    },
+    SetMaintenanceMode {
+        maintenance_enabled: bool,
+    },
+    SetOracleAuthority {
+        oracle_authority: Option<Pubkey>,
+    },
+    SetErcIssuanceFee {
+        erc_issuance_fee: u64,
+    },
+    WithdrawTreasury {
+        recipient: Pubkey,
+        amount: u64,
+    },
+}
+
+impl CouncilAction {
+    pub const MAX_REASON_LEN: usize = 128;
+
+    /// Largest serialized variant plus the variant tag
+    ///
+    /// `RevokeErc` with a maximum length certificate id and reason is the largest today.
+    pub const LEN: usize = 1 + max_len(&[
+        1,
+        8 + 8 + 8,
+        (4 + ErcCertificate::MAX_CERTIFICATE_ID_LEN) + (4 + Self::MAX_REASON_LEN),
+        (4 + 32 * Council::MAX_MEMBERS) + 1,
+        8,
+        1 + 32,
+        8 + 8 + 8,
+        1,
+        1,
+        1 + 32,
+        8,
+        32 + 8,
+    ]);
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::SetPauseFlags { pause_flags } => {
+                require!(pause_flags & !PoAConfig::PAUSE_ALL == 0, GovernanceError::InvalidPauseFlags);
+            }
+            Self::UpdateErcLimits { .. } => {}
+            Self::RevokeErc { certificate_id, reason } => {
+                require!(certificate_id.len() <= ErcCertificate::MAX_CERTIFICATE_ID_LEN, GovernanceError::CertificateIdTooLong);
+                require!(reason.len() <= Self::MAX_REASON_LEN, GovernanceError::ReasonTooLong);
+            }
+            Self::UpdateCouncil { members, threshold } => {
+                Council::validate_members(members, *threshold)?;
+            }
+            Self::SetMaxPauseDuration { max_pause_duration } => {
+                require!(*max_pause_duration >= 0, GovernanceError::InvalidPauseDuration);
+            }
+            Self::SetTradingProgram { .. } => {}
+            Self::UpdateIssuanceRateLimit { issuance_window, .. } => {
+                require!(*issuance_window > 0, GovernanceError::InvalidIssuanceWindow);
+            }
+            Self::SetErcValidation { .. } | Self::SetMaintenanceMode { .. } | Self::SetOracleAuthority { .. } => {}
+            Self::SetErcIssuanceFee { .. } => {}
+            Self::WithdrawTreasury { amount, .. } => {
+                require!(*amount > 0, GovernanceError::InvalidWithdrawalAmount);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[account]
+pub struct CouncilProposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: CouncilAction,
+    /// Members that approved, the proposer included
+    pub approvals: Vec<Pubkey>,
+    pub executed: bool,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl CouncilProposal {
+    /// Seconds a proposal stays open for approval and execution
+    pub const LIFETIME: i64 = 7 * 24 * 60 * 60;
+
+    pub const LEN: usize = 8 + 32 + CouncilAction::LEN + (4 + 32 * Council::MAX_MEMBERS) + 1 + 8 + 8 + 1;
+
+    pub fn require_pending(&self, now: i64) -> Result<()> {
+        require!(!self.executed, GovernanceError::ProposalAlreadyExecuted);
+        require!(now < self.expires_at, GovernanceError::ProposalExpired);
+        Ok(())
+    }
+}
+
+/// Counts registered stakeholders and seeds parameter proposal PDAs
+#[account]
+pub struct StakeholderRegistry {
+    pub stakeholder_count: u32,
+    pub proposal_count: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl StakeholderRegistry {
+    pub const LEN: usize = 4 + 8 + 8 + 1;
+}
+
+/// Campus stakeholder key allowed to vote on parameter proposals
+#[account]
+pub struct Stakeholder {
+    pub key: Pubkey,
+    /// Department, club or office the key represents
+    pub name: String,
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+impl Stakeholder {
+    pub const MAX_NAME_LEN: usize = 64;
+
+    pub const LEN: usize = 32 + (4 + Self::MAX_NAME_LEN) + 8 + 1;
+}
+
+/// Market rule changes that can be put to a stakeholder vote
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ParameterChange {
+    ErcLimits {
+        min_energy_amount: u64,
+        max_erc_amount: u64,
+        erc_validity_period: i64,
+    },
+    ErcIssuanceFee {
+        erc_issuance_fee: u64,
+    },
+}
+
+impl ParameterChange {
+    /// Largest serialized variant (`ErcLimits`) plus the variant tag
+    pub const LEN: usize = 1 + 8 + 8 + 8;
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::ErcLimits {
+                min_energy_amount,
+                max_erc_amount,
+                erc_validity_period,
+            } => validate_erc_limits(*min_energy_amount, *max_erc_amount, *erc_validity_period),
+            Self::ErcIssuanceFee { .. } => Ok(()),
+        }
+    }
+}
+
+/// Pending parameter change collecting stakeholder votes
+#[account]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub change: ParameterChange,
+    pub description: String,
+    pub votes_for: u32,
+    pub votes_against: u32,
+    /// Stakeholders registered when the proposal was created, the base for its quorum
+    pub eligible_voters: u32,
+    pub created_at: i64,
+    /// Votes are accepted until this time; the proposal can be executed from then on
+    pub voting_ends_at: i64,
+    pub executed: bool,
+    pub bump: u8,
 }
 
-impl PoAConfig {
-    pub const LEN: usize = 
-        32 +    // authority
-        64 +    // authority_name
-        128 +   // contact_info
-        1 +     // emergency_paused
-        9 +     // emergency_timestamp (Option<i64>)
-        132 +   // emergency_reason (Option<String>)
-        8 +     // created_at
-        8 +     // last_updated
-        1 +     // erc_validation_enabled
-        8 +     // max_erc_amount
-        8 +     // total_ercs_issued
-        8 +     // total_ercs_validated
-        1 +     // version
-        1 +     // delegation_enabled
-        33 +    // oracle_authority (Option<Pubkey>)
-        8 +     // min_energy_amount
-        8 +     // erc_validity_period
-        1;      // maintenance_mode
+impl Proposal {
+    /// Seconds stakeholders have to vote
+    pub const VOTING_PERIOD: i64 = 3 * 24 * 60 * 60;
+
+    pub const MAX_DESCRIPTION_LEN: usize = 256;
+
+    /// Share of `eligible_voters`, in basis points, that must vote for the result to count
+    pub const QUORUM_BPS: u64 = 5_000;
+
+    pub const LEN: usize = 8 + 32 + ParameterChange::LEN + (4 + Self::MAX_DESCRIPTION_LEN) + 4 + 4 + 4 + 8 + 8 + 1 + 1;
+
+    /// Whether enough of the eligible stakeholders voted, either way
+    pub fn has_quorum(&self) -> bool {
+        let turnout = u64::from(self.votes_for) + u64::from(self.votes_against);
+        turnout > 0 && turnout * 10_000 >= u64::from(self.eligible_voters) * Self::QUORUM_BPS
+    }
+}
+
+/// A stakeholder's vote on a proposal
+#[account]
+pub struct ProposalVote {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub approve: bool,
+    pub timestamp: i64,
+    pub bump: u8,
+}
+
+impl ProposalVote {
+    pub const LEN: usize = 32 + 32 + 1 + 8 + 1;
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct ErcCertificate {
+    /// Source of renewable energy; kept first so its variant byte sits at a fixed offset
+    pub renewable_source: RenewableSource,
     /// Unique certificate identifier
+    #[max_len(64)]
     pub certificate_id: String,
     /// Issuing authority (Engineering Department)
     pub authority: Pubkey,
     /// Amount of renewable energy (kWh)
     pub energy_amount: u64,
-    /// Source of renewable energy (solar, wind, etc.)
-    pub renewable_source: String,
     /// Additional validation data
+    #[max_len(252)]
     pub validation_data: String,
     /// When the certificate was issued
     pub issued_at: i64,
@@ -457,18 +3268,278 @@ pub struct ErcCertificate {
     pub validated_for_trading: bool,
     /// When validated for trading
     pub trading_validated_at: Option<i64>,
+    /// Oracle meter reading PDAs the certificate was issued against
+    #[max_len(8)]
+    pub source_readings: Vec<Pubkey>,
+    /// Trade the certificate currently backs, set by the trading program
+    pub trade_lock: Option<ErcTradeLock>,
+    /// Validity extensions granted by the authority, oldest first
+    #[max_len(3)]
+    pub extensions: Vec<ErcExtension>,
+    /// Registry meter account whose device key signed the issuance payload
+    pub attested_by: Option<Pubkey>,
+    /// Open dispute over the certificate, set while it is `Challenged`
+    pub challenge: Option<ErcChallenge>,
+    /// Bonded delegate that validated the certificate for trading; `None` when the authority did
+    pub validated_by: Option<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, PartialEq, Eq)]
+pub struct ErcChallenge {
+    /// Gateway dispute UUID
+    pub dispute_id: [u8; 16],
+    /// Status restored if the challenge is dismissed
+    pub previous_status: ErcStatus,
+    pub challenged_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
+pub struct ErcExtension {
+    pub previous_expires_at: i64,
+    pub new_expires_at: i64,
+    pub extended_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
+pub struct ErcTradeLock {
+    pub trade: Pubkey,
+    pub locked_at: i64,
+}
+
+/// `ErcCertificate` as stored before `migrate_erc_certificate`, with a free-text source
+#[derive(AnchorDeserialize)]
+struct LegacyErcCertificate {
+    certificate_id: String,
+    authority: Pubkey,
+    energy_amount: u64,
+    renewable_source: String,
+    validation_data: String,
+    issued_at: i64,
+    expires_at: Option<i64>,
+    status: ErcStatus,
+    validated_for_trading: bool,
+    trading_validated_at: Option<i64>,
 }
 
+const _: () = assert!(8 + ErcCertificate::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+
 impl ErcCertificate {
-    pub const LEN: usize = 64 + 32 + 8 + 64 + 256 + 8 + 9 + 1 + 1 + 9;
+    /// Maximum number of meter readings referenced by a single certificate
+    pub const MAX_SOURCE_READINGS: usize = 8;
+
+    /// Maximum number of validity extensions per certificate
+    pub const MAX_EXTENSIONS: usize = 3;
+
+    pub const MAX_CERTIFICATE_ID_LEN: usize = 64;
+    pub const MAX_VALIDATION_DATA_LEN: usize = 252;
+
+    /// Offset of the renewable source variant byte (after the account discriminator),
+    /// for `memcmp` filters selecting certificates by source
+    pub const RENEWABLE_SOURCE_OFFSET: usize = 8;
+
+    const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+    /// Whether the validity period has run out at `now`
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// Stored status, with valid certificates past their expiry reported as expired
+    pub fn effective_status(&self, now: i64) -> ErcStatus {
+        if self.status == ErcStatus::Valid && self.is_expired(now) {
+            ErcStatus::Expired
+        } else {
+            self.status.clone()
+        }
+    }
+
+    /// Whether `lock_erc_for_trade` would accept the certificate at `now`
+    pub fn is_tradable(&self, now: i64) -> bool {
+        self.effective_status(now) == ErcStatus::Valid
+            && self.validated_for_trading
+            && self.trade_lock.is_none()
+    }
+
+    pub fn status_view(&self, now: i64) -> ErcStatusView {
+        let seconds_remaining = self.expires_at.map(|expires_at| expires_at.saturating_sub(now).max(0));
+        
+        ErcStatusView {
+            certificate_id: self.certificate_id.clone(),
+            stored_status: self.status.clone(),
+            effective_status: self.effective_status(now),
+            expires_at: self.expires_at,
+            seconds_remaining,
+            days_remaining: seconds_remaining.map(|seconds| seconds / Self::SECONDS_PER_DAY),
+            validated_for_trading: self.validated_for_trading,
+            locked_for_trade: self.trade_lock.as_ref().map(|lock| lock.trade),
+            tradable: self.is_tradable(now),
+            checked_at: now,
+        }
+    }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+/// What an ERC index PDA enumerates
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ErcIndexKey {
+    Source(RenewableSource),
+    /// UTC month as `YYYYMM`
+    Month(u32),
+}
+
+/// Energy of an oracle meter reading already claimed by certificates, one PDA per reading
+#[account]
+#[derive(InitSpace)]
+pub struct ReadingClaim {
+    /// Oracle `MeterReading` PDA
+    pub reading: Pubkey,
+    /// kWh of the reading's generation backing issued certificates
+    pub claimed_energy: u64,
+    /// Certificates the reading backs
+    pub certificate_count: u32,
+    pub bump: u8,
+}
+
+impl ReadingClaim {
+    /// Claim up to `wanted` kWh of a reading that generated `energy_produced`, returning
+    /// the amount claimed
+    pub fn claim(&mut self, energy_produced: u64, wanted: u64) -> Result<u64> {
+        let available = energy_produced.saturating_sub(self.claimed_energy);
+        require!(available > 0, GovernanceError::SourceReadingFullyClaimed);
+        require!(wanted > 0, GovernanceError::UnneededSourceReading);
+        
+        let claimed = available.min(wanted);
+        self.claimed_energy += claimed;
+        self.certificate_count = self.certificate_count.saturating_add(1);
+        Ok(claimed)
+    }
+}
+
+/// Enumeration index over issued certificates, maintained by `issue_erc`
+///
+/// Clients read the count and latest IDs instead of scanning all program accounts;
+/// certificate PDAs are derived from the IDs.
+#[account]
+pub struct ErcIndex {
+    pub key: ErcIndexKey,
+    pub certificate_count: u64,
+    pub total_energy: u64,
+    /// Most recently issued certificate IDs, oldest first
+    pub latest_certificate_ids: Vec<String>,
+    pub last_issued_at: i64,
+    pub bump: u8,
+}
+
+impl ErcIndex {
+    /// Latest certificate IDs kept per index
+    pub const MAX_LATEST_IDS: usize = 10;
+
+    pub const LEN: usize = (1 + RenewableSource::INIT_SPACE) + 8 + 8 + (4 + Self::MAX_LATEST_IDS * (4 + 64)) + 8 + 1;
+
+    fn record(&mut self, certificate_id: &str, energy_amount: u64, timestamp: i64) -> Result<()> {
+        self.certificate_count = self
+            .certificate_count
+            .checked_add(1)
+            .ok_or(GovernanceError::ArithmeticOverflow)?;
+        self.total_energy = self
+            .total_energy
+            .checked_add(energy_amount)
+            .ok_or(GovernanceError::ArithmeticOverflow)?;
+
+        if self.latest_certificate_ids.len() == Self::MAX_LATEST_IDS {
+            self.latest_certificate_ids.remove(0);
+        }
+        self.latest_certificate_ids.push(certificate_id.to_string());
+        self.last_issued_at = timestamp;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
+pub enum RenewableSource {
+    Solar,
+    Wind,
+    Biomass,
+    Hydro,
+    /// Any other source, by name
+    Other(#[max_len(32)] String),
+}
+
+impl RenewableSource {
+    /// Longest name accepted for `Other`
+    pub const MAX_OTHER_NAME_LEN: usize = 32;
+
+    pub fn validate(&self) -> Result<()> {
+        if let RenewableSource::Other(name) = self {
+            require!(!name.is_empty(), GovernanceError::InvalidRenewableSource);
+            require!(name.len() <= Self::MAX_OTHER_NAME_LEN, GovernanceError::SourceNameTooLong);
+        }
+        Ok(())
+    }
+
+    /// Source from its name, as `name` returns it; unrecognised names are `Other`
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "solar" => RenewableSource::Solar,
+            "wind" => RenewableSource::Wind,
+            "biomass" => RenewableSource::Biomass,
+            "hydro" => RenewableSource::Hydro,
+            _ => RenewableSource::Other(name.to_string()),
+        }
+    }
+
+    /// Seed of the source's index PDA: variant byte, plus the name for `Other`
+    pub fn index_seed(&self) -> Vec<u8> {
+        match self {
+            RenewableSource::Solar => vec![0],
+            RenewableSource::Wind => vec![1],
+            RenewableSource::Biomass => vec![2],
+            RenewableSource::Hydro => vec![3],
+            RenewableSource::Other(name) => [&[4u8][..], name.as_bytes()].concat(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            RenewableSource::Solar => "solar",
+            RenewableSource::Wind => "wind",
+            RenewableSource::Biomass => "biomass",
+            RenewableSource::Hydro => "hydro",
+            RenewableSource::Other(name) => name,
+        }
+    }
+}
+
+/// Discriminants are stored by off-chain indexers; append new variants with the next value
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, PartialEq, Eq)]
 pub enum ErcStatus {
-    Valid,
-    Expired,
-    Revoked,
-    Pending,
+    Valid = 0,
+    Expired = 1,
+    Revoked = 2,
+    Pending = 3,
+    /// Claimed by its holder; the certificate token has been burned
+    Retired = 4,
+    /// Under dispute until `resolve_challenge`
+    Challenged = 5,
+}
+
+/// Certificate status as evaluated by `get_erc_status`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ErcStatusView {
+    pub certificate_id: String,
+    pub stored_status: ErcStatus,
+    /// `stored_status`, or `Expired` once a valid certificate passes `expires_at`
+    pub effective_status: ErcStatus,
+    pub expires_at: Option<i64>,
+    /// Seconds until expiry, 0 once expired; `None` for certificates that never expire
+    pub seconds_remaining: Option<i64>,
+    /// Whole days until expiry
+    pub days_remaining: Option<i64>,
+    pub validated_for_trading: bool,
+    /// Trade the certificate currently backs
+    pub locked_for_trade: Option<Pubkey>,
+    /// Effectively valid, validated for trading and not locked
+    pub tradable: bool,
+    pub checked_at: i64,
 }
 
 // Data structure for governance statistics
@@ -478,10 +3549,17 @@ pub struct GovernanceStats {
     pub total_ercs_validated: u64,
     pub erc_validation_enabled: bool,
     pub emergency_paused: bool,
+    pub pause_flags: u8,
     pub maintenance_mode: bool,
     pub min_energy_amount: u64,
     pub max_erc_amount: u64,
     pub erc_validity_period: i64,
+    pub erc_issuance_fee: u64,
+    pub council_mode: bool,
+    pub max_pause_duration: i64,
+    pub issuance_window: i64,
+    pub max_window_ercs: u64,
+    pub max_window_energy: u64,
     pub created_at: i64,
     pub last_updated: i64,
 }
@@ -506,12 +3584,23 @@ pub struct EmergencyPauseDeactivated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct EmergencyPauseExpired {
+    /// When the lapsed pause started
+    pub paused_at: i64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct ErcIssued {
     pub certificate_id: String,
     pub authority: Pubkey,
     pub energy_amount: u64,
-    pub renewable_source: String,
+    pub renewable_source: RenewableSource,
+    /// Issuance fee paid into the treasury (lamports)
+    pub fee: u64,
+    /// Meter account that signed the generation payload, if any
+    pub attested_by: Option<Pubkey>,
     pub timestamp: i64,
 }
 
@@ -519,6 +3608,91 @@ pub struct ErcIssued {
 pub struct ErcValidatedForTrading {
     pub certificate_id: String,
     pub authority: Pubkey,
+    /// Token-2022 mint representing the certificate
+    pub nft_mint: Pubkey,
+    pub recipient: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ErcLockedForTrade {
+    pub certificate_id: String,
+    pub trade: Pubkey,
+    pub trading_program: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ErcReleased {
+    pub certificate_id: String,
+    pub trade: Pubkey,
+    pub trading_program: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TradingProgramUpdated {
+    pub authority: Pubkey,
+    pub old_program: Option<Pubkey>,
+    pub new_program: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ErcValidityExtended {
+    pub certificate_id: String,
+    pub authority: Pubkey,
+    pub previous_expires_at: i64,
+    pub new_expires_at: i64,
+    pub extension_count: u8,
+    pub timestamp: i64,
+}
+
+/// Every certificate status transition, so indexers can rebuild the full lifecycle
+#[event]
+pub struct ErcStatusChanged {
+    pub certificate_id: String,
+    /// `None` on issuance
+    pub old_status: Option<ErcStatus>,
+    pub new_status: ErcStatus,
+    /// `issued`, `validated_for_trading`, `expired`, `retired`, `challenged`,
+    /// `challenge_dismissed` or the revocation reason
+    pub reason: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ErcRetired {
+    pub certificate_id: String,
+    pub holder: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ErcRevoked {
+    pub certificate_id: String,
+    pub authority: Pubkey,
+    pub reason: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ErcChallenged {
+    pub certificate_id: String,
+    pub dispute_id: [u8; 16],
+    pub authority: Pubkey,
+    pub reason: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ErcChallengeResolved {
+    pub certificate_id: String,
+    pub dispute_id: [u8; 16],
+    pub authority: Pubkey,
+    /// Whether the certificate was revoked rather than restored
+    pub upheld: bool,
+    pub resolution: String,
     pub timestamp: i64,
 }
 
@@ -550,10 +3724,150 @@ pub struct ErcLimitsUpdated {
 }
 
 #[event]
-pub struct AuthorityInfoUpdated {
+pub struct AuthorityProfileUpdated {
+    pub authority: Pubkey,
+    pub old_profile: AuthorityProfile,
+    pub new_profile: AuthorityProfile,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauseFlagsUpdated {
+    pub authority: Pubkey,
+    pub old_flags: u8,
+    pub new_flags: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleAuthorityUpdated {
+    pub authority: Pubkey,
+    pub old_oracle_authority: Option<Pubkey>,
+    pub new_oracle_authority: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ErcIssuanceFeeUpdated {
+    pub authority: Pubkey,
+    pub old_fee: u64,
+    pub new_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IssuanceRateLimitUpdated {
+    pub authority: Pubkey,
+    pub issuance_window: i64,
+    pub max_window_ercs: u64,
+    pub max_window_energy: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaxPauseDurationUpdated {
+    pub authority: Pubkey,
+    pub old_duration: i64,
+    pub new_duration: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub authority: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    /// Withdrawable lamports left after this withdrawal
+    pub remaining: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CouncilUpdated {
+    /// Authority enabling council mode, or the member executing a council update
+    pub actor: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CouncilActionProposed {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CouncilActionApproved {
+    pub proposal_id: u64,
+    pub member: Pubkey,
+    /// Approvals from current members, this one included
+    pub approvals: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CouncilActionExecuted {
+    pub proposal_id: u64,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeholderRegistered {
+    pub stakeholder: Pubkey,
+    pub name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeholderRemoved {
+    pub stakeholder: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub voting_ends_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeholderVoteCast {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub approve: bool,
+    pub votes_for: u32,
+    pub votes_against: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal_id: u64,
+    pub executor: Pubkey,
+    pub votes_for: u32,
+    pub votes_against: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoAConfigMigrated {
+    pub authority: Pubkey,
+    pub old_version: u8,
+    pub new_version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ErcCertificateMigrated {
+    pub certificate_id: String,
+    pub renewable_source: RenewableSource,
     pub authority: Pubkey,
-    pub old_contact: String,
-    pub new_contact: String,
     pub timestamp: i64,
 }
 
@@ -568,6 +3882,14 @@ pub enum GovernanceError {
     NotPaused,
     #[msg("System is currently paused")]
     SystemPaused,
+    #[msg("ERC issuance is paused")]
+    IssuancePaused,
+    #[msg("ERC validation is paused")]
+    ValidationPaused,
+    #[msg("Configuration updates are paused")]
+    ConfigUpdatesPaused,
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
     #[msg("System is in maintenance mode")]
     MaintenanceMode,
     #[msg("ERC validation is disabled")]
@@ -582,10 +3904,22 @@ pub enum GovernanceError {
     ExceedsMaximumEnergy,
     #[msg("Certificate ID too long")]
     CertificateIdTooLong,
+    #[msg("Validation data too long")]
+    ValidationDataTooLong,
     #[msg("Renewable source name too long")]
     SourceNameTooLong,
+    #[msg("Other renewable source needs a name")]
+    InvalidRenewableSource,
+    #[msg("Index period does not match the current month")]
+    InvalidIndexPeriod,
     #[msg("ERC certificate has expired")]
     ErcExpired,
+    #[msg("ERC certificate has not expired")]
+    ErcNotExpired,
+    #[msg("Extension must be positive and at most one validity period")]
+    InvalidExtension,
+    #[msg("ERC certificate has reached its maximum number of extensions")]
+    TooManyExtensions,
     #[msg("Invalid minimum energy amount")]
     InvalidMinimumEnergy,
     #[msg("Invalid maximum energy amount")]
@@ -594,4 +3928,312 @@ pub enum GovernanceError {
     InvalidValidityPeriod,
     #[msg("Contact information too long")]
     ContactInfoTooLong,
-}
\ No newline at end of file
+    #[msg("Authority name must be between 1 and 60 bytes")]
+    InvalidAuthorityName,
+    #[msg("Too many source meter readings")]
+    TooManySourceReadings,
+    #[msg("Source meter readings are required when oracle validation is enabled")]
+    SourceReadingsRequired,
+    #[msg("Duplicate source meter reading")]
+    DuplicateSourceReading,
+    #[msg("Invalid source meter reading")]
+    InvalidSourceReading,
+    #[msg("Source meter readings do not cover the certificate energy amount")]
+    InsufficientSourceGeneration,
+    #[msg("Withdrawal amount must be greater than zero")]
+    InvalidWithdrawalAmount,
+    #[msg("Insufficient treasury balance")]
+    InsufficientTreasuryBalance,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Account is not a PoA config")]
+    InvalidPoAConfig,
+    #[msg("PoA config is already at the current version")]
+    AlreadyMigrated,
+    #[msg("ERC has not been validated for trading")]
+    NotValidatedForTrading,
+    #[msg("Revocation reason too long")]
+    ReasonTooLong,
+    #[msg("Account is not the certificate mint")]
+    InvalidCertificateMint,
+    #[msg("Holder token account and token program are required to burn the certificate token")]
+    CertificateTokenAccountRequired,
+    #[msg("Operation requires council approval")]
+    CouncilApprovalRequired,
+    #[msg("Council must have between one and five distinct members")]
+    InvalidCouncilMembers,
+    #[msg("Council threshold must be between one and the number of members")]
+    InvalidCouncilThreshold,
+    #[msg("Signer is not a council member")]
+    NotCouncilMember,
+    #[msg("Member already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Proposal has already been executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Proposal has expired")]
+    ProposalExpired,
+    #[msg("Proposal does not have enough approvals")]
+    InsufficientApprovals,
+    #[msg("Revocation requires the targeted certificate and its mint")]
+    CertificateRequired,
+    #[msg("Stakeholder name too long")]
+    StakeholderNameTooLong,
+    #[msg("Proposal description too long")]
+    DescriptionTooLong,
+    #[msg("Voting on this proposal has closed")]
+    VotingClosed,
+    #[msg("Voting on this proposal is still open")]
+    VotingStillOpen,
+    #[msg("Proposal did not receive a majority of stakeholder votes")]
+    ProposalRejected,
+    #[msg("Pause duration cannot be negative")]
+    InvalidPauseDuration,
+    #[msg("Pause has not reached its maximum duration")]
+    PauseNotExpired,
+    #[msg("No trading program is configured")]
+    TradingProgramNotSet,
+    #[msg("Signer is not the trading program's lock authority")]
+    UnauthorizedTradingProgram,
+    #[msg("ERC certificate is locked for a trade")]
+    ErcLocked,
+    #[msg("ERC certificate is not locked")]
+    ErcNotLocked,
+    #[msg("ERC certificate is locked for a different trade")]
+    TradeMismatch,
+    #[msg("Issuance would exceed the rate limit for the current window")]
+    IssuanceRateLimitExceeded,
+    #[msg("Issuance window must be positive")]
+    InvalidIssuanceWindow,
+    #[msg("Attesting meter is not active")]
+    MeterNotActive,
+    #[msg("Meter signature check instruction is missing")]
+    MissingMeterSignature,
+    #[msg("Meter signature does not match the issuance payload")]
+    InvalidMeterSignature,
+    #[msg("ERC certificate is not under challenge")]
+    ErcNotChallenged,
+    #[msg("Challenge belongs to a different dispute")]
+    DisputeMismatch,
+    #[msg("Treasury withdrawal needs the treasury and the proposal's recipient")]
+    TreasuryAccountsRequired,
+    #[msg("Source readings must each be followed by their reading claim PDA")]
+    InvalidReadingClaim,
+    #[msg("Source meter reading's generation is already fully claimed by other certificates")]
+    SourceReadingFullyClaimed,
+    #[msg("Source meter reading is not needed to cover the certificate energy amount")]
+    UnneededSourceReading,
+    #[msg("Account is not an ERC certificate in the pre-migration layout")]
+    InvalidErcCertificate,
+    #[msg("ERC certificate is already in the current layout")]
+    CertificateAlreadyMigrated,
+    #[msg("Delegated validation is disabled")]
+    DelegationDisabled,
+    #[msg("Signer is not the staking program's validator authority for the delegate")]
+    UnauthorizedValidator,
+    #[msg("Too few stakeholders voted on the proposal")]
+    QuorumNotReached,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_council_proposal_fits_largest_action() {
+        let action = CouncilAction::RevokeErc {
+            certificate_id: "c".repeat(ErcCertificate::MAX_CERTIFICATE_ID_LEN),
+            reason: "r".repeat(CouncilAction::MAX_REASON_LEN),
+        };
+        action.validate().unwrap();
+        assert_eq!(action.try_to_vec().unwrap().len(), CouncilAction::LEN);
+
+        let proposal = CouncilProposal {
+            id: u64::MAX,
+            proposer: Pubkey::new_unique(),
+            action,
+            approvals: vec![Pubkey::new_unique(); Council::MAX_MEMBERS],
+            executed: true,
+            created_at: i64::MAX,
+            expires_at: i64::MAX,
+            bump: u8::MAX,
+        };
+        let mut data = vec![0u8; 8 + CouncilProposal::LEN];
+        proposal.try_serialize(&mut data.as_mut_slice()).unwrap();
+    }
+
+    #[test]
+    fn test_reading_energy_is_claimed_once() {
+        let mut claim = ReadingClaim {
+            reading: Pubkey::new_unique(),
+            claimed_energy: 0,
+            certificate_count: 0,
+            bump: 255,
+        };
+
+        assert_eq!(claim.claim(100, 60).unwrap(), 60);
+        assert_eq!(claim.claim(100, 60).unwrap(), 40);
+        assert_eq!((claim.claimed_energy, claim.certificate_count), (100, 2));
+        assert_eq!(
+            claim.claim(100, 1).unwrap_err(),
+            GovernanceError::SourceReadingFullyClaimed.into()
+        );
+
+        let mut unused = ReadingClaim { claimed_energy: 0, ..claim };
+        assert_eq!(unused.claim(100, 0).unwrap_err(), GovernanceError::UnneededSourceReading.into());
+    }
+
+    #[test]
+    fn test_legacy_certificate_decodes_for_migration() {
+        let authority = Pubkey::new_unique();
+        let data = (
+            "ERC-1".to_string(),
+            authority,
+            500u64,
+            "Solar".to_string(),
+            "audit 42".to_string(),
+            1_700_000_000i64,
+            Some(1_731_536_000i64),
+            ErcStatus::Valid,
+            true,
+            Some(1_700_000_100i64),
+        )
+            .try_to_vec()
+            .unwrap();
+        // Legacy certificates were allocated with this fixed length, smaller than the current layout
+        assert!(data.len() <= 452 && 8 + 452 < 8 + ErcCertificate::INIT_SPACE);
+
+        let legacy = LegacyErcCertificate::deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!((legacy.certificate_id.as_str(), legacy.authority), ("ERC-1", authority));
+        assert_eq!(legacy.energy_amount, 500);
+        assert_eq!(RenewableSource::from_name(&legacy.renewable_source), RenewableSource::Solar);
+        assert_eq!(legacy.validation_data, "audit 42");
+        assert!(legacy.status == ErcStatus::Valid && legacy.validated_for_trading);
+        assert_eq!(legacy.trading_validated_at, Some(1_700_000_100));
+        assert_eq!(
+            RenewableSource::from_name("geothermal"),
+            RenewableSource::Other("geothermal".to_string())
+        );
+    }
+
+    #[test]
+    fn test_poa_config_fits_with_every_field_at_its_limit() {
+        let config = PoAConfig {
+            authority: Pubkey::new_unique(),
+            authority_name: "a".repeat(AuthorityProfile::MAX_NAME_LEN),
+            contact_email: "e".repeat(AuthorityProfile::MAX_EMAIL_LEN),
+            pause_flags: PoAConfig::PAUSE_ALL,
+            emergency_timestamp: Some(i64::MAX),
+            emergency_reason: Some("r".repeat(128)),
+            created_at: i64::MAX,
+            last_updated: i64::MAX,
+            erc_validation_enabled: true,
+            max_erc_amount: u64::MAX,
+            total_ercs_issued: u64::MAX,
+            total_ercs_validated: u64::MAX,
+            version: PoAConfig::CURRENT_VERSION,
+            delegation_enabled: true,
+            oracle_authority: Some(Pubkey::new_unique()),
+            min_energy_amount: u64::MAX,
+            erc_validity_period: i64::MAX,
+            maintenance_mode: true,
+            erc_issuance_fee: u64::MAX,
+            council_mode: true,
+            max_pause_duration: i64::MAX,
+            trading_program: Some(Pubkey::new_unique()),
+            issuance_window: i64::MAX,
+            max_window_ercs: u64::MAX,
+            max_window_energy: u64::MAX,
+            window_started_at: i64::MAX,
+            window_ercs: u64::MAX,
+            window_energy: u64::MAX,
+            previous_window_ercs: u64::MAX,
+            previous_window_energy: u64::MAX,
+            contact_phone: "p".repeat(AuthorityProfile::MAX_PHONE_LEN),
+            website: "w".repeat(AuthorityProfile::MAX_WEBSITE_LEN),
+            office_location: "o".repeat(AuthorityProfile::MAX_OFFICE_LEN),
+        };
+        config.profile().validate().unwrap();
+        assert_eq!(config.try_to_vec().unwrap().len(), PoAConfig::INIT_SPACE);
+
+        let mut data = vec![0u8; 8 + PoAConfig::INIT_SPACE];
+        config.try_serialize(&mut data.as_mut_slice()).unwrap();
+    }
+
+    #[test]
+    fn test_erc_certificate_fits_with_every_field_at_its_limit() {
+        let renewable_source = RenewableSource::Other("s".repeat(RenewableSource::MAX_OTHER_NAME_LEN));
+        renewable_source.validate().unwrap();
+        let certificate = ErcCertificate {
+            renewable_source,
+            certificate_id: "c".repeat(ErcCertificate::MAX_CERTIFICATE_ID_LEN),
+            authority: Pubkey::new_unique(),
+            energy_amount: u64::MAX,
+            validation_data: "v".repeat(ErcCertificate::MAX_VALIDATION_DATA_LEN),
+            issued_at: i64::MAX,
+            expires_at: Some(i64::MAX),
+            status: ErcStatus::Challenged,
+            validated_for_trading: true,
+            trading_validated_at: Some(i64::MAX),
+            source_readings: vec![Pubkey::new_unique(); ErcCertificate::MAX_SOURCE_READINGS],
+            trade_lock: Some(ErcTradeLock {
+                trade: Pubkey::new_unique(),
+                locked_at: i64::MAX,
+            }),
+            extensions: vec![
+                ErcExtension {
+                    previous_expires_at: i64::MAX,
+                    new_expires_at: i64::MAX,
+                    extended_at: i64::MAX,
+                };
+                ErcCertificate::MAX_EXTENSIONS
+            ],
+            attested_by: Some(Pubkey::new_unique()),
+            challenge: Some(ErcChallenge {
+                dispute_id: [u8::MAX; 16],
+                previous_status: ErcStatus::Valid,
+                challenged_at: i64::MAX,
+            }),
+            validated_by: Some(Pubkey::new_unique()),
+        };
+        assert_eq!(certificate.try_to_vec().unwrap().len(), ErcCertificate::INIT_SPACE);
+
+        let mut data = vec![0u8; 8 + ErcCertificate::INIT_SPACE];
+        certificate.try_serialize(&mut data.as_mut_slice()).unwrap();
+    }
+
+    #[test]
+    fn test_proposal_needs_quorum_of_registered_stakeholders() {
+        let proposal = |votes_for, votes_against| Proposal {
+            id: 0,
+            proposer: Pubkey::new_unique(),
+            change: ParameterChange::ErcIssuanceFee { erc_issuance_fee: 0 },
+            description: String::new(),
+            votes_for,
+            votes_against,
+            eligible_voters: 10,
+            created_at: 0,
+            voting_ends_at: Proposal::VOTING_PERIOD,
+            executed: false,
+            bump: 255,
+        };
+
+        // A lone vote in favour does not carry a ten-stakeholder registry
+        assert!(!proposal(1, 0).has_quorum());
+        assert!(!proposal(3, 1).has_quorum());
+        assert!(proposal(3, 2).has_quorum());
+        assert!(proposal(0, 5).has_quorum());
+        assert!(!Proposal { eligible_voters: 0, ..proposal(0, 0) }.has_quorum());
+
+        let mut data = vec![0u8; 8 + Proposal::LEN];
+        Proposal {
+            description: "d".repeat(Proposal::MAX_DESCRIPTION_LEN),
+            change: ParameterChange::ErcLimits {
+                min_energy_amount: u64::MAX,
+                max_erc_amount: u64::MAX,
+                erc_validity_period: i64::MAX,
+            },
+            ..proposal(u32::MAX, u32::MAX)
+        }
+        .try_serialize(&mut data.as_mut_slice())
+        .unwrap();
+    }
+}
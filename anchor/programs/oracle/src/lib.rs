@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 
 declare_id!("ApwexmUbEZMpez5dJXKza4V7gqSqWvAA9BPbok2psxXg");
 
@@ -9,17 +10,21 @@ pub mod oracle {
     pub fn initialize(ctx: Context<Initialize>, api_gateway: Pubkey) -> Result<()> {
         let oracle_data = &mut ctx.accounts.oracle_data;
         oracle_data.authority = ctx.accounts.authority.key();
-        oracle_data.api_gateway = api_gateway;
+        oracle_data.gateways = vec![api_gateway];
         oracle_data.total_readings = 0;
         oracle_data.last_clearing = 0;
         oracle_data.active = true;
         oracle_data.created_at = Clock::get()?.unix_timestamp;
+        oracle_data.max_reading_age = OracleData::DEFAULT_MAX_READING_AGE;
         
         msg!("Oracle program initialized with API Gateway: {}", api_gateway);
         Ok(())
     }
 
-    /// Submit meter reading data from AMI (only via API Gateway)
+    /// Submit meter reading data from AMI (only via an authorized API Gateway)
+    ///
+    /// Readings older than `max_reading_age` or timestamped ahead of the cluster clock
+    /// are rejected.
     pub fn submit_meter_reading(
         ctx: Context<SubmitMeterReading>,
         meter_id: String,
@@ -28,18 +33,29 @@ pub mod oracle {
         reading_timestamp: i64,
     ) -> Result<()> {
         let oracle_data = &mut ctx.accounts.oracle_data;
+        let now = Clock::get()?.unix_timestamp;
         
         require!(oracle_data.active, ErrorCode::OracleInactive);
         
-        // Only API Gateway can submit meter readings
+        // Only authorized API Gateways can submit meter readings
         require!(
-            ctx.accounts.authority.key() == oracle_data.api_gateway,
+            oracle_data.is_gateway(&ctx.accounts.authority.key()),
             ErrorCode::UnauthorizedGateway
         );
+        check_reading(&meter_id, reading_timestamp, now, oracle_data.max_reading_age)?;
         
         oracle_data.total_readings += 1;
         oracle_data.last_reading_timestamp = reading_timestamp;
         
+        // Persist the reading so ERCs can reference it as provenance
+        let meter_reading = &mut ctx.accounts.meter_reading;
+        meter_reading.meter_id = meter_id.clone();
+        meter_reading.energy_produced = energy_produced;
+        meter_reading.energy_consumed = energy_consumed;
+        meter_reading.reading_timestamp = reading_timestamp;
+        meter_reading.submitter = ctx.accounts.authority.key();
+        meter_reading.recorded_at = now;
+        
         emit!(MeterReadingSubmitted {
             meter_id: meter_id.clone(),
             energy_produced,
@@ -55,15 +71,102 @@ pub mod oracle {
         Ok(())
     }
 
+    /// Submit several meter readings in one transaction (only via an authorized API Gateway)
+    ///
+    /// `remaining_accounts` holds the uninitialized `MeterReading` PDA of each reading, in
+    /// order. Every reading is checked as in `submit_meter_reading`, and one invalid reading
+    /// rejects the whole batch.
+    pub fn submit_meter_readings_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SubmitMeterReadingsBatch<'info>>,
+        readings: Vec<MeterReadingInput>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let oracle_data = &ctx.accounts.oracle_data;
+        
+        require!(oracle_data.active, ErrorCode::OracleInactive);
+        require!(
+            oracle_data.is_gateway(&ctx.accounts.authority.key()),
+            ErrorCode::UnauthorizedGateway
+        );
+        require!(
+            !readings.is_empty()
+                && readings.len() <= OracleData::MAX_BATCH_READINGS
+                && readings.len() == ctx.remaining_accounts.len(),
+            ErrorCode::InvalidBatchSize
+        );
+        
+        let max_reading_age = oracle_data.max_reading_age;
+        for (reading, account) in readings.iter().zip(ctx.remaining_accounts) {
+            check_reading(&reading.meter_id, reading.reading_timestamp, now, max_reading_age)?;
+            record_reading(&ctx, account, reading, now)?;
+        }
+        
+        let oracle_data = &mut ctx.accounts.oracle_data;
+        oracle_data.total_readings += readings.len() as u64;
+        if let Some(last) = readings.last() {
+            oracle_data.last_reading_timestamp = last.reading_timestamp;
+        }
+        
+        msg!("Meter reading batch submitted via API Gateway - {} readings", readings.len());
+        Ok(())
+    }
+
+    /// Publish the official grid tariff (only via an authorized API Gateway)
+    ///
+    /// `price_per_kwh` is in the trading program's order units, payment base units per
+    /// energy base unit, so market clearing can take it as a bound. A snapshot must take
+    /// effect after the one already published and not ahead of the cluster clock.
+    pub fn submit_grid_price(
+        ctx: Context<SubmitGridPrice>,
+        price_per_kwh: u64,
+        effective_at: i64,
+        snapshot_hash: [u8; 32],
+    ) -> Result<()> {
+        let oracle_data = &ctx.accounts.oracle_data;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(oracle_data.active, ErrorCode::OracleInactive);
+        require!(
+            oracle_data.is_gateway(&ctx.accounts.authority.key()),
+            ErrorCode::UnauthorizedGateway
+        );
+        require!(price_per_kwh > 0, ErrorCode::InvalidGridPrice);
+        require!(
+            effective_at <= now.saturating_add(OracleData::MAX_CLOCK_DRIFT),
+            ErrorCode::FutureGridPrice
+        );
+        
+        let grid_price = &mut ctx.accounts.grid_price;
+        require!(effective_at > grid_price.effective_at, ErrorCode::StaleGridPrice);
+        let old_price_per_kwh = grid_price.price_per_kwh;
+        grid_price.price_per_kwh = price_per_kwh;
+        grid_price.effective_at = effective_at;
+        grid_price.snapshot_hash = snapshot_hash;
+        grid_price.submitter = ctx.accounts.authority.key();
+        grid_price.updated_at = now;
+        
+        emit!(GridPriceUpdated {
+            old_price_per_kwh,
+            price_per_kwh,
+            effective_at,
+            snapshot_hash,
+            submitter: ctx.accounts.authority.key(),
+            timestamp: now,
+        });
+        
+        msg!("Grid price updated via API Gateway - {} per kWh from {}", price_per_kwh, effective_at);
+        Ok(())
+    }
+
     /// Trigger market clearing process (only via API Gateway)
     pub fn trigger_market_clearing(ctx: Context<TriggerMarketClearing>) -> Result<()> {
         let oracle_data = &mut ctx.accounts.oracle_data;
         
         require!(oracle_data.active, ErrorCode::OracleInactive);
         
-        // Only API Gateway can trigger market clearing
+        // Only authorized API Gateways can trigger market clearing
         require!(
-            ctx.accounts.authority.key() == oracle_data.api_gateway,
+            oracle_data.is_gateway(&ctx.accounts.authority.key()),
             ErrorCode::UnauthorizedGateway
         );
         
@@ -103,33 +206,282 @@ pub mod oracle {
         Ok(())
     }
 
-    /// Update API Gateway address (admin only)
-    pub fn update_api_gateway(
+    /// Authorize another API Gateway key (admin only)
+    pub fn add_api_gateway(
         ctx: Context<UpdateApiGateway>,
-        new_api_gateway: Pubkey,
+        gateway: Pubkey,
     ) -> Result<()> {
         let oracle_data = &mut ctx.accounts.oracle_data;
         
-        require!(
-            ctx.accounts.authority.key() == oracle_data.authority,
-            ErrorCode::UnauthorizedAuthority
-        );
+        require!(!oracle_data.is_gateway(&gateway), ErrorCode::GatewayAlreadyAuthorized);
+        require!(oracle_data.gateways.len() < OracleData::MAX_GATEWAYS, ErrorCode::TooManyGateways);
+        oracle_data.gateways.push(gateway);
+        
+        emit!(ApiGatewayUpdated {
+            authority: ctx.accounts.authority.key(),
+            gateway,
+            authorized: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("API Gateway authorized: {}", gateway);
+        Ok(())
+    }
+    
+    /// Revoke an API Gateway key (admin only)
+    pub fn remove_api_gateway(
+        ctx: Context<UpdateApiGateway>,
+        gateway: Pubkey,
+    ) -> Result<()> {
+        let oracle_data = &mut ctx.accounts.oracle_data;
         
-        let old_gateway = oracle_data.api_gateway;
-        oracle_data.api_gateway = new_api_gateway;
+        let index = oracle_data
+            .gateways
+            .iter()
+            .position(|authorized| *authorized == gateway)
+            .ok_or(ErrorCode::GatewayNotAuthorized)?;
+        oracle_data.gateways.remove(index);
         
         emit!(ApiGatewayUpdated {
             authority: ctx.accounts.authority.key(),
-            old_gateway,
-            new_gateway: new_api_gateway,
+            gateway,
+            authorized: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("API Gateway revoked: {}", gateway);
+        Ok(())
+    }
+    
+    /// Migrate the oracle account to the current layout (admin only)
+    ///
+    /// Accounts created before several API Gateways could be authorized hold a single
+    /// `api_gateway` and no `max_reading_age`. The account is grown to the current size, its
+    /// gateway becomes the only authorized one and the reading age takes its default.
+    pub fn migrate_oracle_data(ctx: Context<MigrateOracleData>) -> Result<()> {
+        let oracle_info = ctx.accounts.oracle_data.to_account_info();
+        
+        let new_size = 8 + OracleData::INIT_SPACE;
+        require!(oracle_info.data_len() < new_size, ErrorCode::OracleAlreadyMigrated);
+        let legacy = {
+            let data = oracle_info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == *OracleData::DISCRIMINATOR,
+                ErrorCode::InvalidOracleData
+            );
+            LegacyOracleData::deserialize(&mut &data[8..]).map_err(|_| error!(ErrorCode::InvalidOracleData))?
+        };
+        require_keys_eq!(legacy.authority, ctx.accounts.authority.key(), ErrorCode::UnauthorizedAuthority);
+        
+        grow_account(
+            &oracle_info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_size,
+        )?;
+        
+        let oracle_data = OracleData {
+            authority: legacy.authority,
+            gateways: vec![legacy.api_gateway],
+            total_readings: legacy.total_readings,
+            last_reading_timestamp: legacy.last_reading_timestamp,
+            last_clearing: legacy.last_clearing,
+            active: legacy.active,
+            created_at: legacy.created_at,
+            max_reading_age: OracleData::DEFAULT_MAX_READING_AGE,
+        };
+        oracle_data.try_serialize(&mut &mut oracle_info.try_borrow_mut_data()?[..])?;
+        
+        emit!(OracleDataMigrated {
+            authority: legacy.authority,
+            api_gateway: legacy.api_gateway,
+            max_reading_age: oracle_data.max_reading_age,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Oracle account migrated with API Gateway: {}", legacy.api_gateway);
+        Ok(())
+    }
+    
+    /// Update how old a submitted reading may be, in seconds (admin only)
+    pub fn update_max_reading_age(
+        ctx: Context<UpdateOracleStatus>,
+        max_reading_age: i64,
+    ) -> Result<()> {
+        require!(max_reading_age > 0, ErrorCode::InvalidMaxReadingAge);
+        
+        let oracle_data = &mut ctx.accounts.oracle_data;
+        let old_max_reading_age = oracle_data.max_reading_age;
+        oracle_data.max_reading_age = max_reading_age;
+        
+        emit!(MaxReadingAgeUpdated {
+            authority: ctx.accounts.authority.key(),
+            old_max_reading_age,
+            new_max_reading_age: max_reading_age,
             timestamp: Clock::get()?.unix_timestamp,
         });
         
-        msg!("API Gateway updated from {} to {}", old_gateway, new_api_gateway);
         Ok(())
     }
 }
 
+/// Reject readings whose meter ID cannot seed a PDA, or that are older than
+/// `max_reading_age` or ahead of the cluster clock
+fn check_reading(meter_id: &str, reading_timestamp: i64, now: i64, max_reading_age: i64) -> Result<()> {
+    require!(meter_id.len() <= MeterReading::MAX_METER_ID_LEN, ErrorCode::InvalidMeterReading);
+    require!(
+        reading_timestamp <= now.saturating_add(OracleData::MAX_CLOCK_DRIFT),
+        ErrorCode::FutureReading
+    );
+    require!(
+        now.saturating_sub(reading_timestamp) <= max_reading_age,
+        ErrorCode::StaleReading
+    );
+    Ok(())
+}
+
+/// Create a batched reading's `MeterReading` PDA, paid for by the gateway
+fn record_reading<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, SubmitMeterReadingsBatch<'info>>,
+    account: &AccountInfo<'info>,
+    reading: &MeterReadingInput,
+    now: i64,
+) -> Result<()> {
+    let timestamp_bytes = reading.reading_timestamp.to_le_bytes();
+    let (reading_key, bump) = Pubkey::find_program_address(
+        &[b"meter_reading", reading.meter_id.as_bytes(), &timestamp_bytes],
+        ctx.program_id,
+    );
+    require_keys_eq!(account.key(), reading_key, ErrorCode::InvalidReadingAccount);
+    
+    create_pda_account(
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.authority.to_account_info(),
+        account,
+        8 + MeterReading::INIT_SPACE,
+        ctx.program_id,
+        &[b"meter_reading", reading.meter_id.as_bytes(), &timestamp_bytes, &[bump]],
+    )?;
+    
+    let authority = ctx.accounts.authority.key();
+    let meter_reading = MeterReading {
+        meter_id: reading.meter_id.clone(),
+        energy_produced: reading.energy_produced,
+        energy_consumed: reading.energy_consumed,
+        reading_timestamp: reading.reading_timestamp,
+        submitter: authority,
+        recorded_at: now,
+    };
+    meter_reading.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    
+    emit!(MeterReadingSubmitted {
+        meter_id: reading.meter_id.clone(),
+        energy_produced: reading.energy_produced,
+        energy_consumed: reading.energy_consumed,
+        timestamp: reading.reading_timestamp,
+        submitter: authority,
+    });
+    Ok(())
+}
+
+/// Create the PDA `account` with `space` bytes, owned by `owner` and paid for by `payer`
+///
+/// Anyone can fund a PDA with predictable seeds ahead of time, which would make
+/// `create_account` fail, so a funded PDA is topped up, allocated and assigned instead, as
+/// Anchor's `init` does. `signer_seeds` are the PDA's seeds including its bump.
+pub fn create_pda_account<'info>(
+    system_program: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    if account.lamports() == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                &[signer_seeds],
+            ),
+            rent,
+            space as u64,
+            owner,
+        );
+    }
+    
+    let shortfall = rent.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: account.clone(),
+            },
+            &[signer_seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Assign {
+                account_to_assign: account.clone(),
+            },
+            &[signer_seeds],
+        ),
+        owner,
+    )
+}
+
+/// Grow a program-owned account to `new_size` bytes, topping up its rent from `payer`
+///
+/// Accounts already at least that large are left alone; added bytes are zeroed. The
+/// governance and registry programs migrate their accounts with it too.
+pub fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_size: usize,
+) -> Result<()> {
+    if account.data_len() >= new_size {
+        return Ok(());
+    }
+    
+    let rent_shortfall = Rent::get()?
+        .minimum_balance(new_size)
+        .saturating_sub(account.lamports());
+    if rent_shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent_shortfall,
+        )?;
+    }
+    account.resize(new_size)?;
+    Ok(())
+}
+
 // Account structs
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -149,16 +501,60 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(meter_id: String, energy_produced: u64, energy_consumed: u64, reading_timestamp: i64)]
 pub struct SubmitMeterReading<'info> {
+    #[account(mut, seeds = [b"oracle_data"], bump)]
+    pub oracle_data: Account<'info, OracleData>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MeterReading::INIT_SPACE,
+        seeds = [b"meter_reading", meter_id.as_bytes(), &reading_timestamp.to_le_bytes()],
+        bump
+    )]
+    pub meter_reading: Account<'info, MeterReading>,
+    
     #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitMeterReadingsBatch<'info> {
+    #[account(mut, seeds = [b"oracle_data"], bump)]
     pub oracle_data: Account<'info, OracleData>,
     
+    #[account(mut)]
     pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct TriggerMarketClearing<'info> {
+pub struct SubmitGridPrice<'info> {
+    #[account(seeds = [b"oracle_data"], bump)]
+    pub oracle_data: Account<'info, OracleData>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + GridPrice::INIT_SPACE,
+        seeds = [b"grid_price"],
+        bump
+    )]
+    pub grid_price: Account<'info, GridPrice>,
+    
     #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TriggerMarketClearing<'info> {
+    #[account(mut, seeds = [b"oracle_data"], bump)]
     pub oracle_data: Account<'info, OracleData>,
     
     pub authority: Signer<'info>,
@@ -180,17 +576,109 @@ pub struct UpdateApiGateway<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateOracleData<'info> {
+    /// CHECK: holds the single-gateway layout, which does not deserialize as the current one;
+    /// the discriminator and authority are checked in the instruction
+    #[account(
+        mut,
+        seeds = [b"oracle_data"],
+        bump,
+        owner = crate::ID
+    )]
+    pub oracle_data: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
 // Data structs
 #[account]
 #[derive(InitSpace)]
 pub struct OracleData {
     pub authority: Pubkey,
-    pub api_gateway: Pubkey,        // Only API Gateway can call oracle functions
+    #[max_len(8)]
+    pub gateways: Vec<Pubkey>,      // Only these API Gateways can call oracle functions
     pub total_readings: u64,
     pub last_reading_timestamp: i64,
     pub last_clearing: i64,
     pub active: bool,
     pub created_at: i64,
+    /// Oldest reading accepted, in seconds before submission
+    pub max_reading_age: i64,
+}
+
+impl OracleData {
+    pub const MAX_GATEWAYS: usize = 8;
+    
+    /// Covers gateway backfills after an outage
+    pub const DEFAULT_MAX_READING_AGE: i64 = 7 * 24 * 60 * 60;
+    
+    /// Tolerated skew between meter clocks and the cluster clock
+    pub const MAX_CLOCK_DRIFT: i64 = 5 * 60;
+    
+    /// Most readings in one `submit_meter_readings_batch`; the transaction size limit
+    /// usually allows fewer
+    pub const MAX_BATCH_READINGS: usize = 16;
+    
+    pub fn is_gateway(&self, key: &Pubkey) -> bool {
+        self.gateways.contains(key)
+    }
+}
+
+/// A single AMI reading, one PDA per meter and reading timestamp
+#[account]
+#[derive(InitSpace)]
+pub struct MeterReading {
+    #[max_len(32)]
+    pub meter_id: String,
+    pub energy_produced: u64,
+    pub energy_consumed: u64,
+    pub reading_timestamp: i64,
+    pub submitter: Pubkey,
+    pub recorded_at: i64,
+}
+
+impl MeterReading {
+    /// Meter IDs are used as a PDA seed, which is limited to 32 bytes
+    pub const MAX_METER_ID_LEN: usize = 32;
+}
+
+/// Official grid tariff, published by the gateway from signed tariff snapshots
+#[account]
+#[derive(InitSpace)]
+pub struct GridPrice {
+    /// Payment base units per energy base unit, as trading orders are priced
+    pub price_per_kwh: u64,
+    /// When the tariff took effect
+    pub effective_at: i64,
+    /// SHA-256 of the signed snapshot the price comes from
+    pub snapshot_hash: [u8; 32],
+    pub submitter: Pubkey,
+    pub updated_at: i64,
+}
+
+/// `OracleData` as stored before several API Gateways could be authorized
+#[derive(AnchorDeserialize)]
+struct LegacyOracleData {
+    authority: Pubkey,
+    api_gateway: Pubkey,
+    total_readings: u64,
+    last_reading_timestamp: i64,
+    last_clearing: i64,
+    active: bool,
+    created_at: i64,
+}
+
+/// One reading of a `submit_meter_readings_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MeterReadingInput {
+    pub meter_id: String,
+    pub energy_produced: u64,
+    pub energy_consumed: u64,
+    pub reading_timestamp: i64,
 }
 
 // Events
@@ -203,6 +691,16 @@ pub struct MeterReadingSubmitted {
     pub submitter: Pubkey,
 }
 
+#[event]
+pub struct GridPriceUpdated {
+    pub old_price_per_kwh: u64,
+    pub price_per_kwh: u64,
+    pub effective_at: i64,
+    pub snapshot_hash: [u8; 32],
+    pub submitter: Pubkey,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct MarketClearingTriggered {
     pub authority: Pubkey,
@@ -219,8 +717,26 @@ pub struct OracleStatusUpdated {
 #[event]
 pub struct ApiGatewayUpdated {
     pub authority: Pubkey,
-    pub old_gateway: Pubkey,
-    pub new_gateway: Pubkey,
+    pub gateway: Pubkey,
+    /// Whether the gateway was added or removed
+    pub authorized: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleDataMigrated {
+    pub authority: Pubkey,
+    /// Previously the only API Gateway, now the first authorized one
+    pub api_gateway: Pubkey,
+    pub max_reading_age: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaxReadingAgeUpdated {
+    pub authority: Pubkey,
+    pub old_max_reading_age: i64,
+    pub new_max_reading_age: i64,
     pub timestamp: i64,
 }
 
@@ -237,4 +753,30 @@ pub enum ErrorCode {
     InvalidMeterReading,
     #[msg("Market clearing in progress")]
     MarketClearingInProgress,
+    #[msg("Meter reading is older than the maximum reading age")]
+    StaleReading,
+    #[msg("Meter reading is timestamped in the future")]
+    FutureReading,
+    #[msg("Maximum reading age must be positive")]
+    InvalidMaxReadingAge,
+    #[msg("API Gateway is already authorized")]
+    GatewayAlreadyAuthorized,
+    #[msg("API Gateway is not authorized")]
+    GatewayNotAuthorized,
+    #[msg("Too many authorized API Gateways")]
+    TooManyGateways,
+    #[msg("Reading batch must be non-empty, within the batch limit and match its reading accounts")]
+    InvalidBatchSize,
+    #[msg("Reading account is not the reading's PDA")]
+    InvalidReadingAccount,
+    #[msg("Grid price must be positive")]
+    InvalidGridPrice,
+    #[msg("Grid price takes effect in the future")]
+    FutureGridPrice,
+    #[msg("Grid price does not take effect after the published one")]
+    StaleGridPrice,
+    #[msg("Oracle account does not hold oracle data")]
+    InvalidOracleData,
+    #[msg("Oracle account is already in the current layout")]
+    OracleAlreadyMigrated,
 }
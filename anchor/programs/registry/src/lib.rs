@@ -22,29 +22,33 @@ pub mod registry {
         Ok(())
     }
     
-    /// Register a new user in the P2P energy trading system
+    /// Register a prosumer or consumer wallet (registry authority only)
     pub fn register_user(
         ctx: Context<RegisterUser>,
+        wallet: Pubkey,
         user_type: UserType,
         location: String,
     ) -> Result<()> {
+        require!(location.len() <= UserAccount::MAX_LOCATION_LEN, ErrorCode::FieldTooLong);
+        
         let user_account = &mut ctx.accounts.user_account;
         let registry = &mut ctx.accounts.registry;
         
         // Set user account data
-        user_account.authority = ctx.accounts.user_authority.key();
+        user_account.authority = wallet;
         user_account.user_type = user_type;
         user_account.location = location.clone();
         user_account.status = UserStatus::Active;
         user_account.registered_at = Clock::get()?.unix_timestamp;
         user_account.meter_count = 0;
         user_account.created_at = Clock::get()?.unix_timestamp; // For backward compatibility
+        user_account.meters = Vec::new();
         
         // Update registry counters
         registry.user_count += 1;
         
         emit!(UserRegistered {
-            user: ctx.accounts.user_authority.key(),
+            user: wallet,
             user_type,
             location,
             timestamp: Clock::get()?.unix_timestamp,
@@ -53,40 +57,48 @@ pub mod registry {
         Ok(())
     }
     
-    /// Register a smart meter for an existing user
+    /// Register a smart meter and link it to its user (registry authority only)
+    ///
+    /// `meter_pubkey` is the device key the meter signs its readings with.
     pub fn register_meter(
         ctx: Context<RegisterMeter>,
         meter_id: String,
         meter_type: MeterType,
+        building: String,
+        zone: String,
+        meter_pubkey: Pubkey,
     ) -> Result<()> {
+        require!(meter_id.len() <= MeterAccount::MAX_METER_ID_LEN, ErrorCode::FieldTooLong);
+        require!(building.len() <= MeterAccount::MAX_BUILDING_LEN, ErrorCode::FieldTooLong);
+        require!(zone.len() <= MeterAccount::MAX_ZONE_LEN, ErrorCode::FieldTooLong);
+        
         let meter_account = &mut ctx.accounts.meter_account;
         let user_account = &mut ctx.accounts.user_account;
         let registry = &mut ctx.accounts.registry;
         
-        // Verify user owns this operation
-        require!(
-            ctx.accounts.user_authority.key() == user_account.authority,
-            ErrorCode::UnauthorizedUser
-        );
-        
         // Set meter account data
         meter_account.meter_id = meter_id.clone();
-        meter_account.owner = ctx.accounts.user_authority.key();
+        meter_account.owner = user_account.authority;
         meter_account.meter_type = meter_type;
         meter_account.status = MeterStatus::Active;
         meter_account.registered_at = Clock::get()?.unix_timestamp;
         meter_account.last_reading_at = 0;
         meter_account.total_generation = 0;
         meter_account.total_consumption = 0;
+        meter_account.building = building.clone();
+        meter_account.zone = zone.clone();
+        meter_account.meter_pubkey = meter_pubkey;
         
-        // Update counters
-        user_account.meter_count += 1;
+        user_account.link_meter(meter_account.key())?;
         registry.meter_count += 1;
         
         emit!(MeterRegistered {
             meter_id: meter_id.clone(),
-            owner: ctx.accounts.user_authority.key(),
+            owner: user_account.authority,
             meter_type,
+            building,
+            zone,
+            meter_pubkey,
             timestamp: Clock::get()?.unix_timestamp,
         });
         
@@ -120,6 +132,48 @@ pub mod registry {
         Ok(())
     }
     
+    /// Activate, deactivate, put into maintenance or decommission a meter (admin only)
+    ///
+    /// Decommissioning is final.
+    pub fn update_meter_status(
+        ctx: Context<UpdateMeterStatus>,
+        new_status: MeterStatus,
+    ) -> Result<()> {
+        let meter_account = &mut ctx.accounts.meter_account;
+        require!(meter_account.status != MeterStatus::Decommissioned, ErrorCode::MeterDecommissioned);
+        
+        let old_status = meter_account.status;
+        meter_account.status = new_status;
+        
+        emit!(MeterStatusUpdated {
+            meter_id: meter_account.meter_id.clone(),
+            owner: meter_account.owner,
+            old_status,
+            new_status,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Replace the device key a meter signs its readings with (registry authority only)
+    pub fn rotate_meter_key(ctx: Context<RotateMeterKey>, meter_pubkey: Pubkey) -> Result<()> {
+        let meter_account = &mut ctx.accounts.meter_account;
+        require!(meter_account.status != MeterStatus::Decommissioned, ErrorCode::MeterDecommissioned);
+        
+        let old_pubkey = meter_account.meter_pubkey;
+        meter_account.meter_pubkey = meter_pubkey;
+        
+        emit!(MeterKeyRotated {
+            meter_id: meter_account.meter_id.clone(),
+            old_pubkey,
+            new_pubkey: meter_pubkey,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
     /// Update meter reading (for oracles and authorized services)
     pub fn update_meter_reading(
         ctx: Context<UpdateMeterReading>,
@@ -157,10 +211,135 @@ pub mod registry {
         Ok(meter_account.status == MeterStatus::Active)
     }
 
-    pub fn assign_meter(ctx: Context<AssignMeter>, meter_id: String) -> Result<()> {
-        let registry = &mut ctx.accounts.registry;
-        registry.meter_count += 1;
-        msg!("Meter {} assigned", meter_id);
+    /// Grow a user account registered before meters were linked to it (registry authority only)
+    ///
+    /// The user starts with no linked meters; `migrate_meter_account` links each of its meters
+    /// back as they are migrated.
+    pub fn migrate_user_account(ctx: Context<MigrateUserAccount>, wallet: Pubkey) -> Result<()> {
+        let user_info = ctx.accounts.user_account.to_account_info();
+        
+        let new_size = 8 + UserAccount::INIT_SPACE;
+        require!(user_info.data_len() < new_size, ErrorCode::AccountAlreadyMigrated);
+        let legacy = {
+            let data = user_info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == *UserAccount::DISCRIMINATOR,
+                ErrorCode::InvalidLegacyAccount
+            );
+            LegacyUserAccount::deserialize(&mut &data[8..]).map_err(|_| error!(ErrorCode::InvalidLegacyAccount))?
+        };
+        require_keys_eq!(legacy.authority, wallet, ErrorCode::InvalidLegacyAccount);
+        
+        oracle::grow_account(
+            &user_info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_size,
+        )?;
+        
+        let user_account = UserAccount {
+            authority: legacy.authority,
+            user_type: legacy.user_type,
+            location: legacy.location,
+            status: legacy.status,
+            registered_at: legacy.registered_at,
+            meter_count: 0,
+            created_at: legacy.created_at,
+            meters: Vec::new(),
+        };
+        user_account.try_serialize(&mut &mut user_info.try_borrow_mut_data()?[..])?;
+        
+        emit!(UserAccountMigrated {
+            user: wallet,
+            legacy_meter_count: legacy.meter_count,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Grow a meter account registered before meters had a location and device key, and link
+    /// it to its (already migrated) user (registry authority only)
+    pub fn migrate_meter_account(
+        ctx: Context<MigrateMeterAccount>,
+        meter_id: String,
+        building: String,
+        zone: String,
+        meter_pubkey: Pubkey,
+    ) -> Result<()> {
+        require!(building.len() <= MeterAccount::MAX_BUILDING_LEN, ErrorCode::FieldTooLong);
+        require!(zone.len() <= MeterAccount::MAX_ZONE_LEN, ErrorCode::FieldTooLong);
+        
+        let meter_info = ctx.accounts.meter_account.to_account_info();
+        
+        let new_size = 8 + MeterAccount::INIT_SPACE;
+        require!(meter_info.data_len() < new_size, ErrorCode::AccountAlreadyMigrated);
+        let legacy = {
+            let data = meter_info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == *MeterAccount::DISCRIMINATOR,
+                ErrorCode::InvalidLegacyAccount
+            );
+            LegacyMeterAccount::deserialize(&mut &data[8..]).map_err(|_| error!(ErrorCode::InvalidLegacyAccount))?
+        };
+        require!(legacy.meter_id == meter_id, ErrorCode::InvalidLegacyAccount);
+        
+        let user_account = &mut ctx.accounts.user_account;
+        require_keys_eq!(user_account.authority, legacy.owner, ErrorCode::UnauthorizedUser);
+        if !user_account.meters.contains(&meter_info.key()) {
+            user_account.link_meter(meter_info.key())?;
+        }
+        
+        oracle::grow_account(
+            &meter_info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_size,
+        )?;
+        
+        let meter_account = MeterAccount {
+            meter_id: legacy.meter_id,
+            owner: legacy.owner,
+            meter_type: legacy.meter_type,
+            status: legacy.status,
+            registered_at: legacy.registered_at,
+            last_reading_at: legacy.last_reading_at,
+            total_generation: legacy.total_generation,
+            total_consumption: legacy.total_consumption,
+            building: building.clone(),
+            zone: zone.clone(),
+            meter_pubkey,
+        };
+        meter_account.try_serialize(&mut &mut meter_info.try_borrow_mut_data()?[..])?;
+        
+        emit!(MeterAccountMigrated {
+            meter_id,
+            owner: meter_account.owner,
+            building,
+            zone,
+            meter_pubkey,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Move a meter to another user (admin only)
+    pub fn assign_meter(ctx: Context<AssignMeter>) -> Result<()> {
+        let meter_account = &mut ctx.accounts.meter_account;
+        let meter = meter_account.key();
+        
+        ctx.accounts.current_user.unlink_meter(&meter)?;
+        ctx.accounts.new_user.link_meter(meter)?;
+        meter_account.owner = ctx.accounts.new_user.authority;
+        
+        emit!(MeterAssigned {
+            meter_id: meter_account.meter_id.clone(),
+            old_owner: ctx.accounts.current_user.authority,
+            new_owner: meter_account.owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         Ok(())
     }
 }
@@ -184,22 +363,22 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(user_type: UserType, location: String)]
+#[instruction(wallet: Pubkey)]
 pub struct RegisterUser<'info> {
-    #[account(mut)]
+    #[account(mut, has_one = authority @ ErrorCode::UnauthorizedAuthority)]
     pub registry: Account<'info, Registry>,
     
     #[account(
         init,
-        payer = user_authority,
+        payer = authority,
         space = 8 + UserAccount::INIT_SPACE,
-        seeds = [b"user", user_authority.key().as_ref()],
+        seeds = [b"user", wallet.as_ref()],
         bump
     )]
     pub user_account: Account<'info, UserAccount>,
     
     #[account(mut)]
-    pub user_authority: Signer<'info>,
+    pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
@@ -207,15 +386,15 @@ pub struct RegisterUser<'info> {
 #[derive(Accounts)]
 #[instruction(meter_id: String)]
 pub struct RegisterMeter<'info> {
-    #[account(mut)]
+    #[account(mut, has_one = authority @ ErrorCode::UnauthorizedAuthority)]
     pub registry: Account<'info, Registry>,
     
-    #[account(mut)]
+    #[account(mut, seeds = [b"user", user_account.authority.as_ref()], bump)]
     pub user_account: Account<'info, UserAccount>,
     
     #[account(
         init,
-        payer = user_authority,
+        payer = authority,
         space = 8 + MeterAccount::INIT_SPACE,
         seeds = [b"meter", meter_id.as_bytes()],
         bump
@@ -223,7 +402,7 @@ pub struct RegisterMeter<'info> {
     pub meter_account: Account<'info, MeterAccount>,
     
     #[account(mut)]
-    pub user_authority: Signer<'info>,
+    pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
@@ -239,6 +418,28 @@ pub struct UpdateUserStatus<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateMeterStatus<'info> {
+    #[account(has_one = authority @ ErrorCode::UnauthorizedAuthority)]
+    pub registry: Account<'info, Registry>,
+    
+    #[account(mut)]
+    pub meter_account: Account<'info, MeterAccount>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RotateMeterKey<'info> {
+    #[account(has_one = authority @ ErrorCode::UnauthorizedAuthority)]
+    pub registry: Account<'info, Registry>,
+    
+    #[account(mut)]
+    pub meter_account: Account<'info, MeterAccount>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateMeterReading<'info> {
     #[account(mut)]
@@ -259,9 +460,76 @@ pub struct IsValidMeter<'info> {
 
 #[derive(Accounts)]
 pub struct AssignMeter<'info> {
+    #[account(has_one = authority @ ErrorCode::UnauthorizedAuthority)]
+    pub registry: Account<'info, Registry>,
+    
     #[account(mut)]
+    pub meter_account: Account<'info, MeterAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"user", meter_account.owner.as_ref()],
+        bump
+    )]
+    pub current_user: Account<'info, UserAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"user", new_user.authority.as_ref()],
+        bump,
+        constraint = new_user.key() != current_user.key() @ ErrorCode::InvalidMeterAssignment
+    )]
+    pub new_user: Account<'info, UserAccount>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct MigrateUserAccount<'info> {
+    #[account(has_one = authority @ ErrorCode::UnauthorizedAuthority)]
     pub registry: Account<'info, Registry>,
+    
+    /// CHECK: holds the layout without linked meters, which does not deserialize as the
+    /// current one; the discriminator and wallet are checked in the instruction
+    #[account(
+        mut,
+        seeds = [b"user", wallet.as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub user_account: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(meter_id: String)]
+pub struct MigrateMeterAccount<'info> {
+    #[account(has_one = authority @ ErrorCode::UnauthorizedAuthority)]
+    pub registry: Account<'info, Registry>,
+    
+    /// CHECK: holds the layout without building, zone and device key, which does not
+    /// deserialize as the current one; the discriminator and meter id are checked in the
+    /// instruction
+    #[account(
+        mut,
+        seeds = [b"meter", meter_id.as_bytes()],
+        bump,
+        owner = crate::ID
+    )]
+    pub meter_account: UncheckedAccount<'info>,
+    
+    #[account(mut, seeds = [b"user", user_account.authority.as_ref()], bump)]
+    pub user_account: Account<'info, UserAccount>,
+    
+    #[account(mut)]
     pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 // Data structs
@@ -286,6 +554,32 @@ pub struct UserAccount {
     pub meter_count: u32,
     // Backward compatibility field
     pub created_at: i64,
+    /// Meter accounts linked to this user
+    #[max_len(8)]
+    pub meters: Vec<Pubkey>,
+}
+
+impl UserAccount {
+    pub const MAX_LOCATION_LEN: usize = 100;
+    pub const MAX_METERS: usize = 8;
+    
+    fn link_meter(&mut self, meter: Pubkey) -> Result<()> {
+        require!(self.meters.len() < Self::MAX_METERS, ErrorCode::TooManyMeters);
+        self.meters.push(meter);
+        self.meter_count = self.meters.len() as u32;
+        Ok(())
+    }
+    
+    fn unlink_meter(&mut self, meter: &Pubkey) -> Result<()> {
+        let index = self
+            .meters
+            .iter()
+            .position(|linked| linked == meter)
+            .ok_or(ErrorCode::MeterNotFound)?;
+        self.meters.remove(index);
+        self.meter_count = self.meters.len() as u32;
+        Ok(())
+    }
 }
 
 #[account]
@@ -300,6 +594,43 @@ pub struct MeterAccount {
     pub last_reading_at: i64,
     pub total_generation: u64,
     pub total_consumption: u64,
+    #[max_len(64)]
+    pub building: String,
+    #[max_len(32)]
+    pub zone: String,
+    /// Device key the meter signs its readings with
+    pub meter_pubkey: Pubkey,
+}
+
+impl MeterAccount {
+    pub const MAX_METER_ID_LEN: usize = 50;
+    pub const MAX_BUILDING_LEN: usize = 64;
+    pub const MAX_ZONE_LEN: usize = 32;
+}
+
+/// `UserAccount` as stored before meters were linked to it
+#[derive(AnchorDeserialize)]
+struct LegacyUserAccount {
+    authority: Pubkey,
+    user_type: UserType,
+    location: String,
+    status: UserStatus,
+    registered_at: i64,
+    meter_count: u32,
+    created_at: i64,
+}
+
+/// `MeterAccount` as stored before meters had a building, zone and device key
+#[derive(AnchorDeserialize)]
+struct LegacyMeterAccount {
+    meter_id: String,
+    owner: Pubkey,
+    meter_type: MeterType,
+    status: MeterStatus,
+    registered_at: i64,
+    last_reading_at: i64,
+    total_generation: u64,
+    total_consumption: u64,
 }
 
 // Enums
@@ -329,6 +660,8 @@ pub enum MeterStatus {
     Active,
     Inactive,
     Maintenance,
+    /// Permanently retired
+    Decommissioned,
 }
 
 // Events
@@ -351,6 +684,52 @@ pub struct MeterRegistered {
     pub meter_id: String,
     pub owner: Pubkey,
     pub meter_type: MeterType,
+    pub building: String,
+    pub zone: String,
+    pub meter_pubkey: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MeterStatusUpdated {
+    pub meter_id: String,
+    pub owner: Pubkey,
+    pub old_status: MeterStatus,
+    pub new_status: MeterStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MeterKeyRotated {
+    pub meter_id: String,
+    pub old_pubkey: Pubkey,
+    pub new_pubkey: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MeterAssigned {
+    pub meter_id: String,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UserAccountMigrated {
+    pub user: Pubkey,
+    /// Meters the user had before migrating, to be linked again by `migrate_meter_account`
+    pub legacy_meter_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MeterAccountMigrated {
+    pub meter_id: String,
+    pub owner: Pubkey,
+    pub building: String,
+    pub zone: String,
+    pub meter_pubkey: Pubkey,
     pub timestamp: i64,
 }
 
@@ -386,4 +765,16 @@ pub enum ErrorCode {
     UserNotFound,
     #[msg("Meter not found")]
     MeterNotFound,
+    #[msg("Field exceeds its maximum length")]
+    FieldTooLong,
+    #[msg("User has the maximum number of linked meters")]
+    TooManyMeters,
+    #[msg("Meter is already assigned to this user")]
+    InvalidMeterAssignment,
+    #[msg("Meter has been decommissioned")]
+    MeterDecommissioned,
+    #[msg("Account does not hold the registry layout it is migrated from")]
+    InvalidLegacyAccount,
+    #[msg("Account is already in the current layout")]
+    AccountAlreadyMigrated,
 }
\ No newline at end of file
@@ -0,0 +1,654 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_2022::Token2022;
+use governance::program::Governance;
+use governance::{ErcCertificate, ErcStatus, PoAConfig, VALIDATOR_AUTHORITY_SEED};
+
+declare_id!("8yJntc7AgDFWX1pzuqgZxQV1QM7ugYBCpMmWW9ZT6daM");
+
+#[program]
+pub mod staking {
+    use super::*;
+
+    /// Create the staking config and bond vault - Engineering Department only
+    ///
+    /// Bonds are held in `stake_mint`; delegates bond native SOL through the wrapped SOL mint.
+    pub fn initialize_staking(
+        ctx: Context<InitializeStaking>,
+        min_bond: u64,
+        unbonding_period: i64,
+    ) -> Result<()> {
+        require!(unbonding_period > 0, ErrorCode::InvalidUnbondingPeriod);
+
+        let staking_config = &mut ctx.accounts.staking_config;
+        staking_config.stake_mint = ctx.accounts.stake_mint.key();
+        staking_config.min_bond = min_bond;
+        staking_config.unbonding_period = unbonding_period;
+        staking_config.total_bonded = 0;
+        staking_config.total_unbonding = 0;
+        staking_config.total_slashed = 0;
+        staking_config.bump = ctx.bumps.staking_config;
+
+        emit!(StakingInitialized {
+            authority: ctx.accounts.authority.key(),
+            stake_mint: staking_config.stake_mint,
+            min_bond,
+            unbonding_period,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Staking initialized with stake mint: {}", staking_config.stake_mint);
+        Ok(())
+    }
+
+    /// Bond tokens as a delegated validator
+    ///
+    /// Only accepted while governance has delegation enabled; the resulting bond must
+    /// reach `min_bond`.
+    pub fn bond(ctx: Context<Bond>, amount: u64) -> Result<()> {
+        require!(ctx.accounts.poa_config.delegation_enabled, ErrorCode::DelegationDisabled);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let staking_config = &mut ctx.accounts.staking_config;
+        let bond = &mut ctx.accounts.bond;
+        if bond.delegate == Pubkey::default() {
+            bond.delegate = ctx.accounts.delegate.key();
+            bond.bump = ctx.bumps.bond;
+        }
+
+        let bonded_amount = bond.bonded_amount.checked_add(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+        require!(bonded_amount >= staking_config.min_bond, ErrorCode::BondBelowMinimum);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.delegate_token_account.to_account_info(),
+                    to: ctx.accounts.bond_vault.to_account_info(),
+                    authority: ctx.accounts.delegate.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        bond.bonded_amount = bonded_amount;
+        staking_config.total_bonded = staking_config.total_bonded.saturating_add(amount);
+
+        emit!(Bonded {
+            delegate: bond.delegate,
+            amount,
+            bonded_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Start unbonding part or all of a bond
+    ///
+    /// Unbonding tokens stay slashable until `unbonding_period` has passed. Unbonding more
+    /// while a previous request is pending restarts the cooldown for the whole amount.
+    pub fn unbond(ctx: Context<Unbond>, amount: u64) -> Result<()> {
+        let staking_config = &mut ctx.accounts.staking_config;
+        let bond = &mut ctx.accounts.bond;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(amount <= bond.bonded_amount, ErrorCode::InsufficientBond);
+        let remaining = bond.bonded_amount - amount;
+        require!(
+            remaining == 0 || remaining >= staking_config.min_bond,
+            ErrorCode::BondBelowMinimum
+        );
+
+        bond.bonded_amount = remaining;
+        bond.unbonding_amount = bond.unbonding_amount.checked_add(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+        bond.unbonding_available_at = now.saturating_add(staking_config.unbonding_period);
+        staking_config.total_bonded = staking_config.total_bonded.saturating_sub(amount);
+        staking_config.total_unbonding = staking_config.total_unbonding.saturating_add(amount);
+
+        emit!(UnbondRequested {
+            delegate: bond.delegate,
+            amount,
+            unbonding_amount: bond.unbonding_amount,
+            available_at: bond.unbonding_available_at,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Return unbonded tokens to the delegate once the cooldown has passed
+    pub fn withdraw_unbonded(ctx: Context<WithdrawUnbonded>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let amount = ctx.accounts.bond.unbonding_amount;
+
+        require!(amount > 0, ErrorCode::NothingUnbonding);
+        require!(now >= ctx.accounts.bond.unbonding_available_at, ErrorCode::UnbondingCooldown);
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.staking_config,
+            &ctx.accounts.bond_vault,
+            &ctx.accounts.delegate_token_account,
+            amount,
+        )?;
+
+        let staking_config = &mut ctx.accounts.staking_config;
+        let bond = &mut ctx.accounts.bond;
+        bond.unbonding_amount = 0;
+        staking_config.total_unbonding = staking_config.total_unbonding.saturating_sub(amount);
+
+        emit!(UnbondedWithdrawn {
+            delegate: bond.delegate,
+            amount,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Validate a certificate for trading as a bonded delegate
+    ///
+    /// Requires delegation to be enabled and an active bond of at least `min_bond`. Governance
+    /// is called with the delegate's validator authority PDA and records the delegate on the
+    /// certificate, which makes the bond slashable if the certificate is later revoked.
+    pub fn validate_erc(ctx: Context<ValidateErc>) -> Result<()> {
+        require!(ctx.accounts.poa_config.delegation_enabled, ErrorCode::DelegationDisabled);
+        require!(
+            ctx.accounts.bond.bonded_amount >= ctx.accounts.staking_config.min_bond,
+            ErrorCode::BondBelowMinimum
+        );
+
+        let delegate = ctx.accounts.delegate.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            VALIDATOR_AUTHORITY_SEED,
+            delegate.as_ref(),
+            &[ctx.bumps.validator_authority],
+        ]];
+        governance::cpi::validate_erc_as_delegate(CpiContext::new_with_signer(
+            ctx.accounts.governance_program.to_account_info(),
+            governance::cpi::accounts::DelegatedValidateErc {
+                poa_config: ctx.accounts.poa_config.to_account_info(),
+                erc_certificate: ctx.accounts.erc_certificate.to_account_info(),
+                nft_mint: ctx.accounts.nft_mint.to_account_info(),
+                recipient: ctx.accounts.recipient.to_account_info(),
+                recipient_token_account: ctx.accounts.recipient_token_account.to_account_info(),
+                validator_authority: ctx.accounts.validator_authority.to_account_info(),
+                delegate: ctx.accounts.delegate.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            signer_seeds,
+        ))
+    }
+
+    /// Slash a delegate for a certificate governance has revoked - Engineering Department only
+    ///
+    /// The delegate must be the one that validated the certificate through `validate_erc`.
+    /// `amount` is taken from the active bond first and then from tokens still unbonding;
+    /// each certificate can be slashed once.
+    pub fn slash(ctx: Context<Slash>, amount: u64) -> Result<()> {
+        let erc_certificate = &ctx.accounts.erc_certificate;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(erc_certificate.status == ErcStatus::Revoked, ErrorCode::CertificateNotRevoked);
+        require!(
+            erc_certificate.validated_by == Some(ctx.accounts.bond.delegate),
+            ErrorCode::NotCertificateValidator
+        );
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(amount <= ctx.accounts.bond.slashable(), ErrorCode::InsufficientBond);
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.staking_config,
+            &ctx.accounts.bond_vault,
+            &ctx.accounts.destination,
+            amount,
+        )?;
+
+        let staking_config = &mut ctx.accounts.staking_config;
+        let bond = &mut ctx.accounts.bond;
+        let from_bonded = amount.min(bond.bonded_amount);
+        let from_unbonding = amount - from_bonded;
+        bond.bonded_amount -= from_bonded;
+        bond.unbonding_amount -= from_unbonding;
+        bond.slashed_amount = bond.slashed_amount.saturating_add(amount);
+        staking_config.total_bonded = staking_config.total_bonded.saturating_sub(from_bonded);
+        staking_config.total_unbonding = staking_config.total_unbonding.saturating_sub(from_unbonding);
+        staking_config.total_slashed = staking_config.total_slashed.saturating_add(amount);
+
+        let slash_record = &mut ctx.accounts.slash_record;
+        slash_record.delegate = bond.delegate;
+        slash_record.erc_certificate = erc_certificate.key();
+        slash_record.amount = amount;
+        slash_record.slashed_by = ctx.accounts.authority.key();
+        slash_record.slashed_at = now;
+        slash_record.bump = ctx.bumps.slash_record;
+
+        emit!(DelegateSlashed {
+            delegate: bond.delegate,
+            certificate_id: erc_certificate.certificate_id.clone(),
+            amount,
+            from_unbonding,
+            destination: ctx.accounts.destination.key(),
+            authority: ctx.accounts.authority.key(),
+            timestamp: now,
+        });
+
+        msg!("Slashed delegate {} by {} for certificate {}", bond.delegate, amount, erc_certificate.certificate_id);
+        Ok(())
+    }
+
+    /// Update the minimum bond and unbonding cooldown - Engineering Department only
+    ///
+    /// A new cooldown applies to unbonding requested afterwards.
+    pub fn update_staking_params(
+        ctx: Context<UpdateStakingParams>,
+        min_bond: u64,
+        unbonding_period: i64,
+    ) -> Result<()> {
+        require!(unbonding_period > 0, ErrorCode::InvalidUnbondingPeriod);
+
+        let staking_config = &mut ctx.accounts.staking_config;
+        staking_config.min_bond = min_bond;
+        staking_config.unbonding_period = unbonding_period;
+
+        emit!(StakingParamsUpdated {
+            authority: ctx.accounts.authority.key(),
+            min_bond,
+            unbonding_period,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+/// Pay `amount` out of the bond vault, signed by the staking config PDA
+fn transfer_from_vault<'info>(
+    token_program: &Program<'info, Token>,
+    staking_config: &Account<'info, StakingConfig>,
+    bond_vault: &Account<'info, TokenAccount>,
+    destination: &Account<'info, TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: bond_vault.to_account_info(),
+                to: destination.to_account_info(),
+                authority: staking_config.to_account_info(),
+            },
+            &[&[b"staking_config", &[staking_config.bump]]],
+        ),
+        amount,
+    )
+}
+
+// Account structs
+#[derive(Accounts)]
+pub struct InitializeStaking<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        seeds::program = governance::ID,
+        has_one = authority @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + StakingConfig::INIT_SPACE,
+        seeds = [b"staking_config"],
+        bump
+    )]
+    pub staking_config: Account<'info, StakingConfig>,
+
+    pub stake_mint: Account<'info, Mint>,
+
+    /// Holds every delegate's bonded and unbonding tokens
+    #[account(
+        init,
+        payer = authority,
+        token::mint = stake_mint,
+        token::authority = staking_config,
+        seeds = [b"bond_vault"],
+        bump
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Bond<'info> {
+    #[account(seeds = [b"poa_config"], bump, seeds::program = governance::ID)]
+    pub poa_config: Account<'info, PoAConfig>,
+
+    #[account(mut, seeds = [b"staking_config"], bump = staking_config.bump)]
+    pub staking_config: Account<'info, StakingConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = delegate,
+        space = 8 + DelegateBond::INIT_SPACE,
+        seeds = [b"bond", delegate.key().as_ref()],
+        bump
+    )]
+    pub bond: Account<'info, DelegateBond>,
+
+    #[account(mut, seeds = [b"bond_vault"], bump)]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = staking_config.stake_mint,
+        token::authority = delegate
+    )]
+    pub delegate_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub delegate: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Unbond<'info> {
+    #[account(mut, seeds = [b"staking_config"], bump = staking_config.bump)]
+    pub staking_config: Account<'info, StakingConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"bond", delegate.key().as_ref()],
+        bump = bond.bump,
+        has_one = delegate @ ErrorCode::UnauthorizedDelegate
+    )]
+    pub bond: Account<'info, DelegateBond>,
+
+    pub delegate: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawUnbonded<'info> {
+    #[account(mut, seeds = [b"staking_config"], bump = staking_config.bump)]
+    pub staking_config: Account<'info, StakingConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"bond", delegate.key().as_ref()],
+        bump = bond.bump,
+        has_one = delegate @ ErrorCode::UnauthorizedDelegate
+    )]
+    pub bond: Account<'info, DelegateBond>,
+
+    #[account(mut, seeds = [b"bond_vault"], bump)]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = staking_config.stake_mint,
+        token::authority = delegate
+    )]
+    pub delegate_token_account: Account<'info, TokenAccount>,
+
+    pub delegate: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ValidateErc<'info> {
+    #[account(seeds = [b"poa_config"], bump, seeds::program = governance::ID)]
+    pub poa_config: Account<'info, PoAConfig>,
+
+    #[account(seeds = [b"staking_config"], bump = staking_config.bump)]
+    pub staking_config: Account<'info, StakingConfig>,
+
+    #[account(
+        seeds = [b"bond", delegate.key().as_ref()],
+        bump = bond.bump,
+        has_one = delegate @ ErrorCode::UnauthorizedDelegate
+    )]
+    pub bond: Account<'info, DelegateBond>,
+
+    /// CHECK: Certificate PDA, validated by the governance program
+    #[account(mut)]
+    pub erc_certificate: UncheckedAccount<'info>,
+
+    /// CHECK: Certificate mint PDA, created by the governance program
+    #[account(mut)]
+    pub nft_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Any wallet may receive the certificate token
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: Recipient's certificate token account, created by the governance program
+    #[account(mut)]
+    pub recipient_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Signs the governance call for `delegate`; holds no data
+    #[account(seeds = [VALIDATOR_AUTHORITY_SEED, delegate.key().as_ref()], bump)]
+    pub validator_authority: UncheckedAccount<'info>,
+
+    /// Pays for the certificate mint and token account
+    #[account(mut)]
+    pub delegate: Signer<'info>,
+
+    pub governance_program: Program<'info, Governance>,
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Slash<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        seeds::program = governance::ID,
+        has_one = authority @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+
+    #[account(mut, seeds = [b"staking_config"], bump = staking_config.bump)]
+    pub staking_config: Account<'info, StakingConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"bond", bond.delegate.as_ref()],
+        bump = bond.bump
+    )]
+    pub bond: Account<'info, DelegateBond>,
+
+    #[account(
+        seeds = [b"erc_certificate", erc_certificate.certificate_id.as_bytes()],
+        bump,
+        seeds::program = governance::ID
+    )]
+    pub erc_certificate: Account<'info, ErcCertificate>,
+
+    /// Records the slash so a certificate cannot be slashed twice
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SlashRecord::INIT_SPACE,
+        seeds = [b"slash", erc_certificate.key().as_ref()],
+        bump
+    )]
+    pub slash_record: Account<'info, SlashRecord>,
+
+    #[account(mut, seeds = [b"bond_vault"], bump)]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    /// Receives the slashed tokens
+    #[account(mut, token::mint = staking_config.stake_mint)]
+    pub destination: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateStakingParams<'info> {
+    #[account(
+        seeds = [b"poa_config"],
+        bump,
+        seeds::program = governance::ID,
+        has_one = authority @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub poa_config: Account<'info, PoAConfig>,
+
+    #[account(mut, seeds = [b"staking_config"], bump = staking_config.bump)]
+    pub staking_config: Account<'info, StakingConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+// Data structs
+#[account]
+#[derive(InitSpace)]
+pub struct StakingConfig {
+    /// Token delegates bond
+    pub stake_mint: Pubkey,
+    /// Smallest active bond a delegate may hold
+    pub min_bond: u64,
+    /// Seconds between unbonding and withdrawal
+    pub unbonding_period: i64,
+    pub total_bonded: u64,
+    pub total_unbonding: u64,
+    pub total_slashed: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DelegateBond {
+    pub delegate: Pubkey,
+    /// Active bond backing the delegate's validations
+    pub bonded_amount: u64,
+    /// Tokens in the unbonding cooldown; still slashable
+    pub unbonding_amount: u64,
+    /// When `unbonding_amount` can be withdrawn
+    pub unbonding_available_at: i64,
+    /// Lifetime amount slashed from this delegate
+    pub slashed_amount: u64,
+    pub bump: u8,
+}
+
+impl DelegateBond {
+    /// Tokens a slash can take: the active bond plus anything still unbonding
+    pub fn slashable(&self) -> u64 {
+        self.bonded_amount.saturating_add(self.unbonding_amount)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct SlashRecord {
+    pub delegate: Pubkey,
+    pub erc_certificate: Pubkey,
+    pub amount: u64,
+    pub slashed_by: Pubkey,
+    pub slashed_at: i64,
+    pub bump: u8,
+}
+
+// Events
+#[event]
+pub struct StakingInitialized {
+    pub authority: Pubkey,
+    pub stake_mint: Pubkey,
+    pub min_bond: u64,
+    pub unbonding_period: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Bonded {
+    pub delegate: Pubkey,
+    pub amount: u64,
+    /// Active bond after the deposit
+    pub bonded_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnbondRequested {
+    pub delegate: Pubkey,
+    pub amount: u64,
+    /// Total now in the cooldown
+    pub unbonding_amount: u64,
+    pub available_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnbondedWithdrawn {
+    pub delegate: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegateSlashed {
+    pub delegate: Pubkey,
+    pub certificate_id: String,
+    pub amount: u64,
+    /// Part of `amount` taken from tokens that were unbonding
+    pub from_unbonding: u64,
+    pub destination: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakingParamsUpdated {
+    pub authority: Pubkey,
+    pub min_bond: u64,
+    pub unbonding_period: i64,
+    pub timestamp: i64,
+}
+
+// Errors
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Unauthorized authority")]
+    UnauthorizedAuthority,
+    #[msg("Signer is not the bond's delegate")]
+    UnauthorizedDelegate,
+    #[msg("Delegation is disabled in governance")]
+    DelegationDisabled,
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Bond would fall below the minimum")]
+    BondBelowMinimum,
+    #[msg("Insufficient bonded balance")]
+    InsufficientBond,
+    #[msg("Unbonding period must be positive")]
+    InvalidUnbondingPeriod,
+    #[msg("Nothing is unbonding")]
+    NothingUnbonding,
+    #[msg("Unbonding cooldown has not ended")]
+    UnbondingCooldown,
+    #[msg("Certificate has not been revoked")]
+    CertificateNotRevoked,
+    #[msg("Delegate did not validate this certificate")]
+    NotCertificateValidator,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use governance::PoAConfig;
+use oracle::GridPrice;
 
 declare_id!("dS3zvp95PFVrNNBfZDXn78QL5MvhUqDCFR4rn8z9Jgh");
 
@@ -21,6 +24,15 @@ pub mod trading {
         market.created_at = Clock::get()?.unix_timestamp;
         market.clearing_enabled = true;
         market.market_fee_bps = 25; // 0.25% fee
+        market.clearing_authority = None;
+        market.epoch = 0;
+        market.last_cleared_at = market.created_at;
+        market.last_clearing_price = 0;
+        market.energy_mint = ctx.accounts.energy_mint.key();
+        market.payment_mint = ctx.accounts.payment_mint.key();
+        market.maintenance_share_bps = Market::DEFAULT_MAINTENANCE_SHARE_BPS;
+        market.treasury_fees_collected = 0;
+        market.maintenance_fees_collected = 0;
         
         emit!(MarketInitialized {
             authority: ctx.accounts.authority.key(),
@@ -30,43 +42,428 @@ pub mod trading {
         Ok(())
     }
     
-    /// Create a sell order for energy
-    pub fn create_sell_order(
-        _ctx: Context<CreateSellOrder>,
+    /// Place a sell order for energy
+    ///
+    /// `nonce` is chosen by the seller and keys the order PDA; it cannot be reused while
+    /// the order account exists.
+    pub fn place_sell_order(
+        ctx: Context<PlaceOrder>,
+        nonce: u64,
         energy_amount: u64,
         price_per_kwh: u64,
+        expires_at: i64,
     ) -> Result<()> {
+        let order = place_order(ctx, OrderType::Sell, nonce, energy_amount, price_per_kwh, expires_at)?;
+        
+        emit!(SellOrderCreated {
+            seller: order.owner,
+            order_id: order.key,
+            amount: energy_amount,
+            price_per_kwh,
+            timestamp: order.created_at,
+        });
+        
         msg!(
-            "Creating sell order - Amount: {} kWh, Price: {} tokens/kWh",
+            "Sell order placed - Amount: {} kWh, Price: {} tokens/kWh",
             energy_amount,
             price_per_kwh
         );
         Ok(())
     }
     
-    /// Create a buy order for energy
-    pub fn create_buy_order(
-        _ctx: Context<CreateBuyOrder>,
+    /// Place a buy order for energy at up to `max_price_per_kwh`
+    pub fn place_buy_order(
+        ctx: Context<PlaceOrder>,
+        nonce: u64,
         energy_amount: u64,
         max_price_per_kwh: u64,
+        expires_at: i64,
     ) -> Result<()> {
+        let order = place_order(ctx, OrderType::Buy, nonce, energy_amount, max_price_per_kwh, expires_at)?;
+        
+        emit!(BuyOrderCreated {
+            buyer: order.owner,
+            order_id: order.key,
+            amount: energy_amount,
+            price_per_kwh: max_price_per_kwh,
+            timestamp: order.created_at,
+        });
+        
         msg!(
-            "Creating buy order - Amount: {} kWh, Max Price: {} tokens/kWh",
+            "Buy order placed - Amount: {} kWh, Max Price: {} tokens/kWh",
             energy_amount,
             max_price_per_kwh
         );
         Ok(())
     }
     
-    /// Match a buy order with a sell order
-    pub fn match_orders(_ctx: Context<MatchOrders>) -> Result<()> {
-        msg!("Matching orders");
+    /// Clear the current epoch as a uniform-price double auction - clearing or governance authority only
+    ///
+    /// `remaining_accounts` holds `(order, trade fill)` pairs: every open order taking part
+    /// in the auction, followed by the uninitialized `TradeFill` PDA recording its fill.
+    /// Bids are matched highest price first against asks lowest price first, and every
+    /// fill executes at the midpoint of the last matched bid and ask. Given the oracle's
+    /// official grid price, asks above it are left unmatched and no fill executes above it.
+    pub fn clear_market<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClearMarket<'info>>,
+        epoch: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let market = &ctx.accounts.market;
+        
+        require!(market.clearing_enabled, ErrorCode::ClearingDisabled);
+        require!(epoch == market.epoch, ErrorCode::InvalidEpoch);
+        require!(
+            clock.unix_timestamp >= market.last_cleared_at.saturating_add(Market::EPOCH_DURATION),
+            ErrorCode::EpochNotEnded
+        );
+        
+        let ceiling = ctx.accounts.grid_price.as_ref().map(|grid_price| grid_price.price_per_kwh);
+        let mut book = load_order_book(ctx.remaining_accounts, clock.unix_timestamp)?;
+        let clearing = match_order_book(&mut book, ceiling)?;
+        let clearing_price = clearing.map_or(0, |clearing| clearing.price);
+        let market_fee_bps = ctx.accounts.market.market_fee_bps;
+        
+        for entry in book.iter_mut().filter(|entry| entry.matched > 0) {
+            record_fill(&ctx, entry, epoch, clearing_price, market_fee_bps, clock.unix_timestamp)?;
+        }
+        
+        let completed = book.iter().filter(|entry| !entry.order.is_open()).count() as u64;
+        let (volume, trades) = clearing.map_or((0, 0), |clearing| (clearing.volume, clearing.trades));
+        
+        let market = &mut ctx.accounts.market;
+        market.active_orders = market.active_orders.saturating_sub(completed);
+        market.total_volume = market
+            .total_volume
+            .checked_add(volume)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        market.total_trades = market
+            .total_trades
+            .checked_add(trades)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        market.epoch = epoch.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        market.last_cleared_at = clock.unix_timestamp;
+        if clearing.is_some() {
+            market.last_clearing_price = clearing_price;
+        }
+        
+        emit!(MarketCleared {
+            epoch,
+            clearing_price,
+            total_volume: volume,
+            trades,
+            cleared_by: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Epoch {} cleared - Volume: {} kWh, Price: {} tokens/kWh", epoch, volume, clearing_price);
         Ok(())
     }
     
-    /// Cancel an active order
-    pub fn cancel_order(_ctx: Context<CancelOrder>, order_id: u64) -> Result<()> {
-        msg!("Cancelling order: {}", order_id);
+    /// Settle matched fills of one epoch: the seller's energy goes to the buyer and the
+    /// buyer's payment to the seller, less the market fee, in one transaction
+    ///
+    /// Settles the smaller of the two fills' unsettled amounts, so a buy fill matched
+    /// against several sellers is settled pair by pair. Callable by anyone, since funds
+    /// only move to the fills' owners and the fee vaults. The fee is split between the
+    /// Engineering Department treasury and the grid-maintenance pool.
+    pub fn settle_trade(ctx: Context<SettleTrade>) -> Result<()> {
+        let accounts = ctx.accounts;
+        let quantity = accounts.buy_fill.unsettled().min(accounts.sell_fill.unsettled());
+        require!(quantity > 0, ErrorCode::FillSettled);
+        
+        let price = accounts.buy_fill.price_per_kwh;
+        let total_value = quantity.checked_mul(price).ok_or(ErrorCode::ArithmeticOverflow)?;
+        let fee_amount = market_fee(total_value, accounts.market.market_fee_bps);
+        let maintenance_fee = market_fee(fee_amount, accounts.market.maintenance_share_bps);
+        let treasury_fee = fee_amount - maintenance_fee;
+        let escrowed = quantity
+            .checked_mul(accounts.buy_fill.limit_price_per_kwh)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        
+        let market_bump = ctx.bumps.market;
+        let market = &accounts.market;
+        let token_program = &accounts.token_program;
+        transfer_from_escrow(token_program, market, market_bump, &accounts.sell_escrow, &accounts.buyer_energy_account, quantity)?;
+        transfer_from_escrow(token_program, market, market_bump, &accounts.buy_escrow, &accounts.seller_payment_account, total_value - fee_amount)?;
+        transfer_from_escrow(token_program, market, market_bump, &accounts.buy_escrow, &accounts.fee_vault, treasury_fee)?;
+        transfer_from_escrow(token_program, market, market_bump, &accounts.buy_escrow, &accounts.maintenance_vault, maintenance_fee)?;
+        transfer_from_escrow(token_program, market, market_bump, &accounts.buy_escrow, &accounts.buyer_payment_account, escrowed - total_value)?;
+        
+        accounts.buy_fill.settled_amount += quantity;
+        accounts.sell_fill.settled_amount += quantity;
+        accounts.market.treasury_fees_collected = accounts.market.treasury_fees_collected.saturating_add(treasury_fee);
+        accounts.market.maintenance_fees_collected = accounts.market.maintenance_fees_collected.saturating_add(maintenance_fee);
+        
+        emit!(TradeSettled {
+            epoch: accounts.buy_fill.epoch,
+            buy_order: accounts.buy_fill.order,
+            sell_order: accounts.sell_fill.order,
+            buyer: accounts.buy_fill.owner,
+            seller: accounts.sell_fill.owner,
+            amount: quantity,
+            price_per_kwh: price,
+            total_value,
+            fee_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        if fee_amount > 0 {
+            emit!(FeesCollected {
+                epoch: accounts.buy_fill.epoch,
+                buy_order: accounts.buy_fill.order,
+                sell_order: accounts.sell_fill.order,
+                treasury_amount: treasury_fee,
+                maintenance_amount: maintenance_fee,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+        
+        msg!("Trade settled - Amount: {} kWh, Value: {} tokens", quantity, total_value);
+        Ok(())
+    }
+    
+    /// Record an off-chain order its owner signed - clearing or governance authority only
+    ///
+    /// The owner's signature over `order_commitment_message` must be verified by an Ed25519
+    /// program instruction placed immediately before this one. `price_per_kwh` is in payment
+    /// token base units per whole energy token and `quantity` in energy token base units;
+    /// `settle_offchain_batch` only settles trades within them.
+    pub fn commit_order(
+        ctx: Context<CommitOrder>,
+        order_id: [u8; 16],
+        side: OrderType,
+        price_per_kwh: u64,
+        quantity: u64,
+    ) -> Result<()> {
+        require!(price_per_kwh > 0 && quantity > 0, ErrorCode::InvalidAmount);
+        
+        let owner = ctx.accounts.owner.key();
+        let message = order_commitment_message(&order_id, &side, price_per_kwh, quantity);
+        governance::verify_ed25519_instruction(
+            &ctx.accounts.instructions,
+            &owner,
+            &message,
+            ErrorCode::MissingOrderSignature,
+            ErrorCode::InvalidOrderSignature,
+        )?;
+        
+        let commitment = &mut ctx.accounts.commitment;
+        commitment.owner = owner;
+        commitment.order_id = order_id;
+        commitment.side = side;
+        commitment.price_per_kwh = price_per_kwh;
+        commitment.quantity = quantity;
+        commitment.filled = 0;
+        commitment.committed_at = Clock::get()?.unix_timestamp;
+        commitment.bump = ctx.bumps.commitment;
+        
+        emit!(OrderCommitted {
+            commitment: commitment.key(),
+            owner,
+            order_id,
+            price_per_kwh,
+            quantity,
+            timestamp: commitment.committed_at,
+        });
+        
+        Ok(())
+    }
+    
+    /// Settle a batch of trades matched off chain by the gateway - clearing or governance
+    /// authority only
+    ///
+    /// `remaining_accounts` holds the participants' energy and payment token accounts and
+    /// the `OrderCommitment`s of the orders traded, which `trades` refer to by index.
+    /// Participants approve the market PDA as delegate of both token accounts when they
+    /// join the off-chain market; energy moves from seller to buyer and payment, less the
+    /// market fee, from buyer to seller. Each trade counts against both orders' commitments
+    /// and must stay within their quantity and price. The batch record can only be created
+    /// once, so a batch resubmitted after a gateway restart is rejected instead of being
+    /// settled twice.
+    pub fn settle_offchain_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleOffchainBatch<'info>>,
+        batch: u64,
+        trades: Vec<OffchainTrade>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.poa_config.maintenance_mode, ErrorCode::MarketUnavailable);
+        require!(!trades.is_empty(), ErrorCode::InvalidAmount);
+        
+        let mut energy_volume = 0u64;
+        let mut payment_volume = 0u64;
+        let mut treasury_fees = 0u64;
+        let mut maintenance_fees = 0u64;
+        {
+            let market_bump = ctx.bumps.market;
+            let market = &ctx.accounts.market;
+            let token_program = &ctx.accounts.token_program;
+            let energy_decimals = ctx.accounts.energy_mint.decimals;
+            let remaining_account = |index: u8| -> Result<&'info AccountInfo<'info>> {
+                ctx.remaining_accounts
+                    .get(index as usize)
+                    .ok_or(error!(ErrorCode::InvalidSettlementAccount))
+            };
+            let token_account = |index: u8, mint: Pubkey| -> Result<Account<'info, TokenAccount>> {
+                let account = Account::<TokenAccount>::try_from(remaining_account(index)?)?;
+                require_keys_eq!(account.mint, mint, ErrorCode::InvalidSettlementAccount);
+                Ok(account)
+            };
+            // Loaded afresh for every trade, so fills of one order across the batch add up
+            let fill_commitment = |index: u8, owner: Pubkey, side: OrderType, trade: &OffchainTrade| -> Result<()> {
+                let mut commitment = Account::<OrderCommitment>::try_from(remaining_account(index)?)?;
+                require_keys_eq!(commitment.owner, owner, ErrorCode::InvalidOrderCommitment);
+                require!(commitment.side == side, ErrorCode::InvalidOrderCommitment);
+                commitment.fill(trade.energy_amount, trade.payment_amount, energy_decimals)?;
+                commitment.exit(&crate::ID)
+            };
+            
+            for trade in &trades {
+                require!(trade.energy_amount > 0 && trade.payment_amount > 0, ErrorCode::InvalidAmount);
+                let buyer_energy = token_account(trade.buyer_energy, market.energy_mint)?;
+                let buyer_payment = token_account(trade.buyer_payment, market.payment_mint)?;
+                let seller_energy = token_account(trade.seller_energy, market.energy_mint)?;
+                let seller_payment = token_account(trade.seller_payment, market.payment_mint)?;
+                require_keys_eq!(buyer_energy.owner, buyer_payment.owner, ErrorCode::InvalidSettlementAccount);
+                require_keys_eq!(seller_energy.owner, seller_payment.owner, ErrorCode::InvalidSettlementAccount);
+                fill_commitment(trade.buy_commitment, buyer_energy.owner, OrderType::Buy, trade)?;
+                fill_commitment(trade.sell_commitment, seller_energy.owner, OrderType::Sell, trade)?;
+                
+                let fee_amount = market_fee(trade.payment_amount, market.market_fee_bps);
+                let maintenance_fee = market_fee(fee_amount, market.maintenance_share_bps);
+                let treasury_fee = fee_amount - maintenance_fee;
+                transfer_from_escrow(token_program, market, market_bump, &seller_energy, &buyer_energy, trade.energy_amount)?;
+                transfer_from_escrow(token_program, market, market_bump, &buyer_payment, &seller_payment, trade.payment_amount - fee_amount)?;
+                transfer_from_escrow(token_program, market, market_bump, &buyer_payment, &ctx.accounts.fee_vault, treasury_fee)?;
+                transfer_from_escrow(token_program, market, market_bump, &buyer_payment, &ctx.accounts.maintenance_vault, maintenance_fee)?;
+                
+                energy_volume = energy_volume
+                    .checked_add(trade.energy_amount)
+                    .ok_or(ErrorCode::ArithmeticOverflow)?;
+                payment_volume = payment_volume
+                    .checked_add(trade.payment_amount)
+                    .ok_or(ErrorCode::ArithmeticOverflow)?;
+                treasury_fees += treasury_fee;
+                maintenance_fees += maintenance_fee;
+            }
+        }
+        
+        let now = Clock::get()?.unix_timestamp;
+        let market = &mut ctx.accounts.market;
+        market.total_volume = market
+            .total_volume
+            .checked_add(energy_volume)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        market.total_trades = market
+            .total_trades
+            .checked_add(trades.len() as u64)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        market.treasury_fees_collected = market.treasury_fees_collected.saturating_add(treasury_fees);
+        market.maintenance_fees_collected = market.maintenance_fees_collected.saturating_add(maintenance_fees);
+        
+        let record = &mut ctx.accounts.settlement_batch;
+        record.batch = batch;
+        record.trades = trades.len() as u32;
+        record.energy_volume = energy_volume;
+        record.payment_volume = payment_volume;
+        record.fee_amount = treasury_fees + maintenance_fees;
+        record.settled_by = ctx.accounts.authority.key();
+        record.settled_at = now;
+        record.bump = ctx.bumps.settlement_batch;
+        
+        emit!(OffchainBatchSettled {
+            batch,
+            trades: record.trades,
+            energy_volume,
+            payment_volume,
+            fee_amount: record.fee_amount,
+            settled_by: record.settled_by,
+            timestamp: now,
+        });
+        
+        msg!("Off-chain batch {} settled - {} trades, Volume: {} kWh", batch, trades.len(), energy_volume);
+        Ok(())
+    }
+    
+    /// Set the gateway key allowed to clear the market - market authority only
+    ///
+    /// The governance authority can always clear; `None` leaves clearing to it alone.
+    pub fn set_clearing_authority(
+        ctx: Context<UpdateMarketParams>,
+        clearing_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let old_clearing_authority = market.clearing_authority;
+        market.clearing_authority = clearing_authority;
+        
+        emit!(ClearingAuthorityUpdated {
+            authority: ctx.accounts.authority.key(),
+            old_clearing_authority,
+            new_clearing_authority: clearing_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Cancel an open order - order owner only
+    ///
+    /// The escrow backing the unfilled amount is refunded.
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        let accounts = ctx.accounts;
+        let clock = Clock::get()?;
+        
+        require!(accounts.order.is_open(), ErrorCode::OrderNotCancellable);
+        let refund = refund_unfilled(
+            &accounts.token_program,
+            &accounts.market,
+            ctx.bumps.market,
+            &accounts.order,
+            &accounts.escrow,
+            &accounts.owner_token_account,
+        )?;
+        
+        let order = &mut accounts.order;
+        close_order(&mut accounts.market, order, OrderStatus::Cancelled);
+        
+        emit!(OrderCancelled {
+            order_id: order.key(),
+            user: order.owner,
+            refund,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Order cancelled: {}", order.key());
+        Ok(())
+    }
+    
+    /// Mark an open order past its expiry as expired and refund its escrow - callable by anyone
+    pub fn expire_order(ctx: Context<ExpireOrder>) -> Result<()> {
+        let accounts = ctx.accounts;
+        let clock = Clock::get()?;
+        
+        require!(accounts.order.is_open(), ErrorCode::OrderNotCancellable);
+        require!(accounts.order.is_expired(clock.unix_timestamp), ErrorCode::OrderNotExpired);
+        let refund = refund_unfilled(
+            &accounts.token_program,
+            &accounts.market,
+            ctx.bumps.market,
+            &accounts.order,
+            &accounts.escrow,
+            &accounts.owner_token_account,
+        )?;
+        
+        let order = &mut accounts.order;
+        close_order(&mut accounts.market, order, OrderStatus::Expired);
+        
+        emit!(OrderExpired {
+            order_id: order.key(),
+            user: order.owner,
+            expires_at: order.expires_at,
+            refund,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Order expired: {}", order.key());
         Ok(())
     }
     
@@ -82,6 +479,7 @@ pub mod trading {
             ctx.accounts.authority.key() == market.authority,
             ErrorCode::UnauthorizedAuthority
         );
+        require!(market_fee_bps <= Market::MAX_FEE_BPS, ErrorCode::InvalidFeeRate);
         
         market.market_fee_bps = market_fee_bps;
         market.clearing_enabled = clearing_enabled;
@@ -95,6 +493,515 @@ pub mod trading {
         
         Ok(())
     }
+    
+    /// Set the share of each market fee, in basis points, paid to the grid-maintenance
+    /// pool; the rest goes to the Engineering Department treasury
+    pub fn update_fee_split(ctx: Context<UpdateMarketParams>, maintenance_share_bps: u16) -> Result<()> {
+        require!(maintenance_share_bps <= BPS_DENOMINATOR, ErrorCode::InvalidFeeRate);
+        
+        let market = &mut ctx.accounts.market;
+        let old_maintenance_share_bps = market.maintenance_share_bps;
+        market.maintenance_share_bps = maintenance_share_bps;
+        
+        emit!(FeeSplitUpdated {
+            authority: ctx.accounts.authority.key(),
+            old_maintenance_share_bps,
+            new_maintenance_share_bps: maintenance_share_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Withdraw collected fees from the treasury or grid-maintenance vault
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, pool: FeePool, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(ctx.accounts.vault.amount >= amount, ErrorCode::InsufficientFeeBalance);
+        
+        transfer_from_escrow(
+            &ctx.accounts.token_program,
+            &ctx.accounts.market,
+            ctx.bumps.market,
+            &ctx.accounts.vault,
+            &ctx.accounts.destination,
+            amount,
+        )?;
+        
+        emit!(FeesWithdrawn {
+            authority: ctx.accounts.authority.key(),
+            pool,
+            destination: ctx.accounts.destination.key(),
+            amount,
+            remaining: ctx.accounts.vault.amount - amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Withdrew {} from the {:?} fee vault", amount, pool);
+        Ok(())
+    }
+    
+    /// Open an off-chain balance channel between a participant and the market authority
+    ///
+    /// Per-interval balance updates are signed by both parties off chain; only periodic
+    /// checkpoints and disputes reach the chain. Both parties approve the market PDA as
+    /// delegate of their energy token accounts, so that `close_channel` can settle the
+    /// final balance between them.
+    pub fn open_channel(ctx: Context<OpenChannel>) -> Result<()> {
+        let channel = &mut ctx.accounts.channel;
+        let clock = Clock::get()?;
+        
+        channel.participant = ctx.accounts.participant.key();
+        channel.operator = ctx.accounts.market.authority;
+        channel.nonce = 0;
+        channel.balance = 0;
+        channel.status = ChannelStatus::Open;
+        channel.checkpointed_at = clock.unix_timestamp;
+        channel.dispute_ends_at = 0;
+        channel.created_at = clock.unix_timestamp;
+        channel.bump = ctx.bumps.channel;
+        
+        emit!(ChannelOpened {
+            channel: channel.key(),
+            participant: channel.participant,
+            operator: channel.operator,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Record a newer channel state signed by both parties
+    ///
+    /// Submitted by either party; the other party's signature over
+    /// `channel_state_message` must be verified by an Ed25519 program instruction placed
+    /// immediately before this one. Also accepted while a dispute is open.
+    pub fn checkpoint_channel(ctx: Context<SubmitChannelState>, nonce: u64, balance: i64) -> Result<()> {
+        let clock = Clock::get()?;
+        let channel = &ctx.accounts.channel;
+        
+        require!(channel.status != ChannelStatus::Closed, ErrorCode::ChannelClosed);
+        require!(nonce > channel.nonce, ErrorCode::StaleChannelState);
+        verify_channel_state(ctx.accounts, nonce, balance)?;
+        
+        let channel = &mut ctx.accounts.channel;
+        channel.nonce = nonce;
+        channel.balance = balance;
+        channel.checkpointed_at = clock.unix_timestamp;
+        
+        emit!(ChannelCheckpointed {
+            channel: channel.key(),
+            submitter: ctx.accounts.submitter.key(),
+            nonce,
+            balance,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Start closing a channel from the latest state the submitter holds
+    ///
+    /// The counterparty has `BalanceChannel::DISPUTE_WINDOW` seconds to checkpoint a newer
+    /// signed state before anyone can close the channel at its recorded balance.
+    pub fn dispute_channel(ctx: Context<SubmitChannelState>, nonce: u64, balance: i64) -> Result<()> {
+        let clock = Clock::get()?;
+        let channel = &ctx.accounts.channel;
+        
+        require!(channel.status == ChannelStatus::Open, ErrorCode::ChannelAlreadyDisputed);
+        require!(nonce >= channel.nonce, ErrorCode::StaleChannelState);
+        verify_channel_state(ctx.accounts, nonce, balance)?;
+        
+        let channel = &mut ctx.accounts.channel;
+        channel.nonce = nonce;
+        channel.balance = balance;
+        channel.checkpointed_at = clock.unix_timestamp;
+        channel.status = ChannelStatus::Disputed;
+        channel.dispute_ends_at = clock.unix_timestamp + BalanceChannel::DISPUTE_WINDOW;
+        
+        emit!(ChannelDisputed {
+            channel: channel.key(),
+            submitter: ctx.accounts.submitter.key(),
+            nonce,
+            balance,
+            dispute_ends_at: channel.dispute_ends_at,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Close a disputed channel once its dispute window has passed and settle its balance
+    /// - permissionless
+    ///
+    /// The recorded balance moves in energy tokens from the operator to the participant, or
+    /// the other way when it is negative, with the market PDA as delegate of the paying account.
+    pub fn close_channel(ctx: Context<CloseChannel>) -> Result<()> {
+        let clock = Clock::get()?;
+        let settled = ctx.accounts.channel.close_at(clock.unix_timestamp)?;
+        
+        let accounts = &ctx.accounts;
+        let (from, to) = if settled > 0 {
+            (&accounts.operator_energy, &accounts.participant_energy)
+        } else {
+            (&accounts.participant_energy, &accounts.operator_energy)
+        };
+        transfer_from_escrow(
+            &accounts.token_program,
+            &accounts.market,
+            ctx.bumps.market,
+            from,
+            to,
+            settled.unsigned_abs(),
+        )?;
+        
+        emit!(ChannelClosed {
+            channel: accounts.channel.key(),
+            nonce: accounts.channel.nonce,
+            balance: settled,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+}
+
+/// Identity of a newly placed order, for its creation event
+struct PlacedOrder {
+    key: Pubkey,
+    owner: Pubkey,
+    created_at: i64,
+}
+
+/// Validate an order against the governance limits and write it
+///
+/// Amounts must lie within the governance ERC limits, since every kWh sold has to be
+/// certifiable; orders are refused while governance is in maintenance mode.
+fn place_order(
+    ctx: Context<PlaceOrder>,
+    order_type: OrderType,
+    nonce: u64,
+    energy_amount: u64,
+    price_per_kwh: u64,
+    expires_at: i64,
+) -> Result<PlacedOrder> {
+    let poa_config = &ctx.accounts.poa_config;
+    let clock = Clock::get()?;
+    let escrow_mint = match order_type {
+        OrderType::Sell => ctx.accounts.market.energy_mint,
+        OrderType::Buy => ctx.accounts.market.payment_mint,
+    };
+    
+    require_keys_eq!(ctx.accounts.escrow_mint.key(), escrow_mint, ErrorCode::InvalidEscrowMint);
+    require!(!poa_config.maintenance_mode, ErrorCode::MarketUnavailable);
+    require!(
+        energy_amount >= poa_config.min_energy_amount && energy_amount <= poa_config.max_erc_amount,
+        ErrorCode::InvalidAmount
+    );
+    require!(price_per_kwh > 0, ErrorCode::InvalidPrice);
+    require!(expires_at > clock.unix_timestamp, ErrorCode::InvalidExpiry);
+    
+    let market = &mut ctx.accounts.market;
+    market.active_orders = market
+        .active_orders
+        .checked_add(1)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    
+    let order = &mut ctx.accounts.order;
+    order.owner = ctx.accounts.owner.key();
+    order.nonce = nonce;
+    order.order_type = order_type;
+    order.amount = energy_amount;
+    order.filled_amount = 0;
+    order.price_per_kwh = price_per_kwh;
+    order.status = OrderStatus::Active;
+    order.created_at = clock.unix_timestamp;
+    order.expires_at = expires_at;
+    order.bump = ctx.bumps.order;
+    
+    let deposit = order.escrow_amount(energy_amount)?;
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                to: ctx.accounts.escrow.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        deposit,
+    )?;
+    
+    Ok(PlacedOrder {
+        key: order.key(),
+        owner: order.owner,
+        created_at: order.created_at,
+    })
+}
+
+/// Move an open order to a final status and drop it from the market's active count
+fn close_order(market: &mut Market, order: &mut Order, status: OrderStatus) {
+    market.active_orders = market.active_orders.saturating_sub(1);
+    order.status = status;
+}
+
+const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Basis-point share of `value`, rounded down
+fn market_fee(value: u64, bps: u16) -> u64 {
+    (value as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+/// Pay `amount` out of a token account the market PDA owns or is delegate of, signed by
+/// the market PDA
+fn transfer_from_escrow<'info>(
+    token_program: &Program<'info, Token>,
+    market: &Account<'info, Market>,
+    market_bump: u8,
+    escrow: &Account<'info, TokenAccount>,
+    destination: &Account<'info, TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: escrow.to_account_info(),
+                to: destination.to_account_info(),
+                authority: market.to_account_info(),
+            },
+            &[&[b"market", &[market_bump]]],
+        ),
+        amount,
+    )
+}
+
+/// Return the escrow backing an order's unfilled amount to its owner
+///
+/// Filled but unsettled amounts stay in escrow for `settle_trade`.
+fn refund_unfilled<'info>(
+    token_program: &Program<'info, Token>,
+    market: &Account<'info, Market>,
+    market_bump: u8,
+    order: &Order,
+    escrow: &Account<'info, TokenAccount>,
+    owner_token_account: &Account<'info, TokenAccount>,
+) -> Result<u64> {
+    let refund = order.unfilled_escrow()?;
+    transfer_from_escrow(token_program, market, market_bump, escrow, owner_token_account, refund)?;
+    Ok(refund)
+}
+
+/// Order taking part in a market clearing, with the quantity matched this epoch
+struct BookEntry<'info> {
+    order: Account<'info, Order>,
+    fill: &'info AccountInfo<'info>,
+    matched: u64,
+}
+
+impl BookEntry<'_> {
+    fn remaining(&self) -> u64 {
+        self.order.amount - self.order.filled_amount - self.matched
+    }
+}
+
+/// Outcome of matching the order book
+#[derive(Clone, Copy)]
+struct Clearing {
+    price: u64,
+    volume: u64,
+    trades: u64,
+}
+
+/// Read `(order, trade fill)` pairs, skipping orders that are closed or past expiry
+fn load_order_book<'info>(accounts: &'info [AccountInfo<'info>], now: i64) -> Result<Vec<BookEntry<'info>>> {
+    require!(accounts.chunks_exact(2).remainder().is_empty(), ErrorCode::InvalidOrderBook);
+    require!(accounts.len() / 2 <= Market::MAX_CLEARING_ORDERS, ErrorCode::TooManyOrders);
+    
+    let mut book: Vec<BookEntry> = Vec::with_capacity(accounts.len() / 2);
+    for pair in accounts.chunks_exact(2) {
+        let order = Account::<Order>::try_from(&pair[0])?;
+        require!(
+            book.iter().all(|entry| entry.order.key() != order.key()),
+            ErrorCode::InvalidOrderBook
+        );
+        if order.is_open() && !order.is_expired(now) {
+            book.push(BookEntry { order, fill: &pair[1], matched: 0 });
+        }
+    }
+    Ok(book)
+}
+
+/// Match bids against asks in price-time priority, filling `matched` on each entry, with
+/// no ask above `ceiling` matched
+///
+/// Returns `None` when no bid reaches any ask.
+fn match_order_book(book: &mut [BookEntry], ceiling: Option<u64>) -> Result<Option<Clearing>> {
+    let mut bids: Vec<usize> = (0..book.len()).filter(|&i| book[i].order.order_type == OrderType::Buy).collect();
+    let mut asks: Vec<usize> = (0..book.len()).filter(|&i| book[i].order.order_type == OrderType::Sell).collect();
+    bids.sort_by_key(|&i| (std::cmp::Reverse(book[i].order.price_per_kwh), book[i].order.created_at));
+    asks.sort_by_key(|&i| (book[i].order.price_per_kwh, book[i].order.created_at));
+    
+    let (mut b, mut a) = (0, 0);
+    let mut volume = 0u64;
+    let mut trades = 0u64;
+    let mut marginal = None;
+    while b < bids.len() && a < asks.len() {
+        let (bid, ask) = (bids[b], asks[a]);
+        if book[bid].order.price_per_kwh < book[ask].order.price_per_kwh
+            || ceiling.is_some_and(|ceiling| book[ask].order.price_per_kwh > ceiling)
+        {
+            break;
+        }
+        
+        let quantity = book[bid].remaining().min(book[ask].remaining());
+        book[bid].matched += quantity;
+        book[ask].matched += quantity;
+        volume = volume.checked_add(quantity).ok_or(ErrorCode::ArithmeticOverflow)?;
+        trades += 1;
+        marginal = Some((book[bid].order.price_per_kwh, book[ask].order.price_per_kwh));
+        
+        if book[bid].remaining() == 0 {
+            b += 1;
+        }
+        if book[ask].remaining() == 0 {
+            a += 1;
+        }
+    }
+    
+    // Every matched bid is at or above the marginal bid and every matched ask at or below
+    // the marginal ask, so the midpoint is acceptable to all of them. The marginal ask is
+    // within the ceiling, so capping the midpoint keeps it so.
+    Ok(marginal.map(|(bid_price, ask_price)| {
+        let midpoint = ask_price + (bid_price - ask_price) / 2;
+        Clearing {
+            price: ceiling.map_or(midpoint, |ceiling| midpoint.min(ceiling)),
+            volume,
+            trades,
+        }
+    }))
+}
+
+/// Apply an entry's fill to its order and create the order's `TradeFill` for the epoch
+fn record_fill<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, ClearMarket<'info>>,
+    entry: &mut BookEntry<'info>,
+    epoch: u64,
+    clearing_price: u64,
+    market_fee_bps: u16,
+    now: i64,
+) -> Result<()> {
+    let order_key = entry.order.key();
+    let epoch_bytes = epoch.to_le_bytes();
+    let (fill_key, bump) = Pubkey::find_program_address(
+        &[b"trade_fill", &epoch_bytes, order_key.as_ref()],
+        ctx.program_id,
+    );
+    require_keys_eq!(entry.fill.key(), fill_key, ErrorCode::InvalidTradeFill);
+    
+    oracle::create_pda_account(
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.authority.to_account_info(),
+        &entry.fill,
+        8 + TradeFill::INIT_SPACE,
+        ctx.program_id,
+        &[b"trade_fill", &epoch_bytes, order_key.as_ref(), &[bump]],
+    )?;
+    
+    let total_value = entry
+        .matched
+        .checked_mul(clearing_price)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let fill = TradeFill {
+        epoch,
+        order: order_key,
+        owner: entry.order.owner,
+        order_type: entry.order.order_type.clone(),
+        amount: entry.matched,
+        price_per_kwh: clearing_price,
+        limit_price_per_kwh: entry.order.price_per_kwh,
+        total_value,
+        fee_amount: market_fee(total_value, market_fee_bps),
+        executed_at: now,
+        settled_amount: 0,
+        bump,
+    };
+    fill.try_serialize(&mut &mut entry.fill.try_borrow_mut_data()?[..])?;
+    
+    let order = &mut entry.order;
+    order.filled_amount += entry.matched;
+    order.status = if order.filled_amount == order.amount {
+        OrderStatus::Completed
+    } else {
+        OrderStatus::PartiallyFilled
+    };
+    order.exit(ctx.program_id)?;
+    
+    emit!(OrderFilled {
+        order_id: order_key,
+        user: order.owner,
+        epoch,
+        amount: entry.matched,
+        price_per_kwh: clearing_price,
+        filled_amount: order.filled_amount,
+        status: order.status.clone(),
+        timestamp: now,
+    });
+    Ok(())
+}
+
+/// Domain separator prefixed to every signed channel state
+pub const CHANNEL_STATE_DOMAIN: &[u8] = b"gridtokenx:channel:v1";
+
+/// Bytes both parties sign for a channel state
+pub fn channel_state_message(channel: &Pubkey, nonce: u64, balance: i64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(CHANNEL_STATE_DOMAIN.len() + 32 + 8 + 8);
+    message.extend_from_slice(CHANNEL_STATE_DOMAIN);
+    message.extend_from_slice(channel.as_ref());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&balance.to_le_bytes());
+    message
+}
+
+/// Domain separator prefixed to every signed order commitment
+pub const ORDER_COMMITMENT_DOMAIN: &[u8] = b"gridtokenx:order:v1";
+
+/// Bytes a participant signs to commit an off-chain order
+pub fn order_commitment_message(order_id: &[u8; 16], side: &OrderType, price_per_kwh: u64, quantity: u64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(ORDER_COMMITMENT_DOMAIN.len() + 16 + 1 + 8 + 8);
+    message.extend_from_slice(ORDER_COMMITMENT_DOMAIN);
+    message.extend_from_slice(order_id);
+    message.push(match side {
+        OrderType::Sell => 0,
+        OrderType::Buy => 1,
+    });
+    message.extend_from_slice(&price_per_kwh.to_le_bytes());
+    message.extend_from_slice(&quantity.to_le_bytes());
+    message
+}
+
+/// Check that the counterparty of `submitter` signed the state
+fn verify_channel_state(accounts: &SubmitChannelState, nonce: u64, balance: i64) -> Result<()> {
+    let channel = &accounts.channel;
+    let submitter = accounts.submitter.key();
+    let counterparty = if submitter == channel.participant {
+        channel.operator
+    } else if submitter == channel.operator {
+        channel.participant
+    } else {
+        return err!(ErrorCode::UnauthorizedChannelParty);
+    };
+    
+    let message = channel_state_message(&channel.key(), nonce, balance);
+    governance::verify_ed25519_instruction(
+        &accounts.instructions,
+        &counterparty,
+        &message,
+        ErrorCode::MissingStateSignature,
+        ErrorCode::InvalidStateSignature,
+    )
 }
 
 // Account structs
@@ -106,64 +1013,405 @@ pub struct Initialize<'info> {
 #[derive(Accounts)]
 pub struct InitializeMarket<'info> {
     #[account(
-        init,
-        payer = authority,
-        space = 8 + Market::INIT_SPACE,
-        seeds = [b"market"],
+        init,
+        payer = authority,
+        space = 8 + Market::INIT_SPACE,
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+    
+    pub energy_mint: Account<'info, Mint>,
+    
+    pub payment_mint: Account<'info, Mint>,
+    
+    /// Collects the Engineering Department treasury's share of market fees
+    #[account(
+        init,
+        payer = authority,
+        token::mint = payment_mint,
+        token::authority = market,
+        seeds = [FeePool::Treasury.vault_seed()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    
+    /// Collects the grid-maintenance pool's share of market fees
+    #[account(
+        init,
+        payer = authority,
+        token::mint = payment_mint,
+        token::authority = market,
+        seeds = [FeePool::Maintenance.vault_seed()],
+        bump
+    )]
+    pub maintenance_vault: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct PlaceOrder<'info> {
+    #[account(mut, seeds = [b"market"], bump)]
+    pub market: Account<'info, Market>,
+    
+    #[account(seeds = [b"poa_config"], bump, seeds::program = governance::ID)]
+    pub poa_config: Account<'info, PoAConfig>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Order::INIT_SPACE,
+        seeds = [b"order", owner.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub order: Account<'info, Order>,
+    
+    /// Energy mint for sell orders, payment mint for buy orders
+    pub escrow_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = owner,
+        token::mint = escrow_mint,
+        token::authority = market,
+        seeds = [b"escrow", order.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_mint,
+        token::authority = owner
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClearMarket<'info> {
+    #[account(mut, seeds = [b"market"], bump)]
+    pub market: Account<'info, Market>,
+    
+    #[account(seeds = [b"poa_config"], bump, seeds::program = governance::ID)]
+    pub poa_config: Account<'info, PoAConfig>,
+    
+    /// Official grid price published by the oracle, bounding the clearing price
+    #[account(seeds = [b"grid_price"], bump, seeds::program = oracle::ID)]
+    pub grid_price: Option<Account<'info, GridPrice>>,
+    
+    /// Gateway clearing authority or the governance authority; pays for the trade fills
+    #[account(
+        mut,
+        constraint = authority.key() == poa_config.authority
+            || market.clearing_authority == Some(authority.key())
+            @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleTrade<'info> {
+    #[account(seeds = [b"market"], bump)]
+    pub market: Account<'info, Market>,
+    
+    #[account(
+        mut,
+        seeds = [b"trade_fill", &buy_fill.epoch.to_le_bytes(), buy_fill.order.as_ref()],
+        bump = buy_fill.bump,
+        constraint = buy_fill.order_type == OrderType::Buy @ ErrorCode::InvalidTradeFill
+    )]
+    pub buy_fill: Account<'info, TradeFill>,
+    
+    #[account(
+        mut,
+        seeds = [b"trade_fill", &sell_fill.epoch.to_le_bytes(), sell_fill.order.as_ref()],
+        bump = sell_fill.bump,
+        constraint = sell_fill.order_type == OrderType::Sell @ ErrorCode::InvalidTradeFill,
+        constraint = sell_fill.epoch == buy_fill.epoch @ ErrorCode::InvalidTradeFill
+    )]
+    pub sell_fill: Account<'info, TradeFill>,
+    
+    /// Holds the buyer's payment
+    #[account(mut, seeds = [b"escrow", buy_fill.order.as_ref()], bump)]
+    pub buy_escrow: Account<'info, TokenAccount>,
+    
+    /// Holds the seller's energy tokens
+    #[account(mut, seeds = [b"escrow", sell_fill.order.as_ref()], bump)]
+    pub sell_escrow: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = market.energy_mint,
+        token::authority = buy_fill.owner
+    )]
+    pub buyer_energy_account: Account<'info, TokenAccount>,
+    
+    /// Receives the difference between the buyer's limit and the clearing price
+    #[account(
+        mut,
+        token::mint = market.payment_mint,
+        token::authority = buy_fill.owner
+    )]
+    pub buyer_payment_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = market.payment_mint,
+        token::authority = sell_fill.owner
+    )]
+    pub seller_payment_account: Account<'info, TokenAccount>,
+    
+    #[account(mut, seeds = [FeePool::Treasury.vault_seed()], bump)]
+    pub fee_vault: Account<'info, TokenAccount>,
+    
+    #[account(mut, seeds = [FeePool::Maintenance.vault_seed()], bump)]
+    pub maintenance_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(batch: u64)]
+pub struct SettleOffchainBatch<'info> {
+    #[account(mut, seeds = [b"market"], bump)]
+    pub market: Account<'info, Market>,
+    
+    #[account(seeds = [b"poa_config"], bump, seeds::program = governance::ID)]
+    pub poa_config: Account<'info, PoAConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SettlementBatch::INIT_SPACE,
+        seeds = [b"settlement_batch", batch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub settlement_batch: Account<'info, SettlementBatch>,
+    
+    /// Decimals of the energy token, which commitment prices are per whole unit of
+    #[account(address = market.energy_mint)]
+    pub energy_mint: Account<'info, Mint>,
+    
+    /// Gateway clearing authority or the governance authority; pays for the batch record
+    #[account(
+        mut,
+        constraint = authority.key() == poa_config.authority
+            || market.clearing_authority == Some(authority.key())
+            @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub authority: Signer<'info>,
+    
+    #[account(mut, seeds = [FeePool::Treasury.vault_seed()], bump)]
+    pub fee_vault: Account<'info, TokenAccount>,
+    
+    #[account(mut, seeds = [FeePool::Maintenance.vault_seed()], bump)]
+    pub maintenance_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(order_id: [u8; 16])]
+pub struct CommitOrder<'info> {
+    #[account(seeds = [b"market"], bump)]
+    pub market: Account<'info, Market>,
+    
+    #[account(seeds = [b"poa_config"], bump, seeds::program = governance::ID)]
+    pub poa_config: Account<'info, PoAConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + OrderCommitment::INIT_SPACE,
+        seeds = [b"order_commitment", owner.key().as_ref(), order_id.as_ref()],
+        bump
+    )]
+    pub commitment: Account<'info, OrderCommitment>,
+    
+    /// CHECK: Wallet placing the order; its signature is checked through the Ed25519 instruction
+    pub owner: UncheckedAccount<'info>,
+    
+    /// Gateway clearing authority or the governance authority; pays for the commitment
+    #[account(
+        mut,
+        constraint = authority.key() == poa_config.authority
+            || market.clearing_authority == Some(authority.key())
+            @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Instructions sysvar, used to find the owner's Ed25519 signature check
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    #[account(mut, seeds = [b"market"], bump)]
+    pub market: Account<'info, Market>,
+    
+    #[account(
+        mut,
+        seeds = [b"order", order.owner.as_ref(), &order.nonce.to_le_bytes()],
+        bump = order.bump,
+        has_one = owner @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub order: Account<'info, Order>,
+    
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", order.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = escrow.mint,
+        token::authority = order.owner
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireOrder<'info> {
+    #[account(mut, seeds = [b"market"], bump)]
+    pub market: Account<'info, Market>,
+    
+    #[account(
+        mut,
+        seeds = [b"order", order.owner.as_ref(), &order.nonce.to_le_bytes()],
+        bump = order.bump
+    )]
+    pub order: Account<'info, Order>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", order.key().as_ref()],
         bump
     )]
-    pub market: Account<'info, Market>,
+    pub escrow: Account<'info, TokenAccount>,
     
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        token::mint = escrow.mint,
+        token::authority = order.owner
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
     
-    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct CreateSellOrder<'info> {
-    #[account(mut)]
+pub struct UpdateMarketParams<'info> {
+    #[account(mut, has_one = authority @ ErrorCode::UnauthorizedAuthority)]
     pub market: Account<'info, Market>,
     
-    #[account(mut)]
     pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CreateBuyOrder<'info> {
-    #[account(mut)]
+#[instruction(pool: FeePool)]
+pub struct WithdrawFees<'info> {
+    #[account(seeds = [b"market"], bump, has_one = authority @ ErrorCode::UnauthorizedAuthority)]
     pub market: Account<'info, Market>,
     
-    #[account(mut)]
+    #[account(mut, seeds = [pool.vault_seed()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(mut, token::mint = market.payment_mint)]
+    pub destination: Account<'info, TokenAccount>,
+    
     pub authority: Signer<'info>,
     
-    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct MatchOrders<'info> {
-    #[account(mut)]
+pub struct OpenChannel<'info> {
+    #[account(seeds = [b"market"], bump)]
     pub market: Account<'info, Market>,
     
-    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = participant,
+        space = 8 + BalanceChannel::INIT_SPACE,
+        seeds = [b"channel", participant.key().as_ref()],
+        bump
+    )]
+    pub channel: Account<'info, BalanceChannel>,
+    
+    #[account(mut)]
+    pub participant: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CancelOrder<'info> {
-    #[account(mut)]
-    pub market: Account<'info, Market>,
+pub struct SubmitChannelState<'info> {
+    #[account(
+        mut,
+        seeds = [b"channel", channel.participant.as_ref()],
+        bump = channel.bump
+    )]
+    pub channel: Account<'info, BalanceChannel>,
     
-    pub authority: Signer<'info>,
+    /// Participant or operator of the channel
+    pub submitter: Signer<'info>,
+    
+    /// CHECK: Instructions sysvar, used to find the counterparty's Ed25519 signature check
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateMarketParams<'info> {
-    #[account(mut, has_one = authority @ ErrorCode::UnauthorizedAuthority)]
+pub struct CloseChannel<'info> {
+    #[account(seeds = [b"market"], bump)]
     pub market: Account<'info, Market>,
     
-    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"channel", channel.participant.as_ref()],
+        bump = channel.bump
+    )]
+    pub channel: Account<'info, BalanceChannel>,
+    
+    #[account(
+        mut,
+        token::mint = market.energy_mint,
+        token::authority = channel.participant
+    )]
+    pub participant_energy: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = market.energy_mint,
+        token::authority = channel.operator
+    )]
+    pub operator_energy: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
 }
 
 // Data structs
@@ -177,20 +1425,81 @@ pub struct Market {
     pub created_at: i64,
     pub clearing_enabled: bool,
     pub market_fee_bps: u16,
+    /// Gateway key allowed to clear the market alongside the governance authority
+    pub clearing_authority: Option<Pubkey>,
+    /// Epoch the next `clear_market` call clears
+    pub epoch: u64,
+    pub last_cleared_at: i64,
+    /// Price of the most recent epoch that matched any orders
+    pub last_clearing_price: u64,
+    /// Token escrowed by sell orders and delivered to buyers
+    pub energy_mint: Pubkey,
+    /// Token escrowed by buy orders and paid to sellers
+    pub payment_mint: Pubkey,
+    /// Share of each fee, in basis points, paid to the grid-maintenance pool
+    pub maintenance_share_bps: u16,
+    /// Lifetime fees paid into the treasury vault
+    pub treasury_fees_collected: u64,
+    /// Lifetime fees paid into the grid-maintenance vault
+    pub maintenance_fees_collected: u64,
+}
+
+impl Market {
+    /// Upper bound on `market_fee_bps` (10%)
+    pub const MAX_FEE_BPS: u16 = 1_000;
+    
+    /// Even split between the treasury and the grid-maintenance pool
+    pub const DEFAULT_MAINTENANCE_SHARE_BPS: u16 = 5_000;
+    
+    /// Minimum seconds between clearings, matching the AMI reading interval
+    pub const EPOCH_DURATION: i64 = 15 * 60;
+    
+    /// Orders considered by one `clear_market` call
+    pub const MAX_CLEARING_ORDERS: usize = 16;
 }
 
 #[account]
 #[derive(InitSpace)]
 pub struct Order {
-    pub seller: Pubkey,
-    pub buyer: Pubkey,
+    pub owner: Pubkey,
+    /// Owner-chosen nonce in the order PDA seeds
+    pub nonce: u64,
+    pub order_type: OrderType,
     pub amount: u64,
     pub filled_amount: u64,
+    /// Asking price for sells, maximum price for buys
     pub price_per_kwh: u64,
-    pub order_type: OrderType,
     pub status: OrderStatus,
     pub created_at: i64,
     pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl Order {
+    /// Whether the order can still be filled, cancelled or expired
+    pub fn is_open(&self) -> bool {
+        matches!(self.status, OrderStatus::Active | OrderStatus::PartiallyFilled)
+    }
+    
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+    
+    /// Escrow backing `quantity` of the order: energy tokens for sells, payment at the
+    /// limit price for buys
+    pub fn escrow_amount(&self, quantity: u64) -> Result<u64> {
+        match self.order_type {
+            OrderType::Sell => Ok(quantity),
+            OrderType::Buy => quantity
+                .checked_mul(self.price_per_kwh)
+                .ok_or(error!(ErrorCode::ArithmeticOverflow)),
+        }
+    }
+    
+    /// Escrow backing the unfilled amount, refunded when the order closes
+    pub fn unfilled_escrow(&self) -> Result<u64> {
+        self.escrow_amount(self.amount - self.filled_amount)
+    }
 }
 
 #[account]
@@ -207,6 +1516,142 @@ pub struct TradeRecord {
     pub executed_at: i64,
 }
 
+/// An order's fill in one cleared epoch, keyed by epoch and order
+#[account]
+#[derive(InitSpace)]
+pub struct TradeFill {
+    pub epoch: u64,
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub order_type: OrderType,
+    pub amount: u64,
+    /// Uniform clearing price of the epoch
+    pub price_per_kwh: u64,
+    /// Order's own price, the rate at which a buy fill was escrowed
+    pub limit_price_per_kwh: u64,
+    pub total_value: u64,
+    pub fee_amount: u64,
+    pub executed_at: i64,
+    /// Part of `amount` already delivered by `settle_trade`
+    pub settled_amount: u64,
+    pub bump: u8,
+}
+
+impl TradeFill {
+    pub fn unsettled(&self) -> u64 {
+        self.amount - self.settled_amount
+    }
+}
+
+/// Batch of off-chain matched trades settled by `settle_offchain_batch`, keyed by batch number
+#[account]
+#[derive(InitSpace)]
+pub struct SettlementBatch {
+    pub batch: u64,
+    pub trades: u32,
+    /// Energy token base units delivered to buyers
+    pub energy_volume: u64,
+    /// Payment token base units paid by buyers, fees included
+    pub payment_volume: u64,
+    pub fee_amount: u64,
+    pub settled_by: Pubkey,
+    pub settled_at: i64,
+    pub bump: u8,
+}
+
+/// One trade of a `settle_offchain_batch`, referring to token accounts by their index in
+/// `remaining_accounts`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OffchainTrade {
+    pub buyer_energy: u8,
+    pub buyer_payment: u8,
+    pub seller_energy: u8,
+    pub seller_payment: u8,
+    /// Energy token base units delivered to the buyer
+    pub energy_amount: u64,
+    /// Payment token base units paid by the buyer, including the market fee
+    pub payment_amount: u64,
+    pub buy_commitment: u8,
+    pub sell_commitment: u8,
+}
+
+/// Limits of an off-chain order signed by its owner, bounding what settlements may move for it
+#[account]
+#[derive(InitSpace)]
+pub struct OrderCommitment {
+    pub owner: Pubkey,
+    /// Gateway order id
+    pub order_id: [u8; 16],
+    pub side: OrderType,
+    /// Payment token base units per whole energy token; at most this for a buy, at least for a sell
+    pub price_per_kwh: u64,
+    /// Energy token base units the order may trade
+    pub quantity: u64,
+    /// Energy token base units settled so far
+    pub filled: u64,
+    pub committed_at: i64,
+    pub bump: u8,
+}
+
+impl OrderCommitment {
+    /// Count a trade of `energy_amount` for `payment_amount` against the commitment
+    ///
+    /// A sell accepts a payment rounded down to the base unit below its price.
+    pub fn fill(&mut self, energy_amount: u64, payment_amount: u64, energy_decimals: u8) -> Result<()> {
+        let filled = self
+            .filled
+            .checked_add(energy_amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        require!(filled <= self.quantity, ErrorCode::CommitmentExceeded);
+        
+        let scale = 10u128.pow(energy_decimals as u32);
+        let limit = energy_amount as u128 * self.price_per_kwh as u128;
+        let within_price = match self.side {
+            OrderType::Buy => payment_amount as u128 * scale <= limit,
+            OrderType::Sell => payment_amount as u128 >= limit / scale,
+        };
+        require!(within_price, ErrorCode::CommitmentPriceExceeded);
+        
+        self.filled = filled;
+        Ok(())
+    }
+}
+
+/// Net balance between a participant and the market, settled off chain per interval
+#[account]
+#[derive(InitSpace)]
+pub struct BalanceChannel {
+    pub participant: Pubkey,
+    /// Market authority countersigning channel states
+    pub operator: Pubkey,
+    /// Nonce of the latest recorded state
+    pub nonce: u64,
+    /// Energy token base units owed to the participant; negative when the participant owes
+    pub balance: i64,
+    pub status: ChannelStatus,
+    pub checkpointed_at: i64,
+    /// When a disputed channel can be closed
+    pub dispute_ends_at: i64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl BalanceChannel {
+    /// Seconds the counterparty has to answer a dispute with a newer state
+    pub const DISPUTE_WINDOW: i64 = 24 * 60 * 60;
+    
+    /// Close a disputed channel whose window has passed, returning the balance to settle
+    ///
+    /// The balance is cleared, leaving nothing owed on the closed channel.
+    pub fn close_at(&mut self, now: i64) -> Result<i64> {
+        require!(self.status == ChannelStatus::Disputed, ErrorCode::ChannelNotDisputed);
+        require!(now >= self.dispute_ends_at, ErrorCode::DisputeWindowOpen);
+        
+        self.status = ChannelStatus::Closed;
+        Ok(std::mem::take(&mut self.balance))
+    }
+}
+
 // Enums
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
 pub enum OrderType {
@@ -214,13 +1659,39 @@ pub enum OrderType {
     Buy,
 }
 
+/// Discriminants are stored by off-chain indexers; append new variants with the next value
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
 pub enum OrderStatus {
-    Active,
-    PartiallyFilled,
-    Completed,
-    Cancelled,
-    Expired,
+    Active = 0,
+    PartiallyFilled = 1,
+    Completed = 2,
+    Cancelled = 3,
+    Expired = 4,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum ChannelStatus {
+    Open,
+    Disputed,
+    Closed,
+}
+
+/// Destination of a share of the market fee
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeePool {
+    /// Engineering Department treasury
+    Treasury,
+    /// Grid-maintenance pool
+    Maintenance,
+}
+
+impl FeePool {
+    pub fn vault_seed(&self) -> &'static [u8] {
+        match self {
+            FeePool::Treasury => b"fee_vault",
+            FeePool::Maintenance => b"maintenance_vault",
+        }
+    }
 }
 
 // Events
@@ -261,10 +1732,90 @@ pub struct OrderMatched {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct OrderFilled {
+    pub order_id: Pubkey,
+    pub user: Pubkey,
+    pub epoch: u64,
+    pub amount: u64,
+    pub price_per_kwh: u64,
+    /// Total filled so far, across epochs
+    pub filled_amount: u64,
+    pub status: OrderStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketCleared {
+    pub epoch: u64,
+    pub clearing_price: u64,
+    pub total_volume: u64,
+    pub trades: u64,
+    pub cleared_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TradeSettled {
+    pub epoch: u64,
+    pub buy_order: Pubkey,
+    pub sell_order: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub price_per_kwh: u64,
+    pub total_value: u64,
+    pub fee_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrderCommitted {
+    pub commitment: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: [u8; 16],
+    pub price_per_kwh: u64,
+    pub quantity: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OffchainBatchSettled {
+    pub batch: u64,
+    pub trades: u32,
+    pub energy_volume: u64,
+    pub payment_volume: u64,
+    pub fee_amount: u64,
+    pub settled_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesCollected {
+    pub epoch: u64,
+    pub buy_order: Pubkey,
+    pub sell_order: Pubkey,
+    pub treasury_amount: u64,
+    pub maintenance_amount: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct OrderCancelled {
     pub order_id: Pubkey,
     pub user: Pubkey,
+    /// Escrow returned for the unfilled amount
+    pub refund: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrderExpired {
+    pub order_id: Pubkey,
+    pub user: Pubkey,
+    pub expires_at: i64,
+    /// Escrow returned for the unfilled amount
+    pub refund: u64,
     pub timestamp: i64,
 }
 
@@ -276,6 +1827,69 @@ pub struct MarketParamsUpdated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct FeeSplitUpdated {
+    pub authority: Pubkey,
+    pub old_maintenance_share_bps: u16,
+    pub new_maintenance_share_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub authority: Pubkey,
+    pub pool: FeePool,
+    pub destination: Pubkey,
+    pub amount: u64,
+    /// Vault balance after the withdrawal
+    pub remaining: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClearingAuthorityUpdated {
+    pub authority: Pubkey,
+    pub old_clearing_authority: Option<Pubkey>,
+    pub new_clearing_authority: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelOpened {
+    pub channel: Pubkey,
+    pub participant: Pubkey,
+    pub operator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelCheckpointed {
+    pub channel: Pubkey,
+    pub submitter: Pubkey,
+    pub nonce: u64,
+    pub balance: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelDisputed {
+    pub channel: Pubkey,
+    pub submitter: Pubkey,
+    pub nonce: u64,
+    pub balance: i64,
+    pub dispute_ends_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelClosed {
+    pub channel: Pubkey,
+    pub nonce: u64,
+    /// Balance settled between the parties
+    pub balance: i64,
+    pub timestamp: i64,
+}
+
 // Errors
 #[error_code]
 pub enum ErrorCode {
@@ -293,6 +1907,330 @@ pub enum ErrorCode {
     PriceMismatch,
     #[msg("Order not cancellable")]
     OrderNotCancellable,
+    #[msg("Order has not expired")]
+    OrderNotExpired,
+    #[msg("Order expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Market is unavailable during governance maintenance")]
+    MarketUnavailable,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Market clearing is disabled")]
+    ClearingDisabled,
+    #[msg("Epoch does not match the market's current epoch")]
+    InvalidEpoch,
+    #[msg("Current epoch has not ended")]
+    EpochNotEnded,
+    #[msg("Order book accounts must be distinct (order, trade fill) pairs")]
+    InvalidOrderBook,
+    #[msg("Too many orders for one clearing")]
+    TooManyOrders,
+    #[msg("Trade fill account does not match the order and epoch")]
+    InvalidTradeFill,
+    #[msg("Escrow mint does not match the order side")]
+    InvalidEscrowMint,
+    #[msg("Trade fill is already settled")]
+    FillSettled,
     #[msg("Insufficient escrow balance")]
     InsufficientEscrowBalance,
-}
\ No newline at end of file
+    #[msg("Fee rate out of range")]
+    InvalidFeeRate,
+    #[msg("Insufficient balance in the fee vault")]
+    InsufficientFeeBalance,
+    #[msg("Signer is not a party to this channel")]
+    UnauthorizedChannelParty,
+    #[msg("Channel state is older than the recorded state")]
+    StaleChannelState,
+    #[msg("Counterparty signature check instruction is missing")]
+    MissingStateSignature,
+    #[msg("Counterparty signature does not match the channel state")]
+    InvalidStateSignature,
+    #[msg("Channel is closed")]
+    ChannelClosed,
+    #[msg("Channel is already disputed")]
+    ChannelAlreadyDisputed,
+    #[msg("Channel is not disputed")]
+    ChannelNotDisputed,
+    #[msg("Dispute window is still open")]
+    DisputeWindowOpen,
+    #[msg("Settlement token account is missing or does not match the trade")]
+    InvalidSettlementAccount,
+    #[msg("Missing Ed25519 signature check of the order commitment")]
+    MissingOrderSignature,
+    #[msg("Order commitment signature does not match the order or its owner")]
+    InvalidOrderSignature,
+    #[msg("Order commitment does not belong to the trade's participant and side")]
+    InvalidOrderCommitment,
+    #[msg("Trade exceeds the quantity its order committed to")]
+    CommitmentExceeded,
+    #[msg("Trade price is outside the price its order committed to")]
+    CommitmentPriceExceeded,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::ed25519_program;
+    use anchor_lang::solana_program::sysvar::instructions::ID as INSTRUCTIONS_SYSVAR_ID;
+
+    /// Account info living for the rest of the test run
+    fn leaked_account(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> &'static AccountInfo<'static> {
+        Box::leak(Box::new(AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            true,
+            Box::leak(Box::new(0u64)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        )))
+    }
+
+    fn order(order_type: OrderType, price_per_kwh: u64, amount: u64, created_at: i64) -> Order {
+        Order {
+            owner: Pubkey::new_unique(),
+            nonce: 0,
+            order_type,
+            amount,
+            filled_amount: 0,
+            price_per_kwh,
+            status: OrderStatus::Active,
+            created_at,
+            expires_at: i64::MAX,
+            bump: 0,
+        }
+    }
+
+    fn entry(order_type: OrderType, price_per_kwh: u64, amount: u64, created_at: i64) -> BookEntry<'static> {
+        let mut data = Vec::new();
+        order(order_type, price_per_kwh, amount, created_at)
+            .try_serialize(&mut data)
+            .unwrap();
+        let info = leaked_account(Pubkey::new_unique(), crate::ID, data);
+        BookEntry {
+            order: Account::try_from(info).unwrap(),
+            fill: info,
+            matched: 0,
+        }
+    }
+
+    /// A bid at 12 for 10 kWh against asks at 8 for 6 kWh and at 10 for 10 kWh
+    fn sample_book() -> Vec<BookEntry<'static>> {
+        vec![
+            entry(OrderType::Buy, 12, 10, 1),
+            entry(OrderType::Sell, 8, 6, 1),
+            entry(OrderType::Sell, 10, 10, 2),
+        ]
+    }
+
+    #[test]
+    fn test_clears_at_midpoint_of_marginal_bid_and_ask() {
+        let mut book = sample_book();
+        let clearing = match_order_book(&mut book, None).unwrap().unwrap();
+
+        // The bid fills against both asks, so the 10 ask is marginal
+        assert_eq!(clearing.price, 11);
+        assert_eq!(clearing.volume, 10);
+        assert_eq!(clearing.trades, 2);
+        let matched: Vec<u64> = book.iter().map(|entry| entry.matched).collect();
+        assert_eq!(matched, [10, 6, 4]);
+    }
+
+    #[test]
+    fn test_grid_price_caps_clearing() {
+        let mut book = sample_book();
+        let clearing = match_order_book(&mut book, Some(9)).unwrap().unwrap();
+
+        // The ask above the grid price is left out and the midpoint of 12 and 8 is capped
+        assert_eq!(clearing.price, 9);
+        assert_eq!(clearing.volume, 6);
+        let matched: Vec<u64> = book.iter().map(|entry| entry.matched).collect();
+        assert_eq!(matched, [6, 6, 0]);
+
+        let mut book = sample_book();
+        assert!(match_order_book(&mut book, Some(7)).unwrap().is_none());
+        assert!(book.iter().all(|entry| entry.matched == 0));
+    }
+
+    #[test]
+    fn test_no_clearing_without_crossing_prices() {
+        let mut book = vec![entry(OrderType::Buy, 7, 10, 1), entry(OrderType::Sell, 8, 10, 1)];
+        assert!(match_order_book(&mut book, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_cancel_refunds_unfilled_escrow() {
+        let mut buy = order(OrderType::Buy, 12, 10, 1);
+        buy.filled_amount = 4;
+        // Payment at the limit price for the 6 kWh left; the filled 4 stay for settlement
+        assert_eq!(buy.unfilled_escrow().unwrap(), 72);
+
+        let mut sell = order(OrderType::Sell, 8, 6, 1);
+        sell.filled_amount = 6;
+        assert_eq!(sell.unfilled_escrow().unwrap(), 0);
+        sell.filled_amount = 1;
+        assert_eq!(sell.unfilled_escrow().unwrap(), 5);
+    }
+
+    /// Ed25519 program instruction data checking `message` by `signer`, with the signature,
+    /// key and message stored after the offsets record
+    fn ed25519_instruction_data(signer: &Pubkey, message: &[u8]) -> Vec<u8> {
+        let (key_offset, signature_offset, message_offset) = (16u16, 48u16, 112u16);
+        let mut data = vec![1, 0];
+        for value in [
+            signature_offset,
+            u16::MAX,
+            key_offset,
+            u16::MAX,
+            message_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0u8; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    /// Instructions sysvar of a transaction running `previous` and then this program
+    fn instructions_sysvar(previous_program: Pubkey, previous_data: &[u8]) -> &'static AccountInfo<'static> {
+        let first_offset = 2 + 2 * 2;
+        let second_offset = first_offset + 2 + 32 + 2 + previous_data.len();
+        let mut data = Vec::new();
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&(first_offset as u16).to_le_bytes());
+        data.extend_from_slice(&(second_offset as u16).to_le_bytes());
+        for (program_id, instruction_data) in [(previous_program, previous_data), (crate::ID, &[][..])] {
+            data.extend_from_slice(&0u16.to_le_bytes());
+            data.extend_from_slice(program_id.as_ref());
+            data.extend_from_slice(&(instruction_data.len() as u16).to_le_bytes());
+            data.extend_from_slice(instruction_data);
+        }
+        // Index of the executing instruction
+        data.extend_from_slice(&1u16.to_le_bytes());
+        leaked_account(INSTRUCTIONS_SYSVAR_ID, Pubkey::default(), data)
+    }
+
+    fn verify_state(instructions: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+        governance::verify_ed25519_instruction(
+            instructions,
+            signer,
+            message,
+            ErrorCode::MissingStateSignature,
+            ErrorCode::InvalidStateSignature,
+        )
+    }
+
+    #[test]
+    fn test_accepts_state_signed_by_counterparty() {
+        let counterparty = Pubkey::new_unique();
+        let message = channel_state_message(&Pubkey::new_unique(), 3, -250);
+        let instructions = instructions_sysvar(ed25519_program::ID, &ed25519_instruction_data(&counterparty, &message));
+
+        verify_state(instructions, &counterparty, &message).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_bad_state_signature() {
+        let channel = Pubkey::new_unique();
+        let counterparty = Pubkey::new_unique();
+        let message = channel_state_message(&channel, 3, -250);
+        let invalid: Error = ErrorCode::InvalidStateSignature.into();
+
+        // Signed by someone else
+        let instructions = instructions_sysvar(
+            ed25519_program::ID,
+            &ed25519_instruction_data(&Pubkey::new_unique(), &message),
+        );
+        assert_eq!(verify_state(instructions, &counterparty, &message).unwrap_err(), invalid);
+
+        // Signed for another balance
+        let instructions = instructions_sysvar(
+            ed25519_program::ID,
+            &ed25519_instruction_data(&counterparty, &channel_state_message(&channel, 3, 250)),
+        );
+        assert_eq!(verify_state(instructions, &counterparty, &message).unwrap_err(), invalid);
+
+        // Key taken from another instruction
+        let mut data = ed25519_instruction_data(&counterparty, &message);
+        data[8..10].copy_from_slice(&0u16.to_le_bytes());
+        let instructions = instructions_sysvar(ed25519_program::ID, &data);
+        assert_eq!(verify_state(instructions, &counterparty, &message).unwrap_err(), invalid);
+    }
+
+    #[test]
+    fn test_rejects_state_without_signature_check() {
+        let counterparty = Pubkey::new_unique();
+        let message = channel_state_message(&Pubkey::new_unique(), 3, -250);
+        let instructions = instructions_sysvar(
+            Pubkey::new_unique(),
+            &ed25519_instruction_data(&counterparty, &message),
+        );
+
+        assert_eq!(
+            verify_state(instructions, &counterparty, &message).unwrap_err(),
+            ErrorCode::MissingStateSignature.into()
+        );
+    }
+
+    #[test]
+    fn test_closing_settles_the_recorded_balance() {
+        let mut channel = BalanceChannel {
+            participant: Pubkey::new_unique(),
+            operator: Pubkey::new_unique(),
+            nonce: 7,
+            balance: -250,
+            status: ChannelStatus::Open,
+            checkpointed_at: 0,
+            dispute_ends_at: 0,
+            created_at: 0,
+            bump: 255,
+        };
+        assert!(channel.close_at(0).is_err());
+
+        channel.status = ChannelStatus::Disputed;
+        channel.dispute_ends_at = BalanceChannel::DISPUTE_WINDOW;
+        assert!(channel.close_at(BalanceChannel::DISPUTE_WINDOW - 1).is_err());
+
+        // The participant owed the operator 250 base units
+        assert_eq!(channel.close_at(BalanceChannel::DISPUTE_WINDOW).unwrap(), -250);
+        assert!(channel.status == ChannelStatus::Closed);
+        assert_eq!(channel.balance, 0);
+        assert!(channel.close_at(BalanceChannel::DISPUTE_WINDOW).is_err());
+    }
+
+    #[test]
+    fn test_commitment_bounds_fills_by_quantity_and_price() {
+        // 3 decimal energy token, 4,500 payment base units per kWh
+        let mut buy = OrderCommitment {
+            owner: Pubkey::new_unique(),
+            order_id: [1; 16],
+            side: OrderType::Buy,
+            price_per_kwh: 4_500,
+            quantity: 2_000,
+            filled: 0,
+            committed_at: 0,
+            bump: 255,
+        };
+        let mut sell = OrderCommitment {
+            side: OrderType::Sell,
+            ..buy.clone()
+        };
+
+        buy.fill(1_000, 4_500, 3).unwrap();
+        assert!(buy.fill(1_000, 4_501, 3).is_err());
+        buy.fill(1_000, 4_000, 3).unwrap();
+        assert_eq!(buy.filled, 2_000);
+        assert!(buy.fill(1, 1, 3).is_err());
+
+        // 0.333 kWh comes to 1,498.5 base units, which a sell accepts rounded down
+        assert!(sell.fill(333, 1_497, 3).is_err());
+        sell.fill(333, 1_498, 3).unwrap();
+        assert!(sell.fill(1_000, 4_499, 3).is_err());
+        sell.fill(1_000, 5_000, 3).unwrap();
+        assert!(sell.fill(1_000, 5_000, 3).is_err());
+        assert_eq!(sell.filled, 1_333);
+    }
+}
@@ -0,0 +1,576 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use reqwest::{Method, RequestBuilder, Url};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::error::{ClientError, Result};
+use crate::models::*;
+
+/// Retry behaviour for idempotent requests
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 disables retries)
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on every following attempt
+    pub base_delay: Duration,
+    /// Upper bound for a single backoff delay
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Backoff delay before retry number `attempt` (1-based)
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+/// Builder for [`ApiClient`]
+#[derive(Debug)]
+pub struct ApiClientBuilder {
+    base_url: String,
+    token: Option<String>,
+    timeout: Duration,
+    retry_policy: RetryPolicy,
+    user_agent: String,
+}
+
+impl ApiClientBuilder {
+    pub fn bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn build(self) -> Result<ApiClient> {
+        // A trailing slash keeps `Url::join` from dropping the last path segment
+        let base = if self.base_url.ends_with('/') {
+            self.base_url
+        } else {
+            format!("{}/", self.base_url)
+        };
+        let base_url = Url::parse(&base)
+            .map_err(|e| ClientError::Configuration(format!("Invalid base URL: {}", e)))?;
+
+        let http = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .user_agent(self.user_agent)
+            .build()?;
+
+        Ok(ApiClient {
+            http,
+            base_url,
+            token: Arc::new(RwLock::new(self.token)),
+            retry_policy: self.retry_policy,
+        })
+    }
+}
+
+/// Typed client for the API Gateway
+///
+/// Cloning is cheap and clones share the authentication token, so a token
+/// obtained through [`ApiClient::login`] is visible to every clone.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    http: reqwest::Client,
+    base_url: Url,
+    token: Arc<RwLock<Option<String>>>,
+    retry_policy: RetryPolicy,
+}
+
+impl ApiClient {
+    pub fn builder(base_url: impl Into<String>) -> ApiClientBuilder {
+        ApiClientBuilder {
+            base_url: base_url.into(),
+            token: None,
+            timeout: Duration::from_secs(30),
+            retry_policy: RetryPolicy::default(),
+            user_agent: format!("api-client/{}", env!("CARGO_PKG_VERSION")),
+        }
+    }
+
+    pub fn new(base_url: impl Into<String>) -> Result<Self> {
+        Self::builder(base_url).build()
+    }
+
+    /// Replace the bearer token used for authenticated requests
+    pub fn set_token(&self, token: Option<String>) {
+        match self.token.write() {
+            Ok(mut guard) => *guard = token,
+            Err(poisoned) => *poisoned.into_inner() = token,
+        }
+    }
+
+    pub fn token(&self) -> Option<String> {
+        match self.token.read() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    // Health
+
+    pub async fn health(&self) -> Result<HealthStatus> {
+        self.get("health", None::<&()>, false).await
+    }
+
+    // Authentication
+
+    /// Log in and store the returned access token for subsequent requests
+    pub async fn login(&self, username: &str, password: &str) -> Result<AuthResponse> {
+        let request = LoginRequest {
+            username: username.to_string(),
+            password: password.to_string(),
+        };
+
+        let response: AuthResponse = self.send(Method::POST, "auth/login", None::<&()>, Some(&request), false).await?;
+        self.set_token(Some(response.access_token.clone()));
+
+        Ok(response)
+    }
+
+    pub async fn get_profile(&self) -> Result<UserInfo> {
+        self.get("auth/profile", None::<&()>, true).await
+    }
+
+    pub async fn update_profile(&self, request: &UpdateProfileRequest) -> Result<UserInfo> {
+        self.send(Method::POST, "auth/profile", None::<&()>, Some(request), true).await
+    }
+
+    // Users (admin)
+
+    pub async fn get_user(&self, user_id: Uuid) -> Result<UserInfo> {
+        self.get(&format!("users/{}", user_id), None::<&()>, true).await
+    }
+
+    pub async fn list_users(&self, query: &UserSearchQuery) -> Result<UserListResponse> {
+        self.get("users", Some(query), true).await
+    }
+
+    /// Fetch every page of a user search
+    pub async fn list_all_users(&self, query: &UserSearchQuery) -> Result<Vec<UserInfo>> {
+        let mut query = query.clone();
+        let mut page = query.page.unwrap_or(1).max(1);
+        let mut users = Vec::new();
+
+        loop {
+            query.page = Some(page);
+            let response = self.list_users(&query).await?;
+            let last_page = response.users.is_empty() || page >= response.total_pages;
+
+            users.extend(response.users);
+
+            if last_page {
+                return Ok(users);
+            }
+            page += 1;
+        }
+    }
+
+    // Roles and permissions
+
+    /// Permission patterns held by the authenticated user
+    pub async fn get_own_permissions(&self) -> Result<PermissionsResponse> {
+        self.get("auth/permissions", None::<&()>, true).await
+    }
+
+    pub async fn list_permissions(&self) -> Result<Vec<Permission>> {
+        self.get("admin/permissions", None::<&()>, true).await
+    }
+
+    pub async fn list_roles(&self) -> Result<Vec<RoleDefinition>> {
+        self.get("admin/roles", None::<&()>, true).await
+    }
+
+    pub async fn create_role(&self, request: &CreateRoleRequest) -> Result<RoleDefinition> {
+        self.send(Method::POST, "admin/roles", None::<&()>, Some(request), true).await
+    }
+
+    pub async fn update_role(&self, role_id: Uuid, request: &UpdateRoleRequest) -> Result<RoleDefinition> {
+        self.send(Method::PUT, &format!("admin/roles/{}", role_id), None::<&()>, Some(request), true).await
+    }
+
+    pub async fn delete_role(&self, role_id: Uuid) -> Result<()> {
+        self.send::<(), (), ()>(Method::DELETE, &format!("admin/roles/{}", role_id), None, None, true).await
+    }
+
+    pub async fn get_user_roles(&self, user_id: Uuid) -> Result<UserRolesResponse> {
+        self.get(&format!("admin/users/{}/roles", user_id), None::<&()>, true).await
+    }
+
+    pub async fn assign_role(&self, user_id: Uuid, role_id: Uuid) -> Result<()> {
+        let path = format!("admin/users/{}/roles/{}", user_id, role_id);
+        self.send::<(), (), ()>(Method::PUT, &path, None, None, true).await
+    }
+
+    pub async fn revoke_role(&self, user_id: Uuid, role_id: Uuid) -> Result<()> {
+        let path = format!("admin/users/{}/roles/{}", user_id, role_id);
+        self.send::<(), (), ()>(Method::DELETE, &path, None, None, true).await
+    }
+
+    // Reconciliation reports
+
+    pub async fn list_reports(&self, query: &ReportQuery) -> Result<Vec<ReconciliationReport>> {
+        self.get("admin/reports", Some(query), true).await
+    }
+
+    pub async fn get_report(&self, report_id: Uuid) -> Result<ReconciliationReport> {
+        self.get(&format!("admin/reports/{}", report_id), None::<&()>, true).await
+    }
+
+    /// Regenerate and redeliver today's report
+    pub async fn run_report(&self) -> Result<ReconciliationReport> {
+        self.send::<(), (), _>(Method::POST, "admin/reports", None, None, true).await
+    }
+
+    // Indexer
+
+    pub async fn get_indexer_status(&self) -> Result<IndexerStatus> {
+        self.get("admin/indexer/status", None::<&()>, true).await
+    }
+
+    pub async fn start_backfill(&self, request: &StartBackfillRequest) -> Result<IndexerBackfill> {
+        self.send(Method::POST, "admin/indexer/backfills", None::<&()>, Some(request), true).await
+    }
+
+    pub async fn cancel_backfill(&self, backfill_id: Uuid) -> Result<IndexerBackfill> {
+        let path = format!("admin/indexer/backfills/{}", backfill_id);
+        self.send::<(), (), _>(Method::DELETE, &path, None, None, true).await
+    }
+
+    // Live dashboard
+
+    pub async fn get_live_dashboard(&self) -> Result<LiveDashboard> {
+        self.get("dashboard/live", None::<&()>, false).await
+    }
+
+    // Balance channels
+
+    /// Register the channel opened on-chain with the user's wallet
+    pub async fn open_channel(&self) -> Result<BalanceChannel> {
+        self.send::<(), (), _>(Method::POST, "channels", None, None, true).await
+    }
+
+    pub async fn get_own_channel(&self) -> Result<BalanceChannel> {
+        self.get("channels/me", None::<&()>, true).await
+    }
+
+    pub async fn list_channel_updates(&self, query: &ChannelUpdatesQuery) -> Result<Vec<BalanceChannelUpdate>> {
+        self.get("channels/me/updates", Some(query), true).await
+    }
+
+    pub async fn sign_channel_update(
+        &self,
+        nonce: i64,
+        request: &SignChannelUpdateRequest,
+    ) -> Result<BalanceChannelUpdate> {
+        let path = format!("channels/me/updates/{}/signature", nonce);
+        self.send(Method::POST, &path, None::<&()>, Some(request), true).await
+    }
+
+    pub async fn list_channels(&self) -> Result<Vec<BalanceChannel>> {
+        self.get("admin/channels", None::<&()>, true).await
+    }
+
+    pub async fn record_channel_update(
+        &self,
+        channel_id: Uuid,
+        request: &RecordChannelUpdateRequest,
+    ) -> Result<BalanceChannelUpdate> {
+        let path = format!("admin/channels/{}/updates", channel_id);
+        self.send(Method::POST, &path, None::<&()>, Some(request), true).await
+    }
+
+    pub async fn dispute_channel(&self, channel_id: Uuid) -> Result<BalanceChannel> {
+        let path = format!("admin/channels/{}/dispute", channel_id);
+        self.send::<(), (), _>(Method::POST, &path, None, None, true).await
+    }
+
+    // Trading
+
+    pub async fn create_order(&self, request: &CreateOrderRequest) -> Result<CreateOrderResponse> {
+        self.send(Method::POST, "trading/orders", None::<&()>, Some(request), true).await
+    }
+
+    pub async fn list_orders(&self, query: &OrderQuery) -> Result<Vec<TradingOrder>> {
+        self.get("trading/orders", Some(query), true).await
+    }
+
+    /// Fetch all orders matching a filter, `page_size` at a time
+    pub async fn list_all_orders(&self, query: &OrderQuery, page_size: i32) -> Result<Vec<TradingOrder>> {
+        let mut query = query.clone();
+        query.limit = Some(page_size.max(1));
+
+        collect_offset_pages(query.limit.unwrap_or(1), |offset| {
+            let mut page_query = query.clone();
+            page_query.offset = Some(query.offset.unwrap_or(0) + offset);
+            async move { self.list_orders(&page_query).await }
+        })
+        .await
+    }
+
+    pub async fn get_market_data(&self) -> Result<MarketData> {
+        self.get("trading/market", None::<&()>, true).await
+    }
+
+    pub async fn get_order_book(&self) -> Result<OrderBookSnapshot> {
+        self.get("orderbook", None::<&()>, true).await
+    }
+
+    pub async fn get_trading_stats(&self) -> Result<TradingStats> {
+        self.get("trading/stats", None::<&()>, true).await
+    }
+
+    // Indexed on-chain state
+
+    pub async fn list_erc_certificates(&self, query: &ErcQuery) -> Result<Vec<ErcCertificate>> {
+        self.get("erc/certificates", Some(query), true).await
+    }
+
+    /// Fetch an ERC certificate, optionally as it was at `query.as_of`
+    pub async fn get_erc_certificate(&self, certificate_id: &str, query: &AsOfQuery) -> Result<ErcCertificate> {
+        self.get(&format!("erc/certificates/{}", certificate_id), Some(query), true).await
+    }
+
+    /// Signed link to print as the certificate's QR code
+    pub async fn get_erc_verification_link(&self, certificate_id: &str) -> Result<ErcVerificationLink> {
+        let path = format!("erc/certificates/{}/verification-link", certificate_id);
+        self.get(&path, None::<&()>, true).await
+    }
+
+    /// Check a printed certificate's link signature and live on-chain status
+    pub async fn verify_erc_certificate(&self, certificate_id: &str, signature: &str) -> Result<ErcVerification> {
+        let query = ErcVerifyQuery {
+            sig: signature.to_string(),
+        };
+        self.get(&format!("verify/erc/{}", certificate_id), Some(&query), false).await
+    }
+
+    /// Fetch the governance configuration, optionally as it was at `query.as_of`
+    pub async fn get_governance_config(&self, query: &AsOfQuery) -> Result<GovernanceConfig> {
+        self.get("governance/config", Some(query), true).await
+    }
+
+    // Energy meters
+
+    pub async fn submit_energy_reading(&self, reading: &EnergyReadingSubmission) -> Result<EnergyReadingResponse> {
+        self.send(Method::POST, "meters/readings", None::<&()>, Some(reading), true).await
+    }
+
+    pub async fn list_energy_readings(&self, query: &EnergyReadingQuery) -> Result<Vec<EnergyReading>> {
+        self.get("meters/readings", Some(query), true).await
+    }
+
+    /// Fetch all readings matching a filter, `page_size` at a time
+    pub async fn list_all_energy_readings(
+        &self,
+        query: &EnergyReadingQuery,
+        page_size: i32,
+    ) -> Result<Vec<EnergyReading>> {
+        let mut query = query.clone();
+        query.limit = Some(page_size.max(1));
+
+        collect_offset_pages(query.limit.unwrap_or(1), |offset| {
+            let mut page_query = query.clone();
+            page_query.offset = Some(query.offset.unwrap_or(0) + offset);
+            async move { self.list_energy_readings(&page_query).await }
+        })
+        .await
+    }
+
+    pub async fn get_energy_reading(&self, reading_id: Uuid) -> Result<EnergyReading> {
+        self.get(&format!("meters/readings/{}", reading_id), None::<&()>, true).await
+    }
+
+    // Blockchain
+
+    pub async fn submit_transaction(&self, submission: &TransactionSubmission) -> Result<TransactionResponse> {
+        self.send(Method::POST, "blockchain/transactions", None::<&()>, Some(submission), true).await
+    }
+
+    pub async fn get_transaction_status(&self, signature: &str) -> Result<TransactionStatus> {
+        self.get(&format!("blockchain/transactions/{}", signature), None::<&()>, true).await
+    }
+
+    pub async fn create_signing_session(&self, request: &CreateSigningSessionRequest) -> Result<SigningSession> {
+        self.send(Method::POST, "blockchain/signing-sessions", None::<&()>, Some(request), true).await
+    }
+
+    pub async fn get_signing_session(&self, session_id: Uuid) -> Result<SigningSession> {
+        self.get(&format!("blockchain/signing-sessions/{}", session_id), None::<&()>, true).await
+    }
+
+    /// Add a signature; the gateway submits the transaction once every party has signed
+    pub async fn submit_signature(&self, session_id: Uuid, request: &SubmitSignatureRequest) -> Result<SigningSession> {
+        self.send(
+            Method::POST,
+            &format!("blockchain/signing-sessions/{}/signatures", session_id),
+            None::<&()>,
+            Some(request),
+            true,
+        )
+        .await
+    }
+
+    // Request plumbing
+
+    async fn get<Q, T>(&self, path: &str, query: Option<&Q>, authenticated: bool) -> Result<T>
+    where
+        Q: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.send::<Q, (), T>(Method::GET, path, query, None, authenticated).await
+    }
+
+    /// Send a request, retrying idempotent methods according to the retry policy
+    async fn send<Q, B, T>(
+        &self,
+        method: Method,
+        path: &str,
+        query: Option<&Q>,
+        body: Option<&B>,
+        authenticated: bool,
+    ) -> Result<T>
+    where
+        Q: Serialize + ?Sized,
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let url = self
+            .base_url
+            .join(path)
+            .map_err(|e| ClientError::Configuration(format!("Invalid path {}: {}", path, e)))?;
+
+        let idempotent = matches!(method, Method::GET | Method::HEAD | Method::PUT | Method::DELETE);
+        let max_retries = if idempotent { self.retry_policy.max_retries } else { 0 };
+        let mut attempt = 0;
+
+        loop {
+            let mut request = self.http.request(method.clone(), url.clone());
+            if let Some(query) = query {
+                request = request.query(query);
+            }
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+            request = self.authorize(request, authenticated)?;
+
+            match execute(request).await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < max_retries && e.is_retryable() => {
+                    attempt += 1;
+                    let delay = self.retry_policy.delay_for(attempt);
+                    tracing::debug!("Retrying {} {} after {:?} (attempt {}): {}", method, url, delay, attempt, e);
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn authorize(&self, request: RequestBuilder, authenticated: bool) -> Result<RequestBuilder> {
+        if !authenticated {
+            return Ok(request);
+        }
+
+        let token = self.token().ok_or(ClientError::NotAuthenticated)?;
+        Ok(request.bearer_auth(token))
+    }
+}
+
+async fn execute<T: DeserializeOwned>(request: RequestBuilder) -> Result<T> {
+    let response = request.send().await?;
+    let status = response.status();
+    let body = response.text().await?;
+
+    if !status.is_success() {
+        return Err(ClientError::from_response_body(status, &body));
+    }
+
+    // 204 No Content responses deserialize as `()`
+    let body = if body.is_empty() { "null" } else { body.as_str() };
+    Ok(serde_json::from_str(body)?)
+}
+
+/// Collect offset-paginated results until a short page is returned
+async fn collect_offset_pages<T, F, Fut>(page_size: i32, mut fetch: F) -> Result<Vec<T>>
+where
+    F: FnMut(i32) -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<T>>>,
+{
+    let mut items = Vec::new();
+    let mut offset = 0;
+
+    loop {
+        let page = fetch(offset).await?;
+        let fetched = page.len() as i32;
+        items.extend(page);
+
+        if fetched < page_size {
+            return Ok(items);
+        }
+        offset += fetched;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+        };
+
+        assert_eq!(policy.delay_for(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(400));
+        assert_eq!(policy.delay_for(4), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_base_url_keeps_path_prefix() {
+        let client = ApiClient::new("http://gateway.campus.local/api/v1").unwrap();
+        assert_eq!(
+            client.base_url.join("trading/orders").unwrap().as_str(),
+            "http://gateway.campus.local/api/v1/trading/orders"
+        );
+    }
+}
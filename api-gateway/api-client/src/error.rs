@@ -0,0 +1,84 @@
+use reqwest::StatusCode;
+use serde::Deserialize;
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// The gateway answered with a non-success status
+    #[error("API error ({status}): {message}")]
+    Api {
+        status: StatusCode,
+        /// Stable error code, e.g. `VALIDATION_ERROR` or `ERC_EXPIRED`
+        code: Option<String>,
+        message: String,
+    },
+
+    /// The request never produced a response (connection refused, timeout, ...)
+    #[error("Transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+
+    /// The response body did not match the expected model
+    #[error("Failed to decode response: {0}")]
+    Decode(#[from] serde_json::Error),
+
+    #[error("Invalid client configuration: {0}")]
+    Configuration(String),
+
+    #[error("Not authenticated: call login() or provide a token first")]
+    NotAuthenticated,
+}
+
+impl ClientError {
+    /// HTTP status of an API error, if any
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::Api { status, .. } => Some(*status),
+            ClientError::Transport(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// Whether retrying the same request may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ClientError::Api { status, .. } => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            ClientError::Transport(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            _ => false,
+        }
+    }
+
+    /// Stable error code of an API error, if the gateway sent one
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            ClientError::Api { code, .. } => code.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Build an API error from a gateway problem document (`{"code", "detail", ...}`)
+    pub(crate) fn from_response_body(status: StatusCode, body: &str) -> Self {
+        #[derive(Deserialize)]
+        struct Problem {
+            detail: String,
+            code: Option<String>,
+        }
+
+        match serde_json::from_str::<Problem>(body) {
+            Ok(problem) => ClientError::Api {
+                status,
+                code: problem.code,
+                message: problem.detail,
+            },
+            // Responses from proxies in front of the gateway may be plain text
+            Err(_) => ClientError::Api {
+                status,
+                code: None,
+                message: body.trim().to_string(),
+            },
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! Typed client for the P2P Energy Trading API Gateway
+//!
+//! Wraps the gateway's REST routes with typed request/response models,
+//! bearer-token authentication, retries with exponential backoff for
+//! idempotent requests, and helpers that walk paginated listings.
+//!
+//! ```no_run
+//! # async fn run() -> api_client::Result<()> {
+//! let client = api_client::ApiClient::new("http://localhost:8080")?;
+//! client.login("engineering_admin", "S3cure!Passw0rd").await?;
+//! let market = client.get_market_data().await?;
+//! println!("current epoch: {}", market.current_epoch);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod client;
+pub mod error;
+pub mod models;
+
+pub use client::{ApiClient, ApiClientBuilder, RetryPolicy};
+pub use error::{ClientError, Result};
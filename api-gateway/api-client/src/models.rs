@@ -0,0 +1,704 @@
+// Request/response models mirroring the API Gateway handlers.
+// Keep field names and serde representations in sync with `api-gateway/src/handlers`.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use api_types::{ErcStatus, OrderStatus, ReadingStatus};
+
+// Health
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthStatus {
+    pub status: String,
+    pub timestamp: DateTime<Utc>,
+    pub version: String,
+    pub environment: String,
+    pub uptime: u64,
+    pub dependencies: Vec<ServiceHealth>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceHealth {
+    pub name: String,
+    pub status: String,
+    pub response_time_ms: Option<u64>,
+    pub last_check: DateTime<Utc>,
+    pub error_message: Option<String>,
+}
+
+// Authentication
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub user: SecureUserInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecureUserInfo {
+    pub username: String,
+    pub email: String,
+    pub role: String,
+    pub department: String,
+    pub blockchain_registered: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserInfo {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub role: String,
+    pub department: String,
+    pub wallet_address: Option<String>,
+    pub blockchain_registered: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateProfileRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub department: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wallet_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UserSearchQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub department: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserListResponse {
+    pub users: Vec<UserInfo>,
+    pub total: u64,
+    pub page: u32,
+    pub per_page: u32,
+    pub total_pages: u32,
+}
+
+// Roles and permissions
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionsResponse {
+    /// Granted permission patterns, e.g. `trading:*` or `*:read`
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Permission {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleDefinition {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_system: bool,
+    pub permissions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRoleRequest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateRoleRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Replaces the role's permissions when present
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserRolesResponse {
+    pub user_id: Uuid,
+    pub system_role: String,
+    pub custom_roles: Vec<RoleDefinition>,
+    pub permissions: Vec<String>,
+}
+
+// Reconciliation reports
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    pub id: Uuid,
+    pub report_date: NaiveDate,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// generating, ok, attention or failed
+    pub status: String,
+    pub summary: Option<ReconciliationSummary>,
+    pub recipients: Vec<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationSummary {
+    pub chain: ChainReconciliation,
+    pub energy: EnergyBalance,
+    pub queues: QueueHealth,
+    pub fees: FeeSpend,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainReconciliation {
+    pub checked: i64,
+    pub matched: i64,
+    pub mismatches: Vec<TransactionMismatch>,
+    pub rpc_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionMismatch {
+    pub signature: String,
+    pub db_status: String,
+    pub chain_status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyBalance {
+    pub readings: i64,
+    pub total_generated: f64,
+    pub total_consumed: f64,
+    pub net_energy: f64,
+    pub invalid_readings: i64,
+    pub silent_meters: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueHealth {
+    pub stale_pending_transactions: i64,
+    pub collecting_signing_sessions: i64,
+    pub stuck_signing_sessions: i64,
+    pub failed_signing_sessions: i64,
+    pub open_orders: i64,
+    pub order_book_diverged: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeSpend {
+    pub transactions: i64,
+    pub total_lamports: i64,
+    pub by_program: Vec<ProgramFeeSpend>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramFeeSpend {
+    pub program_id: String,
+    pub transactions: i64,
+    pub lamports: i64,
+}
+
+// Indexer
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartBackfillRequest {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until_signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexerBackfill {
+    pub id: Uuid,
+    pub address: String,
+    pub cursor_signature: Option<String>,
+    pub until_signature: Option<String>,
+    /// running, completed, failed or cancelled
+    pub status: String,
+    pub signatures_processed: i64,
+    pub transactions_stored: i64,
+    pub newest_slot: Option<i64>,
+    pub oldest_slot: Option<i64>,
+    pub error_message: Option<String>,
+    pub started_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexerStatus {
+    pub indexed_transactions: i64,
+    pub running_backfills: i64,
+    pub backfills: Vec<IndexerBackfill>,
+}
+
+// Live dashboard
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatteryState {
+    pub meters: i64,
+    pub charged_kwh: f64,
+    pub discharged_kwh: f64,
+    /// charging, discharging or idle
+    pub mode: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveDashboard {
+    pub generation_kwh: f64,
+    pub consumption_kwh: f64,
+    pub battery: BatteryState,
+    pub last_clearing_price: Option<f64>,
+    pub last_cleared_at: Option<DateTime<Utc>>,
+    pub ercs_issued_today: i64,
+    pub erc_energy_issued_today: i64,
+    pub active_buy_orders: usize,
+    pub active_sell_orders: usize,
+    pub refreshed_at: Option<DateTime<Utc>>,
+    /// Aggregates are older than the gateway's staleness limit
+    pub stale: bool,
+    pub generated_at: DateTime<Utc>,
+}
+
+// Balance channels
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceChannel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub participant_wallet: String,
+    pub channel_address: String,
+    pub nonce: i64,
+    pub balance: i64,
+    pub signed_nonce: i64,
+    pub signed_balance: i64,
+    pub checkpointed_nonce: i64,
+    pub checkpoint_tx_signature: Option<String>,
+    /// open, disputed or closed
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceChannelUpdate {
+    pub channel_id: Uuid,
+    pub nonce: i64,
+    pub delta: i64,
+    pub balance: i64,
+    pub interval_start: DateTime<Utc>,
+    pub interval_end: DateTime<Utc>,
+    pub operator_signature: String,
+    pub participant_signature: Option<String>,
+    pub signed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChannelUpdatesQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignChannelUpdateRequest {
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordChannelUpdateRequest {
+    pub delta: i64,
+    pub interval_start: DateTime<Utc>,
+    pub interval_end: DateTime<Utc>,
+}
+
+// Trading
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderType {
+    Market,
+    Limit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateOrderRequest {
+    pub energy_amount: Decimal,
+    pub price_per_kwh: Decimal,
+    pub order_type: OrderType,
+    pub expiry_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateOrderResponse {
+    pub id: Uuid,
+    pub status: OrderStatus,
+    pub created_at: DateTime<Utc>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrderQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<OrderStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub side: Option<OrderSide>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i32>,
+    /// Reconstruct orders as they were at this time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradingOrder {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub order_type: OrderType,
+    pub side: OrderSide,
+    pub energy_amount: Decimal,
+    pub price_per_kwh: Decimal,
+    pub filled_amount: Decimal,
+    pub status: OrderStatus,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub filled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketData {
+    pub current_epoch: u64,
+    pub epoch_start_time: DateTime<Utc>,
+    pub epoch_end_time: DateTime<Utc>,
+    pub status: String,
+    pub order_book: OrderBook,
+    pub recent_trades: Vec<TradeExecution>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBook {
+    pub sell_orders: Vec<TradingOrder>,
+    pub buy_orders: Vec<TradingOrder>,
+}
+
+/// Order book served from the gateway's in-memory mirror
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookSnapshot {
+    pub version: u64,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub buy_orders: Vec<TradingOrder>,
+    pub sell_orders: Vec<TradingOrder>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeExecution {
+    pub id: Uuid,
+    pub buyer_id: Uuid,
+    pub seller_id: Uuid,
+    pub energy_amount: Decimal,
+    pub price_per_kwh: Decimal,
+    pub total_price: Decimal,
+    pub executed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradingStats {
+    pub total_orders: i64,
+    pub active_orders: i64,
+    pub filled_orders: i64,
+    pub cancelled_orders: i64,
+}
+
+// Indexed on-chain state
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AsOfQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ErcQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ErcStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErcCertificate {
+    pub certificate_id: String,
+    pub account_address: String,
+    pub authority: String,
+    pub energy_amount: i64,
+    pub renewable_source: String,
+    pub validation_data: String,
+    pub status: ErcStatus,
+    pub validated_for_trading: bool,
+    pub source_readings: serde_json::Value,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub trading_validated_at: Option<DateTime<Utc>>,
+    pub slot: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErcVerificationLink {
+    pub certificate_id: String,
+    pub url: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErcVerifyQuery {
+    pub sig: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErcVerification {
+    pub certificate_id: String,
+    pub verified: bool,
+    pub signature_valid: bool,
+    pub status: Option<String>,
+    pub renewable_source: Option<String>,
+    pub energy_amount: Option<u64>,
+    pub issued_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub account_address: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernanceConfig {
+    pub account_address: String,
+    pub authority: String,
+    pub authority_name: String,
+    pub contact_email: String,
+    pub contact_phone: String,
+    pub website: String,
+    pub office_location: String,
+    pub emergency_paused: bool,
+    /// Pause bitmask: 1 = issuance, 2 = validation, 4 = config updates
+    pub pause_flags: i16,
+    pub maintenance_mode: bool,
+    pub erc_validation_enabled: bool,
+    pub oracle_authority: Option<String>,
+    pub min_energy_amount: i64,
+    pub max_erc_amount: i64,
+    pub erc_validity_period: i64,
+    /// Lamports charged per ERC issuance
+    pub erc_issuance_fee: i64,
+    pub council_mode: bool,
+    pub max_pause_duration: i64,
+    pub trading_program: Option<String>,
+    pub issuance_window: i64,
+    pub max_window_ercs: i64,
+    pub max_window_energy: i64,
+    pub total_ercs_issued: i64,
+    pub total_ercs_validated: i64,
+    pub slot: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Energy meters
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyReadingSubmission {
+    pub meter_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub energy_generated: f64,
+    pub energy_consumed: f64,
+    pub solar_irradiance: Option<f64>,
+    pub temperature: Option<f64>,
+    pub engineering_authority_signature: String,
+    pub metadata: Option<EnergyMetadata>,
+    /// Base58 ed25519 signature by the meter's registered key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meter_signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyMetadata {
+    pub location: String,
+    pub device_type: String,
+    pub weather_conditions: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyReadingResponse {
+    pub id: Uuid,
+    pub meter_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub status: ReadingStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnergyReadingQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meter_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyReading {
+    pub id: Option<Uuid>,
+    pub meter_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub energy_generated: f64,
+    pub energy_consumed: f64,
+    pub solar_irradiance: Option<f64>,
+    pub temperature: Option<f64>,
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Blockchain
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionSubmission {
+    pub transaction: String,
+    pub program_id: String,
+    pub priority_fee: Decimal,
+    pub compute_units: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionResponse {
+    pub signature: String,
+    pub status: String,
+    pub submitted_at: DateTime<Utc>,
+    pub estimated_confirmation_time: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionStatus {
+    pub signature: String,
+    pub status: String,
+    pub block_height: Option<u64>,
+    pub confirmation_status: String,
+    pub fee: Decimal,
+    pub compute_units_consumed: Option<u32>,
+    pub logs: Vec<String>,
+    pub program_interactions: Vec<ProgramInteraction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountMetaRequest {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstructionRequest {
+    pub program_id: String,
+    pub accounts: Vec<AccountMetaRequest>,
+    /// Base64 encoded instruction data
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSigningSessionRequest {
+    pub instructions: Vec<InstructionRequest>,
+    pub fee_payer: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce_account: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitSignatureRequest {
+    pub signer: String,
+    /// Base58 ed25519 signature over the session message
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningSession {
+    pub id: Uuid,
+    pub created_by: Uuid,
+    pub description: Option<String>,
+    /// Base64 serialized message every party signs
+    pub message: String,
+    pub required_signers: Vec<String>,
+    pub signatures: std::collections::HashMap<String, String>,
+    pub missing_signers: Vec<String>,
+    pub recent_blockhash: String,
+    pub last_valid_block_height: Option<i64>,
+    pub nonce_account: Option<String>,
+    pub status: String,
+    pub tx_signature: Option<String>,
+    pub error_message: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramInteraction {
+    pub program_id: String,
+    pub instruction_name: String,
+    pub success: bool,
+}
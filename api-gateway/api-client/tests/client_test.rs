@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use api_client::models::{OrderQuery, UserSearchQuery};
+use api_client::{ApiClient, ClientError, RetryPolicy};
+use serde_json::json;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn fast_retries() -> RetryPolicy {
+    RetryPolicy {
+        max_retries: 2,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(5),
+    }
+}
+
+#[tokio::test]
+async fn test_login_stores_token_for_authenticated_calls() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/auth/login"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "token-123",
+            "token_type": "Bearer",
+            "expires_in": 86400,
+            "user": {
+                "username": "alice",
+                "email": "alice@campus.local",
+                "role": "student",
+                "department": "engineering",
+                "blockchain_registered": false
+            }
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/trading/stats"))
+        .and(header("authorization", "Bearer token-123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "total_orders": 3,
+            "active_orders": 1,
+            "filled_orders": 2,
+            "cancelled_orders": 0
+        })))
+        .mount(&server)
+        .await;
+
+    let client = ApiClient::new(server.uri()).unwrap();
+    assert!(matches!(client.get_trading_stats().await, Err(ClientError::NotAuthenticated)));
+
+    client.login("alice", "S3cure!Passw0rd").await.unwrap();
+    let stats = client.get_trading_stats().await.unwrap();
+
+    assert_eq!(stats.total_orders, 3);
+}
+
+#[tokio::test]
+async fn test_get_requests_are_retried_on_server_errors() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "status": "healthy",
+            "timestamp": "2024-09-23T00:00:00Z",
+            "version": "0.1.0",
+            "environment": "test",
+            "uptime": 0,
+            "dependencies": []
+        })))
+        .mount(&server)
+        .await;
+
+    let client = ApiClient::builder(server.uri()).retry_policy(fast_retries()).build().unwrap();
+    let health = client.health().await.unwrap();
+
+    assert_eq!(health.status, "healthy");
+}
+
+#[tokio::test]
+async fn test_api_errors_are_decoded() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/trading/orders"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "type": "/problems/bad-request",
+            "title": "Bad Request",
+            "status": 400,
+            "detail": "Bad request: invalid status",
+            "code": "BAD_REQUEST",
+            "timestamp": "2024-09-23T00:00:00Z"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = ApiClient::builder(server.uri())
+        .bearer_token("token")
+        .retry_policy(fast_retries())
+        .build()
+        .unwrap();
+
+    match client.list_orders(&OrderQuery::default()).await {
+        Err(ClientError::Api { status, code, message }) => {
+            assert_eq!(status.as_u16(), 400);
+            assert_eq!(code.as_deref(), Some("BAD_REQUEST"));
+            assert_eq!(message, "Bad request: invalid status");
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_list_all_users_walks_pages() {
+    let server = MockServer::start().await;
+
+    for page in 1..=2 {
+        Mock::given(method("GET"))
+            .and(path("/users"))
+            .and(query_param("page", page.to_string()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "users": [{
+                    "id": uuid::Uuid::new_v4(),
+                    "username": format!("user{}", page),
+                    "email": format!("user{}@campus.local", page),
+                    "role": "student",
+                    "department": "engineering",
+                    "wallet_address": null,
+                    "blockchain_registered": false
+                }],
+                "total": 2,
+                "page": page,
+                "per_page": 1,
+                "total_pages": 2
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let client = ApiClient::builder(server.uri()).bearer_token("token").build().unwrap();
+    let users = client
+        .list_all_users(&UserSearchQuery {
+            per_page: Some(1),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let usernames: Vec<_> = users.iter().map(|u| u.username.as_str()).collect();
+    assert_eq!(usernames, vec!["user1", "user2"]);
+}
@@ -0,0 +1,9 @@
+//! Types shared by the API Gateway and its client
+//!
+//! Status enums carry stable integer codes and an `Unknown(u8)` variant, so a build that
+//! predates a new status still reads rows, accounts and responses that contain it. With
+//! the `sqlx` feature they are stored as `SMALLINT` codes.
+
+mod status;
+
+pub use status::{ErcStatus, OrderStatus, ReadingStatus};
@@ -0,0 +1,191 @@
+use std::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Define a status enum with stable codes, lowercase names and an `Unknown(u8)` fallback
+///
+/// Codes are append-only: existing variants keep their code forever and new ones take
+/// the next free value.
+macro_rules! stable_status {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $code:literal => $label:literal,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)+
+            /// Status introduced after this build, with its stored code
+            Unknown(u8),
+        }
+
+        impl $name {
+            /// Code given to unrecognised names, which carry no code of their own
+            pub const UNRECOGNIZED: u8 = u8::MAX;
+
+            pub fn code(self) -> u8 {
+                match self {
+                    $(Self::$variant => $code,)+
+                    Self::Unknown(code) => code,
+                }
+            }
+
+            pub fn from_code(code: u8) -> Self {
+                match code {
+                    $($code => Self::$variant,)+
+                    code => Self::Unknown(code),
+                }
+            }
+
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $label,)+
+                    Self::Unknown(_) => "unknown",
+                }
+            }
+
+            pub fn from_name(name: &str) -> Self {
+                match name {
+                    $($label => Self::$variant,)+
+                    _ => Self::Unknown(Self::UNRECOGNIZED),
+                }
+            }
+
+            pub fn is_known(self) -> bool {
+                !matches!(self, Self::Unknown(_))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct StatusVisitor;
+
+                impl Visitor<'_> for StatusVisitor {
+                    type Value = $name;
+
+                    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        f.write_str(concat!("a ", stringify!($name), " name or code"))
+                    }
+
+                    fn visit_str<E: de::Error>(self, name: &str) -> Result<Self::Value, E> {
+                        Ok($name::from_name(name))
+                    }
+
+                    fn visit_u64<E: de::Error>(self, code: u64) -> Result<Self::Value, E> {
+                        u8::try_from(code)
+                            .map($name::from_code)
+                            .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(code), &self))
+                    }
+                }
+
+                deserializer.deserialize_any(StatusVisitor)
+            }
+        }
+
+        #[cfg(feature = "sqlx")]
+        impl sqlx::Type<sqlx::Postgres> for $name {
+            fn type_info() -> sqlx::postgres::PgTypeInfo {
+                <i16 as sqlx::Type<sqlx::Postgres>>::type_info()
+            }
+        }
+
+        #[cfg(feature = "sqlx")]
+        impl sqlx::Encode<'_, sqlx::Postgres> for $name {
+            fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
+                <i16 as sqlx::Encode<sqlx::Postgres>>::encode_by_ref(&i16::from(self.code()), buf)
+            }
+        }
+
+        #[cfg(feature = "sqlx")]
+        impl<'r> sqlx::Decode<'r, sqlx::Postgres> for $name {
+            fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+                let code = <i16 as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+                Ok(Self::from_code(u8::try_from(code)?))
+            }
+        }
+    };
+}
+
+stable_status! {
+    /// ERC certificate status; codes match the governance program's `ErcStatus` discriminants
+    pub enum ErcStatus {
+        Valid = 0 => "valid",
+        Expired = 1 => "expired",
+        Revoked = 2 => "revoked",
+        Pending = 3 => "pending",
+        /// Claimed by its holder; the certificate token has been burned
+        Retired = 4 => "retired",
+        /// Under dispute; cannot be traded or retired until the challenge is resolved
+        Challenged = 5 => "challenged",
+    }
+}
+
+stable_status! {
+    /// Trading order lifecycle in the gateway
+    pub enum OrderStatus {
+        Pending = 0 => "pending",
+        Active = 1 => "active",
+        Filled = 2 => "filled",
+        Cancelled = 3 => "cancelled",
+        Expired = 4 => "expired",
+    }
+}
+
+stable_status! {
+    /// Meter reading progress from submission to on-chain verification
+    pub enum ReadingStatus {
+        Submitted = 0 => "submitted",
+        Verified = 1 => "verified",
+        Rejected = 2 => "rejected",
+        /// Held for review after failing validation; not stored or sent on-chain
+        Quarantined = 3 => "quarantined",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_round_trip_including_unknown() {
+        for code in 0..=u8::MAX {
+            assert_eq!(ErcStatus::from_code(code).code(), code);
+            assert_eq!(OrderStatus::from_code(code).code(), code);
+        }
+        assert_eq!(ErcStatus::from_code(4), ErcStatus::Retired);
+        assert_eq!(ErcStatus::from_code(5), ErcStatus::Challenged);
+        assert_eq!(ErcStatus::from_code(6), ErcStatus::Unknown(6));
+        assert!(!OrderStatus::from_code(9).is_known());
+        assert_eq!(OrderStatus::from_name("cancelled").code(), 3);
+    }
+
+    #[test]
+    fn test_serde_falls_back_to_unknown() {
+        assert_eq!(serde_json::to_string(&OrderStatus::Filled).unwrap(), "\"filled\"");
+        assert_eq!(serde_json::to_string(&ErcStatus::Unknown(7)).unwrap(), "\"unknown\"");
+
+        let newer: ErcStatus = serde_json::from_str("\"suspended\"").unwrap();
+        assert_eq!(newer, ErcStatus::Unknown(ErcStatus::UNRECOGNIZED));
+        let by_code: ReadingStatus = serde_json::from_str("7").unwrap();
+        assert_eq!(by_code, ReadingStatus::Unknown(7));
+        assert!(serde_json::from_str::<ReadingStatus>("300").is_err());
+
+        let known: Vec<OrderStatus> = serde_json::from_str("[\"pending\", 1]").unwrap();
+        assert_eq!(known, vec![OrderStatus::Pending, OrderStatus::Active]);
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Anchor programs whose custom errors the gateway decodes
+const PROGRAMS_DIR: &str = "../anchor/programs";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "grpc")]
+    compile_protos()?;
+
+    let out_dir = std::env::var("OUT_DIR")?;
+    std::fs::write(Path::new(&out_dir).join("program_errors.rs"), program_errors()?)?;
+    Ok(())
+}
+
+#[cfg(feature = "grpc")]
+fn compile_protos() -> Result<(), Box<dyn std::error::Error>> {
+    // Build without a system `protoc`
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/gridtokenx/v1/gateway.proto"], &["proto"])?;
+    Ok(())
+}
+
+/// Error table of every program, as Rust source for `services::program_errors`
+///
+/// Anchor numbers the variants of a program's `#[error_code]` enum from 6000 in declaration
+/// order, so the names and messages are listed in that order.
+fn program_errors() -> Result<String, Box<dyn std::error::Error>> {
+    let mut programs = Vec::new();
+    for entry in std::fs::read_dir(PROGRAMS_DIR)? {
+        let lib = entry?.path().join("src/lib.rs");
+        if !lib.exists() {
+            continue;
+        }
+        println!("cargo:rerun-if-changed={}", lib.display());
+        let source = std::fs::read_to_string(&lib)?;
+        let name = lib
+            .parent()
+            .and_then(Path::parent)
+            .and_then(Path::file_name)
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_string();
+        if let (Some(id), Some(errors)) = (declared_id(&source), error_variants(&source)) {
+            programs.push((name, id, errors));
+        }
+    }
+    programs.sort();
+
+    let mut out = String::from("pub const PROGRAM_ERRORS: &[ProgramErrors] = &[\n");
+    for (name, id, errors) in programs {
+        writeln!(out, "    ProgramErrors {{ program: {:?}, program_id: {:?}, errors: &[", name, id)?;
+        for (variant, message) in errors {
+            writeln!(out, "        ({:?}, {:?}),", variant, message)?;
+        }
+        out.push_str("    ] },\n");
+    }
+    out.push_str("];\n");
+    Ok(out)
+}
+
+fn declared_id(source: &str) -> Option<String> {
+    let (_, rest) = source.split_once("declare_id!(\"")?;
+    let (id, _) = rest.split_once('"')?;
+    Some(id.to_string())
+}
+
+/// Variant names and `#[msg]` texts of the `#[error_code]` enum
+fn error_variants(source: &str) -> Option<Vec<(String, String)>> {
+    let (_, rest) = source.split_once("#[error_code]")?;
+    let (_, body) = rest.split_once('{')?;
+    let (body, _) = body.split_once("\n}")?;
+
+    let mut variants = Vec::new();
+    let mut message = None;
+    for line in body.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        if let Some(msg) = line.strip_prefix("#[msg(\"") {
+            message = msg.strip_suffix("\")]").map(|msg| msg.replace("\\\"", "\""));
+        } else if !line.starts_with('#') {
+            let variant = line.trim_end_matches(',').trim().to_string();
+            let message = message.take().unwrap_or_else(|| variant.clone());
+            variants.push((variant, message));
+        }
+    }
+    Some(variants)
+}
@@ -0,0 +1,115 @@
+-- Indexed on-chain state: current ERC certificates
+CREATE TABLE erc_certificates (
+    certificate_id VARCHAR(64) PRIMARY KEY,
+    account_address VARCHAR(44) NOT NULL UNIQUE, -- ERC certificate PDA
+    authority VARCHAR(44) NOT NULL,
+    energy_amount BIGINT NOT NULL, -- kWh
+    renewable_source VARCHAR(64) NOT NULL,
+    validation_data TEXT NOT NULL DEFAULT '',
+    status VARCHAR(20) NOT NULL,
+    validated_for_trading BOOLEAN NOT NULL DEFAULT FALSE,
+    source_readings JSONB NOT NULL DEFAULT '[]',
+    issued_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ,
+    trading_validated_at TIMESTAMPTZ,
+    slot BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW() -- block time of the last on-chain change
+);
+
+CREATE INDEX idx_erc_certificates_status ON erc_certificates(status);
+CREATE INDEX idx_erc_certificates_authority ON erc_certificates(authority);
+
+-- Indexed on-chain state: governance PoA configuration (single row)
+CREATE TABLE governance_config (
+    id SMALLINT PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    account_address VARCHAR(44) NOT NULL,
+    authority VARCHAR(44) NOT NULL,
+    authority_name VARCHAR(64) NOT NULL,
+    contact_info VARCHAR(128) NOT NULL,
+    emergency_paused BOOLEAN NOT NULL,
+    maintenance_mode BOOLEAN NOT NULL,
+    erc_validation_enabled BOOLEAN NOT NULL,
+    oracle_authority VARCHAR(44),
+    min_energy_amount BIGINT NOT NULL,
+    max_erc_amount BIGINT NOT NULL,
+    erc_validity_period BIGINT NOT NULL, -- seconds
+    total_ercs_issued BIGINT NOT NULL,
+    total_ercs_validated BIGINT NOT NULL,
+    slot BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW() -- block time of the last on-chain change
+);
+
+-- History tables: one row per version, valid over [valid_from, valid_to)
+CREATE TABLE erc_certificates_history (
+    LIKE erc_certificates INCLUDING DEFAULTS,
+    valid_from TIMESTAMPTZ NOT NULL,
+    valid_to TIMESTAMPTZ
+);
+
+CREATE TABLE governance_config_history (
+    LIKE governance_config INCLUDING DEFAULTS,
+    valid_from TIMESTAMPTZ NOT NULL,
+    valid_to TIMESTAMPTZ
+);
+
+CREATE TABLE trading_orders_history (
+    LIKE trading_orders INCLUDING DEFAULTS,
+    valid_from TIMESTAMPTZ NOT NULL,
+    valid_to TIMESTAMPTZ
+);
+
+CREATE INDEX idx_erc_certificates_history_version ON erc_certificates_history(certificate_id, valid_from);
+CREATE INDEX idx_governance_config_history_version ON governance_config_history(id, valid_from);
+CREATE INDEX idx_trading_orders_history_version ON trading_orders_history(id, valid_from);
+CREATE INDEX idx_trading_orders_history_user_id ON trading_orders_history(user_id, valid_from);
+
+-- Close the current version of a row and, unless it was deleted, open a new one.
+-- TG_ARGV[0] names the key column; versions start at the row's updated_at.
+CREATE OR REPLACE FUNCTION record_row_version()
+RETURNS TRIGGER AS $$
+DECLARE
+    history_table TEXT := TG_TABLE_NAME || '_history';
+    key_column TEXT := TG_ARGV[0];
+    key_value TEXT;
+    changed_at TIMESTAMPTZ;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        EXECUTE format('SELECT ($1).%I::text', key_column) INTO key_value USING OLD;
+        changed_at := NOW();
+    ELSE
+        EXECUTE format('SELECT ($1).%I::text', key_column) INTO key_value USING NEW;
+        changed_at := NEW.updated_at;
+    END IF;
+
+    EXECUTE format(
+        'UPDATE %I SET valid_to = $1 WHERE %I::text = $2 AND valid_to IS NULL',
+        history_table, key_column
+    ) USING changed_at, key_value;
+
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    END IF;
+
+    EXECUTE format('INSERT INTO %I SELECT ($1).*, $2, NULL', history_table)
+        USING NEW, changed_at;
+
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER record_erc_certificates_version
+    AFTER INSERT OR UPDATE OR DELETE ON erc_certificates
+    FOR EACH ROW EXECUTE FUNCTION record_row_version('certificate_id');
+
+CREATE TRIGGER record_governance_config_version
+    AFTER INSERT OR UPDATE OR DELETE ON governance_config
+    FOR EACH ROW EXECUTE FUNCTION record_row_version('id');
+
+CREATE TRIGGER record_trading_orders_version
+    AFTER INSERT OR UPDATE OR DELETE ON trading_orders
+    FOR EACH ROW EXECUTE FUNCTION record_row_version('id');
+
+-- Seed history with existing orders
+INSERT INTO trading_orders_history
+SELECT trading_orders.*, trading_orders.updated_at, NULL
+FROM trading_orders;
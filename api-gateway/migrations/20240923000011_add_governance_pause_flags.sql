@@ -0,0 +1,38 @@
+-- Granular pause flags replace the single emergency pause
+ALTER TABLE governance_config ADD COLUMN pause_flags SMALLINT NOT NULL DEFAULT 0;
+ALTER TABLE governance_config_history ADD COLUMN pause_flags SMALLINT NOT NULL DEFAULT 0;
+
+-- Copy row versions by column name so live and history tables may add columns independently
+CREATE OR REPLACE FUNCTION record_row_version()
+RETURNS TRIGGER AS $$
+DECLARE
+    history_table TEXT := TG_TABLE_NAME || '_history';
+    key_column TEXT := TG_ARGV[0];
+    key_value TEXT;
+    changed_at TIMESTAMPTZ;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        EXECUTE format('SELECT ($1).%I::text', key_column) INTO key_value USING OLD;
+        changed_at := NOW();
+    ELSE
+        EXECUTE format('SELECT ($1).%I::text', key_column) INTO key_value USING NEW;
+        changed_at := NEW.updated_at;
+    END IF;
+
+    EXECUTE format(
+        'UPDATE %I SET valid_to = $1 WHERE %I::text = $2 AND valid_to IS NULL',
+        history_table, key_column
+    ) USING changed_at, key_value;
+
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    END IF;
+
+    EXECUTE format(
+        'INSERT INTO %I SELECT * FROM jsonb_populate_record(NULL::%I, to_jsonb($1) || jsonb_build_object(''valid_from'', $2))',
+        history_table, history_table
+    ) USING NEW, changed_at;
+
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
@@ -0,0 +1,27 @@
+-- Create multi-party signing sessions table
+CREATE TABLE signing_sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    created_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    description VARCHAR(255),
+    message TEXT NOT NULL, -- base64 serialized transaction message
+    required_signers JSONB NOT NULL, -- base58 public keys in signature order
+    signatures JSONB NOT NULL DEFAULT '{}', -- signer public key -> base58 signature
+    recent_blockhash VARCHAR(44) NOT NULL, -- or durable nonce value
+    last_valid_block_height BIGINT, -- NULL for durable nonce sessions
+    nonce_account VARCHAR(44),
+    status VARCHAR(20) NOT NULL DEFAULT 'collecting', -- collecting, submitting, submitted, failed, expired
+    tx_signature VARCHAR(88),
+    error_message TEXT,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes for signing sessions
+CREATE INDEX idx_signing_sessions_created_by ON signing_sessions(created_by);
+CREATE INDEX idx_signing_sessions_status ON signing_sessions(status);
+CREATE INDEX idx_signing_sessions_required_signers ON signing_sessions USING GIN (required_signers);
+
+CREATE TRIGGER update_signing_sessions_updated_at
+    BEFORE UPDATE ON signing_sessions
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
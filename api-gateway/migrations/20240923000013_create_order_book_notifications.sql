@@ -0,0 +1,24 @@
+-- Publish trading order changes for the in-memory order book mirror
+CREATE OR REPLACE FUNCTION notify_order_book_change()
+RETURNS TRIGGER AS $$
+DECLARE
+    order_id UUID;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        order_id := OLD.id;
+    ELSE
+        order_id := NEW.id;
+    END IF;
+
+    PERFORM pg_notify('order_book_changes', order_id::text);
+
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    END IF;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER notify_trading_orders_change
+    AFTER INSERT OR UPDATE OR DELETE ON trading_orders
+    FOR EACH ROW EXECUTE FUNCTION notify_order_book_change();
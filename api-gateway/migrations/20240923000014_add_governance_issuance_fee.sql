@@ -0,0 +1,3 @@
+-- Lamport fee charged per ERC issuance, paid into the governance treasury
+ALTER TABLE governance_config ADD COLUMN erc_issuance_fee BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE governance_config_history ADD COLUMN erc_issuance_fee BIGINT NOT NULL DEFAULT 0;
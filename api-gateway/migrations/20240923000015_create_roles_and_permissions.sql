@@ -0,0 +1,95 @@
+-- Permission catalogue checked by route guards
+CREATE TABLE permissions (
+    name VARCHAR(100) PRIMARY KEY, -- resource:action
+    description TEXT NOT NULL
+);
+
+-- Roles group permissions; system roles mirror the user_role enum
+CREATE TABLE roles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(50) UNIQUE NOT NULL,
+    description TEXT,
+    is_system BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Permissions may be catalogue entries or patterns such as "trading:*" or "*:read"
+CREATE TABLE role_permissions (
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    permission VARCHAR(100) NOT NULL,
+    PRIMARY KEY (role_id, permission)
+);
+
+-- Custom roles granted to users in addition to their system role
+CREATE TABLE user_roles (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    granted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, role_id)
+);
+
+CREATE INDEX idx_user_roles_role_id ON user_roles(role_id);
+
+CREATE TRIGGER update_roles_updated_at
+    BEFORE UPDATE ON roles
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+INSERT INTO permissions (name, description) VALUES
+    ('profile:read', 'View own profile'),
+    ('profile:update', 'Update own profile'),
+    ('energy:read', 'View energy readings'),
+    ('energy:submit', 'Submit energy readings'),
+    ('meters:read', 'View meter data'),
+    ('meters:update', 'Update meter data'),
+    ('trading:read', 'View orders and market data'),
+    ('trading:create', 'Create trading orders'),
+    ('erc:read', 'View ERC certificates'),
+    ('governance:read', 'View governance configuration'),
+    ('blockchain:read', 'View blockchain transactions and accounts'),
+    ('blockchain:submit', 'Submit blockchain transactions'),
+    ('signing:read', 'View any multi-party signing session'),
+    ('analytics:read', 'View own analytics'),
+    ('analytics:system', 'View system-wide analytics'),
+    ('users:read', 'View users and their activity'),
+    ('users:update', 'Update any user'),
+    ('users:manage', 'Deactivate and reactivate users'),
+    ('roles:read', 'View roles and permissions'),
+    ('roles:manage', 'Create, update, delete and assign roles');
+
+INSERT INTO roles (name, description, is_system) VALUES
+    ('student', 'Default role for students', true),
+    ('faculty', 'Default role for faculty members', true),
+    ('admin', 'Engineering Department administrators', true),
+    ('sustainability_office', 'Read-only access to all data', false);
+
+INSERT INTO role_permissions (role_id, permission)
+SELECT r.id, p.permission
+FROM roles r
+JOIN (VALUES
+    ('student', 'profile:*'),
+    ('student', 'energy:read'),
+    ('student', 'energy:submit'),
+    ('student', 'trading:read'),
+    ('student', 'trading:create'),
+    ('student', 'erc:read'),
+    ('student', 'governance:read'),
+    ('student', 'blockchain:read'),
+    ('student', 'blockchain:submit'),
+    ('student', 'analytics:read'),
+    ('faculty', 'profile:*'),
+    ('faculty', 'energy:read'),
+    ('faculty', 'energy:submit'),
+    ('faculty', 'trading:read'),
+    ('faculty', 'trading:create'),
+    ('faculty', 'erc:read'),
+    ('faculty', 'governance:read'),
+    ('faculty', 'blockchain:read'),
+    ('faculty', 'blockchain:submit'),
+    ('faculty', 'analytics:read'),
+    ('faculty', 'users:read'),
+    ('admin', '*'),
+    ('sustainability_office', '*:read'),
+    ('sustainability_office', 'analytics:system')
+) AS p(role_name, permission) ON p.role_name = r.name;
@@ -0,0 +1,25 @@
+-- Archive of nightly reconciliation reports
+CREATE TABLE reconciliation_reports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    report_date DATE NOT NULL UNIQUE,
+    period_start TIMESTAMPTZ NOT NULL,
+    period_end TIMESTAMPTZ NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'generating', -- generating, ok, attention, failed
+    summary JSONB,
+    html TEXT,
+    recipients JSONB NOT NULL DEFAULT '[]',
+    delivered_at TIMESTAMPTZ,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_reconciliation_reports_status ON reconciliation_reports(status);
+
+CREATE TRIGGER update_reconciliation_reports_updated_at
+    BEFORE UPDATE ON reconciliation_reports
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+INSERT INTO permissions (name, description) VALUES
+    ('reports:read', 'View archived reconciliation reports'),
+    ('reports:run', 'Generate reconciliation reports on demand');
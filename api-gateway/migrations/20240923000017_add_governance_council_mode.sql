@@ -0,0 +1,3 @@
+-- Whether sensitive governance operations require M-of-N council approval
+ALTER TABLE governance_config ADD COLUMN council_mode BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE governance_config_history ADD COLUMN council_mode BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,41 @@
+-- Checkpointed history backfills; the cursor lets a backfill resume after a crash
+CREATE TABLE indexer_backfills (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    address VARCHAR(44) NOT NULL,
+    cursor_signature VARCHAR(88), -- oldest signature stored so far; the next page starts before it
+    until_signature VARCHAR(88),  -- stop once this signature is reached
+    status VARCHAR(20) NOT NULL DEFAULT 'running', -- running, completed, failed, cancelled
+    signatures_processed BIGINT NOT NULL DEFAULT 0,
+    transactions_stored BIGINT NOT NULL DEFAULT 0,
+    newest_slot BIGINT,
+    oldest_slot BIGINT,
+    error_message TEXT,
+    started_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One running backfill per address
+CREATE UNIQUE INDEX idx_indexer_backfills_running ON indexer_backfills(address) WHERE status = 'running';
+CREATE INDEX idx_indexer_backfills_created_at ON indexer_backfills(created_at DESC);
+
+CREATE TRIGGER update_indexer_backfills_updated_at
+    BEFORE UPDATE ON indexer_backfills
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Raw transactions fetched by the indexer, decoded into state tables separately
+CREATE TABLE indexed_transactions (
+    signature VARCHAR(88) PRIMARY KEY,
+    address VARCHAR(44) NOT NULL,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMPTZ,
+    succeeded BOOLEAN NOT NULL,
+    transaction JSONB NOT NULL,
+    indexed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_indexed_transactions_address_slot ON indexed_transactions(address, slot DESC);
+
+INSERT INTO permissions (name, description) VALUES
+    ('indexer:read', 'View indexer backfill progress'),
+    ('indexer:manage', 'Start and cancel indexer backfills');
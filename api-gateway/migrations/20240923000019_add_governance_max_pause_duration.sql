@@ -0,0 +1,3 @@
+-- Seconds after which an emergency pause lapses; 0 never lapses
+ALTER TABLE governance_config ADD COLUMN max_pause_duration BIGINT NOT NULL DEFAULT 604800;
+ALTER TABLE governance_config_history ADD COLUMN max_pause_duration BIGINT NOT NULL DEFAULT 604800;
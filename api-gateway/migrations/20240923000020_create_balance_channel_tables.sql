@@ -0,0 +1,43 @@
+-- Off-chain balance channels between participants and the market authority (the operator)
+CREATE TABLE balance_channels (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    participant_wallet VARCHAR(44) NOT NULL UNIQUE,
+    channel_address VARCHAR(44) NOT NULL UNIQUE, -- trading program PDA ["channel", participant]
+    nonce BIGINT NOT NULL DEFAULT 0,             -- latest state countersigned by the operator
+    balance BIGINT NOT NULL DEFAULT 0,           -- energy token base units owed to the participant
+    signed_nonce BIGINT NOT NULL DEFAULT 0,      -- latest state signed by both parties
+    signed_balance BIGINT NOT NULL DEFAULT 0,
+    checkpointed_nonce BIGINT NOT NULL DEFAULT 0,
+    checkpoint_tx_signature VARCHAR(88),
+    status VARCHAR(20) NOT NULL DEFAULT 'open', -- open, disputed, closed
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_balance_channels_user_id ON balance_channels(user_id);
+CREATE INDEX idx_balance_channels_pending_checkpoint ON balance_channels(id)
+    WHERE status = 'open' AND signed_nonce > checkpointed_nonce;
+
+CREATE TRIGGER update_balance_channels_updated_at
+    BEFORE UPDATE ON balance_channels
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Per-interval balance updates; each carries the cumulative balance both parties sign
+CREATE TABLE balance_channel_updates (
+    channel_id UUID NOT NULL REFERENCES balance_channels(id) ON DELETE CASCADE,
+    nonce BIGINT NOT NULL,
+    delta BIGINT NOT NULL,
+    balance BIGINT NOT NULL,
+    interval_start TIMESTAMPTZ NOT NULL,
+    interval_end TIMESTAMPTZ NOT NULL,
+    operator_signature VARCHAR(88) NOT NULL,
+    participant_signature VARCHAR(88),
+    signed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (channel_id, nonce)
+);
+
+INSERT INTO permissions (name, description) VALUES
+    ('channels:read', 'View all balance channels'),
+    ('channels:manage', 'Record interval balances and dispute balance channels');
@@ -0,0 +1,3 @@
+-- Trading program allowed to lock ERC certificates for trades via CPI
+ALTER TABLE governance_config ADD COLUMN trading_program VARCHAR(44);
+ALTER TABLE governance_config_history ADD COLUMN trading_program VARCHAR(44);
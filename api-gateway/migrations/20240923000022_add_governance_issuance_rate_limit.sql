@@ -0,0 +1,7 @@
+-- Sliding-window ERC issuance caps; 0 disables a cap
+ALTER TABLE governance_config ADD COLUMN issuance_window BIGINT NOT NULL DEFAULT 86400;
+ALTER TABLE governance_config ADD COLUMN max_window_ercs BIGINT NOT NULL DEFAULT 1000;
+ALTER TABLE governance_config ADD COLUMN max_window_energy BIGINT NOT NULL DEFAULT 10000000;
+ALTER TABLE governance_config_history ADD COLUMN issuance_window BIGINT NOT NULL DEFAULT 86400;
+ALTER TABLE governance_config_history ADD COLUMN max_window_ercs BIGINT NOT NULL DEFAULT 1000;
+ALTER TABLE governance_config_history ADD COLUMN max_window_energy BIGINT NOT NULL DEFAULT 10000000;
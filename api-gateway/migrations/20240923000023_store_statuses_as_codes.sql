@@ -0,0 +1,45 @@
+-- Store order and ERC statuses as stable SMALLINT codes (see api-types) so that statuses
+-- added later do not break readers built before them. Codes are append-only; 255 marks
+-- values that were not recognised.
+CREATE OR REPLACE FUNCTION order_status_code(status TEXT) RETURNS SMALLINT AS $$
+    SELECT CASE lower(status)
+        WHEN 'pending' THEN 0
+        WHEN 'active' THEN 1
+        WHEN 'filled' THEN 2
+        WHEN 'cancelled' THEN 3
+        WHEN 'expired' THEN 4
+        ELSE 255
+    END::SMALLINT
+$$ LANGUAGE SQL IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION erc_status_code(status TEXT) RETURNS SMALLINT AS $$
+    SELECT CASE lower(status)
+        WHEN 'valid' THEN 0
+        WHEN 'expired' THEN 1
+        WHEN 'revoked' THEN 2
+        WHEN 'pending' THEN 3
+        WHEN 'retired' THEN 4
+        ELSE 255
+    END::SMALLINT
+$$ LANGUAGE SQL IMMUTABLE;
+
+DROP INDEX IF EXISTS idx_trading_orders_active;
+
+ALTER TABLE trading_orders ALTER COLUMN status DROP DEFAULT;
+ALTER TABLE trading_orders ALTER COLUMN status TYPE SMALLINT USING order_status_code(status::TEXT);
+ALTER TABLE trading_orders ALTER COLUMN status SET DEFAULT 0;
+
+ALTER TABLE trading_orders_history ALTER COLUMN status DROP DEFAULT;
+ALTER TABLE trading_orders_history ALTER COLUMN status TYPE SMALLINT USING order_status_code(status::TEXT);
+ALTER TABLE trading_orders_history ALTER COLUMN status SET DEFAULT 0;
+
+-- Pending and active orders
+CREATE INDEX idx_trading_orders_active ON trading_orders(status, created_at)
+WHERE status IN (0, 1);
+
+ALTER TABLE erc_certificates ALTER COLUMN status TYPE SMALLINT USING erc_status_code(status);
+ALTER TABLE erc_certificates_history ALTER COLUMN status TYPE SMALLINT USING erc_status_code(status);
+
+DROP FUNCTION order_status_code(TEXT);
+DROP FUNCTION erc_status_code(TEXT);
+DROP TYPE order_status_enum;
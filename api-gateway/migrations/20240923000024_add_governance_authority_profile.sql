@@ -0,0 +1,9 @@
+-- Structured authority profile; contact_info always held the contact email
+ALTER TABLE governance_config RENAME COLUMN contact_info TO contact_email;
+ALTER TABLE governance_config ADD COLUMN contact_phone VARCHAR(32) NOT NULL DEFAULT '';
+ALTER TABLE governance_config ADD COLUMN website VARCHAR(128) NOT NULL DEFAULT '';
+ALTER TABLE governance_config ADD COLUMN office_location VARCHAR(128) NOT NULL DEFAULT '';
+ALTER TABLE governance_config_history RENAME COLUMN contact_info TO contact_email;
+ALTER TABLE governance_config_history ADD COLUMN contact_phone VARCHAR(32) NOT NULL DEFAULT '';
+ALTER TABLE governance_config_history ADD COLUMN website VARCHAR(128) NOT NULL DEFAULT '';
+ALTER TABLE governance_config_history ADD COLUMN office_location VARCHAR(128) NOT NULL DEFAULT '';
@@ -0,0 +1,4 @@
+-- Permissions for issuing and validating ERCs on-chain through the gateway
+INSERT INTO permissions (name, description) VALUES
+    ('erc:issue', 'Issue ERC certificates on-chain'),
+    ('erc:validate', 'Validate ERC certificates for trading on-chain');
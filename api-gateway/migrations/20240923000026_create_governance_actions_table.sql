@@ -0,0 +1,17 @@
+-- Audit trail of governance instructions submitted through the admin API
+CREATE TABLE governance_actions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    action VARCHAR(64) NOT NULL,
+    parameters JSONB NOT NULL DEFAULT '{}',
+    -- Set when the transaction was accepted, otherwise error_message explains the failure
+    tx_signature VARCHAR(128),
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_governance_actions_user_id ON governance_actions(user_id);
+CREATE INDEX idx_governance_actions_created_at ON governance_actions(created_at);
+
+INSERT INTO permissions (name, description) VALUES
+    ('governance:manage', 'Pause, unpause and configure the governance program on-chain');
@@ -0,0 +1,19 @@
+-- Database-backed API keys replacing the shared ENGINEERING_API_KEY. Keys are found by a
+-- public prefix and stored only as hashes salted per key; scopes replace free-form permissions.
+ALTER TABLE api_keys
+    ADD COLUMN key_prefix VARCHAR(16) UNIQUE,
+    ADD COLUMN salt VARCHAR(64),
+    ADD COLUMN scope VARCHAR(16) NOT NULL DEFAULT 'read_only'
+        CHECK (scope IN ('ingest', 'admin', 'read_only')),
+    ADD COLUMN rate_limit_per_minute INTEGER NOT NULL DEFAULT 600
+        CHECK (rate_limit_per_minute > 0),
+    ADD COLUMN created_by UUID REFERENCES users(id),
+    ADD COLUMN revoked_at TIMESTAMPTZ,
+    DROP COLUMN permissions;
+
+-- Keys hashed without a salt cannot be verified any more
+UPDATE api_keys SET is_active = FALSE, revoked_at = NOW() WHERE key_prefix IS NULL;
+
+INSERT INTO permissions (name, description) VALUES
+    ('api_keys:read', 'List API keys'),
+    ('api_keys:manage', 'Create, update and revoke API keys');
@@ -0,0 +1,35 @@
+-- Tamper-evident audit log of state-changing requests, written when AUDIT_LOG_ENABLED is set.
+-- Each entry's hash covers the previous entry's hash, so altering, removing or reordering
+-- entries breaks the chain from that point on.
+CREATE TABLE audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    user_id UUID,
+    api_key_id UUID,
+    method VARCHAR(10) NOT NULL,
+    path TEXT NOT NULL,
+    status SMALLINT NOT NULL,
+    outcome VARCHAR(16) NOT NULL CHECK (outcome IN ('success', 'failure')),
+    -- SHA-256 of the request body
+    payload_hash CHAR(64) NOT NULL,
+    tx_signature VARCHAR(128),
+    prev_hash CHAR(64) NOT NULL,
+    entry_hash CHAR(64) NOT NULL UNIQUE
+);
+
+CREATE INDEX idx_audit_log_user_id ON audit_log(user_id);
+CREATE INDEX idx_audit_log_occurred_at ON audit_log(occurred_at);
+
+CREATE OR REPLACE FUNCTION reject_audit_log_changes()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION reject_audit_log_changes();
+
+INSERT INTO permissions (name, description) VALUES
+    ('audit:read', 'Read and verify the request audit log');
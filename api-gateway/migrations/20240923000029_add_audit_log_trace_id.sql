@@ -0,0 +1,4 @@
+-- OpenTelemetry trace of each audited request, linking audit entries to exported spans
+ALTER TABLE audit_log ADD COLUMN trace_id VARCHAR(32);
+
+CREATE INDEX idx_audit_log_trace_id ON audit_log(trace_id) WHERE trace_id IS NOT NULL;
@@ -0,0 +1,30 @@
+-- Chain operations queued by API handlers and signed, submitted and confirmed by the
+-- background transaction worker
+CREATE TABLE tx_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    operation VARCHAR(32) NOT NULL, -- issue_erc, validate_erc
+    payload JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'queued', -- queued, submitted, confirmed, failed
+    attempts INTEGER NOT NULL DEFAULT 0,
+    signature VARCHAR(88), -- latest submission
+    result JSONB,
+    error_code VARCHAR(64),
+    error_message TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), -- when the worker next looks at the job
+    submitted_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Jobs the worker still has to submit or confirm
+CREATE INDEX idx_tx_jobs_due ON tx_jobs(next_attempt_at) WHERE status IN ('queued', 'submitted');
+CREATE INDEX idx_tx_jobs_created_by ON tx_jobs(created_by, created_at DESC);
+
+CREATE TRIGGER update_tx_jobs_updated_at
+    BEFORE UPDATE ON tx_jobs
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+INSERT INTO permissions (name, description) VALUES
+    ('tx:read', 'View queued transaction jobs of every user');
@@ -0,0 +1,3 @@
+-- Runtime rotation of the gateway signing keypair, recorded in governance_actions
+INSERT INTO permissions (name, description) VALUES
+    ('signer:rotate', 'Reload the gateway signing keypair without a restart');
@@ -0,0 +1,31 @@
+-- Meters without push capability, read on a schedule over Modbus-TCP or DLMS/COSEM by the
+-- gateway's meter poller
+CREATE TABLE meter_polling_configs (
+    meter_id VARCHAR(20) PRIMARY KEY,
+    protocol VARCHAR(20) NOT NULL CHECK (protocol IN ('modbus_tcp', 'dlms_cosem')),
+    host VARCHAR(255) NOT NULL,
+    port INTEGER NOT NULL CHECK (port BETWEEN 1 AND 65535),
+    unit_id INTEGER NOT NULL DEFAULT 1, -- Modbus unit id or DLMS server address
+    produced_register VARCHAR(32) NOT NULL, -- register address (Modbus) or OBIS code (DLMS)
+    consumed_register VARCHAR(32) NOT NULL,
+    register_scale DOUBLE PRECISION NOT NULL DEFAULT 0.001, -- kWh per Modbus register count
+    poll_interval_seconds INTEGER NOT NULL DEFAULT 900 CHECK (poll_interval_seconds > 0),
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_produced_kwh DOUBLE PRECISION, -- cumulative counters at the last successful poll
+    last_consumed_kwh DOUBLE PRECISION,
+    last_polled_at TIMESTAMPTZ,
+    last_error TEXT,
+    next_poll_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Meters the poller has to read
+CREATE INDEX idx_meter_polling_configs_due ON meter_polling_configs(next_poll_at) WHERE enabled = TRUE;
+
+CREATE TRIGGER update_meter_polling_configs_updated_at
+    BEFORE UPDATE ON meter_polling_configs
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+INSERT INTO permissions (name, description) VALUES
+    ('meters:manage', 'Configure polling of Modbus and DLMS/COSEM meters');
@@ -0,0 +1,3 @@
+-- Bulk imports of historical meter readings from CSV or NDJSON uploads
+INSERT INTO permissions (name, description) VALUES
+    ('readings:import', 'Import historical meter readings and backfill them on-chain');
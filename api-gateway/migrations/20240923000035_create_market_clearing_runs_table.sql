@@ -0,0 +1,20 @@
+-- Each attempt to trigger the oracle's market clearing, scheduled or manual
+CREATE TABLE market_clearing_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    trigger VARCHAR(20) NOT NULL, -- scheduled, manual
+    scheduled_for TIMESTAMPTZ NOT NULL, -- epoch boundary, or request time for manual runs
+    status VARCHAR(20) NOT NULL DEFAULT 'running', -- running, submitted, skipped, failed
+    signature VARCHAR(88),
+    error TEXT, -- why the run failed or was skipped
+    triggered_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+-- One scheduled run per boundary however many gateway replicas reach it
+CREATE UNIQUE INDEX idx_market_clearing_runs_scheduled ON market_clearing_runs(scheduled_for) WHERE trigger = 'scheduled';
+CREATE INDEX idx_market_clearing_runs_created ON market_clearing_runs(created_at DESC);
+
+INSERT INTO permissions (name, description) VALUES
+    ('clearing:read', 'View market clearing runs'),
+    ('clearing:run', 'Trigger market clearing manually');
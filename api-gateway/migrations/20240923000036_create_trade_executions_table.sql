@@ -0,0 +1,23 @@
+-- Trades matched by the gateway's off-chain matching engine and the trading program
+-- settlement batches they are settled in
+CREATE SEQUENCE settlement_batch_seq; -- batch numbers of settle_offchain_batch
+
+CREATE TABLE trade_executions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    buy_order_id UUID NOT NULL REFERENCES trading_orders(id) ON DELETE CASCADE,
+    sell_order_id UUID NOT NULL REFERENCES trading_orders(id) ON DELETE CASCADE,
+    buyer_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    seller_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    energy_amount DECIMAL(18, 8) NOT NULL CHECK (energy_amount > 0),
+    price_per_kwh DECIMAL(18, 8) NOT NULL CHECK (price_per_kwh > 0),
+    total_price DECIMAL(18, 8) NOT NULL,
+    settlement_batch BIGINT, -- NULL when the trade rounds to nothing in token base units
+    settlement_job_id UUID REFERENCES tx_jobs(id) ON DELETE SET NULL,
+    executed_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_trade_executions_executed_at ON trade_executions(executed_at DESC);
+CREATE INDEX idx_trade_executions_buy_order ON trade_executions(buy_order_id);
+CREATE INDEX idx_trade_executions_sell_order ON trade_executions(sell_order_id);
+CREATE INDEX idx_trade_executions_settlement_job ON trade_executions(settlement_job_id);
@@ -0,0 +1,54 @@
+-- Monthly billing periods and the settlement statements finance bills each prosumer from
+CREATE TABLE billing_periods (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    period_start DATE NOT NULL UNIQUE, -- first day of the month
+    period_end DATE NOT NULL, -- first day of the next month, exclusive
+    status VARCHAR(20) NOT NULL DEFAULT 'closing', -- closing, closed, attention, failed
+    market_fee_bps INTEGER, -- trading fee in force when the period closed
+    grid_import_price DECIMAL(18, 8), -- per kWh drawn from the grid
+    statements INTEGER NOT NULL DEFAULT 0,
+    reconciliation JSONB, -- statement trades compared with the settled on-chain batches
+    error_message TEXT,
+    closed_by UUID REFERENCES users(id) ON DELETE SET NULL, -- NULL for scheduled closes
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    closed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_billing_periods_status ON billing_periods(status);
+
+CREATE TABLE settlement_statements (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    billing_period_id UUID NOT NULL REFERENCES billing_periods(id),
+    user_id UUID NOT NULL REFERENCES users(id),
+    energy_bought DECIMAL(18, 8) NOT NULL,
+    purchase_cost DECIMAL(18, 8) NOT NULL,
+    energy_sold DECIMAL(18, 8) NOT NULL,
+    sales_revenue DECIMAL(18, 8) NOT NULL,
+    trading_fees DECIMAL(18, 8) NOT NULL, -- deducted from sales by the trading program
+    energy_generated DECIMAL(18, 8) NOT NULL,
+    energy_consumed DECIMAL(18, 8) NOT NULL,
+    grid_import DECIMAL(18, 8) NOT NULL, -- kWh consumed beyond own generation kept and energy bought
+    grid_import_cost DECIMAL(18, 8) NOT NULL,
+    net_amount DECIMAL(18, 8) NOT NULL, -- owed by the prosumer; negative when owed to them
+    trades INTEGER NOT NULL,
+    unsettled_trades INTEGER NOT NULL, -- trades not yet confirmed on-chain at close
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (billing_period_id, user_id)
+);
+
+CREATE INDEX idx_settlement_statements_user ON settlement_statements(user_id, created_at DESC);
+
+CREATE OR REPLACE FUNCTION reject_settlement_statement_changes()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'settlement_statements are immutable';
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER settlement_statements_immutable
+    BEFORE UPDATE OR DELETE ON settlement_statements
+    FOR EACH ROW EXECUTE FUNCTION reject_settlement_statement_changes();
+
+INSERT INTO permissions (name, description) VALUES
+    ('billing:read', 'View billing periods and every prosumer''s settlement statements'),
+    ('billing:close', 'Close a billing period and issue its settlement statements');
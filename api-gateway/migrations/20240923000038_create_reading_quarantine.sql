@@ -0,0 +1,35 @@
+-- Rated inverter output of metered installations; generation beyond it is implausible
+CREATE TABLE meter_inverters (
+    meter_id VARCHAR(20) PRIMARY KEY,
+    capacity_kw DOUBLE PRECISION NOT NULL CHECK (capacity_kw > 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_meter_inverters_updated_at
+    BEFORE UPDATE ON meter_inverters
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Readings failing validation, held for review instead of being stored and sent on-chain
+CREATE TABLE quarantined_readings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    meter_id VARCHAR(20) NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    energy_generated DOUBLE PRECISION NOT NULL,
+    energy_consumed DOUBLE PRECISION NOT NULL,
+    solar_irradiance DOUBLE PRECISION,
+    temperature DOUBLE PRECISION,
+    metadata JSONB,
+    reasons TEXT[] NOT NULL, -- future_timestamp, above_inverter_capacity, impossible_delta, stuck_value, ...
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    reading_id UUID, -- energy_readings row stored on approval
+    reviewed_by UUID REFERENCES users(id),
+    review_note TEXT,
+    reviewed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_quarantined_readings_status ON quarantined_readings(status, created_at DESC);
+CREATE INDEX idx_quarantined_readings_meter ON quarantined_readings(meter_id, timestamp);
+
+INSERT INTO permissions (name, description) VALUES
+    ('readings:review', 'Approve or reject quarantined meter readings');
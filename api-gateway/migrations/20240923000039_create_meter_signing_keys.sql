@@ -0,0 +1,16 @@
+-- Ed25519 keys meters sign their pushed readings with, so a meter ID alone cannot be spoofed
+CREATE TABLE meter_signing_keys (
+    meter_id VARCHAR(20) PRIMARY KEY,
+    public_key VARCHAR(44) NOT NULL, -- base58
+    registered_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_meter_signing_keys_updated_at
+    BEFORE UPDATE ON meter_signing_keys
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Meter signature a pushed reading was accepted with, kept for forwarding on-chain
+ALTER TABLE energy_readings ADD COLUMN meter_signature VARCHAR(88);
+ALTER TABLE quarantined_readings ADD COLUMN meter_signature VARCHAR(88);
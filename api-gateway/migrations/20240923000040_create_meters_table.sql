@@ -0,0 +1,25 @@
+-- Meters provisioned through the gateway and registered with the registry program; their
+-- rated capacity and signing key live in meter_inverters and meter_signing_keys
+CREATE TABLE meters (
+    meter_id VARCHAR(20) PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    meter_type VARCHAR(20) NOT NULL CHECK (meter_type IN ('solar', 'wind', 'battery', 'grid')),
+    building VARCHAR(64) NOT NULL,
+    zone VARCHAR(32) NOT NULL DEFAULT '',
+    sampling_interval_seconds INTEGER NOT NULL DEFAULT 900 CHECK (sampling_interval_seconds > 0),
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'decommissioned')),
+    registry_job_id UUID REFERENCES tx_jobs(id), -- latest registry program transaction
+    registered_by UUID REFERENCES users(id),
+    decommissioned_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_meters_user ON meters(user_id);
+
+CREATE TRIGGER update_meters_updated_at
+    BEFORE UPDATE ON meters
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+INSERT INTO permissions (name, description) VALUES
+    ('meters:provision', 'Register and decommission meters and issue their signing credentials');
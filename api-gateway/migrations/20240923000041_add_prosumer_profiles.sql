@@ -0,0 +1,5 @@
+-- Student or staff ID a prosumer is known by at the university
+ALTER TABLE users ADD COLUMN university_id VARCHAR(20) UNIQUE;
+
+INSERT INTO permissions (name, description) VALUES
+    ('users:create', 'Create prosumer accounts');
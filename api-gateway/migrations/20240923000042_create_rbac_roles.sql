@@ -0,0 +1,59 @@
+-- Role matrix for serving both the Engineering Department dashboard and student prosumers.
+-- Every non-admin account is a prosumer; operator, auditor and meter are granted through
+-- user_roles, meter to the service accounts whose API keys push readings.
+INSERT INTO roles (name, description, is_system) VALUES
+    ('operator', 'Engineering Department staff operating meters, the market and on-chain jobs', true),
+    ('auditor', 'Read-only access to all data and the audit log', true),
+    ('prosumer', 'Students and staff who trade their own energy', true),
+    ('meter', 'Meter and data logger service accounts pushing readings', true)
+ON CONFLICT (name) DO UPDATE SET description = EXCLUDED.description, is_system = true;
+
+INSERT INTO role_permissions (role_id, permission)
+SELECT r.id, p.permission
+FROM roles r
+JOIN (VALUES
+    ('operator', 'profile:*'),
+    ('operator', 'energy:*'),
+    ('operator', 'meters:*'),
+    ('operator', 'readings:*'),
+    ('operator', 'trading:read'),
+    ('operator', 'clearing:*'),
+    ('operator', 'billing:read'),
+    ('operator', 'erc:*'),
+    ('operator', 'governance:read'),
+    ('operator', 'blockchain:*'),
+    ('operator', 'signing:read'),
+    ('operator', 'tx:read'),
+    ('operator', 'channels:*'),
+    ('operator', 'indexer:*'),
+    ('operator', 'reports:*'),
+    ('operator', 'webhooks:*'),
+    ('operator', 'analytics:*'),
+    ('operator', 'users:read'),
+    ('auditor', 'profile:*'),
+    ('auditor', '*:read'),
+    ('auditor', 'analytics:system'),
+    ('prosumer', 'profile:*'),
+    ('prosumer', 'energy:read'),
+    ('prosumer', 'energy:submit'),
+    ('prosumer', 'trading:read'),
+    ('prosumer', 'trading:create'),
+    ('prosumer', 'erc:read'),
+    ('prosumer', 'governance:read'),
+    ('prosumer', 'blockchain:read'),
+    ('prosumer', 'blockchain:submit'),
+    ('prosumer', 'analytics:read'),
+    ('meter', 'energy:submit'),
+    ('meter', 'meters:read')
+) AS p(role_name, permission) ON p.role_name = r.name
+ON CONFLICT DO NOTHING;
+
+-- Student and faculty keep only what they hold beyond the prosumer role
+DELETE FROM role_permissions rp
+USING roles r, roles prosumer
+WHERE rp.role_id = r.id
+  AND r.name IN ('student', 'faculty')
+  AND prosumer.name = 'prosumer'
+  AND EXISTS (
+      SELECT 1 FROM role_permissions pp WHERE pp.role_id = prosumer.id AND pp.permission = rp.permission
+  );
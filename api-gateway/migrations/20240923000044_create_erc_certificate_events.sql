@@ -0,0 +1,63 @@
+-- Certificate lifecycle events streamed by `GET /erc/stream`, numbered so clients can
+-- resume from the last one they saw
+CREATE TABLE erc_certificate_events (
+    id BIGSERIAL PRIMARY KEY,
+    certificate_id VARCHAR(64) NOT NULL,
+    event_type VARCHAR(20) NOT NULL, -- issued, validated, revoked, expired
+    status SMALLINT NOT NULL, -- certificate status after the event
+    authority VARCHAR(44) NOT NULL,
+    energy_amount BIGINT NOT NULL, -- kWh
+    slot BIGINT, -- NULL for expiries derived from expires_at
+    occurred_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_erc_certificate_events_certificate ON erc_certificate_events(certificate_id);
+
+-- A certificate expires once, whether expire_erc was called or it lapsed past expires_at
+CREATE UNIQUE INDEX idx_erc_certificate_events_expired ON erc_certificate_events(certificate_id)
+WHERE event_type = 'expired';
+
+-- Record the lifecycle events an indexed certificate change amounts to and wake the stream
+CREATE OR REPLACE FUNCTION record_erc_certificate_event()
+RETURNS TRIGGER AS $$
+DECLARE
+    kind TEXT;
+    happened_at TIMESTAMPTZ;
+    event_id BIGINT;
+BEGIN
+    FOREACH kind IN ARRAY ARRAY['issued', 'validated', 'revoked', 'expired'] LOOP
+        CONTINUE WHEN NOT CASE kind
+            WHEN 'issued' THEN TG_OP = 'INSERT'
+            WHEN 'validated' THEN NEW.validated_for_trading
+                AND (TG_OP = 'INSERT' OR NOT OLD.validated_for_trading)
+            WHEN 'revoked' THEN NEW.status = 2 AND (TG_OP = 'INSERT' OR OLD.status <> 2)
+            WHEN 'expired' THEN NEW.status = 1 AND (TG_OP = 'INSERT' OR OLD.status <> 1)
+        END;
+
+        happened_at := CASE kind
+            WHEN 'issued' THEN NEW.issued_at
+            WHEN 'validated' THEN COALESCE(NEW.trading_validated_at, NEW.updated_at)
+            ELSE NEW.updated_at
+        END;
+
+        INSERT INTO erc_certificate_events (
+            certificate_id, event_type, status, authority, energy_amount, slot, occurred_at
+        ) VALUES (
+            NEW.certificate_id, kind, NEW.status, NEW.authority, NEW.energy_amount, NEW.slot, happened_at
+        )
+        ON CONFLICT DO NOTHING
+        RETURNING id INTO event_id;
+
+        IF event_id IS NOT NULL THEN
+            PERFORM pg_notify('erc_certificate_events', event_id::text);
+        END IF;
+    END LOOP;
+
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER record_erc_certificates_event
+    AFTER INSERT OR UPDATE ON erc_certificates
+    FOR EACH ROW EXECUTE FUNCTION record_erc_certificate_event();
@@ -0,0 +1,3 @@
+-- Synthetic meter load runs measuring ingestion and oracle throughput
+INSERT INTO permissions (name, description) VALUES
+    ('readings:simulate', 'Run simulated meters through reading ingestion and the oracle program');
@@ -0,0 +1,45 @@
+-- Buildings and faculties sharing the gateway. Users and meters belong to at most one;
+-- readings, certificates, orders and statements are visible within their owner's tenant
+CREATE TABLE tenants (
+    id VARCHAR(32) PRIMARY KEY CHECK (id ~ '^[a-z0-9][a-z0-9_-]*$'),
+    name VARCHAR(255) NOT NULL,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('faculty', 'building')),
+    requests_per_minute INTEGER CHECK (requests_per_minute > 0), -- NULL uses TENANT_RATE_LIMIT_PER_MINUTE
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_tenants_updated_at
+    BEFORE UPDATE ON tenants
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE users ADD COLUMN tenant_id VARCHAR(32) REFERENCES tenants(id);
+ALTER TABLE meters ADD COLUMN tenant_id VARCHAR(32) REFERENCES tenants(id);
+
+CREATE INDEX idx_users_tenant ON users(tenant_id) WHERE tenant_id IS NOT NULL;
+CREATE INDEX idx_meters_tenant ON meters(tenant_id) WHERE tenant_id IS NOT NULL;
+
+-- Tenant a certificate was issued for; certificates indexed from the chain without one are
+-- visible campus-wide only
+CREATE TABLE erc_certificate_tenants (
+    certificate_id VARCHAR(64) PRIMARY KEY,
+    tenant_id VARCHAR(32) NOT NULL REFERENCES tenants(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_erc_certificate_tenants_tenant ON erc_certificate_tenants(tenant_id);
+
+INSERT INTO permissions (name, description) VALUES
+    ('tenants:campus', 'See readings, certificates, orders and statements of every tenant'),
+    ('tenants:read', 'View tenants and their usage'),
+    ('tenants:manage', 'Create tenants, change their rate limits and assign users and meters to them');
+
+INSERT INTO role_permissions (role_id, permission)
+SELECT r.id, p.permission
+FROM roles r
+JOIN (VALUES
+    ('operator', 'tenants:campus'),
+    ('operator', 'tenants:read'),
+    ('auditor', 'tenants:campus')
+) AS p(role_name, permission) ON p.role_name = r.name
+ON CONFLICT DO NOTHING;
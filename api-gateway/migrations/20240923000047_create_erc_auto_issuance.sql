@@ -0,0 +1,31 @@
+-- Net generation already certified per prosumer and renewable source; generation after
+-- issued_through accumulates until it crosses ERC_AUTO_ISSUANCE_THRESHOLD_KWH
+CREATE TABLE erc_issuance_accounts (
+    user_id UUID NOT NULL REFERENCES users(id),
+    renewable_source VARCHAR(32) NOT NULL,
+    issued_through TIMESTAMPTZ NOT NULL,
+    carried_kwh DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK (carried_kwh >= 0), -- fraction left over from the last certificate
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, renewable_source)
+);
+
+CREATE TRIGGER update_erc_issuance_accounts_updated_at
+    BEFORE UPDATE ON erc_issuance_accounts
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Certificates the gateway issued on its own, with the generation window and meters behind each
+CREATE TABLE erc_auto_issuances (
+    certificate_id VARCHAR(64) PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    renewable_source VARCHAR(32) NOT NULL,
+    energy_amount BIGINT NOT NULL CHECK (energy_amount > 0),
+    generated_from TIMESTAMPTZ NOT NULL,
+    generated_to TIMESTAMPTZ NOT NULL,
+    net_generation_kwh DOUBLE PRECISION NOT NULL, -- including the carried fraction
+    provenance JSONB NOT NULL, -- per-meter net kWh and reading counts
+    tx_job_id UUID NOT NULL REFERENCES tx_jobs(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_erc_auto_issuances_user ON erc_auto_issuances(user_id, created_at DESC);
+CREATE INDEX idx_erc_auto_issuances_created ON erc_auto_issuances(created_at DESC);
@@ -0,0 +1,54 @@
+-- Lifecycle emissions per kWh of each renewable source, and of the grid electricity that
+-- campus generation displaces. Avoided emissions are generation times the difference.
+CREATE TABLE emission_factors (
+    renewable_source VARCHAR(32) PRIMARY KEY, -- solar, wind, ... or grid
+    kg_co2e_per_kwh DOUBLE PRECISION NOT NULL CHECK (kg_co2e_per_kwh >= 0),
+    reference TEXT NOT NULL DEFAULT '', -- where the factor comes from
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_emission_factors_updated_at
+    BEFORE UPDATE ON emission_factors
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+INSERT INTO emission_factors (renewable_source, kg_co2e_per_kwh, reference) VALUES
+    ('grid', 0.4999, 'Thailand Greenhouse Gas Management Organization, national grid emission factor'),
+    ('solar', 0.041, 'IPCC AR5 WGIII Annex III, utility-scale PV lifecycle median'),
+    ('wind', 0.011, 'IPCC AR5 WGIII Annex III, onshore wind lifecycle median'),
+    ('hydro', 0.024, 'IPCC AR5 WGIII Annex III, hydropower lifecycle median'),
+    ('biomass', 0.230, 'IPCC AR5 WGIII Annex III, dedicated biomass lifecycle median');
+
+-- Monthly avoided emissions per prosumer, building and the whole campus for sustainability
+-- reporting, with the factors they were computed at
+CREATE TABLE carbon_rollups (
+    month DATE NOT NULL, -- first day of the month
+    scope VARCHAR(16) NOT NULL CHECK (scope IN ('prosumer', 'building', 'campus')),
+    scope_id VARCHAR(255) NOT NULL, -- user ID or building; empty for the campus
+    renewable_source VARCHAR(32) NOT NULL,
+    generated_kwh DOUBLE PRECISION NOT NULL,
+    certified_kwh DOUBLE PRECISION NOT NULL, -- backed by ERCs issued in the month
+    avoided_kg_co2e DOUBLE PRECISION NOT NULL,
+    certified_avoided_kg_co2e DOUBLE PRECISION NOT NULL,
+    grid_kg_co2e_per_kwh DOUBLE PRECISION NOT NULL,
+    source_kg_co2e_per_kwh DOUBLE PRECISION, -- NULL when the source had no factor
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (month, scope, scope_id, renewable_source)
+);
+
+CREATE INDEX idx_carbon_rollups_scope ON carbon_rollups(scope, scope_id, month DESC);
+
+INSERT INTO permissions (name, description) VALUES
+    ('carbon:read', 'View avoided emissions of any prosumer, building or the campus, and monthly rollups'),
+    ('carbon:manage', 'Change emission factors and recompute monthly carbon rollups');
+
+INSERT INTO role_permissions (role_id, permission)
+SELECT r.id, p.permission
+FROM roles r
+JOIN (VALUES
+    ('operator', 'carbon:read'),
+    -- The sustainability office reports on the whole campus
+    ('sustainability_office', 'carbon:manage'),
+    ('sustainability_office', 'tenants:campus')
+) AS p(role_name, permission) ON p.role_name = r.name
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,47 @@
+-- Time-of-use periods of the utility tariff the campus buys from (PEA/MEA TOU rates). The
+-- period in force bounds the price of every P2P trade: nobody sells below the floor or buys
+-- above the ceiling, which by default is what the grid charges at that time.
+CREATE TABLE tou_periods (
+    id VARCHAR(32) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    weekdays SMALLINT[] NOT NULL, -- ISO weekdays, 1 = Monday .. 7 = Sunday
+    start_time TIME NOT NULL, -- campus local time (UTC+7)
+    end_time TIME NOT NULL, -- at or before start_time runs past midnight; equal is the whole day
+    applies_on_holidays BOOLEAN NOT NULL DEFAULT FALSE,
+    grid_rate DECIMAL(18, 8) NOT NULL CHECK (grid_rate >= 0), -- THB per kWh
+    price_floor DECIMAL(18, 8) CHECK (price_floor >= 0), -- NULL for no bound
+    price_ceiling DECIMAL(18, 8) CHECK (price_ceiling >= 0),
+    priority INTEGER NOT NULL DEFAULT 0, -- the highest priority wins where periods overlap
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (cardinality(weekdays) > 0 AND weekdays <@ ARRAY[1, 2, 3, 4, 5, 6, 7]::SMALLINT[]),
+    CHECK (price_floor IS NULL OR price_ceiling IS NULL OR price_floor <= price_ceiling)
+);
+
+CREATE TRIGGER update_tou_periods_updated_at
+    BEFORE UPDATE ON tou_periods
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- PEA TOU rate 1.1.3 (22-33 kV): peak on working days 09:00-22:00, off-peak otherwise,
+-- including all day on weekends and public holidays
+INSERT INTO tou_periods (
+    id, name, weekdays, start_time, end_time, applies_on_holidays, grid_rate, price_floor, price_ceiling, priority
+) VALUES
+    ('peak', 'Peak', ARRAY[1, 2, 3, 4, 5], '09:00', '22:00', FALSE, 5.7982, 2.2, 5.7982, 1),
+    ('off_peak', 'Off-peak', ARRAY[1, 2, 3, 4, 5, 6, 7], '00:00', '00:00', TRUE, 2.6369, 2.2, 2.6369, 0);
+
+-- Public holidays observed as off-peak all day
+CREATE TABLE tou_holidays (
+    day DATE PRIMARY KEY,
+    name VARCHAR(255) NOT NULL
+);
+
+INSERT INTO permissions (name, description) VALUES
+    ('pricing:manage', 'Change time-of-use periods, their price bounds and public holidays');
+
+INSERT INTO role_permissions (role_id, permission)
+SELECT r.id, p.permission
+FROM roles r
+JOIN (VALUES
+    ('operator', 'pricing:manage')
+) AS p(role_name, permission) ON p.role_name = r.name
+ON CONFLICT DO NOTHING;
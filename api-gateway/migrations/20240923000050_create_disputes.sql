@@ -0,0 +1,96 @@
+-- Prosumer disputes over a meter reading or an ERC certificate. A dispute over a certificate
+-- challenges it on-chain while open, so it cannot be traded or retired until resolved.
+CREATE TABLE disputes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    subject_type VARCHAR(20) NOT NULL CHECK (subject_type IN ('reading', 'certificate')),
+    subject_id VARCHAR(64) NOT NULL, -- reading UUID or certificate ID
+    opened_by UUID NOT NULL REFERENCES users(id),
+    reason TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'upheld', 'dismissed')),
+    resolution TEXT,
+    resolved_by UUID REFERENCES users(id),
+    resolved_at TIMESTAMPTZ,
+    challenge_job_id UUID REFERENCES tx_jobs(id), -- challenge_erc, for certificates
+    resolution_job_id UUID REFERENCES tx_jobs(id), -- resolve_challenge, once the challenge landed
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((status = 'open') = (resolved_at IS NULL))
+);
+
+-- One open dispute per reading or certificate
+CREATE UNIQUE INDEX idx_disputes_open_subject ON disputes(subject_type, subject_id) WHERE status = 'open';
+CREATE INDEX idx_disputes_opened_by ON disputes(opened_by, created_at DESC);
+CREATE INDEX idx_disputes_status ON disputes(status, created_at DESC);
+
+CREATE TRIGGER update_disputes_updated_at
+    BEFORE UPDATE ON disputes
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Evidence attached to a dispute; documents are referenced by URL and pinned by their hash
+CREATE TABLE dispute_evidence (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    dispute_id UUID NOT NULL REFERENCES disputes(id) ON DELETE CASCADE,
+    submitted_by UUID NOT NULL REFERENCES users(id),
+    description TEXT NOT NULL,
+    url TEXT,
+    sha256 CHAR(64), -- hex digest of the document at `url`
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_dispute_evidence_dispute ON dispute_evidence(dispute_id, created_at);
+
+-- Record challenges in the certificate event stream alongside the other lifecycle events
+CREATE OR REPLACE FUNCTION record_erc_certificate_event()
+RETURNS TRIGGER AS $$
+DECLARE
+    kind TEXT;
+    happened_at TIMESTAMPTZ;
+    event_id BIGINT;
+BEGIN
+    FOREACH kind IN ARRAY ARRAY['issued', 'validated', 'revoked', 'expired', 'challenged'] LOOP
+        CONTINUE WHEN NOT CASE kind
+            WHEN 'issued' THEN TG_OP = 'INSERT'
+            WHEN 'validated' THEN NEW.validated_for_trading
+                AND (TG_OP = 'INSERT' OR NOT OLD.validated_for_trading)
+            WHEN 'revoked' THEN NEW.status = 2 AND (TG_OP = 'INSERT' OR OLD.status <> 2)
+            WHEN 'expired' THEN NEW.status = 1 AND (TG_OP = 'INSERT' OR OLD.status <> 1)
+            WHEN 'challenged' THEN NEW.status = 5 AND (TG_OP = 'INSERT' OR OLD.status <> 5)
+        END;
+
+        happened_at := CASE kind
+            WHEN 'issued' THEN NEW.issued_at
+            WHEN 'validated' THEN COALESCE(NEW.trading_validated_at, NEW.updated_at)
+            ELSE NEW.updated_at
+        END;
+
+        INSERT INTO erc_certificate_events (
+            certificate_id, event_type, status, authority, energy_amount, slot, occurred_at
+        ) VALUES (
+            NEW.certificate_id, kind, NEW.status, NEW.authority, NEW.energy_amount, NEW.slot, happened_at
+        )
+        ON CONFLICT DO NOTHING
+        RETURNING id INTO event_id;
+
+        IF event_id IS NOT NULL THEN
+            PERFORM pg_notify('erc_certificate_events', event_id::text);
+        END IF;
+    END LOOP;
+
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+INSERT INTO permissions (name, description) VALUES
+    ('disputes:create', 'Open disputes over visible readings and certificates and attach evidence to them'),
+    ('disputes:read', 'View every dispute and its evidence'),
+    ('disputes:manage', 'Resolve disputes, revoking challenged certificates when upheld');
+
+INSERT INTO role_permissions (role_id, permission)
+SELECT r.id, p.permission
+FROM roles r
+JOIN (VALUES
+    ('prosumer', 'disputes:create'),
+    ('operator', 'disputes:read'),
+    ('operator', 'disputes:manage')
+) AS p(role_name, permission) ON p.role_name = r.name
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,70 @@
+-- Per-user alert channels. Without an email row, alerts go to the account's email address.
+CREATE TABLE notification_preferences (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel VARCHAR(20) NOT NULL CHECK (channel IN ('email', 'line')),
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    destination TEXT, -- email address overriding the account's, or LINE Notify access token
+    event_types TEXT[] NOT NULL DEFAULT ARRAY['order_filled', 'certificate_issued', 'meter_offline', 'settlement_ready'],
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, channel),
+    CHECK (channel = 'email' OR destination IS NOT NULL)
+);
+
+CREATE TRIGGER update_notification_preferences_updated_at
+    BEFORE UPDATE ON notification_preferences
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Alerts queued from gateway events, one per event, user and channel
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    event_type VARCHAR(32) NOT NULL,
+    event_key VARCHAR(128) NOT NULL, -- identifies the event, so replicas hearing it queue it once
+    channel VARCHAR(20) NOT NULL CHECK (channel IN ('email', 'line')),
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'sent', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (event_key, user_id, channel)
+);
+
+CREATE INDEX idx_notifications_due ON notifications(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX idx_notifications_user ON notifications(user_id, created_at DESC);
+
+CREATE TRIGGER update_notifications_updated_at
+    BEFORE UPDATE ON notifications
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Set when the offline sweep announces a meter, cleared once it reports again
+ALTER TABLE meters ADD COLUMN offline_since TIMESTAMPTZ;
+
+-- Announce issuance of auto-issued certificates to their prosumer on the gateway event bus
+CREATE OR REPLACE FUNCTION publish_certificate_issued()
+RETURNS TRIGGER AS $$
+DECLARE
+    owner UUID;
+BEGIN
+    SELECT user_id INTO owner FROM erc_auto_issuances WHERE certificate_id = NEW.certificate_id;
+    IF owner IS NOT NULL THEN
+        PERFORM pg_notify('gateway_events', json_build_object(
+            'event', 'certificate_issued',
+            'certificate_id', NEW.certificate_id,
+            'user_id', owner,
+            'energy_amount', NEW.energy_amount,
+            'issued_at', NEW.occurred_at
+        )::text);
+    END IF;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER erc_certificate_events_publish_issued
+    AFTER INSERT ON erc_certificate_events
+    FOR EACH ROW WHEN (NEW.event_type = 'issued')
+    EXECUTE FUNCTION publish_certificate_issued();
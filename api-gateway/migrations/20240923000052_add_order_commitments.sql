@@ -0,0 +1,23 @@
+-- Off-chain orders settle only within a commitment their owner signed, recorded on-chain by a
+-- commit_order transaction job
+ALTER TABLE trading_orders ADD COLUMN commitment_job_id UUID REFERENCES tx_jobs(id);
+ALTER TABLE trading_orders_history ADD COLUMN commitment_job_id UUID;
+
+CREATE INDEX idx_trading_orders_commitment_job ON trading_orders(commitment_job_id);
+
+-- An order enters the matching book once its commitment is confirmed
+CREATE OR REPLACE FUNCTION notify_order_commitment_confirmed()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.status = 'confirmed' AND OLD.status IS DISTINCT FROM NEW.status THEN
+        PERFORM pg_notify('order_book_changes', id::text)
+        FROM trading_orders
+        WHERE commitment_job_id = NEW.id;
+    END IF;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER notify_order_commitment_confirmed
+    AFTER UPDATE OF status ON tx_jobs
+    FOR EACH ROW EXECUTE FUNCTION notify_order_commitment_confirmed();
@@ -0,0 +1,340 @@
+use axum::http::Method;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::jwt::ApiKeyService;
+use crate::auth::permissions::permission_matches;
+use crate::auth::{ApiKey, Claims};
+use crate::database::redis_pool::RedisPool;
+use crate::error::{ApiError, Result};
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+/// Header carrying an API key in place of a bearer token
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Requests per minute allowed when a key is created without a limit
+pub const DEFAULT_RATE_LIMIT_PER_MINUTE: i32 = 600;
+
+/// Highest per-minute limit a key may be given
+pub const MAX_RATE_LIMIT_PER_MINUTE: i32 = 100_000;
+
+const RATE_KEY_PREFIX: &str = "api_keys:rate:";
+
+const API_KEY_COLUMNS: &str = "id, name, key_prefix, scope, rate_limit_per_minute, is_active, \
+    created_by, created_at, last_used_at, revoked_at";
+
+/// Permissions an ingest key may exercise, matching the AMI role
+const INGEST_PERMISSIONS: &[&str] = &["energy:submit", "meters:read", "meters:update"];
+
+/// Route prefix an ingest key may call
+const INGEST_PATH_PREFIX: &str = "/meters/";
+
+/// What an API key may be used for
+///
+/// A key acts as the user who created it, narrowed to its scope: it never grants more than
+/// that user currently holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    /// Meter reading submission by AMI systems
+    Ingest,
+    /// Everything the creating user may do
+    Admin,
+    /// Reads only
+    ReadOnly,
+}
+
+impl ApiKeyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ingest => "ingest",
+            Self::Admin => "admin",
+            Self::ReadOnly => "read_only",
+        }
+    }
+
+    /// Whether route guards requiring `permission` admit this scope
+    pub fn allows_permission(&self, permission: &str) -> bool {
+        match self {
+            Self::Admin => true,
+            Self::ReadOnly => permission_matches("*:read", permission),
+            Self::Ingest => INGEST_PERMISSIONS.contains(&permission),
+        }
+    }
+
+    /// Whether this scope may call `method` on `path`, for routes without a permission guard
+    pub fn allows_request(&self, method: &Method, path: &str) -> bool {
+        match self {
+            Self::Admin => true,
+            Self::ReadOnly => method == Method::GET || method == Method::HEAD,
+            Self::Ingest => path.starts_with(INGEST_PATH_PREFIX),
+        }
+    }
+}
+
+impl TryFrom<String> for ApiKeyScope {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        match value.as_str() {
+            "ingest" => Ok(Self::Ingest),
+            "admin" => Ok(Self::Admin),
+            "read_only" => Ok(Self::ReadOnly),
+            _ => Err(format!("Invalid API key scope: {}", value)),
+        }
+    }
+}
+
+/// Key that authenticated a request, added to the request extensions next to its [`Claims`]
+#[derive(Debug, Clone)]
+pub struct ApiKeyPrincipal {
+    pub id: Uuid,
+    pub name: String,
+    pub scope: ApiKeyScope,
+    pub rate_limit_per_minute: i32,
+}
+
+#[derive(sqlx::FromRow)]
+struct CredentialRow {
+    id: Uuid,
+    name: String,
+    salt: String,
+    key_hash: String,
+    #[sqlx(try_from = "String")]
+    scope: ApiKeyScope,
+    rate_limit_per_minute: i32,
+    created_by: Uuid,
+    role: String,
+    department: String,
+    tenant_id: Option<String>,
+}
+
+/// Issues, verifies and revokes API keys stored in Postgres, rate limited through Redis
+#[derive(Clone)]
+pub struct ApiKeyStore {
+    db: PgPool,
+    redis: RedisPool,
+    keys: ApiKeyService,
+    clock: SharedClock,
+}
+
+impl ApiKeyStore {
+    pub fn new(db: PgPool, redis: RedisPool, keys: ApiKeyService, clock: SharedClock) -> Self {
+        Self { db, redis, keys, clock }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(
+            state.db.clone(),
+            state.redis.clone(),
+            state.api_key_service.clone(),
+            state.clock.clone(),
+        )
+    }
+
+    /// Create a key for `created_by`, returning the plaintext key alongside its record
+    pub async fn create(
+        &self,
+        created_by: Uuid,
+        name: &str,
+        scope: ApiKeyScope,
+        rate_limit_per_minute: i32,
+    ) -> Result<(String, ApiKey)> {
+        let generated = self.keys.generate_key();
+
+        let query = format!(
+            "INSERT INTO api_keys (key_prefix, salt, key_hash, name, scope, rate_limit_per_minute, created_by)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING {}",
+            API_KEY_COLUMNS
+        );
+        let api_key = sqlx::query_as::<_, ApiKey>(&query)
+            .bind(&generated.prefix)
+            .bind(&generated.salt)
+            .bind(&generated.key_hash)
+            .bind(name)
+            .bind(scope.as_str())
+            .bind(rate_limit_per_minute)
+            .bind(created_by)
+            .fetch_one(&self.db)
+            .await?;
+
+        Ok((generated.key, api_key))
+    }
+
+    pub async fn list(&self) -> Result<Vec<ApiKey>> {
+        let query = format!("SELECT {} FROM api_keys ORDER BY created_at DESC", API_KEY_COLUMNS);
+        Ok(sqlx::query_as::<_, ApiKey>(&query).fetch_all(&self.db).await?)
+    }
+
+    pub async fn set_rate_limit(&self, id: Uuid, rate_limit_per_minute: i32) -> Result<ApiKey> {
+        let query = format!(
+            "UPDATE api_keys SET rate_limit_per_minute = $2 WHERE id = $1 RETURNING {}",
+            API_KEY_COLUMNS
+        );
+        sqlx::query_as::<_, ApiKey>(&query)
+            .bind(id)
+            .bind(rate_limit_per_minute)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("API key {} not found", id)))
+    }
+
+    /// Deactivate a key; revoking an already revoked key keeps its original revocation time
+    pub async fn revoke(&self, id: Uuid) -> Result<ApiKey> {
+        let query = format!(
+            "UPDATE api_keys SET is_active = FALSE, revoked_at = COALESCE(revoked_at, NOW())
+             WHERE id = $1 RETURNING {}",
+            API_KEY_COLUMNS
+        );
+        sqlx::query_as::<_, ApiKey>(&query)
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("API key {} not found", id)))
+    }
+
+    /// Verify `key`, returning the key and the claims of the active user it acts as
+    pub async fn authenticate(&self, key: &str) -> Result<(ApiKeyPrincipal, Claims)> {
+        let invalid = || ApiError::Unauthorized("Invalid API key".to_string());
+        let prefix = ApiKeyService::key_prefix(key).ok_or_else(invalid)?;
+
+        let row = sqlx::query_as::<_, CredentialRow>(
+            "SELECT k.id, k.name, k.salt, k.key_hash, k.scope, k.rate_limit_per_minute, k.created_by,
+                    u.role::text AS role, u.department, u.tenant_id
+             FROM api_keys k
+             JOIN users u ON u.id = k.created_by
+             WHERE k.key_prefix = $1 AND k.is_active = TRUE AND u.is_active = TRUE",
+        )
+        .bind(prefix)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(invalid)?;
+
+        if !self.keys.verify_key(key, &row.salt, &row.key_hash) {
+            return Err(invalid());
+        }
+
+        let _ = sqlx::query("UPDATE api_keys SET last_used_at = NOW() WHERE id = $1")
+            .bind(row.id)
+            .execute(&self.db)
+            .await;
+
+        let claims = Claims::issued_at(
+            row.created_by,
+            format!("api-key:{}", row.name),
+            row.role,
+            row.department,
+            self.clock.now(),
+        )
+        .with_tenant(row.tenant_id);
+        let principal = ApiKeyPrincipal {
+            id: row.id,
+            name: row.name,
+            scope: row.scope,
+            rate_limit_per_minute: row.rate_limit_per_minute,
+        };
+        Ok((principal, claims))
+    }
+
+    /// Verify `key` for a `method` request to `path`, enforcing its scope and rate limit
+    pub async fn authorize(&self, key: &str, method: &Method, path: &str) -> Result<(ApiKeyPrincipal, Claims)> {
+        let (principal, claims) = self.authenticate(key).await?;
+        if !principal.scope.allows_request(method, path) {
+            return Err(ApiError::Authorization(format!(
+                "API key scope {} does not allow {} {}",
+                principal.scope.as_str(),
+                method,
+                path
+            )));
+        }
+
+        self.check_rate_limit(&principal).await?;
+        Ok((principal, claims))
+    }
+
+    /// Count a request against the key's per-minute limit
+    ///
+    /// The limit is best effort: requests are admitted when Redis is unavailable.
+    pub async fn check_rate_limit(&self, principal: &ApiKeyPrincipal) -> Result<()> {
+        let key = rate_key(principal.id, self.clock.now().timestamp());
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            redis::pipe()
+                .atomic()
+                .incr(&key, 1)
+                .expire(&key, 60)
+                .ignore()
+                .query_async::<_, (i64,)>(&mut conn)
+                .await
+        }
+        .await;
+
+        match result {
+            Ok((count,)) if count > i64::from(principal.rate_limit_per_minute) => {
+                tracing::warn!(
+                    "API key {} ({}) exceeded {} requests per minute",
+                    principal.name,
+                    principal.id,
+                    principal.rate_limit_per_minute
+                );
+                Err(ApiError::RateLimit)
+            }
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::warn!("API key rate limiter unavailable: {}", e);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Redis counter for a key's requests in the minute containing `timestamp`
+fn rate_key(id: Uuid, timestamp: i64) -> String {
+    format!("{}{}:{}", RATE_KEY_PREFIX, id, timestamp.div_euclid(60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes_narrow_permissions() {
+        assert!(ApiKeyScope::Admin.allows_permission("roles:manage"));
+
+        assert!(ApiKeyScope::ReadOnly.allows_permission("users:read"));
+        assert!(!ApiKeyScope::ReadOnly.allows_permission("erc:issue"));
+
+        assert!(ApiKeyScope::Ingest.allows_permission("energy:submit"));
+        assert!(!ApiKeyScope::Ingest.allows_permission("users:read"));
+    }
+
+    #[test]
+    fn test_scopes_restrict_unguarded_routes() {
+        assert!(ApiKeyScope::ReadOnly.allows_request(&Method::GET, "/trading/orders"));
+        assert!(!ApiKeyScope::ReadOnly.allows_request(&Method::POST, "/trading/orders"));
+
+        assert!(ApiKeyScope::Ingest.allows_request(&Method::POST, "/meters/readings"));
+        assert!(!ApiKeyScope::Ingest.allows_request(&Method::POST, "/trading/orders"));
+
+        assert!(ApiKeyScope::Admin.allows_request(&Method::DELETE, "/admin/roles/1"));
+    }
+
+    #[test]
+    fn test_scope_names_round_trip() {
+        for scope in [ApiKeyScope::Ingest, ApiKeyScope::Admin, ApiKeyScope::ReadOnly] {
+            assert_eq!(ApiKeyScope::try_from(scope.as_str().to_string()), Ok(scope));
+            assert_eq!(serde_json::to_value(scope).unwrap(), scope.as_str());
+        }
+        assert!(ApiKeyScope::try_from("write".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_rate_windows_are_per_minute() {
+        let id = Uuid::nil();
+        assert_eq!(rate_key(id, 120), rate_key(id, 179));
+        assert_ne!(rate_key(id, 179), rate_key(id, 180));
+    }
+}
@@ -4,12 +4,17 @@ use std::env;
 
 use crate::auth::Claims;
 use crate::error::{ApiError, Result};
+use crate::utils::clock::{SharedClock, SystemClock};
+
+/// Seconds of clock difference tolerated between the gateway and whoever issued a token
+pub const CLOCK_SKEW_LEEWAY_SECS: i64 = 60;
 
 #[derive(Clone)]
 pub struct JwtService {
     encoding_key: EncodingKey,
     decoding_key: DecodingKey,
     validation: Validation,
+    clock: SharedClock,
 }
 
 impl JwtService {
@@ -22,14 +27,26 @@ impl JwtService {
         
         let mut validation = Validation::new(Algorithm::HS256);
         validation.set_issuer(&["api-gateway"]);
-        validation.validate_exp = true;
+        // Expiry is checked against the injected clock rather than the system time
+        validation.validate_exp = false;
         
         Ok(Self {
             encoding_key,
             decoding_key,
             validation,
+            clock: SystemClock::shared(),
         })
     }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Claims for a new token issued now
+    pub fn issue_claims(&self, user_id: Uuid, username: String, role: String, department: String) -> Claims {
+        Claims::issued_at(user_id, username, role, department, self.clock.now())
+    }
     
     pub fn encode_token(&self, claims: &Claims) -> Result<String> {
         let header = Header::new(Algorithm::HS256);
@@ -39,46 +56,62 @@ impl JwtService {
     }
     
     pub fn decode_token(&self, token: &str) -> Result<Claims> {
+        // Every decode failure is the caller's token being unusable, never a server error
         let token_data = decode::<Claims>(token, &self.decoding_key, &self.validation)
             .map_err(|e| match e.kind() {
                 jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
                     ApiError::Unauthorized("Token has expired".to_string())
                 }
-                jsonwebtoken::errors::ErrorKind::InvalidToken => {
-                    ApiError::Unauthorized("Invalid token".to_string())
-                }
                 jsonwebtoken::errors::ErrorKind::InvalidSignature => {
                     ApiError::Unauthorized("Invalid token signature".to_string())
                 }
-                _ => ApiError::Internal(format!("JWT decode error: {}", e)),
+                _ => ApiError::Unauthorized("Invalid token".to_string()),
             })?;
+
+        let leeway = chrono::Duration::seconds(CLOCK_SKEW_LEEWAY_SECS);
+        let now = self.clock.now();
+        if token_data.claims.is_expired_at(now - leeway) {
+            return Err(ApiError::Unauthorized("Token has expired".to_string()));
+        }
+        if token_data.claims.iat > (now + leeway).timestamp() {
+            return Err(ApiError::Unauthorized("Token is not valid yet".to_string()));
+        }
         
         Ok(token_data.claims)
     }
     
     pub fn validate_token(&self, token: &str) -> Result<bool> {
-        match self.decode_token(token) {
-            Ok(claims) => Ok(!claims.is_expired()),
-            Err(_) => Ok(false),
-        }
+        Ok(self.decode_token(token).is_ok())
     }
     
     pub fn refresh_token(&self, old_token: &str) -> Result<String> {
         let claims = self.decode_token(old_token)?;
         
         // Create new claims with extended expiration
-        let new_claims = Claims::new(
-            claims.sub,
-            claims.username,
-            claims.role,
-            claims.department,
-        );
+        let new_claims = self
+            .issue_claims(claims.sub, claims.username, claims.role, claims.department)
+            .with_tenant(claims.tenant);
         
         self.encode_token(&new_claims)
     }
 }
 
+/// Length of the public lookup prefix embedded in every API key
+pub const API_KEY_PREFIX_LEN: usize = 12;
+
+/// Freshly generated API key; `key` is shown to its owner once and never stored
+#[derive(Debug, Clone)]
+pub struct GeneratedApiKey {
+    pub key: String,
+    pub prefix: String,
+    pub salt: String,
+    pub key_hash: String,
+}
+
 /// API Key service for AMI systems
+///
+/// Keys look like `ak_<prefix>_<secret>`. The prefix identifies the key's row; only a hash
+/// of the whole key salted per key and peppered with `API_KEY_SECRET` is stored.
 #[derive(Clone)]
 pub struct ApiKeyService {
     secret: String,
@@ -92,32 +125,63 @@ impl ApiKeyService {
         Ok(Self { secret })
     }
     
-    pub fn generate_key(&self, _name: &str, _permissions: Vec<String>) -> Result<(String, String)> {
-        let key = format!("ak_{}", Uuid::new_v4().to_string().replace('-', ""));
-        let key_hash = self.hash_key(&key)?;
-        
-        Ok((key, key_hash))
+    pub fn generate_key(&self) -> GeneratedApiKey {
+        let prefix = random_hex(API_KEY_PREFIX_LEN / 2);
+        let key = format!("ak_{}_{}", prefix, random_hex(32));
+        let salt = random_hex(16);
+        let key_hash = self.hash_key(&key, &salt);
+
+        GeneratedApiKey {
+            key,
+            prefix,
+            salt,
+            key_hash,
+        }
+    }
+
+    /// Lookup prefix of a well-formed key
+    pub fn key_prefix(key: &str) -> Option<&str> {
+        let (prefix, secret) = key.strip_prefix("ak_")?.split_once('_')?;
+        (prefix.len() == API_KEY_PREFIX_LEN && !secret.is_empty()).then_some(prefix)
     }
     
-    pub fn verify_key(&self, key: &str, stored_hash: &str) -> Result<bool> {
-        let computed_hash = self.hash_key(key)?;
-        Ok(computed_hash == stored_hash)
+    pub fn verify_key(&self, key: &str, salt: &str, stored_hash: &str) -> bool {
+        let computed_hash = self.hash_key(key, salt);
+
+        // Compare without short-circuiting so timing does not reveal matching bytes
+        computed_hash.len() == stored_hash.len()
+            && computed_hash
+                .bytes()
+                .zip(stored_hash.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
     }
     
-    fn hash_key(&self, key: &str) -> Result<String> {
+    fn hash_key(&self, key: &str, salt: &str) -> String {
         use sha2::{Sha256, Digest};
         
         let mut hasher = Sha256::new();
+        hasher.update(salt.as_bytes());
         hasher.update(key.as_bytes());
         hasher.update(self.secret.as_bytes());
         
-        Ok(format!("{:x}", hasher.finalize()))
+        format!("{:x}", hasher.finalize())
     }
 }
 
+fn random_hex(bytes: usize) -> String {
+    use rand::RngCore;
+
+    let mut buf = vec![0u8; bytes];
+    rand::thread_rng().fill_bytes(&mut buf);
+    buf.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::clock::SimulatedClock;
+    use chrono::{Duration, TimeZone, Utc};
     use std::env;
 
     fn setup_test_env() {
@@ -145,17 +209,127 @@ mod tests {
         assert_eq!(claims.role, decoded_claims.role);
     }
     
+    #[test]
+    fn test_jwt_expires_on_simulated_clock() {
+        setup_test_env();
+
+        let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2024, 9, 23, 8, 0, 0).unwrap());
+        let jwt_service = JwtService::new().unwrap().with_clock(clock.shared());
+        let claims = jwt_service.issue_claims(
+            Uuid::new_v4(),
+            "test_user".to_string(),
+            "student".to_string(),
+            "engineering".to_string(),
+        );
+        let token = jwt_service.encode_token(&claims).unwrap();
+
+        clock.advance(Duration::hours(23));
+        assert!(jwt_service.validate_token(&token).unwrap());
+        let refreshed = jwt_service.refresh_token(&token).unwrap();
+
+        clock.advance(Duration::hours(2));
+        assert!(!jwt_service.validate_token(&token).unwrap());
+        assert!(matches!(jwt_service.decode_token(&token), Err(ApiError::Unauthorized(_))));
+
+        // The refreshed token was issued 23 hours in and is still valid
+        assert!(jwt_service.validate_token(&refreshed).unwrap());
+    }
+
+    #[test]
+    fn test_jwt_tolerates_bounded_clock_skew() {
+        setup_test_env();
+
+        let issued = Utc.with_ymd_and_hms(2024, 9, 23, 8, 0, 0).unwrap();
+        let clock = SimulatedClock::new(issued);
+        let jwt_service = JwtService::new().unwrap().with_clock(clock.shared());
+        let claims = jwt_service.issue_claims(
+            Uuid::new_v4(),
+            "test_user".to_string(),
+            "student".to_string(),
+            "engineering".to_string(),
+        );
+        let token = jwt_service.encode_token(&claims).unwrap();
+
+        // Issued by a node whose clock runs slightly ahead
+        clock.set(issued - Duration::seconds(CLOCK_SKEW_LEEWAY_SECS));
+        assert!(jwt_service.validate_token(&token).unwrap());
+        clock.set(issued - Duration::seconds(CLOCK_SKEW_LEEWAY_SECS + 1));
+        assert!(!jwt_service.validate_token(&token).unwrap());
+
+        let expiry = issued + Duration::hours(24);
+        clock.set(expiry + Duration::seconds(CLOCK_SKEW_LEEWAY_SECS));
+        assert!(jwt_service.validate_token(&token).unwrap());
+        clock.set(expiry + Duration::seconds(CLOCK_SKEW_LEEWAY_SECS + 1));
+        assert!(matches!(jwt_service.decode_token(&token), Err(ApiError::Unauthorized(_))));
+    }
+
+    #[test]
+    fn test_jwt_rejects_tampered_tokens() {
+        setup_test_env();
+
+        let jwt_service = JwtService::new().unwrap();
+        let claims = jwt_service.issue_claims(
+            Uuid::new_v4(),
+            "test_user".to_string(),
+            "student".to_string(),
+            "engineering".to_string(),
+        );
+        let token = jwt_service.encode_token(&claims).unwrap();
+        let parts: Vec<&str> = token.split('.').collect();
+
+        // Payload promoted to admin but signed with the original signature
+        let mut elevated = claims.clone();
+        elevated.role = "admin".to_string();
+        let forged_payload = jwt_service.encode_token(&elevated).unwrap();
+        let forged = format!("{}.{}.{}", parts[0], forged_payload.split('.').nth(1).unwrap(), parts[2]);
+        assert!(matches!(jwt_service.decode_token(&forged), Err(ApiError::Unauthorized(_))));
+
+        // Signed with another secret
+        let foreign = encode(
+            &Header::new(Algorithm::HS256),
+            &elevated,
+            &EncodingKey::from_secret(b"some_other_secret"),
+        )
+        .unwrap();
+        assert!(matches!(jwt_service.decode_token(&foreign), Err(ApiError::Unauthorized(_))));
+
+        // Unsigned tokens and garbage are rejected as unauthorized, not as server errors
+        let unsigned = format!("{}.{}.", parts[0], parts[1]);
+        for token in [unsigned.as_str(), "not.a.token", ""] {
+            assert!(matches!(jwt_service.decode_token(token), Err(ApiError::Unauthorized(_))));
+        }
+    }
+    
     #[test]
     fn test_api_key_generation() {
         setup_test_env();
         
         let api_key_service = ApiKeyService::new().unwrap();
-        let (key, hash) = api_key_service
-            .generate_key("test_ami", vec!["energy:submit".to_string()])
-            .unwrap();
+        let generated = api_key_service.generate_key();
         
-        assert!(key.starts_with("ak_"));
-        assert!(api_key_service.verify_key(&key, &hash).unwrap());
-        assert!(!api_key_service.verify_key("wrong_key", &hash).unwrap());
+        assert!(generated.key.starts_with("ak_"));
+        assert_eq!(ApiKeyService::key_prefix(&generated.key), Some(generated.prefix.as_str()));
+        assert!(api_key_service.verify_key(&generated.key, &generated.salt, &generated.key_hash));
+        assert!(!api_key_service.verify_key("wrong_key", &generated.salt, &generated.key_hash));
+    }
+
+    #[test]
+    fn test_api_keys_are_salted_per_key() {
+        setup_test_env();
+
+        let api_key_service = ApiKeyService::new().unwrap();
+        let first = api_key_service.generate_key();
+        let second = api_key_service.generate_key();
+
+        assert_ne!(first.prefix, second.prefix);
+        assert_ne!(first.salt, second.salt);
+        // The same key hashes differently under another key's salt
+        assert!(!api_key_service.verify_key(&first.key, &second.salt, &first.key_hash));
+        assert_ne!(api_key_service.hash_key(&first.key, &second.salt), first.key_hash);
+
+        assert_eq!(ApiKeyService::key_prefix("ak_0123456789ab_secret"), Some("0123456789ab"));
+        assert_eq!(ApiKeyService::key_prefix("ak_short_secret"), None);
+        assert_eq!(ApiKeyService::key_prefix("ak_0123456789ab_"), None);
+        assert_eq!(ApiKeyService::key_prefix("engineering-department-api-key"), None);
     }
 }
\ No newline at end of file
@@ -1,23 +1,47 @@
 use axum::{
-    extract::{Request, State},
-    http::{header::AUTHORIZATION, StatusCode},
+    extract::{OriginalUri, Query, Request, State},
+    http::header::{AUTHORIZATION, UPGRADE},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
 use axum::async_trait;
+use serde::Deserialize;
 
+use crate::auth::api_keys::{ApiKeyPrincipal, ApiKeyStore, API_KEY_HEADER};
+use crate::auth::permissions::PermissionService;
 use crate::auth::{Claims, Role};
 use crate::error::{ApiError, Result};
+use crate::middleware::audit::AuditActor;
+use crate::services::tenants::TenantStore;
 use crate::AppState;
 
 /// JWT Authentication middleware
+///
+/// Requests may instead carry an API key in the `X-API-Key` header. Either way, requests of
+/// users in a tenant count against the tenant's per-minute limit.
 pub async fn auth_middleware(
     State(state): State<AppState>,
     mut request: Request,
     next: Next,
 ) -> Response {
+    if let Some(key) = request.headers().get(API_KEY_HEADER) {
+        let key = key.to_str().unwrap_or_default().to_string();
+        let authenticated = match authenticate_api_key(&state, &key, &mut request).await {
+            Ok(actor) => check_tenant_rate_limit(&state, request_tenant(&request)).await.map(|()| actor),
+            Err(e) => Err(e),
+        };
+        return match authenticated {
+            Ok(actor) => {
+                let mut response = next.run(request).await;
+                response.extensions_mut().insert(actor);
+                response
+            }
+            Err(e) => e.into_response(),
+        };
+    }
+
     let auth_header = request
         .headers()
         .get(AUTHORIZATION)
@@ -25,27 +49,87 @@ pub async fn auth_middleware(
 
     let token = match auth_header {
         Some(auth_value) if auth_value.starts_with("Bearer ") => {
-            &auth_value[7..] // Remove "Bearer " prefix
-        }
-        _ => {
-            return Response::builder()
-                .status(StatusCode::UNAUTHORIZED)
-                .body("Missing or invalid Authorization header".into())
-                .unwrap();
+            auth_value[7..].to_string() // Remove "Bearer " prefix
         }
+        _ => match websocket_token(&request) {
+            Some(token) => token,
+            None => {
+                return ApiError::Unauthorized("Missing or invalid Authorization header".to_string()).into_response();
+            }
+        },
     };
 
-    match state.jwt_service.decode_token(token) {
+    match state.jwt_service.decode_token(&token) {
         Ok(claims) => {
+            let actor = AuditActor {
+                user_id: claims.sub,
+                api_key_id: None,
+            };
             // Add claims to request extensions for use in handlers
+            let tenant = claims.tenant.clone();
             request.extensions_mut().insert(claims);
-            next.run(request).await
+            if let Err(e) = check_tenant_rate_limit(&state, tenant).await {
+                return e.into_response();
+            }
+            let mut response = next.run(request).await;
+            response.extensions_mut().insert(actor);
+            response
         }
-        Err(_) => Response::builder()
-            .status(StatusCode::UNAUTHORIZED)
-            .body("Invalid or expired token".into())
-            .unwrap(),
+        Err(_) => ApiError::Unauthorized("Invalid or expired token".to_string()).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct WebsocketToken {
+    access_token: String,
+}
+
+/// Token of a websocket handshake in the `access_token` query parameter, since browsers
+/// cannot set headers on one
+fn websocket_token(request: &Request) -> Option<String> {
+    let upgrade = request.headers().get(UPGRADE)?.to_str().ok()?;
+    if !upgrade.eq_ignore_ascii_case("websocket") {
+        return None;
     }
+    Query::<WebsocketToken>::try_from_uri(request.uri())
+        .ok()
+        .map(|query| query.0.access_token)
+}
+
+/// Verify an API key, enforce its scope and rate limit, and attach what it acts as
+async fn authenticate_api_key(state: &AppState, key: &str, request: &mut Request) -> Result<AuditActor> {
+    // Nested routers see a stripped path; scopes are defined on the full one
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let (principal, claims) = ApiKeyStore::from_state(state)
+        .authorize(key, request.method(), &path)
+        .await?;
+
+    let actor = AuditActor {
+        user_id: claims.sub,
+        api_key_id: Some(principal.id),
+    };
+    request.extensions_mut().insert(claims);
+    request.extensions_mut().insert(principal);
+    Ok(actor)
+}
+
+/// Count an authenticated request against the budget its user's tenant shares
+///
+/// Takes the tenant rather than the request, whose body is not Sync and so cannot be held
+/// across the Redis round trip.
+async fn check_tenant_rate_limit(state: &AppState, tenant: Option<String>) -> Result<()> {
+    match tenant {
+        Some(tenant) => TenantStore::from_state(state).check_rate_limit(&tenant).await,
+        None => Ok(()),
+    }
+}
+
+fn request_tenant(request: &Request) -> Option<String> {
+    request.extensions().get::<Claims>().and_then(|claims| claims.tenant.clone())
 }
 
 /// Role-based authorization middleware for admin access
@@ -57,23 +141,57 @@ pub async fn require_admin_role(
     let user_role = match Role::from_str(&user.0.role) {
         Ok(role) => role,
         Err(_) => {
-            return Response::builder()
-                .status(StatusCode::FORBIDDEN)
-                .body("Invalid user role".into())
-                .unwrap();
+            return ApiError::Authorization("Invalid user role".to_string()).into_response();
         }
     };
 
     if user_role == Role::Admin {
         next.run(request).await
     } else {
-        Response::builder()
-            .status(StatusCode::FORBIDDEN)
-            .body("Admin access required".into())
-            .unwrap()
+        ApiError::Authorization("Admin access required".to_string()).into_response()
+    }
+}
+
+/// State for [`require_permission`]: the permission a route requires
+#[derive(Clone)]
+pub struct PermissionGuard {
+    state: AppState,
+    permission: &'static str,
+}
+
+impl PermissionGuard {
+    pub fn new(state: &AppState, permission: &'static str) -> Self {
+        Self {
+            state: state.clone(),
+            permission,
+        }
     }
 }
 
+/// Permission-based route guard; layer inside `auth_middleware`
+pub async fn require_permission(
+    State(guard): State<PermissionGuard>,
+    user: AuthenticatedUser,
+    request: Request,
+    next: Next,
+) -> Result<Response> {
+    if let Some(principal) = request.extensions().get::<ApiKeyPrincipal>() {
+        if !principal.scope.allows_permission(guard.permission) {
+            return Err(ApiError::Authorization(format!(
+                "API key scope {} does not grant {}",
+                principal.scope.as_str(),
+                guard.permission
+            )));
+        }
+    }
+
+    PermissionService::from_state(&guard.state)
+        .require(user.0.sub, guard.permission)
+        .await?;
+
+    Ok(next.run(request).await)
+}
+
 /// Extractor for authenticated user claims
 pub struct AuthenticatedUser(pub Claims);
 
@@ -94,54 +212,6 @@ where
     }
 }
 
-/// Verify API key against database
-async fn verify_api_key(state: &AppState, key: &str) -> Result<crate::auth::ApiKey> {
-    let query = "
-        SELECT id, key_hash, name, permissions, is_active, created_at, last_used_at
-        FROM api_keys
-        WHERE is_active = true
-    ";
-
-    let api_keys = sqlx::query_as::<_, ApiKeyRow>(query)
-        .fetch_all(&state.db)
-        .await
-        .map_err(|e| ApiError::Internal(format!("Database error: {}", e)))?;
-
-    for api_key_row in api_keys {
-        if state.api_key_service.verify_key(key, &api_key_row.key_hash)? {
-            // Update last_used_at
-            let _ = sqlx::query("UPDATE api_keys SET last_used_at = NOW() WHERE id = $1")
-                .bind(api_key_row.id)
-                .execute(&state.db)
-                .await;
-
-            return Ok(crate::auth::ApiKey {
-                id: api_key_row.id,
-                key_hash: api_key_row.key_hash,
-                name: api_key_row.name,
-                permissions: serde_json::from_value(api_key_row.permissions)
-                    .unwrap_or_default(),
-                is_active: api_key_row.is_active,
-                created_at: api_key_row.created_at,
-                last_used_at: api_key_row.last_used_at,
-            });
-        }
-    }
-
-    Err(ApiError::Unauthorized("Invalid API key".to_string()))
-}
-
-#[derive(sqlx::FromRow)]
-struct ApiKeyRow {
-    id: uuid::Uuid,
-    key_hash: String,
-    name: String,
-    permissions: serde_json::Value,
-    is_active: bool,
-    created_at: chrono::DateTime<chrono::Utc>,
-    last_used_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,9 +2,13 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
+pub mod api_keys;
 pub mod jwt;
 pub mod password;
 pub mod middleware;
+pub mod oidc;
+pub mod permissions;
+pub mod tenant;
 
 /// User claims for JWT tokens
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,11 +20,18 @@ pub struct Claims {
     pub exp: i64,           // Expiration time
     pub iat: i64,           // Issued at
     pub iss: String,        // Issuer
+    /// Building or faculty the user belongs to; absent for users outside every tenant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
 }
 
 impl Claims {
     pub fn new(user_id: Uuid, username: String, role: String, department: String) -> Self {
-        let now = Utc::now();
+        Self::issued_at(user_id, username, role, department, Utc::now())
+    }
+
+    /// Claims issued at `now`, as read from the service clock
+    pub fn issued_at(user_id: Uuid, username: String, role: String, department: String, now: DateTime<Utc>) -> Self {
         let exp = now + chrono::Duration::hours(24); // 24 hour expiration
         
         Self {
@@ -31,11 +42,21 @@ impl Claims {
             exp: exp.timestamp(),
             iat: now.timestamp(),
             iss: "api-gateway".to_string(),
+            tenant: None,
         }
     }
+
+    pub fn with_tenant(mut self, tenant: Option<String>) -> Self {
+        self.tenant = tenant;
+        self
+    }
     
     pub fn is_expired(&self) -> bool {
-        Utc::now().timestamp() > self.exp
+        self.is_expired_at(Utc::now())
+    }
+
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        now.timestamp() > self.exp
     }
     
     pub fn has_role(&self, required_role: &str) -> bool {
@@ -47,20 +68,25 @@ impl Claims {
     }
 }
 
-/// API Key for AMI systems
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// API Key for AMI systems and integrations; the key itself is never stored
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct ApiKey {
     pub id: Uuid,
-    pub key_hash: String,
     pub name: String,
-    pub permissions: Vec<String>,
+    /// Public part of the key identifying it in logs and listings
+    pub key_prefix: Option<String>,
+    #[sqlx(try_from = "String")]
+    pub scope: api_keys::ApiKeyScope,
+    pub rate_limit_per_minute: i32,
     pub is_active: bool,
+    pub created_by: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
 }
 
 /// Secure authentication response (excludes sensitive user data)
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct SecureAuthResponse {
     pub access_token: String,
     pub token_type: String,
@@ -81,7 +107,7 @@ pub struct UserInfo {
 }
 
 /// Secure user information for login responses (excludes sensitive data)
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct SecureUserInfo {
     pub username: String,
     pub email: String,
@@ -180,6 +206,7 @@ impl Role {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
 
     #[test]
     fn test_role_permissions() {
@@ -208,4 +235,41 @@ mod tests {
         assert!(claims.has_role("student"));
         assert!(!claims.has_role("admin"));
     }
+
+    #[test]
+    fn test_claims_expire_after_24_hours() {
+        let issued = Utc.with_ymd_and_hms(2024, 9, 23, 8, 0, 0).unwrap();
+        let claims = Claims::issued_at(
+            Uuid::new_v4(),
+            "test_user".to_string(),
+            "student".to_string(),
+            "engineering".to_string(),
+            issued,
+        );
+
+        assert_eq!(claims.iat, issued.timestamp());
+        assert!(!claims.is_expired_at(issued + chrono::Duration::hours(24)));
+        assert!(claims.is_expired_at(issued + chrono::Duration::hours(24) + chrono::Duration::seconds(1)));
+    }
+
+    #[test]
+    fn test_claims_carry_an_optional_tenant() {
+        let claims = Claims::new(
+            Uuid::new_v4(),
+            "test_user".to_string(),
+            "student".to_string(),
+            "engineering".to_string(),
+        );
+        let mut value = serde_json::to_value(&claims).unwrap();
+        assert!(value.get("tenant").is_none());
+
+        // Tokens issued before tenants existed still decode
+        value.as_object_mut().unwrap().remove("tenant");
+        assert_eq!(serde_json::from_value::<Claims>(value).unwrap().tenant, None);
+
+        let claims = claims.with_tenant(Some("eng".to_string()));
+        let value = serde_json::to_value(&claims).unwrap();
+        assert_eq!(value["tenant"], "eng");
+        assert_eq!(serde_json::from_value::<Claims>(value).unwrap().tenant.as_deref(), Some("eng"));
+    }
 }
\ No newline at end of file
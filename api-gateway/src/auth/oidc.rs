@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use rand::RngCore;
+use redis::AsyncCommands;
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::auth::jwt::CLOCK_SKEW_LEEWAY_SECS;
+use crate::database::redis_pool::RedisPool;
+use crate::error::{ApiError, Result};
+use crate::AppState;
+
+const PENDING_KEY_PREFIX: &str = "oidc:login:";
+
+/// Seconds a user has to finish signing in at the provider
+const PENDING_LOGIN_TTL: u64 = 600;
+
+const SCOPES: &str = "openid profile email";
+
+/// Account roles the provider can set, from least to most privileged; every other mapped role
+/// is granted as an assigned role
+pub const ACCOUNT_ROLES: [&str; 3] = ["student", "faculty", "admin"];
+
+/// Signature algorithms accepted on ID tokens; HMAC ones are refused since anyone holding the
+/// client secret could mint them
+const ID_TOKEN_ALGORITHMS: [Algorithm; 8] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+];
+
+/// Unguessable URL-safe token, used for the state, nonce and PKCE code verifier
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// S256 PKCE code challenge of `verifier` (RFC 7636)
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Gateway roles granted to members of provider groups, from `OIDC_GROUP_ROLES`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroupRoles(Vec<(String, String)>);
+
+impl GroupRoles {
+    /// Parse comma-separated `group=role` pairs; a group may map to several roles
+    pub fn parse(spec: &str) -> Result<Self> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((group, role)) if !group.trim().is_empty() && !role.trim().is_empty() => {
+                    Ok((group.trim().to_string(), role.trim().to_lowercase()))
+                }
+                _ => Err(ApiError::Configuration(format!(
+                    "OIDC_GROUP_ROLES entry \"{}\" is not group=role",
+                    pair
+                ))),
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+
+    /// Every role the mapping can grant; the provider is authoritative for these
+    pub fn mapped(&self) -> Vec<&str> {
+        let mut roles: Vec<&str> = self.0.iter().map(|(_, role)| role.as_str()).collect();
+        roles.sort_unstable();
+        roles.dedup();
+        roles
+    }
+
+    /// Roles granted to a member of `groups`
+    pub fn granted(&self, groups: &[String]) -> Vec<&str> {
+        let mut roles: Vec<&str> = self
+            .0
+            .iter()
+            .filter(|(group, _)| groups.contains(group))
+            .map(|(_, role)| role.as_str())
+            .collect();
+        roles.sort_unstable();
+        roles.dedup();
+        roles
+    }
+
+    /// Account role for a member of `groups`: the most privileged mapped account role they
+    /// hold, `student` without one, or `None` when the mapping sets no account roles
+    pub fn account_role(&self, groups: &[String]) -> Option<&'static str> {
+        let mapped = self.mapped();
+        if !ACCOUNT_ROLES.iter().any(|role| mapped.contains(role)) {
+            return None;
+        }
+        let granted = self.granted(groups);
+        ACCOUNT_ROLES
+            .iter()
+            .rev()
+            .find(|role| granted.contains(role))
+            .or(ACCOUNT_ROLES.first())
+            .copied()
+    }
+}
+
+/// The provider's discovery document
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+/// What the callback needs from the login that sent the user to the provider, keyed by its state
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingLogin {
+    nonce: String,
+    code_verifier: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// Verified ID token claims
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    /// Provider-specific claims, among them the groups claim
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl IdTokenClaims {
+    /// Groups listed in `claim`, which providers send as an array or a single string
+    pub fn groups(&self, claim: &str) -> Vec<String> {
+        match self.extra.get(claim) {
+            Some(serde_json::Value::Array(groups)) => groups
+                .iter()
+                .filter_map(|group| group.as_str().map(str::to_string))
+                .collect(),
+            Some(serde_json::Value::String(group)) => vec![group.clone()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Provider URL the user signs in at, sending them back to `redirect_uri` with a code
+fn authorization_url(
+    endpoint: &str,
+    client_id: &str,
+    redirect_uri: &str,
+    state: &str,
+    nonce: &str,
+    code_challenge: &str,
+) -> Result<Url> {
+    Url::parse_with_params(
+        endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", client_id),
+            ("redirect_uri", redirect_uri),
+            ("scope", SCOPES),
+            ("state", state),
+            ("nonce", nonce),
+            ("code_challenge", code_challenge),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|e| ApiError::ExternalService(format!("Invalid provider authorization endpoint: {}", e)))
+}
+
+/// Authorization code flow with PKCE against the university identity provider
+///
+/// The provider only vouches for who the user is; the gateway then issues its own tokens.
+#[derive(Clone)]
+pub struct OidcClient {
+    http: reqwest::Client,
+    redis: RedisPool,
+    issuer: String,
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_uri: String,
+    groups_claim: String,
+    group_roles: GroupRoles,
+}
+
+impl OidcClient {
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let config = &state.config;
+        let issuer = config
+            .oidc_issuer
+            .clone()
+            .ok_or_else(|| ApiError::NotFound("Single sign-on is not configured".to_string()))?;
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| ApiError::Configuration(format!("Failed to build OIDC client: {}", e)))?;
+
+        Ok(Self {
+            http,
+            redis: state.redis.clone(),
+            issuer,
+            client_id: config.oidc_client_id.clone(),
+            client_secret: config.oidc_client_secret.clone(),
+            redirect_uri: config
+                .oidc_redirect_uri
+                .clone()
+                .unwrap_or_else(|| format!("{}/auth/oidc/callback", config.public_base_url)),
+            groups_claim: config.oidc_groups_claim.clone(),
+            group_roles: GroupRoles::parse(&config.oidc_group_roles)?,
+        })
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    pub fn groups_claim(&self) -> &str {
+        &self.groups_claim
+    }
+
+    pub fn group_roles(&self) -> &GroupRoles {
+        &self.group_roles
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| ApiError::ExternalService(format!("Identity provider request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(ApiError::ExternalService(format!(
+                "Identity provider returned {} for {}",
+                response.status(),
+                url
+            )));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| ApiError::ExternalService(format!("Invalid identity provider response: {}", e)))
+    }
+
+    async fn discover(&self) -> Result<ProviderMetadata> {
+        let metadata: ProviderMetadata = self
+            .get_json(&format!("{}/.well-known/openid-configuration", self.issuer))
+            .await?;
+        if metadata.issuer.trim_end_matches('/') != self.issuer {
+            return Err(ApiError::ExternalService(format!(
+                "Identity provider reports issuer {}, expected {}",
+                metadata.issuer, self.issuer
+            )));
+        }
+        Ok(metadata)
+    }
+
+    /// Start a sign-in, returning the provider URL to send the user to
+    pub async fn begin_login(&self) -> Result<Url> {
+        let metadata = self.discover().await?;
+        let state = random_token();
+        let pending = PendingLogin {
+            nonce: random_token(),
+            code_verifier: random_token(),
+        };
+        let url = authorization_url(
+            &metadata.authorization_endpoint,
+            &self.client_id,
+            &self.redirect_uri,
+            &state,
+            &pending.nonce,
+            &pkce_challenge(&pending.code_verifier),
+        )?;
+
+        let mut conn = self.redis.get().await?;
+        let pending = serde_json::to_string(&pending).expect("pending logins serialize");
+        conn.set_ex::<_, _, ()>(format!("{}{}", PENDING_KEY_PREFIX, state), pending, PENDING_LOGIN_TTL)
+            .await?;
+
+        Ok(url)
+    }
+
+    /// Finish the sign-in `state` belongs to, exchanging `code` for a verified ID token
+    ///
+    /// Each state is accepted once, so a replayed callback fails.
+    pub async fn complete_login(&self, code: &str, state: &str) -> Result<IdTokenClaims> {
+        let mut conn = self.redis.get().await?;
+        let pending: Option<String> = redis::cmd("GETDEL")
+            .arg(format!("{}{}", PENDING_KEY_PREFIX, state))
+            .query_async(&mut conn)
+            .await?;
+        let pending: PendingLogin = pending
+            .as_deref()
+            .and_then(|pending| serde_json::from_str(pending).ok())
+            .ok_or_else(|| ApiError::Unauthorized("Sign-in expired or was already completed".to_string()))?;
+
+        let metadata = self.discover().await?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("client_id", self.client_id.as_str()),
+            ("code_verifier", pending.code_verifier.as_str()),
+        ];
+        if let Some(secret) = &self.client_secret {
+            form.push(("client_secret", secret));
+        }
+        let response = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| ApiError::ExternalService(format!("Identity provider request failed: {}", e)))?;
+        match response.status() {
+            status if status.is_success() => {}
+            StatusCode::BAD_REQUEST => {
+                return Err(ApiError::Unauthorized(
+                    "Identity provider rejected the authorization code".to_string(),
+                ))
+            }
+            status => {
+                return Err(ApiError::ExternalService(format!(
+                    "Identity provider token endpoint returned {}",
+                    status
+                )))
+            }
+        }
+        let tokens: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| ApiError::ExternalService(format!("Invalid identity provider token response: {}", e)))?;
+
+        self.verify_id_token(&metadata, &tokens.id_token, &pending.nonce).await
+    }
+
+    /// Check the ID token's signature against the provider's keys, and that it was issued by
+    /// the provider to this client for this sign-in
+    async fn verify_id_token(&self, metadata: &ProviderMetadata, id_token: &str, nonce: &str) -> Result<IdTokenClaims> {
+        let header = decode_header(id_token)
+            .map_err(|e| ApiError::Unauthorized(format!("Malformed ID token: {}", e)))?;
+        if !ID_TOKEN_ALGORITHMS.contains(&header.alg) {
+            return Err(ApiError::Unauthorized(format!("ID token algorithm {:?} is not accepted", header.alg)));
+        }
+
+        let keys: JwkSet = self.get_json(&metadata.jwks_uri).await?;
+        let jwk = match &header.kid {
+            Some(kid) => keys.find(kid),
+            None if keys.keys.len() == 1 => keys.keys.first(),
+            None => None,
+        }
+        .ok_or_else(|| ApiError::Unauthorized("ID token is signed by an unknown key".to_string()))?;
+        let key = DecodingKey::from_jwk(jwk)
+            .map_err(|e| ApiError::ExternalService(format!("Unusable identity provider key: {}", e)))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.issuer, &metadata.issuer]);
+        validation.set_audience(&[&self.client_id]);
+        validation.leeway = CLOCK_SKEW_LEEWAY_SECS as u64;
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|e| ApiError::Unauthorized(format!("Invalid ID token: {}", e)))?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(ApiError::Unauthorized("ID token was not issued for this sign-in".to_string()));
+        }
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn groups(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_pkce_challenge_matches_rfc_7636() {
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        assert_eq!(random_token().len(), 43);
+        assert_ne!(random_token(), random_token());
+    }
+
+    #[test]
+    fn test_group_roles() {
+        let mapping = GroupRoles::parse(" eng-admins=admin, eng-staff=operator,eng-staff=Faculty ,eng-audit=auditor,").unwrap();
+        assert_eq!(mapping.mapped(), vec!["admin", "auditor", "faculty", "operator"]);
+        assert_eq!(mapping.granted(&groups(&["eng-staff", "library"])), vec!["faculty", "operator"]);
+        assert_eq!(mapping.account_role(&groups(&["eng-staff", "eng-admins"])), Some("admin"));
+        assert_eq!(mapping.account_role(&groups(&["eng-staff"])), Some("faculty"));
+        assert_eq!(mapping.account_role(&groups(&["eng-audit"])), Some("student"));
+
+        // Without account roles in the mapping, the account role is left alone
+        let assigned_only = GroupRoles::parse("eng-audit=auditor").unwrap();
+        assert_eq!(assigned_only.account_role(&groups(&["eng-audit"])), None);
+        assert_eq!(GroupRoles::parse("").unwrap(), GroupRoles::default());
+
+        assert!(GroupRoles::parse("eng-admins").is_err());
+        assert!(GroupRoles::parse("=admin").is_err());
+    }
+
+    #[test]
+    fn test_authorization_url_requests_code_with_pkce() {
+        let url = authorization_url(
+            "https://sso.example.ac.th/authorize?prompt=login",
+            "gridtokenx-gateway",
+            "https://gridtokenx.example.ac.th/auth/oidc/callback",
+            "state-1",
+            "nonce-1",
+            "challenge-1",
+        )
+        .unwrap();
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert_eq!(url.path(), "/authorize");
+        assert_eq!(params["prompt"], "login");
+        assert_eq!(params["response_type"], "code");
+        assert_eq!(params["redirect_uri"], "https://gridtokenx.example.ac.th/auth/oidc/callback");
+        assert_eq!(params["scope"], "openid profile email");
+        assert_eq!(params["state"], "state-1");
+        assert_eq!(params["nonce"], "nonce-1");
+        assert_eq!(params["code_challenge"], "challenge-1");
+        assert_eq!(params["code_challenge_method"], "S256");
+    }
+
+    #[test]
+    fn test_groups_claim_as_array_or_string() {
+        let claims: IdTokenClaims = serde_json::from_value(serde_json::json!({
+            "iss": "https://sso.example.ac.th",
+            "sub": "u-1",
+            "groups": ["eng-staff", 7, "eng-audit"],
+            "department": "eng-admins",
+        }))
+        .unwrap();
+        assert_eq!(claims.groups("groups"), groups(&["eng-staff", "eng-audit"]));
+        assert_eq!(claims.groups("department"), groups(&["eng-admins"]));
+        assert!(claims.groups("roles").is_empty());
+        assert!(!claims.email_verified);
+    }
+}
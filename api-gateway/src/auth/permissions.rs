@@ -0,0 +1,220 @@
+use redis::AsyncCommands;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::database::redis_pool::RedisPool;
+use crate::error::{ApiError, Result};
+use crate::services::metrics::record_cache_lookup;
+use crate::AppState;
+
+const CACHE_KEY_PREFIX: &str = "permissions:user:";
+
+/// Permission patterns granted to a user through their system role, the prosumer role every
+/// non-admin account holds, and their assigned roles
+///
+/// Inactive users have no permissions.
+const USER_PERMISSIONS_QUERY: &str = "
+    SELECT DISTINCT rp.permission
+    FROM users u
+    JOIN roles r ON r.name = u.role::text
+        OR (r.name = 'prosumer' AND u.role <> 'admin')
+        OR r.id IN (SELECT role_id FROM user_roles WHERE user_id = u.id)
+    JOIN role_permissions rp ON rp.role_id = r.id
+    WHERE u.id = $1 AND u.is_active = true
+";
+
+/// Whether a granted permission pattern covers `permission`
+///
+/// Permissions are `resource:action` strings. A `*` segment matches any single segment and a
+/// trailing `*` matches everything after it, so `*` alone grants every permission and `*:read`
+/// grants read access to every resource.
+pub fn permission_matches(pattern: &str, permission: &str) -> bool {
+    let mut pattern_parts = pattern.split(':').peekable();
+    let mut parts = permission.split(':');
+
+    loop {
+        match (pattern_parts.next(), parts.next()) {
+            (Some("*"), Some(_)) if pattern_parts.peek().is_none() => return true,
+            (Some(expected), Some(actual)) if expected == "*" || expected == actual => continue,
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Whether `pattern` is a well-formed permission or permission pattern
+pub fn is_valid_permission_pattern(pattern: &str) -> bool {
+    pattern.len() <= 100
+        && pattern.split(':').all(|part| {
+            part == "*" || (!part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c == '_'))
+        })
+}
+
+/// Resolves user permissions from Postgres, cached in Redis
+#[derive(Clone)]
+pub struct PermissionService {
+    db: PgPool,
+    redis: RedisPool,
+    cache_ttl: u64,
+}
+
+impl PermissionService {
+    pub fn new(db: PgPool, redis: RedisPool, cache_ttl_secs: u64) -> Self {
+        Self {
+            db,
+            redis,
+            cache_ttl: cache_ttl_secs,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone(), state.redis.clone(), state.config.permission_cache_ttl)
+    }
+
+    /// Effective permission patterns of a user
+    pub async fn user_permissions(&self, user_id: Uuid) -> Result<Vec<String>> {
+        let cached = self.cached(user_id).await;
+        record_cache_lookup("permissions", cached.is_some());
+        if let Some(permissions) = cached {
+            return Ok(permissions);
+        }
+
+        let mut permissions: Vec<String> = sqlx::query_scalar(USER_PERMISSIONS_QUERY)
+            .bind(user_id)
+            .fetch_all(&self.db)
+            .await?;
+        permissions.sort();
+
+        self.store(user_id, &permissions).await;
+
+        Ok(permissions)
+    }
+
+    pub async fn has_permission(&self, user_id: Uuid, permission: &str) -> Result<bool> {
+        Ok(self
+            .user_permissions(user_id)
+            .await?
+            .iter()
+            .any(|pattern| permission_matches(pattern, permission)))
+    }
+
+    /// Fail with an authorization error unless the user holds `permission`
+    pub async fn require(&self, user_id: Uuid, permission: &str) -> Result<()> {
+        if self.has_permission(user_id, permission).await? {
+            Ok(())
+        } else {
+            Err(ApiError::Authorization(format!("Permission {} required", permission)))
+        }
+    }
+
+    /// Drop a user's cached permissions after their roles or status change
+    pub async fn invalidate_user(&self, user_id: Uuid) {
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            conn.del::<_, ()>(cache_key(user_id)).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to invalidate cached permissions for {}: {}", user_id, e);
+        }
+    }
+
+    /// Drop every cached permission set after a role's permissions change
+    pub async fn invalidate_all(&self) {
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            let keys: Vec<String> = {
+                let mut iter = conn.scan_match::<_, String>(format!("{}*", CACHE_KEY_PREFIX)).await?;
+                let mut keys = Vec::new();
+                while let Some(key) = iter.next_item().await {
+                    keys.push(key);
+                }
+                keys
+            };
+
+            if !keys.is_empty() {
+                conn.del::<_, ()>(keys).await?;
+            }
+            Ok::<_, redis::RedisError>(())
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to invalidate cached permissions: {}", e);
+        }
+    }
+
+    // The cache is best effort: Redis failures fall back to Postgres
+
+    async fn cached(&self, user_id: Uuid) -> Option<Vec<String>> {
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            conn.get::<_, Option<String>>(cache_key(user_id)).await
+        }
+        .await;
+
+        match result {
+            Ok(cached) => cached.and_then(|value| serde_json::from_str(&value).ok()),
+            Err(e) => {
+                tracing::warn!("Permission cache unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn store(&self, user_id: Uuid, permissions: &[String]) {
+        let Ok(value) = serde_json::to_string(permissions) else {
+            return;
+        };
+
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            conn.set_ex::<_, _, ()>(cache_key(user_id), value, self.cache_ttl).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to cache permissions for {}: {}", user_id, e);
+        }
+    }
+}
+
+fn cache_key(user_id: Uuid) -> String {
+    format!("{}{}", CACHE_KEY_PREFIX, user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_matches() {
+        assert!(permission_matches("users:read", "users:read"));
+        assert!(!permission_matches("users:read", "users:update"));
+        assert!(!permission_matches("users", "users:read"));
+        assert!(!permission_matches("users:read", "users"));
+
+        assert!(permission_matches("trading:*", "trading:create"));
+        assert!(!permission_matches("trading:*", "energy:read"));
+
+        assert!(permission_matches("*:read", "governance:read"));
+        assert!(!permission_matches("*:read", "users:update"));
+
+        assert!(permission_matches("*", "roles:manage"));
+        assert!(permission_matches("*", "analytics:system"));
+    }
+
+    #[test]
+    fn test_permission_pattern_validation() {
+        assert!(is_valid_permission_pattern("users:read"));
+        assert!(is_valid_permission_pattern("*:read"));
+        assert!(is_valid_permission_pattern("*"));
+        assert!(is_valid_permission_pattern("analytics:system"));
+
+        assert!(!is_valid_permission_pattern(""));
+        assert!(!is_valid_permission_pattern("users:"));
+        assert!(!is_valid_permission_pattern("Users:Read"));
+        assert!(!is_valid_permission_pattern("users:re*d"));
+    }
+}
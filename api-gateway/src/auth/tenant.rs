@@ -0,0 +1,170 @@
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use sqlx::PgPool;
+
+use crate::auth::api_keys::{ApiKeyPrincipal, ApiKeyScope};
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::permissions::PermissionService;
+use crate::auth::Claims;
+use crate::error::{ApiError, Result};
+use crate::AppState;
+
+/// Permission to see the data of every tenant
+pub const CAMPUS_PERMISSION: &str = "tenants:campus";
+
+/// Tenant owning a reading, resolved through its meter; for `energy_readings` rows
+pub const READING_TENANT: &str = "(SELECT m.tenant_id FROM meters m WHERE m.meter_id = energy_readings.meter_id)";
+
+/// Tenant a certificate was issued for; for certificate rows aliased `c`
+pub const CERTIFICATE_TENANT: &str =
+    "(SELECT t.tenant_id FROM erc_certificate_tenants t WHERE t.certificate_id = c.certificate_id)";
+
+/// Tenant of a statement's prosumer; for `settlement_statements` rows aliased `s`
+pub const STATEMENT_TENANT: &str = "(SELECT u.tenant_id FROM users u WHERE u.id = s.user_id)";
+
+/// Whose readings, certificates, orders and statements a request may see
+///
+/// Holders of `tenants:campus` see every tenant. Everyone else sees their own tenant's rows,
+/// and users outside every tenant only rows belonging to none. Rows of other tenants are
+/// reported as not found rather than forbidden.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TenantScope {
+    Campus,
+    Tenant(Option<String>),
+}
+
+impl TenantScope {
+    /// Scope of the user `claims` were issued to
+    pub async fn of(state: &AppState, claims: &Claims) -> Result<Self> {
+        if PermissionService::from_state(state)
+            .has_permission(claims.sub, CAMPUS_PERMISSION)
+            .await?
+        {
+            Ok(Self::Campus)
+        } else {
+            Ok(Self::Tenant(claims.tenant.clone()))
+        }
+    }
+
+    pub fn is_campus(&self) -> bool {
+        matches!(self, Self::Campus)
+    }
+
+    /// Tenant rows are narrowed to, `None` when campus-wide or outside every tenant
+    pub fn tenant(&self) -> Option<&str> {
+        match self {
+            Self::Campus => None,
+            Self::Tenant(tenant) => tenant.as_deref(),
+        }
+    }
+
+    /// Whether a row owned by `tenant` is visible
+    pub fn allows(&self, tenant: Option<&str>) -> bool {
+        match self {
+            Self::Campus => true,
+            Self::Tenant(own) => own.as_deref() == tenant,
+        }
+    }
+
+    /// SQL condition admitting rows whose tenant is `tenant_expr`, with [`Self::is_campus`]
+    /// bound as `$bind` and [`Self::tenant`] as the next parameter
+    pub fn condition(tenant_expr: &str, bind: usize) -> String {
+        format!(
+            "(${}::boolean OR {} IS NOT DISTINCT FROM ${}::varchar)",
+            bind,
+            tenant_expr,
+            bind + 1
+        )
+    }
+
+    /// Whether a meter's readings are visible; meters the gateway did not provision belong
+    /// to no tenant
+    pub async fn sees_meter(&self, db: &PgPool, meter_id: &str) -> Result<bool> {
+        if self.is_campus() {
+            return Ok(true);
+        }
+        let tenant: Option<String> = sqlx::query_scalar("SELECT tenant_id FROM meters WHERE meter_id = $1")
+            .bind(meter_id)
+            .fetch_optional(db)
+            .await?
+            .flatten();
+        Ok(self.allows(tenant.as_deref()))
+    }
+
+    /// Fail with not found unless a meter's readings are visible
+    pub async fn require_meter(&self, db: &PgPool, meter_id: &str) -> Result<()> {
+        if self.sees_meter(db, meter_id).await? {
+            Ok(())
+        } else {
+            Err(ApiError::NotFound(format!("Meter {} not found", meter_id)))
+        }
+    }
+
+    /// Fail with not found unless a certificate is visible
+    pub async fn require_certificate(&self, db: &PgPool, certificate_id: &str) -> Result<()> {
+        if self.is_campus() {
+            return Ok(());
+        }
+        let tenant: Option<String> =
+            sqlx::query_scalar("SELECT tenant_id FROM erc_certificate_tenants WHERE certificate_id = $1")
+                .bind(certificate_id)
+                .fetch_optional(db)
+                .await?;
+        if self.allows(tenant.as_deref()) {
+            Ok(())
+        } else {
+            Err(ApiError::NotFound(format!("ERC certificate {} not found", certificate_id)))
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for TenantScope {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self> {
+        let AuthenticatedUser(claims) = AuthenticatedUser::from_request_parts(parts, state).await?;
+
+        // Ingest keys push readings for their own meters and never act campus-wide
+        if parts
+            .extensions
+            .get::<ApiKeyPrincipal>()
+            .is_some_and(|principal| principal.scope == ApiKeyScope::Ingest)
+        {
+            return Ok(Self::Tenant(claims.tenant));
+        }
+        Self::of(state, &claims).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes_admit_their_own_tenant() {
+        assert!(TenantScope::Campus.allows(Some("eng")));
+        assert!(TenantScope::Campus.allows(None));
+
+        let engineering = TenantScope::Tenant(Some("eng".to_string()));
+        assert!(engineering.allows(Some("eng")));
+        assert!(!engineering.allows(Some("sci")));
+        assert!(!engineering.allows(None));
+
+        // Users outside every tenant see only rows belonging to none
+        let unassigned = TenantScope::Tenant(None);
+        assert!(unassigned.allows(None));
+        assert!(!unassigned.allows(Some("eng")));
+    }
+
+    #[test]
+    fn test_condition_binds_scope() {
+        assert_eq!(
+            TenantScope::condition("u.tenant_id", 3),
+            "($3::boolean OR u.tenant_id IS NOT DISTINCT FROM $4::varchar)"
+        );
+        assert_eq!(TenantScope::Campus.tenant(), None);
+        assert_eq!(TenantScope::Tenant(Some("eng".to_string())).tenant(), Some("eng"));
+    }
+}
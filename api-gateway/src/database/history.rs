@@ -0,0 +1,54 @@
+// Time-travel reads over versioned tables.
+// Every versioned table `<name>` has a `<name>_history` table holding one row per
+// version, valid over [valid_from, valid_to), maintained by the `record_row_version` trigger.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+/// `as_of` query parameter accepted by read endpoints backed by versioned tables
+#[derive(Debug, Default, Deserialize)]
+pub struct AsOfQuery {
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// Table to read from: the live table, or its history when reconstructing past state
+pub fn source_table(table: &str, as_of: Option<DateTime<Utc>>) -> String {
+    match as_of {
+        Some(_) => format!("{}_history", table),
+        None => table.to_string(),
+    }
+}
+
+/// Condition selecting the row versions valid at the timestamp bound to `$bind`
+pub fn as_of_condition(bind: usize) -> String {
+    format!(
+        "valid_from <= ${bind} AND (valid_to IS NULL OR valid_to > ${bind})",
+        bind = bind
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::SimulatedClock;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_source_table_switches_to_history() {
+        let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2024, 9, 23, 12, 0, 0).unwrap()).shared();
+
+        assert_eq!(source_table("erc_certificates", None), "erc_certificates");
+        assert_eq!(
+            source_table("erc_certificates", Some(clock.now())),
+            "erc_certificates_history"
+        );
+    }
+
+    #[test]
+    fn test_as_of_condition_reuses_bind() {
+        assert_eq!(
+            as_of_condition(2),
+            "valid_from <= $2 AND (valid_to IS NULL OR valid_to > $2)"
+        );
+    }
+}
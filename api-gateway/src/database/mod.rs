@@ -1,15 +1,55 @@
+use std::collections::HashSet;
+
+use std::time::Duration;
+
 use anyhow::Result;
-use sqlx::{PgPool, Pool, Postgres};
+use sqlx::migrate::{Migrate, Migration, Migrator};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Pool, Postgres};
 use tracing::info;
 
+use crate::config::Config;
+
+pub mod history;
+pub mod queue;
+pub mod redis_pool;
 pub mod schema;
 
 pub type DatabasePool = Pool<Postgres>;
 
-pub async fn setup_database(database_url: &str) -> Result<DatabasePool> {
+/// Postgres pool bounds shared by the gateway and TimescaleDB pools
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolSettings {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub idle_timeout: Duration,
+    /// Wait for a free connection before the query fails with `PoolTimedOut`
+    pub acquire_timeout: Duration,
+}
+
+impl PoolSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_connections: config.max_connections,
+            min_connections: config.db_min_connections.min(config.max_connections),
+            idle_timeout: Duration::from_secs(config.db_idle_timeout),
+            acquire_timeout: Duration::from_millis(config.db_acquire_timeout_ms),
+        }
+    }
+
+    fn options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .idle_timeout(self.idle_timeout)
+            .acquire_timeout(self.acquire_timeout)
+    }
+}
+
+pub async fn setup_database(database_url: &str, settings: &PoolSettings) -> Result<DatabasePool> {
     info!("Connecting to database: {}", database_url);
     
-    let pool = PgPool::connect(database_url).await?;
+    let pool = settings.options().connect(database_url).await?;
     
     // Test the connection
     sqlx::query("SELECT 1").execute(&pool).await?;
@@ -17,10 +57,10 @@ pub async fn setup_database(database_url: &str) -> Result<DatabasePool> {
     Ok(pool)
 }
 
-pub async fn setup_timescale_database(timescale_url: &str) -> Result<DatabasePool> {
+pub async fn setup_timescale_database(timescale_url: &str, settings: &PoolSettings) -> Result<DatabasePool> {
     info!("Connecting to TimescaleDB: {}", timescale_url);
     
-    let pool = PgPool::connect(timescale_url).await?;
+    let pool = settings.options().connect(timescale_url).await?;
     
     // Test the connection and TimescaleDB extension
     sqlx::query("SELECT 1").execute(&pool).await?;
@@ -28,12 +68,52 @@ pub async fn setup_timescale_database(timescale_url: &str) -> Result<DatabasePoo
     Ok(pool)
 }
 
+/// Gateway schema (users, audit log, indexer, settlement and the rest), embedded at build time
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Meter reading hypertable and its continuous aggregates, embedded at build time
+pub static TIMESCALE_MIGRATOR: Migrator = sqlx::migrate!("./timescale_migrations");
+
 pub async fn run_migrations(pool: &DatabasePool) -> Result<()> {
-    info!("Running database migrations");
-    
-    sqlx::migrate!("./migrations").run(pool).await?;
-    
-    info!("Database migrations completed successfully");
+    apply(&MIGRATOR, pool, "PostgreSQL").await
+}
+
+/// Create the meter reading hypertable and its continuous aggregates
+pub async fn run_timescale_migrations(pool: &DatabasePool) -> Result<()> {
+    apply(&TIMESCALE_MIGRATOR, pool, "TimescaleDB").await
+}
+
+/// Embedded migrations not yet applied to `pool`
+pub async fn pending_migrations<'m>(migrator: &'m Migrator, pool: &DatabasePool) -> Result<Vec<&'m Migration>> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let applied: HashSet<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+
+    Ok(migrator
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration() && !applied.contains(&migration.version))
+        .collect())
+}
+
+/// Apply `migrator`'s pending migrations; concurrent replicas wait on sqlx's advisory lock
+async fn apply(migrator: &Migrator, pool: &DatabasePool, schema: &str) -> Result<()> {
+    let pending = pending_migrations(migrator, pool).await?;
+    for migration in &pending {
+        info!("Applying {} migration {} {}", schema, migration.version, migration.description);
+    }
+
+    // Also run when nothing is pending, so edited migrations fail their checksum check
+    migrator.run(pool).await?;
+
+    match pending.len() {
+        0 => info!("{} schema is up to date", schema),
+        applied => info!("Applied {} {} migrations", applied, schema),
+    }
     Ok(())
 }
 
@@ -54,4 +134,15 @@ mod tests {
             todo!("Test database setup will be implemented in Phase 2")
         }
     }
+
+    #[test]
+    fn test_embedded_migrations_cover_the_gateway_schema() {
+        let versions: Vec<i64> = MIGRATOR.iter().map(|migration| migration.version).collect();
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let sql: String = MIGRATOR.iter().map(|migration| migration.sql.as_ref()).collect();
+        for table in ["users", "audit_log", "indexer_backfills", "trade_executions", "billing_periods"] {
+            assert!(sql.contains(&format!("CREATE TABLE {} (", table)), "no migration creates {}", table);
+        }
+    }
 }
\ No newline at end of file
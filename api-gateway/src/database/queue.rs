@@ -0,0 +1,44 @@
+// Postgres-backed work queues: tables with `id`, `status` and `next_attempt_at` columns
+// that dispatchers on every replica drain concurrently.
+
+use std::time::Duration;
+
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, PgPool};
+
+use crate::error::Result;
+
+/// Claim up to `limit` rows of `table` in one of `statuses` whose `next_attempt_at` has
+/// passed, pushing it out by `lease`
+///
+/// Rows another dispatcher is claiming are skipped, so no two claim the same row. Rows of a
+/// dispatcher that stops mid-attempt are claimed again once their lease runs out.
+pub async fn claim_due<T>(
+    db: &PgPool,
+    table: &str,
+    columns: &str,
+    statuses: &[&str],
+    lease: Duration,
+    limit: i64,
+) -> Result<Vec<T>>
+where
+    T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+{
+    let query = format!(
+        "UPDATE {table} SET next_attempt_at = NOW() + make_interval(secs => $1)
+         WHERE id IN (
+             SELECT id FROM {table}
+             WHERE status = ANY($2) AND next_attempt_at <= NOW()
+             ORDER BY next_attempt_at
+             LIMIT $3
+             FOR UPDATE SKIP LOCKED
+         )
+         RETURNING {columns}"
+    );
+    Ok(sqlx::query_as::<_, T>(&query)
+        .bind(lease.as_secs_f64())
+        .bind(statuses)
+        .bind(limit)
+        .fetch_all(db)
+        .await?)
+}
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use redis::aio::{ConnectionLike, ConnectionManager};
+use redis::{Cmd, ErrorKind, Pipeline, RedisError, RedisFuture, RedisResult, Value};
+use tokio::sync::{OwnedSemaphorePermit, OnceCell, Semaphore};
+
+use crate::services::metrics::{record_pool_timeout, record_pool_wait};
+
+/// Bounded access to Redis for the gateway's caches
+///
+/// Commands share one reconnecting multiplexed connection; `size` caps how many callers hold
+/// it at once. Callers that cannot get a slot within the acquire timeout fail instead of
+/// queueing behind a slow or unreachable Redis, so best-effort caches fall back quickly.
+#[derive(Clone)]
+pub struct RedisPool {
+    client: redis::Client,
+    connection: Arc<OnceCell<ConnectionManager>>,
+    permits: Arc<Semaphore>,
+    size: usize,
+    acquire_timeout: Duration,
+}
+
+impl RedisPool {
+    pub fn new(client: redis::Client, size: u32, acquire_timeout: Duration) -> Self {
+        let size = size.max(1) as usize;
+        Self {
+            client,
+            connection: Arc::new(OnceCell::new()),
+            permits: Arc::new(Semaphore::new(size)),
+            size,
+            acquire_timeout,
+        }
+    }
+
+    pub fn client(&self) -> &redis::Client {
+        &self.client
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Connections checked out right now
+    pub fn in_use(&self) -> usize {
+        self.size - self.permits.available_permits()
+    }
+
+    /// Check out a connection, connecting on first use
+    pub async fn get(&self) -> RedisResult<PooledConnection> {
+        let started = Instant::now();
+        let checkout = async {
+            let permit = Arc::clone(&self.permits)
+                .acquire_owned()
+                .await
+                .map_err(|_| RedisError::from((ErrorKind::ClientError, "Redis pool closed")))?;
+            let connection = self
+                .connection
+                // Few retries: a cache miss is cheaper than waiting out a long backoff
+                .get_or_try_init(|| ConnectionManager::new_with_backoff(self.client.clone(), 2, 100, 1))
+                .await?
+                .clone();
+            Ok::<_, RedisError>(PooledConnection {
+                connection,
+                _permit: permit,
+            })
+        };
+
+        match tokio::time::timeout(self.acquire_timeout, checkout).await {
+            Ok(pooled) => {
+                record_pool_wait("redis", started.elapsed());
+                pooled
+            }
+            Err(_) => {
+                record_pool_timeout("redis");
+                Err(RedisError::from((
+                    ErrorKind::IoError,
+                    "Redis pool exhausted",
+                    format!("no connection within {:?}", self.acquire_timeout),
+                )))
+            }
+        }
+    }
+}
+
+/// Connection checked out of a `RedisPool`, returned when dropped
+pub struct PooledConnection {
+    connection: ConnectionManager,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl ConnectionLike for PooledConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        self.connection.req_packed_command(cmd)
+    }
+
+    fn req_packed_commands<'a>(&'a mut self, cmd: &'a Pipeline, offset: usize, count: usize) -> RedisFuture<'a, Vec<Value>> {
+        self.connection.req_packed_commands(cmd, offset, count)
+    }
+
+    fn get_db(&self) -> i64 {
+        self.connection.get_db()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_checkout_fails_fast_when_redis_is_unreachable() {
+        // Nothing listens on port 1
+        let client = redis::Client::open("redis://127.0.0.1:1").unwrap();
+        let pool = RedisPool::new(client, 2, Duration::from_millis(200));
+
+        let started = Instant::now();
+        assert!(pool.get().await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(pool.in_use(), 0);
+    }
+}
@@ -12,27 +12,20 @@ pub mod types {
         Admin,
     }
 
-    #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+    #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, utoipa::ToSchema)]
     #[sqlx(type_name = "order_type_enum", rename_all = "lowercase")]
     pub enum OrderType {
         Market,
         Limit,
     }
 
-    #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, utoipa::ToSchema)]
     #[sqlx(type_name = "order_side_enum", rename_all = "lowercase")]
     pub enum OrderSide {
         Buy,
         Sell,
     }
 
-    #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
-    #[sqlx(type_name = "order_status_enum", rename_all = "lowercase")]
-    pub enum OrderStatus {
-        Pending,
-        Active,
-        Filled,
-        Cancelled,
-        Expired,
-    }
+    /// Statuses are stored as stable `SMALLINT` codes
+    pub use api_types::{ErcStatus, OrderStatus, ReadingStatus};
 }
\ No newline at end of file
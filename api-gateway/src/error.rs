@@ -1,13 +1,58 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use serde::Serialize;
 use thiserror::Error;
+use utoipa::ToSchema;
 
 pub type Result<T> = std::result::Result<T, ApiError>;
 
+/// Media type of every error response
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Body of every error response, an RFC 7807 problem document
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    /// Relative URI identifying the problem type, e.g. `/problems/erc-expired`
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// Reason phrase of `status`
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    /// Stable machine-readable code, e.g. `VALIDATION_ERROR` or, for a transaction a program
+    /// rejected, its Anchor error such as `ERC_EXPIRED`
+    pub code: String,
+    /// RFC 3339
+    pub timestamp: String,
+}
+
+impl ProblemDetails {
+    pub fn new(status: StatusCode, code: String, detail: String) -> Self {
+        Self {
+            problem_type: format!("/problems/{}", code.to_lowercase().replace('_', "-")),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail,
+            code,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+impl IntoResponse for ProblemDetails {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = (status, Json(self)).into_response();
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        response
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ApiError {
     #[error("Authentication failed: {0}")]
@@ -33,6 +78,9 @@ pub enum ApiError {
     
     #[error("Blockchain error: {0}")]
     Blockchain(String),
+
+    #[error(transparent)]
+    Chain(#[from] BlockchainError),
     
     #[error("External service error: {0}")]
     ExternalService(String),
@@ -48,6 +96,10 @@ pub enum ApiError {
     
     #[error("Rate limit exceeded")]
     RateLimit,
+
+    /// A dependency is failing and calls to it are refused until it recovers
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
     
     #[error("Internal server error: {0}")]
     Internal(String),
@@ -55,52 +107,192 @@ pub enum ApiError {
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match &self {
-            ApiError::Authentication(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
-            ApiError::Authorization(_) => (StatusCode::FORBIDDEN, self.to_string()),
-            ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
-            ApiError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
-            ApiError::Validation(_) => (StatusCode::BAD_REQUEST, self.to_string()),
-            ApiError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
-            ApiError::Conflict(_) => (StatusCode::CONFLICT, self.to_string()),
-            ApiError::RateLimit => (StatusCode::TOO_MANY_REQUESTS, self.to_string()),
-            ApiError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error occurred".to_string()),
-            ApiError::Redis(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Cache error occurred".to_string()),
-            ApiError::Blockchain(_) => (StatusCode::BAD_GATEWAY, self.to_string()),
-            ApiError::ExternalService(_) => (StatusCode::BAD_GATEWAY, self.to_string()),
-            ApiError::Configuration(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Configuration error".to_string()),
-            ApiError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
-        };
-
-        let body = Json(json!({
-            "error": {
-                "message": error_message,
-                "type": self.error_type(),
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-            }
-        }));
-
-        (status, body).into_response()
+        ProblemDetails::new(self.status(), self.code(), self.public_message()).into_response()
     }
 }
 
 impl ApiError {
-    fn error_type(&self) -> &'static str {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::Authentication(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Authorization(_) => StatusCode::FORBIDDEN,
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Validation(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::RateLimit => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            // Every pooled connection stayed busy past DB_ACQUIRE_TIMEOUT_MS; the client should back off
+            ApiError::Database(sqlx::Error::PoolTimedOut) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::Redis(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::Blockchain(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Chain(error) => error.status(),
+            ApiError::ExternalService(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Configuration(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Message shown to clients; database, cache and configuration details stay in the logs
+    pub fn public_message(&self) -> String {
+        match self {
+            ApiError::Database(sqlx::Error::PoolTimedOut) => "Database is busy; retry shortly".to_string(),
+            ApiError::Database(_) => "Database error occurred".to_string(),
+            ApiError::Redis(_) => "Cache error occurred".to_string(),
+            ApiError::Configuration(_) => "Configuration error".to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    pub fn error_type(&self) -> &'static str {
         match self {
             ApiError::Authentication(_) => "authentication_error",
             ApiError::Authorization(_) => "authorization_error",
             ApiError::BadRequest(_) => "bad_request",
             ApiError::Unauthorized(_) => "unauthorized",
             ApiError::Validation(_) => "validation_error",
+            ApiError::Database(sqlx::Error::PoolTimedOut) => "database_busy",
             ApiError::Database(_) => "database_error",
             ApiError::Redis(_) => "cache_error",
             ApiError::Blockchain(_) => "blockchain_error",
+            ApiError::Chain(error) => error.error_type(),
             ApiError::ExternalService(_) => "external_service_error",
             ApiError::Configuration(_) => "configuration_error",
             ApiError::NotFound(_) => "not_found",
             ApiError::Conflict(_) => "conflict",
             ApiError::RateLimit => "rate_limit_exceeded",
+            ApiError::Unavailable(_) => "service_unavailable",
             ApiError::Internal(_) => "internal_error",
         }
     }
-}
\ No newline at end of file
+
+    /// Stable code clients can match on, the uppercased error type or a program's error name
+    pub fn code(&self) -> String {
+        match self {
+            ApiError::Chain(error) => error.stable_code(),
+            _ => self.error_type().to_uppercase(),
+        }
+    }
+}
+
+/// Failure of a transaction the gateway simulated before sending, decoded from the program logs
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BlockchainError {
+    /// The program is paused or in maintenance mode
+    #[error("{message}")]
+    Paused { code: String, message: String },
+
+    /// The gateway's signing authority may not perform the instruction
+    #[error("{message}")]
+    Unauthorized { code: String, message: String },
+
+    /// The program rejected the request's values or the current on-chain state
+    #[error("{message}")]
+    Rejected { code: String, message: String },
+
+    #[error("Insufficient SOL to pay for the transaction")]
+    InsufficientFunds,
+
+    /// Simulation failed without a program error the gateway can decode
+    #[error("Transaction simulation failed: {0}")]
+    Failed(String),
+}
+
+impl BlockchainError {
+    /// Anchor error name, e.g. `BelowMinimumEnergy`
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Self::Paused { code, .. } | Self::Unauthorized { code, .. } | Self::Rejected { code, .. } => Some(code),
+            Self::InsufficientFunds | Self::Failed(_) => None,
+        }
+    }
+
+    /// Anchor error name in screaming snake case, e.g. `BELOW_MINIMUM_ENERGY`, or the error type
+    /// when no program error was decoded
+    pub fn stable_code(&self) -> String {
+        match self.code() {
+            Some(code) => screaming_snake_case(code),
+            None => self.error_type().to_uppercase(),
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Paused { .. } | Self::InsufficientFunds => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Rejected { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Unauthorized { .. } | Self::Failed(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+
+    fn error_type(&self) -> &'static str {
+        match self {
+            Self::Paused { .. } => "program_paused",
+            Self::Unauthorized { .. } => "program_unauthorized",
+            Self::Rejected { .. } => "program_rejected",
+            Self::InsufficientFunds => "insufficient_funds",
+            Self::Failed(_) => "simulation_failed",
+        }
+    }
+}
+
+/// `ErcExpired` as `ERC_EXPIRED`; other characters, as in `Custom(6001)`, become separators
+fn screaming_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 8);
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            let boundary = previous.is_some_and(|p| {
+                (c.is_ascii_uppercase() && (p.is_ascii_lowercase() || p.is_ascii_digit()))
+                    || (c.is_ascii_digit() && p.is_ascii_alphabetic())
+            });
+            if boundary || (previous.is_none() && !out.is_empty()) {
+                out.push('_');
+            }
+            out.push(c.to_ascii_uppercase());
+            previous = Some(c);
+        } else {
+            previous = None;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_codes() {
+        assert_eq!(screaming_snake_case("ErcExpired"), "ERC_EXPIRED");
+        assert_eq!(screaming_snake_case("UnauthorizedAuthority"), "UNAUTHORIZED_AUTHORITY");
+        assert_eq!(screaming_snake_case("Custom(6001)"), "CUSTOM_6001");
+
+        assert_eq!(ApiError::NotFound("order".to_string()).code(), "NOT_FOUND");
+        assert_eq!(ApiError::Chain(BlockchainError::InsufficientFunds).code(), "INSUFFICIENT_FUNDS");
+        let rejected = ApiError::Chain(BlockchainError::Rejected {
+            code: "BelowMinimumEnergy".to_string(),
+            message: "Energy amount below minimum required".to_string(),
+        });
+        assert_eq!(rejected.code(), "BELOW_MINIMUM_ENERGY");
+
+        let busy = ApiError::Database(sqlx::Error::PoolTimedOut);
+        assert_eq!((busy.status(), busy.code()), (StatusCode::SERVICE_UNAVAILABLE, "DATABASE_BUSY".to_string()));
+    }
+
+    #[test]
+    fn test_problem_details() {
+        let problem =
+            ProblemDetails::new(StatusCode::UNPROCESSABLE_ENTITY, "ERC_EXPIRED".to_string(), "expired".to_string());
+        let body = serde_json::to_value(&problem).unwrap();
+        assert_eq!(body["type"], "/problems/erc-expired");
+        assert_eq!(body["title"], "Unprocessable Entity");
+        assert_eq!(body["status"], 422);
+        assert_eq!(body["code"], "ERC_EXPIRED");
+
+        let response = ApiError::Validation("bad kWh".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+    }
+}
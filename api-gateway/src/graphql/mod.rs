@@ -0,0 +1,484 @@
+use std::str::FromStr;
+
+use async_graphql::connection::{Connection, Edge};
+use async_graphql::{ComplexObject, Context, EmptyMutation, EmptySubscription, ErrorExtensions, Object, OutputType, Schema, SimpleObject};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::auth::permissions::PermissionService;
+use crate::auth::tenant::{TenantScope, CERTIFICATE_TENANT};
+use crate::auth::Claims;
+use crate::database::schema::types::{ErcStatus, OrderSide, OrderStatus, OrderType};
+use crate::error::ApiError;
+use crate::models::erc::ErcCertificate;
+use crate::services::certificates::{CertificateStore, ERC_COLUMNS};
+use crate::services::transaction::Pubkey;
+use crate::utils::cursor;
+use crate::AppState;
+
+pub type GatewaySchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+const DEFAULT_PAGE_SIZE: i32 = 20;
+const MAX_PAGE_SIZE: i32 = 100;
+
+/// Deepest nesting a query may use; prosumer → meters → readings → certificates is six levels
+/// including the connection edges
+const MAX_QUERY_DEPTH: usize = 10;
+
+const MAX_QUERY_COMPLEXITY: usize = 500;
+
+/// Permission needed to read another user's prosumer record, as on `GET /users/:id`
+const READ_USERS_PERMISSION: &str = "users:read";
+
+pub fn schema() -> GatewaySchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+fn graphql_error(error: ApiError) -> async_graphql::Error {
+    let (error_type, code) = (error.error_type(), error.code());
+    async_graphql::Error::new(error.public_message()).extend_with(|_, e| {
+        e.set("type", error_type);
+        e.set("code", code.clone());
+    })
+}
+
+fn bad_request(message: impl Into<String>) -> async_graphql::Error {
+    graphql_error(ApiError::BadRequest(message.into()))
+}
+
+fn page_size(first: Option<i32>) -> async_graphql::Result<i64> {
+    match first.unwrap_or(DEFAULT_PAGE_SIZE) {
+        first @ 1..=MAX_PAGE_SIZE => Ok(i64::from(first)),
+        _ => Err(bad_request(format!("first must be between 1 and {}", MAX_PAGE_SIZE))),
+    }
+}
+
+fn decode_cursor(after: &str) -> async_graphql::Result<(DateTime<Utc>, String)> {
+    cursor::decode(after).map_err(graphql_error)
+}
+
+/// Page of `rows`, fetched with one row beyond `limit` to tell whether another page follows
+fn page<T: OutputType>(
+    mut rows: Vec<T>,
+    limit: i64,
+    after: bool,
+    cursor: impl Fn(&T) -> String,
+) -> Connection<String, T> {
+    let has_next_page = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+    let mut connection = Connection::new(after, has_next_page);
+    connection
+        .edges
+        .extend(rows.into_iter().map(|row| Edge::new(cursor(&row), row)));
+    connection
+}
+
+fn state<'a>(ctx: &Context<'a>) -> &'a AppState {
+    ctx.data_unchecked::<AppState>()
+}
+
+/// Registered user producing or consuming energy on campus
+#[derive(Debug, Clone, SimpleObject, sqlx::FromRow)]
+#[graphql(complex)]
+pub struct Prosumer {
+    pub id: Uuid,
+    pub username: String,
+    pub role: String,
+    pub department: String,
+    pub wallet_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[ComplexObject]
+impl Prosumer {
+    /// Meters assigned to the prosumer, active ones first
+    async fn meters(&self, ctx: &Context<'_>, active_only: Option<bool>) -> async_graphql::Result<Vec<Meter>> {
+        sqlx::query_as::<_, Meter>(
+            "SELECT meter_id, building, floor_level, room_number, is_active, assigned_at, deactivated_at
+             FROM meter_assignments
+             WHERE user_id = $1 AND (is_active OR NOT $2)
+             ORDER BY is_active DESC, assigned_at DESC",
+        )
+        .bind(self.id)
+        .bind(active_only.unwrap_or(false))
+        .fetch_all(&state(ctx).db)
+        .await
+        .map_err(|e| graphql_error(e.into()))
+    }
+
+    /// Orders that have been at least partly filled, newest first
+    async fn trades(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> async_graphql::Result<Connection<String, Trade>> {
+        let limit = page_size(first)?;
+        let cursor = after.as_deref().map(decode_cursor).transpose()?;
+        let (after_time, after_id) = match cursor {
+            Some((time, id)) => (Some(time), Some(Uuid::from_str(&id).map_err(|_| bad_request("Invalid cursor"))?)),
+            None => (None, None),
+        };
+
+        let trades = sqlx::query_as::<_, Trade>(
+            "SELECT id, order_type, side, energy_amount::text AS energy_amount,
+                    filled_amount::text AS filled_amount, price_per_kwh::text AS price_per_kwh,
+                    status, created_at, filled_at
+             FROM trading_orders
+             WHERE user_id = $1 AND filled_amount > 0
+               AND ($2::timestamptz IS NULL OR (created_at, id) < ($2, $3))
+             ORDER BY created_at DESC, id DESC
+             LIMIT $4",
+        )
+        .bind(self.id)
+        .bind(after_time)
+        .bind(after_id)
+        .bind(limit + 1)
+        .fetch_all(&state(ctx).db)
+        .await
+        .map_err(|e| graphql_error(e.into()))?;
+
+        Ok(page(trades, limit, after.is_some(), |trade| {
+            cursor::encode(trade.created_at, &trade.id.to_string())
+        }))
+    }
+}
+
+/// Smart meter as assigned to a prosumer
+#[derive(Debug, Clone, SimpleObject, sqlx::FromRow)]
+#[graphql(complex)]
+pub struct Meter {
+    pub meter_id: String,
+    pub building: Option<String>,
+    pub floor_level: Option<i32>,
+    pub room_number: Option<String>,
+    pub is_active: bool,
+    pub assigned_at: DateTime<Utc>,
+    pub deactivated_at: Option<DateTime<Utc>>,
+}
+
+#[ComplexObject]
+impl Meter {
+    /// Readings in `[from, to)`, newest first
+    async fn readings(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> async_graphql::Result<Connection<String, Reading>> {
+        let limit = page_size(first)?;
+        let cursor = after.as_deref().map(decode_cursor).transpose()?;
+        let (after_time, after_id) = match cursor {
+            Some((time, id)) => (Some(time), Some(Uuid::from_str(&id).map_err(|_| bad_request("Invalid cursor"))?)),
+            None => (None, None),
+        };
+
+        let readings = sqlx::query_as::<_, Reading>(
+            "SELECT id, meter_id, timestamp, energy_generated::float8 AS energy_generated,
+                    energy_consumed::float8 AS energy_consumed, solar_irradiance::float8 AS solar_irradiance,
+                    temperature::float8 AS temperature, created_at
+             FROM energy_readings
+             WHERE meter_id = $1
+               AND ($2::timestamptz IS NULL OR timestamp >= $2)
+               AND ($3::timestamptz IS NULL OR timestamp < $3)
+               AND ($4::timestamptz IS NULL OR (timestamp, id) < ($4, $5))
+             ORDER BY timestamp DESC, id DESC
+             LIMIT $6",
+        )
+        .bind(&self.meter_id)
+        .bind(from)
+        .bind(to)
+        .bind(after_time)
+        .bind(after_id)
+        .bind(limit + 1)
+        .fetch_all(&state(ctx).db)
+        .await
+        .map_err(|e| graphql_error(e.into()))?;
+
+        Ok(page(readings, limit, after.is_some(), |reading| {
+            cursor::encode(reading.timestamp, &reading.id.to_string())
+        }))
+    }
+}
+
+/// Meter reading, in kWh
+#[derive(Debug, Clone, SimpleObject, sqlx::FromRow)]
+#[graphql(complex)]
+pub struct Reading {
+    pub id: Uuid,
+    pub meter_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub energy_generated: f64,
+    pub energy_consumed: f64,
+    pub solar_irradiance: Option<f64>,
+    pub temperature: Option<f64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[ComplexObject]
+impl Reading {
+    /// Certificates issued from this reading, found by its oracle reading address
+    async fn certificates(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Certificate>> {
+        let state = state(ctx);
+        let oracle = Pubkey::from_str(&state.config.oracle_program_id)
+            .map_err(|e| graphql_error(ApiError::Configuration(format!("Invalid ORACLE_PROGRAM_ID: {}", e))))?;
+        let Some(address) = reading_address(&oracle, &self.meter_id, self.timestamp) else {
+            return Ok(vec![]);
+        };
+
+        let certificates = sqlx::query_as::<_, ErcCertificate>(&format!(
+            "SELECT {} FROM erc_certificates WHERE source_readings ? $1 ORDER BY issued_at DESC",
+            ERC_COLUMNS
+        ))
+        .bind(address.to_string())
+        .fetch_all(&state.db)
+        .await
+        .map_err(|e| graphql_error(e.into()))?;
+        Ok(certificates.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Oracle `MeterReading` account a reading is recorded in on-chain
+fn reading_address(oracle: &Pubkey, meter_id: &str, timestamp: DateTime<Utc>) -> Option<Pubkey> {
+    let timestamp = timestamp.timestamp().to_le_bytes();
+    Pubkey::find_program_address(&[b"meter_reading", meter_id.as_bytes(), &timestamp], oracle).map(|(address, _)| address)
+}
+
+/// ERC certificate as indexed from the governance program
+#[derive(Debug, Clone, SimpleObject)]
+pub struct Certificate {
+    pub certificate_id: String,
+    pub account_address: String,
+    pub authority: String,
+    /// kWh
+    pub energy_amount: i64,
+    pub renewable_source: String,
+    pub validation_data: String,
+    pub status: String,
+    pub validated_for_trading: bool,
+    /// Oracle reading addresses backing the certificate
+    pub source_readings: Vec<String>,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub trading_validated_at: Option<DateTime<Utc>>,
+    pub slot: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<ErcCertificate> for Certificate {
+    fn from(certificate: ErcCertificate) -> Self {
+        Self {
+            certificate_id: certificate.certificate_id,
+            account_address: certificate.account_address,
+            authority: certificate.authority,
+            energy_amount: certificate.energy_amount,
+            renewable_source: certificate.renewable_source,
+            validation_data: certificate.validation_data,
+            status: certificate.status.as_str().to_string(),
+            validated_for_trading: certificate.validated_for_trading,
+            source_readings: serde_json::from_value(certificate.source_readings).unwrap_or_default(),
+            issued_at: certificate.issued_at,
+            expires_at: certificate.expires_at,
+            trading_validated_at: certificate.trading_validated_at,
+            slot: certificate.slot,
+            updated_at: certificate.updated_at,
+        }
+    }
+}
+
+/// Trading order that has been at least partly filled; amounts are decimal strings
+#[derive(Debug, Clone, SimpleObject, sqlx::FromRow)]
+#[graphql(complex)]
+pub struct Trade {
+    pub id: Uuid,
+    #[graphql(skip)]
+    pub order_type: OrderType,
+    #[graphql(skip)]
+    pub side: OrderSide,
+    /// kWh
+    pub energy_amount: String,
+    /// kWh
+    pub filled_amount: String,
+    pub price_per_kwh: Option<String>,
+    #[graphql(skip)]
+    pub status: OrderStatus,
+    pub created_at: DateTime<Utc>,
+    pub filled_at: Option<DateTime<Utc>>,
+}
+
+#[ComplexObject]
+impl Trade {
+    /// `market` or `limit`
+    async fn order_type(&self) -> &str {
+        match self.order_type {
+            OrderType::Market => "market",
+            OrderType::Limit => "limit",
+        }
+    }
+
+    /// `buy` or `sell`
+    async fn side(&self) -> &str {
+        match self.side {
+            OrderSide::Buy => "buy",
+            OrderSide::Sell => "sell",
+        }
+    }
+
+    async fn status(&self) -> &str {
+        self.status.as_str()
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The authenticated user
+    async fn me(&self, ctx: &Context<'_>) -> async_graphql::Result<Prosumer> {
+        let claims = ctx.data_unchecked::<Claims>();
+        find_prosumer(state(ctx), claims.sub, &TenantScope::Campus).await
+    }
+
+    /// Another user; requires `users:read` unless it is the caller, and only finds users of
+    /// the caller's tenant
+    async fn prosumer(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Prosumer> {
+        let state = state(ctx);
+        let claims = ctx.data_unchecked::<Claims>();
+        if id == claims.sub {
+            return find_prosumer(state, id, &TenantScope::Campus).await;
+        }
+        PermissionService::from_state(state)
+            .require(claims.sub, READ_USERS_PERMISSION)
+            .await
+            .map_err(graphql_error)?;
+        find_prosumer(state, id, ctx.data_unchecked::<TenantScope>()).await
+    }
+
+    /// Indexed ERC certificate, optionally as it was at `as_of`
+    async fn certificate(
+        &self,
+        ctx: &Context<'_>,
+        certificate_id: String,
+        as_of: Option<DateTime<Utc>>,
+    ) -> async_graphql::Result<Certificate> {
+        ctx.data_unchecked::<TenantScope>()
+            .require_certificate(&state(ctx).db, &certificate_id)
+            .await
+            .map_err(graphql_error)?;
+        let certificate = CertificateStore::from_state(state(ctx))
+            .get(&certificate_id, as_of)
+            .await
+            .map_err(graphql_error)?;
+        Ok(certificate.into())
+    }
+
+    /// Indexed ERC certificates, newest first
+    async fn certificates(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> async_graphql::Result<Connection<String, Certificate>> {
+        let limit = page_size(first)?;
+        let status = status.as_deref().map(ErcStatus::from_name);
+        if status.is_some_and(|status| !status.is_known()) {
+            return Err(bad_request("Unknown certificate status"));
+        }
+        let (after_time, after_id) = match after.as_deref().map(decode_cursor).transpose()? {
+            Some((time, id)) => (Some(time), Some(id)),
+            None => (None, None),
+        };
+
+        let scope = ctx.data_unchecked::<TenantScope>();
+        let certificates = sqlx::query_as::<_, ErcCertificate>(&format!(
+            "SELECT {} FROM erc_certificates c
+             WHERE ($1::smallint IS NULL OR status = $1)
+               AND ($2::timestamptz IS NULL OR (issued_at, certificate_id) < ($2, $3))
+               AND {}
+             ORDER BY issued_at DESC, certificate_id DESC
+             LIMIT $4",
+            ERC_COLUMNS,
+            TenantScope::condition(CERTIFICATE_TENANT, 5)
+        ))
+        .bind(status)
+        .bind(after_time)
+        .bind(after_id)
+        .bind(limit + 1)
+        .bind(scope.is_campus())
+        .bind(scope.tenant())
+        .fetch_all(&state(ctx).db)
+        .await
+        .map_err(|e| graphql_error(e.into()))?;
+
+        let certificates = certificates.into_iter().map(Certificate::from).collect();
+        Ok(page(certificates, limit, after.is_some(), |certificate| {
+            cursor::encode(certificate.issued_at, &certificate.certificate_id)
+        }))
+    }
+}
+
+async fn find_prosumer(state: &AppState, id: Uuid, scope: &TenantScope) -> async_graphql::Result<Prosumer> {
+    sqlx::query_as::<_, Prosumer>(&format!(
+        "SELECT id, username, role::text AS role, department, wallet_address, created_at
+         FROM users WHERE id = $1 AND is_active = TRUE AND {}",
+        TenantScope::condition("tenant_id", 2)
+    ))
+    .bind(id)
+    .bind(scope.is_campus())
+    .bind(scope.tenant())
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| graphql_error(e.into()))?
+    .ok_or_else(|| graphql_error(ApiError::NotFound(format!("User {} not found", id))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_page_size_limits() {
+        assert_eq!(page_size(None).unwrap(), 20);
+        assert_eq!(page_size(Some(100)).unwrap(), 100);
+        assert!(page_size(Some(0)).is_err());
+        assert!(page_size(Some(101)).is_err());
+    }
+
+    #[test]
+    fn test_page_reports_following_rows() {
+        let connection = page(vec![3, 2, 1], 2, true, |n| n.to_string());
+        assert_eq!(connection.edges.iter().map(|edge| edge.node).collect::<Vec<_>>(), [3, 2]);
+        assert!(connection.has_next_page && connection.has_previous_page);
+
+        let last = page(vec![1], 2, false, |n| n.to_string());
+        assert!(!last.has_next_page && !last.has_previous_page);
+    }
+
+    #[test]
+    fn test_reading_address_matches_oracle_seeds() {
+        let oracle = Pubkey([9; 32]);
+        let time = Utc.with_ymd_and_hms(2024, 9, 1, 0, 0, 0).unwrap();
+        let expected = Pubkey::find_program_address(
+            &[b"meter_reading", b"METER-001", &time.timestamp().to_le_bytes()],
+            &oracle,
+        )
+        .unwrap()
+        .0;
+        assert_eq!(reading_address(&oracle, "METER-001", time), Some(expected));
+    }
+
+    #[test]
+    fn test_schema_exposes_nested_connections() {
+        let sdl = schema().sdl();
+        assert!(sdl.contains("readings(from: DateTime, to: DateTime, first: Int, after: String): ReadingConnection!"));
+        assert!(sdl.contains("certificates(status: String, first: Int, after: String): CertificateConnection!"));
+        assert!(sdl.contains("trades(first: Int, after: String): TradeConnection!"));
+    }
+}
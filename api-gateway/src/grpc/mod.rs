@@ -0,0 +1,408 @@
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::time::Duration;
+
+use axum::http::Method;
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use prost_types::Timestamp;
+use tokio_stream::wrappers::IntervalStream;
+use tokio_stream::StreamExt;
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::auth::api_keys::{ApiKeyStore, API_KEY_HEADER};
+use crate::auth::tenant::TenantScope;
+use crate::auth::Claims;
+use crate::database::schema::types::{OrderSide, OrderType, ReadingStatus};
+use crate::error::{ApiError, BlockchainError};
+use crate::models::energy::EnergyReadingSubmission;
+use crate::models::erc::ErcCertificate;
+use crate::models::trading::{MarketData, TradingOrder};
+use crate::services::certificates::CertificateStore;
+use crate::services::readings::ReadingStore;
+use crate::services::tenants::TenantStore;
+use crate::AppState;
+
+pub mod proto {
+    tonic::include_proto!("gridtokenx.v1");
+}
+
+use proto::gateway_server::{Gateway, GatewayServer};
+
+/// How often market watchers look for a new order book version or epoch
+const MARKET_WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Most rejected readings listed in a streamed submission summary; the rest are only counted
+const MAX_REPORTED_ERRORS: usize = 1_000;
+
+/// REST routes whose API key scopes govern the equivalent calls
+const READINGS_PATH: &str = "/meters/readings";
+const MARKET_PATH: &str = "/trading/market";
+
+impl From<ApiError> for Status {
+    fn from(error: ApiError) -> Self {
+        let message = error.public_message();
+        match &error {
+            ApiError::Authentication(_) | ApiError::Unauthorized(_) => Status::unauthenticated(message),
+            ApiError::Authorization(_) => Status::permission_denied(message),
+            ApiError::BadRequest(_) | ApiError::Validation(_) => Status::invalid_argument(message),
+            ApiError::NotFound(_) => Status::not_found(message),
+            ApiError::Conflict(_) => Status::already_exists(message),
+            ApiError::RateLimit => Status::resource_exhausted(message),
+            ApiError::Database(_) | ApiError::Redis(_) | ApiError::Configuration(_) | ApiError::Internal(_) => {
+                Status::internal(message)
+            }
+            ApiError::Chain(BlockchainError::Rejected { .. }) => Status::failed_precondition(message),
+            ApiError::Blockchain(_) | ApiError::Chain(_) | ApiError::ExternalService(_) | ApiError::Unavailable(_) => {
+                Status::unavailable(message)
+            }
+        }
+    }
+}
+
+fn timestamp(time: DateTime<Utc>) -> Timestamp {
+    Timestamp {
+        seconds: time.timestamp(),
+        nanos: time.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn from_timestamp(field: &str, value: Timestamp) -> crate::error::Result<DateTime<Utc>> {
+    u32::try_from(value.nanos)
+        .ok()
+        .and_then(|nanos| DateTime::from_timestamp(value.seconds, nanos))
+        .ok_or_else(|| ApiError::BadRequest(format!("{} is out of range", field)))
+}
+
+fn reading_submission(reading: proto::MeterReading) -> crate::error::Result<EnergyReadingSubmission> {
+    let timestamp = reading
+        .timestamp
+        .ok_or_else(|| ApiError::BadRequest("timestamp is required".to_string()))?;
+
+    Ok(EnergyReadingSubmission {
+        meter_id: reading.meter_id,
+        timestamp: from_timestamp("timestamp", timestamp)?,
+        energy_generated: reading.energy_generated,
+        energy_consumed: reading.energy_consumed,
+        solar_irradiance: reading.solar_irradiance,
+        temperature: reading.temperature,
+        engineering_authority_signature: reading.engineering_authority_signature,
+        metadata: None,
+        meter_signature: Some(reading.meter_signature).filter(|signature| !signature.is_empty()),
+    })
+}
+
+impl From<ErcCertificate> for proto::Certificate {
+    fn from(certificate: ErcCertificate) -> Self {
+        Self {
+            certificate_id: certificate.certificate_id,
+            account_address: certificate.account_address,
+            authority: certificate.authority,
+            energy_amount: certificate.energy_amount,
+            renewable_source: certificate.renewable_source,
+            validation_data: certificate.validation_data,
+            status: certificate.status.as_str().to_string(),
+            validated_for_trading: certificate.validated_for_trading,
+            source_readings_json: certificate.source_readings.to_string(),
+            issued_at: Some(timestamp(certificate.issued_at)),
+            expires_at: certificate.expires_at.map(timestamp),
+            trading_validated_at: certificate.trading_validated_at.map(timestamp),
+            slot: u64::try_from(certificate.slot).unwrap_or_default(),
+            updated_at: Some(timestamp(certificate.updated_at)),
+        }
+    }
+}
+
+impl From<TradingOrder> for proto::Order {
+    fn from(order: TradingOrder) -> Self {
+        let order_type = match order.order_type {
+            OrderType::Market => "market",
+            OrderType::Limit => "limit",
+        };
+        let side = match order.side {
+            OrderSide::Buy => "buy",
+            OrderSide::Sell => "sell",
+        };
+
+        Self {
+            id: order.id.to_string(),
+            user_id: order.user_id.to_string(),
+            order_type: order_type.to_string(),
+            side: side.to_string(),
+            energy_amount: order.energy_amount.to_string(),
+            price_per_kwh: order.price_per_kwh.to_string(),
+            filled_amount: order.filled_amount.to_string(),
+            status: order.status.as_str().to_string(),
+            expires_at: order.expires_at.map(timestamp),
+            created_at: Some(timestamp(order.created_at)),
+        }
+    }
+}
+
+impl From<MarketData> for proto::MarketState {
+    fn from(market: MarketData) -> Self {
+        Self {
+            current_epoch: market.current_epoch,
+            epoch_start_time: Some(timestamp(market.epoch_start_time)),
+            epoch_end_time: Some(timestamp(market.epoch_end_time)),
+            status: market.status,
+            order_book_version: market.order_book_version,
+            buy_orders: market.order_book.buy_orders.into_iter().map(Into::into).collect(),
+            sell_orders: market.order_book.sell_orders.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Authenticate a call as REST would a `method` request to `path`: with a bearer token,
+/// or an API key whose scope allows that request
+async fn authenticate(state: &AppState, metadata: &MetadataMap, method: Method, path: &str) -> Result<Claims, Status> {
+    if let Some(key) = metadata.get(API_KEY_HEADER) {
+        let key = key
+            .to_str()
+            .map_err(|_| Status::unauthenticated("Invalid API key"))?;
+        let (_, claims) = ApiKeyStore::from_state(state).authorize(key, &method, path).await?;
+        check_tenant_rate_limit(state, &claims).await?;
+        return Ok(claims);
+    }
+
+    let token = metadata
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| Status::unauthenticated("Missing or invalid authorization metadata"))?;
+    let claims = state
+        .jwt_service
+        .decode_token(token)
+        .map_err(|_| Status::unauthenticated("Invalid or expired token"))?;
+    check_tenant_rate_limit(state, &claims).await?;
+    Ok(claims)
+}
+
+/// Count a call against the budget of the caller's tenant, as REST requests are
+async fn check_tenant_rate_limit(state: &AppState, claims: &Claims) -> Result<(), Status> {
+    if let Some(tenant) = &claims.tenant {
+        TenantStore::from_state(state).check_rate_limit(tenant).await?;
+    }
+    Ok(())
+}
+
+/// gRPC surface over the same services as the REST handlers
+#[derive(Clone)]
+pub struct GatewayService {
+    state: AppState,
+}
+
+impl GatewayService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl Gateway for GatewayService {
+    async fn submit_reading(
+        &self,
+        request: Request<proto::MeterReading>,
+    ) -> Result<Response<proto::ReadingReceipt>, Status> {
+        authenticate(&self.state, request.metadata(), Method::POST, READINGS_PATH).await?;
+        let payload = reading_submission(request.into_inner())?;
+
+        let now = self.state.clock.now();
+        let recorded = ReadingStore::from_state(&self.state).submit(&payload, now).await?;
+        Ok(Response::new(proto::ReadingReceipt {
+            id: recorded.id.to_string(),
+            meter_id: payload.meter_id,
+            created_at: Some(timestamp(now)),
+            status: recorded.status.to_string(),
+        }))
+    }
+
+    /// Streams authenticate, and count against an API key's rate limit, once
+    async fn submit_readings(
+        &self,
+        request: Request<Streaming<proto::MeterReading>>,
+    ) -> Result<Response<proto::SubmitReadingsSummary>, Status> {
+        let claims = authenticate(&self.state, request.metadata(), Method::POST, READINGS_PATH).await?;
+        let store = ReadingStore::from_state(&self.state);
+        let mut readings = request.into_inner();
+        let mut summary = proto::SubmitReadingsSummary::default();
+        let mut index = 0;
+
+        while let Some(reading) = readings.message().await? {
+            let meter_id = reading.meter_id.clone();
+            let result = match reading_submission(reading) {
+                Ok(payload) => store.submit(&payload, self.state.clock.now()).await,
+                Err(e) => Err(e),
+            };
+
+            match result.map_err(Status::from) {
+                Ok(recorded) => {
+                    summary.accepted += 1;
+                    if recorded.status == ReadingStatus::Quarantined {
+                        summary.quarantined += 1;
+                    }
+                }
+                Err(status) => {
+                    summary.rejected += 1;
+                    if summary.errors.len() < MAX_REPORTED_ERRORS {
+                        summary.errors.push(proto::ReadingError {
+                            index,
+                            meter_id,
+                            error: status.message().to_string(),
+                        });
+                    }
+                }
+            }
+            index += 1;
+        }
+
+        tracing::info!(
+            "Streamed {} readings from {} ({} rejected)",
+            index,
+            claims.sub,
+            summary.rejected
+        );
+        Ok(Response::new(summary))
+    }
+
+    async fn get_certificate(
+        &self,
+        request: Request<proto::GetCertificateRequest>,
+    ) -> Result<Response<proto::Certificate>, Status> {
+        let path = format!("/erc/certificates/{}", request.get_ref().certificate_id);
+        let claims = authenticate(&self.state, request.metadata(), Method::GET, &path).await?;
+        let request = request.into_inner();
+        TenantScope::of(&self.state, &claims)
+            .await?
+            .require_certificate(&self.state.db, &request.certificate_id)
+            .await?;
+        let as_of = request
+            .as_of
+            .map(|as_of| from_timestamp("as_of", as_of))
+            .transpose()?;
+
+        let certificate = CertificateStore::from_state(&self.state)
+            .get(&request.certificate_id, as_of)
+            .await?;
+        Ok(Response::new(certificate.into()))
+    }
+
+    async fn get_market_state(
+        &self,
+        request: Request<proto::GetMarketStateRequest>,
+    ) -> Result<Response<proto::MarketState>, Status> {
+        authenticate(&self.state, request.metadata(), Method::GET, MARKET_PATH).await?;
+        let market = self.state.order_book.market_data(self.state.clock.now());
+        Ok(Response::new(market.into()))
+    }
+
+    type WatchMarketStateStream = Pin<Box<dyn Stream<Item = Result<proto::MarketState, Status>> + Send>>;
+
+    async fn watch_market_state(
+        &self,
+        request: Request<proto::GetMarketStateRequest>,
+    ) -> Result<Response<Self::WatchMarketStateStream>, Status> {
+        authenticate(&self.state, request.metadata(), Method::GET, MARKET_PATH).await?;
+
+        // Checking the mirror is a lock-free load, so watchers poll it rather than subscribe
+        let state = self.state.clone();
+        let mut last = None;
+        let updates = IntervalStream::new(tokio::time::interval(MARKET_WATCH_INTERVAL)).filter_map(move |_| {
+            let market = state.order_book.market_data(state.clock.now());
+            let current = Some((market.order_book_version, market.current_epoch));
+            if current == last {
+                return None;
+            }
+            last = current;
+            Some(Ok(market.into()))
+        });
+        Ok(Response::new(Box::pin(updates)))
+    }
+}
+
+/// Serve the gRPC surface on `addr`
+pub async fn serve(state: AppState, addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+    tonic::transport::Server::builder()
+        .add_service(GatewayServer::new(GatewayService::new(state)))
+        .serve(addr)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::types::OrderStatus;
+    use chrono::TimeZone;
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_timestamps_round_trip() {
+        let time = Utc.with_ymd_and_hms(2024, 9, 1, 8, 30, 0).unwrap() + chrono::Duration::milliseconds(250);
+        assert_eq!(from_timestamp("t", timestamp(time)).unwrap(), time);
+
+        let negative_nanos = Timestamp { seconds: 0, nanos: -1 };
+        let status = Status::from(from_timestamp("as_of", negative_nanos).unwrap_err());
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_reading_requires_timestamp() {
+        let reading = proto::MeterReading {
+            meter_id: "METER-001".to_string(),
+            energy_generated: 2.5,
+            engineering_authority_signature: "sig".to_string(),
+            ..Default::default()
+        };
+        let status = Status::from(reading_submission(reading.clone()).unwrap_err());
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let time = Utc.with_ymd_and_hms(2024, 9, 1, 0, 0, 0).unwrap();
+        let submission = reading_submission(proto::MeterReading {
+            timestamp: Some(timestamp(time)),
+            ..reading
+        })
+        .unwrap();
+        assert_eq!(submission.timestamp, time);
+        assert_eq!(submission.energy_generated, 2.5);
+        assert_eq!(submission.solar_irradiance, None);
+    }
+
+    #[test]
+    fn test_api_errors_map_to_status_codes() {
+        let code = |error: ApiError| Status::from(error).code();
+        assert_eq!(code(ApiError::NotFound("x".to_string())), tonic::Code::NotFound);
+        assert_eq!(code(ApiError::Unauthorized("x".to_string())), tonic::Code::Unauthenticated);
+        assert_eq!(code(ApiError::Authorization("x".to_string())), tonic::Code::PermissionDenied);
+        assert_eq!(code(ApiError::BadRequest("x".to_string())), tonic::Code::InvalidArgument);
+        assert_eq!(code(ApiError::RateLimit), tonic::Code::ResourceExhausted);
+        assert_eq!(code(ApiError::ExternalService("x".to_string())), tonic::Code::Unavailable);
+
+        // Internal details stay out of responses, as in REST
+        let status = Status::from(ApiError::Configuration("KEY missing".to_string()));
+        assert_eq!(status.message(), "Configuration error");
+    }
+
+    #[test]
+    fn test_orders_use_lowercase_names_and_exact_decimals() {
+        let created_at = Utc.with_ymd_and_hms(2024, 9, 1, 0, 0, 0).unwrap();
+        let order = proto::Order::from(TradingOrder {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            order_type: OrderType::Limit,
+            side: OrderSide::Sell,
+            energy_amount: Decimal::new(12_500, 3),
+            price_per_kwh: Decimal::new(35, 1),
+            filled_amount: Decimal::ZERO,
+            status: OrderStatus::Active,
+            expires_at: None,
+            created_at,
+            filled_at: None,
+        });
+        assert_eq!((order.order_type.as_str(), order.side.as_str()), ("limit", "sell"));
+        assert_eq!((order.energy_amount.as_str(), order.price_per_kwh.as_str()), ("12.500", "3.5"));
+        assert_eq!(order.status, "active");
+        assert_eq!(order.created_at, Some(timestamp(created_at)));
+    }
+}
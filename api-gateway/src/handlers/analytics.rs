@@ -67,14 +67,13 @@ pub async fn get_user_analytics(
     Ok(Json(analytics))
 }
 
-// Get system-wide analytics (admin only)
+// Get system-wide analytics (requires analytics:system, enforced by the route guard)
 pub async fn get_system_analytics(
     State(_state): State<AppState>,
     _user: AuthenticatedUser,
     Query(_params): Query<AnalyticsQuery>,
 ) -> Result<Json<UserAnalytics>, ApiError> {
     // TODO: Implement system analytics
-    // TODO: Add admin role check
     
     let analytics = UserAnalytics {
         energy_stats: EnergyStats {
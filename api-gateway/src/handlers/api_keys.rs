@@ -0,0 +1,139 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::api_keys::{ApiKeyScope, ApiKeyStore, DEFAULT_RATE_LIMIT_PER_MINUTE, MAX_RATE_LIMIT_PER_MINUTE};
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::ApiKey;
+use crate::error::{ApiError, Result};
+use crate::handlers::user_management::log_user_activity;
+use crate::AppState;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub scope: ApiKeyScope,
+    /// Defaults to [`DEFAULT_RATE_LIMIT_PER_MINUTE`]
+    pub rate_limit_per_minute: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateApiKeyRequest {
+    pub rate_limit_per_minute: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKeyResponse {
+    /// The key itself; it is not stored and cannot be retrieved again
+    pub key: String,
+    pub api_key: ApiKey,
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() || name.len() > 255 {
+        return Err(ApiError::Validation("API key name must be 1-255 characters".to_string()));
+    }
+    Ok(())
+}
+
+fn validate_rate_limit(rate_limit_per_minute: i32) -> Result<()> {
+    if !(1..=MAX_RATE_LIMIT_PER_MINUTE).contains(&rate_limit_per_minute) {
+        return Err(ApiError::Validation(format!(
+            "rate_limit_per_minute must be between 1 and {}",
+            MAX_RATE_LIMIT_PER_MINUTE
+        )));
+    }
+    Ok(())
+}
+
+/// List API keys, including revoked ones
+/// GET /api/v1/admin/api-keys
+pub async fn list_api_keys(State(state): State<AppState>) -> Result<Json<Vec<ApiKey>>> {
+    Ok(Json(ApiKeyStore::from_state(&state).list().await?))
+}
+
+/// Create an API key acting as the caller within its scope
+/// POST /api/v1/admin/api-keys
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreatedApiKeyResponse>)> {
+    validate_name(&request.name)?;
+    let rate_limit = request.rate_limit_per_minute.unwrap_or(DEFAULT_RATE_LIMIT_PER_MINUTE);
+    validate_rate_limit(rate_limit)?;
+
+    let (key, api_key) = ApiKeyStore::from_state(&state)
+        .create(user.0.sub, request.name.trim(), request.scope, rate_limit)
+        .await?;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "api_key_created".to_string(),
+        Some(serde_json::json!({
+            "api_key_id": api_key.id,
+            "name": api_key.name,
+            "scope": api_key.scope,
+            "rate_limit_per_minute": api_key.rate_limit_per_minute,
+        })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(CreatedApiKeyResponse { key, api_key })))
+}
+
+/// Change an API key's rate limit
+/// PUT /api/v1/admin/api-keys/:id
+pub async fn update_api_key(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthenticatedUser,
+    Json(request): Json<UpdateApiKeyRequest>,
+) -> Result<Json<ApiKey>> {
+    validate_rate_limit(request.rate_limit_per_minute)?;
+
+    let api_key = ApiKeyStore::from_state(&state)
+        .set_rate_limit(id, request.rate_limit_per_minute)
+        .await?;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "api_key_updated".to_string(),
+        Some(serde_json::json!({ "api_key_id": id, "changes": request })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(Json(api_key))
+}
+
+/// Revoke an API key; it stops authenticating immediately
+/// DELETE /api/v1/admin/api-keys/:id
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthenticatedUser,
+) -> Result<StatusCode> {
+    let api_key = ApiKeyStore::from_state(&state).revoke(id).await?;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "api_key_revoked".to_string(),
+        Some(serde_json::json!({ "api_key_id": id, "name": api_key.name })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
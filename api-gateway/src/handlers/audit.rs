@@ -0,0 +1,36 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use serde::Deserialize;
+
+use crate::error::Result;
+use crate::models::audit::{AuditRecord, AuditVerification};
+use crate::services::audit_log::AuditLog;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub limit: Option<i64>,
+    /// Only entries older than this id, for paging back through the log
+    pub before_id: Option<i64>,
+}
+
+/// List audit log entries, newest first
+/// GET /api/v1/admin/audit
+pub async fn list_audit_entries(
+    State(state): State<AppState>,
+    Query(params): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditRecord>>> {
+    let entries = AuditLog::from_state(&state)
+        .list(params.limit.unwrap_or(100).clamp(1, 1000), params.before_id)
+        .await?;
+
+    Ok(Json(entries))
+}
+
+/// Re-hash the audit chain and report the first tampered entry, if any
+/// GET /api/v1/admin/audit/verify
+pub async fn verify_audit_log(State(state): State<AppState>) -> Result<Json<AuditVerification>> {
+    Ok(Json(AuditLog::from_state(&state).verify().await?))
+}
@@ -1,20 +1,24 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    response::Json,
+    response::{Json, Redirect},
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::auth::{SecureAuthResponse, Claims, UserInfo, SecureUserInfo};
+use crate::auth::{SecureAuthResponse, UserInfo, SecureUserInfo};
 use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::oidc::OidcClient;
+use crate::auth::permissions::PermissionService;
 use crate::auth::password::PasswordService;
-use crate::error::{ApiError, Result};
+use crate::error::{ApiError, ProblemDetails, Result};
+use crate::handlers::user_management::log_user_activity;
+use crate::services::identities::IdentityStore;
 use crate::AppState;
 
 /// Login request
-#[derive(Debug, Deserialize, Serialize, Validate)]
+#[derive(Debug, Deserialize, Serialize, Validate, utoipa::ToSchema)]
 pub struct LoginRequest {
     #[validate(length(min = 3, max = 50))]
     pub username: String,
@@ -73,6 +77,17 @@ pub struct UserListResponse {
 }
 
 /// Login handler
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Signed in", body = SecureAuthResponse),
+        (status = 401, description = "Invalid credentials",
+            body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn login(
     State(state): State<AppState>,
     Json(request): Json<LoginRequest>,
@@ -85,7 +100,7 @@ pub async fn login(
     let user = sqlx::query_as::<_, UserRow>(
         "SELECT id, username, email, password_hash, role::text as role, department, 
                 first_name, last_name, wallet_address, blockchain_registered,
-                is_active, created_at, updated_at
+                is_active, tenant_id, created_at, updated_at
          FROM users 
          WHERE username = $1 AND is_active = true"
     )
@@ -103,7 +118,10 @@ pub async fn login(
     }
 
     // Create JWT claims
-    let claims = Claims::new(user.id, user.username.clone(), user.role.clone(), user.department.clone());
+    let claims = state
+        .jwt_service
+        .issue_claims(user.id, user.username.clone(), user.role.clone(), user.department.clone())
+        .with_tenant(user.tenant_id.clone());
     
     // Generate token
     let access_token = state.jwt_service.encode_token(&claims)?;
@@ -130,6 +148,161 @@ pub async fn login(
     Ok(Json(response))
 }
 
+/// Exchange a valid token for a fresh one carrying the user's current role and department
+/// POST /api/v1/auth/refresh
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Fresh token", body = SecureAuthResponse),
+        (status = 401, description = "Token invalid or user deactivated",
+            body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn refresh(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<SecureAuthResponse>> {
+    // Deactivated users and role changes take effect at refresh rather than at expiry
+    let user = sqlx::query_as::<_, UserRow>(
+        "SELECT id, username, email, password_hash, role::text as role, department, 
+                first_name, last_name, wallet_address, blockchain_registered,
+                is_active, tenant_id, created_at, updated_at
+         FROM users 
+         WHERE id = $1 AND is_active = true"
+    )
+    .bind(user.0.sub)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Database error: {}", e)))?
+    .ok_or_else(|| ApiError::Unauthorized("User is no longer active".to_string()))?;
+
+    let claims = state
+        .jwt_service
+        .issue_claims(user.id, user.username.clone(), user.role.clone(), user.department.clone())
+        .with_tenant(user.tenant_id.clone());
+    let access_token = state.jwt_service.encode_token(&claims)?;
+
+    Ok(Json(SecureAuthResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: claims.exp - claims.iat,
+        user: SecureUserInfo {
+            username: user.username,
+            email: user.email,
+            role: user.role,
+            department: user.department,
+            blockchain_registered: user.blockchain_registered,
+        },
+    }))
+}
+
+/// Redirect sent back by the identity provider
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+/// Start single sign-on by sending the user to the university identity provider
+/// GET /api/v1/auth/oidc/login
+#[utoipa::path(
+    get,
+    path = "/auth/oidc/login",
+    tag = "auth",
+    responses((status = 303, description = "Redirect to the identity provider"))
+)]
+pub async fn oidc_login(State(state): State<AppState>) -> Result<Redirect> {
+    let url = OidcClient::from_state(&state)?.begin_login().await?;
+    Ok(Redirect::to(url.as_str()))
+}
+
+/// Finish single sign-on, issuing a gateway token for the provider account's user
+/// GET /api/v1/auth/oidc/callback
+#[utoipa::path(
+    get,
+    path = "/auth/oidc/callback",
+    tag = "auth",
+    params(OidcCallbackQuery),
+    responses(
+        (status = 200, description = "Signed in", body = SecureAuthResponse),
+        (status = 401, description = "Sign-in refused",
+            body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn oidc_callback(
+    State(state): State<AppState>,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Result<Json<SecureAuthResponse>> {
+    if let Some(error) = query.error {
+        return Err(ApiError::Unauthorized(format!(
+            "Sign-in failed at the identity provider: {}",
+            query.error_description.unwrap_or(error)
+        )));
+    }
+    let (Some(code), Some(login_state)) = (query.code, query.state) else {
+        return Err(ApiError::BadRequest("code and state are required".to_string()));
+    };
+
+    let oidc = OidcClient::from_state(&state)?;
+    let id_token = oidc.complete_login(&code, &login_state).await?;
+    let groups = id_token.groups(oidc.groups_claim());
+    let user_id = IdentityStore::from_state(&state)
+        .sign_in(&id_token, &groups, oidc.group_roles())
+        .await?;
+    PermissionService::from_state(&state).invalidate_user(user_id).await;
+
+    let user = sqlx::query_as::<_, UserRow>(
+        "SELECT id, username, email, password_hash, role::text as role, department, 
+                first_name, last_name, wallet_address, blockchain_registered,
+                is_active, tenant_id, created_at, updated_at
+         FROM users 
+         WHERE id = $1 AND is_active = true"
+    )
+    .bind(user_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Database error: {}", e)))?
+    .ok_or_else(|| ApiError::Unauthorized("User is no longer active".to_string()))?;
+
+    let claims = state
+        .jwt_service
+        .issue_claims(user.id, user.username.clone(), user.role.clone(), user.department.clone())
+        .with_tenant(user.tenant_id.clone());
+    let access_token = state.jwt_service.encode_token(&claims)?;
+
+    let _ = sqlx::query("UPDATE users SET last_login_at = NOW() WHERE id = $1")
+        .bind(user.id)
+        .execute(&state.db)
+        .await;
+    let _ = log_user_activity(
+        &state.db,
+        user.id,
+        "oidc_login".to_string(),
+        Some(serde_json::json!({ "issuer": oidc.issuer(), "groups": groups })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(Json(SecureAuthResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: claims.exp - claims.iat,
+        user: SecureUserInfo {
+            username: user.username,
+            email: user.email,
+            role: user.role,
+            department: user.department,
+            blockchain_registered: user.blockchain_registered,
+        },
+    }))
+}
+
 /// Get current user profile
 pub async fn get_profile(
     State(state): State<AppState>,
@@ -138,7 +311,7 @@ pub async fn get_profile(
     let user_data = sqlx::query_as::<_, UserRow>(
         "SELECT id, username, email, password_hash, role::text as role, department, 
                 first_name, last_name, wallet_address, blockchain_registered,
-                is_active, created_at, updated_at
+                is_active, tenant_id, created_at, updated_at
          FROM users 
          WHERE id = $1 AND is_active = true"
     )
@@ -296,7 +469,7 @@ pub async fn get_user(
     let user_data = sqlx::query_as::<_, UserRow>(
         "SELECT id, username, email, password_hash, role::text as role, department, 
                 first_name, last_name, wallet_address, blockchain_registered,
-                is_active, created_at, updated_at
+                is_active, tenant_id, created_at, updated_at
          FROM users 
          WHERE id = $1"
     )
@@ -372,7 +545,7 @@ pub async fn list_users(
     let users_query = format!(
         "SELECT id, username, email, password_hash, role::text as role, department, 
                 first_name, last_name, wallet_address, blockchain_registered,
-                is_active, created_at, updated_at
+                is_active, tenant_id, created_at, updated_at
          FROM users 
          WHERE {} 
          ORDER BY created_at DESC 
@@ -426,6 +599,7 @@ struct UserRow {
     wallet_address: Option<String>,
     blockchain_registered: bool,
     is_active: bool,
+    tenant_id: Option<String>,
     created_at: chrono::DateTime<chrono::Utc>,
     updated_at: chrono::DateTime<chrono::Utc>,
 }
\ No newline at end of file
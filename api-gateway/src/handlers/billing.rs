@@ -0,0 +1,142 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::tenant::TenantScope;
+use crate::error::{ApiError, Result};
+use crate::models::billing::{BillingPeriod, SettlementStatement};
+use crate::services::billing::{BillingService, MAX_STATEMENT_LIMIT};
+use crate::services::billing_export::{render_csv, render_pdf, ExportFormat};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct BillingQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl BillingQuery {
+    fn limit(&self) -> i64 {
+        self.limit.unwrap_or(50).clamp(1, MAX_STATEMENT_LIMIT)
+    }
+
+    fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClosePeriodRequest {
+    /// First day of the month to close
+    pub period_start: NaiveDate,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// csv or pdf
+    pub format: String,
+    /// Export just this prosumer's statement
+    pub user_id: Option<Uuid>,
+}
+
+/// List billing periods, newest first
+/// GET /api/v1/admin/billing/periods
+pub async fn list_periods(
+    State(state): State<AppState>,
+    Query(params): Query<BillingQuery>,
+) -> Result<Json<Vec<BillingPeriod>>> {
+    let periods = BillingService::from_state(&state)?
+        .list(params.limit(), params.offset())
+        .await?;
+    Ok(Json(periods))
+}
+
+/// Close an ended month now rather than waiting for the schedule
+/// POST /api/v1/admin/billing/periods
+pub async fn close_period(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<ClosePeriodRequest>,
+) -> Result<(StatusCode, Json<BillingPeriod>)> {
+    let period = BillingService::from_state(&state)?
+        .close(payload.period_start, Some(user.0.sub))
+        .await?
+        .ok_or_else(|| ApiError::Conflict(format!("Billing period {} is already closed", payload.period_start)))?;
+    Ok((StatusCode::CREATED, Json(period)))
+}
+
+/// Settlement statements issued when a period closed
+/// GET /api/v1/admin/billing/periods/:id/statements
+pub async fn list_period_statements(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Path(period_id): Path<Uuid>,
+    Query(params): Query<BillingQuery>,
+) -> Result<Json<Vec<SettlementStatement>>> {
+    let service = BillingService::from_state(&state)?;
+    service.load(period_id).await?;
+    let statements = service
+        .statements(Some(period_id), None, &scope, params.limit(), params.offset())
+        .await?;
+    Ok(Json(statements))
+}
+
+/// The caller's own settlement statements, newest period first
+/// GET /api/v1/billing/statements
+pub async fn list_own_statements(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<BillingQuery>,
+) -> Result<Json<Vec<SettlementStatement>>> {
+    // Narrowed to the caller, who always sees their own statements
+    let statements = BillingService::from_state(&state)?
+        .statements(None, Some(user.0.sub), &TenantScope::Campus, params.limit(), params.offset())
+        .await?;
+    Ok(Json(statements))
+}
+
+/// Export a closed period's statements for invoicing, one CSV row or PDF page per prosumer
+/// GET /api/v1/settlements/:period/export?format=csv|pdf
+///
+/// `period` is the billed month, e.g. `2024-09`.
+pub async fn export_statements(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Path(period): Path<String>,
+    Query(params): Query<ExportQuery>,
+) -> Result<Response> {
+    let format: ExportFormat = params.format.parse().map_err(ApiError::BadRequest)?;
+    let period_start = NaiveDate::parse_from_str(&format!("{}-01", period), "%Y-%m-%d")
+        .map_err(|_| ApiError::BadRequest(format!("Billing period {:?} is not a YYYY-MM month", period)))?;
+
+    let service = BillingService::from_state(&state)?;
+    let period = service.period_starting(period_start).await?;
+    if period.status != BillingPeriod::CLOSED && period.status != BillingPeriod::ATTENTION {
+        return Err(ApiError::Conflict(format!(
+            "Billing period {} is {}, not closed",
+            period.period_start, period.status
+        )));
+    }
+    let statements = service.prosumer_statements(period.id, params.user_id, &scope).await?;
+
+    let body = match format {
+        ExportFormat::Csv => render_csv(&period, &statements, state.config.vat_rate).into_bytes(),
+        ExportFormat::Pdf => render_pdf(&period, &statements, state.config.vat_rate),
+    };
+    let disposition = format!(
+        "attachment; filename=\"settlements-{}.{}\"",
+        period.period_start.format("%Y-%m"),
+        format.extension()
+    );
+    Ok((
+        [(header::CONTENT_TYPE, format.content_type().to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        body,
+    )
+        .into_response())
+}
@@ -2,6 +2,8 @@ use axum::{
     extract::{Path, Query, State},
     response::Json,
 };
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -9,7 +11,12 @@ use validator::Validate;
 
 use crate::auth::middleware::AuthenticatedUser;
 use crate::error::{ApiError, Result};
-use crate::models::blockchain::{TransactionSubmission, TransactionStatus, ProgramInteraction};
+use crate::models::blockchain::{ProgramInteraction, TransactionStatus, TransactionSubmission};
+#[cfg(feature = "chain")]
+use crate::models::blockchain::{OracleState, PoaConfigState};
+#[cfg(feature = "chain")]
+use crate::services::chain_cache::{CachedAccount, ChainCache};
+use crate::services::transaction::Pubkey;
 use crate::AppState;
 
 /// Query parameters for transaction history
@@ -109,7 +116,7 @@ pub async fn submit_transaction(
     let response = TransactionResponse {
         signature: signature.clone(),
         status: "pending".to_string(),
-        submitted_at: Utc::now(),
+        submitted_at: state.clock.now(),
         estimated_confirmation_time: 30, // 30 seconds estimated
     };
 
@@ -261,7 +268,7 @@ pub async fn interact_with_program(
     let response = TransactionResponse {
         signature: signature.clone(),
         status: "pending".to_string(),
-        submitted_at: Utc::now(),
+        submitted_at: state.clock.now(),
         estimated_confirmation_time: 15,
     };
 
@@ -296,10 +303,50 @@ pub async fn get_account_info(
     Ok(Json(account_info))
 }
 
+/// Address of a program's singleton PDA with the given seed
+pub(crate) fn singleton_address(program_id: &str, seed: &[u8]) -> Result<String> {
+    let program_id = Pubkey::from_str(program_id).map_err(ApiError::Configuration)?;
+    let (address, _) = Pubkey::find_program_address(&[seed], &program_id)
+        .ok_or_else(|| ApiError::Internal("No program address for seed".to_string()))?;
+    Ok(address.to_string())
+}
+
+/// Get the oracle program's current on-chain state
+/// GET /api/v1/blockchain/oracle
+#[cfg(feature = "chain")]
+pub async fn get_oracle_state(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+) -> Result<Json<OracleState>> {
+    let address = singleton_address(&state.config.oracle_program_id, b"oracle_data")?;
+    let oracle_data = ChainCache::from_state(&state)
+        .get_anchor_account::<oracle::OracleData>(&state.blockchain_service, &address, CachedAccount::Oracle)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Oracle is not initialized".to_string()))?;
+
+    Ok(Json(OracleState::from_account(address, oracle_data)))
+}
+
+/// Get the governance PoA configuration as currently stored on-chain
+/// GET /api/v1/blockchain/governance
+#[cfg(feature = "chain")]
+pub async fn get_poa_config(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+) -> Result<Json<PoaConfigState>> {
+    let address = singleton_address(&state.config.governance_program_id, b"poa_config")?;
+    let poa_config = ChainCache::from_state(&state)
+        .get_anchor_account::<governance::PoAConfig>(&state.blockchain_service, &address, CachedAccount::Governance)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Governance is not initialized".to_string()))?;
+
+    Ok(Json(PoaConfigState::from_account(address, poa_config, state.clock.now().timestamp())))
+}
+
 /// Get current network status
 /// GET /api/v1/blockchain/network
 pub async fn get_network_status(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     _user: AuthenticatedUser,
 ) -> Result<Json<NetworkStatus>> {
     tracing::info!("Fetching network status");
@@ -308,7 +355,7 @@ pub async fn get_network_status(
     let network_status = NetworkStatus {
         cluster: "devnet".to_string(),
         block_height: 1000000,
-        block_time: Utc::now(),
+        block_time: state.clock.now(),
         tps: 2500.0,
         health: "ok".to_string(),
         version: "1.17.0".to_string(),
@@ -0,0 +1,182 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::tenant::TenantScope;
+use crate::error::{ApiError, Result};
+use crate::handlers::user_management::log_user_activity;
+use crate::models::carbon::{CarbonReport, CarbonRollup, CarbonRollupRun, EmissionFactor};
+use crate::services::carbon::{CarbonScope, CarbonService};
+use crate::utils::clock::month_of;
+use crate::AppState;
+
+/// Rollups returned by a request without a limit
+const DEFAULT_ROLLUP_LIMIT: i64 = 100;
+
+/// Window of a carbon report; by default the current month so far
+#[derive(Debug, Deserialize)]
+pub struct CarbonQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl CarbonQuery {
+    fn window(&self, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let from = self.from.unwrap_or_else(|| {
+            month_of(now.date_naive())
+                .0
+                .and_hms_opt(0, 0, 0)
+                .expect("midnight exists")
+                .and_utc()
+        });
+        (from, self.to.unwrap_or(now))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RollupQuery {
+    /// prosumer, building or campus
+    pub scope: Option<String>,
+    pub scope_id: Option<String>,
+    /// Any day of the month
+    pub month: Option<NaiveDate>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateFactorRequest {
+    pub kg_co2e_per_kwh: f64,
+    /// Where the factor comes from; unchanged when omitted
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RollupRequest {
+    /// Any day of the month to recompute
+    pub month: NaiveDate,
+}
+
+async fn report(state: &AppState, scope: CarbonScope, tenant: &TenantScope, query: &CarbonQuery) -> Result<Json<CarbonReport>> {
+    let (from, to) = query.window(state.clock.now());
+    let report = CarbonService::from_state(state).report(&scope, tenant, from, to).await?;
+    Ok(Json(report))
+}
+
+/// Emissions avoided by the caller's own generation
+/// GET /api/v1/carbon/me?from=&to=
+pub async fn get_own_emissions(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(query): Query<CarbonQuery>,
+) -> Result<Json<CarbonReport>> {
+    report(&state, CarbonScope::Prosumer(user.0.sub), &TenantScope::Campus, &query).await
+}
+
+/// Emissions avoided by a prosumer's generation
+/// GET /api/v1/carbon/prosumers/:user_id?from=&to=
+pub async fn get_prosumer_emissions(
+    State(state): State<AppState>,
+    tenant: TenantScope,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<CarbonQuery>,
+) -> Result<Json<CarbonReport>> {
+    let user_tenant: Option<Option<String>> = sqlx::query_scalar("SELECT tenant_id FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(&state.db)
+        .await?;
+    match user_tenant {
+        Some(user_tenant) if tenant.allows(user_tenant.as_deref()) => {
+            report(&state, CarbonScope::Prosumer(user_id), &tenant, &query).await
+        }
+        _ => Err(ApiError::NotFound(format!("Prosumer {} not found", user_id))),
+    }
+}
+
+/// Emissions avoided by the generation of a building's meters
+/// GET /api/v1/carbon/buildings/:building?from=&to=
+pub async fn get_building_emissions(
+    State(state): State<AppState>,
+    tenant: TenantScope,
+    Path(building): Path<String>,
+    Query(query): Query<CarbonQuery>,
+) -> Result<Json<CarbonReport>> {
+    report(&state, CarbonScope::Building(building), &tenant, &query).await
+}
+
+/// Emissions avoided by all campus generation; tenants see their own share
+/// GET /api/v1/carbon/campus?from=&to=
+pub async fn get_campus_emissions(
+    State(state): State<AppState>,
+    tenant: TenantScope,
+    Query(query): Query<CarbonQuery>,
+) -> Result<Json<CarbonReport>> {
+    report(&state, CarbonScope::Campus, &tenant, &query).await
+}
+
+/// Stored monthly rollups for sustainability reporting
+/// GET /api/v1/carbon/rollups?scope=&scope_id=&month=&limit=
+pub async fn list_rollups(
+    State(state): State<AppState>,
+    tenant: TenantScope,
+    Query(query): Query<RollupQuery>,
+) -> Result<Json<Vec<CarbonRollup>>> {
+    let rollups = CarbonService::from_state(&state)
+        .rollups(
+            query.scope.as_deref(),
+            query.scope_id.as_deref(),
+            query.month,
+            &tenant,
+            query.limit.unwrap_or(DEFAULT_ROLLUP_LIMIT),
+        )
+        .await?;
+    Ok(Json(rollups))
+}
+
+/// Emission factors of each renewable source and of the grid
+/// GET /api/v1/carbon/factors
+pub async fn list_factors(State(state): State<AppState>) -> Result<Json<Vec<EmissionFactor>>> {
+    Ok(Json(CarbonService::from_state(&state).factors().await?))
+}
+
+/// Set the emission factor of a renewable source or of the grid (`grid`)
+/// PUT /api/v1/admin/carbon/factors/:source
+pub async fn update_factor(
+    State(state): State<AppState>,
+    Path(source): Path<String>,
+    user: AuthenticatedUser,
+    Json(request): Json<UpdateFactorRequest>,
+) -> Result<Json<EmissionFactor>> {
+    let factor = CarbonService::from_state(&state)
+        .set_factor(&source, request.kg_co2e_per_kwh, request.reference.as_deref(), user.0.sub)
+        .await?;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "emission_factor_updated".to_string(),
+        Some(serde_json::json!({ "renewable_source": source, "kg_co2e_per_kwh": request.kg_co2e_per_kwh })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(Json(factor))
+}
+
+/// Recompute a month's rollups, e.g. after correcting an emission factor
+/// POST /api/v1/admin/carbon/rollups
+pub async fn recompute_rollups(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<RollupRequest>,
+) -> Result<(StatusCode, Json<CarbonRollupRun>)> {
+    let run = CarbonService::from_state(&state).rollup(request.month).await?;
+    tracing::info!("Carbon rollups for {} recomputed by {}", run.month, user.0.sub);
+    Ok((StatusCode::CREATED, Json(run)))
+}
@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::Result;
+use crate::models::channel::{BalanceChannel, BalanceChannelUpdate};
+use crate::services::channels::{ChannelService, CHANNEL_COLUMNS, UPDATE_COLUMNS};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelUpdatesQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignChannelUpdateRequest {
+    /// Base58 ed25519 signature over the channel state message
+    pub signature: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecordChannelUpdateRequest {
+    /// Energy token base units owed to the participant for the interval; negative when they owe
+    pub delta: i64,
+    pub interval_start: DateTime<Utc>,
+    pub interval_end: DateTime<Utc>,
+}
+
+/// Record the balance channel the user opened on-chain
+/// POST /api/v1/channels
+pub async fn open_channel(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<(StatusCode, Json<BalanceChannel>)> {
+    let channel = ChannelService::from_state(&state)?.open(user.0.sub).await?;
+    Ok((StatusCode::CREATED, Json(channel)))
+}
+
+/// Get the user's balance channel
+/// GET /api/v1/channels/me
+pub async fn get_own_channel(State(state): State<AppState>, user: AuthenticatedUser) -> Result<Json<BalanceChannel>> {
+    let channel = ChannelService::from_state(&state)?.load_for_user(user.0.sub).await?;
+    Ok(Json(channel))
+}
+
+/// List the user's channel updates, newest first
+/// GET /api/v1/channels/me/updates
+pub async fn list_own_updates(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<ChannelUpdatesQuery>,
+) -> Result<Json<Vec<BalanceChannelUpdate>>> {
+    let channel = ChannelService::from_state(&state)?.load_for_user(user.0.sub).await?;
+
+    let query = format!(
+        "SELECT {} FROM balance_channel_updates WHERE channel_id = $1 ORDER BY nonce DESC LIMIT $2 OFFSET $3",
+        UPDATE_COLUMNS
+    );
+    let updates = sqlx::query_as::<_, BalanceChannelUpdate>(&query)
+        .bind(channel.id)
+        .bind(params.limit.unwrap_or(50).clamp(1, 500))
+        .bind(params.offset.unwrap_or(0).max(0))
+        .fetch_all(&state.db)
+        .await?;
+
+    Ok(Json(updates))
+}
+
+/// Countersign a channel state as the participant
+/// POST /api/v1/channels/me/updates/:nonce/signature
+pub async fn sign_update(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(nonce): Path<i64>,
+    Json(request): Json<SignChannelUpdateRequest>,
+) -> Result<Json<BalanceChannelUpdate>> {
+    let update = ChannelService::from_state(&state)?
+        .sign_update(user.0.sub, nonce, &request.signature)
+        .await?;
+    Ok(Json(update))
+}
+
+/// List all balance channels
+/// GET /api/v1/admin/channels
+pub async fn list_channels(State(state): State<AppState>) -> Result<Json<Vec<BalanceChannel>>> {
+    let query = format!("SELECT {} FROM balance_channels ORDER BY created_at DESC", CHANNEL_COLUMNS);
+    let channels = sqlx::query_as::<_, BalanceChannel>(&query)
+        .fetch_all(&state.db)
+        .await?;
+    Ok(Json(channels))
+}
+
+/// Record an interval's balance change on a channel
+/// POST /api/v1/admin/channels/:id/updates
+pub async fn record_update(
+    State(state): State<AppState>,
+    Path(channel_id): Path<Uuid>,
+    Json(request): Json<RecordChannelUpdateRequest>,
+) -> Result<(StatusCode, Json<BalanceChannelUpdate>)> {
+    let update = ChannelService::from_state(&state)?
+        .record_update(channel_id, request.delta, request.interval_start, request.interval_end)
+        .await?;
+    Ok((StatusCode::CREATED, Json(update)))
+}
+
+/// Submit the latest jointly signed state as a dispute
+/// POST /api/v1/admin/channels/:id/dispute
+pub async fn dispute_channel(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(channel_id): Path<Uuid>,
+) -> Result<Json<BalanceChannel>> {
+    tracing::info!("Dispute of channel {} requested by {}", channel_id, user.0.sub);
+    let channel = ChannelService::from_state(&state)?.dispute(channel_id).await?;
+    Ok(Json(channel))
+}
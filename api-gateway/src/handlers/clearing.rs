@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::{ApiError, Result};
+use crate::models::market_clearing::MarketClearingRun;
+use crate::services::market_clearing::MarketClearingService;
+use crate::AppState;
+
+/// Runs returned by a history request without a limit
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+
+/// Query parameters for a clearing run history request
+#[derive(Debug, Deserialize)]
+pub struct RunQuery {
+    pub limit: Option<i64>,
+}
+
+/// List recent market clearing runs, scheduled and manual
+/// GET /api/v1/admin/market/clearing/runs
+pub async fn list_runs(
+    State(state): State<AppState>,
+    Query(query): Query<RunQuery>,
+) -> Result<Json<Vec<MarketClearingRun>>> {
+    let runs = MarketClearingService::from_state(&state)?
+        .list(query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+        .await?;
+    Ok(Json(runs))
+}
+
+/// Trigger market clearing now; the run is recorded as skipped while governance is paused
+/// POST /api/v1/admin/market/clearing
+pub async fn trigger_clearing(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<(StatusCode, Json<MarketClearingRun>)> {
+    let run = MarketClearingService::from_state(&state)?
+        .run(MarketClearingRun::MANUAL, state.clock.now(), Some(user.0.sub))
+        .await?
+        .ok_or_else(|| ApiError::Internal("Manual clearing run was not recorded".to_string()))?;
+    tracing::info!("Market clearing run {} triggered by {}: {}", run.id, user.0.sub, run.status);
+    Ok((StatusCode::CREATED, Json(run)))
+}
@@ -0,0 +1,16 @@
+use axum::{extract::State, response::Json};
+
+use crate::models::dashboard::LiveDashboard;
+use crate::AppState;
+
+/// Campus-wide live figures for the lobby display
+/// GET /api/v1/dashboard/live
+///
+/// Served entirely from in-memory mirrors so it never waits on the database; `stale`
+/// reports when the background refresh has fallen behind.
+pub async fn get_live_dashboard(State(state): State<AppState>) -> Json<LiveDashboard> {
+    let started = std::time::Instant::now();
+    let dashboard = state.dashboard.live(&state.order_book.snapshot());
+    metrics::histogram!("dashboard_live_seconds").record(started.elapsed().as_secs_f64());
+    Json(dashboard)
+}
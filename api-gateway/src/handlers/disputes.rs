@@ -0,0 +1,163 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::permissions::PermissionService;
+use crate::auth::tenant::TenantScope;
+use crate::error::{ApiError, Result};
+use crate::handlers::user_management::log_user_activity;
+use crate::models::dispute::{
+    Dispute, DisputeDetail, DisputeEvidence, EvidenceRequest, OpenDisputeRequest, ResolveDisputeRequest,
+};
+use crate::services::disputes::DisputeService;
+use crate::AppState;
+
+/// Disputes returned by a listing without a limit
+const DEFAULT_DISPUTE_LIMIT: i64 = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct DisputeQuery {
+    /// open, upheld or dismissed
+    pub status: Option<String>,
+    /// Only disputes opened by this user
+    pub opened_by: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// Dispute visible to its opener and holders of disputes:read
+async fn visible_dispute(state: &AppState, user: &AuthenticatedUser, id: Uuid) -> Result<DisputeDetail> {
+    let detail = DisputeService::from_state(state).get(id).await?;
+    if detail.dispute.opened_by != user.0.sub
+        && !PermissionService::from_state(state).has_permission(user.0.sub, "disputes:read").await?
+    {
+        return Err(ApiError::NotFound(format!("Dispute {} not found", id)));
+    }
+    Ok(detail)
+}
+
+/// Open a dispute over a reading or certificate, challenging a certificate on-chain
+/// POST /api/v1/disputes
+pub async fn open_dispute(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    scope: TenantScope,
+    Json(request): Json<OpenDisputeRequest>,
+) -> Result<(StatusCode, Json<DisputeDetail>)> {
+    let detail = DisputeService::from_state(&state).open(&request, user.0.sub, &scope).await?;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "dispute_opened".to_string(),
+        Some(serde_json::json!({
+            "dispute_id": detail.dispute.id,
+            "subject_type": detail.dispute.subject_type,
+            "subject_id": detail.dispute.subject_id,
+        })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(detail)))
+}
+
+/// Own disputes, newest first
+/// GET /api/v1/disputes?status=&limit=
+pub async fn list_own_disputes(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(query): Query<DisputeQuery>,
+) -> Result<Json<Vec<Dispute>>> {
+    let disputes = DisputeService::from_state(&state)
+        .list(
+            Some(user.0.sub),
+            query.status.as_deref(),
+            &TenantScope::Campus,
+            query.limit.unwrap_or(DEFAULT_DISPUTE_LIMIT),
+        )
+        .await?;
+    Ok(Json(disputes))
+}
+
+/// Dispute with its evidence and status
+/// GET /api/v1/disputes/:id
+/// GET /api/v1/admin/disputes/:id
+pub async fn get_dispute(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<DisputeDetail>> {
+    Ok(Json(visible_dispute(&state, &user, id).await?))
+}
+
+/// Attach evidence to an open dispute
+/// POST /api/v1/disputes/:id/evidence
+pub async fn add_evidence(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<EvidenceRequest>,
+) -> Result<(StatusCode, Json<DisputeEvidence>)> {
+    let detail = visible_dispute(&state, &user, id).await?;
+    if detail.dispute.opened_by != user.0.sub {
+        return Err(ApiError::Authorization(
+            "Only the opener can attach evidence to this dispute".to_string(),
+        ));
+    }
+
+    let evidence = DisputeService::from_state(&state).add_evidence(id, user.0.sub, &request).await?;
+    Ok((StatusCode::CREATED, Json(evidence)))
+}
+
+/// Every dispute within the caller's tenant, newest first
+/// GET /api/v1/admin/disputes?status=&opened_by=&limit=
+pub async fn list_disputes(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Query(query): Query<DisputeQuery>,
+) -> Result<Json<Vec<Dispute>>> {
+    let disputes = DisputeService::from_state(&state)
+        .list(
+            query.opened_by,
+            query.status.as_deref(),
+            &scope,
+            query.limit.unwrap_or(DEFAULT_DISPUTE_LIMIT),
+        )
+        .await?;
+    Ok(Json(disputes))
+}
+
+/// Uphold or dismiss a dispute; an upheld certificate dispute revokes the certificate
+/// POST /api/v1/admin/disputes/:id/resolve
+pub async fn resolve_dispute(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<ResolveDisputeRequest>,
+) -> Result<Json<Dispute>> {
+    let dispute = DisputeService::from_state(&state)
+        .resolve(id, request.upheld, &request.resolution, user.0.sub)
+        .await?;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "dispute_resolved".to_string(),
+        Some(serde_json::json!({
+            "dispute_id": dispute.id,
+            "status": dispute.status,
+            "resolution_job_id": dispute.resolution_job_id,
+        })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(Json(dispute))
+}
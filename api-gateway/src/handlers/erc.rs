@@ -0,0 +1,457 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Json, Response,
+    },
+};
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::tenant::{TenantScope, CERTIFICATE_TENANT};
+use crate::database::history::{as_of_condition, source_table, AsOfQuery};
+use crate::database::schema::types::ErcStatus;
+use crate::error::{ApiError, ProblemDetails, Result};
+use crate::models::erc::{ErcAutoIssuance, ErcCertificate, ErcPage, ErcVerification, ErcVerificationLink};
+use crate::models::tx_job::TxJob;
+use crate::services::certificates::{CertificateFilter, CertificateStore, ERC_COLUMNS};
+use crate::services::erc_auto_issuance::AutoIssuer;
+use crate::services::erc_verification::CertificateVerifier;
+use crate::services::tenants::TenantStore;
+use crate::services::transaction::Pubkey;
+use crate::services::tx_queue::{TxOperation, TxQueue};
+use crate::utils::cursor;
+use crate::utils::html::escape;
+use crate::utils::validation::{self, ValidateRequest, ValidatedJson, MAX_SOURCE_READINGS};
+use crate::AppState;
+
+/// Verification results may be cached briefly by phones and intermediaries
+const VERIFICATION_CACHE_CONTROL: &str = "public, max-age=60";
+
+/// Query parameters for ERC certificates
+#[derive(Debug, Deserialize)]
+pub struct ErcQuery {
+    pub status: Option<ErcStatus>,
+    pub as_of: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// List indexed ERC certificates, optionally as they were at `as_of`
+/// GET /api/v1/erc/certificates
+pub async fn list_certificates(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    scope: TenantScope,
+    Query(params): Query<ErcQuery>,
+) -> Result<Json<Vec<ErcCertificate>>> {
+    let mut query = format!(
+        "SELECT {} FROM {} c WHERE {}",
+        ERC_COLUMNS,
+        source_table("erc_certificates", params.as_of),
+        TenantScope::condition(CERTIFICATE_TENANT, 1)
+    );
+    let mut bind_count = 3;
+
+    if params.as_of.is_some() {
+        query.push_str(&format!(" AND {}", as_of_condition(bind_count)));
+        bind_count += 1;
+    }
+
+    if params.status.is_some() {
+        query.push_str(&format!(" AND status = ${}", bind_count));
+        bind_count += 1;
+    }
+
+    query.push_str(&format!(
+        " ORDER BY issued_at DESC LIMIT ${} OFFSET ${}",
+        bind_count,
+        bind_count + 1
+    ));
+
+    let mut sqlx_query = sqlx::query_as::<_, ErcCertificate>(&query)
+        .bind(scope.is_campus())
+        .bind(scope.tenant());
+    if let Some(as_of) = params.as_of {
+        sqlx_query = sqlx_query.bind(as_of);
+    }
+    if let Some(status) = &params.status {
+        sqlx_query = sqlx_query.bind(status);
+    }
+
+    let certificates = sqlx_query
+        .bind(params.limit.unwrap_or(50).clamp(1, 500))
+        .bind(params.offset.unwrap_or(0).max(0))
+        .fetch_all(&state.db)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to fetch ERC certificates: {}", e);
+            ApiError::Database(e)
+        })?;
+
+    Ok(Json(certificates))
+}
+
+/// Query parameters for the certificate listing
+#[derive(Debug, Deserialize)]
+pub struct ErcListQuery {
+    /// Effective status; `expired` includes valid certificates past their expiry
+    pub status: Option<ErcStatus>,
+    /// Renewable source, e.g. `solar`
+    pub source: Option<String>,
+    pub issued_after: Option<DateTime<Utc>>,
+    /// Cursor from the previous page's `next_page`
+    pub page: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// List ERC certificates newest first, from the indexer or straight from the governance program
+/// GET /api/v1/erc
+pub async fn search_certificates(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    scope: TenantScope,
+    Query(params): Query<ErcListQuery>,
+) -> Result<Json<ErcPage>> {
+    if params.status.is_some_and(|status| !status.is_known()) {
+        return Err(ApiError::BadRequest("Unknown certificate status".to_string()));
+    }
+    let filter = CertificateFilter {
+        status: params.status,
+        source: params.source.filter(|source| !source.trim().is_empty()),
+        issued_after: params.issued_after,
+        after: params.page.as_deref().map(cursor::decode).transpose()?,
+        limit: params.limit.unwrap_or(50).clamp(1, 500),
+    };
+
+    let page = CertificateStore::from_state(&state)
+        .list(&filter, &scope, state.clock.now())
+        .await?;
+    Ok(Json(page))
+}
+
+/// Query parameters for the certificate event stream
+#[derive(Debug, Deserialize)]
+pub struct ErcStreamQuery {
+    /// Resume after this event, for clients that cannot send `Last-Event-ID`
+    pub last_event_id: Option<i64>,
+}
+
+/// Event ID to resume after, from the `Last-Event-ID` header or else the query
+fn resume_after(headers: &HeaderMap, params: &ErcStreamQuery) -> Result<Option<i64>> {
+    match headers.get("last-event-id") {
+        Some(value) => value
+            .to_str()
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .map(Some)
+            .ok_or_else(|| ApiError::BadRequest("Last-Event-ID must be an event ID".to_string())),
+        None => Ok(params.last_event_id),
+    }
+}
+
+/// Live certificate issuance, validation, revocation and expiry as server-sent events
+/// GET /api/v1/erc/stream
+///
+/// Each event's ID is its position in the event log. `EventSource` clients reconnect with
+/// `Last-Event-ID` and receive every event recorded after it; without one the stream starts
+/// with the next event.
+pub async fn stream_events(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    headers: HeaderMap,
+    Query(params): Query<ErcStreamQuery>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, axum::Error>>>> {
+    let after = match resume_after(&headers, &params)? {
+        Some(after) => after,
+        None => state.erc_events.latest_id().await?,
+    };
+
+    let events = state.erc_events.stream(after).map(|event| {
+        Event::default()
+            .id(event.id.to_string())
+            .event(event.event_type.as_str())
+            .json_data(&event)
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Get an indexed ERC certificate, optionally as it was at `as_of`
+/// GET /api/v1/erc/certificates/:certificate_id
+pub async fn get_certificate(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    scope: TenantScope,
+    Path(certificate_id): Path<String>,
+    Query(params): Query<AsOfQuery>,
+) -> Result<Json<ErcCertificate>> {
+    scope.require_certificate(&state.db, &certificate_id).await?;
+    let certificate = CertificateStore::from_state(&state)
+        .get(&certificate_id, params.as_of)
+        .await?;
+    Ok(Json(certificate))
+}
+
+/// Signed verification link to print as a certificate's QR code
+/// GET /api/v1/erc/certificates/:certificate_id/verification-link
+pub async fn get_verification_link(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    scope: TenantScope,
+    Path(certificate_id): Path<String>,
+) -> Result<Json<ErcVerificationLink>> {
+    scope.require_certificate(&state.db, &certificate_id).await?;
+    let indexed: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM erc_certificates WHERE certificate_id = $1)")
+        .bind(&certificate_id)
+        .fetch_one(&state.db)
+        .await?;
+    if !indexed {
+        return Err(ApiError::NotFound(format!("ERC certificate {} not found", certificate_id)));
+    }
+
+    let link = CertificateVerifier::from_state(&state)?.link(&certificate_id)?;
+    Ok(Json(link))
+}
+
+/// Checked against the limits the governance program enforces, for clearer errors
+#[derive(Debug, Deserialize, Validate, utoipa::ToSchema)]
+pub struct IssueErcRequest {
+    #[validate(custom(function = "validation::certificate_id"))]
+    pub certificate_id: String,
+    /// Certified energy in kWh
+    #[validate(range(min = 1))]
+    pub energy_amount: u64,
+    /// `solar`, `wind`, `biomass`, `hydro` or the name of another source
+    #[validate(length(min = 1, max = 32))]
+    pub renewable_source: String,
+    #[serde(default)]
+    #[validate(custom(function = "validation::validation_data"))]
+    pub validation_data: String,
+    /// Oracle meter reading PDAs backing the certificate
+    #[serde(default)]
+    #[validate(length(max = MAX_SOURCE_READINGS))]
+    pub source_readings: Vec<String>,
+    /// Building or faculty the certificate is issued for; defaults to the issuer's own
+    pub tenant: Option<String>,
+}
+
+impl ValidateRequest for IssueErcRequest {}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidateErcRequest {
+    /// Wallet that receives the certificate token
+    pub recipient: String,
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).map_err(ApiError::BadRequest)
+}
+
+/// Queue issuance of an ERC certificate on-chain as the Engineering Department
+/// POST /api/v1/erc
+///
+/// Returns the transaction job to poll at `GET /api/v1/tx/:job_id`.
+#[utoipa::path(
+    post,
+    path = "/erc",
+    tag = "erc",
+    request_body = IssueErcRequest,
+    responses(
+        (status = 202, description = "Issuance queued", body = TxJob),
+        (status = 400, description = "Request outside the governance program's limits",
+            body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn issue_certificate(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    scope: TenantScope,
+    ValidatedJson(payload): ValidatedJson<IssueErcRequest>,
+) -> Result<(StatusCode, Json<TxJob>)> {
+    for reading in &payload.source_readings {
+        parse_pubkey(reading)?;
+    }
+    let tenant = payload.tenant.or_else(|| user.0.tenant.clone());
+    if !scope.allows(tenant.as_deref()) {
+        return Err(ApiError::Authorization(
+            "Certificates can only be issued for your own tenant".to_string(),
+        ));
+    }
+    if let Some(tenant) = &tenant {
+        TenantStore::from_state(&state).get(tenant).await?;
+        CertificateStore::from_state(&state)
+            .assign_tenant(&payload.certificate_id, tenant)
+            .await?;
+    }
+
+    tracing::info!("User {} issuing ERC {}", user.0.sub, payload.certificate_id);
+    let operation = TxOperation::IssueErc {
+        certificate_id: payload.certificate_id,
+        energy_amount: payload.energy_amount,
+        renewable_source: payload.renewable_source,
+        validation_data: payload.validation_data,
+        source_readings: payload.source_readings,
+    };
+    let job = TxQueue::from_state(&state).enqueue(&operation, user.0.sub).await?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Queue validation of an ERC certificate for trading, minting its token to the recipient
+/// POST /api/v1/erc/:certificate_id/validate
+///
+/// Returns the transaction job to poll at `GET /api/v1/tx/:job_id`.
+pub async fn validate_certificate(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(certificate_id): Path<String>,
+    Json(payload): Json<ValidateErcRequest>,
+) -> Result<(StatusCode, Json<TxJob>)> {
+    parse_pubkey(&payload.recipient)?;
+
+    tracing::info!("User {} validating ERC {} for trading", user.0.sub, certificate_id);
+    let operation = TxOperation::ValidateErc {
+        certificate_id,
+        recipient: payload.recipient,
+    };
+    let job = TxQueue::from_state(&state).enqueue(&operation, user.0.sub).await?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Automatic issuances returned by a history request without a limit
+const DEFAULT_AUTO_ISSUANCE_LIMIT: i64 = 50;
+
+/// Query parameters for the automatic issuance history
+#[derive(Debug, Deserialize)]
+pub struct AutoIssuanceQuery {
+    /// Only certificates issued to this prosumer
+    pub user_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// Certificates issued automatically from validated net generation, newest first
+/// GET /api/v1/admin/erc/auto-issuances
+pub async fn list_auto_issuances(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Query(query): Query<AutoIssuanceQuery>,
+) -> Result<Json<Vec<ErcAutoIssuance>>> {
+    let issuances = AutoIssuer::from_state(&state)?
+        .list(query.user_id, &scope, query.limit.unwrap_or(DEFAULT_AUTO_ISSUANCE_LIMIT))
+        .await?;
+    Ok(Json(issuances))
+}
+
+/// Issue certificates now to every prosumer whose uncertified net generation crossed the
+/// threshold, without waiting for `ERC_AUTO_ISSUANCE_SCHEDULE`
+/// POST /api/v1/admin/erc/auto-issuances
+pub async fn run_auto_issuance(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<(StatusCode, Json<Vec<ErcAutoIssuance>>)> {
+    let issued = AutoIssuer::from_state(&state)?.run(state.clock.now()).await?;
+    tracing::info!("Automatic ERC issuance run by {} queued {} certificates", user.0.sub, issued.len());
+    Ok((StatusCode::ACCEPTED, Json(issued)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyQuery {
+    /// Gateway signature embedded in the printed QR code
+    #[serde(default)]
+    pub sig: String,
+}
+
+/// Verify a printed certificate against its live on-chain state (public)
+/// GET /verify/erc/:certificate_id?sig=...
+///
+/// Browsers get a small HTML page; other clients get JSON.
+pub async fn verify_certificate(
+    State(state): State<AppState>,
+    Path(certificate_id): Path<String>,
+    Query(params): Query<VerifyQuery>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let verification = CertificateVerifier::from_state(&state)?
+        .verify(&certificate_id, &params.sig)
+        .await?;
+
+    let wants_html = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+
+    let cache = [
+        (header::CACHE_CONTROL, VERIFICATION_CACHE_CONTROL),
+        (header::VARY, "Accept"),
+    ];
+    if wants_html {
+        Ok((cache, Html(render_verification(&verification))).into_response())
+    } else {
+        Ok((cache, Json(verification)).into_response())
+    }
+}
+
+fn render_verification(verification: &ErcVerification) -> String {
+    let (color, headline) = match (verification.verified, verification.signature_valid) {
+        (true, _) => ("#1b7f3b", "Certificate verified"),
+        (false, true) => ("#b26a00", "Certificate not currently valid"),
+        (false, false) => ("#b00020", "Verification link is not genuine"),
+    };
+
+    let mut html = String::new();
+    // Writing to a String cannot fail
+    let _ = write!(
+        html,
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <title>ERC {id}</title></head>\
+         <body style=\"font-family:sans-serif;margin:1.5em\">\
+         <h1 style=\"color:{color}\">{headline}</h1><p>Certificate <strong>{id}</strong></p><dl>",
+        id = escape(&verification.certificate_id),
+    );
+
+    let mut row = |label: &str, value: Option<String>| {
+        if let Some(value) = value {
+            let _ = write!(html, "<dt>{}</dt><dd>{}</dd>", label, escape(&value));
+        }
+    };
+    row("Status", verification.status.clone());
+    row("Source", verification.renewable_source.clone());
+    row("Energy", verification.energy_amount.map(|kwh| format!("{} kWh", kwh)));
+    row("Issued", verification.issued_at.map(|t| t.format("%Y-%m-%d").to_string()));
+    row("Expires", verification.expires_at.map(|t| t.format("%Y-%m-%d").to_string()));
+    row("Account", verification.account_address.clone());
+
+    let _ = write!(
+        html,
+        "</dl><p><small>Checked on-chain at {} UTC</small></p></body></html>",
+        verification.checked_at.format("%Y-%m-%d %H:%M:%S")
+    );
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_event_id_header_takes_precedence() {
+        let params = ErcStreamQuery { last_event_id: Some(3) };
+        assert_eq!(resume_after(&HeaderMap::new(), &params).unwrap(), Some(3));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("last-event-id", "42".parse().unwrap());
+        assert_eq!(resume_after(&headers, &params).unwrap(), Some(42));
+
+        headers.insert("last-event-id", "erc-42".parse().unwrap());
+        assert!(matches!(resume_after(&headers, &params), Err(ApiError::BadRequest(_))));
+    }
+}
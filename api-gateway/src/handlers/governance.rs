@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::database::history::{as_of_condition, source_table, AsOfQuery};
+use crate::error::{ApiError, Result};
+use crate::models::governance::{GovernanceConfig, GovernanceTransaction};
+use crate::services::gateway_signer::KeyRotation;
+use crate::services::governance_admin::{GovernanceAction, GovernanceAdmin};
+use crate::AppState;
+
+/// Get the indexed governance configuration, optionally as it was at `as_of`
+/// GET /api/v1/governance/config
+pub async fn get_governance_config(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    Query(params): Query<AsOfQuery>,
+) -> Result<Json<GovernanceConfig>> {
+    let mut query = format!(
+        "SELECT account_address, authority, authority_name, contact_email, contact_phone, website, \
+         office_location, emergency_paused, pause_flags, maintenance_mode, erc_validation_enabled, \
+         oracle_authority, min_energy_amount, \
+         max_erc_amount, erc_validity_period, erc_issuance_fee, council_mode, max_pause_duration, trading_program, \
+         issuance_window, max_window_ercs, max_window_energy, total_ercs_issued, total_ercs_validated, slot, updated_at \
+         FROM {} WHERE id = 1",
+        source_table("governance_config", params.as_of)
+    );
+    if params.as_of.is_some() {
+        query.push_str(&format!(" AND {}", as_of_condition(1)));
+    }
+
+    let mut sqlx_query = sqlx::query_as::<_, GovernanceConfig>(&query);
+    if let Some(as_of) = params.as_of {
+        sqlx_query = sqlx_query.bind(as_of);
+    }
+
+    let config = sqlx_query
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to fetch governance config: {}", e);
+            ApiError::Database(e)
+        })?
+        .ok_or_else(|| ApiError::NotFound("Governance configuration not indexed".to_string()))?;
+
+    Ok(Json(config))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MaintenanceRequest {
+    pub enabled: bool,
+}
+
+/// ERC limits; the governance program rejects inconsistent values
+#[derive(Debug, Deserialize)]
+pub struct ErcLimitsRequest {
+    pub min_energy_amount: u64,
+    pub max_erc_amount: u64,
+    /// Certificate validity in seconds
+    pub erc_validity_period: i64,
+}
+
+async fn execute(state: &AppState, user: &AuthenticatedUser, action: GovernanceAction) -> Result<Json<GovernanceTransaction>> {
+    let transaction = GovernanceAdmin::from_state(state)?.execute(user.0.sub, action).await?;
+    Ok(Json(transaction))
+}
+
+/// Emergency-pause ERC issuance and validation on-chain
+/// POST /api/v1/admin/governance/pause
+pub async fn pause(State(state): State<AppState>, user: AuthenticatedUser) -> Result<Json<GovernanceTransaction>> {
+    execute(&state, &user, GovernanceAction::Pause).await
+}
+
+/// Lift an on-chain pause
+/// POST /api/v1/admin/governance/unpause
+pub async fn unpause(State(state): State<AppState>, user: AuthenticatedUser) -> Result<Json<GovernanceTransaction>> {
+    execute(&state, &user, GovernanceAction::Unpause).await
+}
+
+/// Update the on-chain ERC limits
+/// PUT /api/v1/admin/governance/limits
+pub async fn update_limits(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<ErcLimitsRequest>,
+) -> Result<Json<GovernanceTransaction>> {
+    let action = GovernanceAction::Limits {
+        min_energy_amount: payload.min_energy_amount,
+        max_erc_amount: payload.max_erc_amount,
+        erc_validity_period: payload.erc_validity_period,
+    };
+    execute(&state, &user, action).await
+}
+
+/// Turn on-chain maintenance mode on or off
+/// PUT /api/v1/admin/governance/maintenance
+pub async fn set_maintenance(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<MaintenanceRequest>,
+) -> Result<Json<GovernanceTransaction>> {
+    execute(&state, &user, GovernanceAction::Maintenance { enabled: payload.enabled }).await
+}
+
+/// Reload the gateway signing keypair from `API_GATEWAY_KEYPAIR_PATH` (audited)
+/// POST /api/v1/admin/signer/reload
+///
+/// Transactions being signed when the reload starts are submitted with the old key; every
+/// later one is signed with the new key.
+pub async fn reload_signer(State(state): State<AppState>, user: AuthenticatedUser) -> Result<Json<KeyRotation>> {
+    let rotation = state.signer.reload().await?;
+
+    sqlx::query("INSERT INTO governance_actions (user_id, action, parameters) VALUES ($1, $2, $3)")
+        .bind(user.0.sub)
+        .bind("rotate_gateway_signer")
+        .bind(json!({ "previous": rotation.previous, "current": rotation.current }))
+        .execute(&state.db)
+        .await?;
+
+    tracing::warn!(
+        "User {} rotated the gateway signer from {} to {}",
+        user.0.sub,
+        rotation.previous.as_deref().unwrap_or("none"),
+        rotation.current
+    );
+    Ok(Json(rotation))
+}
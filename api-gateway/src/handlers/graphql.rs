@@ -0,0 +1,23 @@
+use axum::{extract::State, response::Json};
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::tenant::TenantScope;
+use crate::AppState;
+
+/// Nested queries over prosumers, meters, readings, certificates and trades
+/// POST /api/v1/graphql
+pub async fn execute(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    scope: TenantScope,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let schema = state.graphql.clone();
+    Json(schema.execute(request.data(state).data(user.0).data(scope)).await)
+}
+
+/// Schema in SDL, for client code generation
+/// GET /api/v1/graphql/schema
+pub async fn schema(State(state): State<AppState>) -> String {
+    state.graphql.sdl()
+}
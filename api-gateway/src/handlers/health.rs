@@ -1,8 +1,28 @@
-use axum::{response::Json, http::StatusCode};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::handlers::blockchain::singleton_address;
+use crate::services::blockchain::BlockchainService;
+#[cfg(feature = "payments")]
+use crate::services::channels::channel_operator;
+use crate::services::circuit_breaker::CircuitState;
+#[cfg(feature = "payments")]
+use crate::services::transaction::Pubkey;
+use crate::AppState;
+
+/// Time each readiness dependency check may take before it counts as failed
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+type CheckResult = std::result::Result<(), String>;
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct HealthStatus {
     pub status: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
@@ -12,7 +32,7 @@ pub struct HealthStatus {
     pub dependencies: Vec<ServiceHealth>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct ServiceHealth {
     pub name: String,
     pub status: String,
@@ -50,34 +70,190 @@ impl HealthStatus {
 }
 
 /// Basic health check endpoint
+#[utoipa::path(get, path = "/health", tag = "health", responses((status = 200, body = HealthStatus)))]
 pub async fn health_check() -> Json<HealthStatus> {
     Json(HealthStatus::new())
 }
 
-/// Readiness check - checks if service is ready to accept traffic
-pub async fn readiness_check() -> Result<Json<HealthStatus>, StatusCode> {
+/// Run `check` under `CHECK_TIMEOUT`, returning its result and duration in milliseconds
+async fn timed(check: impl Future<Output = CheckResult>) -> (CheckResult, u64) {
+    let started = Instant::now();
+    let result = tokio::time::timeout(CHECK_TIMEOUT, check)
+        .await
+        .unwrap_or_else(|_| Err(format!("Timed out after {}s", CHECK_TIMEOUT.as_secs())));
+    (result, started.elapsed().as_millis() as u64)
+}
+
+async fn check_postgres(pool: &sqlx::PgPool) -> CheckResult {
+    sqlx::query("SELECT 1")
+        .execute(pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+async fn check_redis(client: &redis::Client) -> CheckResult {
+    let mut conn = client
+        .get_multiplexed_tokio_connection()
+        .await
+        .map_err(|e| e.to_string())?;
+    redis::cmd("PING")
+        .query_async::<_, String>(&mut conn)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// The node must be healthy and the RPC circuit breaker closed; a half-open breaker's health
+/// call is one of its probes
+async fn check_solana_rpc(chain: &BlockchainService) -> CheckResult {
+    let breaker = chain.circuit_breaker();
+    if let Some(retry_at) = breaker.retry_at() {
+        return Err(format!("Circuit breaker open, probing again at {}", retry_at.to_rfc3339()));
+    }
+    chain.get_health().await.map_err(|e| e.to_string())?;
+    match breaker.state() {
+        CircuitState::Closed => Ok(()),
+        state => Err(format!("Circuit breaker {}", state.as_str().replace('_', "-"))),
+    }
+}
+
+/// The oracle PDA must be initialized for meter readings to reach the chain
+async fn check_oracle(state: &AppState) -> CheckResult {
+    let address = singleton_address(&state.config.oracle_program_id, b"oracle_data").map_err(|e| e.to_string())?;
+    match state.blockchain_service.get_account_data(&address).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(format!("Oracle account {} does not exist", address)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn balance_check(signer: &str, address: &str, balance: u64, minimum: u64) -> CheckResult {
+    if balance < minimum {
+        return Err(format!(
+            "{} {} holds {} lamports, below the {} required",
+            signer, address, balance, minimum
+        ));
+    }
+    Ok(())
+}
+
+/// Every configured signing key must hold enough SOL to pay transaction fees
+async fn check_signer_balances(state: &AppState) -> CheckResult {
+    #[cfg(feature = "payments")]
+    let channel_operator = channel_operator(&state.config)
+        .map_err(|e| e.to_string())?
+        .map(|key| Pubkey(key.verifying_key().to_bytes()));
+    // Payment channels are only run with the `payments` feature
+    #[cfg(not(feature = "payments"))]
+    let channel_operator = None;
+    let signers = [
+        ("Gateway signer", state.signer.pubkey().await),
+        ("Channel operator", channel_operator),
+    ];
+    for (signer, key) in signers {
+        let Some(key) = key else {
+            continue;
+        };
+        let address = key.to_string();
+        let balance = state
+            .blockchain_service
+            .get_balance(&address)
+            .await
+            .map_err(|e| e.to_string())?;
+        balance_check(signer, &address, balance, state.config.min_signer_balance)?;
+    }
+    check_fee_payer_balances(state).await
+}
+
+/// A configured fee payer pool needs at least one payer able to pay; the rest are skipped
+async fn check_fee_payer_balances(state: &AppState) -> CheckResult {
+    let payers = state.fee_payers.pubkeys();
+    let mut shortfalls = Vec::new();
+    for payer in &payers {
+        let address = payer.to_string();
+        let balance = state
+            .blockchain_service
+            .get_balance(&address)
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Err(shortfall) = balance_check("Fee payer", &address, balance, state.config.min_signer_balance) {
+            shortfalls.push(shortfall);
+        }
+    }
+    if !payers.is_empty() && shortfalls.len() == payers.len() {
+        return Err(shortfalls.join("; "));
+    }
+    Ok(())
+}
+
+/// Readiness check for Kubernetes probes, at `/readyz`
+///
+/// Checks every dependency concurrently and responds 503 with the per-dependency status
+/// if any of them is unhealthy.
+pub async fn readiness_check(State(state): State<AppState>) -> (StatusCode, Json<HealthStatus>) {
+    let (postgres, timescale, redis, solana_rpc, oracle, signer_balances) = tokio::join!(
+        timed(check_postgres(&state.db)),
+        timed(check_postgres(&state.timescale_db)),
+        timed(check_redis(state.redis.client())),
+        timed(check_solana_rpc(&state.blockchain_service)),
+        timed(check_oracle(&state)),
+        timed(check_signer_balances(&state)),
+    );
+
     let mut status = HealthStatus::new();
-    
-    // TODO: Add actual database connectivity check
-    status.add_dependency_check("database", true, Some(5), None);
-    
-    // TODO: Add Redis connectivity check
-    status.add_dependency_check("redis", true, Some(2), None);
-    
-    // TODO: Add Solana RPC connectivity check
-    status.add_dependency_check("solana_rpc", true, Some(10), None);
-    
-    if status.status == "healthy" {
-        Ok(Json(status))
-    } else {
-        Err(StatusCode::SERVICE_UNAVAILABLE)
+    for (name, (result, response_time)) in [
+        ("postgres", postgres),
+        ("timescale", timescale),
+        ("redis", redis),
+        ("solana_rpc", solana_rpc),
+        ("oracle", oracle),
+        ("signer_balances", signer_balances),
+    ] {
+        status.add_dependency_check(name, result.is_ok(), Some(response_time), result.err());
     }
+
+    let code = if status.status == "healthy" {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (code, Json(status))
 }
 
-/// Liveness check - checks if service is running
+/// Liveness check - checks if the process is running, at `/healthz`
 pub async fn liveness_check() -> Json<HashMap<String, String>> {
     let mut response = HashMap::new();
     response.insert("status".to_string(), "alive".to_string());
     response.insert("timestamp".to_string(), chrono::Utc::now().to_rfc3339());
     Json(response)
-}
\ No newline at end of file
+}
+/// Prometheus metrics in the text exposition format
+pub async fn prometheus_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signer_balance_threshold() {
+        assert!(balance_check("Governance authority", "Gov1", 10_000_000, 10_000_000).is_ok());
+        let error = balance_check("Channel operator", "Op1", 9_999, 10_000_000).unwrap_err();
+        assert_eq!(error, "Channel operator Op1 holds 9999 lamports, below the 10000000 required");
+    }
+
+    #[test]
+    fn test_unhealthy_dependency_degrades_status() {
+        let mut status = HealthStatus::new();
+        status.add_dependency_check("postgres", true, Some(1), None);
+        assert_eq!(status.status, "healthy");
+        status.add_dependency_check("redis", false, Some(3000), Some("Timed out after 3s".to_string()));
+        assert_eq!(status.status, "degraded");
+        assert_eq!(status.dependencies[1].error_message.as_deref(), Some("Timed out after 3s"));
+    }
+}
@@ -0,0 +1,83 @@
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::{ApiError, Result};
+use crate::models::indexer::{IndexerBackfill, IndexerStatus};
+use crate::services::backfill::{BackfillService, BACKFILL_COLUMNS};
+use crate::services::transaction::{Pubkey, SIGNATURE_LENGTH};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct StartBackfillRequest {
+    /// Program or account whose history is backfilled
+    pub address: String,
+    /// Stop at this signature, e.g. the newest one already indexed
+    pub until_signature: Option<String>,
+}
+
+/// Indexer progress, including recent backfills
+/// GET /api/v1/admin/indexer/status
+pub async fn get_indexer_status(State(state): State<AppState>) -> Result<Json<IndexerStatus>> {
+    let (indexed_transactions, running_backfills): (i64, i64) = sqlx::query_as(
+        "SELECT (SELECT COUNT(*) FROM indexed_transactions),
+                (SELECT COUNT(*) FROM indexer_backfills WHERE status = $1)",
+    )
+    .bind(IndexerBackfill::RUNNING)
+    .fetch_one(&state.db)
+    .await?;
+
+    let query = format!(
+        "SELECT {} FROM indexer_backfills ORDER BY created_at DESC LIMIT 20",
+        BACKFILL_COLUMNS
+    );
+    let backfills = sqlx::query_as::<_, IndexerBackfill>(&query)
+        .fetch_all(&state.db)
+        .await?;
+
+    Ok(Json(IndexerStatus {
+        indexed_transactions,
+        running_backfills,
+        backfills,
+    }))
+}
+
+/// Start backfilling an address's history
+/// POST /api/v1/admin/indexer/backfills
+pub async fn start_backfill(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<StartBackfillRequest>,
+) -> Result<(StatusCode, Json<IndexerBackfill>)> {
+    Pubkey::from_str(&request.address).map_err(ApiError::BadRequest)?;
+    if let Some(until) = &request.until_signature {
+        let valid = bs58::decode(until).into_vec().map(|bytes| bytes.len() == SIGNATURE_LENGTH).unwrap_or(false);
+        if !valid {
+            return Err(ApiError::BadRequest(format!("Invalid signature: {}", until)));
+        }
+    }
+
+    let backfill = BackfillService::from_state(&state)
+        .start(&request.address, request.until_signature.as_deref(), user.0.sub)
+        .await?;
+
+    tracing::info!("Indexer backfill of {} started by {}", request.address, user.0.sub);
+    Ok((StatusCode::ACCEPTED, Json(backfill)))
+}
+
+/// Cancel a running backfill; its progress is kept
+/// DELETE /api/v1/admin/indexer/backfills/:id
+pub async fn cancel_backfill(
+    State(state): State<AppState>,
+    Path(backfill_id): Path<Uuid>,
+) -> Result<Json<IndexerBackfill>> {
+    let backfill = BackfillService::from_state(&state).cancel(backfill_id).await?;
+    Ok(Json(backfill))
+}
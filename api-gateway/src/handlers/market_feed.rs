@@ -0,0 +1,57 @@
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::services::market_feed::MarketUpdate;
+use crate::services::order_book::OrderBookChange;
+use crate::AppState;
+
+/// Live order book deltas, trades and clearing results
+/// GET /ws/market
+///
+/// Sends a `snapshot` first, then updates as they happen. Another `snapshot` follows
+/// whenever the client fell behind or the book was rebuilt. Browsers, which cannot set
+/// headers on a websocket, pass their token as `?access_token=`.
+pub async fn market_socket(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ws.on_upgrade(move |socket| stream_market(socket, state))
+}
+
+async fn stream_market(mut socket: WebSocket, state: AppState) {
+    // Subscribing before taking the snapshot means no update falls between the two
+    let mut updates = state.market_feed.subscribe();
+    let mut next = Some(state.market_feed.snapshot(&state.order_book));
+    metrics::gauge!("market_feed_clients").increment(1.0);
+
+    loop {
+        if let Some(update) = next.take() {
+            let text = match serde_json::to_string(&update) {
+                Ok(text) => text,
+                Err(e) => {
+                    tracing::error!("Failed to serialize market update: {}", e);
+                    continue;
+                }
+            };
+            if socket.send(Message::Text(text)).await.is_err() {
+                break;
+            }
+        }
+
+        tokio::select! {
+            update = updates.recv() => match update {
+                Ok(MarketUpdate::OrderBook(OrderBookChange::Reset { .. })) | Err(RecvError::Lagged(_)) => {
+                    next = Some(state.market_feed.snapshot(&state.order_book));
+                }
+                Ok(update) => next = Some(update),
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                // Pings are answered by axum; anything else from the client is ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    metrics::gauge!("market_feed_clients").decrement(1.0);
+}
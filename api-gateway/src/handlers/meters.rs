@@ -1,5 +1,7 @@
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     response::Json,
 };
 use chrono::{DateTime, Utc};
@@ -9,8 +11,22 @@ use validator::Validate;
 
 use crate::{
     auth::middleware::AuthenticatedUser,
-    error::{ApiError, Result},
-    models::energy::{EnergyReading, EnergyReadingDb, EnergyReadingSubmission},
+    auth::tenant::{TenantScope, READING_TENANT},
+    database::schema::types::ReadingStatus,
+    error::{ApiError, ProblemDetails, Result},
+    models::energy::{
+        EnergyForecast, EnergyPoint, EnergyReading, EnergyReadingDb, EnergyReadingSubmission, Meter, MeterCredentials,
+        MeterSigningKey, QuarantinedReading,
+    },
+    models::meter_polling::MeterPollingConfig,
+    services::forecast::{parse_horizon, ForecastMethod, ForecastService},
+    services::meter_keys::MeterKeyStore,
+    services::meter_polling::{MeterPoller, MeterPollingRequest},
+    services::meter_provisioning::{MeterProvisioning, MeterRegistrationRequest},
+    services::reading_import::{ImportFormat, ImportSummary, ReadingImporter},
+    services::readings::{ReadingStore, ReviewDecision},
+    services::timeseries::{EnergyBucket, TimeseriesStore},
+    utils::validation::ValidatedJson,
     AppState,
 };
 
@@ -25,84 +41,52 @@ pub struct EnergyReadingQuery {
 }
 
 /// Response for energy reading submission
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct EnergyReadingResponse {
     pub id: Uuid,
     pub meter_id: String,
     pub timestamp: DateTime<Utc>,
-    pub status: String,
+    /// submitted, or quarantined for review when it fails anomaly screening
+    #[schema(value_type = String, example = "submitted")]
+    pub status: ReadingStatus,
     pub created_at: DateTime<Utc>,
 }
 
 /// Submit a new energy reading from a smart meter
 /// POST /api/v1/meters/readings
+#[utoipa::path(
+    post,
+    path = "/meters/readings",
+    tag = "meters",
+    request_body = EnergyReadingSubmission,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the first response to retries with the same key"),
+    ),
+    responses(
+        (status = 200, description = "Reading recorded or quarantined", body = EnergyReadingResponse),
+        (status = 400, description = "Reading outside the limits the oracle program accepts",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing credentials, or meter signature missing or invalid",
+            body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = []), ("api_key" = []))
+)]
+#[tracing::instrument(skip_all, fields(meter_id = %payload.meter_id))]
 pub async fn submit_energy_reading(
     State(state): State<AppState>,
     _user: AuthenticatedUser,
-    Json(payload): Json<EnergyReadingSubmission>,
+    ValidatedJson(payload): ValidatedJson<EnergyReadingSubmission>,
 ) -> Result<Json<EnergyReadingResponse>> {
     tracing::info!("Submitting energy reading for meter: {}", payload.meter_id);
 
-    // Validate engineering authority signature (for Phase 3)
-    if payload.engineering_authority_signature.is_empty() {
-        return Err(ApiError::BadRequest("Engineering authority signature required".to_string()));
-    }
-
-    // Insert energy reading into TimescaleDB
-    let reading_id = Uuid::new_v4();
-    let now = Utc::now();
-    
-    let metadata_json = payload.metadata.as_ref().map(|m| serde_json::to_value(m).unwrap());
-
-    // Convert f64 values to BigDecimal for database storage
-    let energy_generated_bd = {
-        use std::str::FromStr;
-        sqlx::types::BigDecimal::from_str(&payload.energy_generated.to_string()).unwrap_or_default()
-    };
-    let energy_consumed_bd = {
-        use std::str::FromStr;
-        sqlx::types::BigDecimal::from_str(&payload.energy_consumed.to_string()).unwrap_or_default()
-    };
-    let solar_irradiance_bd = payload.solar_irradiance.map(|val| {
-        use std::str::FromStr;
-        sqlx::types::BigDecimal::from_str(&val.to_string()).unwrap_or_default()
-    });
-    let temperature_bd = payload.temperature.map(|val| {
-        use std::str::FromStr;
-        sqlx::types::BigDecimal::from_str(&val.to_string()).unwrap_or_default()
-    });
-
-    sqlx::query!(
-        r#"
-        INSERT INTO energy_readings (
-            id, meter_id, timestamp, energy_generated, energy_consumed, 
-            solar_irradiance, temperature, metadata, created_at
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-        "#,
-        reading_id,
-        payload.meter_id,
-        payload.timestamp,
-        energy_generated_bd,
-        energy_consumed_bd,
-        solar_irradiance_bd,
-        temperature_bd,
-        metadata_json,
-        now
-    )
-    .execute(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to insert energy reading: {}", e);
-        ApiError::Database(e)
-    })?;
-
-    // TODO: In Phase 4, trigger blockchain submission for verified readings
+    let now = state.clock.now();
+    let recorded = ReadingStore::from_state(&state).submit(&payload, now).await?;
 
     Ok(Json(EnergyReadingResponse {
-        id: reading_id,
+        id: recorded.id,
         meter_id: payload.meter_id,
         timestamp: payload.timestamp,
-        status: "submitted".to_string(),
+        status: recorded.status,
         created_at: now,
     }))
 }
@@ -112,13 +96,17 @@ pub async fn submit_energy_reading(
 pub async fn get_energy_readings(
     State(state): State<AppState>,
     user: AuthenticatedUser,
+    scope: TenantScope,
     Query(params): Query<EnergyReadingQuery>,
 ) -> Result<Json<Vec<EnergyReading>>> {
     tracing::info!("Fetching energy readings for user: {}", user.0.sub);
 
-    // Build dynamic query based on parameters
-    let mut query = "SELECT id, meter_id, timestamp, energy_generated, energy_consumed, solar_irradiance, temperature, metadata, created_at FROM energy_readings WHERE 1=1".to_string();
-    let mut bind_count = 1;
+    // Build dynamic query based on parameters, narrowed to the caller's tenant
+    let mut query = format!(
+        "SELECT id, meter_id, timestamp, energy_generated, energy_consumed, solar_irradiance, temperature, metadata, created_at FROM energy_readings WHERE {}",
+        TenantScope::condition(READING_TENANT, 1)
+    );
+    let mut bind_count = 3;
     
     if let Some(meter_id) = &params.meter_id {
         query.push_str(&format!(" AND meter_id = ${}", bind_count));
@@ -147,7 +135,9 @@ pub async fn get_energy_readings(
     }
 
     // Execute parameterized query
-    let mut sqlx_query = sqlx::query_as::<_, EnergyReadingDb>(&query);
+    let mut sqlx_query = sqlx::query_as::<_, EnergyReadingDb>(&query)
+        .bind(scope.is_campus())
+        .bind(scope.tenant());
     
     if let Some(meter_id) = &params.meter_id {
         sqlx_query = sqlx_query.bind(meter_id);
@@ -184,6 +174,7 @@ pub async fn get_energy_readings(
 pub async fn get_energy_reading_by_id(
     State(state): State<AppState>,
     _user: AuthenticatedUser,
+    scope: TenantScope,
     Path(reading_id): Path<Uuid>,
 ) -> Result<Json<EnergyReading>> {
     tracing::info!("Fetching energy reading: {}", reading_id);
@@ -201,6 +192,10 @@ pub async fn get_energy_reading_by_id(
     })?
     .ok_or_else(|| ApiError::NotFound("Energy reading not found".to_string()))?;
 
+    if !scope.sees_meter(&state.db, &reading.meter_id).await? {
+        return Err(ApiError::NotFound("Energy reading not found".to_string()));
+    }
+
     Ok(Json(reading.into()))
 }
 
@@ -228,9 +223,11 @@ pub struct EnergyAggregation {
 pub async fn get_aggregated_readings(
     State(state): State<AppState>,
     user: AuthenticatedUser,
+    scope: TenantScope,
     Query(params): Query<AggregationQuery>,
 ) -> Result<Json<Vec<EnergyAggregation>>> {
     tracing::info!("Fetching aggregated readings for meter: {}", params.meter_id);
+    scope.require_meter(&state.db, &params.meter_id).await?;
 
     // Use standard PostgreSQL date_trunc function for aggregation
     let aggregated_data = sqlx::query_as!(
@@ -259,4 +256,336 @@ pub async fn get_aggregated_readings(
     })?;
 
     Ok(Json(aggregated_data))
-}
\ No newline at end of file
+}
+/// Query parameters for a meter's energy time series
+#[derive(Debug, Deserialize)]
+pub struct EnergySeriesQuery {
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// `15m`, `1h` (default) or `1d`
+    #[serde(default)]
+    pub bucket: EnergyBucket,
+}
+
+/// Energy generated and consumed per bucket for charts
+/// GET /api/v1/meters/{meter_id}/energy
+pub async fn get_energy_series(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    scope: TenantScope,
+    Path(meter_id): Path<String>,
+    Query(params): Query<EnergySeriesQuery>,
+) -> Result<Json<Vec<EnergyPoint>>> {
+    scope.require_meter(&state.db, &meter_id).await?;
+    let points = TimeseriesStore::from_state(&state)
+        .energy_series(&meter_id, params.bucket, params.start_time, params.end_time)
+        .await?;
+    Ok(Json(points))
+}
+
+/// Query parameters for a meter forecast
+#[derive(Debug, Deserialize)]
+pub struct ForecastQuery {
+    /// Hours (`24h`) or days (`2d`) ahead, up to a week; defaults to `24h`
+    pub horizon: Option<String>,
+    /// `exponential_smoothing` (default) or `seasonal_naive`
+    #[serde(default)]
+    pub method: ForecastMethod,
+}
+
+/// Expected hourly generation and consumption of a meter, e.g. solar output before placing orders
+/// GET /api/v1/forecast/{meter_id}?horizon=24h
+pub async fn get_forecast(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    scope: TenantScope,
+    Path(meter_id): Path<String>,
+    Query(params): Query<ForecastQuery>,
+) -> Result<Json<EnergyForecast>> {
+    scope.require_meter(&state.db, &meter_id).await?;
+    let hours = parse_horizon(params.horizon.as_deref().unwrap_or("24h"))?;
+    let forecast = ForecastService::from_state(&state)
+        .forecast(&meter_id, params.method, hours)
+        .await?;
+    Ok(Json(forecast))
+}
+
+/// Meters read over Modbus-TCP or DLMS/COSEM, with their last poll
+/// GET /api/v1/admin/meters/polling
+pub async fn list_polling(State(state): State<AppState>) -> Result<Json<Vec<MeterPollingConfig>>> {
+    Ok(Json(MeterPoller::from_state(&state).list().await?))
+}
+
+/// Poll a registered meter on a schedule instead of waiting for it to push readings
+/// PUT /api/v1/admin/meters/:meter_id/polling
+pub async fn configure_polling(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(meter_id): Path<String>,
+    Json(request): Json<MeterPollingRequest>,
+) -> Result<Json<MeterPollingConfig>> {
+    let config = MeterPoller::from_state(&state).configure(&meter_id, request).await?;
+    tracing::info!(
+        "Meter {} polled over {} every {}s, configured by {}",
+        meter_id,
+        config.protocol,
+        config.poll_interval_seconds,
+        user.0.sub
+    );
+    Ok(Json(config))
+}
+
+/// Stop polling a meter; its recorded readings are kept
+/// DELETE /api/v1/admin/meters/:meter_id/polling
+pub async fn remove_polling(State(state): State<AppState>, Path(meter_id): Path<String>) -> Result<StatusCode> {
+    MeterPoller::from_state(&state).remove(&meter_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Rated output of the inverter behind a meter
+#[derive(Debug, Deserialize)]
+pub struct InverterRequest {
+    pub capacity_kw: f64,
+}
+
+/// Quarantine readings from a meter generating more than its inverter can
+/// PUT /api/v1/admin/meters/:meter_id/inverter
+pub async fn set_inverter(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(meter_id): Path<String>,
+    Json(request): Json<InverterRequest>,
+) -> Result<StatusCode> {
+    ReadingStore::from_state(&state)
+        .set_inverter_capacity(&meter_id, request.capacity_kw)
+        .await?;
+    tracing::info!(
+        "Meter {} inverter rated at {} kW by {}",
+        meter_id,
+        request.capacity_kw,
+        user.0.sub
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Query parameters for provisioned meters
+#[derive(Debug, Deserialize)]
+pub struct MeterListQuery {
+    /// active or decommissioned; all when omitted
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Provisioned meters with their registration and reporting status
+/// GET /api/v1/admin/meters
+pub async fn list_meters(
+    State(state): State<AppState>,
+    Query(params): Query<MeterListQuery>,
+) -> Result<Json<Vec<Meter>>> {
+    if let Some(status) = params.status.as_deref() {
+        if ![Meter::ACTIVE, Meter::DECOMMISSIONED].contains(&status) {
+            return Err(ApiError::BadRequest(format!(
+                "status must be active or decommissioned, not {}",
+                status
+            )));
+        }
+    }
+
+    let meters = MeterProvisioning::from_state(&state)
+        .list(params.status.as_deref(), params.limit.unwrap_or(100))
+        .await?;
+    Ok(Json(meters))
+}
+
+/// Provision a meter, returning the signing key to install on it
+/// POST /api/v1/admin/meters
+pub async fn provision_meter(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<MeterRegistrationRequest>,
+) -> Result<(StatusCode, Json<MeterCredentials>)> {
+    let credentials = MeterProvisioning::from_state(&state).register(request, user.0.sub).await?;
+    tracing::info!("Meter {} provisioned by {}", credentials.meter.meter_id, user.0.sub);
+    Ok((StatusCode::CREATED, Json(credentials)))
+}
+
+/// GET /api/v1/admin/meters/:meter_id
+pub async fn get_meter(State(state): State<AppState>, Path(meter_id): Path<String>) -> Result<Json<Meter>> {
+    Ok(Json(MeterProvisioning::from_state(&state).get(&meter_id).await?))
+}
+
+/// Issue a provisioned meter a new signing key, revoking the previous one
+/// POST /api/v1/admin/meters/:meter_id/credentials
+pub async fn rotate_meter_credentials(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(meter_id): Path<String>,
+) -> Result<Json<MeterCredentials>> {
+    let credentials = MeterProvisioning::from_state(&state)
+        .rotate_credentials(&meter_id, user.0.sub)
+        .await?;
+    tracing::info!("Meter {} credentials rotated by {}", meter_id, user.0.sub);
+    Ok(Json(credentials))
+}
+
+/// Permanently retire a provisioned meter; its readings are rejected from then on
+/// POST /api/v1/admin/meters/:meter_id/decommission
+pub async fn decommission_meter(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(meter_id): Path<String>,
+) -> Result<Json<Meter>> {
+    let meter = MeterProvisioning::from_state(&state)
+        .decommission(&meter_id, user.0.sub)
+        .await?;
+    tracing::info!("Meter {} decommissioned by {}", meter_id, user.0.sub);
+    Ok(Json(meter))
+}
+
+/// Meter public key readings must be signed with
+#[derive(Debug, Deserialize)]
+pub struct MeterKeyRequest {
+    /// Base58 ed25519 public key
+    pub public_key: String,
+}
+
+/// Registered meter signing keys
+/// GET /api/v1/admin/meters/keys
+pub async fn list_meter_keys(State(state): State<AppState>) -> Result<Json<Vec<MeterSigningKey>>> {
+    Ok(Json(MeterKeyStore::from_state(&state).list().await?))
+}
+
+/// Register or rotate the key a meter signs its readings with
+/// PUT /api/v1/admin/meters/:meter_id/key
+pub async fn register_meter_key(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(meter_id): Path<String>,
+    Json(request): Json<MeterKeyRequest>,
+) -> Result<Json<MeterSigningKey>> {
+    let key = MeterKeyStore::from_state(&state)
+        .register(&meter_id, &request.public_key, user.0.sub)
+        .await?;
+    tracing::info!("Meter {} signing key set to {} by {}", meter_id, key.public_key, user.0.sub);
+    Ok(Json(key))
+}
+
+/// Remove a meter's signing key; its readings are then rejected while signatures are required
+/// DELETE /api/v1/admin/meters/:meter_id/key
+pub async fn remove_meter_key(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(meter_id): Path<String>,
+) -> Result<StatusCode> {
+    MeterKeyStore::from_state(&state).remove(&meter_id).await?;
+    tracing::info!("Meter {} signing key removed by {}", meter_id, user.0.sub);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Query parameters for the reading review queue
+#[derive(Debug, Deserialize)]
+pub struct QuarantineQuery {
+    /// pending (default), approved or rejected
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Optional explanation recorded with a review decision
+#[derive(Debug, Default, Deserialize)]
+pub struct ReviewRequest {
+    pub note: Option<String>,
+}
+
+/// Readings held for review after failing validation, newest first
+/// GET /api/v1/admin/readings/quarantine
+pub async fn list_quarantined(
+    State(state): State<AppState>,
+    Query(params): Query<QuarantineQuery>,
+) -> Result<Json<Vec<QuarantinedReading>>> {
+    let status = params.status.as_deref().unwrap_or(QuarantinedReading::PENDING);
+    if ![QuarantinedReading::PENDING, QuarantinedReading::APPROVED, QuarantinedReading::REJECTED].contains(&status) {
+        return Err(ApiError::BadRequest(format!(
+            "status must be pending, approved or rejected, not {}",
+            status
+        )));
+    }
+
+    let readings = ReadingStore::from_state(&state)
+        .quarantined(Some(status), params.limit.unwrap_or(100))
+        .await?;
+    Ok(Json(readings))
+}
+
+/// Store a quarantined reading as if it had passed validation
+/// POST /api/v1/admin/readings/quarantine/:id/approve
+pub async fn approve_reading(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    request: Option<Json<ReviewRequest>>,
+) -> Result<Json<QuarantinedReading>> {
+    review_reading(state, user, id, ReviewDecision::Approve, request).await
+}
+
+/// Discard a quarantined reading
+/// POST /api/v1/admin/readings/quarantine/:id/reject
+pub async fn reject_reading(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    request: Option<Json<ReviewRequest>>,
+) -> Result<Json<QuarantinedReading>> {
+    review_reading(state, user, id, ReviewDecision::Reject, request).await
+}
+
+async fn review_reading(
+    state: AppState,
+    user: AuthenticatedUser,
+    id: Uuid,
+    decision: ReviewDecision,
+    request: Option<Json<ReviewRequest>>,
+) -> Result<Json<QuarantinedReading>> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let reading = ReadingStore::from_state(&state)
+        .review(id, decision, user.0.sub, request.note.as_deref(), state.clock.now())
+        .await?;
+    tracing::info!(
+        "Quarantined reading {} from meter {} {} by {}",
+        id,
+        reading.meter_id,
+        reading.status,
+        user.0.sub
+    );
+    Ok(Json(reading))
+}
+
+/// Query parameters for a reading import
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    /// Also record the imported readings with the oracle program
+    pub on_chain: Option<bool>,
+}
+
+/// Import historical readings from a CSV (`text/csv`) or NDJSON (`application/x-ndjson`) upload
+/// POST /api/v1/readings/import
+pub async fn import_readings(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<ImportQuery>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Json<ImportSummary>> {
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok());
+    let format = ImportFormat::from_content_type(content_type)?;
+    let summary = ReadingImporter::from_state(&state)?
+        .import(format, body, params.on_chain.unwrap_or(false))
+        .await?;
+    tracing::info!(
+        "Imported {} of {} readings for {} ({} rejected)",
+        summary.imported,
+        summary.rows,
+        user.0.sub,
+        summary.rejected
+    );
+    Ok(Json(summary))
+}
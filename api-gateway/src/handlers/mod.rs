@@ -3,6 +3,36 @@ pub mod auth;
 pub mod user_management;
 pub mod users;
 pub mod meters;
+pub mod notifications;
 pub mod trading;
 pub mod blockchain;
-pub mod analytics;
\ No newline at end of file
+pub mod analytics;
+#[cfg(feature = "chain")]
+pub mod erc;
+#[cfg(feature = "chain")]
+pub mod governance;
+pub mod signing;
+pub mod roles;
+pub mod reports;
+pub mod indexer;
+#[cfg(feature = "payments")]
+pub mod channels;
+pub mod dashboard;
+pub mod api_keys;
+pub mod audit;
+#[cfg(feature = "chain")]
+pub mod tx;
+pub mod graphql;
+pub mod webhooks;
+#[cfg(feature = "chain")]
+pub mod clearing;
+#[cfg(feature = "payments")]
+pub mod billing;
+pub mod carbon;
+#[cfg(feature = "chain")]
+pub mod pricing;
+#[cfg(feature = "chain")]
+pub mod disputes;
+pub mod market_feed;
+pub mod simulation;
+pub mod tenants;
\ No newline at end of file
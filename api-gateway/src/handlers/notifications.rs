@@ -0,0 +1,61 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use serde::Deserialize;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::Result;
+use crate::models::notification::{Notification, NotificationPreference, NotificationPreferenceRequest};
+use crate::services::notifications::NotificationStore;
+use crate::AppState;
+
+/// Alerts returned by a history request without a limit
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationQuery {
+    /// pending, sent or failed
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Own alert channels; without an email preference, alerts go to the account's address
+/// GET /api/v1/notifications/preferences
+pub async fn get_preferences(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<NotificationPreference>>> {
+    Ok(Json(NotificationStore::from_state(&state).preferences(user.0.sub).await?))
+}
+
+/// Enable, disable or retarget one alert channel
+/// PUT /api/v1/notifications/preferences
+pub async fn set_preference(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<NotificationPreferenceRequest>,
+) -> Result<Json<NotificationPreference>> {
+    Ok(Json(
+        NotificationStore::from_state(&state)
+            .set_preference(user.0.sub, &request)
+            .await?,
+    ))
+}
+
+/// Own alerts with their delivery status, newest first
+/// GET /api/v1/notifications?status=&limit=
+pub async fn list_notifications(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(query): Query<NotificationQuery>,
+) -> Result<Json<Vec<Notification>>> {
+    let notifications = NotificationStore::from_state(&state)
+        .history(
+            user.0.sub,
+            query.status.as_deref(),
+            query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT),
+        )
+        .await?;
+    Ok(Json(notifications))
+}
@@ -0,0 +1,159 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::{ApiError, Result};
+use crate::handlers::user_management::log_user_activity;
+use crate::models::pricing::{CurrentPrice, GridPrice, GridPriceIngestion, TouHoliday, TouPeriod, TouPeriodRequest};
+use crate::services::price_oracle::PriceOracle;
+use crate::services::pricing::{campus_offset, PricingService};
+use crate::AppState;
+
+/// Grid prices returned by a history request without a limit
+const DEFAULT_GRID_PRICE_LIMIT: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct HolidayQuery {
+    /// First day listed; today on campus by default
+    pub from: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GridPriceQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HolidayRequest {
+    pub name: String,
+}
+
+/// Time-of-use period in force now, bounding the prices orders match at
+/// GET /api/v1/pricing/current
+pub async fn get_current(State(state): State<AppState>) -> Result<Json<CurrentPrice>> {
+    Ok(Json(PricingService::from_state(&state).current().await?))
+}
+
+/// Configured time-of-use periods, highest priority first
+/// GET /api/v1/pricing/periods
+pub async fn list_periods(State(state): State<AppState>) -> Result<Json<Vec<TouPeriod>>> {
+    Ok(Json(PricingService::from_state(&state).periods().await?))
+}
+
+/// Public holidays observed as off-peak
+/// GET /api/v1/pricing/holidays?from=
+pub async fn list_holidays(
+    State(state): State<AppState>,
+    Query(query): Query<HolidayQuery>,
+) -> Result<Json<Vec<TouHoliday>>> {
+    let from = query
+        .from
+        .unwrap_or_else(|| state.clock.now().with_timezone(&campus_offset()).date_naive());
+    Ok(Json(PricingService::from_state(&state).holidays(from).await?))
+}
+
+/// Official grid tariff most recently ingested by the price oracle
+/// GET /api/v1/pricing/grid
+pub async fn get_grid_price(State(state): State<AppState>) -> Result<Json<GridPrice>> {
+    PriceOracle::from_state(&state)?
+        .latest()
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound("No grid price has been ingested".to_string()))
+}
+
+/// History of the official grid tariff, newest first
+/// GET /api/v1/pricing/grid/history?from=&to=&limit=
+pub async fn list_grid_prices(
+    State(state): State<AppState>,
+    Query(query): Query<GridPriceQuery>,
+) -> Result<Json<Vec<GridPrice>>> {
+    let prices = PriceOracle::from_state(&state)?
+        .history(query.from, query.to, query.limit.unwrap_or(DEFAULT_GRID_PRICE_LIMIT))
+        .await?;
+    Ok(Json(prices))
+}
+
+/// Fetch the grid tariff now rather than at the next scheduled fetch
+/// POST /api/v1/admin/pricing/grid/ingest
+pub async fn ingest_grid_price(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<GridPriceIngestion>> {
+    let ingestion = PriceOracle::from_state(&state)?.ingest().await?;
+    tracing::info!("Grid tariff fetched by {}", user.0.sub);
+    Ok(Json(ingestion))
+}
+
+/// Create or replace a time-of-use period
+/// PUT /api/v1/admin/pricing/periods/:id
+pub async fn update_period(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    user: AuthenticatedUser,
+    Json(request): Json<TouPeriodRequest>,
+) -> Result<Json<TouPeriod>> {
+    let period = PricingService::from_state(&state).set_period(&id, &request).await?;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "tou_period_updated".to_string(),
+        Some(serde_json::json!({
+            "period_id": id,
+            "price_floor": period.price_floor,
+            "price_ceiling": period.price_ceiling,
+        })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(Json(period))
+}
+
+/// Remove a time-of-use period
+/// DELETE /api/v1/admin/pricing/periods/:id
+pub async fn delete_period(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    user: AuthenticatedUser,
+) -> Result<StatusCode> {
+    PricingService::from_state(&state).delete_period(&id).await?;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "tou_period_deleted".to_string(),
+        Some(serde_json::json!({ "period_id": id })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Observe a public holiday as off-peak
+/// PUT /api/v1/admin/pricing/holidays/:day
+pub async fn update_holiday(
+    State(state): State<AppState>,
+    Path(day): Path<NaiveDate>,
+    Json(request): Json<HolidayRequest>,
+) -> Result<Json<TouHoliday>> {
+    Ok(Json(PricingService::from_state(&state).set_holiday(day, &request.name).await?))
+}
+
+/// Stop observing a public holiday
+/// DELETE /api/v1/admin/pricing/holidays/:day
+pub async fn delete_holiday(State(state): State<AppState>, Path(day): Path<NaiveDate>) -> Result<StatusCode> {
+    PricingService::from_state(&state).delete_holiday(day).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,76 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::{Html, Json},
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::{ApiError, Result};
+use crate::models::report::ReconciliationReport;
+use crate::services::reports::{ReportService, REPORT_COLUMNS};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ReportQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// List archived reconciliation reports, newest first
+/// GET /api/v1/admin/reports
+pub async fn list_reports(
+    State(state): State<AppState>,
+    Query(params): Query<ReportQuery>,
+) -> Result<Json<Vec<ReconciliationReport>>> {
+    let query = format!(
+        "SELECT {} FROM reconciliation_reports ORDER BY report_date DESC LIMIT $1 OFFSET $2",
+        REPORT_COLUMNS
+    );
+
+    let reports = sqlx::query_as::<_, ReconciliationReport>(&query)
+        .bind(params.limit.unwrap_or(30).clamp(1, 365))
+        .bind(params.offset.unwrap_or(0).max(0))
+        .fetch_all(&state.db)
+        .await?;
+
+    Ok(Json(reports))
+}
+
+/// Get an archived reconciliation report
+/// GET /api/v1/admin/reports/:id
+pub async fn get_report(
+    State(state): State<AppState>,
+    Path(report_id): Path<Uuid>,
+) -> Result<Json<ReconciliationReport>> {
+    let report = ReportService::from_state(&state)?.load(report_id).await?;
+    Ok(Json(report))
+}
+
+/// Get the rendered report as delivered by email
+/// GET /api/v1/admin/reports/:id/html
+pub async fn get_report_html(State(state): State<AppState>, Path(report_id): Path<Uuid>) -> Result<Html<String>> {
+    let html: Option<Option<String>> = sqlx::query_scalar("SELECT html FROM reconciliation_reports WHERE id = $1")
+        .bind(report_id)
+        .fetch_optional(&state.db)
+        .await?;
+
+    match html {
+        Some(Some(html)) => Ok(Html(html)),
+        Some(None) => Err(ApiError::NotFound(format!("Report {} has not been rendered", report_id))),
+        None => Err(ApiError::NotFound(format!("Report {} not found", report_id))),
+    }
+}
+
+/// Regenerate and redeliver today's report immediately
+/// POST /api/v1/admin/reports
+pub async fn run_report(State(state): State<AppState>, user: AuthenticatedUser) -> Result<Json<ReconciliationReport>> {
+    tracing::info!("Reconciliation report run requested by {}", user.0.sub);
+
+    let report = ReportService::from_state(&state)?
+        .run(true)
+        .await?
+        .ok_or_else(|| ApiError::Internal("Report was not generated".to_string()))?;
+
+    Ok(Json(report))
+}
@@ -0,0 +1,388 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::permissions::{is_valid_permission_pattern, PermissionService};
+use crate::error::{ApiError, Result};
+use crate::handlers::user_management::log_user_activity;
+use crate::models::role::{Permission, RoleDefinition};
+use crate::AppState;
+
+const ROLE_QUERY: &str = "
+    SELECT r.id, r.name, r.description, r.is_system,
+           COALESCE(array_agg(rp.permission ORDER BY rp.permission)
+                    FILTER (WHERE rp.permission IS NOT NULL), '{}')::text[] AS permissions,
+           r.created_at, r.updated_at
+    FROM roles r
+    LEFT JOIN role_permissions rp ON rp.role_id = r.id
+";
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateRoleRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateRoleRequest {
+    pub description: Option<String>,
+    /// Replaces the role's permissions when present
+    pub permissions: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PermissionsResponse {
+    /// Granted permission patterns, e.g. `trading:*`
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserRolesResponse {
+    pub user_id: Uuid,
+    pub system_role: String,
+    pub custom_roles: Vec<RoleDefinition>,
+    pub permissions: Vec<String>,
+}
+
+/// List the permission catalogue
+/// GET /api/v1/admin/permissions
+pub async fn list_permissions(State(state): State<AppState>) -> Result<Json<Vec<Permission>>> {
+    let permissions = sqlx::query_as::<_, Permission>("SELECT name, description FROM permissions ORDER BY name")
+        .fetch_all(&state.db)
+        .await?;
+
+    Ok(Json(permissions))
+}
+
+/// List system and custom roles with their permissions
+/// GET /api/v1/admin/roles
+pub async fn list_roles(State(state): State<AppState>) -> Result<Json<Vec<RoleDefinition>>> {
+    let query = format!("{} GROUP BY r.id ORDER BY r.is_system DESC, r.name", ROLE_QUERY);
+    let roles = sqlx::query_as::<_, RoleDefinition>(&query)
+        .fetch_all(&state.db)
+        .await?;
+
+    Ok(Json(roles))
+}
+
+/// Create a custom role
+/// POST /api/v1/admin/roles
+pub async fn create_role(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<CreateRoleRequest>,
+) -> Result<(StatusCode, Json<RoleDefinition>)> {
+    validate_role_name(&request.name)?;
+    validate_description(request.description.as_deref())?;
+    let permissions = validate_permissions(&state, &request.permissions).await?;
+
+    let mut tx = state.db.begin().await?;
+
+    let role_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO roles (name, description) VALUES ($1, $2)
+         ON CONFLICT (name) DO NOTHING RETURNING id",
+    )
+    .bind(&request.name)
+    .bind(&request.description)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::Conflict(format!("Role {} already exists", request.name)))?;
+
+    replace_permissions(&mut tx, role_id, &permissions).await?;
+    tx.commit().await?;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "role_created".to_string(),
+        Some(serde_json::json!({ "role_id": role_id, "role": request })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(load_role(&state, role_id).await?)))
+}
+
+/// Update a role's description or permissions
+/// PUT /api/v1/admin/roles/:id
+pub async fn update_role(
+    State(state): State<AppState>,
+    Path(role_id): Path<Uuid>,
+    user: AuthenticatedUser,
+    Json(request): Json<UpdateRoleRequest>,
+) -> Result<Json<RoleDefinition>> {
+    validate_description(request.description.as_deref())?;
+    let permissions = match &request.permissions {
+        Some(permissions) => Some(validate_permissions(&state, permissions).await?),
+        None => None,
+    };
+
+    let mut tx = state.db.begin().await?;
+
+    let updated = sqlx::query(
+        "UPDATE roles SET description = COALESCE($2, description), updated_at = NOW() WHERE id = $1",
+    )
+    .bind(role_id)
+    .bind(&request.description)
+    .execute(&mut *tx)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("Role {} not found", role_id)));
+    }
+
+    if let Some(permissions) = &permissions {
+        replace_permissions(&mut tx, role_id, permissions).await?;
+    }
+    tx.commit().await?;
+
+    if permissions.is_some() {
+        PermissionService::from_state(&state).invalidate_all().await;
+    }
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "role_updated".to_string(),
+        Some(serde_json::json!({ "role_id": role_id, "changes": request })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(Json(load_role(&state, role_id).await?))
+}
+
+/// Delete a custom role, revoking it from every user
+/// DELETE /api/v1/admin/roles/:id
+pub async fn delete_role(
+    State(state): State<AppState>,
+    Path(role_id): Path<Uuid>,
+    user: AuthenticatedUser,
+) -> Result<StatusCode> {
+    let role = load_role(&state, role_id).await?;
+    if role.is_system {
+        return Err(ApiError::Conflict(format!("System role {} cannot be deleted", role.name)));
+    }
+
+    sqlx::query("DELETE FROM roles WHERE id = $1")
+        .bind(role_id)
+        .execute(&state.db)
+        .await?;
+
+    PermissionService::from_state(&state).invalidate_all().await;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "role_deleted".to_string(),
+        Some(serde_json::json!({ "role_id": role_id, "name": role.name })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A user's system role, custom roles and effective permissions
+/// GET /api/v1/admin/users/:user_id/roles
+pub async fn get_user_roles(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<UserRolesResponse>> {
+    let system_role: String = sqlx::query_scalar("SELECT role::text FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("User not found".to_string()))?;
+
+    let query = format!(
+        "{} WHERE r.id IN (SELECT role_id FROM user_roles WHERE user_id = $1) GROUP BY r.id ORDER BY r.name",
+        ROLE_QUERY
+    );
+    let custom_roles = sqlx::query_as::<_, RoleDefinition>(&query)
+        .bind(user_id)
+        .fetch_all(&state.db)
+        .await?;
+
+    let permissions = PermissionService::from_state(&state).user_permissions(user_id).await?;
+
+    Ok(Json(UserRolesResponse {
+        user_id,
+        system_role,
+        custom_roles,
+        permissions,
+    }))
+}
+
+/// Grant a custom role to a user
+/// PUT /api/v1/admin/users/:user_id/roles/:role_id
+pub async fn assign_role(
+    State(state): State<AppState>,
+    Path((user_id, role_id)): Path<(Uuid, Uuid)>,
+    user: AuthenticatedUser,
+) -> Result<StatusCode> {
+    let role = load_role(&state, role_id).await?;
+    if role.is_system {
+        return Err(ApiError::BadRequest(format!(
+            "System role {} is assigned through the user's role",
+            role.name
+        )));
+    }
+
+    let user_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+        .bind(user_id)
+        .fetch_one(&state.db)
+        .await?;
+    if !user_exists {
+        return Err(ApiError::NotFound("User not found".to_string()));
+    }
+
+    sqlx::query(
+        "INSERT INTO user_roles (user_id, role_id, granted_by) VALUES ($1, $2, $3)
+         ON CONFLICT (user_id, role_id) DO NOTHING",
+    )
+    .bind(user_id)
+    .bind(role_id)
+    .bind(user.0.sub)
+    .execute(&state.db)
+    .await?;
+
+    PermissionService::from_state(&state).invalidate_user(user_id).await;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "role_assigned".to_string(),
+        Some(serde_json::json!({ "target_user_id": user_id, "role_id": role_id, "role": role.name })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Revoke a custom role from a user
+/// DELETE /api/v1/admin/users/:user_id/roles/:role_id
+pub async fn revoke_role(
+    State(state): State<AppState>,
+    Path((user_id, role_id)): Path<(Uuid, Uuid)>,
+    user: AuthenticatedUser,
+) -> Result<StatusCode> {
+    let result = sqlx::query("DELETE FROM user_roles WHERE user_id = $1 AND role_id = $2")
+        .bind(user_id)
+        .bind(role_id)
+        .execute(&state.db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("Role is not assigned to this user".to_string()));
+    }
+
+    PermissionService::from_state(&state).invalidate_user(user_id).await;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "role_revoked".to_string(),
+        Some(serde_json::json!({ "target_user_id": user_id, "role_id": role_id })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Permissions of the authenticated user
+/// GET /api/v1/auth/permissions
+pub async fn get_own_permissions(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<PermissionsResponse>> {
+    let permissions = PermissionService::from_state(&state).user_permissions(user.0.sub).await?;
+
+    Ok(Json(PermissionsResponse { permissions }))
+}
+
+async fn load_role(state: &AppState, role_id: Uuid) -> Result<RoleDefinition> {
+    let query = format!("{} WHERE r.id = $1 GROUP BY r.id", ROLE_QUERY);
+
+    sqlx::query_as::<_, RoleDefinition>(&query)
+        .bind(role_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Role {} not found", role_id)))
+}
+
+async fn replace_permissions(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    role_id: Uuid,
+    permissions: &[String],
+) -> Result<()> {
+    sqlx::query("DELETE FROM role_permissions WHERE role_id = $1")
+        .bind(role_id)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query("INSERT INTO role_permissions (role_id, permission) SELECT $1, UNNEST($2::text[])")
+        .bind(role_id)
+        .bind(permissions)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+fn validate_role_name(name: &str) -> Result<()> {
+    let valid = (2..=50).contains(&name.len())
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::BadRequest(
+            "Role name must be 2-50 lowercase letters, digits or underscores".to_string(),
+        ))
+    }
+}
+
+fn validate_description(description: Option<&str>) -> Result<()> {
+    if description.is_some_and(|d| d.len() > 500) {
+        return Err(ApiError::BadRequest("Description must be at most 500 characters".to_string()));
+    }
+    Ok(())
+}
+
+/// Deduplicate permissions, rejecting malformed patterns and unknown exact permissions
+async fn validate_permissions(state: &AppState, permissions: &[String]) -> Result<Vec<String>> {
+    let mut permissions = permissions.to_vec();
+    permissions.sort();
+    permissions.dedup();
+
+    if let Some(invalid) = permissions.iter().find(|p| !is_valid_permission_pattern(p)) {
+        return Err(ApiError::BadRequest(format!("Invalid permission {}", invalid)));
+    }
+
+    let exact: Vec<String> = permissions.iter().filter(|p| !p.contains('*')).cloned().collect();
+    let known: Vec<String> = sqlx::query_scalar("SELECT name FROM permissions WHERE name = ANY($1)")
+        .bind(&exact)
+        .fetch_all(&state.db)
+        .await?;
+
+    if let Some(unknown) = exact.iter().find(|p| !known.contains(p)) {
+        return Err(ApiError::BadRequest(format!("Unknown permission {}", unknown)));
+    }
+
+    Ok(permissions)
+}
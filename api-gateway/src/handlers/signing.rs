@@ -0,0 +1,184 @@
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::permissions::PermissionService;
+use crate::error::{ApiError, Result};
+use crate::models::signing::SigningSession;
+use crate::services::signing::{NewSigningSession, SigningCoordinator};
+use crate::services::transaction::{AccountMeta, Instruction, Pubkey};
+use crate::AppState;
+
+const MAX_INSTRUCTIONS: usize = 16;
+
+#[derive(Debug, Deserialize)]
+pub struct AccountMetaRequest {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InstructionRequest {
+    pub program_id: String,
+    pub accounts: Vec<AccountMetaRequest>,
+    /// Base64 encoded instruction data
+    pub data: String,
+}
+
+/// Request to build a transaction that several parties must sign
+#[derive(Debug, Deserialize)]
+pub struct CreateSigningSessionRequest {
+    pub instructions: Vec<InstructionRequest>,
+    pub fee_payer: String,
+    /// Durable nonce account, for sessions that must outlive a blockhash (~90 seconds)
+    pub nonce_account: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitSignatureRequest {
+    pub signer: String,
+    /// Base58 ed25519 signature over the session message
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SigningSessionResponse {
+    #[serde(flatten)]
+    pub session: SigningSession,
+    pub missing_signers: Vec<String>,
+}
+
+impl From<SigningSession> for SigningSessionResponse {
+    fn from(session: SigningSession) -> Self {
+        Self {
+            missing_signers: session.missing_signers(),
+            session,
+        }
+    }
+}
+
+/// Build a transaction and open a signing session
+/// POST /api/v1/blockchain/signing-sessions
+pub async fn create_signing_session(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<CreateSigningSessionRequest>,
+) -> Result<Json<SigningSessionResponse>> {
+    if payload.instructions.is_empty() || payload.instructions.len() > MAX_INSTRUCTIONS {
+        return Err(ApiError::BadRequest(format!(
+            "Between 1 and {} instructions are required",
+            MAX_INSTRUCTIONS
+        )));
+    }
+
+    if payload.description.as_ref().is_some_and(|d| d.len() > 255) {
+        return Err(ApiError::BadRequest("Description must be at most 255 characters".to_string()));
+    }
+
+    let instructions = payload
+        .instructions
+        .iter()
+        .map(|instruction| {
+            Ok(Instruction {
+                program_id: parse_pubkey(&instruction.program_id)?,
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| {
+                        Ok(AccountMeta {
+                            pubkey: parse_pubkey(&meta.pubkey)?,
+                            is_signer: meta.is_signer,
+                            is_writable: meta.is_writable,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                data: BASE64
+                    .decode(&instruction.data)
+                    .map_err(|_| ApiError::BadRequest("Instruction data must be base64 encoded".to_string()))?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let request = NewSigningSession {
+        instructions,
+        fee_payer: parse_pubkey(&payload.fee_payer)?,
+        nonce_account: payload.nonce_account.as_deref().map(parse_pubkey).transpose()?,
+        description: payload.description,
+    };
+
+    let session = coordinator(&state).create_session(user.0.sub, request).await?;
+
+    Ok(Json(session.into()))
+}
+
+/// Get a signing session, including the message to sign and missing signers
+/// GET /api/v1/blockchain/signing-sessions/:id
+pub async fn get_signing_session(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<SigningSessionResponse>> {
+    let session = coordinator(&state).get_session(session_id).await?;
+    ensure_participant(&state, &user, &session).await?;
+
+    Ok(Json(session.into()))
+}
+
+/// Add a party's signature; the transaction is submitted once all parties have signed
+/// POST /api/v1/blockchain/signing-sessions/:id/signatures
+pub async fn submit_signature(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(session_id): Path<Uuid>,
+    Json(payload): Json<SubmitSignatureRequest>,
+) -> Result<Json<SigningSessionResponse>> {
+    tracing::info!("User {} submitting signature for session {}", user.0.sub, session_id);
+
+    let session = coordinator(&state)
+        .add_signature(session_id, &payload.signer, &payload.signature)
+        .await?;
+
+    Ok(Json(session.into()))
+}
+
+fn coordinator(state: &AppState) -> SigningCoordinator {
+    SigningCoordinator::new(
+        state.db.clone(),
+        state.blockchain_service.clone(),
+        state.config.signing_session_ttl,
+        state.clock.clone(),
+    )
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).map_err(ApiError::BadRequest)
+}
+
+/// Sessions are visible to their creator, users whose wallet must sign and holders of signing:read
+async fn ensure_participant(state: &AppState, user: &AuthenticatedUser, session: &SigningSession) -> Result<()> {
+    if session.created_by == user.0.sub
+        || PermissionService::from_state(state).has_permission(user.0.sub, "signing:read").await?
+    {
+        return Ok(());
+    }
+
+    let wallet_address: Option<String> = sqlx::query_scalar("SELECT wallet_address FROM users WHERE id = $1")
+        .bind(user.0.sub)
+        .fetch_optional(&state.db)
+        .await?
+        .flatten();
+
+    match wallet_address {
+        Some(wallet) if session.required_signers.contains(&wallet) => Ok(()),
+        _ => Err(ApiError::Authorization("Not a participant of this signing session".to_string())),
+    }
+}
@@ -0,0 +1,35 @@
+use axum::{extract::State, http::StatusCode, response::Json};
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::Result;
+use crate::services::simulation::{SimulationRequest, SimulationSettings, SimulationStats, Simulator};
+use crate::AppState;
+
+/// Start driving virtual meters through reading ingestion and, with `on_chain`, the oracle
+/// POST /api/v1/admin/simulation
+pub async fn start_simulation(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<SimulationRequest>,
+) -> Result<(StatusCode, Json<SimulationStats>)> {
+    let settings = SimulationSettings::from_request(&request)?;
+    let stats = Simulator::from_state(&state)?.start(settings)?;
+
+    tracing::info!("Simulation of {} meters started by {}", settings.meters, user.0.sub);
+    Ok((StatusCode::ACCEPTED, Json(stats)))
+}
+
+/// Progress and throughput of the current or latest simulation
+/// GET /api/v1/admin/simulation
+pub async fn get_simulation(State(state): State<AppState>) -> Result<Json<SimulationStats>> {
+    Ok(Json(Simulator::from_state(&state)?.status()))
+}
+
+/// Stop the running simulation; the readings it stored are kept
+/// DELETE /api/v1/admin/simulation
+pub async fn stop_simulation(State(state): State<AppState>, user: AuthenticatedUser) -> Result<Json<SimulationStats>> {
+    let stats = Simulator::from_state(&state)?.stop()?;
+
+    tracing::info!("Simulation stopped by {} after {} rounds", user.0.sub, stats.rounds);
+    Ok(Json(stats))
+}
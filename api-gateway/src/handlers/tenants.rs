@@ -0,0 +1,137 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::tenant::TenantScope;
+use crate::error::{ApiError, Result};
+use crate::handlers::user_management::log_user_activity;
+use crate::models::tenant::{Tenant, TenantUsage};
+use crate::services::tenants::{TenantRequest, TenantStore};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateTenantRequest {
+    /// `null` returns the tenant to `TENANT_RATE_LIMIT_PER_MINUTE`
+    pub requests_per_minute: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AssignTenantRequest {
+    /// `null` takes the user or meter out of every tenant
+    pub tenant: Option<String>,
+}
+
+/// Buildings and faculties the caller can see
+/// GET /api/v1/admin/tenants
+pub async fn list_tenants(State(state): State<AppState>, scope: TenantScope) -> Result<Json<Vec<Tenant>>> {
+    let tenants = TenantStore::from_state(&state).list().await?;
+    Ok(Json(
+        tenants
+            .into_iter()
+            .filter(|tenant| scope.allows(Some(&tenant.id)))
+            .collect(),
+    ))
+}
+
+/// Register a building or faculty
+/// POST /api/v1/admin/tenants
+pub async fn create_tenant(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<TenantRequest>,
+) -> Result<(StatusCode, Json<Tenant>)> {
+    let tenant = TenantStore::from_state(&state).create(&request).await?;
+    tracing::info!("Tenant {} ({}) created by {}", tenant.id, tenant.kind, user.0.sub);
+    Ok((StatusCode::CREATED, Json(tenant)))
+}
+
+/// Change a tenant's shared per-minute request limit
+/// PUT /api/v1/admin/tenants/:id
+pub async fn update_tenant(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    user: AuthenticatedUser,
+    Json(request): Json<UpdateTenantRequest>,
+) -> Result<Json<Tenant>> {
+    let tenant = TenantStore::from_state(&state)
+        .set_rate_limit(&id, request.requests_per_minute)
+        .await?;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "tenant_updated".to_string(),
+        Some(serde_json::json!({ "tenant_id": id, "changes": request })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(Json(tenant))
+}
+
+/// Users, meters, recent readings, open orders, certificates and current request rate of a tenant
+/// GET /api/v1/admin/tenants/:id/usage
+pub async fn get_tenant_usage(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Path(id): Path<String>,
+) -> Result<Json<TenantUsage>> {
+    if !scope.allows(Some(&id)) {
+        return Err(ApiError::NotFound(format!("Tenant {} not found", id)));
+    }
+    let store = TenantStore::from_state(&state);
+    let tenant = store.get(&id).await?;
+    Ok(Json(store.usage(&tenant).await?))
+}
+
+/// Move a user into a tenant; their token carries it from their next sign-in or refresh
+/// PUT /api/v1/admin/users/:user_id/tenant
+pub async fn assign_user_tenant(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+    user: AuthenticatedUser,
+    Json(request): Json<AssignTenantRequest>,
+) -> Result<StatusCode> {
+    TenantStore::from_state(&state)
+        .assign_user(user_id, request.tenant.as_deref())
+        .await?;
+
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "user_tenant_assigned".to_string(),
+        Some(serde_json::json!({ "user_id": user_id, "tenant_id": request.tenant })),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Move a meter, and with it every reading it recorded, into a tenant
+/// PUT /api/v1/admin/meters/:meter_id/tenant
+pub async fn assign_meter_tenant(
+    State(state): State<AppState>,
+    Path(meter_id): Path<String>,
+    user: AuthenticatedUser,
+    Json(request): Json<AssignTenantRequest>,
+) -> Result<StatusCode> {
+    TenantStore::from_state(&state)
+        .assign_meter(&meter_id, request.tenant.as_deref())
+        .await?;
+
+    tracing::info!(
+        "Meter {} moved to tenant {} by {}",
+        meter_id,
+        request.tenant.as_deref().unwrap_or("none"),
+        user.0.sub
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,5 +1,6 @@
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
+    http::StatusCode,
     response::Json,
 };
 use chrono::{DateTime, Utc};
@@ -8,11 +9,20 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::auth::middleware::AuthenticatedUser;
+use crate::database::history::{as_of_condition, source_table};
 use crate::database::schema::types::{OrderSide, OrderStatus, OrderType};
-use crate::error::{ApiError, Result};
-use crate::models::trading::{CreateOrderRequest, MarketData, OrderBook, TradingOrder, TradingOrderDb};
+use crate::error::{ApiError, ProblemDetails, Result};
+use crate::models::trading::{CreateOrderRequest, MarketData, PlaceOrderRequest, TradingOrder, TradingOrderDb};
+use crate::services::order_book::OrderBookSnapshot;
+use crate::services::prosumers::lock_trading_wallet;
+use crate::services::tx_queue::{enqueue_with, TxOperation};
+use crate::utils::decimal::big_decimal;
+use crate::utils::validation::ValidatedJson;
 use crate::AppState;
 
+const ORDER_COLUMNS: &str = "id, user_id, order_type, side, energy_amount, price_per_kwh, filled_amount, status, \
+    expires_at, created_at, filled_at";
+
 /// Query parameters for trading orders
 #[derive(Debug, Deserialize, Validate)]
 pub struct OrderQuery {
@@ -20,12 +30,15 @@ pub struct OrderQuery {
     pub side: Option<OrderSide>,
     pub limit: Option<i32>,
     pub offset: Option<i32>,
+    /// Reconstruct orders as they were at this time
+    pub as_of: Option<DateTime<Utc>>,
 }
 
 /// Response for order creation
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct CreateOrderResponse {
     pub id: Uuid,
+    #[schema(value_type = String, example = "pending")]
     pub status: OrderStatus,
     pub created_at: DateTime<Utc>,
     pub message: String,
@@ -33,25 +46,28 @@ pub struct CreateOrderResponse {
 
 /// Create a new trading order
 /// POST /api/v1/trading/orders
+#[utoipa::path(
+    post,
+    path = "/trading/orders",
+    tag = "trading",
+    request_body = CreateOrderRequest,
+    responses(
+        (status = 200, description = "Order created", body = CreateOrderResponse),
+        (status = 400, description = "Invalid amounts or expiry",
+            body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn create_order(
     State(state): State<AppState>,
     user: AuthenticatedUser,
-    Json(payload): Json<CreateOrderRequest>,
+    ValidatedJson(payload): ValidatedJson<CreateOrderRequest>,
 ) -> Result<Json<CreateOrderResponse>> {
     tracing::info!("Creating trading order for user: {}", user.0.sub);
 
-    // Validate order parameters
-    if payload.energy_amount <= rust_decimal::Decimal::ZERO {
-        return Err(ApiError::BadRequest("Energy amount must be positive".to_string()));
-    }
-
-    if payload.price_per_kwh <= rust_decimal::Decimal::ZERO {
-        return Err(ApiError::BadRequest("Price per kWh must be positive".to_string()));
-    }
-
     // Create trading order
     let order_id = Uuid::new_v4();
-    let now = Utc::now();
+    let now = state.clock.now();
     let expires_at = payload.expiry_time.unwrap_or_else(|| now + chrono::Duration::days(1));
 
     // Determine order side based on user role/permissions (simplified logic)
@@ -62,18 +78,9 @@ pub async fn create_order(
     };
 
     // Convert Decimal to BigDecimal for database storage
-    let energy_amount_bd = {
-        use std::str::FromStr;
-        sqlx::types::BigDecimal::from_str(&payload.energy_amount.to_string()).unwrap_or_default()
-    };
-    let price_per_kwh_bd = {
-        use std::str::FromStr;
-        sqlx::types::BigDecimal::from_str(&payload.price_per_kwh.to_string()).unwrap_or_default()
-    };
-    let filled_amount_bd = {
-        use std::str::FromStr;
-        sqlx::types::BigDecimal::from_str("0").unwrap_or_default()
-    };
+    let energy_amount_bd = big_decimal(payload.energy_amount);
+    let price_per_kwh_bd = big_decimal(payload.price_per_kwh);
+    let filled_amount_bd = big_decimal(rust_decimal::Decimal::ZERO);
 
     sqlx::query!(
         r#"
@@ -100,7 +107,7 @@ pub async fn create_order(
         ApiError::Database(e)
     })?;
 
-    // TODO: In Phase 4, trigger order matching engine
+    // Without a signed commitment the order is not matched off-chain; see `place_order`
 
     Ok(Json(CreateOrderResponse {
         id: order_id,
@@ -110,6 +117,143 @@ pub async fn create_order(
     }))
 }
 
+/// Place an order for the matching engine, within the per-user limits
+/// POST /api/v1/orders
+///
+/// The order is settled by the trading program from the user's registered wallet, so placing
+/// one requires a wallet. Its signed commitment is queued for the trading program, and the
+/// order is matched once that lands.
+#[utoipa::path(
+    post,
+    path = "/orders",
+    tag = "trading",
+    request_body = PlaceOrderRequest,
+    responses(
+        (status = 201, description = "Order placed for matching", body = TradingOrder),
+        (status = 400, description = "Invalid amounts or expiry, or no registered wallet",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Too many open orders, or the order ID is taken",
+            body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn place_order(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    ValidatedJson(payload): ValidatedJson<PlaceOrderRequest>,
+) -> Result<(StatusCode, Json<TradingOrder>)> {
+    if payload.energy_amount > state.config.max_order_energy_kwh {
+        return Err(ApiError::BadRequest(format!(
+            "Energy amount must be at most {} kWh",
+            state.config.max_order_energy_kwh
+        )));
+    }
+
+    let now = state.clock.now();
+    let expires_at = payload.expiry_time.unwrap_or_else(|| now + chrono::Duration::days(1));
+
+    let mut tx = state.db.begin().await?;
+
+    // Locking the user serializes their order placements, keeping the open order count exact
+    let wallet = lock_trading_wallet(&mut tx, user.0.sub).await?;
+
+    let open_orders: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM trading_orders WHERE user_id = $1 AND status IN ($2, $3)")
+        .bind(user.0.sub)
+        .bind(OrderStatus::Pending)
+        .bind(OrderStatus::Active)
+        .fetch_one(&mut *tx)
+        .await?;
+    if open_orders >= state.config.max_open_orders_per_user {
+        return Err(ApiError::Conflict(format!(
+            "At most {} orders may be open at once",
+            state.config.max_open_orders_per_user
+        )));
+    }
+
+    let operation = TxOperation::CommitOrder {
+        order_id: payload.order_id,
+        owner: wallet,
+        side: payload.side.clone(),
+        energy_amount: payload.energy_amount,
+        price_per_kwh: payload.price_per_kwh,
+        signature: payload.commitment_signature.clone(),
+    };
+    let job = enqueue_with(&mut *tx, &operation, Some(user.0.sub)).await?;
+
+    let order = sqlx::query_as::<_, TradingOrderDb>(&format!(
+        "INSERT INTO trading_orders (
+            id, user_id, order_type, side, energy_amount, price_per_kwh, filled_amount, status, expires_at, created_at,
+            commitment_job_id
+         ) VALUES ($1, $2, $3, $4, $5, $6, 0, $7, $8, $9, $10)
+         RETURNING {}",
+        ORDER_COLUMNS
+    ))
+    .bind(payload.order_id)
+    .bind(user.0.sub)
+    .bind(payload.order_type)
+    .bind(payload.side)
+    .bind(big_decimal(payload.energy_amount))
+    .bind(big_decimal(payload.price_per_kwh))
+    .bind(OrderStatus::Pending)
+    .bind(expires_at)
+    .bind(now)
+    .bind(job.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+            ApiError::Conflict(format!("Order {} already exists", payload.order_id))
+        }
+        e => e.into(),
+    })?;
+    tx.commit().await?;
+
+    tracing::info!("User {} placed order {}", user.0.sub, order.id);
+    Ok((StatusCode::CREATED, Json(order.into())))
+}
+
+/// Cancel one of the user's open orders
+/// DELETE /api/v1/orders/:id
+pub async fn cancel_order(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<TradingOrder>> {
+    let cancelled = sqlx::query_as::<_, TradingOrderDb>(&format!(
+        "UPDATE trading_orders SET status = $3, updated_at = $4
+         WHERE id = $1 AND user_id = $2 AND status IN ($5, $6)
+         RETURNING {}",
+        ORDER_COLUMNS
+    ))
+    .bind(order_id)
+    .bind(user.0.sub)
+    .bind(OrderStatus::Cancelled)
+    .bind(state.clock.now())
+    .bind(OrderStatus::Pending)
+    .bind(OrderStatus::Active)
+    .fetch_optional(&state.db)
+    .await?;
+
+    match cancelled {
+        Some(order) => {
+            tracing::info!("User {} cancelled order {}", user.0.sub, order_id);
+            Ok(Json(order.into()))
+        }
+        None => {
+            let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM trading_orders WHERE id = $1 AND user_id = $2)")
+                .bind(order_id)
+                .bind(user.0.sub)
+                .fetch_one(&state.db)
+                .await?;
+            if exists {
+                Err(ApiError::Conflict(format!("Order {} is no longer open", order_id)))
+            } else {
+                Err(ApiError::NotFound(format!("Order {} not found", order_id)))
+            }
+        }
+    }
+}
+
 /// Get user's trading orders
 /// GET /api/v1/trading/orders
 pub async fn get_user_orders(
@@ -120,9 +264,17 @@ pub async fn get_user_orders(
     tracing::info!("Fetching orders for user: {}", user.0.sub);
 
     // Build dynamic query based on parameters  
-    let mut query = "SELECT id, user_id, order_type, side, energy_amount, price_per_kwh, filled_amount, status, expires_at, created_at, filled_at FROM trading_orders WHERE user_id = $1".to_string();
+    let mut query = format!(
+        "SELECT id, user_id, order_type, side, energy_amount, price_per_kwh, filled_amount, status, expires_at, created_at, filled_at FROM {} WHERE user_id = $1",
+        source_table("trading_orders", params.as_of)
+    );
     let mut bind_count = 2;
 
+    if params.as_of.is_some() {
+        query.push_str(&format!(" AND {}", as_of_condition(bind_count)));
+        bind_count += 1;
+    }
+
     if let Some(_status) = &params.status {
         query.push_str(&format!(" AND status = ${}", bind_count));
         bind_count += 1;
@@ -148,6 +300,10 @@ pub async fn get_user_orders(
     let mut sqlx_query = sqlx::query_as::<_, TradingOrderDb>(&query);
     sqlx_query = sqlx_query.bind(user.0.sub);
 
+    if let Some(as_of) = params.as_of {
+        sqlx_query = sqlx_query.bind(as_of);
+    }
+
     if let Some(status) = &params.status {
         sqlx_query = sqlx_query.bind(status);
     }
@@ -183,27 +339,28 @@ pub async fn get_market_data(
 ) -> Result<Json<MarketData>> {
     tracing::info!("Fetching current market data");
 
-    // Get current epoch information (for now, use simple hour-based epochs)
-    let now = Utc::now();
-    let current_epoch = (now.timestamp() / 3600) as u64; // 1-hour epochs
-    let epoch_start = DateTime::from_timestamp(current_epoch as i64 * 3600, 0).unwrap();
-    let epoch_end = epoch_start + chrono::Duration::hours(1);
-
-    // For now, return basic market data structure
-    // In Phase 4, this will include real order book and trade data
-    let market_data = MarketData {
-        current_epoch,
-        epoch_start_time: epoch_start,
-        epoch_end_time: epoch_end,
-        status: "active".to_string(),
-        order_book: OrderBook {
-            sell_orders: vec![],
-            buy_orders: vec![],
-        },
-        recent_trades: vec![],
-    };
+    Ok(Json(state.order_book.market_data(state.clock.now())))
+}
 
-    Ok(Json(market_data))
+/// Query parameters for the order book
+#[derive(Debug, Deserialize)]
+pub struct OrderBookQuery {
+    /// Best orders to return per side; all of them when unset
+    pub depth: Option<usize>,
+}
+
+/// Get the current order book from the in-memory mirror
+/// GET /api/v1/orderbook
+pub async fn get_order_book(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    Query(params): Query<OrderBookQuery>,
+) -> Json<OrderBookSnapshot> {
+    let snapshot = state.order_book.snapshot();
+    match params.depth {
+        Some(depth) => Json(snapshot.top(depth)),
+        None => Json(snapshot.as_ref().clone()),
+    }
 }
 
 /// Get trading statistics for the user
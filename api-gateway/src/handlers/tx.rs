@@ -0,0 +1,31 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::permissions::PermissionService;
+use crate::error::{ApiError, Result};
+use crate::models::tx_job::TxJob;
+use crate::services::tx_queue::TxQueue;
+use crate::AppState;
+
+/// Poll a queued transaction job for its status and, once submitted, its signature
+/// GET /api/v1/tx/:job_id
+///
+/// Jobs are visible to the user who queued them and holders of tx:read.
+pub async fn get_job(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<TxJob>> {
+    let job = TxQueue::from_state(&state).load(job_id).await?;
+    if job.created_by != Some(user.0.sub)
+        && !PermissionService::from_state(&state).has_permission(user.0.sub, "tx:read").await?
+    {
+        return Err(ApiError::Authorization("Not the creator of this transaction job".to_string()));
+    }
+
+    Ok(Json(job))
+}
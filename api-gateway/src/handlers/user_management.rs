@@ -7,8 +7,9 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::auth::{SecureAuthResponse, Claims, UserInfo, SecureUserInfo};
+use crate::auth::{SecureAuthResponse, UserInfo, SecureUserInfo};
 use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::permissions::PermissionService;
 use crate::auth::password::PasswordService;
 use crate::error::{ApiError, Result};
 use crate::AppState;
@@ -206,7 +207,7 @@ pub async fn enhanced_register(
     ).await;
 
     // Create JWT claims
-    let claims = Claims::new(user_id, request.username.clone(), request.role.clone(), request.department.clone());
+    let claims = state.jwt_service.issue_claims(user_id, request.username.clone(), request.role.clone(), request.department.clone());
     
     // Generate token
     let access_token = state.jwt_service.encode_token(&claims)?;
@@ -318,17 +319,15 @@ pub async fn remove_wallet_address(
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Admin: Update any user (requires admin role)
+/// Admin: Update any user (requires users:update)
 pub async fn admin_update_user(
     State(state): State<AppState>,
     Path(user_id): Path<Uuid>,
     user: AuthenticatedUser,
     Json(request): Json<AdminUserUpdateRequest>,
 ) -> Result<Json<UserInfo>> {
-    // Check admin permissions
-    if !user.0.has_any_role(&["admin"]) {
-        return Err(ApiError::Authorization("Admin access required".to_string()));
-    }
+    let permissions = PermissionService::from_state(&state);
+    permissions.require(user.0.sub, "users:update").await?;
 
     // Validate request
     request.validate()
@@ -426,6 +425,10 @@ pub async fn admin_update_user(
         return Err(ApiError::NotFound("User not found".to_string()));
     }
 
+    if request.role.is_some() || request.is_active.is_some() {
+        permissions.invalidate_user(user_id).await;
+    }
+
     // Log admin action
     let _ = log_user_activity(
         &state.db,
@@ -449,10 +452,8 @@ pub async fn admin_deactivate_user(
     Path(user_id): Path<Uuid>,
     user: AuthenticatedUser,
 ) -> Result<StatusCode> {
-    // Check admin permissions
-    if !user.0.has_any_role(&["admin"]) {
-        return Err(ApiError::Authorization("Admin access required".to_string()));
-    }
+    let permissions = PermissionService::from_state(&state);
+    permissions.require(user.0.sub, "users:manage").await?;
 
     // Cannot deactivate self
     if user_id == user.0.sub {
@@ -472,6 +473,8 @@ pub async fn admin_deactivate_user(
         return Err(ApiError::NotFound("User not found".to_string()));
     }
 
+    permissions.invalidate_user(user_id).await;
+
     // Log admin action
     let _ = log_user_activity(
         &state.db,
@@ -493,10 +496,8 @@ pub async fn admin_reactivate_user(
     Path(user_id): Path<Uuid>,
     user: AuthenticatedUser,
 ) -> Result<StatusCode> {
-    // Check admin permissions
-    if !user.0.has_any_role(&["admin"]) {
-        return Err(ApiError::Authorization("Admin access required".to_string()));
-    }
+    let permissions = PermissionService::from_state(&state);
+    permissions.require(user.0.sub, "users:manage").await?;
 
     // Reactivate user
     let result = sqlx::query(
@@ -511,6 +512,8 @@ pub async fn admin_reactivate_user(
         return Err(ApiError::NotFound("User not found".to_string()));
     }
 
+    permissions.invalidate_user(user_id).await;
+
     // Log admin action
     let _ = log_user_activity(
         &state.db,
@@ -526,16 +529,15 @@ pub async fn admin_reactivate_user(
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Get user activity log (admin only)
+/// Get user activity log (own activity, or any user's with users:read)
 pub async fn get_user_activity(
     State(state): State<AppState>,
     Path(user_id): Path<Uuid>,
     Query(params): Query<ActivityQuery>,
     user: AuthenticatedUser,
 ) -> Result<Json<ActivityListResponse>> {
-    // Check admin permissions or self-access
-    if !user.0.has_any_role(&["admin"]) && user_id != user.0.sub {
-        return Err(ApiError::Authorization("Admin access required or can only view own activity".to_string()));
+    if user_id != user.0.sub {
+        PermissionService::from_state(&state).require(user.0.sub, "users:read").await?;
     }
 
     let page = params.page.unwrap_or(1).max(1);
@@ -637,7 +639,7 @@ pub async fn get_department_info(
 
 // Helper functions
 
-async fn log_user_activity(
+pub(crate) async fn log_user_activity(
     db: &sqlx::PgPool,
     user_id: Uuid,
     action: String,
@@ -1,2 +1,114 @@
-// User management handlers - to be implemented in Phase 1 Week 4
-// Will include user profile, blockchain registration, etc.
\ No newline at end of file
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::permissions::PermissionService;
+use crate::error::Result;
+use crate::handlers::user_management::log_user_activity;
+use crate::models::user::ProsumerProfile;
+use crate::services::prosumers::{CreateProsumerRequest, ProsumerStore, UpdateProsumerRequest};
+use crate::AppState;
+
+/// Query parameters for prosumer listings
+#[derive(Debug, Deserialize)]
+pub struct ProsumerQuery {
+    /// Matched against name, username, email and student or staff ID
+    pub search: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// GET /api/v1/prosumers
+pub async fn list_prosumers(
+    State(state): State<AppState>,
+    Query(params): Query<ProsumerQuery>,
+) -> Result<Json<Vec<ProsumerProfile>>> {
+    let profiles = ProsumerStore::from_state(&state)
+        .list(
+            params.search.as_deref().filter(|search| !search.is_empty()),
+            params.limit.unwrap_or(100),
+            params.offset.unwrap_or(0),
+        )
+        .await?;
+    Ok(Json(profiles))
+}
+
+/// Own profile with assigned meters
+/// GET /api/v1/prosumers/me
+pub async fn get_own_profile(State(state): State<AppState>, user: AuthenticatedUser) -> Result<Json<ProsumerProfile>> {
+    Ok(Json(ProsumerStore::from_state(&state).get(user.0.sub).await?))
+}
+
+/// GET /api/v1/prosumers/:id
+pub async fn get_prosumer(State(state): State<AppState>, Path(id): Path<Uuid>) -> Result<Json<ProsumerProfile>> {
+    Ok(Json(ProsumerStore::from_state(&state).get(id).await?))
+}
+
+/// Create a prosumer account on a student's or staff member's behalf
+/// POST /api/v1/prosumers
+pub async fn create_prosumer(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<CreateProsumerRequest>,
+) -> Result<(StatusCode, Json<ProsumerProfile>)> {
+    let profile = ProsumerStore::from_state(&state).create(request).await?;
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "prosumer_created".to_string(),
+        Some(serde_json::json!({ "target_user_id": profile.id })),
+        None,
+        None,
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(profile)))
+}
+
+/// PUT /api/v1/prosumers/:id
+pub async fn update_prosumer(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateProsumerRequest>,
+) -> Result<Json<ProsumerProfile>> {
+    let profile = ProsumerStore::from_state(&state).update(id, &request).await?;
+    if request.role.is_some() {
+        PermissionService::from_state(&state).invalidate_user(id).await;
+    }
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "prosumer_updated".to_string(),
+        Some(serde_json::json!({ "target_user_id": id, "changes": request })),
+        None,
+        None,
+    )
+    .await;
+    Ok(Json(profile))
+}
+
+/// Deactivate a prosumer without open orders or assigned meters
+/// DELETE /api/v1/prosumers/:id
+pub async fn delete_prosumer(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ProsumerProfile>> {
+    let profile = ProsumerStore::from_state(&state).deactivate(id).await?;
+    PermissionService::from_state(&state).invalidate_user(id).await;
+    let _ = log_user_activity(
+        &state.db,
+        user.0.sub,
+        "prosumer_deactivated".to_string(),
+        Some(serde_json::json!({ "target_user_id": id })),
+        None,
+        None,
+    )
+    .await;
+    Ok(Json(profile))
+}
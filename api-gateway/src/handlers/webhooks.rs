@@ -0,0 +1,108 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::{ApiError, Result};
+use crate::models::webhook::{WebhookDelivery, WebhookSubscription};
+use crate::services::webhooks::{WebhookStore, WebhookSubscriptionRequest};
+use crate::AppState;
+
+/// Deliveries returned by a history request without a limit
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+
+#[derive(Debug, Serialize)]
+pub struct CreatedWebhookResponse {
+    /// Key the `X-GridTokenX-Signature` HMAC is computed with; it cannot be retrieved again
+    pub secret: String,
+    pub subscription: WebhookSubscription,
+}
+
+/// Query parameters for a delivery history request
+#[derive(Debug, Deserialize)]
+pub struct DeliveryQuery {
+    /// pending, delivered or dead_lettered
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// List registered webhooks
+/// GET /api/v1/admin/webhooks
+pub async fn list_webhooks(State(state): State<AppState>) -> Result<Json<Vec<WebhookSubscription>>> {
+    Ok(Json(WebhookStore::from_state(&state).list().await?))
+}
+
+/// Register an endpoint for erc_issued, market_cleared and/or reading_anomaly events
+/// POST /api/v1/admin/webhooks
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<WebhookSubscriptionRequest>,
+) -> Result<(StatusCode, Json<CreatedWebhookResponse>)> {
+    let subscription = WebhookStore::from_state(&state).create(&request, user.0.sub).await?;
+    tracing::info!(
+        "Webhook {} to {} for {} registered by {}",
+        subscription.id,
+        subscription.url,
+        subscription.event_types.join(", "),
+        user.0.sub
+    );
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedWebhookResponse {
+            secret: subscription.secret.clone(),
+            subscription,
+        }),
+    ))
+}
+
+/// Remove a webhook and its delivery history
+/// DELETE /api/v1/admin/webhooks/:id
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    WebhookStore::from_state(&state).remove(id).await?;
+    tracing::info!("Webhook {} removed by {}", id, user.0.sub);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Most recent deliveries to a webhook, newest first
+/// GET /api/v1/admin/webhooks/:id/deliveries
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<DeliveryQuery>,
+) -> Result<Json<Vec<WebhookDelivery>>> {
+    let status = params.status.as_deref();
+    if let Some(status) = status {
+        if ![WebhookDelivery::PENDING, WebhookDelivery::DELIVERED, WebhookDelivery::DEAD_LETTERED].contains(&status) {
+            return Err(ApiError::BadRequest(format!(
+                "status must be pending, delivered or dead_lettered, not {}",
+                status
+            )));
+        }
+    }
+
+    let deliveries = WebhookStore::from_state(&state)
+        .deliveries(id, status, params.limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+        .await?;
+    Ok(Json(deliveries))
+}
+
+/// Send a dead-lettered delivery again
+/// POST /api/v1/admin/webhooks/deliveries/:id/redeliver
+pub async fn redeliver(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WebhookDelivery>> {
+    let delivery = WebhookStore::from_state(&state).redeliver(id).await?;
+    tracing::info!("Webhook delivery {} requeued by {}", id, user.0.sub);
+    Ok(Json(delivery))
+}
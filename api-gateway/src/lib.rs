@@ -7,6 +7,11 @@ pub mod services;
 pub mod utils;
 pub mod error;
 pub mod auth;
+pub mod graphql;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "chain")]
+pub mod openapi;
 
 pub use config::Config;
 pub use error::ApiError;
@@ -16,8 +21,20 @@ pub use error::ApiError;
 pub struct AppState {
     pub db: sqlx::PgPool,
     pub timescale_db: sqlx::PgPool,
-    pub redis: redis::Client,
+    pub redis: database::redis_pool::RedisPool,
     pub config: Config,
     pub jwt_service: auth::jwt::JwtService,
     pub api_key_service: auth::jwt::ApiKeyService,
+    pub blockchain_service: services::blockchain::BlockchainService,
+    pub order_book: std::sync::Arc<services::order_book::OrderBookMirror>,
+    pub dashboard: std::sync::Arc<services::dashboard::DashboardMirror>,
+    pub program_events: std::sync::Arc<services::program_logs::ProgramLogSubscriber>,
+    pub market_feed: std::sync::Arc<services::market_feed::MarketFeed>,
+    pub erc_events: std::sync::Arc<services::erc_events::ErcEventFeed>,
+    pub simulation: services::simulation::SimulationRuns,
+    pub signer: services::gateway_signer::GatewaySigner,
+    pub fee_payers: services::fee_payers::FeePayerPool,
+    pub clock: utils::clock::SharedClock,
+    pub metrics: metrics_exporter_prometheus::PrometheusHandle,
+    pub graphql: graphql::GatewaySchema,
 }
\ No newline at end of file
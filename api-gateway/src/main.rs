@@ -1,11 +1,16 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
-use axum::{routing::{get, post}, Router, middleware::from_fn_with_state};
+use axum::{routing::{delete, get, post, put}, Router, middleware::{from_fn, from_fn_with_state}};
 use tower::ServiceBuilder;
 use tower_http::{cors::CorsLayer, trace::TraceLayer, timeout::TimeoutLayer};
-use tracing::info;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing::{info, warn};
+use metrics_exporter_prometheus::PrometheusHandle;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 mod config;
 mod database;
@@ -16,65 +21,378 @@ mod services;
 mod utils;
 mod error;
 mod auth;
+mod graphql;
+mod grpc;
+mod openapi;
 
 use config::Config;
-use handlers::{health, auth as auth_handlers, user_management, blockchain, analytics, trading, meters};
-use auth::{jwt::JwtService, jwt::ApiKeyService};
+use handlers::{health, auth as auth_handlers, user_management, blockchain, analytics, trading, meters, erc, governance, signing, roles, reports, indexer, channels, dashboard, api_keys, audit, tx, graphql as graphql_handlers, webhooks, clearing, billing, carbon, pricing, disputes, notifications, users, market_feed, simulation, tenants};
+use auth::{jwt::JwtService, jwt::ApiKeyService, middleware::PermissionGuard};
+use database::redis_pool::RedisPool;
+use database::PoolSettings;
+use services::blockchain::BlockchainService;
+use services::circuit_breaker::{BreakerSettings, CircuitBreaker};
+use services::channels::ChannelService;
+use services::dashboard::DashboardMirror;
+use services::erc_events::ErcEventFeed;
+use services::event_bus::EventBus;
+use services::fee_payers::FeePayerPool;
+use services::gateway_signer::GatewaySigner;
+use services::billing::BillingService;
+use services::carbon::CarbonService;
+use services::price_oracle::PriceOracle;
+use services::erc_auto_issuance::AutoIssuer;
+use services::market_clearing::MarketClearingService;
+use services::market_feed::MarketFeed;
+use services::matching::{MatchingEngine, MatchingMode};
+use services::meter_polling::MeterPoller;
+use services::meter_watch::MeterWatch;
+use services::metrics::{PoolUsage, QueueDepths};
+use services::backfill::BackfillService;
+use services::notifications::{NotificationDispatcher, NotificationStore};
+use services::order_book::OrderBookMirror;
+use services::program_logs::ProgramLogSubscriber;
+use services::reports::ReportService;
+use services::scheduler::{CronSchedule, DailySchedule};
+use services::simulation::{SimulationRuns, SimulationSettings, Simulator};
+use services::tx_worker::TxWorker;
+use services::webhooks::{WebhookDispatcher, WebhookStore};
+use utils::clock::{SharedClock, SystemClock};
+use utils::log_sampling::{LogSamplingConfig, SamplingFilter};
+use utils::telemetry;
 
 /// Application state shared across handlers
 #[derive(Clone)]
 pub struct AppState {
     pub db: sqlx::PgPool,
     pub timescale_db: sqlx::PgPool,
-    pub redis: redis::Client,
+    pub redis: RedisPool,
     pub config: Config,
     pub jwt_service: JwtService,
     pub api_key_service: ApiKeyService,
+    pub blockchain_service: BlockchainService,
+    pub order_book: Arc<OrderBookMirror>,
+    pub dashboard: Arc<DashboardMirror>,
+    pub program_events: Arc<ProgramLogSubscriber>,
+    pub market_feed: Arc<MarketFeed>,
+    pub erc_events: Arc<ErcEventFeed>,
+    pub simulation: SimulationRuns,
+    pub signer: GatewaySigner,
+    pub fee_payers: FeePayerPool,
+    pub clock: SharedClock,
+    pub metrics: PrometheusHandle,
+    pub graphql: graphql::GatewaySchema,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
+    // Load configuration
+    let config = Config::from_env()?;
+
+    // Initialize tracing with optional per-module log sampling and OTLP span export
+    let otel_tracer = config
+        .otel_exporter_endpoint
+        .as_deref()
+        .map(|endpoint| telemetry::otlp_tracer(endpoint, &config.otel_service_name))
+        .transpose()?;
+    let log_sampling = LogSamplingConfig::parse(config.log_sampling.as_deref().unwrap_or_default())
+        .map_err(|e| anyhow::anyhow!("Invalid LOG_SAMPLING: {}", e))?;
+    let sampling_filter = (!log_sampling.is_empty()).then(|| SamplingFilter::new(log_sampling));
+
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "api_gateway=debug,tower_http=debug".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_filter(sampling_filter))
+        .with(otel_tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer)))
         .init();
 
-    // Load configuration
-    let config = Config::from_env()?;
     info!("Loaded configuration for environment: {}", config.environment);
+    if let Some(spec) = config.log_sampling.as_deref() {
+        info!("Log sampling enabled: {}", spec);
+    }
+    if let Some(endpoint) = config.otel_exporter_endpoint.as_deref() {
+        info!("Exporting traces to {} as {}", endpoint, config.otel_service_name);
+    }
+
+    // Prometheus recorder behind GET /metrics
+    let metrics = services::metrics::install_recorder()?;
+    info!("Prometheus metrics recorder installed");
 
     // Setup database connections
-    let db_pool = database::setup_database(&config.database_url).await?;
+    let pool_settings = PoolSettings::from_config(&config);
+    let db_pool = database::setup_database(&config.database_url, &pool_settings).await?;
     info!("PostgreSQL connection established");
 
-    let timescale_pool = database::setup_timescale_database(&config.timescale_url).await?;
+    let timescale_pool = database::setup_timescale_database(&config.timescale_url, &pool_settings).await?;
     info!("TimescaleDB connection established");
 
-    // Run database migrations (PostgreSQL only - TimescaleDB has its own schema)
+    // Embedded schema migrations; `--migrate-only` applies them and exits, for deploy jobs
+    // that migrate before new replicas start
     database::run_migrations(&db_pool).await?;
-    info!("Database migrations completed successfully");
+    if config.timescale_url != config.database_url {
+        database::run_timescale_migrations(&timescale_pool).await?;
+    } else {
+        // Both sets would share one `_sqlx_migrations` table, where their versions collide
+        warn!("TIMESCALE_URL is DATABASE_URL; skipping TimescaleDB migrations");
+    }
+    if std::env::args().any(|arg| arg == "--migrate-only") {
+        info!("Migrations applied; exiting (--migrate-only)");
+        return Ok(());
+    }
+
+    // Setup Redis connection pool, connected on first use
+    let redis_pool = RedisPool::new(
+        redis::Client::open(config.redis_url.as_str())?,
+        config.redis_pool_size,
+        Duration::from_millis(config.redis_acquire_timeout_ms),
+    );
+    info!("Redis pool of {} connections configured", config.redis_pool_size);
 
-    // Setup Redis connection
-    let redis_client = redis::Client::open(config.redis_url.as_str())?;
-    info!("Redis connection established");
+    // Queue depth and connection pool gauges for GET /metrics
+    QueueDepths::spawn(db_pool.clone(), Duration::from_secs(config.queue_metrics_interval));
+    PoolUsage::spawn(
+        vec![("postgres", db_pool.clone()), ("timescale", timescale_pool.clone())],
+        redis_pool.clone(),
+        Duration::from_secs(config.queue_metrics_interval),
+    );
+
+    // Wall clock shared by every service, so tests can substitute a simulated one
+    let clock = SystemClock::shared();
 
     // Initialize authentication services
-    let jwt_service = JwtService::new()?;
+    let jwt_service = JwtService::new()?.with_clock(clock.clone());
     let api_key_service = ApiKeyService::new()?;
     info!("Authentication services initialized");
 
+    // RPC calls fail fast while the node is failing, instead of waiting out the request timeout
+    let rpc_breaker = CircuitBreaker::new("Solana RPC", BreakerSettings::from_config(&config), clock.clone());
+    let blockchain_service = BlockchainService::new(&config.solana_rpc_url)?.with_circuit_breaker(rpc_breaker);
+    info!("Solana RPC client configured for {}", config.solana_rpc_url);
+    if config.blockhash_refresh_ms > 0 {
+        // Submissions share one recent blockhash instead of each fetching its own
+        blockchain_service
+            .clone()
+            .spawn_blockhash_refresh(Duration::from_millis(config.blockhash_refresh_ms));
+    }
+
+    // ERC and governance signing key (local, Vault transit or KMS), rotatable without a restart
+    let signer = GatewaySigner::from_config(&config, clock.clone()).await?;
+    if let Some(pubkey) = signer.pubkey().await {
+        info!("Gateway signer {}", pubkey);
+    }
+
+    // Fee payers rotated across gateway transactions, skipped while their balance is low
+    let fee_payers = FeePayerPool::from_config(&config)?;
+    if !fee_payers.is_empty() {
+        fee_payers.clone().spawn_balance_monitor(
+            blockchain_service.clone(),
+            config.min_signer_balance,
+            Duration::from_secs(config.fee_payer_balance_interval),
+        );
+        info!("{} fee payers in rotation", fee_payers.pubkeys().len());
+    }
+
+    // In-memory order book mirror fed by order change notifications
+    let order_book = Arc::new(OrderBookMirror::with_clock(clock.clone()));
+    order_book.spawn(db_pool.clone(), Duration::from_secs(config.order_book_check_interval));
+    info!("Order book mirror started");
+
+    // Live dashboard aggregates, refreshed in the background
+    let dashboard = Arc::new(DashboardMirror::new(
+        clock.clone(),
+        Duration::from_secs(config.dashboard_stale_after),
+    ));
+    dashboard.spawn(db_pool.clone(), Duration::from_secs(config.dashboard_refresh_interval));
+    info!("Live dashboard refreshing every {}s", config.dashboard_refresh_interval);
+
+    // Decoded governance and trading events from the RPC websocket
+    let program_events = Arc::new(ProgramLogSubscriber::from_config(&config)?);
+    program_events.spawn();
+    let mut events = program_events.subscribe();
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(notice) => info!("Program event in {}: {:?}", notice.signature, notice.event),
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!("Program event log skipped {} events", missed)
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+    info!("Program log subscriber connecting to {}", config.solana_ws_url);
+
+    // Order book deltas, trades and clearing results streamed to `/ws/market` clients
+    let market_feed = Arc::new(MarketFeed::new());
+    market_feed.spawn(db_pool.clone(), order_book.clone(), &program_events);
+
+    // Certificate lifecycle events served by `/erc/stream`, including lapsed expiries
+    let erc_events = Arc::new(ErcEventFeed::new(db_pool.clone(), clock.clone()));
+    erc_events.spawn(Duration::from_secs(config.erc_expiry_sweep_interval));
+
     // Create application state
     let app_state = AppState {
         db: db_pool,
         timescale_db: timescale_pool,
-        redis: redis_client,
+        redis: redis_pool,
         config: config.clone(),
         jwt_service,
         api_key_service,
+        blockchain_service,
+        order_book,
+        dashboard,
+        program_events,
+        market_feed,
+        erc_events,
+        simulation: SimulationRuns::new(),
+        signer,
+        fee_payers,
+        clock,
+        metrics,
+        graphql: graphql::schema(),
+    };
+
+    // Nightly reconciliation report
+    let report_schedule = DailySchedule::at(config.report_hour, 0)
+        .ok_or_else(|| anyhow::anyhow!("REPORT_HOUR must be between 0 and 23"))?;
+    ReportService::from_state(&app_state)?.spawn(report_schedule);
+    info!("Reconciliation report scheduled daily at {:02}:00 UTC", config.report_hour);
+
+    // Monthly billing periods, closed alongside the nightly report once their grace days are over
+    BillingService::from_state(&app_state)?.spawn(report_schedule);
+    info!("Billing periods close {} days after month end", config.billing_grace_days);
+
+    // Monthly avoided-emission rollups for sustainability reporting, kept current through the next month
+    CarbonService::from_state(&app_state).spawn(report_schedule);
+
+    // Market clearing at the trading program's epoch boundaries
+    match config.market_clearing_schedule.as_deref() {
+        Some(expression) => {
+            let schedule = CronSchedule::parse(expression)
+                .map_err(|e| anyhow::anyhow!("MARKET_CLEARING_SCHEDULE: {}", e))?;
+            MarketClearingService::from_state(&app_state)?.spawn(schedule);
+            info!("Market clearing scheduled at {}", expression);
+        }
+        None => info!("Market clearing schedule disabled; clearing runs only when triggered manually"),
+    }
+
+    // ERCs issued from prosumers' validated net generation once it crosses the threshold
+    match config.erc_auto_issuance_schedule.as_deref() {
+        Some(expression) => {
+            let schedule = CronSchedule::parse(expression)
+                .map_err(|e| anyhow::anyhow!("ERC_AUTO_ISSUANCE_SCHEDULE: {}", e))?;
+            AutoIssuer::from_state(&app_state)?.spawn(schedule);
+            info!(
+                "Automatic ERC issuance scheduled at {} past {} kWh",
+                expression, config.erc_auto_issuance_threshold_kwh
+            );
+        }
+        None => info!("Automatic ERC issuance disabled; certificates are issued only on request"),
+    }
+
+    // Official grid tariff from signed snapshots, published to the oracle program as a clearing bound
+    let price_oracle = PriceOracle::from_state(&app_state)?;
+    if price_oracle.is_configured() {
+        let schedule = CronSchedule::parse(&config.price_oracle_schedule)
+            .map_err(|e| anyhow::anyhow!("PRICE_ORACLE_SCHEDULE: {}", e))?;
+        price_oracle.spawn(schedule);
+        info!("Grid tariff fetched at {}", config.price_oracle_schedule);
+    } else {
+        info!("Price oracle disabled; PRICE_ORACLE_URL is not set");
+    }
+
+    // Off-chain order matching, settled on-chain in batches through the transaction queue
+    match config.matching_mode.as_deref() {
+        Some(mode) => {
+            let mode: MatchingMode = mode.parse().map_err(|e| anyhow::anyhow!("MATCHING_MODE: {}", e))?;
+            let epoch_schedule = match mode {
+                MatchingMode::Continuous => None,
+                MatchingMode::Epoch => {
+                    let expression = config.market_clearing_schedule.as_deref().ok_or_else(|| {
+                        anyhow::anyhow!("MATCHING_MODE=epoch matches at MARKET_CLEARING_SCHEDULE, which is unset")
+                    })?;
+                    Some(CronSchedule::parse(expression).map_err(|e| anyhow::anyhow!("MARKET_CLEARING_SCHEDULE: {}", e))?)
+                }
+            };
+            Arc::new(MatchingEngine::from_state(&app_state, mode)?).spawn(epoch_schedule);
+            info!("Matching engine running in {:?} mode", mode);
+        }
+        None => info!("Matching engine disabled"),
+    }
+
+    // Pick up indexer backfills interrupted by the last shutdown
+    let resumed = BackfillService::from_state(&app_state).resume_running().await?;
+    if resumed > 0 {
+        info!("Resumed {} indexer backfills", resumed);
+    }
+
+    // Background signing, submission and confirmation of queued chain transactions
+    TxWorker::from_state(&app_state)?.spawn(Duration::from_secs(config.tx_queue_interval));
+    info!("Transaction queue worker polling every {}s", config.tx_queue_interval);
+
+    // Signed integrator webhooks for decoded program events and reading anomalies
+    WebhookStore::from_state(&app_state).spawn_program_events(app_state.program_events.subscribe());
+    WebhookDispatcher::from_state(&app_state)?.spawn(Duration::from_secs(config.webhook_delivery_interval));
+    info!("Webhook dispatcher delivering every {}s", config.webhook_delivery_interval);
+
+    // Scheduled reads of Modbus-TCP and DLMS/COSEM meters that cannot push readings
+    MeterPoller::from_state(&app_state).spawn(Duration::from_secs(config.meter_poll_interval));
+    info!("Meter poller checking for due meters every {}s", config.meter_poll_interval);
+
+    // Signed readings meters publish to an MQTT broker instead of the API
+    #[cfg(feature = "mqtt")]
+    if let Some(subscriber) = services::mqtt_readings::MqttReadingSubscriber::from_state(&app_state) {
+        subscriber.spawn()?;
+        info!("Taking meter readings published on MQTT topic {}", config.mqtt_reading_topic);
+    }
+
+    // User alerts by email and LINE Notify for fills, certificates, offline meters and statements
+    let event_bus = Arc::new(EventBus::new(app_state.db.clone()));
+    event_bus.spawn();
+    NotificationStore::from_state(&app_state).spawn_events(event_bus.subscribe());
+    NotificationDispatcher::from_state(&app_state)?.spawn(Duration::from_secs(config.notification_delivery_interval));
+    MeterWatch::from_state(&app_state).spawn(Duration::from_secs(config.meter_offline_sweep_interval));
+    info!(
+        "Notification dispatcher sending every {}s; offline meter sweep every {}s",
+        config.notification_delivery_interval, config.meter_offline_sweep_interval
+    );
+
+    // Periodic on-chain checkpoints of jointly signed balance channel states
+    if config.channel_operator_key.is_some() {
+        ChannelService::from_state(&app_state)?
+            .spawn(Duration::from_secs(config.channel_checkpoint_interval));
+        info!("Balance channel checkpoints every {}s", config.channel_checkpoint_interval);
+    }
+
+    // Synthetic meter load for throughput tests: `--simulate-meters N [--simulate-on-chain]`
+    if let Some(request) = services::simulation::request_from_args(std::env::args().skip(1))
+        .map_err(|e| anyhow::anyhow!(e))?
+    {
+        let settings = SimulationSettings::from_request(&request)?;
+        Simulator::from_state(&app_state)?.start(settings)?;
+    }
+
+    // gRPC surface for machine-to-machine integrations, sharing the REST service layer
+    if let Some(grpc_port) = config.grpc_port {
+        let grpc_addr = SocketAddr::from(([0, 0, 0, 0], grpc_port));
+        let grpc_state = app_state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = grpc::serve(grpc_state, grpc_addr).await {
+                tracing::error!("gRPC server on {} stopped: {}", grpc_addr, e);
+            }
+        });
+        info!("Starting gRPC server on {}", grpc_addr);
+    }
+
+    // Permission route guard; applied inside the authentication layer. Every authenticated
+    // route carries one except token refresh and listing one's own permissions.
+    let require = |permission: &'static str| {
+        from_fn_with_state(
+            PermissionGuard::new(&app_state, permission),
+            auth::middleware::require_permission,
+        )
     };
 
     // Build application router
@@ -83,16 +401,26 @@ async fn main() -> Result<()> {
         .route("/health", get(health::health_check))
         .route("/health/ready", get(health::readiness_check))
         .route("/health/live", get(health::liveness_check))
+        .route("/healthz", get(health::liveness_check))
+        .route("/readyz", get(health::readiness_check))
+        .route("/metrics", get(health::prometheus_metrics))
+
+        // Generated OpenAPI spec and its Swagger UI (no authentication required)
+        .merge(SwaggerUi::new("/swagger-ui").url("/openapi.json", openapi::ApiDoc::openapi()))
         
         // Authentication routes (no authentication required)
         .route("/auth/login", post(auth_handlers::login))
         .route("/auth/register", post(user_management::enhanced_register))
+        .route("/auth/oidc/login", get(auth_handlers::oidc_login))
+        .route("/auth/oidc/callback", get(auth_handlers::oidc_callback))
         
         // Protected user routes
         .nest("/auth", Router::new()
-            .route("/profile", get(auth_handlers::get_profile))
-            .route("/profile", post(auth_handlers::update_profile))
-            .route("/password", post(auth_handlers::change_password))
+            .route("/refresh", post(auth_handlers::refresh))
+            .route("/profile", get(auth_handlers::get_profile).route_layer(require("profile:read")))
+            .route("/profile", post(auth_handlers::update_profile).route_layer(require("profile:update")))
+            .route("/password", post(auth_handlers::change_password).route_layer(require("profile:update")))
+            .route("/permissions", get(roles::get_own_permissions))
             .layer(from_fn_with_state(
                 app_state.clone(),
                 auth::middleware::auth_middleware,
@@ -101,40 +429,92 @@ async fn main() -> Result<()> {
         
         // Enhanced user management routes (authenticated users)
         .nest("/user", Router::new()
-            .route("/wallet", post(user_management::update_wallet_address))
-            .route("/wallet", axum::routing::delete(user_management::remove_wallet_address))
-            .route("/activity", get(user_management::get_user_activity))
+            .route("/wallet", post(user_management::update_wallet_address).route_layer(require("profile:update")))
+            .route(
+                "/wallet",
+                axum::routing::delete(user_management::remove_wallet_address).route_layer(require("profile:update")),
+            )
+            .route("/activity", get(user_management::get_user_activity).route_layer(require("profile:read")))
             .layer(from_fn_with_state(
                 app_state.clone(),
                 auth::middleware::auth_middleware,
             ))
         )
         
-        // Admin-only user management routes
+        // Permission-guarded user management routes
         .nest("/users", Router::new()
-            .route("/:id", get(auth_handlers::get_user))
-            .route("/:id", axum::routing::put(user_management::admin_update_user))
-            .route("/:id/deactivate", post(user_management::admin_deactivate_user))
-            .route("/:id/reactivate", post(user_management::admin_reactivate_user))
-            .route("/:id/activity", get(user_management::get_user_activity))
-            .route("/", get(auth_handlers::list_users))
+            .route("/:id", get(auth_handlers::get_user).route_layer(require("users:read")))
+            .route("/:id", put(user_management::admin_update_user).route_layer(require("users:update")))
+            .route("/:id/deactivate", post(user_management::admin_deactivate_user).route_layer(require("users:manage")))
+            .route("/:id/reactivate", post(user_management::admin_reactivate_user).route_layer(require("users:manage")))
+            .route("/:id/activity", get(user_management::get_user_activity).route_layer(require("users:read")))
+            .route("/", get(auth_handlers::list_users).route_layer(require("users:read")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+
+        // Prosumer profiles with their wallets and meters
+        .nest("/prosumers", Router::new()
+            .route("/me", get(users::get_own_profile).route_layer(require("profile:read")))
+            .route("/", get(users::list_prosumers).route_layer(require("users:read")))
+            .route("/", post(users::create_prosumer).route_layer(require("users:create")))
+            .route("/:id", get(users::get_prosumer).route_layer(require("users:read")))
+            .route("/:id", put(users::update_prosumer).route_layer(require("users:update")))
+            .route("/:id", delete(users::delete_prosumer).route_layer(require("users:manage")))
             .layer(from_fn_with_state(
                 app_state.clone(),
                 auth::middleware::auth_middleware,
             ))
         )
         
+        // Live campus dashboard for lobby displays (public, served from memory)
+        .route("/dashboard/live", get(dashboard::get_live_dashboard))
+        
+        // Live market data stream (authenticated users; browsers may pass `?access_token=`)
+        .nest("/ws", Router::new()
+            .route("/market", get(market_feed::market_socket).route_layer(require("trading:read")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Printed certificate QR verification (public, cacheable)
+        .route("/verify/erc/:certificate_id", get(erc::verify_certificate))
+        
         // Department information routes (public)
         .route("/departments/:department", get(user_management::get_department_info))
         
         // Blockchain interaction routes (authenticated users)
         .nest("/blockchain", Router::new()
-            .route("/transactions", post(blockchain::submit_transaction))
-            .route("/transactions", get(blockchain::get_transaction_history))
-            .route("/transactions/:signature", get(blockchain::get_transaction_status))
-            .route("/programs/:name", post(blockchain::interact_with_program))
-            .route("/accounts/:address", get(blockchain::get_account_info))
-            .route("/network", get(blockchain::get_network_status))
+            .route("/transactions", post(blockchain::submit_transaction).route_layer(require("blockchain:submit")))
+            .route("/transactions", get(blockchain::get_transaction_history).route_layer(require("blockchain:read")))
+            .route(
+                "/transactions/:signature",
+                get(blockchain::get_transaction_status).route_layer(require("blockchain:read")),
+            )
+            .route(
+                "/programs/:name",
+                post(blockchain::interact_with_program).route_layer(require("blockchain:submit")),
+            )
+            .route("/accounts/:address", get(blockchain::get_account_info).route_layer(require("blockchain:read")))
+            .route("/network", get(blockchain::get_network_status).route_layer(require("blockchain:read")))
+            .route("/oracle", get(blockchain::get_oracle_state).route_layer(require("blockchain:read")))
+            .route("/governance", get(blockchain::get_poa_config).route_layer(require("governance:read")))
+            .route(
+                "/signing-sessions",
+                post(signing::create_signing_session).route_layer(require("blockchain:submit")),
+            )
+            .route(
+                "/signing-sessions/:id",
+                get(signing::get_signing_session).route_layer(require("blockchain:read")),
+            )
+            .route(
+                "/signing-sessions/:id/signatures",
+                post(signing::submit_signature).route_layer(require("blockchain:submit")),
+            )
             .layer(from_fn_with_state(
                 app_state.clone(),
                 auth::middleware::auth_middleware,
@@ -143,10 +523,125 @@ async fn main() -> Result<()> {
         
         // Trading routes (authenticated users)
         .nest("/trading", Router::new()
-            .route("/orders", post(trading::create_order))
-            .route("/orders", get(trading::get_user_orders))
-            .route("/market", get(trading::get_market_data))
-            .route("/stats", get(trading::get_trading_stats))
+            .route("/orders", post(trading::create_order).route_layer(require("trading:create")))
+            .route("/orders", get(trading::get_user_orders).route_layer(require("trading:read")))
+            .route("/market", get(trading::get_market_data).route_layer(require("trading:read")))
+            .route("/stats", get(trading::get_trading_stats).route_layer(require("trading:read")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Orders for the off-chain matching engine (authenticated users)
+        .nest("/orders", Router::new()
+            .route("/", post(trading::place_order).route_layer(require("trading:create")))
+            .route("/:id", delete(trading::cancel_order).route_layer(require("trading:create")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Short-term meter forecasts (authenticated users)
+        .nest("/forecast", Router::new()
+            .route("/:meter_id", get(meters::get_forecast).route_layer(require("energy:read")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Settlement statement exports for invoicing (finance)
+        .nest("/settlements", Router::new()
+            .route(
+                "/:period/export",
+                get(billing::export_statements).route_layer(require("billing:read")),
+            )
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Own settlement statements (authenticated users)
+        .nest("/billing", Router::new()
+            .route("/statements", get(billing::list_own_statements).route_layer(require("profile:read")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Time-of-use periods bounding the prices orders match at
+        .nest("/pricing", Router::new()
+            .route("/current", get(pricing::get_current).route_layer(require("trading:read")))
+            .route("/periods", get(pricing::list_periods).route_layer(require("trading:read")))
+            .route("/holidays", get(pricing::list_holidays).route_layer(require("trading:read")))
+            .route("/grid", get(pricing::get_grid_price).route_layer(require("trading:read")))
+            .route("/grid/history", get(pricing::list_grid_prices).route_layer(require("trading:read")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Avoided emissions of own, prosumers', buildings' and campus generation
+        .nest("/carbon", Router::new()
+            .route("/me", get(carbon::get_own_emissions).route_layer(require("profile:read")))
+            .route(
+                "/prosumers/:user_id",
+                get(carbon::get_prosumer_emissions).route_layer(require("carbon:read")),
+            )
+            .route(
+                "/buildings/:building",
+                get(carbon::get_building_emissions).route_layer(require("carbon:read")),
+            )
+            .route("/campus", get(carbon::get_campus_emissions).route_layer(require("carbon:read")))
+            .route("/rollups", get(carbon::list_rollups).route_layer(require("carbon:read")))
+            .route("/factors", get(carbon::list_factors).route_layer(require("carbon:read")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Prosumer disputes over readings and certificates
+        .nest("/disputes", Router::new()
+            .route(
+                "/",
+                get(disputes::list_own_disputes)
+                    .post(disputes::open_dispute)
+                    .route_layer(require("disputes:create")),
+            )
+            .route("/:id", get(disputes::get_dispute).route_layer(require("disputes:create")))
+            .route("/:id/evidence", post(disputes::add_evidence).route_layer(require("disputes:create")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Own alert channels and alert history
+        .nest("/notifications", Router::new()
+            .route("/", get(notifications::list_notifications).route_layer(require("profile:read")))
+            .route(
+                "/preferences",
+                get(notifications::get_preferences).route_layer(require("profile:read")),
+            )
+            .route(
+                "/preferences",
+                put(notifications::set_preference).route_layer(require("profile:update")),
+            )
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Order book served from the in-memory mirror (authenticated users)
+        .nest("/orderbook", Router::new()
+            .route("/", get(trading::get_order_book).route_layer(require("trading:read")))
             .layer(from_fn_with_state(
                 app_state.clone(),
                 auth::middleware::auth_middleware,
@@ -155,10 +650,89 @@ async fn main() -> Result<()> {
         
         // Energy meter routes (authenticated users)
         .nest("/meters", Router::new()
-            .route("/readings", post(meters::submit_energy_reading))
-            .route("/readings", get(meters::get_energy_readings))
-            .route("/readings/:id", get(meters::get_energy_reading_by_id))
-            .route("/aggregated", get(meters::get_aggregated_readings))
+            .route(
+                "/readings",
+                post(meters::submit_energy_reading)
+                    .route_layer(from_fn_with_state(
+                        app_state.clone(),
+                        middleware::idempotency::idempotency_middleware,
+                    ))
+                    .route_layer(require("energy:submit")),
+            )
+            .route("/readings", get(meters::get_energy_readings).route_layer(require("energy:read")))
+            .route("/readings/:id", get(meters::get_energy_reading_by_id).route_layer(require("energy:read")))
+            .route("/aggregated", get(meters::get_aggregated_readings).route_layer(require("energy:read")))
+            .route("/:meter_id/energy", get(meters::get_energy_series).route_layer(require("energy:read")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // GraphQL queries over indexed data (authenticated users)
+        .nest("/graphql", Router::new()
+            .route("/", post(graphql_handlers::execute).route_layer(require("blockchain:read")))
+            .route("/schema", get(graphql_handlers::schema).route_layer(require("blockchain:read")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Historical reading imports
+        .nest("/readings", Router::new()
+            .route("/import", post(meters::import_readings).route_layer(require("readings:import")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // ERC certificate routes (authenticated users; issuance for the department)
+        .nest("/erc", Router::new()
+            .route("/", post(erc::issue_certificate).route_layer(require("erc:issue")))
+            .route("/", get(erc::search_certificates).route_layer(require("erc:read")))
+            .route("/stream", get(erc::stream_events).route_layer(require("erc:read")))
+            .route("/:certificate_id/validate", post(erc::validate_certificate).route_layer(require("erc:validate")))
+            .route("/certificates", get(erc::list_certificates).route_layer(require("erc:read")))
+            .route("/certificates/:certificate_id", get(erc::get_certificate).route_layer(require("erc:read")))
+            .route(
+                "/certificates/:certificate_id/verification-link",
+                get(erc::get_verification_link).route_layer(require("erc:read")),
+            )
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Queued transaction job status (authenticated users)
+        .nest("/tx", Router::new()
+            .route("/:job_id", get(tx::get_job).route_layer(require("blockchain:read")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Indexed governance state routes (authenticated users)
+        .nest("/governance", Router::new()
+            .route("/config", get(governance::get_governance_config).route_layer(require("governance:read")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Balance channel routes (authenticated users)
+        .nest("/channels", Router::new()
+            .route("/", post(channels::open_channel).route_layer(require("trading:create")))
+            .route("/me", get(channels::get_own_channel).route_layer(require("trading:read")))
+            .route("/me/updates", get(channels::list_own_updates).route_layer(require("trading:read")))
+            .route(
+                "/me/updates/:nonce/signature",
+                post(channels::sign_update).route_layer(require("trading:create")),
+            )
             .layer(from_fn_with_state(
                 app_state.clone(),
                 auth::middleware::auth_middleware,
@@ -167,18 +741,202 @@ async fn main() -> Result<()> {
         
         // Analytics routes (authenticated users with role restrictions)
         .nest("/analytics", Router::new()
-            .route("/user", get(analytics::get_user_analytics))
-            .route("/system", get(analytics::get_system_analytics))
+            .route("/user", get(analytics::get_user_analytics).route_layer(require("analytics:read")))
+            .route("/system", get(analytics::get_system_analytics).route_layer(require("analytics:system")))
             .layer(from_fn_with_state(
                 app_state.clone(),
                 auth::middleware::auth_middleware,
             ))
         )
         
+        // Role, permission, report, indexer, channel, governance, API key and audit administration
+        .nest("/admin", Router::new()
+            .route("/permissions", get(roles::list_permissions).route_layer(require("roles:read")))
+            .route("/roles", get(roles::list_roles).route_layer(require("roles:read")))
+            .route("/roles", post(roles::create_role).route_layer(require("roles:manage")))
+            .route(
+                "/roles/:id",
+                put(roles::update_role).delete(roles::delete_role).route_layer(require("roles:manage")),
+            )
+            .route("/users/:user_id/roles", get(roles::get_user_roles).route_layer(require("roles:read")))
+            .route(
+                "/users/:user_id/roles/:role_id",
+                put(roles::assign_role).delete(roles::revoke_role).route_layer(require("roles:manage")),
+            )
+            .route("/reports", get(reports::list_reports).route_layer(require("reports:read")))
+            .route("/reports", post(reports::run_report).route_layer(require("reports:run")))
+            .route("/reports/:id", get(reports::get_report).route_layer(require("reports:read")))
+            .route("/reports/:id/html", get(reports::get_report_html).route_layer(require("reports:read")))
+            .route("/indexer/status", get(indexer::get_indexer_status).route_layer(require("indexer:read")))
+            .route("/indexer/backfills", post(indexer::start_backfill).route_layer(require("indexer:manage")))
+            .route(
+                "/indexer/backfills/:id",
+                delete(indexer::cancel_backfill).route_layer(require("indexer:manage")),
+            )
+            .route("/channels", get(channels::list_channels).route_layer(require("channels:read")))
+            .route(
+                "/channels/:id/updates",
+                post(channels::record_update).route_layer(require("channels:manage")),
+            )
+            .route(
+                "/channels/:id/dispute",
+                post(channels::dispute_channel).route_layer(require("channels:manage")),
+            )
+            .route("/signer/reload", post(governance::reload_signer).route_layer(require("signer:rotate")))
+            .route("/meters/polling", get(meters::list_polling).route_layer(require("meters:manage")))
+            .route(
+                "/meters/:meter_id/polling",
+                put(meters::configure_polling)
+                    .delete(meters::remove_polling)
+                    .route_layer(require("meters:manage")),
+            )
+            .route("/meters/:meter_id/inverter", put(meters::set_inverter).route_layer(require("meters:manage")))
+            .route(
+                "/meters",
+                get(meters::list_meters)
+                    .post(meters::provision_meter)
+                    .route_layer(require("meters:provision")),
+            )
+            .route("/meters/:meter_id", get(meters::get_meter).route_layer(require("meters:provision")))
+            .route(
+                "/meters/:meter_id/credentials",
+                post(meters::rotate_meter_credentials).route_layer(require("meters:provision")),
+            )
+            .route(
+                "/meters/:meter_id/decommission",
+                post(meters::decommission_meter).route_layer(require("meters:provision")),
+            )
+            .route("/meters/keys", get(meters::list_meter_keys).route_layer(require("meters:manage")))
+            .route(
+                "/simulation",
+                get(simulation::get_simulation)
+                    .post(simulation::start_simulation)
+                    .delete(simulation::stop_simulation)
+                    .route_layer(require("readings:simulate")),
+            )
+            .route(
+                "/meters/:meter_id/key",
+                put(meters::register_meter_key)
+                    .delete(meters::remove_meter_key)
+                    .route_layer(require("meters:manage")),
+            )
+            .route(
+                "/readings/quarantine",
+                get(meters::list_quarantined).route_layer(require("readings:review")),
+            )
+            .route(
+                "/readings/quarantine/:id/approve",
+                post(meters::approve_reading).route_layer(require("readings:review")),
+            )
+            .route(
+                "/readings/quarantine/:id/reject",
+                post(meters::reject_reading).route_layer(require("readings:review")),
+            )
+            .route("/webhooks", get(webhooks::list_webhooks).route_layer(require("webhooks:read")))
+            .route("/webhooks", post(webhooks::create_webhook).route_layer(require("webhooks:manage")))
+            .route("/webhooks/:id", delete(webhooks::delete_webhook).route_layer(require("webhooks:manage")))
+            .route(
+                "/webhooks/:id/deliveries",
+                get(webhooks::list_deliveries).route_layer(require("webhooks:read")),
+            )
+            .route(
+                "/webhooks/deliveries/:id/redeliver",
+                post(webhooks::redeliver).route_layer(require("webhooks:manage")),
+            )
+            .route(
+                "/market/clearing",
+                post(clearing::trigger_clearing).route_layer(require("clearing:run")),
+            )
+            .route(
+                "/market/clearing/runs",
+                get(clearing::list_runs).route_layer(require("clearing:read")),
+            )
+            .route(
+                "/erc/auto-issuances",
+                get(erc::list_auto_issuances).route_layer(require("erc:read")),
+            )
+            .route(
+                "/erc/auto-issuances",
+                post(erc::run_auto_issuance).route_layer(require("erc:issue")),
+            )
+            .route("/billing/periods", get(billing::list_periods).route_layer(require("billing:read")))
+            .route("/billing/periods", post(billing::close_period).route_layer(require("billing:close")))
+            .route(
+                "/billing/periods/:id/statements",
+                get(billing::list_period_statements).route_layer(require("billing:read")),
+            )
+            .route(
+                "/carbon/factors/:source",
+                put(carbon::update_factor).route_layer(require("carbon:manage")),
+            )
+            .route(
+                "/carbon/rollups",
+                post(carbon::recompute_rollups).route_layer(require("carbon:manage")),
+            )
+            .route(
+                "/pricing/periods/:id",
+                put(pricing::update_period).delete(pricing::delete_period).route_layer(require("pricing:manage")),
+            )
+            .route(
+                "/pricing/grid/ingest",
+                post(pricing::ingest_grid_price).route_layer(require("pricing:manage")),
+            )
+            .route(
+                "/pricing/holidays/:day",
+                put(pricing::update_holiday).delete(pricing::delete_holiday).route_layer(require("pricing:manage")),
+            )
+            .route("/disputes", get(disputes::list_disputes).route_layer(require("disputes:read")))
+            .route("/disputes/:id", get(disputes::get_dispute).route_layer(require("disputes:read")))
+            .route(
+                "/disputes/:id/resolve",
+                post(disputes::resolve_dispute).route_layer(require("disputes:manage")),
+            )
+            .route("/governance/pause", post(governance::pause).route_layer(require("governance:manage")))
+            .route("/governance/unpause", post(governance::unpause).route_layer(require("governance:manage")))
+            .route("/governance/limits", put(governance::update_limits).route_layer(require("governance:manage")))
+            .route(
+                "/governance/maintenance",
+                put(governance::set_maintenance).route_layer(require("governance:manage")),
+            )
+            .route("/api-keys", get(api_keys::list_api_keys).route_layer(require("api_keys:read")))
+            .route("/api-keys", post(api_keys::create_api_key).route_layer(require("api_keys:manage")))
+            .route(
+                "/api-keys/:id",
+                put(api_keys::update_api_key).delete(api_keys::revoke_api_key).route_layer(require("api_keys:manage")),
+            )
+            .route("/tenants", get(tenants::list_tenants).route_layer(require("tenants:read")))
+            .route("/tenants", post(tenants::create_tenant).route_layer(require("tenants:manage")))
+            .route("/tenants/:id", put(tenants::update_tenant).route_layer(require("tenants:manage")))
+            .route("/tenants/:id/usage", get(tenants::get_tenant_usage).route_layer(require("tenants:read")))
+            .route(
+                "/users/:user_id/tenant",
+                put(tenants::assign_user_tenant).route_layer(require("tenants:manage")),
+            )
+            .route(
+                "/meters/:meter_id/tenant",
+                put(tenants::assign_meter_tenant).route_layer(require("tenants:manage")),
+            )
+            .route("/audit", get(audit::list_audit_entries).route_layer(require("audit:read")))
+            .route("/audit/verify", get(audit::verify_audit_log).route_layer(require("audit:read")))
+            .layer(from_fn_with_state(
+                app_state.clone(),
+                auth::middleware::auth_middleware,
+            ))
+        )
+        
+        // Audit log of state-changing requests (outside the routers so it sees full paths)
+        .layer(from_fn_with_state(
+            app_state.clone(),
+            middleware::audit::audit_middleware,
+        ))
+
+        // Request counts and latencies per route
+        .layer(from_fn(middleware::metrics::track_requests))
+
         // Global middleware stack
         .layer(
             ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
+                .layer(TraceLayer::new_for_http().make_span_with(telemetry::request_span::<axum::body::Body>))
                 .layer(TimeoutLayer::new(std::time::Duration::from_secs(30)))
                 .layer(CorsLayer::permissive()) // TODO: Configure proper CORS in production
         )
@@ -190,6 +948,7 @@ async fn main() -> Result<()> {
     
     let listener = tokio::net::TcpListener::bind(addr).await?;
     axum::serve(listener, app).await?;
+    telemetry::shutdown();
 
     Ok(())
 }
\ No newline at end of file
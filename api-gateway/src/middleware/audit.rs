@@ -0,0 +1,128 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header::CONTENT_TYPE, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::services::audit_log::{sha256_hex, AuditEntry, AuditLog};
+use crate::utils::telemetry::current_trace_id;
+use crate::AppState;
+
+/// Largest request or JSON response body buffered for auditing
+const MAX_AUDITED_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Response fields holding the transaction signature of a submitted transaction
+const SIGNATURE_FIELDS: &[&str] = &["signature", "tx_signature"];
+
+/// Who made a request, set on the response by `auth_middleware` for the audit log
+#[derive(Debug, Clone, Copy)]
+pub struct AuditActor {
+    pub user_id: Uuid,
+    pub api_key_id: Option<Uuid>,
+}
+
+fn is_state_changing(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE)
+}
+
+/// Transaction signature a handler reported in its JSON response
+fn response_signature(body: &[u8]) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_slice(body).ok()?;
+    SIGNATURE_FIELDS
+        .iter()
+        .find_map(|field| value.get(field)?.as_str().map(str::to_string))
+}
+
+/// Record every state-changing request in the audit log when `AUDIT_LOG_ENABLED` is set
+///
+/// Entries are appended in the background so auditing does not add latency; failures to
+/// append are logged.
+pub async fn audit_middleware(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if !state.config.audit_log_enabled || !is_state_changing(request.method()) {
+        return next.run(request).await;
+    }
+
+    let occurred_at = state.clock.now();
+    let (parts, body) = request.into_parts();
+    let payload = match to_bytes(body, MAX_AUDITED_BODY_BYTES).await {
+        Ok(payload) => payload,
+        Err(_) => return ApiError::BadRequest("Request body too large".to_string()).into_response(),
+    };
+    let method = parts.method.to_string();
+    let path = parts.uri.path().to_string();
+    let payload_hash = sha256_hex(&payload);
+
+    let response = next.run(Request::from_parts(parts, Body::from(payload))).await;
+
+    let (parts, body) = response.into_parts();
+    let is_json = parts
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    let (body, tx_signature) = if is_json {
+        match to_bytes(body, MAX_AUDITED_BODY_BYTES).await {
+            Ok(bytes) => {
+                let signature = response_signature(&bytes);
+                (Body::from(bytes), signature)
+            }
+            Err(e) => return ApiError::Internal(format!("Failed to read response body: {}", e)).into_response(),
+        }
+    } else {
+        (body, None)
+    };
+
+    let actor = parts.extensions.get::<AuditActor>().copied();
+    let entry = AuditEntry {
+        occurred_at,
+        user_id: actor.map(|actor| actor.user_id),
+        api_key_id: actor.and_then(|actor| actor.api_key_id),
+        method,
+        path,
+        status: parts.status.as_u16(),
+        payload_hash,
+        tx_signature,
+        trace_id: current_trace_id(),
+    };
+    let log = AuditLog::from_state(&state);
+    tokio::spawn(
+        async move {
+            if let Err(e) = log.append(&entry).await {
+                tracing::error!("Failed to append audit entry for {} {}: {}", entry.method, entry.path, e);
+            }
+        }
+        .in_current_span(),
+    );
+
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_state_changing_requests_are_audited() {
+        assert!(is_state_changing(&Method::POST));
+        assert!(is_state_changing(&Method::DELETE));
+        assert!(!is_state_changing(&Method::GET));
+        assert!(!is_state_changing(&Method::OPTIONS));
+    }
+
+    #[test]
+    fn test_response_signature() {
+        assert_eq!(
+            response_signature(br#"{"certificate_id":"ERC-7","signature":"5xyz"}"#),
+            Some("5xyz".to_string())
+        );
+        assert_eq!(response_signature(br#"{"tx_signature":"4abc"}"#), Some("4abc".to_string()));
+        assert_eq!(response_signature(br#"{"signature_valid":true}"#), None);
+        assert_eq!(response_signature(b"[1, 2]"), None);
+        assert_eq!(response_signature(b"not json"), None);
+    }
+}
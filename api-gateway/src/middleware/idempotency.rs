@@ -0,0 +1,188 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header::CONTENT_TYPE, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+use crate::auth::api_keys::ApiKeyPrincipal;
+use crate::auth::Claims;
+use crate::error::ApiError;
+use crate::services::audit_log::sha256_hex;
+use crate::services::idempotency::{Claim, IdempotencyRecord, IdempotencyStore};
+use crate::AppState;
+
+/// Header a client sets so a retried request replays the original response
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Header set on responses replayed from an earlier request
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Largest request or response body kept for replay
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && key.len() <= MAX_KEY_LENGTH && key.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Key scope: the API key a request authenticated with, otherwise its user
+fn caller_scope(request: &Request) -> Option<String> {
+    if let Some(principal) = request.extensions().get::<ApiKeyPrincipal>() {
+        return Some(format!("api-key:{}", principal.id));
+    }
+    request
+        .extensions()
+        .get::<Claims>()
+        .map(|claims| format!("user:{}", claims.sub))
+}
+
+/// Response to a request whose key an earlier request already used
+fn replay(record: IdempotencyRecord, fingerprint: &str) -> Response {
+    if record.fingerprint() != fingerprint {
+        return ApiError::BadRequest("Idempotency-Key was already used for a different request".to_string())
+            .into_response();
+    }
+
+    match record {
+        IdempotencyRecord::InFlight { .. } => {
+            ApiError::Conflict("A request with this Idempotency-Key is still in progress".to_string()).into_response()
+        }
+        IdempotencyRecord::Completed {
+            status,
+            content_type,
+            body,
+            ..
+        } => {
+            let (Ok(status), Ok(body)) = (StatusCode::from_u16(status), BASE64.decode(body)) else {
+                return ApiError::Internal("Stored idempotent response is corrupt".to_string()).into_response();
+            };
+            let mut response = Response::builder().status(status);
+            if let Some(content_type) = content_type {
+                response = response.header(CONTENT_TYPE, content_type);
+            }
+            response
+                .header(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"))
+                .body(Body::from(body))
+                .unwrap_or_else(|e| ApiError::Internal(e.to_string()).into_response())
+        }
+    }
+}
+
+/// Replay the original response when a client retries a request with the same `Idempotency-Key`
+///
+/// Applied inside the authentication layer, so keys are scoped per API key or user. Server
+/// errors are not stored, letting retries run the request again; when Redis is unavailable
+/// requests run without deduplication.
+pub async fn idempotency_middleware(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(request).await;
+    };
+    let key = match key.to_str() {
+        Ok(key) if is_valid_key(key) => key.to_string(),
+        _ => {
+            return ApiError::BadRequest(format!(
+                "Idempotency-Key must be 1 to {} visible ASCII characters",
+                MAX_KEY_LENGTH
+            ))
+            .into_response()
+        }
+    };
+    let Some(scope) = caller_scope(&request) else {
+        return next.run(request).await;
+    };
+
+    let (parts, body) = request.into_parts();
+    let payload = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(payload) => payload,
+        Err(_) => return ApiError::BadRequest("Request body too large".to_string()).into_response(),
+    };
+    let mut fingerprinted = format!("{} {}\n", parts.method, parts.uri.path()).into_bytes();
+    fingerprinted.extend_from_slice(&payload);
+    let fingerprint = sha256_hex(&fingerprinted);
+    let request = Request::from_parts(parts, Body::from(payload));
+
+    let store = IdempotencyStore::from_state(&state);
+    match store.claim(&scope, &key, &fingerprint).await {
+        Ok(Claim::Acquired) => {}
+        Ok(Claim::Existing(record)) => return replay(record, &fingerprint),
+        Err(e) => {
+            tracing::warn!("Idempotency store unavailable, running request without deduplication: {}", e);
+            return next.run(request).await;
+        }
+    }
+
+    let response = next.run(request).await;
+    if response.status().is_server_error() {
+        store.release(&scope, &key).await;
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => {
+            store.release(&scope, &key).await;
+            return ApiError::Internal(format!("Failed to read response body: {}", e)).into_response();
+        }
+    };
+    let record = IdempotencyRecord::Completed {
+        fingerprint,
+        status: parts.status.as_u16(),
+        content_type: parts
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        body: BASE64.encode(&body),
+    };
+    store.complete(&scope, &key, &record).await;
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completed(fingerprint: &str) -> IdempotencyRecord {
+        IdempotencyRecord::Completed {
+            fingerprint: fingerprint.to_string(),
+            status: 200,
+            content_type: Some("application/json".to_string()),
+            body: BASE64.encode(br#"{"status":"submitted"}"#),
+        }
+    }
+
+    #[test]
+    fn test_key_format() {
+        assert!(is_valid_key("meter-42/2024-09-23T10:15:00Z"));
+        assert!(!is_valid_key(""));
+        assert!(!is_valid_key("has space"));
+        assert!(!is_valid_key(&"k".repeat(MAX_KEY_LENGTH + 1)));
+    }
+
+    #[tokio::test]
+    async fn test_completed_requests_replay() {
+        let response = replay(completed("abc"), "abc");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+
+        let body = to_bytes(response.into_body(), MAX_BODY_BYTES).await.unwrap();
+        assert_eq!(&body[..], br#"{"status":"submitted"}"#);
+    }
+
+    #[test]
+    fn test_conflicting_requests_are_rejected() {
+        assert_eq!(replay(completed("abc"), "def").status(), StatusCode::BAD_REQUEST);
+
+        let in_flight = IdempotencyRecord::InFlight {
+            fingerprint: "abc".to_string(),
+        };
+        assert_eq!(replay(in_flight, "abc").status(), StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,58 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+
+/// Route label for requests that matched no route, keeping label cardinality bounded
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Count requests and record their latency per route template and status
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    metrics::histogram!("http_request_duration_seconds", "method" => method.clone(), "route" => route.clone())
+        .record(started.elapsed().as_secs_f64());
+    metrics::counter!("http_requests_total", "method" => method, "route" => route, "status" => status).increment(1);
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware::from_fn, routing::get, Router};
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_requests_are_labelled_by_route_template() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        let app = Router::new()
+            .nest("/meters", Router::new().route("/:meter_id/energy", get(|| async { "ok" })))
+            .layer(from_fn(track_requests));
+
+        for uri in ["/meters/M-1/energy", "/meters/M-2/energy", "/nowhere"] {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+
+        let rendered = handle.render();
+        assert!(rendered.contains(r#"http_requests_total{method="GET",route="/meters/:meter_id/energy",status="200"} 2"#));
+        assert!(rendered.contains(r#"http_requests_total{method="GET",route="unmatched",status="404"} 1"#));
+    }
+}
@@ -1,2 +1,5 @@
 // Middleware module - authentication, rate limiting, CORS, etc.
-// To be implemented in Phase 1
\ No newline at end of file
+
+pub mod audit;
+pub mod idempotency;
+pub mod metrics;
@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Stored audit log entry for a state-changing request
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuditRecord {
+    pub id: i64,
+    pub occurred_at: DateTime<Utc>,
+    pub user_id: Option<Uuid>,
+    pub api_key_id: Option<Uuid>,
+    pub method: String,
+    pub path: String,
+    pub status: i16,
+    /// `success` or `failure`, from the response status
+    pub outcome: String,
+    /// SHA-256 of the request body
+    pub payload_hash: String,
+    pub tx_signature: Option<String>,
+    /// OpenTelemetry trace the request ran in, when traces are exported
+    pub trace_id: Option<String>,
+    pub prev_hash: String,
+    pub entry_hash: String,
+}
+
+/// Result of re-hashing the audit chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditVerification {
+    pub entries_checked: u64,
+    /// First entry whose hash or link does not match, if any
+    pub first_invalid_id: Option<i64>,
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+/// Calendar month of trading and metering billed together; amounts are decimal strings
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BillingPeriod {
+    pub id: Uuid,
+    pub period_start: NaiveDate,
+    /// First day of the next month, exclusive
+    pub period_end: NaiveDate,
+    /// closing, closed, attention or failed
+    pub status: String,
+    /// Trading fee in force when the period closed
+    pub market_fee_bps: Option<i32>,
+    pub grid_import_price: Option<String>,
+    pub statements: i32,
+    pub reconciliation: Option<Json<SettlementReconciliation>>,
+    pub error_message: Option<String>,
+    /// Unset for scheduled closes
+    pub closed_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl BillingPeriod {
+    pub const CLOSING: &'static str = "closing";
+    pub const CLOSED: &'static str = "closed";
+    pub const ATTENTION: &'static str = "attention";
+    pub const FAILED: &'static str = "failed";
+}
+
+/// A prosumer's netted trading, metering and fees for one billing period; never changed once issued
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SettlementStatement {
+    pub id: Uuid,
+    pub billing_period_id: Uuid,
+    pub user_id: Uuid,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub energy_bought: String,
+    pub purchase_cost: String,
+    pub energy_sold: String,
+    pub sales_revenue: String,
+    /// Deducted from sales by the trading program
+    pub trading_fees: String,
+    pub energy_generated: String,
+    pub energy_consumed: String,
+    /// kWh consumed beyond the generation kept and the energy bought
+    pub grid_import: String,
+    pub grid_import_cost: String,
+    /// Owed by the prosumer; negative when owed to them
+    pub net_amount: String,
+    pub trades: i32,
+    /// Trades not yet confirmed on-chain when the period closed
+    pub unsettled_trades: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The period's matched trades compared with the batches the trading program settled
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SettlementReconciliation {
+    pub trades: i64,
+    /// Trades too small to settle in token base units
+    pub unsettleable_trades: i64,
+    pub batches: i64,
+    /// Batches whose settlement job had not been confirmed at close
+    pub pending_batches: i64,
+    pub matched_batches: i64,
+    pub mismatches: Vec<BatchMismatch>,
+    /// Set when the RPC node could not be queried
+    pub rpc_error: Option<String>,
+}
+
+impl SettlementReconciliation {
+    /// Whether anything needs a human to look at it before billing
+    pub fn needs_attention(&self) -> bool {
+        self.pending_batches > 0 || !self.mismatches.is_empty() || self.rpc_error.is_some()
+    }
+}
+
+/// Confirmed settlement batch whose on-chain record differs from its trades, or is missing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchMismatch {
+    pub batch: i64,
+    pub expected_trades: u32,
+    pub expected_energy: u64,
+    pub expected_payment: u64,
+    pub chain_trades: Option<u32>,
+    pub chain_energy: Option<u64>,
+    pub chain_payment: Option<u64>,
+}
+
+/// Statement with the prosumer it is addressed to, as exported for invoicing
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ProsumerStatement {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub statement: SettlementStatement,
+    pub username: String,
+    pub full_name: Option<String>,
+    pub email: Option<String>,
+    pub department: String,
+}
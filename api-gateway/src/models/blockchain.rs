@@ -25,4 +25,111 @@ pub struct ProgramInteraction {
     pub program_id: String,
     pub instruction_name: String,
     pub success: bool,
-}
\ No newline at end of file
+}
+/// Oracle program state read live from its `oracle_data` PDA
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OracleState {
+    pub account_address: String,
+    pub authority: String,
+    /// Gateways authorized to submit readings and trigger clearing
+    pub gateways: Vec<String>,
+    pub total_readings: u64,
+    pub last_reading_timestamp: i64,
+    pub last_clearing: i64,
+    pub active: bool,
+    pub created_at: i64,
+    /// Oldest reading accepted, in seconds before submission
+    pub max_reading_age: i64,
+}
+
+#[cfg(feature = "chain")]
+impl OracleState {
+    pub fn from_account(account_address: String, data: oracle::OracleData) -> Self {
+        Self {
+            account_address,
+            authority: data.authority.to_string(),
+            gateways: data.gateways.iter().map(ToString::to_string).collect(),
+            total_readings: data.total_readings,
+            last_reading_timestamp: data.last_reading_timestamp,
+            last_clearing: data.last_clearing,
+            active: data.active,
+            created_at: data.created_at,
+            max_reading_age: data.max_reading_age,
+        }
+    }
+}
+
+/// Governance PoA configuration read live from its `poa_config` PDA
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PoaConfigState {
+    pub account_address: String,
+    pub authority: String,
+    pub authority_name: String,
+    pub contact_email: String,
+    pub contact_phone: String,
+    pub website: String,
+    pub office_location: String,
+    /// Stored pause bitmask: 1 = issuance, 2 = validation, 4 = config updates
+    pub pause_flags: u8,
+    /// Pause flags still in force, after lapsed pauses are discounted
+    pub active_pause_flags: u8,
+    pub emergency_timestamp: Option<i64>,
+    pub emergency_reason: Option<String>,
+    pub maintenance_mode: bool,
+    pub erc_validation_enabled: bool,
+    pub delegation_enabled: bool,
+    pub oracle_authority: Option<String>,
+    pub min_energy_amount: u64,
+    pub max_erc_amount: u64,
+    pub erc_validity_period: i64,
+    pub erc_issuance_fee: u64,
+    pub council_mode: bool,
+    pub max_pause_duration: i64,
+    pub trading_program: Option<String>,
+    pub issuance_window: i64,
+    pub max_window_ercs: u64,
+    pub max_window_energy: u64,
+    pub total_ercs_issued: u64,
+    pub total_ercs_validated: u64,
+    pub version: u8,
+    pub created_at: i64,
+    pub last_updated: i64,
+}
+
+#[cfg(feature = "chain")]
+impl PoaConfigState {
+    pub fn from_account(account_address: String, data: governance::PoAConfig, now: i64) -> Self {
+        Self {
+            account_address,
+            authority: data.authority.to_string(),
+            active_pause_flags: data.active_pause_flags(now),
+            pause_flags: data.pause_flags,
+            emergency_timestamp: data.emergency_timestamp,
+            maintenance_mode: data.maintenance_mode,
+            erc_validation_enabled: data.erc_validation_enabled,
+            delegation_enabled: data.delegation_enabled,
+            oracle_authority: data.oracle_authority.map(|key| key.to_string()),
+            min_energy_amount: data.min_energy_amount,
+            max_erc_amount: data.max_erc_amount,
+            erc_validity_period: data.erc_validity_period,
+            erc_issuance_fee: data.erc_issuance_fee,
+            council_mode: data.council_mode,
+            max_pause_duration: data.max_pause_duration,
+            trading_program: data.trading_program.map(|key| key.to_string()),
+            issuance_window: data.issuance_window,
+            max_window_ercs: data.max_window_ercs,
+            max_window_energy: data.max_window_energy,
+            total_ercs_issued: data.total_ercs_issued,
+            total_ercs_validated: data.total_ercs_validated,
+            version: data.version,
+            created_at: data.created_at,
+            last_updated: data.last_updated,
+            authority_name: data.authority_name,
+            contact_email: data.contact_email,
+            contact_phone: data.contact_phone,
+            website: data.website,
+            office_location: data.office_location,
+            emergency_reason: data.emergency_reason,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Lifecycle emissions per kWh of a renewable source, or of the grid electricity it displaces
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct EmissionFactor {
+    /// `solar`, `wind`, ... or `grid`
+    pub renewable_source: String,
+    pub kg_co2e_per_kwh: f64,
+    /// Where the factor comes from
+    pub reference: String,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Generation and avoided emissions of one renewable source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceEmissions {
+    pub renewable_source: String,
+    pub generated_kwh: f64,
+    /// Backed by ERCs issued in the window
+    pub certified_kwh: f64,
+    pub avoided_kg_co2e: f64,
+    pub certified_avoided_kg_co2e: f64,
+    /// `None` when the source has no emission factor, in which case it avoids nothing
+    pub source_kg_co2e_per_kwh: Option<f64>,
+}
+
+/// Avoided emissions of a prosumer, a building or the whole campus over `[from, to)`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarbonReport {
+    /// prosumer, building or campus
+    pub scope: String,
+    /// User ID or building; empty for the campus
+    pub scope_id: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub grid_kg_co2e_per_kwh: f64,
+    pub generated_kwh: f64,
+    pub certified_kwh: f64,
+    pub avoided_kg_co2e: f64,
+    pub certified_avoided_kg_co2e: f64,
+    pub sources: Vec<SourceEmissions>,
+}
+
+/// Avoided emissions of one source for a prosumer, building or the campus over a month
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CarbonRollup {
+    /// First day of the month
+    pub month: NaiveDate,
+    pub scope: String,
+    pub scope_id: String,
+    pub renewable_source: String,
+    pub generated_kwh: f64,
+    pub certified_kwh: f64,
+    pub avoided_kg_co2e: f64,
+    pub certified_avoided_kg_co2e: f64,
+    /// Factors in force when the month was computed
+    pub grid_kg_co2e_per_kwh: f64,
+    pub source_kg_co2e_per_kwh: Option<f64>,
+    pub computed_at: DateTime<Utc>,
+}
+
+impl CarbonRollup {
+    pub const PROSUMER: &'static str = "prosumer";
+    pub const BUILDING: &'static str = "building";
+    pub const CAMPUS: &'static str = "campus";
+
+    pub const SCOPES: [&'static str; 3] = [Self::PROSUMER, Self::BUILDING, Self::CAMPUS];
+}
+
+/// Monthly rollups written by one computation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarbonRollupRun {
+    pub month: NaiveDate,
+    pub rollups: usize,
+    pub computed_at: DateTime<Utc>,
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Off-chain balance channel between a participant and the market authority
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BalanceChannel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub participant_wallet: String,
+    /// Trading program PDA holding the channel's on-chain checkpoint
+    pub channel_address: String,
+    /// Latest state countersigned by the operator
+    pub nonce: i64,
+    pub balance: i64,
+    /// Latest state signed by both parties; checkpoints and disputes submit this one
+    pub signed_nonce: i64,
+    pub signed_balance: i64,
+    pub checkpointed_nonce: i64,
+    pub checkpoint_tx_signature: Option<String>,
+    /// open, disputed or closed
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BalanceChannel {
+    pub const OPEN: &'static str = "open";
+    pub const DISPUTED: &'static str = "disputed";
+    pub const CLOSED: &'static str = "closed";
+}
+
+/// Balance change for one metering interval
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BalanceChannelUpdate {
+    pub channel_id: Uuid,
+    pub nonce: i64,
+    pub delta: i64,
+    /// Cumulative balance after this update, which is what both parties sign
+    pub balance: i64,
+    pub interval_start: DateTime<Utc>,
+    pub interval_end: DateTime<Utc>,
+    /// Base58 ed25519 signatures over the channel state message
+    pub operator_signature: String,
+    pub participant_signature: Option<String>,
+    pub signed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Campus figures aggregated from the database by the dashboard refresher
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DashboardAggregates {
+    /// kWh generated across all meters over the live window
+    pub generation_kwh: f64,
+    /// kWh consumed across all meters over the live window
+    pub consumption_kwh: f64,
+    pub battery: BatteryState,
+    /// Price of the most recently filled order
+    pub last_clearing_price: Option<f64>,
+    pub last_cleared_at: Option<DateTime<Utc>>,
+    /// Certificates issued since midnight UTC
+    pub ercs_issued_today: i64,
+    pub erc_energy_issued_today: i64,
+}
+
+/// Battery flows over the live window, from meters reporting `device_type: "battery"`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatteryState {
+    pub meters: i64,
+    pub charged_kwh: f64,
+    pub discharged_kwh: f64,
+    /// charging, discharging or idle
+    pub mode: String,
+}
+
+/// Lobby display payload; assembled from in-memory state only
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveDashboard {
+    #[serde(flatten)]
+    pub aggregates: DashboardAggregates,
+    pub active_buy_orders: usize,
+    pub active_sell_orders: usize,
+    /// When the aggregates were last refreshed; `None` before the first refresh
+    pub refreshed_at: Option<DateTime<Utc>>,
+    /// Set when the aggregates are older than the configured staleness limit
+    pub stale: bool,
+    pub generated_at: DateTime<Utc>,
+}
@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Prosumer dispute over a meter reading or an ERC certificate
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Dispute {
+    pub id: Uuid,
+    /// reading or certificate
+    pub subject_type: String,
+    /// Reading UUID or certificate ID
+    pub subject_id: String,
+    pub opened_by: Uuid,
+    pub reason: String,
+    /// open, upheld or dismissed
+    pub status: String,
+    pub resolution: Option<String>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Job challenging a disputed certificate on-chain
+    pub challenge_job_id: Option<Uuid>,
+    /// Job resolving the certificate's challenge; `None` if the challenge never landed
+    pub resolution_job_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Dispute {
+    pub const READING: &'static str = "reading";
+    pub const CERTIFICATE: &'static str = "certificate";
+
+    pub const OPEN: &'static str = "open";
+    pub const UPHELD: &'static str = "upheld";
+    pub const DISMISSED: &'static str = "dismissed";
+}
+
+/// Evidence attached to a dispute
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DisputeEvidence {
+    pub id: Uuid,
+    pub dispute_id: Uuid,
+    pub submitted_by: Uuid,
+    pub description: String,
+    /// Where the supporting document can be fetched
+    pub url: Option<String>,
+    /// Hex SHA-256 of the document, pinning what was submitted
+    pub sha256: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Dispute with its evidence, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisputeDetail {
+    #[serde(flatten)]
+    pub dispute: Dispute,
+    pub evidence: Vec<DisputeEvidence>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenDisputeRequest {
+    /// reading or certificate
+    pub subject_type: String,
+    pub subject_id: String,
+    pub reason: String,
+    /// Evidence to attach straight away
+    #[serde(default)]
+    pub evidence: Vec<EvidenceRequest>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvidenceRequest {
+    pub description: String,
+    pub url: Option<String>,
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResolveDisputeRequest {
+    /// Whether the prosumer was right; an upheld certificate dispute revokes the certificate
+    pub upheld: bool,
+    pub resolution: String,
+}
@@ -1,7 +1,11 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::types::BigDecimal;
+use utoipa::ToSchema;
 use uuid::Uuid;
+use validator::{Validate, ValidationErrors};
+
+use crate::utils::validation::{self, ValidateRequest, MAX_SIGNATURE_LEN};
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct EnergyReading {
@@ -48,21 +52,165 @@ impl From<EnergyReadingDb> for EnergyReading {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct EnergyReadingSubmission {
+    #[validate(custom(function = "validation::meter_id"))]
     pub meter_id: String,
+    /// At most a week old and no more than five minutes ahead, as the oracle program accepts
     pub timestamp: DateTime<Utc>,
+    /// kWh
+    #[validate(custom(function = "validation::reading_kwh"))]
     pub energy_generated: f64,
+    /// kWh
+    #[validate(custom(function = "validation::reading_kwh"))]
     pub energy_consumed: f64,
+    /// W/m²
+    #[validate(range(min = 0.0, max = 2000.0))]
     pub solar_irradiance: Option<f64>,
+    /// °C
+    #[validate(range(min = -50.0, max = 100.0))]
     pub temperature: Option<f64>,
+    #[validate(length(min = 1, max = MAX_SIGNATURE_LEN))]
     pub engineering_authority_signature: String,
+    #[validate(nested)]
     pub metadata: Option<EnergyMetadata>,
+    /// Base58 ed25519 signature over `meter_keys::reading_message` by the meter's registered key
+    #[serde(default)]
+    #[validate(length(max = MAX_SIGNATURE_LEN))]
+    pub meter_signature: Option<String>,
+}
+
+impl ValidateRequest for EnergyReadingSubmission {
+    fn validate_at(&self, now: DateTime<Utc>) -> Result<(), ValidationErrors> {
+        validation::reading_time(self.timestamp, now).map_err(|e| validation::field_error("timestamp", e))
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct EnergyMetadata {
+    #[validate(custom(function = "validation::location"))]
     pub location: String,
+    #[validate(length(max = 50))]
     pub device_type: String,
+    #[validate(length(max = 100))]
     pub weather_conditions: Option<String>,
-}
\ No newline at end of file
+}
+/// Per-meter energy over one time bucket, from the TimescaleDB rollups
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct EnergyPoint {
+    pub bucket: DateTime<Utc>,
+    pub energy_generated: f64,
+    pub energy_consumed: f64,
+    pub reading_count: i64,
+}
+
+/// Net generation of one meter over a window, from the TimescaleDB hourly rollup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MeterGeneration {
+    pub meter_id: String,
+    /// Sum of each hour's generation less its consumption, ignoring hours of net consumption
+    pub net_kwh: f64,
+    pub reading_count: i64,
+}
+
+/// Expected energy of a meter over one hour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForecastPoint {
+    pub hour: DateTime<Utc>,
+    pub energy_generated: f64,
+    pub energy_consumed: f64,
+}
+
+/// Hourly forecast of a meter's generation and consumption
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyForecast {
+    pub meter_id: String,
+    /// seasonal_naive or exponential_smoothing
+    pub method: String,
+    pub generated_at: DateTime<Utc>,
+    /// Hourly buckets of history the forecast was fitted to
+    pub history_hours: usize,
+    pub points: Vec<ForecastPoint>,
+}
+
+/// Reading that failed validation, held until an operator approves or rejects it
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct QuarantinedReading {
+    pub id: Uuid,
+    pub meter_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub energy_generated: f64,
+    pub energy_consumed: f64,
+    pub solar_irradiance: Option<f64>,
+    pub temperature: Option<f64>,
+    pub metadata: Option<serde_json::Value>,
+    pub meter_signature: Option<String>,
+    /// Checks the reading failed, e.g. future_timestamp or stuck_value
+    pub reasons: Vec<String>,
+    /// pending, approved or rejected
+    pub status: String,
+    /// Stored reading, once approved
+    pub reading_id: Option<Uuid>,
+    pub reviewed_by: Option<Uuid>,
+    pub review_note: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl QuarantinedReading {
+    pub const PENDING: &'static str = "pending";
+    pub const APPROVED: &'static str = "approved";
+    pub const REJECTED: &'static str = "rejected";
+}
+
+/// Ed25519 key a meter signs its pushed readings with
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MeterSigningKey {
+    pub meter_id: String,
+    /// Base58
+    pub public_key: String,
+    pub registered_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Meter provisioned through the gateway, with its screening and on-chain registration state
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Meter {
+    pub meter_id: String,
+    pub user_id: Uuid,
+    /// solar, wind, battery or grid
+    pub meter_type: String,
+    pub building: String,
+    pub zone: String,
+    /// Rated inverter output generation is screened against
+    pub capacity_kw: Option<f64>,
+    pub sampling_interval_seconds: i32,
+    /// active or decommissioned
+    pub status: String,
+    /// Base58 key readings must be signed with; removed on decommissioning
+    pub public_key: Option<String>,
+    /// Latest registry program transaction job
+    pub registry_job_id: Option<Uuid>,
+    /// queued, submitted, confirmed or failed
+    pub registry_status: Option<String>,
+    pub last_reading_at: Option<DateTime<Utc>>,
+    pub registered_by: Option<Uuid>,
+    pub decommissioned_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Meter {
+    pub const ACTIVE: &'static str = "active";
+    pub const DECOMMISSIONED: &'static str = "decommissioned";
+    pub const TYPES: [&'static str; 4] = ["solar", "wind", "battery", "grid"];
+}
+
+/// Newly issued meter signing credentials; the secret key cannot be retrieved again
+#[derive(Debug, Clone, Serialize)]
+pub struct MeterCredentials {
+    /// Base58 ed25519 seed to install on the meter
+    pub secret_key: String,
+    pub meter: Meter,
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database::schema::types::ErcStatus;
+
+/// ERC certificate as indexed from the governance program
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ErcCertificate {
+    pub certificate_id: String,
+    pub account_address: String,
+    pub authority: String,
+    pub energy_amount: i64,
+    pub renewable_source: String,
+    pub validation_data: String,
+    pub status: ErcStatus,
+    pub validated_for_trading: bool,
+    pub source_readings: serde_json::Value,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub trading_validated_at: Option<DateTime<Utc>>,
+    pub slot: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Certificate lifecycle event from `GET /api/v1/erc/stream`
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ErcEvent {
+    /// Increasing ID, sent as the server-sent event ID to resume from
+    pub id: i64,
+    pub certificate_id: String,
+    /// `issued`, `validated`, `revoked` or `expired`
+    pub event_type: String,
+    /// Certificate status after the event
+    pub status: ErcStatus,
+    pub authority: String,
+    pub energy_amount: i64,
+    /// Slot of the on-chain change; absent for expiries derived from `expires_at`
+    pub slot: Option<i64>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl ErcEvent {
+    pub const ISSUED: &'static str = "issued";
+    pub const VALIDATED: &'static str = "validated";
+    pub const REVOKED: &'static str = "revoked";
+    pub const EXPIRED: &'static str = "expired";
+    pub const CHALLENGED: &'static str = "challenged";
+}
+
+/// Certificate in a listing, with its status as of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErcListing {
+    #[serde(flatten)]
+    pub certificate: ErcCertificate,
+    /// `status`, with valid certificates past `expires_at` reported as expired
+    pub effective_status: ErcStatus,
+}
+
+/// Page of certificates from `GET /api/v1/erc`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErcPage {
+    pub certificates: Vec<ErcListing>,
+    /// Cursor of the next page, passed back as `page`; absent on the last page
+    pub next_page: Option<String>,
+    /// Read from the indexer database rather than decoded from the governance program's accounts
+    pub indexed: bool,
+}
+
+/// Governance transaction submitted for a certificate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErcTransaction {
+    pub certificate_id: String,
+    /// Certificate PDA in the governance program
+    pub certificate_address: String,
+    pub signature: String,
+    /// Certificate token mint, set once the certificate is validated for trading
+    pub nft_mint: Option<String>,
+}
+
+/// Certificate the gateway issued on its own once a prosumer's net generation crossed
+/// `ERC_AUTO_ISSUANCE_THRESHOLD_KWH`
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ErcAutoIssuance {
+    pub certificate_id: String,
+    pub user_id: Uuid,
+    pub renewable_source: String,
+    /// Certified whole kWh; the fraction left over is carried into the next certificate
+    pub energy_amount: i64,
+    /// Hours of readings behind the certificate, `[generated_from, generated_to)`
+    pub generated_from: DateTime<Utc>,
+    pub generated_to: DateTime<Utc>,
+    /// Net generation over the window plus the fraction carried from the last certificate
+    pub net_generation_kwh: f64,
+    /// Net kWh and reading count of each meter in the window
+    pub provenance: serde_json::Value,
+    /// Queued `issue_erc` transaction, polled at `GET /api/v1/tx/:job_id`
+    pub tx_job_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Verification link printed as a QR code on a certificate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErcVerificationLink {
+    pub certificate_id: String,
+    pub url: String,
+    /// Base58 ed25519 signature by the gateway's certificate signing key
+    pub signature: String,
+}
+
+/// Result of verifying a printed certificate against the chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErcVerification {
+    pub certificate_id: String,
+    /// The link signature is genuine and the certificate is currently valid on-chain
+    pub verified: bool,
+    pub signature_valid: bool,
+    /// On-chain status, with lapsed certificates reported as expired; `None` when not checked
+    pub status: Option<String>,
+    pub renewable_source: Option<String>,
+    pub energy_amount: Option<u64>,
+    pub issued_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub account_address: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Governance PoA configuration as indexed from the governance program
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct GovernanceConfig {
+    pub account_address: String,
+    pub authority: String,
+    pub authority_name: String,
+    pub contact_email: String,
+    pub contact_phone: String,
+    pub website: String,
+    pub office_location: String,
+    pub emergency_paused: bool,
+    /// Pause bitmask: 1 = issuance, 2 = validation, 4 = config updates
+    pub pause_flags: i16,
+    pub maintenance_mode: bool,
+    pub erc_validation_enabled: bool,
+    pub oracle_authority: Option<String>,
+    pub min_energy_amount: i64,
+    pub max_erc_amount: i64,
+    pub erc_validity_period: i64,
+    /// Lamports charged per ERC issuance
+    pub erc_issuance_fee: i64,
+    /// Whether configuration changes, pauses, revocations and treasury withdrawals require council approval
+    pub council_mode: bool,
+    /// Seconds after which an emergency pause lapses; 0 never lapses
+    pub max_pause_duration: i64,
+    /// Program allowed to lock certificates for trades
+    pub trading_program: Option<String>,
+    /// Length of the issuance rate-limit window in seconds
+    pub issuance_window: i64,
+    /// Certificates that may be issued per trailing window; 0 is unlimited
+    pub max_window_ercs: i64,
+    /// kWh that may be certified per trailing window; 0 is unlimited
+    pub max_window_energy: i64,
+    pub total_ercs_issued: i64,
+    pub total_ercs_validated: i64,
+    pub slot: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Governance instruction submitted through the admin API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernanceTransaction {
+    /// Audit record of the submission
+    pub audit_id: Uuid,
+    /// Governance program instruction name
+    pub action: String,
+    pub signature: String,
+}
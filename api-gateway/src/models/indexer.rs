@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Checkpointed backfill of an address's transaction history
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct IndexerBackfill {
+    pub id: Uuid,
+    pub address: String,
+    /// Oldest signature stored so far; the backfill resumes before it
+    pub cursor_signature: Option<String>,
+    /// Signature at which the backfill stops
+    pub until_signature: Option<String>,
+    /// running, completed, failed or cancelled
+    pub status: String,
+    pub signatures_processed: i64,
+    pub transactions_stored: i64,
+    pub newest_slot: Option<i64>,
+    pub oldest_slot: Option<i64>,
+    pub error_message: Option<String>,
+    pub started_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl IndexerBackfill {
+    pub const RUNNING: &'static str = "running";
+    pub const COMPLETED: &'static str = "completed";
+    pub const FAILED: &'static str = "failed";
+    pub const CANCELLED: &'static str = "cancelled";
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexerStatus {
+    pub indexed_transactions: i64,
+    pub running_backfills: i64,
+    /// Most recent backfills, newest first
+    pub backfills: Vec<IndexerBackfill>,
+}
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// One attempt to trigger the oracle's market clearing
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MarketClearingRun {
+    pub id: Uuid,
+    /// scheduled or manual
+    pub trigger: String,
+    /// Epoch boundary the run was scheduled for, or when a manual run was requested
+    pub scheduled_for: DateTime<Utc>,
+    /// running, submitted, skipped or failed
+    pub status: String,
+    pub signature: Option<String>,
+    /// Why the run failed or was skipped
+    pub error: Option<String>,
+    pub triggered_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl MarketClearingRun {
+    pub const SCHEDULED: &'static str = "scheduled";
+    pub const MANUAL: &'static str = "manual";
+
+    pub const RUNNING: &'static str = "running";
+    pub const SUBMITTED: &'static str = "submitted";
+    pub const SKIPPED: &'static str = "skipped";
+    pub const FAILED: &'static str = "failed";
+}
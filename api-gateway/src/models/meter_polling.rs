@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Schedule and connection details of a meter the gateway polls
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MeterPollingConfig {
+    pub meter_id: String,
+    /// modbus_tcp or dlms_cosem
+    pub protocol: String,
+    pub host: String,
+    pub port: i32,
+    /// Modbus unit id or DLMS server address
+    pub unit_id: i32,
+    /// Register address (Modbus) or OBIS code (DLMS) of the cumulative exported energy
+    pub produced_register: String,
+    /// Register address (Modbus) or OBIS code (DLMS) of the cumulative imported energy
+    pub consumed_register: String,
+    /// kWh per Modbus register count; DLMS registers carry their own scaler and unit
+    pub register_scale: f64,
+    pub poll_interval_seconds: i32,
+    pub enabled: bool,
+    pub last_produced_kwh: Option<f64>,
+    pub last_consumed_kwh: Option<f64>,
+    pub last_polled_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub next_poll_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MeterPollingConfig {
+    pub const MODBUS_TCP: &'static str = "modbus_tcp";
+    pub const DLMS_COSEM: &'static str = "dlms_cosem";
+}
@@ -4,4 +4,23 @@
 pub mod user;
 pub mod energy;
 pub mod trading;
-pub mod blockchain;
\ No newline at end of file
+pub mod blockchain;
+pub mod erc;
+pub mod governance;
+pub mod signing;
+pub mod indexer;
+pub mod report;
+pub mod channel;
+pub mod dashboard;
+pub mod role;
+pub mod audit;
+pub mod tx_job;
+pub mod meter_polling;
+pub mod notification;
+pub mod webhook;
+pub mod market_clearing;
+pub mod billing;
+pub mod carbon;
+pub mod pricing;
+pub mod dispute;
+pub mod tenant;
\ No newline at end of file
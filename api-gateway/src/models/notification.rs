@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Channel a user receives alerts on, and which alerts
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct NotificationPreference {
+    pub user_id: Uuid,
+    /// email or line
+    pub channel: String,
+    pub enabled: bool,
+    /// Email address overriding the account's; LINE Notify token, returned masked
+    pub destination: Option<String>,
+    /// order_filled, certificate_issued, meter_offline and/or settlement_ready
+    pub event_types: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NotificationPreference {
+    pub const EMAIL: &'static str = "email";
+    pub const LINE: &'static str = "line";
+    pub const CHANNELS: [&'static str; 2] = [Self::EMAIL, Self::LINE];
+}
+
+/// Alert queued for, or sent to, one channel of a user
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub event_type: String,
+    /// Identifies the event the alert is about
+    pub event_key: String,
+    pub channel: String,
+    pub subject: String,
+    pub body: String,
+    /// pending, sent or failed
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Notification {
+    pub const PENDING: &'static str = "pending";
+    pub const SENT: &'static str = "sent";
+    pub const FAILED: &'static str = "failed";
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationPreferenceRequest {
+    /// email or line
+    pub channel: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Email address, or the LINE Notify token required for LINE; an email row without one uses the account's address
+    pub destination: Option<String>,
+    /// Defaults to every event type
+    pub event_types: Option<Vec<String>>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
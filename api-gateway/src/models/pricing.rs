@@ -0,0 +1,162 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::types::BigDecimal;
+use uuid::Uuid;
+
+use crate::utils::decimal::decimal;
+
+/// Time-of-use period of the utility tariff, bounding P2P prices while it is in force
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TouPeriod {
+    pub id: String,
+    pub name: String,
+    /// ISO weekdays, 1 = Monday .. 7 = Sunday
+    pub weekdays: Vec<i16>,
+    /// Campus local time
+    pub start_time: NaiveTime,
+    /// At or before `start_time` the period runs past midnight; equal is the whole day
+    pub end_time: NaiveTime,
+    pub applies_on_holidays: bool,
+    /// What the grid charges, THB per kWh
+    pub grid_rate: Decimal,
+    pub price_floor: Option<Decimal>,
+    pub price_ceiling: Option<Decimal>,
+    /// The highest priority wins where periods overlap
+    pub priority: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Internal database model with BigDecimal for database operations
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TouPeriodDb {
+    pub id: String,
+    pub name: String,
+    pub weekdays: Vec<i16>,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub applies_on_holidays: bool,
+    pub grid_rate: BigDecimal,
+    pub price_floor: Option<BigDecimal>,
+    pub price_ceiling: Option<BigDecimal>,
+    pub priority: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<TouPeriodDb> for TouPeriod {
+    fn from(db_period: TouPeriodDb) -> Self {
+        Self {
+            id: db_period.id,
+            name: db_period.name,
+            weekdays: db_period.weekdays,
+            start_time: db_period.start_time,
+            end_time: db_period.end_time,
+            applies_on_holidays: db_period.applies_on_holidays,
+            grid_rate: decimal(&db_period.grid_rate),
+            price_floor: db_period.price_floor.as_ref().map(decimal),
+            price_ceiling: db_period.price_ceiling.as_ref().map(decimal),
+            priority: db_period.priority,
+            updated_at: db_period.updated_at,
+        }
+    }
+}
+
+/// Public holiday observed as off-peak
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TouHoliday {
+    pub day: NaiveDate,
+    pub name: String,
+}
+
+/// Period in force at a time and when it ends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentPrice {
+    pub at: DateTime<Utc>,
+    pub is_holiday: bool,
+    pub period: TouPeriod,
+    /// `None` when no other period takes over within a week
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+/// Time-of-use period as an operator sets it
+#[derive(Debug, Clone, Deserialize)]
+pub struct TouPeriodRequest {
+    pub name: String,
+    pub weekdays: Vec<i16>,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    #[serde(default)]
+    pub applies_on_holidays: bool,
+    pub grid_rate: Decimal,
+    pub price_floor: Option<Decimal>,
+    pub price_ceiling: Option<Decimal>,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+/// Official grid tariff from one signed snapshot of the price oracle's source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridPrice {
+    /// When the tariff took effect
+    pub effective_at: DateTime<Utc>,
+    pub tariff: String,
+    pub price_per_kwh: Decimal,
+    pub currency: String,
+    pub published_at: Option<DateTime<Utc>>,
+    /// SHA-256 hex of `payload`
+    pub snapshot_hash: String,
+    /// Signed snapshot as published, so the signature can be checked again
+    pub payload: String,
+    /// Base58 ed25519 signature over `payload`
+    pub signature: String,
+    pub public_key: String,
+    pub source_url: String,
+    /// Job publishing the price to the oracle program; `None` until it is queued
+    pub tx_job_id: Option<Uuid>,
+    pub ingested_at: DateTime<Utc>,
+}
+
+// Internal database model with BigDecimal for database operations
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct GridPriceDb {
+    pub time: DateTime<Utc>,
+    pub tariff: String,
+    pub price_per_kwh: BigDecimal,
+    pub currency: String,
+    pub published_at: Option<DateTime<Utc>>,
+    pub snapshot_hash: String,
+    pub payload: String,
+    pub signature: String,
+    pub public_key: String,
+    pub source_url: String,
+    pub tx_job_id: Option<Uuid>,
+    pub ingested_at: DateTime<Utc>,
+}
+
+impl From<GridPriceDb> for GridPrice {
+    fn from(db_price: GridPriceDb) -> Self {
+        Self {
+            effective_at: db_price.time,
+            tariff: db_price.tariff,
+            price_per_kwh: decimal(&db_price.price_per_kwh),
+            currency: db_price.currency,
+            published_at: db_price.published_at,
+            snapshot_hash: db_price.snapshot_hash,
+            payload: db_price.payload,
+            signature: db_price.signature,
+            public_key: db_price.public_key,
+            source_url: db_price.source_url,
+            tx_job_id: db_price.tx_job_id,
+            ingested_at: db_price.ingested_at,
+        }
+    }
+}
+
+/// Outcome of one fetch of the grid tariff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridPriceIngestion {
+    /// Whether the fetched snapshot was new
+    pub ingested: bool,
+    /// Latest grid price after the fetch
+    pub latest: GridPrice,
+}
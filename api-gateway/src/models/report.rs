@@ -0,0 +1,106 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+/// Archived nightly reconciliation report; the rendered HTML is served separately
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ReconciliationReport {
+    pub id: Uuid,
+    pub report_date: NaiveDate,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// generating, ok, attention or failed
+    pub status: String,
+    pub summary: Option<Json<ReconciliationSummary>>,
+    pub recipients: Json<Vec<String>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ReconciliationReport {
+    pub const GENERATING: &'static str = "generating";
+    pub const OK: &'static str = "ok";
+    pub const ATTENTION: &'static str = "attention";
+    pub const FAILED: &'static str = "failed";
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationSummary {
+    pub chain: ChainReconciliation,
+    pub energy: EnergyBalance,
+    pub queues: QueueHealth,
+    pub fees: FeeSpend,
+}
+
+impl ReconciliationSummary {
+    /// Whether anything in the report needs a human to look at it
+    pub fn needs_attention(&self) -> bool {
+        !self.chain.mismatches.is_empty()
+            || self.chain.rpc_error.is_some()
+            || self.energy.invalid_readings > 0
+            || !self.energy.silent_meters.is_empty()
+            || self.queues.stale_pending_transactions > 0
+            || self.queues.stuck_signing_sessions > 0
+            || self.queues.order_book_diverged
+    }
+}
+
+/// Transactions recorded in the database compared with their on-chain status
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChainReconciliation {
+    pub checked: i64,
+    pub matched: i64,
+    pub mismatches: Vec<TransactionMismatch>,
+    /// Set when the RPC node could not be queried
+    pub rpc_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionMismatch {
+    pub signature: String,
+    pub db_status: String,
+    /// `processed`, `confirmed`, `finalized`, `failed` or `not_found`
+    pub chain_status: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnergyBalance {
+    pub readings: i64,
+    pub total_generated: f64,
+    pub total_consumed: f64,
+    pub net_energy: f64,
+    /// Readings with negative generation or consumption
+    pub invalid_readings: i64,
+    /// Actively assigned meters that reported nothing during the period
+    pub silent_meters: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueHealth {
+    /// Transactions still pending an hour after submission
+    pub stale_pending_transactions: i64,
+    pub collecting_signing_sessions: i64,
+    /// Sessions stuck between claiming and recording a submission
+    pub stuck_signing_sessions: i64,
+    pub failed_signing_sessions: i64,
+    pub open_orders: i64,
+    /// Whether the in-memory order book had diverged from the database
+    pub order_book_diverged: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeeSpend {
+    pub transactions: i64,
+    pub total_lamports: i64,
+    pub by_program: Vec<ProgramFeeSpend>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ProgramFeeSpend {
+    pub program_id: String,
+    pub transactions: i64,
+    pub lamports: i64,
+}
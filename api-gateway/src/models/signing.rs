@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+/// Multi-party signing session for a gateway-built transaction
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SigningSession {
+    pub id: Uuid,
+    pub created_by: Uuid,
+    pub description: Option<String>,
+    /// Base64 serialized message every party signs
+    pub message: String,
+    pub required_signers: Json<Vec<String>>,
+    pub signatures: Json<HashMap<String, String>>,
+    pub recent_blockhash: String,
+    pub last_valid_block_height: Option<i64>,
+    pub nonce_account: Option<String>,
+    pub status: String,
+    pub tx_signature: Option<String>,
+    pub error_message: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SigningSession {
+    pub const COLLECTING: &'static str = "collecting";
+    pub const SUBMITTING: &'static str = "submitting";
+    pub const SUBMITTED: &'static str = "submitted";
+    pub const FAILED: &'static str = "failed";
+    pub const EXPIRED: &'static str = "expired";
+
+    /// Required signers that have not signed yet
+    pub fn missing_signers(&self) -> Vec<String> {
+        self.required_signers
+            .iter()
+            .filter(|signer| !self.signatures.contains_key(*signer))
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Building or faculty whose users and meters share a data scope and a request budget
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Tenant {
+    pub id: String,
+    pub name: String,
+    /// faculty or building
+    pub kind: String,
+    /// Requests per minute shared by the tenant's users; `None` uses the gateway default
+    pub requests_per_minute: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Tenant {
+    pub const FACULTY: &'static str = "faculty";
+    pub const BUILDING: &'static str = "building";
+    pub const KINDS: [&'static str; 2] = [Self::FACULTY, Self::BUILDING];
+}
+
+/// What a tenant holds and how busy it is
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TenantUsage {
+    pub tenant_id: String,
+    pub users: i64,
+    pub meters: i64,
+    /// Readings recorded by the tenant's meters in the last 24 hours
+    pub readings_24h: i64,
+    /// Pending and active orders of the tenant's users
+    pub open_orders: i64,
+    pub certificates: i64,
+    /// Requests counted against the tenant's limit in the current minute
+    #[sqlx(default)]
+    pub requests_this_minute: i64,
+    /// Limit in force, the tenant's own or the gateway default
+    #[sqlx(default)]
+    pub requests_per_minute: i32,
+}
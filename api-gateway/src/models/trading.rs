@@ -1,10 +1,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::types::BigDecimal;
+use utoipa::ToSchema;
 use uuid::Uuid;
+use validator::{Validate, ValidationErrors};
 use crate::database::schema::types::{OrderType, OrderSide, OrderStatus};
+use crate::utils::decimal::decimal;
+use crate::utils::validation::{self, ValidateRequest, MAX_SIGNATURE_LEN};
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct TradingOrder {
     pub id: Uuid,
     pub user_id: Uuid,
@@ -13,6 +17,7 @@ pub struct TradingOrder {
     pub energy_amount: rust_decimal::Decimal,
     pub price_per_kwh: rust_decimal::Decimal,
     pub filled_amount: rust_decimal::Decimal,
+    #[schema(value_type = String, example = "pending")]
     pub status: OrderStatus,
     pub expires_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
@@ -37,16 +42,14 @@ pub struct TradingOrderDb {
 
 impl From<TradingOrderDb> for TradingOrder {
     fn from(db_order: TradingOrderDb) -> Self {
-        use std::str::FromStr;
-        
         TradingOrder {
             id: db_order.id,
             user_id: db_order.user_id,
             order_type: db_order.order_type,
             side: db_order.side,
-            energy_amount: rust_decimal::Decimal::from_str(&db_order.energy_amount.to_string()).unwrap_or_default(),
-            price_per_kwh: rust_decimal::Decimal::from_str(&db_order.price_per_kwh.to_string()).unwrap_or_default(),
-            filled_amount: rust_decimal::Decimal::from_str(&db_order.filled_amount.to_string()).unwrap_or_default(),
+            energy_amount: decimal(&db_order.energy_amount),
+            price_per_kwh: decimal(&db_order.price_per_kwh),
+            filled_amount: decimal(&db_order.filled_amount),
             status: db_order.status,
             expires_at: db_order.expires_at,
             created_at: db_order.created_at,
@@ -55,20 +58,62 @@ impl From<TradingOrderDb> for TradingOrder {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct CreateOrderRequest {
+    #[validate(custom(function = "validation::order_energy"))]
     pub energy_amount: rust_decimal::Decimal,
+    #[validate(custom(function = "validation::order_price"))]
     pub price_per_kwh: rust_decimal::Decimal,
     pub order_type: OrderType,
     pub expiry_time: Option<DateTime<Utc>>,
 }
 
+impl ValidateRequest for CreateOrderRequest {
+    fn validate_at(&self, now: DateTime<Utc>) -> Result<(), ValidationErrors> {
+        self.expiry_time
+            .map_or(Ok(()), |expiry_time| validation::future_time(expiry_time, now))
+            .map_err(|e| validation::field_error("expiry_time", e))
+    }
+}
+
+/// Order placed through `/orders`, matched off-chain and settled by the trading program
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct PlaceOrderRequest {
+    /// Chosen by the client, since the commitment it signs names the order
+    pub order_id: Uuid,
+    pub side: OrderSide,
+    /// kWh, at most `MAX_ORDER_ENERGY_KWH`
+    #[validate(custom(function = "validation::order_energy"))]
+    pub energy_amount: rust_decimal::Decimal,
+    #[validate(custom(function = "validation::order_price"))]
+    pub price_per_kwh: rust_decimal::Decimal,
+    pub order_type: OrderType,
+    /// Defaults to one day after the order is placed
+    pub expiry_time: Option<DateTime<Utc>>,
+    /// Base58 ed25519 signature by the user's wallet over the trading program's
+    /// `order_commitment_message` for this order. Its quantity is in energy token base units,
+    /// rounded down, and its price in payment token base units per kWh, rounded up for a buy
+    /// and down for a sell. The trading program settles the order only within it.
+    #[validate(length(min = 1, max = MAX_SIGNATURE_LEN))]
+    pub commitment_signature: String,
+}
+
+impl ValidateRequest for PlaceOrderRequest {
+    fn validate_at(&self, now: DateTime<Utc>) -> Result<(), ValidationErrors> {
+        self.expiry_time
+            .map_or(Ok(()), |expiry_time| validation::future_time(expiry_time, now))
+            .map_err(|e| validation::field_error("expiry_time", e))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MarketData {
     pub current_epoch: u64,
     pub epoch_start_time: DateTime<Utc>,
     pub epoch_end_time: DateTime<Utc>,
     pub status: String,
+    /// Order book mirror version the order book was taken from
+    pub order_book_version: u64,
     pub order_book: OrderBook,
     pub recent_trades: Vec<TradeExecution>,
 }
@@ -88,4 +133,17 @@ pub struct TradeExecution {
     pub price_per_kwh: rust_decimal::Decimal,
     pub total_price: rust_decimal::Decimal,
     pub executed_at: DateTime<Utc>,
-}
\ No newline at end of file
+}
+
+/// Off-chain matched trade between two registered wallets, in token base units
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettlementTrade {
+    /// Orders whose on-chain commitments the trade counts against
+    pub buy_order: Uuid,
+    pub sell_order: Uuid,
+    pub buyer: String,
+    pub seller: String,
+    pub energy_amount: u64,
+    /// Paid by the buyer, market fee included
+    pub payment_amount: u64,
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Chain operation queued for the background transaction worker
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, utoipa::ToSchema)]
+pub struct TxJob {
+    pub id: Uuid,
+    /// issue_erc or validate_erc
+    pub operation: String,
+    #[schema(value_type = Object)]
+    pub payload: Value,
+    /// queued, submitted, confirmed or failed
+    pub status: String,
+    pub attempts: i32,
+    /// Signature of the latest submission
+    pub signature: Option<String>,
+    /// Operation result once submitted, e.g. the certificate address
+    #[schema(value_type = Option<Object>)]
+    pub result: Option<Value>,
+    pub error_code: Option<String>,
+    pub error_message: Option<String>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TxJob {
+    pub const QUEUED: &'static str = "queued";
+    pub const SUBMITTED: &'static str = "submitted";
+    pub const CONFIRMED: &'static str = "confirmed";
+    pub const FAILED: &'static str = "failed";
+}
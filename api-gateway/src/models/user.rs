@@ -35,4 +35,25 @@ pub struct UserProfile {
 pub struct UserBalances {
     pub grid_tokens: rust_decimal::Decimal,
     pub pending_trades: rust_decimal::Decimal,
-}
\ No newline at end of file
+}
+/// Prosumer account with the wallet its trades settle to and the meters assigned to it
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ProsumerProfile {
+    pub id: Uuid,
+    pub username: String,
+    pub email: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    /// Student or staff ID
+    pub university_id: Option<String>,
+    /// student, faculty or admin
+    pub role: String,
+    pub department: String,
+    pub wallet_address: Option<String>,
+    pub blockchain_registered: bool,
+    pub is_active: bool,
+    /// Meters actively assigned to the prosumer
+    pub meters: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Integrator endpoint notified of the events it subscribed to
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    pub url: String,
+    /// erc_issued, market_cleared and/or reading_anomaly
+    pub event_types: Vec<String>,
+    /// HMAC-SHA256 key deliveries are signed with; only returned when the subscription is created
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub description: Option<String>,
+    pub active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WebhookSubscription {
+    pub const ERC_ISSUED: &'static str = "erc_issued";
+    pub const MARKET_CLEARED: &'static str = "market_cleared";
+    pub const READING_ANOMALY: &'static str = "reading_anomaly";
+    pub const EVENT_TYPES: [&'static str; 3] = [Self::ERC_ISSUED, Self::MARKET_CLEARED, Self::READING_ANOMALY];
+}
+
+/// One event sent, or still to be sent, to a subscription
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub event_type: String,
+    pub payload: Value,
+    /// pending, delivered or dead_lettered
+    pub status: String,
+    pub attempts: i32,
+    /// HTTP status the endpoint answered the latest attempt with
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WebhookDelivery {
+    pub const PENDING: &'static str = "pending";
+    pub const DELIVERED: &'static str = "delivered";
+    pub const DEAD_LETTERED: &'static str = "dead_lettered";
+}
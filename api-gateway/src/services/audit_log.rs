@@ -0,0 +1,311 @@
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::models::audit::{AuditRecord, AuditVerification};
+use crate::AppState;
+
+/// Previous hash of the first entry in the chain
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Advisory lock serialising appends so concurrent writers cannot fork the chain
+const CHAIN_LOCK_ID: i64 = 0x6175_6469_745f_6c6f;
+
+/// Entries re-hashed per query while verifying
+const VERIFY_PAGE_SIZE: i64 = 1_000;
+
+const RECORD_COLUMNS: &str = "id, occurred_at, user_id, api_key_id, method, path, status, outcome, \
+    payload_hash, tx_signature, trace_id, prev_hash, entry_hash";
+
+/// Hex SHA-256 of `bytes`
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// State-changing request to append to the audit log
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub occurred_at: DateTime<Utc>,
+    pub user_id: Option<Uuid>,
+    pub api_key_id: Option<Uuid>,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub payload_hash: String,
+    pub tx_signature: Option<String>,
+    pub trace_id: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn outcome(&self) -> &'static str {
+        if self.status < 400 {
+            "success"
+        } else {
+            "failure"
+        }
+    }
+
+    /// Hash of this entry linked after `prev_hash`
+    ///
+    /// Timestamps are hashed at microsecond precision, which Postgres preserves. The trace id
+    /// is only hashed when present, so entries written before it was recorded still verify.
+    pub fn chain_hash(&self, prev_hash: &str) -> String {
+        let mut fields = json!([
+            prev_hash,
+            self.occurred_at.timestamp_micros(),
+            self.user_id,
+            self.api_key_id,
+            self.method,
+            self.path,
+            self.status,
+            self.outcome(),
+            self.payload_hash,
+            self.tx_signature,
+        ]);
+        if let (Some(trace_id), Some(fields)) = (&self.trace_id, fields.as_array_mut()) {
+            fields.push(json!(trace_id));
+        }
+        sha256_hex(fields.to_string().as_bytes())
+    }
+}
+
+impl From<&AuditRecord> for AuditEntry {
+    fn from(record: &AuditRecord) -> Self {
+        Self {
+            occurred_at: record.occurred_at,
+            user_id: record.user_id,
+            api_key_id: record.api_key_id,
+            method: record.method.clone(),
+            path: record.path.clone(),
+            status: record.status as u16,
+            payload_hash: record.payload_hash.clone(),
+            tx_signature: record.tx_signature.clone(),
+            trace_id: record.trace_id.clone(),
+        }
+    }
+}
+
+/// Check `records`, in id order, against the chain ending in `prev_hash`
+///
+/// Returns the hash the next page continues from, or the id of the first broken entry.
+fn verify_records(records: &[AuditRecord], mut prev_hash: String) -> std::result::Result<String, i64> {
+    for record in records {
+        let entry = AuditEntry::from(record);
+        if record.prev_hash != prev_hash
+            || entry.outcome() != record.outcome
+            || entry.chain_hash(&prev_hash) != record.entry_hash
+        {
+            return Err(record.id);
+        }
+        prev_hash = record.entry_hash.clone();
+    }
+    Ok(prev_hash)
+}
+
+/// Append-only, hash-chained log of state-changing requests in Postgres
+#[derive(Clone)]
+pub struct AuditLog {
+    db: sqlx::PgPool,
+}
+
+impl AuditLog {
+    pub fn new(db: sqlx::PgPool) -> Self {
+        Self { db }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone())
+    }
+
+    /// Link `entry` after the latest entry, returning its hash
+    #[tracing::instrument(name = "audit_append", skip_all)]
+    pub async fn append(&self, entry: &AuditEntry) -> Result<String> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(CHAIN_LOCK_ID)
+            .execute(&mut *tx)
+            .await?;
+
+        let prev_hash = sqlx::query_scalar!("SELECT entry_hash FROM audit_log ORDER BY id DESC LIMIT 1")
+            .fetch_optional(&mut *tx)
+            .await?
+            .unwrap_or_else(|| GENESIS_HASH.to_string());
+        let entry_hash = entry.chain_hash(&prev_hash);
+
+        sqlx::query!(
+            "INSERT INTO audit_log (occurred_at, user_id, api_key_id, method, path, status, outcome,
+                                    payload_hash, tx_signature, trace_id, prev_hash, entry_hash)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+            entry.occurred_at,
+            entry.user_id,
+            entry.api_key_id,
+            entry.method,
+            entry.path,
+            entry.status as i16,
+            entry.outcome(),
+            entry.payload_hash,
+            entry.tx_signature,
+            entry.trace_id,
+            prev_hash,
+            entry_hash,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(entry_hash)
+    }
+
+    /// Newest entries first, optionally only those older than `before_id`
+    pub async fn list(&self, limit: i64, before_id: Option<i64>) -> Result<Vec<AuditRecord>> {
+        let query = format!(
+            "SELECT {} FROM audit_log WHERE ($2::bigint IS NULL OR id < $2) ORDER BY id DESC LIMIT $1",
+            RECORD_COLUMNS
+        );
+        let records = sqlx::query_as::<_, AuditRecord>(&query)
+            .bind(limit)
+            .bind(before_id)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(records)
+    }
+
+    /// Re-hash the whole chain from the genesis hash
+    pub async fn verify(&self) -> Result<AuditVerification> {
+        let query = format!(
+            "SELECT {} FROM audit_log WHERE id > $1 ORDER BY id LIMIT $2",
+            RECORD_COLUMNS
+        );
+        let mut prev_hash = GENESIS_HASH.to_string();
+        let mut after_id = 0i64;
+        let mut entries_checked = 0u64;
+
+        loop {
+            let records = sqlx::query_as::<_, AuditRecord>(&query)
+                .bind(after_id)
+                .bind(VERIFY_PAGE_SIZE)
+                .fetch_all(&self.db)
+                .await?;
+            let Some(last) = records.last() else {
+                break;
+            };
+            after_id = last.id;
+
+            match verify_records(&records, prev_hash) {
+                Ok(hash) => {
+                    prev_hash = hash;
+                    entries_checked += records.len() as u64;
+                }
+                Err(id) => {
+                    entries_checked += records.iter().take_while(|r| r.id != id).count() as u64;
+                    return Ok(AuditVerification {
+                        entries_checked,
+                        first_invalid_id: Some(id),
+                    });
+                }
+            }
+        }
+
+        Ok(AuditVerification {
+            entries_checked,
+            first_invalid_id: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(entries: &[AuditEntry]) -> Vec<AuditRecord> {
+        let mut prev_hash = GENESIS_HASH.to_string();
+        entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let entry_hash = entry.chain_hash(&prev_hash);
+                AuditRecord {
+                    id: i as i64 + 1,
+                    occurred_at: entry.occurred_at,
+                    user_id: entry.user_id,
+                    api_key_id: entry.api_key_id,
+                    method: entry.method.clone(),
+                    path: entry.path.clone(),
+                    status: entry.status as i16,
+                    outcome: entry.outcome().to_string(),
+                    payload_hash: entry.payload_hash.clone(),
+                    tx_signature: entry.tx_signature.clone(),
+                    trace_id: entry.trace_id.clone(),
+                    prev_hash: std::mem::replace(&mut prev_hash, entry_hash.clone()),
+                    entry_hash,
+                }
+            })
+            .collect()
+    }
+
+    fn entry(path: &str, status: u16) -> AuditEntry {
+        AuditEntry {
+            occurred_at: DateTime::from_timestamp_micros(1_727_078_400_123_456).unwrap(),
+            user_id: Some(Uuid::nil()),
+            api_key_id: None,
+            method: "POST".to_string(),
+            path: path.to_string(),
+            status,
+            payload_hash: sha256_hex(b"{}"),
+            tx_signature: None,
+            trace_id: None,
+        }
+    }
+
+    #[test]
+    fn test_chain_verifies() {
+        let records = chain(&[entry("/erc", 201), entry("/trading/orders", 400), entry("/erc", 200)]);
+        assert_eq!(records[1].outcome, "failure");
+        assert_eq!(
+            verify_records(&records, GENESIS_HASH.to_string()),
+            Ok(records[2].entry_hash.clone())
+        );
+    }
+
+    #[test]
+    fn test_tampering_breaks_chain() {
+        let records = chain(&[entry("/erc", 201), entry("/trading/orders", 201), entry("/erc", 200)]);
+
+        let mut edited = records.clone();
+        edited[1].tx_signature = Some("forged".to_string());
+        assert_eq!(verify_records(&edited, GENESIS_HASH.to_string()), Err(2));
+
+        let mut removed = records.clone();
+        removed.remove(1);
+        assert_eq!(verify_records(&removed, GENESIS_HASH.to_string()), Err(3));
+
+        let mut relabelled = records.clone();
+        relabelled[0].outcome = "failure".to_string();
+        assert_eq!(verify_records(&relabelled, GENESIS_HASH.to_string()), Err(1));
+    }
+
+    #[test]
+    fn test_trace_id_is_hashed_when_present() {
+        let untraced = entry("/erc", 201);
+        let traced = AuditEntry {
+            trace_id: Some("4bf92f3577b34da6a3ce929d0e0e4736".to_string()),
+            ..untraced.clone()
+        };
+        assert_ne!(traced.chain_hash(GENESIS_HASH), untraced.chain_hash(GENESIS_HASH));
+
+        let records = chain(&[untraced, traced]);
+        let mut edited = records.clone();
+        edited[1].trace_id = None;
+        assert_eq!(verify_records(&edited, GENESIS_HASH.to_string()), Err(2));
+    }
+
+    #[test]
+    fn test_payload_hash() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use std::future::Future;
+use std::time::Duration;
+
+use futures::{stream, StreamExt, TryStreamExt};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{ApiError, Result};
+use crate::models::indexer::IndexerBackfill;
+use crate::services::blockchain::{BlockchainService, SignatureInfo};
+use crate::utils::backoff;
+use crate::AppState;
+
+/// Signatures requested per `getSignaturesForAddress` page (the RPC maximum)
+const PAGE_SIZE: usize = 1000;
+/// Rate-limited RPC calls are retried this many times before the backfill fails
+const MAX_RATE_LIMIT_RETRIES: i32 = 8;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+pub const BACKFILL_COLUMNS: &str = "id, address, cursor_signature, until_signature, status, signatures_processed, \
+    transactions_stored, newest_slot, oldest_slot, error_message, started_by, created_at, updated_at";
+
+/// Streams an address's history into `indexed_transactions` page by page
+///
+/// Each page is stored together with its cursor, so a backfill interrupted by a crash or
+/// restart resumes from the last committed page.
+#[derive(Clone)]
+pub struct BackfillService {
+    db: PgPool,
+    chain: BlockchainService,
+    concurrency: usize,
+}
+
+impl BackfillService {
+    pub fn new(db: PgPool, chain: BlockchainService, concurrency: usize) -> Self {
+        Self {
+            db,
+            chain,
+            concurrency: concurrency.max(1),
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(
+            state.db.clone(),
+            state.blockchain_service.clone(),
+            state.config.indexer_backfill_concurrency,
+        )
+    }
+
+    /// Record a new backfill and start streaming it in the background
+    pub async fn start(&self, address: &str, until: Option<&str>, started_by: Uuid) -> Result<IndexerBackfill> {
+        let query = format!(
+            "INSERT INTO indexer_backfills (address, until_signature, started_by) VALUES ($1, $2, $3)
+             ON CONFLICT (address) WHERE status = 'running' DO NOTHING
+             RETURNING {}",
+            BACKFILL_COLUMNS
+        );
+
+        let backfill = sqlx::query_as::<_, IndexerBackfill>(&query)
+            .bind(address)
+            .bind(until)
+            .bind(started_by)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::Conflict(format!("A backfill of {} is already running", address)))?;
+
+        self.clone().spawn(backfill.id);
+        Ok(backfill)
+    }
+
+    /// Resume backfills left running by a previous process
+    pub async fn resume_running(&self) -> Result<usize> {
+        let ids = sqlx::query_scalar!("SELECT id FROM indexer_backfills WHERE status = $1", IndexerBackfill::RUNNING)
+            .fetch_all(&self.db)
+            .await?;
+
+        for id in &ids {
+            tracing::info!("Resuming indexer backfill {}", id);
+            self.clone().spawn(*id);
+        }
+        Ok(ids.len())
+    }
+
+    /// Stop a running backfill after its current page
+    pub async fn cancel(&self, id: Uuid) -> Result<IndexerBackfill> {
+        sqlx::query!(
+            "UPDATE indexer_backfills SET status = $2 WHERE id = $1 AND status = $3",
+            id,
+            IndexerBackfill::CANCELLED,
+            IndexerBackfill::RUNNING,
+        )
+        .execute(&self.db)
+            .await?;
+
+        self.load(id).await
+    }
+
+    pub async fn load(&self, id: Uuid) -> Result<IndexerBackfill> {
+        let query = format!("SELECT {} FROM indexer_backfills WHERE id = $1", BACKFILL_COLUMNS);
+
+        sqlx::query_as::<_, IndexerBackfill>(&query)
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Backfill {} not found", id)))
+    }
+
+    fn spawn(self, id: Uuid) {
+        tokio::spawn(async move {
+            match self.run(id).await {
+                Ok(()) => tracing::info!("Indexer backfill {} finished", id),
+                Err(e) => {
+                    tracing::error!("Indexer backfill {} failed: {}", id, e);
+                    let _ = sqlx::query(
+                        "UPDATE indexer_backfills SET status = $2, error_message = $3 WHERE id = $1 AND status = $4",
+                    )
+                    .bind(id)
+                    .bind(IndexerBackfill::FAILED)
+                    .bind(e.to_string())
+                    .bind(IndexerBackfill::RUNNING)
+                    .execute(&self.db)
+                    .await;
+                }
+            }
+        });
+    }
+
+    async fn run(&self, id: Uuid) -> Result<()> {
+        let backfill = self.load(id).await?;
+        let mut cursor = backfill.cursor_signature;
+
+        loop {
+            let page = with_backoff(|| {
+                self.chain.get_signatures_for_address(
+                    &backfill.address,
+                    cursor.as_deref(),
+                    backfill.until_signature.as_deref(),
+                    PAGE_SIZE,
+                )
+            })
+            .await?;
+
+            let Some(last) = page.last() else {
+                return self.finish(id).await;
+            };
+            let next_cursor = last.signature.clone();
+
+            let transactions: Vec<(SignatureInfo, Option<Value>)> = stream::iter(page.iter().cloned())
+                .map(|info| async move {
+                    let transaction = with_backoff(|| self.chain.get_transaction(&info.signature)).await?;
+                    Ok::<_, ApiError>((info, transaction))
+                })
+                .buffered(self.concurrency)
+                .try_collect()
+                .await?;
+
+            if !self.checkpoint(id, &backfill.address, &page, transactions).await? {
+                tracing::info!("Indexer backfill {} is no longer running, stopping", id);
+                return Ok(());
+            }
+
+            if page.len() < PAGE_SIZE {
+                return self.finish(id).await;
+            }
+            cursor = Some(next_cursor);
+        }
+    }
+
+    /// Store a page and advance the cursor atomically; `false` if the backfill was cancelled
+    async fn checkpoint(
+        &self,
+        id: Uuid,
+        address: &str,
+        page: &[SignatureInfo],
+        transactions: Vec<(SignatureInfo, Option<Value>)>,
+    ) -> Result<bool> {
+        let mut tx = self.db.begin().await?;
+        let mut stored: i64 = 0;
+
+        for (info, transaction) in transactions {
+            // Transactions pruned from the node's ledger are counted but not stored
+            let Some(transaction) = transaction else { continue };
+
+            let result = sqlx::query(
+                "INSERT INTO indexed_transactions (signature, address, slot, block_time, succeeded, transaction)
+                 VALUES ($1, $2, $3, to_timestamp($4), $5, $6)
+                 ON CONFLICT (signature) DO NOTHING",
+            )
+            .bind(&info.signature)
+            .bind(address)
+            .bind(info.slot as i64)
+            .bind(info.block_time.map(|t| t as f64))
+            .bind(info.err.is_none())
+            .bind(transaction)
+            .execute(&mut *tx)
+            .await?;
+            stored += result.rows_affected() as i64;
+        }
+
+        let (first, last) = (&page[0], &page[page.len() - 1]);
+        let updated = sqlx::query(
+            "UPDATE indexer_backfills
+             SET cursor_signature = $2,
+                 signatures_processed = signatures_processed + $3,
+                 transactions_stored = transactions_stored + $4,
+                 newest_slot = COALESCE(newest_slot, $5),
+                 oldest_slot = $6
+             WHERE id = $1 AND status = $7",
+        )
+        .bind(id)
+        .bind(&last.signature)
+        .bind(page.len() as i64)
+        .bind(stored)
+        .bind(first.slot as i64)
+        .bind(last.slot as i64)
+        .bind(IndexerBackfill::RUNNING)
+        .execute(&mut *tx)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        tx.commit().await?;
+        metrics::counter!("indexer_backfill_signatures_total").increment(page.len() as u64);
+        Ok(true)
+    }
+
+    async fn finish(&self, id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE indexer_backfills SET status = $2 WHERE id = $1 AND status = $3")
+            .bind(id)
+            .bind(IndexerBackfill::COMPLETED)
+            .bind(IndexerBackfill::RUNNING)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Retry `call` with exponential backoff while the RPC node rate limits us
+async fn with_backoff<T, F, Fut>(mut call: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut retries = 0;
+    loop {
+        match call().await {
+            Err(ApiError::RateLimit) if retries < MAX_RATE_LIMIT_RETRIES => {
+                metrics::counter!("indexer_rpc_rate_limited_total").increment(1);
+                retries += 1;
+                tokio::time::sleep(backoff::retry_delay(retries, INITIAL_BACKOFF, MAX_BACKOFF)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_with_backoff_retries_rate_limits_only() {
+        tokio::time::pause();
+
+        let mut calls = 0;
+        let result = with_backoff(|| {
+            calls += 1;
+            let attempt = calls;
+            async move {
+                if attempt < 3 {
+                    Err(ApiError::RateLimit)
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: Result<()> = with_backoff(|| {
+            calls += 1;
+            async { Err(ApiError::Blockchain("node down".to_string())) }
+        })
+        .await;
+        assert!(matches!(result, Err(ApiError::Blockchain(_))));
+        assert_eq!(calls, 1);
+    }
+}
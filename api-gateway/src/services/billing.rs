@@ -0,0 +1,560 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::types::{BigDecimal, Json};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::tenant::{TenantScope, STATEMENT_TENANT};
+use crate::error::{ApiError, Result};
+use crate::models::billing::{
+    BatchMismatch, BillingPeriod, ProsumerStatement, SettlementReconciliation, SettlementStatement,
+};
+use crate::models::tx_job::TxJob;
+use crate::services::event_bus::{self, GatewayEvent};
+use crate::services::scheduler::{spawn_daily, DailySchedule};
+use crate::services::settlement::{MarketTokens, Settler};
+use crate::utils::clock::{month_of, SharedClock};
+use crate::utils::decimal::{big_decimal, decimal};
+use crate::AppState;
+
+pub const PERIOD_COLUMNS: &str = "id, period_start, period_end, status, market_fee_bps, \
+    grid_import_price::text AS grid_import_price, statements, reconciliation, error_message, closed_by, created_at, \
+    closed_at";
+
+pub const STATEMENT_COLUMNS: &str = "s.id, s.billing_period_id, s.user_id, p.period_start, p.period_end, \
+    s.energy_bought::text AS energy_bought, s.purchase_cost::text AS purchase_cost, \
+    s.energy_sold::text AS energy_sold, s.sales_revenue::text AS sales_revenue, \
+    s.trading_fees::text AS trading_fees, s.energy_generated::text AS energy_generated, \
+    s.energy_consumed::text AS energy_consumed, s.grid_import::text AS grid_import, \
+    s.grid_import_cost::text AS grid_import_cost, s.net_amount::text AS net_amount, s.trades, s.unsettled_trades, \
+    s.created_at";
+
+/// Most statements returned per page
+pub const MAX_STATEMENT_LIMIT: i64 = 500;
+
+const BPS_DENOMINATOR: i64 = 10_000;
+
+/// Payment decimals amounts are rounded to when the market cannot be read
+const DEFAULT_AMOUNT_SCALE: u32 = 8;
+
+/// Start of the last month that has ended at least `grace_days` before `today`, so that
+/// its trades have had time to settle on-chain
+pub fn closable_period(today: NaiveDate, grace_days: i64) -> NaiveDate {
+    month_of(month_of(today - Duration::days(grace_days)).0 - Duration::days(1)).0
+}
+
+fn start_of(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).expect("midnight exists").and_utc()
+}
+
+/// A prosumer's trading and metering over a billing period
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Activity {
+    pub energy_bought: Decimal,
+    pub purchase_cost: Decimal,
+    pub energy_sold: Decimal,
+    pub sales_revenue: Decimal,
+    pub energy_generated: Decimal,
+    pub energy_consumed: Decimal,
+    pub trades: i32,
+    pub unsettled_trades: i32,
+}
+
+/// What a prosumer is billed for their `Activity`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Netting {
+    pub trading_fees: Decimal,
+    pub grid_import: Decimal,
+    pub grid_import_cost: Decimal,
+    pub net_amount: Decimal,
+}
+
+impl Activity {
+    /// Net the activity at the period's fee and grid tariff, rounding amounts down to `scale` places
+    ///
+    /// Own generation not sold covers consumption first, then energy bought; the rest was
+    /// drawn from the grid.
+    pub fn net(&self, market_fee_bps: u16, grid_import_price: Decimal, scale: u32) -> Netting {
+        let round = |value: Decimal| value.round_dp_with_strategy(scale, RoundingStrategy::ToZero);
+        let trading_fees = round(self.sales_revenue * Decimal::from(market_fee_bps) / Decimal::from(BPS_DENOMINATOR));
+        let grid_import =
+            (self.energy_consumed - (self.energy_generated - self.energy_sold).max(Decimal::ZERO) - self.energy_bought)
+                .max(Decimal::ZERO);
+        let grid_import_cost = round(grid_import * grid_import_price);
+
+        Netting {
+            trading_fees,
+            grid_import,
+            grid_import_cost,
+            net_amount: self.purchase_cost + grid_import_cost + trading_fees - self.sales_revenue,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ActivityRow {
+    user_id: Uuid,
+    energy_bought: BigDecimal,
+    purchase_cost: BigDecimal,
+    energy_sold: BigDecimal,
+    sales_revenue: BigDecimal,
+    energy_generated: BigDecimal,
+    energy_consumed: BigDecimal,
+    trades: i64,
+    unsettled_trades: i64,
+}
+
+impl From<&ActivityRow> for Activity {
+    fn from(row: &ActivityRow) -> Self {
+        Self {
+            energy_bought: decimal(&row.energy_bought),
+            purchase_cost: decimal(&row.purchase_cost),
+            energy_sold: decimal(&row.energy_sold),
+            sales_revenue: decimal(&row.sales_revenue),
+            energy_generated: decimal(&row.energy_generated),
+            energy_consumed: decimal(&row.energy_consumed),
+            trades: row.trades as i32,
+            unsettled_trades: row.unsettled_trades as i32,
+        }
+    }
+}
+
+/// Matched trade as the reconciliation compares it with its settlement batch
+#[derive(Debug, sqlx::FromRow)]
+struct ExecutionRow {
+    energy_amount: BigDecimal,
+    price_per_kwh: BigDecimal,
+    settlement_batch: Option<i64>,
+    job_status: Option<String>,
+}
+
+/// Closes monthly billing periods into immutable per-prosumer settlement statements
+#[derive(Clone)]
+pub struct BillingService {
+    db: PgPool,
+    settler: Settler,
+    grid_import_price: Decimal,
+    grace_days: i64,
+    clock: SharedClock,
+}
+
+impl BillingService {
+    pub fn new(db: PgPool, settler: Settler, grid_import_price: Decimal, grace_days: i64, clock: SharedClock) -> Self {
+        Self {
+            db,
+            settler,
+            grid_import_price,
+            grace_days,
+            clock,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        Ok(Self::new(
+            state.db.clone(),
+            Settler::from_state(state)?,
+            state.config.grid_import_price,
+            state.config.billing_grace_days,
+            state.clock.clone(),
+        ))
+    }
+
+    /// Close the last month once its grace period is over, checking daily at `schedule`
+    pub fn spawn(self, schedule: DailySchedule) {
+        let clock = self.clock.clone();
+        spawn_daily("billing_close", schedule, clock, move || {
+            let service = self.clone();
+            async move {
+                let period_start = closable_period(service.clock.now().date_naive(), service.grace_days);
+                service.close(period_start, None).await.map(|_| ())
+            }
+        });
+    }
+
+    pub async fn list(&self, limit: i64, offset: i64) -> Result<Vec<BillingPeriod>> {
+        let query = format!(
+            "SELECT {} FROM billing_periods ORDER BY period_start DESC LIMIT $1 OFFSET $2",
+            PERIOD_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, BillingPeriod>(&query)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    pub async fn load(&self, period_id: Uuid) -> Result<BillingPeriod> {
+        let query = format!("SELECT {} FROM billing_periods WHERE id = $1", PERIOD_COLUMNS);
+        sqlx::query_as::<_, BillingPeriod>(&query)
+            .bind(period_id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Billing period {} not found", period_id)))
+    }
+
+    pub async fn period_starting(&self, period_start: NaiveDate) -> Result<BillingPeriod> {
+        let query = format!("SELECT {} FROM billing_periods WHERE period_start = $1", PERIOD_COLUMNS);
+        sqlx::query_as::<_, BillingPeriod>(&query)
+            .bind(period_start)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("No billing period starts {}", period_start)))
+    }
+
+    /// Every statement of a period visible in `scope`, or just `user_id`'s, with whom each is
+    /// addressed to
+    pub async fn prosumer_statements(
+        &self,
+        period_id: Uuid,
+        user_id: Option<Uuid>,
+        scope: &TenantScope,
+    ) -> Result<Vec<ProsumerStatement>> {
+        let query = format!(
+            "SELECT {}, u.username, NULLIF(CONCAT_WS(' ', u.first_name, u.last_name), '') AS full_name, u.email,
+                    u.department
+             FROM settlement_statements s
+             JOIN billing_periods p ON p.id = s.billing_period_id
+             JOIN users u ON u.id = s.user_id
+             WHERE s.billing_period_id = $1 AND ($2::uuid IS NULL OR s.user_id = $2) AND {}
+             ORDER BY u.department, u.username",
+            STATEMENT_COLUMNS,
+            TenantScope::condition("u.tenant_id", 3)
+        );
+        Ok(sqlx::query_as::<_, ProsumerStatement>(&query)
+            .bind(period_id)
+            .bind(user_id)
+            .bind(scope.is_campus())
+            .bind(scope.tenant())
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Statements visible in `scope` of a period, or of every period for `user_id`, newest
+    /// period first
+    pub async fn statements(
+        &self,
+        period_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        scope: &TenantScope,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<SettlementStatement>> {
+        let query = format!(
+            "SELECT {} FROM settlement_statements s JOIN billing_periods p ON p.id = s.billing_period_id
+             WHERE ($1::uuid IS NULL OR s.billing_period_id = $1) AND ($2::uuid IS NULL OR s.user_id = $2)
+               AND {}
+             ORDER BY p.period_start DESC, s.user_id
+             LIMIT $3 OFFSET $4",
+            STATEMENT_COLUMNS,
+            TenantScope::condition(STATEMENT_TENANT, 5)
+        );
+        Ok(sqlx::query_as::<_, SettlementStatement>(&query)
+            .bind(period_id)
+            .bind(user_id)
+            .bind(limit)
+            .bind(offset)
+            .bind(scope.is_campus())
+            .bind(scope.tenant())
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Close the month starting `period_start`, issuing its statements
+    ///
+    /// Returns `None` when the period is already closed or being closed, so replicas running
+    /// the same schedule close it once. A failed close can be retried.
+    pub async fn close(&self, period_start: NaiveDate, closed_by: Option<Uuid>) -> Result<Option<BillingPeriod>> {
+        let (start, end) = month_of(period_start);
+        if start != period_start {
+            return Err(ApiError::BadRequest("Billing periods start on the first day of a month".to_string()));
+        }
+        if start_of(end) > self.clock.now() {
+            return Err(ApiError::BadRequest(format!("Billing period {} has not ended", start)));
+        }
+
+        let Some(period_id) = self.claim(start, end, closed_by).await? else {
+            tracing::info!("Billing period {} is already closed", start);
+            return Ok(None);
+        };
+
+        if let Err(e) = self.issue(period_id, start, end).await {
+            sqlx::query!(
+                "UPDATE billing_periods SET status = $2, error_message = $3 WHERE id = $1",
+                period_id,
+                BillingPeriod::FAILED,
+                e.to_string(),
+            )
+            .execute(&self.db)
+            .await?;
+            return Err(e);
+        }
+
+        let period = self.load(period_id).await?;
+        tracing::info!("Closed billing period {} with {} statements: {}", start, period.statements, period.status);
+        metrics::counter!("billing_periods_closed_total", "status" => period.status.clone()).increment(1);
+        Ok(Some(period))
+    }
+
+    async fn claim(&self, start: NaiveDate, end: NaiveDate, closed_by: Option<Uuid>) -> Result<Option<Uuid>> {
+        Ok(sqlx::query_scalar(
+            "INSERT INTO billing_periods (period_start, period_end, status, closed_by) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (period_start) DO UPDATE SET
+                status = EXCLUDED.status, closed_by = EXCLUDED.closed_by, error_message = NULL
+             WHERE billing_periods.status = $5
+             RETURNING id",
+        )
+        .bind(start)
+        .bind(end)
+        .bind(BillingPeriod::CLOSING)
+        .bind(closed_by)
+        .bind(BillingPeriod::FAILED)
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn issue(&self, period_id: Uuid, start: NaiveDate, end: NaiveDate) -> Result<()> {
+        let market = self.settler.market().await?;
+        let tokens = self.settler.tokens(&market).await?;
+        let (period_start, period_end) = (start_of(start), start_of(end));
+        let reconciliation = self.reconcile(&tokens, period_start, period_end).await?;
+        let status = if reconciliation.needs_attention() {
+            BillingPeriod::ATTENTION
+        } else {
+            BillingPeriod::CLOSED
+        };
+
+        let mut tx = self.db.begin().await?;
+        let activity = sqlx::query_as::<_, ActivityRow>(
+            "WITH executions AS (
+                 SELECT e.buyer_id, e.seller_id, e.energy_amount, e.total_price,
+                        j.status IS DISTINCT FROM $3 AS unsettled
+                 FROM trade_executions e
+                 LEFT JOIN tx_jobs j ON j.id = e.settlement_job_id
+                 WHERE e.executed_at >= $1 AND e.executed_at < $2
+             ),
+             traded AS (
+                 SELECT user_id, SUM(bought) AS bought, SUM(cost) AS cost, SUM(sold) AS sold,
+                        SUM(revenue) AS revenue, COUNT(*) AS trades, COUNT(*) FILTER (WHERE unsettled) AS unsettled
+                 FROM (
+                     SELECT buyer_id AS user_id, energy_amount AS bought, total_price AS cost,
+                            0 AS sold, 0 AS revenue, unsettled
+                     FROM executions
+                     UNION ALL
+                     SELECT seller_id, 0, 0, energy_amount, total_price, unsettled FROM executions
+                 ) sides
+                 GROUP BY user_id
+             ),
+             metered AS (
+                 SELECT ma.user_id, SUM(er.energy_generated) AS generated, SUM(er.energy_consumed) AS consumed
+                 FROM energy_readings er
+                 JOIN meter_assignments ma ON ma.meter_id = er.meter_id
+                     AND er.timestamp >= ma.assigned_at
+                     AND (ma.deactivated_at IS NULL OR er.timestamp < ma.deactivated_at)
+                 WHERE er.timestamp >= $1 AND er.timestamp < $2
+                 GROUP BY ma.user_id
+             )
+             SELECT COALESCE(t.user_id, m.user_id) AS user_id,
+                    COALESCE(t.bought, 0)::numeric AS energy_bought,
+                    COALESCE(t.cost, 0)::numeric AS purchase_cost,
+                    COALESCE(t.sold, 0)::numeric AS energy_sold,
+                    COALESCE(t.revenue, 0)::numeric AS sales_revenue,
+                    COALESCE(m.generated, 0)::numeric AS energy_generated,
+                    COALESCE(m.consumed, 0)::numeric AS energy_consumed,
+                    COALESCE(t.trades, 0) AS trades,
+                    COALESCE(t.unsettled, 0) AS unsettled_trades
+             FROM traded t
+             FULL JOIN metered m ON m.user_id = t.user_id
+             ORDER BY 1",
+        )
+        .bind(period_start)
+        .bind(period_end)
+        .bind(TxJob::CONFIRMED)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let scale = u32::from(tokens.payment_decimals).min(DEFAULT_AMOUNT_SCALE);
+        for row in &activity {
+            let activity = Activity::from(row);
+            let netting = activity.net(market.market_fee_bps, self.grid_import_price, scale);
+            let statement_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO settlement_statements (
+                    billing_period_id, user_id, energy_bought, purchase_cost, energy_sold, sales_revenue, trading_fees,
+                    energy_generated, energy_consumed, grid_import, grid_import_cost, net_amount, trades, unsettled_trades
+                 ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+                 RETURNING id",
+            )
+            .bind(period_id)
+            .bind(row.user_id)
+            .bind(&row.energy_bought)
+            .bind(&row.purchase_cost)
+            .bind(&row.energy_sold)
+            .bind(&row.sales_revenue)
+            .bind(big_decimal(netting.trading_fees))
+            .bind(&row.energy_generated)
+            .bind(&row.energy_consumed)
+            .bind(big_decimal(netting.grid_import))
+            .bind(big_decimal(netting.grid_import_cost))
+            .bind(big_decimal(netting.net_amount))
+            .bind(activity.trades)
+            .bind(activity.unsettled_trades)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let event = GatewayEvent::SettlementReady {
+                statement_id,
+                billing_period_id: period_id,
+                user_id: row.user_id,
+                net_amount: netting.net_amount,
+            };
+            event_bus::publish(&mut *tx, &event).await?;
+        }
+
+        sqlx::query(
+            "UPDATE billing_periods SET status = $2, market_fee_bps = $3, grid_import_price = $4, statements = $5,
+                reconciliation = $6, closed_at = $7
+             WHERE id = $1",
+        )
+        .bind(period_id)
+        .bind(status)
+        .bind(i32::from(market.market_fee_bps))
+        .bind(big_decimal(self.grid_import_price))
+        .bind(activity.len() as i32)
+        .bind(Json(&reconciliation))
+        .bind(self.clock.now())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Compare each confirmed settlement batch of the period with its on-chain record
+    async fn reconcile(
+        &self,
+        tokens: &MarketTokens,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<SettlementReconciliation> {
+        let executions = sqlx::query_as::<_, ExecutionRow>(
+            "SELECT e.energy_amount, e.price_per_kwh, e.settlement_batch, j.status AS job_status
+             FROM trade_executions e
+             LEFT JOIN tx_jobs j ON j.id = e.settlement_job_id
+             WHERE e.executed_at >= $1 AND e.executed_at < $2",
+        )
+        .bind(period_start)
+        .bind(period_end)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut reconciliation = SettlementReconciliation {
+            trades: executions.len() as i64,
+            ..Default::default()
+        };
+        // Batch -> (confirmed, trades, energy, payment) expected on-chain
+        let mut batches: BTreeMap<i64, (bool, u32, u64, u64)> = BTreeMap::new();
+        for execution in &executions {
+            let Some(batch) = execution.settlement_batch else {
+                reconciliation.unsettleable_trades += 1;
+                continue;
+            };
+            let (energy, payment) = tokens
+                .amounts(decimal(&execution.energy_amount), decimal(&execution.price_per_kwh))
+                .unwrap_or_default();
+            let expected = batches.entry(batch).or_default();
+            expected.0 = execution.job_status.as_deref() == Some(TxJob::CONFIRMED);
+            expected.1 += 1;
+            expected.2 = expected.2.saturating_add(energy);
+            expected.3 = expected.3.saturating_add(payment);
+        }
+
+        reconciliation.batches = batches.len() as i64;
+        for (batch, (confirmed, trades, energy, payment)) in batches {
+            if !confirmed {
+                reconciliation.pending_batches += 1;
+                continue;
+            }
+
+            let record = match self.settler.settled_batch(batch as u64).await {
+                Ok(record) => record,
+                Err(e) => {
+                    tracing::warn!("Settlement reconciliation stopped: {}", e);
+                    reconciliation.rpc_error = Some(e.to_string());
+                    break;
+                }
+            };
+            let chain = record.as_ref().map(|r| (r.trades, r.energy_volume, r.payment_volume));
+            if chain == Some((trades, energy, payment)) {
+                reconciliation.matched_batches += 1;
+            } else {
+                reconciliation.mismatches.push(BatchMismatch {
+                    batch,
+                    expected_trades: trades,
+                    expected_energy: energy,
+                    expected_payment: payment,
+                    chain_trades: chain.map(|(trades, _, _)| trades),
+                    chain_energy: chain.map(|(_, energy, _)| energy),
+                    chain_payment: chain.map(|(_, _, payment)| payment),
+                });
+            }
+        }
+
+        Ok(reconciliation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_closable_period_waits_out_the_grace_days() {
+        assert_eq!(closable_period(date(2024, 10, 3), 3), date(2024, 8, 1));
+        assert_eq!(closable_period(date(2024, 10, 4), 3), date(2024, 9, 1));
+        assert_eq!(closable_period(date(2025, 1, 1), 0), date(2024, 12, 1));
+    }
+
+    #[test]
+    fn test_netting_bills_grid_imports_and_seller_fees() {
+        let activity = Activity {
+            energy_bought: Decimal::new(20, 0),
+            purchase_cost: Decimal::new(70, 0),
+            energy_sold: Decimal::new(30, 0),
+            sales_revenue: Decimal::new(105, 0),
+            energy_generated: Decimal::new(50, 0),
+            energy_consumed: Decimal::new(100, 0),
+            trades: 3,
+            unsettled_trades: 0,
+        };
+
+        let netting = activity.net(25, Decimal::new(45, 1), 6);
+
+        // 105 * 0.25%
+        assert_eq!(netting.trading_fees, Decimal::new(2625, 4));
+        // 100 consumed - (50 - 30) kept - 20 bought
+        assert_eq!(netting.grid_import, Decimal::new(60, 0));
+        assert_eq!(netting.grid_import_cost, Decimal::new(270, 0));
+        assert_eq!(netting.net_amount, Decimal::new(2352625, 4));
+    }
+
+    #[test]
+    fn test_surplus_is_not_a_grid_import() {
+        let activity = Activity {
+            energy_sold: Decimal::new(10, 0),
+            sales_revenue: Decimal::new(40, 0),
+            energy_generated: Decimal::new(80, 0),
+            energy_consumed: Decimal::new(30, 0),
+            ..Default::default()
+        };
+
+        let netting = activity.net(0, Decimal::new(45, 1), 6);
+
+        assert_eq!(netting.grid_import, Decimal::ZERO);
+        assert_eq!(netting.net_amount, Decimal::new(-40, 0));
+    }
+}
@@ -0,0 +1,335 @@
+use std::str::FromStr;
+
+use chrono::Duration;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::models::billing::{BillingPeriod, ProsumerStatement, SettlementStatement};
+use crate::utils::pdf::{PdfDocument, LINE_WIDTH};
+
+/// Invoices are issued in baht and satang
+const INVOICE_SCALE: u32 = 2;
+/// Places average prices per kWh are shown to
+const PRICE_SCALE: u32 = 4;
+
+/// Format settlement statements are exported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Pdf,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> std::result::Result<Self, Self::Err> {
+        match format.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "pdf" => Ok(Self::Pdf),
+            _ => Err(format!("Unknown export format {:?}; expected csv or pdf", format)),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+/// Invoice figures of a statement beyond what it stores
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvoiceLines {
+    /// Average paid per kWh bought, when any was bought
+    pub buy_price: Option<Decimal>,
+    /// Average received per kWh sold, when any was sold
+    pub sell_price: Option<Decimal>,
+    /// Net amount rounded to satang
+    pub net_due: Decimal,
+    /// VAT on the net amount when the prosumer owes it; credits carry none
+    pub vat: Decimal,
+    pub total_due: Decimal,
+}
+
+fn amount(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap_or_default()
+}
+
+fn average(total: Decimal, quantity: Decimal) -> Option<Decimal> {
+    (!quantity.is_zero()).then(|| (total / quantity).round_dp(PRICE_SCALE))
+}
+
+pub fn invoice_lines(statement: &SettlementStatement, vat_rate: Decimal) -> InvoiceLines {
+    let round = |value: Decimal| value.round_dp_with_strategy(INVOICE_SCALE, RoundingStrategy::MidpointAwayFromZero);
+    let net_due = round(amount(&statement.net_amount));
+    let vat = round(net_due.max(Decimal::ZERO) * vat_rate);
+
+    InvoiceLines {
+        buy_price: average(amount(&statement.purchase_cost), amount(&statement.energy_bought)),
+        sell_price: average(amount(&statement.sales_revenue), amount(&statement.energy_sold)),
+        net_due,
+        vat,
+        total_due: net_due + vat,
+    }
+}
+
+/// VAT rate as a percentage, e.g. `7` for 0.07
+fn percent(vat_rate: Decimal) -> String {
+    (vat_rate * Decimal::ONE_HUNDRED).normalize().to_string()
+}
+
+/// `value` as a CSV field, quoted when needed and defused if a spreadsheet would run it as a formula
+fn csv_text(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn optional(value: Option<Decimal>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// One row per prosumer, amounts as stored and invoice figures in satang
+pub fn render_csv(period: &BillingPeriod, statements: &[ProsumerStatement], vat_rate: Decimal) -> String {
+    let mut csv = String::from(
+        "period_start,period_end,statement_id,user_id,username,full_name,department,email,energy_bought_kwh,\
+         average_buy_price,purchase_cost,energy_sold_kwh,average_sell_price,sales_revenue,trading_fees,\
+         grid_import_kwh,grid_import_cost,net_amount,vat_rate,vat,total_due,unsettled_trades\r\n",
+    );
+
+    for prosumer in statements {
+        let statement = &prosumer.statement;
+        let lines = invoice_lines(statement, vat_rate);
+        let fields = [
+            period.period_start.to_string(),
+            (period.period_end - Duration::days(1)).to_string(),
+            statement.id.to_string(),
+            statement.user_id.to_string(),
+            csv_text(&prosumer.username),
+            csv_text(prosumer.full_name.as_deref().unwrap_or_default()),
+            csv_text(&prosumer.department),
+            csv_text(prosumer.email.as_deref().unwrap_or_default()),
+            statement.energy_bought.clone(),
+            optional(lines.buy_price),
+            statement.purchase_cost.clone(),
+            statement.energy_sold.clone(),
+            optional(lines.sell_price),
+            statement.sales_revenue.clone(),
+            statement.trading_fees.clone(),
+            statement.grid_import.clone(),
+            statement.grid_import_cost.clone(),
+            statement.net_amount.clone(),
+            vat_rate.normalize().to_string(),
+            lines.vat.to_string(),
+            lines.total_due.to_string(),
+            statement.unsettled_trades.to_string(),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+fn money(value: Decimal) -> String {
+    value
+        .round_dp_with_strategy(INVOICE_SCALE, RoundingStrategy::MidpointAwayFromZero)
+        .to_string()
+}
+
+fn kwh(value: &str) -> String {
+    amount(value).normalize().to_string()
+}
+
+fn row(label: &str, kwh: &str, price: &str, amount: &str) -> String {
+    format!("{:<34}{:>16}{:>18}{:>23}", label, kwh, price, amount)
+}
+
+/// One page per prosumer, laid out to attach to their invoice
+pub fn render_pdf(period: &BillingPeriod, statements: &[ProsumerStatement], vat_rate: Decimal) -> Vec<u8> {
+    let mut pdf = PdfDocument::new();
+    let last_day = period.period_end - Duration::days(1);
+    let rule = "-".repeat(LINE_WIDTH);
+
+    if statements.is_empty() {
+        pdf.line(format!("No settlement statements for {} to {}", period.period_start, last_day));
+    }
+
+    for (i, prosumer) in statements.iter().enumerate() {
+        if i > 0 {
+            pdf.page_break();
+        }
+        let statement = &prosumer.statement;
+        let lines = invoice_lines(statement, vat_rate);
+        let name = match &prosumer.full_name {
+            Some(full_name) => format!("{} ({})", full_name, prosumer.username),
+            None => prosumer.username.clone(),
+        };
+
+        pdf.line("GridTokenX energy settlement statement");
+        pdf.line(rule.clone());
+        pdf.line(format!("{:<22}{} to {}", "Billing period", period.period_start, last_day));
+        pdf.line(format!("{:<22}{}", "Statement", statement.id));
+        pdf.line(format!("{:<22}{}", "Prosumer", name));
+        pdf.line(format!("{:<22}{}", "Department", prosumer.department));
+        if let Some(email) = &prosumer.email {
+            pdf.line(format!("{:<22}{}", "Email", email));
+        }
+        pdf.line("");
+        pdf.line(row("", "kWh", "Avg price/kWh", "Amount"));
+        pdf.line(rule.clone());
+        pdf.line(row(
+            "Energy bought",
+            &kwh(&statement.energy_bought),
+            &optional(lines.buy_price),
+            &money(amount(&statement.purchase_cost)),
+        ));
+        pdf.line(row(
+            "Energy sold",
+            &kwh(&statement.energy_sold),
+            &optional(lines.sell_price),
+            &money(-amount(&statement.sales_revenue)),
+        ));
+        pdf.line(row("Trading fees on sales", "", "", &money(amount(&statement.trading_fees))));
+        pdf.line(row(
+            "Grid import",
+            &kwh(&statement.grid_import),
+            &period.grid_import_price.as_deref().map(kwh).unwrap_or_default(),
+            &money(amount(&statement.grid_import_cost)),
+        ));
+        pdf.line(rule.clone());
+        pdf.line(row("Net amount", "", "", &lines.net_due.to_string()));
+        pdf.line(row(&format!("VAT {}%", percent(vat_rate)), "", "", &lines.vat.to_string()));
+        pdf.line(row("Total due", "", "", &lines.total_due.to_string()));
+        pdf.line("");
+        pdf.line(format!(
+            "Metered: {} kWh generated, {} kWh consumed; {} trades.",
+            kwh(&statement.energy_generated),
+            kwh(&statement.energy_consumed),
+            statement.trades
+        ));
+        if statement.unsettled_trades > 0 {
+            pdf.line(format!(
+                "{} trades had not settled on-chain when the period closed.",
+                statement.unsettled_trades
+            ));
+        }
+        pdf.line("A negative total is a credit owed to the prosumer.");
+    }
+
+    pdf.to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+    use uuid::Uuid;
+
+    fn period() -> BillingPeriod {
+        BillingPeriod {
+            id: Uuid::new_v4(),
+            period_start: NaiveDate::from_ymd_opt(2024, 9, 1).unwrap(),
+            period_end: NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
+            status: BillingPeriod::CLOSED.to_string(),
+            market_fee_bps: Some(25),
+            grid_import_price: Some("4.50000000".to_string()),
+            statements: 1,
+            reconciliation: None,
+            error_message: None,
+            closed_by: None,
+            created_at: Utc::now(),
+            closed_at: Some(Utc::now()),
+        }
+    }
+
+    fn statement(net_amount: &str) -> ProsumerStatement {
+        let period = period();
+        ProsumerStatement {
+            statement: SettlementStatement {
+                id: Uuid::new_v4(),
+                billing_period_id: period.id,
+                user_id: Uuid::new_v4(),
+                period_start: period.period_start,
+                period_end: period.period_end,
+                energy_bought: "20.00000000".to_string(),
+                purchase_cost: "70.00000000".to_string(),
+                energy_sold: "30.00000000".to_string(),
+                sales_revenue: "105.00000000".to_string(),
+                trading_fees: "0.26250000".to_string(),
+                energy_generated: "50.00000000".to_string(),
+                energy_consumed: "100.00000000".to_string(),
+                grid_import: "60.00000000".to_string(),
+                grid_import_cost: "270.00000000".to_string(),
+                net_amount: net_amount.to_string(),
+                trades: 3,
+                unsettled_trades: 0,
+                created_at: Utc::now(),
+            },
+            username: "=somchai".to_string(),
+            full_name: Some("Somchai, Jaidee".to_string()),
+            email: None,
+            department: "Electrical Engineering".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_vat_applies_to_amounts_owed_only() {
+        let vat_rate = Decimal::new(7, 2);
+
+        let owed = invoice_lines(&statement("235.26250000").statement, vat_rate);
+        assert_eq!(owed.buy_price, Some(Decimal::new(35, 1)));
+        assert_eq!(owed.sell_price, Some(Decimal::new(35, 1)));
+        assert_eq!(owed.net_due, Decimal::new(23526, 2));
+        assert_eq!(owed.vat, Decimal::new(1647, 2));
+        assert_eq!(owed.total_due, Decimal::new(25173, 2));
+
+        let credit = invoice_lines(&statement("-40.00000000").statement, vat_rate);
+        assert_eq!(credit.vat, Decimal::ZERO);
+        assert_eq!(credit.total_due, Decimal::new(-40, 0));
+    }
+
+    #[test]
+    fn test_csv_quotes_and_defuses_text() {
+        let csv = render_csv(&period(), &[statement("235.26250000")], Decimal::new(7, 2));
+        let rows: Vec<&str> = csv.split("\r\n").collect();
+
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].starts_with("period_start,period_end,statement_id"));
+        assert!(rows[1].starts_with("2024-09-01,2024-09-30,"));
+        assert!(rows[1].contains(",'=somchai,\"Somchai, Jaidee\",Electrical Engineering,,"));
+        assert!(rows[1].ends_with(",235.26250000,0.07,16.47,251.73,0"));
+    }
+
+    #[test]
+    fn test_pdf_has_a_page_per_prosumer() {
+        let statements = [statement("235.26250000"), statement("-40.00000000")];
+        let pdf = String::from_utf8(render_pdf(&period(), &statements, Decimal::new(7, 2))).unwrap();
+
+        assert!(pdf.contains("/Count 2"));
+        assert!(pdf.contains("VAT 7%"));
+        assert!(pdf.contains("251.73"));
+    }
+
+    #[test]
+    fn test_export_format_parses() {
+        assert_eq!("PDF".parse::<ExportFormat>(), Ok(ExportFormat::Pdf));
+        assert_eq!("csv".parse::<ExportFormat>(), Ok(ExportFormat::Csv));
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+    }
+}
@@ -0,0 +1,914 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::error::{ApiError, Result};
+use crate::services::chain_client::MeterReadingInput;
+use crate::services::circuit_breaker::{BreakerSettings, CircuitBreaker};
+use crate::services::metrics::record_cache_lookup;
+use crate::services::program_errors::decode_simulation_error;
+use crate::services::transaction::{advance_nonce_instruction, parse_nonce_account, Instruction, NonceState, Pubkey};
+use crate::utils::clock::SystemClock;
+use crate::utils::telemetry;
+
+// Oracle instructions and account decoding need the program crates
+#[cfg(feature = "chain")]
+use std::ops::Range;
+
+#[cfg(feature = "chain")]
+use anchor_lang::AccountDeserialize;
+
+#[cfg(feature = "chain")]
+use crate::services::fee_payers::sign_transaction;
+#[cfg(feature = "chain")]
+use crate::services::fees::set_compute_unit_limit_instruction;
+#[cfg(feature = "chain")]
+use crate::services::transaction::{anchor_instruction, serialize_transaction, AccountMeta, Message, SIGNATURE_LENGTH};
+#[cfg(feature = "chain")]
+use crate::services::tx_signer::TxSigner;
+
+/// Solana JSON-RPC client used by the gateway
+#[derive(Debug, Clone)]
+pub struct BlockchainService {
+    http: reqwest::Client,
+    rpc_url: String,
+    /// Last value read from each durable nonce account, used while the RPC is unreachable
+    nonces: Arc<Mutex<HashMap<Pubkey, NonceState>>>,
+    /// Fails calls fast while the RPC node is failing, shared by every clone
+    breaker: CircuitBreaker,
+    /// Latest blockhash shared by concurrent submissions, refreshed in the background
+    blockhash: Arc<Mutex<Option<CachedBlockhash>>>,
+}
+
+/// Age after which the shared blockhash is no longer handed out and submissions fetch their
+/// own; well inside the ~60 seconds a blockhash stays valid
+const MAX_BLOCKHASH_AGE: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+struct CachedBlockhash {
+    blockhash: [u8; 32],
+    last_valid_block_height: u64,
+    fetched_at: Instant,
+}
+
+/// What keeps a transaction valid: a recent blockhash for ~90 seconds, or a durable nonce
+/// until the nonce account is advanced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionLifetime {
+    Blockhash {
+        blockhash: [u8; 32],
+        last_valid_block_height: u64,
+    },
+    Nonce {
+        account: Pubkey,
+        state: NonceState,
+    },
+}
+
+impl TransactionLifetime {
+    /// Value of the message's recent blockhash field
+    pub fn recent_blockhash(&self) -> [u8; 32] {
+        match self {
+            Self::Blockhash { blockhash, .. } => *blockhash,
+            Self::Nonce { state, .. } => state.nonce,
+        }
+    }
+
+    /// `instructions`, led by `AdvanceNonceAccount` when the lifetime is a durable nonce
+    pub fn instructions(&self, instructions: &[Instruction]) -> Vec<Instruction> {
+        match self {
+            Self::Blockhash { .. } => instructions.to_vec(),
+            Self::Nonce { account, state } => std::iter::once(advance_nonce_instruction(*account, state.authority))
+                .chain(instructions.iter().cloned())
+                .collect(),
+        }
+    }
+}
+
+/// Latest blockhash and the last block height at which it is accepted
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestBlockhash {
+    pub blockhash: String,
+    pub last_valid_block_height: u64,
+}
+
+/// Processing state of a submitted transaction
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureStatus {
+    pub slot: u64,
+    /// `processed`, `confirmed` or `finalized`
+    pub confirmation_status: Option<String>,
+    /// Transaction error, `None` if it succeeded
+    pub err: Option<Value>,
+}
+
+/// Entry returned by `getSignaturesForAddress`, newest first
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Transaction error, `None` if it succeeded
+    pub err: Option<Value>,
+}
+
+/// Outcome of simulating a transaction against the latest bank
+#[derive(Debug, Clone, Deserialize)]
+pub struct SimulationResult {
+    /// Transaction error, `None` if it would succeed
+    pub err: Option<Value>,
+    pub logs: Option<Vec<String>>,
+}
+
+/// Fee paid to prioritize a transaction in a recent slot
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrioritizationFee {
+    pub slot: u64,
+    /// Micro-lamports per compute unit
+    pub prioritization_fee: u64,
+}
+
+/// Outcome of one reading of a batched submission
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReadingSubmission {
+    pub meter_id: String,
+    pub reading_timestamp: i64,
+    /// Transaction that recorded the reading, `None` if it was not recorded
+    pub signature: Option<String>,
+    pub error: Option<String>,
+}
+
+impl ReadingSubmission {
+    pub(crate) fn new(reading: &MeterReadingInput, outcome: std::result::Result<String, String>) -> Self {
+        let (signature, error) = match outcome {
+            Ok(signature) => (Some(signature), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            meter_id: reading.meter_id.clone(),
+            reading_timestamp: reading.reading_timestamp,
+            signature,
+            error,
+        }
+    }
+}
+
+/// Largest wire-format transaction the cluster accepts
+#[cfg(feature = "chain")]
+pub(crate) const MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// Compute units of a reading batch besides its readings, and of each reading, which
+/// creates one account
+#[cfg(feature = "chain")]
+const BATCH_BASE_UNITS: u32 = 20_000;
+#[cfg(feature = "chain")]
+const UNITS_PER_BATCHED_READING: u32 = 25_000;
+
+/// Compute budget and `submit_meter_readings_batch` instructions recording `readings`
+#[cfg(feature = "chain")]
+fn reading_batch_instructions(
+    program_id: Pubkey,
+    authority: Pubkey,
+    readings: &[MeterReadingInput],
+) -> Result<Vec<Instruction>> {
+    let address = |seeds: &[&[u8]]| {
+        Pubkey::find_program_address(seeds, &program_id)
+            .map(|(address, _)| address)
+            .ok_or_else(|| ApiError::Internal("No program address for seeds".to_string()))
+    };
+    let accounts = oracle::accounts::SubmitMeterReadingsBatch {
+        oracle_data: address(&[b"oracle_data"])?.into(),
+        authority: authority.into(),
+        system_program: anchor_lang::system_program::ID,
+    };
+    let mut instruction = anchor_instruction(
+        program_id,
+        accounts,
+        oracle::instruction::SubmitMeterReadingsBatch {
+            readings: readings.to_vec(),
+        },
+    );
+    // Each reading's new account, in reading order
+    for reading in readings {
+        instruction.accounts.push(AccountMeta {
+            pubkey: address(&[
+                b"meter_reading",
+                reading.meter_id.as_bytes(),
+                &reading.reading_timestamp.to_le_bytes(),
+            ])?,
+            is_signer: false,
+            is_writable: true,
+        });
+    }
+
+    let units = BATCH_BASE_UNITS + UNITS_PER_BATCHED_READING * readings.len() as u32;
+    Ok(vec![set_compute_unit_limit_instruction(units), instruction])
+}
+
+/// Wire size of a transaction of `instructions` once signed
+#[cfg(feature = "chain")]
+pub(crate) fn transaction_size(instructions: &[Instruction], fee_payer: Pubkey) -> Result<usize> {
+    let message = Message::new(instructions, fee_payer, [0; 32]).map_err(ApiError::Internal)?;
+    let signatures = vec![[0; SIGNATURE_LENGTH]; message.signers().len()];
+    Ok(serialize_transaction(&signatures, &message.serialize()).len())
+}
+
+/// Split `readings` into the longest runs that fit one batch transaction, within both the
+/// transaction size limit and the program's batch limit
+#[cfg(feature = "chain")]
+fn chunk_readings(
+    program_id: Pubkey,
+    authority: Pubkey,
+    fee_payer: Pubkey,
+    readings: &[MeterReadingInput],
+) -> Result<Vec<Range<usize>>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < readings.len() {
+        let mut end = start + 1;
+        while end < readings.len() && end + 1 - start <= oracle::OracleData::MAX_BATCH_READINGS {
+            let instructions = reading_batch_instructions(program_id, authority, &readings[start..=end])?;
+            if transaction_size(&instructions, fee_payer)? > MAX_TRANSACTION_SIZE {
+                break;
+            }
+            end += 1;
+        }
+        chunks.push(start..end);
+        start = end;
+    }
+    Ok(chunks)
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// `memcmp` filter of `getProgramAccounts`: accounts holding `bytes` at `offset` of their data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemcmpFilter {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+/// Accounts returned by `getProgramAccounts`, by address, and the slot they were read at
+#[derive(Debug, Clone, Default)]
+pub struct ProgramAccounts {
+    pub slot: u64,
+    pub accounts: Vec<(String, Vec<u8>)>,
+}
+
+/// `{"context": ..., "value": ...}` wrapper used by most RPC methods
+#[derive(Debug, Deserialize)]
+struct WithContext<T> {
+    value: T,
+}
+
+impl BlockchainService {
+    pub fn new(rpc_url: &str) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| ApiError::Configuration(format!("Failed to build RPC client: {}", e)))?;
+
+        Ok(Self {
+            http,
+            rpc_url: rpc_url.to_string(),
+            nonces: Arc::default(),
+            breaker: CircuitBreaker::new("Solana RPC", BreakerSettings::default(), SystemClock::shared()),
+            blockhash: Arc::default(),
+        })
+    }
+
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    /// Lifetime built on the latest confirmed blockhash
+    ///
+    /// The blockhash comes from the shared cache while it is fresh, so concurrent submissions
+    /// do not each wait on a `getLatestBlockhash` round trip.
+    pub async fn latest_lifetime(&self) -> Result<TransactionLifetime> {
+        let cached = self
+            .blockhash
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .filter(|cached| cached.fetched_at.elapsed() < MAX_BLOCKHASH_AGE);
+        record_cache_lookup("blockhash", cached.is_some());
+
+        let cached = match cached {
+            Some(cached) => cached,
+            None => self.refresh_blockhash().await?,
+        };
+        Ok(TransactionLifetime::Blockhash {
+            blockhash: cached.blockhash,
+            last_valid_block_height: cached.last_valid_block_height,
+        })
+    }
+
+    /// Latest confirmed blockhash for a transaction's message, from the shared cache while
+    /// it is fresh
+    pub async fn recent_blockhash(&self) -> Result<[u8; 32]> {
+        Ok(self.latest_lifetime().await?.recent_blockhash())
+    }
+
+    /// Fetch the latest blockhash into the shared cache
+    async fn refresh_blockhash(&self) -> Result<CachedBlockhash> {
+        let latest = self.get_latest_blockhash().await?;
+        let blockhash = Pubkey::from_str(&latest.blockhash)
+            .map_err(|e| ApiError::Blockchain(format!("Invalid blockhash from RPC: {}", e)))?;
+        let cached = CachedBlockhash {
+            blockhash: blockhash.to_bytes(),
+            last_valid_block_height: latest.last_valid_block_height,
+            fetched_at: Instant::now(),
+        };
+        *self.blockhash.lock().unwrap_or_else(|e| e.into_inner()) = Some(cached.clone());
+        Ok(cached)
+    }
+
+    /// Refresh the shared blockhash every `interval`, about a slot
+    pub fn spawn_blockhash_refresh(self, interval: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                // Submissions fetch their own blockhash once the cached one is too old
+                if let Err(e) = self.refresh_blockhash().await {
+                    tracing::debug!("Blockhash refresh failed: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Lifetime built on the current value of durable nonce `account`, or `None` if the
+    /// account is not an initialized nonce account
+    ///
+    /// When the RPC is unreachable the last value read is used, so transactions can still be
+    /// prepared for signing and submitted once it is back.
+    pub async fn nonce_lifetime(&self, account: Pubkey) -> Result<Option<TransactionLifetime>> {
+        let state = match self.get_account_data(&account.to_string()).await {
+            Ok(data) => {
+                let state = data.as_deref().and_then(parse_nonce_account);
+                let mut nonces = self.nonces.lock().unwrap_or_else(|e| e.into_inner());
+                match &state {
+                    Some(state) => nonces.insert(account, state.clone()),
+                    None => nonces.remove(&account),
+                };
+                state
+            }
+            Err(e) => {
+                let cached = self.nonces.lock().unwrap_or_else(|e| e.into_inner()).get(&account).cloned();
+                match cached {
+                    Some(state) => {
+                        tracing::warn!("Using cached nonce of {} while the RPC is unavailable: {}", account, e);
+                        Some(state)
+                    }
+                    None => return Err(e),
+                }
+            }
+        };
+        Ok(state.map(|state| TransactionLifetime::Nonce { account, state }))
+    }
+
+    /// `Ok` when the RPC node reports itself healthy and caught up with the cluster
+    pub async fn get_health(&self) -> Result<()> {
+        let _: String = self.call("getHealth", json!([])).await?;
+        Ok(())
+    }
+
+    /// Balance of `address` in lamports
+    pub async fn get_balance(&self, address: &str) -> Result<u64> {
+        let response: WithContext<u64> = self
+            .call("getBalance", json!([address, { "commitment": "confirmed" }]))
+            .await?;
+        Ok(response.value)
+    }
+
+    pub async fn get_latest_blockhash(&self) -> Result<LatestBlockhash> {
+        let response: WithContext<LatestBlockhash> = self
+            .call("getLatestBlockhash", json!([{ "commitment": "confirmed" }]))
+            .await?;
+        Ok(response.value)
+    }
+
+    pub async fn is_blockhash_valid(&self, blockhash: &str) -> Result<bool> {
+        let response: WithContext<bool> = self
+            .call("isBlockhashValid", json!([blockhash, { "commitment": "confirmed" }]))
+            .await?;
+        Ok(response.value)
+    }
+
+    /// Raw account data, or `None` if the account does not exist
+    pub async fn get_account_data(&self, address: &str) -> Result<Option<Vec<u8>>> {
+        #[derive(Deserialize)]
+        struct AccountData {
+            data: (String, String),
+        }
+
+        let response: WithContext<Option<AccountData>> = self
+            .call("getAccountInfo", json!([address, { "encoding": "base64", "commitment": "confirmed" }]))
+            .await?;
+
+        response
+            .value
+            .map(|account| {
+                BASE64
+                    .decode(account.data.0)
+                    .map_err(|e| ApiError::Blockchain(format!("Invalid account data encoding: {}", e)))
+            })
+            .transpose()
+    }
+
+    /// Anchor account of type `T`, or `None` if the account does not exist
+    #[cfg(feature = "chain")]
+    pub async fn get_anchor_account<T: AccountDeserialize>(&self, address: &str) -> Result<Option<T>> {
+        self.get_account_data(address)
+            .await?
+            .map(|data| decode_account(address, &data))
+            .transpose()
+    }
+
+    /// Token account holding a balance of `mint`, or `None` if the mint does not exist or
+    /// nothing of it is held
+    pub async fn token_holder_account(&self, mint: &str) -> Result<Option<String>> {
+        #[derive(Deserialize)]
+        struct TokenBalance {
+            address: String,
+            amount: String,
+        }
+
+        if self.get_account_data(mint).await?.is_none() {
+            return Ok(None);
+        }
+        let response: WithContext<Vec<TokenBalance>> = self
+            .call("getTokenLargestAccounts", json!([mint, { "commitment": "confirmed" }]))
+            .await?;
+        Ok(response
+            .value
+            .into_iter()
+            .find(|balance| balance.amount != "0")
+            .map(|balance| balance.address))
+    }
+
+    /// Accounts owned by `program_id` matching every filter
+    pub async fn get_program_accounts(&self, program_id: &str, filters: &[MemcmpFilter]) -> Result<ProgramAccounts> {
+        #[derive(Deserialize)]
+        struct Slot {
+            slot: u64,
+        }
+        #[derive(Deserialize)]
+        struct AccountData {
+            data: (String, String),
+        }
+        #[derive(Deserialize)]
+        struct KeyedAccount {
+            pubkey: String,
+            account: AccountData,
+        }
+        #[derive(Deserialize)]
+        struct Response {
+            context: Slot,
+            value: Vec<KeyedAccount>,
+        }
+
+        let filters: Vec<Value> = filters
+            .iter()
+            .map(|filter| {
+                json!({ "memcmp": {
+                    "offset": filter.offset,
+                    "bytes": bs58::encode(&filter.bytes).into_string(),
+                    "encoding": "base58",
+                } })
+            })
+            .collect();
+        let response: Response = self
+            .call(
+                "getProgramAccounts",
+                json!([program_id, {
+                    "encoding": "base64",
+                    "commitment": "confirmed",
+                    "withContext": true,
+                    "filters": filters,
+                }]),
+            )
+            .await?;
+
+        let accounts = response
+            .value
+            .into_iter()
+            .map(|keyed| {
+                BASE64
+                    .decode(keyed.account.data.0)
+                    .map(|data| (keyed.pubkey, data))
+                    .map_err(|e| ApiError::Blockchain(format!("Invalid account data encoding: {}", e)))
+            })
+            .collect::<Result<_>>()?;
+        Ok(ProgramAccounts {
+            slot: response.context.slot,
+            accounts,
+        })
+    }
+
+    /// Statuses of up to 256 signatures, `None` for signatures the cluster does not know
+    pub async fn get_signature_statuses(&self, signatures: &[String]) -> Result<Vec<Option<SignatureStatus>>> {
+        let response: WithContext<Vec<Option<SignatureStatus>>> = self
+            .call(
+                "getSignatureStatuses",
+                json!([signatures, { "searchTransactionHistory": true }]),
+            )
+            .await?;
+        Ok(response.value)
+    }
+
+    /// Up to `limit` (max 1000) finalized signatures involving `address`, newest first
+    ///
+    /// Pages walk backwards from `before` and stop at `until`, both exclusive.
+    pub async fn get_signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>> {
+        let mut options = json!({ "limit": limit, "commitment": "finalized" });
+        if let Some(before) = before {
+            options["before"] = json!(before);
+        }
+        if let Some(until) = until {
+            options["until"] = json!(until);
+        }
+
+        self.call("getSignaturesForAddress", json!([address, options])).await
+    }
+
+    /// Full finalized transaction as JSON, `None` if the cluster no longer has it
+    pub async fn get_transaction(&self, signature: &str) -> Result<Option<Value>> {
+        self.call_optional(
+            "getTransaction",
+            json!([signature, {
+                "encoding": "json",
+                "commitment": "finalized",
+                "maxSupportedTransactionVersion": 0,
+            }]),
+        )
+        .await
+    }
+
+    /// Lowest prioritization fees that landed transactions writing all of `addresses` in each
+    /// of the last 150 slots
+    pub async fn get_recent_prioritization_fees(&self, addresses: &[String]) -> Result<Vec<PrioritizationFee>> {
+        self.call("getRecentPrioritizationFees", json!([addresses])).await
+    }
+
+    /// Simulate a signed wire-format transaction without submitting it
+    pub async fn simulate_transaction(&self, transaction: &[u8]) -> Result<SimulationResult> {
+        let response: WithContext<SimulationResult> = self
+            .call(
+                "simulateTransaction",
+                json!([BASE64.encode(transaction), { "encoding": "base64", "commitment": "confirmed", "sigVerify": true }]),
+            )
+            .await?;
+        Ok(response.value)
+    }
+
+    /// Submit a signed wire-format transaction and return its signature
+    ///
+    /// The transaction is simulated first, so program errors come back as a typed
+    /// [`BlockchainError`](crate::error::BlockchainError) instead of an opaque RPC failure.
+    pub async fn send_transaction(&self, transaction: &[u8]) -> Result<String> {
+        let simulation = self.simulate_transaction(transaction).await?;
+        if let Some(err) = simulation.err {
+            metrics::counter!("solana_transactions_submitted_total", "outcome" => "rejected").increment(1);
+            return Err(decode_simulation_error(&err, &simulation.logs.unwrap_or_default()).into());
+        }
+
+        let result = self
+            .call(
+                "sendTransaction",
+                json!([BASE64.encode(transaction), { "encoding": "base64", "skipPreflight": true }]),
+            )
+            .await;
+
+        let outcome = if result.is_ok() { "success" } else { "failure" };
+        metrics::counter!("solana_transactions_submitted_total", "outcome" => outcome).increment(1);
+        result
+    }
+
+    /// Record `readings` through the oracle's `submit_meter_readings_batch`, packing as many
+    /// into each transaction as the size and compute limits allow
+    ///
+    /// Each chunk is its own transaction, sent concurrently, so a rejected chunk leaves the
+    /// others recorded. Results follow the order of `readings`, each carrying its chunk's
+    /// signature or error.
+    #[cfg(feature = "chain")]
+    pub async fn submit_meter_readings_batch(
+        &self,
+        program_id: Pubkey,
+        authority: &dyn TxSigner,
+        fee_payer: Option<&dyn TxSigner>,
+        readings: &[MeterReadingInput],
+    ) -> Result<Vec<ReadingSubmission>> {
+        let mut results: Vec<Option<ReadingSubmission>> = vec![None; readings.len()];
+        let mut valid = Vec::with_capacity(readings.len());
+        for (index, reading) in readings.iter().enumerate() {
+            if reading.meter_id.len() > oracle::MeterReading::MAX_METER_ID_LEN {
+                let error = format!("Meter ID is longer than {} bytes", oracle::MeterReading::MAX_METER_ID_LEN);
+                results[index] = Some(ReadingSubmission::new(reading, Err(error)));
+            } else {
+                valid.push(index);
+            }
+        }
+        let batch: Vec<MeterReadingInput> = valid.iter().map(|&index| readings[index].clone()).collect();
+
+        let authority_key = authority.pubkey();
+        let payer_key = fee_payer.map_or(authority_key, |payer| payer.pubkey());
+        let chunks = chunk_readings(program_id, authority_key, payer_key, &batch)?;
+
+        let blockhash = self.recent_blockhash().await?;
+
+        let submissions = chunks.iter().map(|chunk| async {
+            let instructions = reading_batch_instructions(program_id, authority_key, &batch[chunk.clone()])?;
+            let message =
+                Message::new(&instructions, payer_key, blockhash).map_err(ApiError::Internal)?;
+            let bytes = message.serialize();
+            let signatures = sign_transaction(message.signers(), &bytes, authority, fee_payer).await?;
+            self.send_transaction(&serialize_transaction(&signatures, &bytes)).await
+        });
+        let outcomes = futures::future::join_all(submissions).await;
+
+        for (chunk, outcome) in chunks.into_iter().zip(outcomes) {
+            let outcome = outcome.map_err(|e| e.to_string());
+            for position in chunk {
+                let index = valid[position];
+                results[index] = Some(ReadingSubmission::new(&readings[index], outcome.clone()));
+            }
+        }
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    /// Call the oracle's `trigger_market_clearing`, returning the transaction signature
+    #[cfg(feature = "chain")]
+    pub async fn trigger_market_clearing(
+        &self,
+        program_id: Pubkey,
+        authority: &dyn TxSigner,
+        fee_payer: Option<&dyn TxSigner>,
+    ) -> Result<String> {
+        let (oracle_data, _) = Pubkey::find_program_address(&[b"oracle_data"], &program_id)
+            .ok_or_else(|| ApiError::Internal("No program address for seeds".to_string()))?;
+        let authority_key = authority.pubkey();
+        let instruction = anchor_instruction(
+            program_id,
+            oracle::accounts::TriggerMarketClearing {
+                oracle_data: oracle_data.into(),
+                authority: authority_key.into(),
+            },
+            oracle::instruction::TriggerMarketClearing {},
+        );
+
+        let blockhash = self.recent_blockhash().await?;
+        let payer_key = fee_payer.map_or(authority_key, |payer| payer.pubkey());
+        let message = Message::new(&[instruction], payer_key, blockhash).map_err(ApiError::Internal)?;
+        let bytes = message.serialize();
+        let signatures = sign_transaction(message.signers(), &bytes, authority, fee_payer).await?;
+        self.send_transaction(&serialize_transaction(&signatures, &bytes)).await
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        self.call_optional(method, params)
+            .await?
+            .ok_or_else(|| ApiError::Blockchain(format!("Empty RPC response for {}", method)))
+    }
+
+    /// Like `call`, for methods that return `null` when nothing is found
+    ///
+    /// Calls go through the circuit breaker: failed requests, unreadable responses and RPC
+    /// errors count against the node, while rate limiting does not.
+    async fn call_optional<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<Option<T>> {
+        let permit = self.breaker.acquire()?;
+        let started = std::time::Instant::now();
+        let result = self.request(method, params).await;
+        permit.record(matches!(result, Err(ApiError::Blockchain(_))));
+
+        metrics::histogram!("solana_rpc_duration_seconds", "method" => method.to_string())
+            .record(started.elapsed().as_secs_f64());
+        if result.is_err() {
+            metrics::counter!("solana_rpc_errors_total", "method" => method.to_string()).increment(1);
+        }
+        result
+    }
+
+    #[tracing::instrument(name = "solana_rpc", skip(self, params), fields(otel.kind = "client"))]
+    async fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<Option<T>> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let mut builder = self.http.post(&self.rpc_url).json(&request);
+        for (name, value) in telemetry::propagation_headers() {
+            builder = builder.header(name, value);
+        }
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| ApiError::Blockchain(format!("RPC request {} failed: {}", method, e)))?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(ApiError::RateLimit);
+        }
+
+        let response: RpcResponse<T> = response
+            .json()
+            .await
+            .map_err(|e| ApiError::Blockchain(format!("Invalid RPC response for {}: {}", method, e)))?;
+
+        if let Some(error) = response.error {
+            return Err(ApiError::Blockchain(format!(
+                "RPC error {} in {}: {}",
+                error.code, method, error.message
+            )));
+        }
+
+        Ok(response.result)
+    }
+}
+
+/// Check the account discriminator and Borsh-decode the rest of `data` as `T`
+#[cfg(feature = "chain")]
+pub fn decode_anchor_account<T: AccountDeserialize>(data: &[u8]) -> Option<T> {
+    T::try_deserialize(&mut &data[..]).ok()
+}
+
+/// Decode the data of the account at `address`, failing if it is not a `T`
+#[cfg(feature = "chain")]
+pub fn decode_account<T: AccountDeserialize>(address: &str, data: &[u8]) -> Result<T> {
+    decode_anchor_account(data).ok_or_else(|| {
+        ApiError::Blockchain(format!(
+            "Account {} is not a {} in the current layout",
+            address,
+            std::any::type_name::<T>().rsplit("::").next().unwrap_or_default()
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "chain")]
+    #[test]
+    fn test_decode_anchor_account_checks_discriminator() {
+        use anchor_lang::prelude::Pubkey;
+        use anchor_lang::AccountSerialize;
+
+        let oracle_data = oracle::OracleData {
+            authority: Pubkey::new_from_array([1; 32]),
+            gateways: vec![Pubkey::new_from_array([2; 32])],
+            total_readings: 42,
+            last_reading_timestamp: 1_700_000_000,
+            last_clearing: 1_700_000_900,
+            active: true,
+            created_at: 1_600_000_000,
+            max_reading_age: 3600,
+        };
+        let mut data = Vec::new();
+        oracle_data.try_serialize(&mut data).unwrap();
+
+        let decoded: oracle::OracleData = decode_anchor_account(&data).unwrap();
+        assert_eq!(decoded.gateways, oracle_data.gateways);
+        assert_eq!(decoded.total_readings, 42);
+        assert_eq!(decoded.max_reading_age, 3600);
+
+        assert!(decode_anchor_account::<oracle::MeterReading>(&data).is_none());
+        assert!(decode_anchor_account::<oracle::OracleData>(&data[..20]).is_none());
+    }
+
+    fn nonce_lifetime() -> TransactionLifetime {
+        TransactionLifetime::Nonce {
+            account: crate::services::transaction::Pubkey([5; 32]),
+            state: NonceState {
+                authority: crate::services::transaction::Pubkey([6; 32]),
+                nonce: [7; 32],
+            },
+        }
+    }
+
+    #[test]
+    fn test_nonce_lifetimes_advance_the_nonce_first() {
+        let instruction = Instruction {
+            program_id: crate::services::transaction::Pubkey([9; 32]),
+            accounts: Vec::new(),
+            data: vec![1],
+        };
+        let lifetime = nonce_lifetime();
+        let instructions = lifetime.instructions(std::slice::from_ref(&instruction));
+        assert_eq!(lifetime.recent_blockhash(), [7; 32]);
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].data, 4u32.to_le_bytes());
+        assert_eq!(instructions[1], instruction);
+
+        let blockhash = TransactionLifetime::Blockhash {
+            blockhash: [8; 32],
+            last_valid_block_height: 100,
+        };
+        assert_eq!(blockhash.instructions(std::slice::from_ref(&instruction)), vec![instruction]);
+    }
+
+    #[tokio::test]
+    async fn test_cached_nonce_is_used_while_rpc_is_unreachable() {
+        let chain = BlockchainService::new("http://127.0.0.1:9").unwrap();
+        let TransactionLifetime::Nonce { account, state } = nonce_lifetime() else {
+            unreachable!()
+        };
+        assert!(chain.nonce_lifetime(account).await.is_err());
+
+        chain.nonces.lock().unwrap().insert(account, state);
+        assert_eq!(chain.nonce_lifetime(account).await.unwrap(), Some(nonce_lifetime()));
+    }
+
+    #[tokio::test]
+    async fn test_shared_blockhash_is_used_until_stale() {
+        let chain = BlockchainService::new("http://127.0.0.1:9").unwrap();
+        assert!(chain.recent_blockhash().await.is_err());
+
+        let mut cached = CachedBlockhash {
+            blockhash: [7; 32],
+            last_valid_block_height: 1_000,
+            fetched_at: Instant::now(),
+        };
+        *chain.blockhash.lock().unwrap() = Some(cached.clone());
+        assert_eq!(chain.recent_blockhash().await.unwrap(), [7; 32]);
+        assert_eq!(
+            chain.latest_lifetime().await.unwrap(),
+            TransactionLifetime::Blockhash {
+                blockhash: [7; 32],
+                last_valid_block_height: 1_000,
+            }
+        );
+
+        // A stale blockhash is refetched rather than handed out
+        cached.fetched_at -= MAX_BLOCKHASH_AGE;
+        *chain.blockhash.lock().unwrap() = Some(cached);
+        assert!(chain.recent_blockhash().await.is_err());
+    }
+
+    #[cfg(feature = "chain")]
+    #[test]
+    fn test_reading_batches_fit_one_transaction() {
+        let program_id = crate::services::transaction::Pubkey([3; 32]);
+        let authority = crate::services::transaction::Pubkey([4; 32]);
+        let fee_payer = crate::services::transaction::Pubkey([5; 32]);
+        let readings: Vec<MeterReadingInput> = (0..50)
+            .map(|i| MeterReadingInput {
+                meter_id: format!("DORM-A-METER-{:04}", i),
+                energy_produced: 1_500,
+                energy_consumed: 900,
+                reading_timestamp: 1_700_000_000,
+            })
+            .collect();
+
+        let chunks = chunk_readings(program_id, authority, fee_payer, &readings).unwrap();
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.first().unwrap().start, 0);
+        assert_eq!(chunks.last().unwrap().end, readings.len());
+        for (chunk, next) in chunks.iter().zip(chunks.iter().skip(1)) {
+            assert_eq!(chunk.end, next.start);
+            // A chunk stops only where one more reading would not fit
+            let grown = reading_batch_instructions(program_id, authority, &readings[chunk.start..=chunk.end]).unwrap();
+            assert!(transaction_size(&grown, fee_payer).unwrap() > MAX_TRANSACTION_SIZE);
+        }
+        for chunk in &chunks {
+            assert!(chunk.len() <= oracle::OracleData::MAX_BATCH_READINGS);
+            let instructions = reading_batch_instructions(program_id, authority, &readings[chunk.clone()]).unwrap();
+            assert!(transaction_size(&instructions, fee_payer).unwrap() <= MAX_TRANSACTION_SIZE);
+            assert_eq!(instructions[1].accounts.len(), 3 + chunk.len());
+        }
+    }
+}
@@ -0,0 +1,481 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::tenant::{TenantScope, CERTIFICATE_TENANT, READING_TENANT};
+use crate::database::schema::types::ErcStatus;
+use crate::error::{ApiError, Result};
+use crate::models::carbon::{CarbonReport, CarbonRollup, CarbonRollupRun, EmissionFactor, SourceEmissions};
+use crate::services::scheduler::{spawn_daily, DailySchedule};
+use crate::utils::clock::{month_of, SharedClock};
+use crate::AppState;
+
+pub const FACTOR_COLUMNS: &str = "renewable_source, kg_co2e_per_kwh, reference, updated_by, updated_at";
+
+pub const ROLLUP_COLUMNS: &str = "month, scope, scope_id, renewable_source, generated_kwh, certified_kwh, \
+    avoided_kg_co2e, certified_avoided_kg_co2e, grid_kg_co2e_per_kwh, source_kg_co2e_per_kwh, computed_at";
+
+/// Factor of the grid electricity that renewable generation displaces
+pub const GRID: &str = "grid";
+
+/// Most rollups returned by one request
+pub const MAX_ROLLUP_LIMIT: i64 = 1_000;
+
+/// Longest window one report may cover
+pub const MAX_REPORT_DAYS: i64 = 366;
+
+/// Tenant of a rollup row aliased `r`: its prosumer's, or any of its building's meters'
+const ROLLUP_TENANT_CONDITION: &str = "($1::boolean
+     OR (r.scope = 'prosumer' AND (SELECT u.tenant_id FROM users u WHERE u.id::text = r.scope_id) IS NOT DISTINCT FROM $2::varchar)
+     OR (r.scope = 'building' AND EXISTS (
+         SELECT 1 FROM meters m WHERE m.building = r.scope_id AND m.tenant_id IS NOT DISTINCT FROM $2::varchar)))";
+
+/// Whose generation a report covers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CarbonScope {
+    Prosumer(Uuid),
+    Building(String),
+    Campus,
+}
+
+impl CarbonScope {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Prosumer(_) => CarbonRollup::PROSUMER,
+            Self::Building(_) => CarbonRollup::BUILDING,
+            Self::Campus => CarbonRollup::CAMPUS,
+        }
+    }
+
+    pub fn id(&self) -> String {
+        match self {
+            Self::Prosumer(user_id) => user_id.to_string(),
+            Self::Building(building) => building.clone(),
+            Self::Campus => String::new(),
+        }
+    }
+
+    /// Reading and certificate attribution of a scope kind: the key readings are grouped by,
+    /// and the certificates joined with the key they are grouped by
+    fn attribution(kind: &str) -> (&'static str, &'static str, &'static str) {
+        match kind {
+            CarbonRollup::PROSUMER => (
+                "COALESCE(ma.user_id, m.user_id)::text",
+                "JOIN erc_auto_issuances a ON a.certificate_id = c.certificate_id",
+                "a.user_id::text",
+            ),
+            // Automatically issued certificates are shared between the buildings of the meters
+            // behind them in proportion to each meter's net generation
+            CarbonRollup::BUILDING => (
+                "COALESCE(ma.building, m.building)",
+                "JOIN erc_auto_issuances a ON a.certificate_id = c.certificate_id
+                 CROSS JOIN LATERAL jsonb_array_elements(a.provenance) p
+                 JOIN meters pm ON pm.meter_id = p->>'meter_id'",
+                "pm.building",
+            ),
+            _ => ("''::text", "", "''::text"),
+        }
+    }
+}
+
+/// Emission factors by source, with the grid's
+#[derive(Debug, Clone)]
+pub struct EmissionFactors {
+    grid: f64,
+    sources: HashMap<String, f64>,
+}
+
+impl EmissionFactors {
+    pub fn new(factors: &[EmissionFactor]) -> Result<Self> {
+        let mut sources: HashMap<String, f64> = factors
+            .iter()
+            .map(|factor| (factor.renewable_source.clone(), factor.kg_co2e_per_kwh))
+            .collect();
+        let grid = sources
+            .remove(GRID)
+            .ok_or_else(|| ApiError::Configuration("No grid emission factor is set".to_string()))?;
+        Ok(Self { grid, sources })
+    }
+
+    pub fn grid(&self) -> f64 {
+        self.grid
+    }
+
+    /// Emissions avoided by `generated_kwh` of `source`, of which `certified_kwh` are backed
+    /// by certificates; sources without a factor avoid nothing
+    pub fn emissions(&self, source: &str, generated_kwh: f64, certified_kwh: f64) -> SourceEmissions {
+        let factor = self.sources.get(source).copied();
+        let avoided_per_kwh = factor.map_or(0.0, |factor| (self.grid - factor).max(0.0));
+        SourceEmissions {
+            renewable_source: source.to_string(),
+            generated_kwh,
+            certified_kwh,
+            avoided_kg_co2e: generated_kwh * avoided_per_kwh,
+            certified_avoided_kg_co2e: certified_kwh * avoided_per_kwh,
+            source_kg_co2e_per_kwh: factor,
+        }
+    }
+}
+
+/// Generated or certified kWh of one source for one prosumer, building or the campus
+#[derive(Debug, sqlx::FromRow)]
+struct SourceEnergy {
+    scope_id: String,
+    renewable_source: String,
+    kwh: f64,
+}
+
+/// Generated and certified kWh by scope ID and source
+type EnergyBySource = BTreeMap<(String, String), (f64, f64)>;
+
+/// Validate an emission factor before storing it
+pub fn validate_factor(source: &str, kg_co2e_per_kwh: f64) -> Result<()> {
+    if source.is_empty()
+        || source.len() > 32
+        || !source.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(ApiError::BadRequest(
+            "Source must be 1-32 lowercase letters, digits or underscores".to_string(),
+        ));
+    }
+    if !kg_co2e_per_kwh.is_finite() || !(0.0..=10.0).contains(&kg_co2e_per_kwh) {
+        return Err(ApiError::BadRequest(
+            "kg_co2e_per_kwh must be between 0 and 10".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check a report window and cap its length
+pub fn validate_window(from: DateTime<Utc>, to: DateTime<Utc>) -> Result<()> {
+    if to <= from {
+        return Err(ApiError::BadRequest("to must be after from".to_string()));
+    }
+    if to - from > Duration::days(MAX_REPORT_DAYS) {
+        return Err(ApiError::BadRequest(format!(
+            "Reports cover at most {} days; use the monthly rollups for longer periods",
+            MAX_REPORT_DAYS
+        )));
+    }
+    Ok(())
+}
+
+fn start_of(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).expect("midnight exists").and_utc()
+}
+
+/// Avoided CO2e of campus generation from its meter readings and certificates
+#[derive(Clone)]
+pub struct CarbonService {
+    db: PgPool,
+    clock: SharedClock,
+}
+
+impl CarbonService {
+    pub fn new(db: PgPool, clock: SharedClock) -> Self {
+        Self { db, clock }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone(), state.clock.clone())
+    }
+
+    pub async fn factors(&self) -> Result<Vec<EmissionFactor>> {
+        let query = format!("SELECT {} FROM emission_factors ORDER BY renewable_source", FACTOR_COLUMNS);
+        Ok(sqlx::query_as::<_, EmissionFactor>(&query).fetch_all(&self.db).await?)
+    }
+
+    /// Set the factor of a source or of the grid; reports and rollups computed afterwards use it
+    pub async fn set_factor(
+        &self,
+        source: &str,
+        kg_co2e_per_kwh: f64,
+        reference: Option<&str>,
+        updated_by: Uuid,
+    ) -> Result<EmissionFactor> {
+        validate_factor(source, kg_co2e_per_kwh)?;
+        let query = format!(
+            "INSERT INTO emission_factors (renewable_source, kg_co2e_per_kwh, reference, updated_by)
+             VALUES ($1, $2, COALESCE($3, ''), $4)
+             ON CONFLICT (renewable_source) DO UPDATE
+                 SET kg_co2e_per_kwh = EXCLUDED.kg_co2e_per_kwh,
+                     reference = COALESCE($3, emission_factors.reference),
+                     updated_by = EXCLUDED.updated_by
+             RETURNING {}",
+            FACTOR_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, EmissionFactor>(&query)
+            .bind(source)
+            .bind(kg_co2e_per_kwh)
+            .bind(reference)
+            .bind(updated_by)
+            .fetch_one(&self.db)
+            .await?)
+    }
+
+    /// Avoided emissions of `scope` over `[from, to)`, counting only readings and certificates
+    /// visible in `tenant`
+    pub async fn report(
+        &self,
+        scope: &CarbonScope,
+        tenant: &TenantScope,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<CarbonReport> {
+        validate_window(from, to)?;
+        let factors = EmissionFactors::new(&self.factors().await?)?;
+        let scope_id = scope.id();
+        let energy = self.energy(scope.kind(), Some(&scope_id), tenant, from, to).await?;
+
+        let sources: Vec<SourceEmissions> = energy
+            .into_iter()
+            .map(|((_, source), (generated, certified))| factors.emissions(&source, generated, certified))
+            .collect();
+        Ok(CarbonReport {
+            scope: scope.kind().to_string(),
+            scope_id,
+            from,
+            to,
+            grid_kg_co2e_per_kwh: factors.grid(),
+            generated_kwh: sources.iter().map(|s| s.generated_kwh).sum(),
+            certified_kwh: sources.iter().map(|s| s.certified_kwh).sum(),
+            avoided_kg_co2e: sources.iter().map(|s| s.avoided_kg_co2e).sum(),
+            certified_avoided_kg_co2e: sources.iter().map(|s| s.certified_avoided_kg_co2e).sum(),
+            sources,
+        })
+    }
+
+    /// Generated and certified kWh over `[from, to)` of every prosumer, building or the campus,
+    /// or only of `scope_id`
+    ///
+    /// Generation is read from the relational readings, attributed through meter assignments
+    /// and, for provisioned meters, the meter's owner and building. Grid and battery meters
+    /// generate nothing renewable; meters the gateway did not provision are counted as solar.
+    async fn energy(
+        &self,
+        kind: &str,
+        scope_id: Option<&str>,
+        tenant: &TenantScope,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<EnergyBySource> {
+        let (reading_key, certificate_join, certificate_key) = CarbonScope::attribution(kind);
+        let certified_kwh = if kind == CarbonRollup::BUILDING {
+            "SUM(c.energy_amount * (p->>'net_kwh')::float8 / NULLIF(a.net_generation_kwh, 0))"
+        } else {
+            "SUM(c.energy_amount)"
+        };
+
+        let generated = format!(
+            "SELECT {key} AS scope_id, COALESCE(m.meter_type, 'solar')::text AS renewable_source,
+                    SUM(energy_readings.energy_generated)::float8 AS kwh
+             FROM energy_readings
+             LEFT JOIN meter_assignments ma ON ma.meter_id = energy_readings.meter_id
+                 AND energy_readings.timestamp >= ma.assigned_at
+                 AND (ma.deactivated_at IS NULL OR energy_readings.timestamp < ma.deactivated_at)
+             LEFT JOIN meters m ON m.meter_id = energy_readings.meter_id
+             WHERE energy_readings.timestamp >= $1 AND energy_readings.timestamp < $2
+               AND COALESCE(m.meter_type, 'solar') NOT IN ('grid', 'battery')
+               AND {key} IS NOT NULL AND ($3::text IS NULL OR {key} = $3)
+               AND {tenant}
+             GROUP BY 1, 2",
+            key = reading_key,
+            tenant = TenantScope::condition(READING_TENANT, 4)
+        );
+        let certified = format!(
+            "SELECT {key} AS scope_id, c.renewable_source::text AS renewable_source, {kwh}::float8 AS kwh
+             FROM erc_certificates c
+             {join}
+             WHERE c.issued_at >= $1 AND c.issued_at < $2 AND c.status <> $6
+               AND {key} IS NOT NULL AND ($3::text IS NULL OR {key} = $3)
+               AND {tenant}
+             GROUP BY 1, 2",
+            key = certificate_key,
+            kwh = certified_kwh,
+            join = certificate_join,
+            tenant = TenantScope::condition(CERTIFICATE_TENANT, 4)
+        );
+
+        let generated = sqlx::query_as::<_, SourceEnergy>(&generated)
+            .bind(from)
+            .bind(to)
+            .bind(scope_id)
+            .bind(tenant.is_campus())
+            .bind(tenant.tenant())
+            .fetch_all(&self.db)
+            .await?;
+        let certified = sqlx::query_as::<_, SourceEnergy>(&certified)
+            .bind(from)
+            .bind(to)
+            .bind(scope_id)
+            .bind(tenant.is_campus())
+            .bind(tenant.tenant())
+            .bind(ErcStatus::Revoked)
+            .fetch_all(&self.db)
+            .await?;
+
+        let mut energy = EnergyBySource::new();
+        for row in generated {
+            energy.entry((row.scope_id, row.renewable_source)).or_default().0 += row.kwh;
+        }
+        for row in certified {
+            energy.entry((row.scope_id, row.renewable_source)).or_default().1 += row.kwh;
+        }
+        Ok(energy)
+    }
+
+    /// Compute and store the rollups of every prosumer, building and the campus for the month
+    /// `date` falls in, replacing any computed before
+    pub async fn rollup(&self, date: NaiveDate) -> Result<CarbonRollupRun> {
+        let (start, end) = month_of(date);
+        let factors = EmissionFactors::new(&self.factors().await?)?;
+        let computed_at = self.clock.now();
+
+        let mut tx = self.db.begin().await?;
+        sqlx::query("DELETE FROM carbon_rollups WHERE month = $1")
+            .bind(start)
+            .execute(&mut *tx)
+            .await?;
+
+        let mut rollups = 0;
+        for kind in CarbonRollup::SCOPES {
+            let energy = self
+                .energy(kind, None, &TenantScope::Campus, start_of(start), start_of(end))
+                .await?;
+            for ((scope_id, source), (generated, certified)) in energy {
+                let emissions = factors.emissions(&source, generated, certified);
+                sqlx::query(
+                    "INSERT INTO carbon_rollups (month, scope, scope_id, renewable_source, generated_kwh, certified_kwh,
+                         avoided_kg_co2e, certified_avoided_kg_co2e, grid_kg_co2e_per_kwh, source_kg_co2e_per_kwh,
+                         computed_at)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+                )
+                .bind(start)
+                .bind(kind)
+                .bind(&scope_id)
+                .bind(&source)
+                .bind(emissions.generated_kwh)
+                .bind(emissions.certified_kwh)
+                .bind(emissions.avoided_kg_co2e)
+                .bind(emissions.certified_avoided_kg_co2e)
+                .bind(factors.grid())
+                .bind(emissions.source_kg_co2e_per_kwh)
+                .bind(computed_at)
+                .execute(&mut *tx)
+                .await?;
+                rollups += 1;
+            }
+        }
+        tx.commit().await?;
+
+        tracing::info!("Computed {} carbon rollups for {}", rollups, start);
+        Ok(CarbonRollupRun {
+            month: start,
+            rollups,
+            computed_at,
+        })
+    }
+
+    /// Stored rollups visible in `tenant`, newest month first
+    pub async fn rollups(
+        &self,
+        scope: Option<&str>,
+        scope_id: Option<&str>,
+        month: Option<NaiveDate>,
+        tenant: &TenantScope,
+        limit: i64,
+    ) -> Result<Vec<CarbonRollup>> {
+        if let Some(scope) = scope.filter(|scope| !CarbonRollup::SCOPES.contains(scope)) {
+            return Err(ApiError::BadRequest(format!(
+                "Unknown scope {}; expected prosumer, building or campus",
+                scope
+            )));
+        }
+        let query = format!(
+            "SELECT {} FROM carbon_rollups r
+             WHERE {}
+               AND ($3::text IS NULL OR r.scope = $3)
+               AND ($4::text IS NULL OR r.scope_id = $4)
+               AND ($5::date IS NULL OR r.month = $5)
+             ORDER BY r.month DESC, r.scope, r.scope_id, r.renewable_source
+             LIMIT $6",
+            ROLLUP_COLUMNS, ROLLUP_TENANT_CONDITION
+        );
+        Ok(sqlx::query_as::<_, CarbonRollup>(&query)
+            .bind(tenant.is_campus())
+            .bind(tenant.tenant())
+            .bind(scope)
+            .bind(scope_id)
+            .bind(month.map(|month| month_of(month).0))
+            .bind(limit.clamp(1, MAX_ROLLUP_LIMIT))
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Recompute the last month's rollups daily at `schedule`, so readings arriving late in
+    /// the following month are still counted
+    pub fn spawn(self, schedule: DailySchedule) {
+        let clock = self.clock.clone();
+        spawn_daily("carbon_rollup", schedule, clock, move || {
+            let service = self.clone();
+            async move {
+                let last_month = month_of(service.clock.now().date_naive()).0 - Duration::days(1);
+                service.rollup(last_month).await.map(|_| ())
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn factor(source: &str, kg_co2e_per_kwh: f64) -> EmissionFactor {
+        EmissionFactor {
+            renewable_source: source.to_string(),
+            kg_co2e_per_kwh,
+            reference: String::new(),
+            updated_by: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_generation_avoids_the_grid_factor_less_its_own() {
+        let factors = EmissionFactors::new(&[factor(GRID, 0.5), factor("solar", 0.04), factor("biomass", 0.6)]).unwrap();
+
+        let solar = factors.emissions("solar", 1000.0, 250.0);
+        assert!((solar.avoided_kg_co2e - 460.0).abs() < 1e-9);
+        assert!((solar.certified_avoided_kg_co2e - 115.0).abs() < 1e-9);
+        assert_eq!(solar.source_kg_co2e_per_kwh, Some(0.04));
+
+        // Dirtier than the grid avoids nothing rather than adding emissions
+        assert_eq!(factors.emissions("biomass", 100.0, 0.0).avoided_kg_co2e, 0.0);
+
+        let unknown = factors.emissions("tidal", 100.0, 0.0);
+        assert_eq!(unknown.avoided_kg_co2e, 0.0);
+        assert_eq!(unknown.source_kg_co2e_per_kwh, None);
+    }
+
+    #[test]
+    fn test_factors_need_the_grid() {
+        assert!(matches!(
+            EmissionFactors::new(&[factor("solar", 0.04)]),
+            Err(ApiError::Configuration(_))
+        ));
+        assert!(validate_factor("solar", 0.041).is_ok());
+        assert!(validate_factor("Solar", 0.041).is_err());
+        assert!(validate_factor("solar", -1.0).is_err());
+        assert!(validate_factor("solar", f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_report_windows_are_bounded() {
+        let from = Utc::now();
+        assert!(validate_window(from, from + Duration::days(31)).is_ok());
+        assert!(validate_window(from, from).is_err());
+        assert!(validate_window(from, from + Duration::days(MAX_REPORT_DAYS + 1)).is_err());
+    }
+}
@@ -0,0 +1,434 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::auth::tenant::{TenantScope, CERTIFICATE_TENANT};
+use crate::database::history::{as_of_condition, source_table};
+use crate::database::schema::types::ErcStatus;
+use crate::error::{ApiError, Result};
+use crate::models::erc::{ErcCertificate, ErcListing, ErcPage};
+use crate::services::blockchain::BlockchainService;
+use crate::utils::cursor;
+use crate::AppState;
+
+// Listing certificates before the indexer has caught up decodes the governance program's
+// accounts, which needs its crate
+#[cfg(feature = "chain")]
+use std::collections::HashMap;
+
+#[cfg(feature = "chain")]
+use anchor_lang::Space;
+
+#[cfg(feature = "chain")]
+use crate::services::blockchain::{decode_anchor_account, MemcmpFilter};
+#[cfg(feature = "chain")]
+use crate::services::program_logs::renewable_source_name;
+#[cfg(feature = "chain")]
+use crate::services::transaction::anchor_account_discriminator;
+
+pub const ERC_COLUMNS: &str = "certificate_id, account_address, authority, energy_amount, renewable_source, \
+    validation_data, status, validated_for_trading, source_readings, issued_at, expires_at, \
+    trading_validated_at, slot, updated_at";
+
+/// Stored status, with valid certificates past their expiry reported as expired
+///
+/// Expiry is only recorded on-chain once someone calls `expire_erc`; this derives it as the
+/// program's `get_erc_status` view does.
+pub fn effective_status(status: ErcStatus, expires_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> ErcStatus {
+    if status == ErcStatus::Valid && expires_at.is_some_and(|expiry| expiry <= now) {
+        ErcStatus::Expired
+    } else {
+        status
+    }
+}
+
+/// Which certificates a listing returns
+#[derive(Debug, Clone, Default)]
+pub struct CertificateFilter {
+    /// Effective status, so `expired` includes lapsed certificates never expired on-chain
+    pub status: Option<ErcStatus>,
+    /// Renewable source name, e.g. `solar`
+    pub source: Option<String>,
+    pub issued_after: Option<DateTime<Utc>>,
+    /// Issue time and ID of the last certificate on the previous page
+    pub after: Option<(DateTime<Utc>, String)>,
+    pub limit: i64,
+}
+
+#[cfg(feature = "chain")]
+impl CertificateFilter {
+    /// Whether `certificate`, decoded from the chain, belongs in the listing at `now`
+    fn matches(&self, certificate: &ErcCertificate, now: DateTime<Utc>) -> bool {
+        self.status
+            .is_none_or(|status| effective_status(certificate.status, certificate.expires_at, now) == status)
+            && self
+                .source
+                .as_deref()
+                .is_none_or(|source| certificate.renewable_source == source)
+            && self
+                .issued_after
+                .is_none_or(|issued_after| certificate.issued_at > issued_after)
+            && self.after.as_ref().is_none_or(|(time, id)| {
+                (certificate.issued_at, certificate.certificate_id.as_str()) < (*time, id.as_str())
+            })
+    }
+}
+
+/// Canonical name of a renewable source filter and the variant byte it is stored as
+#[cfg(feature = "chain")]
+fn source_filter(source: &str) -> (String, u8) {
+    let source = governance::RenewableSource::from_name(source.trim());
+    let variant = match source {
+        governance::RenewableSource::Solar => 0,
+        governance::RenewableSource::Wind => 1,
+        governance::RenewableSource::Biomass => 2,
+        governance::RenewableSource::Hydro => 3,
+        governance::RenewableSource::Other(_) => 4,
+    };
+    (renewable_source_name(source), variant)
+}
+
+/// Decode the governance program's `ErcCertificate` account at `address`, read at `slot`
+#[cfg(feature = "chain")]
+fn decode_certificate(address: &str, data: &[u8], slot: u64, read_at: DateTime<Utc>) -> Option<ErcCertificate> {
+    // Certificates issued before trade locks and extensions were tracked are shorter; the
+    // missing trailing fields read as absent once zero-filled
+    let mut data = data.to_vec();
+    let len = 8 + governance::ErcCertificate::INIT_SPACE;
+    if data.len() < len {
+        data.resize(len, 0);
+    }
+    let certificate: governance::ErcCertificate = decode_anchor_account(&data)?;
+    let timestamp = |seconds: i64| DateTime::<Utc>::from_timestamp(seconds, 0);
+
+    Some(ErcCertificate {
+        certificate_id: certificate.certificate_id,
+        account_address: address.to_string(),
+        authority: certificate.authority.to_string(),
+        energy_amount: i64::try_from(certificate.energy_amount).ok()?,
+        renewable_source: renewable_source_name(certificate.renewable_source),
+        validation_data: certificate.validation_data,
+        status: ErcStatus::from_code(certificate.status as u8),
+        validated_for_trading: certificate.validated_for_trading,
+        source_readings: certificate
+            .source_readings
+            .iter()
+            .map(|reading| serde_json::Value::String(reading.to_string()))
+            .collect(),
+        issued_at: timestamp(certificate.issued_at)?,
+        expires_at: certificate.expires_at.and_then(timestamp),
+        trading_validated_at: certificate.trading_validated_at.and_then(timestamp),
+        slot: i64::try_from(slot).unwrap_or(i64::MAX),
+        updated_at: read_at,
+    })
+}
+
+/// Split rows fetched with one beyond `limit` into a page and the cursor of the next one
+fn page(mut certificates: Vec<ErcCertificate>, limit: i64, indexed: bool, now: DateTime<Utc>) -> ErcPage {
+    let limit = usize::try_from(limit).unwrap_or(0);
+    let next_page = (certificates.len() > limit)
+        .then(|| {
+            certificates.truncate(limit);
+            certificates
+                .last()
+                .map(|certificate| cursor::encode(certificate.issued_at, &certificate.certificate_id))
+        })
+        .flatten();
+
+    ErcPage {
+        certificates: certificates
+            .into_iter()
+            .map(|certificate| ErcListing {
+                effective_status: effective_status(certificate.status, certificate.expires_at, now),
+                certificate,
+            })
+            .collect(),
+        next_page,
+        indexed,
+    }
+}
+
+/// Reads ERC certificates from the indexer database or the governance program itself
+#[derive(Clone)]
+pub struct CertificateStore {
+    db: PgPool,
+    #[cfg_attr(not(feature = "chain"), allow(dead_code))]
+    chain: BlockchainService,
+    program_id: String,
+}
+
+impl CertificateStore {
+    pub fn new(db: PgPool, chain: BlockchainService, program_id: String) -> Self {
+        Self { db, chain, program_id }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(
+            state.db.clone(),
+            state.blockchain_service.clone(),
+            state.config.governance_program_id.clone(),
+        )
+    }
+
+    /// Indexed certificate, optionally as it was at `as_of`
+    pub async fn get(&self, certificate_id: &str, as_of: Option<DateTime<Utc>>) -> Result<ErcCertificate> {
+        let mut query = format!(
+            "SELECT {} FROM {} WHERE certificate_id = $1",
+            ERC_COLUMNS,
+            source_table("erc_certificates", as_of)
+        );
+        if as_of.is_some() {
+            query.push_str(&format!(" AND {}", as_of_condition(2)));
+        }
+
+        let mut sqlx_query = sqlx::query_as::<_, ErcCertificate>(&query).bind(certificate_id);
+        if let Some(as_of) = as_of {
+            sqlx_query = sqlx_query.bind(as_of);
+        }
+
+        sqlx_query
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to fetch ERC certificate {}: {}", certificate_id, e);
+                ApiError::Database(e)
+            })?
+            .ok_or_else(|| ApiError::NotFound(format!("ERC certificate {} not found", certificate_id)))
+    }
+
+    /// Certificates matching `filter` visible in `scope`, newest first, from the indexer
+    /// database once it has indexed the governance program and from the program's accounts
+    /// until then
+    pub async fn list(&self, filter: &CertificateFilter, scope: &TenantScope, now: DateTime<Utc>) -> Result<ErcPage> {
+        #[cfg(feature = "chain")]
+        let filter = &CertificateFilter {
+            source: filter.source.as_deref().map(|source| source_filter(source).0),
+            ..filter.clone()
+        };
+
+        let indexed: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM indexed_transactions WHERE address = $1)")
+            .bind(&self.program_id)
+            .fetch_one(&self.db)
+            .await?;
+        #[cfg(feature = "chain")]
+        let certificates = if indexed {
+            self.list_indexed(filter, scope, now).await?
+        } else {
+            self.list_on_chain(filter, scope, now).await?
+        };
+        #[cfg(not(feature = "chain"))]
+        let certificates = self.list_indexed(filter, scope, now).await?;
+        Ok(page(certificates, filter.limit, indexed, now))
+    }
+
+    async fn list_indexed(
+        &self,
+        filter: &CertificateFilter,
+        scope: &TenantScope,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ErcCertificate>> {
+        let (after_time, after_id) = filter.after.clone().unzip();
+        let query = format!(
+            "SELECT {} FROM erc_certificates c
+             WHERE ($1::smallint IS NULL OR
+                    CASE WHEN status = $2 AND expires_at <= $3 THEN $4 ELSE status END = $1)
+               AND ($5::varchar IS NULL OR renewable_source = $5)
+               AND ($6::timestamptz IS NULL OR issued_at > $6)
+               AND ($7::timestamptz IS NULL OR (issued_at, certificate_id) < ($7, $8))
+               AND {}
+             ORDER BY issued_at DESC, certificate_id DESC
+             LIMIT $9",
+            ERC_COLUMNS,
+            TenantScope::condition(CERTIFICATE_TENANT, 10)
+        );
+        Ok(sqlx::query_as::<_, ErcCertificate>(&query)
+            .bind(filter.status)
+            .bind(ErcStatus::Valid)
+            .bind(now)
+            .bind(ErcStatus::Expired)
+            .bind(filter.source.as_deref())
+            .bind(filter.issued_after)
+            .bind(after_time)
+            .bind(after_id)
+            .bind(filter.limit + 1)
+            .bind(scope.is_campus())
+            .bind(scope.tenant())
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Tenant of every certificate issued for one
+    #[cfg(feature = "chain")]
+    async fn certificate_tenants(&self) -> Result<HashMap<String, String>> {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT certificate_id, tenant_id FROM erc_certificate_tenants")
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Record the tenant a certificate is being issued for; the first issuance of an ID keeps it
+    pub async fn assign_tenant(&self, certificate_id: &str, tenant: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO erc_certificate_tenants (certificate_id, tenant_id) VALUES ($1, $2)
+             ON CONFLICT (certificate_id) DO NOTHING",
+        )
+        .bind(certificate_id)
+        .bind(tenant)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Decode the governance program's certificate accounts, narrowed by renewable source
+    /// on the RPC node and by everything else here
+    #[cfg(feature = "chain")]
+    async fn list_on_chain(
+        &self,
+        filter: &CertificateFilter,
+        scope: &TenantScope,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ErcCertificate>> {
+        let tenants = match scope {
+            TenantScope::Campus => HashMap::new(),
+            TenantScope::Tenant(_) => self.certificate_tenants().await?,
+        };
+        let mut filters = vec![MemcmpFilter {
+            offset: 0,
+            bytes: anchor_account_discriminator("ErcCertificate").to_vec(),
+        }];
+        if let Some(source) = &filter.source {
+            filters.push(MemcmpFilter {
+                offset: governance::ErcCertificate::RENEWABLE_SOURCE_OFFSET,
+                bytes: vec![source_filter(source).1],
+            });
+        }
+        let program_accounts = self.chain.get_program_accounts(&self.program_id, &filters).await?;
+
+        let mut certificates: Vec<ErcCertificate> = program_accounts
+            .accounts
+            .iter()
+            .filter_map(|(address, data)| {
+                let certificate = decode_certificate(address, data, program_accounts.slot, now);
+                if certificate.is_none() {
+                    tracing::warn!("Skipping undecodable ERC certificate account {}", address);
+                }
+                certificate
+            })
+            .filter(|certificate| filter.matches(certificate, now))
+            .filter(|certificate| scope.allows(tenants.get(&certificate.certificate_id).map(String::as_str)))
+            .collect();
+        certificates.sort_by(|a, b| (b.issued_at, &b.certificate_id).cmp(&(a.issued_at, &a.certificate_id)));
+        certificates.truncate(usize::try_from(filter.limit + 1).unwrap_or(0));
+        Ok(certificates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn time(seconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(seconds, 0).unwrap()
+    }
+
+    #[cfg(feature = "chain")]
+    fn account(certificate_id: &str, source: governance::RenewableSource, expires_at: Option<i64>) -> Vec<u8> {
+        use anchor_lang::prelude::Pubkey;
+        use anchor_lang::AccountSerialize;
+
+        let certificate = governance::ErcCertificate {
+            renewable_source: source,
+            certificate_id: certificate_id.to_string(),
+            authority: Pubkey::new_from_array([7; 32]),
+            energy_amount: 1_500,
+            validation_data: "meter MTR-1".to_string(),
+            issued_at: 1_700_000_000,
+            expires_at,
+            status: governance::ErcStatus::Valid,
+            validated_for_trading: false,
+            trading_validated_at: None,
+            source_readings: vec![Pubkey::new_from_array([9; 32])],
+            trade_lock: None,
+            extensions: Vec::new(),
+            attested_by: None,
+            challenge: None,
+            validated_by: None,
+        };
+        let mut data = Vec::new();
+        certificate.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_effective_status() {
+        let now = time(1_800_000_000);
+        assert_eq!(effective_status(ErcStatus::Valid, Some(now), now), ErcStatus::Expired);
+        assert_eq!(effective_status(ErcStatus::Valid, Some(time(1_800_000_001)), now), ErcStatus::Valid);
+        assert_eq!(effective_status(ErcStatus::Valid, None, now), ErcStatus::Valid);
+        assert_eq!(effective_status(ErcStatus::Revoked, Some(time(0)), now), ErcStatus::Revoked);
+    }
+
+    #[cfg(feature = "chain")]
+    #[test]
+    fn test_source_filters() {
+        assert_eq!(source_filter("Solar"), ("solar".to_string(), 0));
+        assert_eq!(source_filter("hydro"), ("hydro".to_string(), 3));
+        assert_eq!(source_filter("geothermal"), ("geothermal".to_string(), 4));
+        let data = account("ERC-1", governance::RenewableSource::Wind, None);
+        assert_eq!(data[governance::ErcCertificate::RENEWABLE_SOURCE_OFFSET], 1);
+    }
+
+    #[cfg(feature = "chain")]
+    #[test]
+    fn test_decode_certificate_accounts() {
+        let read_at = time(1_800_000_000);
+        let data = account("ERC-1", governance::RenewableSource::Other("geothermal".to_string()), Some(1_750_000_000));
+        let certificate = decode_certificate("Cert1111", &data, 42, read_at).unwrap();
+
+        assert_eq!(certificate.certificate_id, "ERC-1");
+        assert_eq!(certificate.renewable_source, "geothermal");
+        assert_eq!(certificate.status, ErcStatus::Valid);
+        assert_eq!(certificate.expires_at, Some(time(1_750_000_000)));
+        assert_eq!(certificate.source_readings.as_array().map(Vec::len), Some(1));
+        assert_eq!(certificate.slot, 42);
+
+        // Accounts allocated before trade locks and extensions end after the source readings
+        let legacy = &data[..data.len() - 1 - 4];
+        assert_eq!(decode_certificate("Cert1111", legacy, 42, read_at).unwrap().certificate_id, "ERC-1");
+        assert!(decode_certificate("Cert1111", &data[8..], 42, read_at).is_none());
+    }
+
+    #[cfg(feature = "chain")]
+    #[test]
+    fn test_chain_filter_and_pages() {
+        let now = time(1_800_000_000);
+        let data = account("ERC-1", governance::RenewableSource::Solar, Some(1_750_000_000));
+        let certificate = decode_certificate("Cert1111", &data, 42, now).unwrap();
+        let filter = |status, issued_after| CertificateFilter {
+            status,
+            issued_after,
+            limit: 10,
+            ..Default::default()
+        };
+
+        assert!(filter(Some(ErcStatus::Expired), None).matches(&certificate, now));
+        assert!(!filter(Some(ErcStatus::Valid), None).matches(&certificate, now));
+        assert!(!filter(None, Some(time(1_700_000_000))).matches(&certificate, now));
+        let after = CertificateFilter {
+            after: Some((time(1_700_000_000), "ERC-2".to_string())),
+            limit: 10,
+            ..Default::default()
+        };
+        assert!(after.matches(&certificate, now));
+
+        let mut second = certificate.clone();
+        second.certificate_id = "ERC-0".to_string();
+        let page = page(vec![certificate.clone(), second], 1, false, now);
+        assert_eq!(page.certificates.len(), 1);
+        assert_eq!(page.certificates[0].effective_status, ErcStatus::Expired);
+        assert_eq!(
+            page.next_page.as_deref().map(cursor::decode).transpose().unwrap(),
+            Some((time(1_700_000_000), "ERC-1".to_string()))
+        );
+    }
+}
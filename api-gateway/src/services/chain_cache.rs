@@ -0,0 +1,176 @@
+#[cfg(feature = "chain")]
+use anchor_lang::AccountDeserialize;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use redis::AsyncCommands;
+
+use crate::database::redis_pool::RedisPool;
+use crate::error::Result;
+use crate::services::blockchain::BlockchainService;
+#[cfg(feature = "chain")]
+use crate::services::blockchain::decode_account;
+use crate::services::metrics::record_cache_lookup;
+use crate::AppState;
+
+const CACHE_KEY_PREFIX: &str = "chain:account:";
+
+/// Kind of on-chain account, selecting how long reads of it stay cached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachedAccount {
+    Oracle,
+    Governance,
+    Certificate,
+}
+
+/// Seconds each kind of account stays cached; 0 disables caching it
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChainCacheTtls {
+    pub oracle: u64,
+    pub governance: u64,
+    pub certificate: u64,
+}
+
+impl ChainCacheTtls {
+    fn for_account(&self, kind: CachedAccount) -> u64 {
+        match kind {
+            CachedAccount::Oracle => self.oracle,
+            CachedAccount::Governance => self.governance,
+            CachedAccount::Certificate => self.certificate,
+        }
+    }
+}
+
+/// Caches on-chain account reads in Redis so dashboard polling does not hit the RPC node
+///
+/// Writers submitting transactions through the gateway invalidate the accounts they
+/// change; changes made by others show up once the TTL lapses. The cache is best effort:
+/// Redis failures fall back to reading the chain.
+#[derive(Clone)]
+pub struct ChainCache {
+    redis: RedisPool,
+    ttls: ChainCacheTtls,
+}
+
+impl ChainCache {
+    pub fn new(redis: RedisPool, ttls: ChainCacheTtls) -> Self {
+        Self { redis, ttls }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(
+            state.redis.clone(),
+            ChainCacheTtls {
+                oracle: state.config.oracle_cache_ttl,
+                governance: state.config.governance_cache_ttl,
+                certificate: state.config.certificate_cache_ttl,
+            },
+        )
+    }
+
+    /// Raw account data, or `None` if the account does not exist
+    pub async fn get_account_data(
+        &self,
+        chain: &BlockchainService,
+        address: &str,
+        kind: CachedAccount,
+    ) -> Result<Option<Vec<u8>>> {
+        let ttl = self.ttls.for_account(kind);
+        if ttl == 0 {
+            return chain.get_account_data(address).await;
+        }
+
+        let cached = self.cached(address).await;
+        record_cache_lookup("chain", cached.is_some());
+        if let Some(data) = cached {
+            return Ok(data);
+        }
+        let data = chain.get_account_data(address).await?;
+        self.store(address, data.as_deref(), ttl).await;
+        Ok(data)
+    }
+
+    /// Anchor account of type `T`, or `None` if the account does not exist
+    #[cfg(feature = "chain")]
+    pub async fn get_anchor_account<T: AccountDeserialize>(
+        &self,
+        chain: &BlockchainService,
+        address: &str,
+        kind: CachedAccount,
+    ) -> Result<Option<T>> {
+        self.get_account_data(chain, address, kind)
+            .await?
+            .map(|data| decode_account(address, &data))
+            .transpose()
+    }
+
+    /// Drop cached reads of accounts a transaction submitted by the gateway has changed
+    pub async fn invalidate(&self, addresses: &[String]) {
+        let keys: Vec<String> = addresses.iter().map(|address| cache_key(address)).collect();
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            conn.del::<_, ()>(keys).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to invalidate cached accounts {:?}: {}", addresses, e);
+        }
+    }
+
+    /// Cached data: `Some(None)` records that the account did not exist
+    async fn cached(&self, address: &str) -> Option<Option<Vec<u8>>> {
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            conn.get::<_, Option<String>>(cache_key(address)).await
+        }
+        .await;
+
+        match result {
+            Ok(cached) => cached.and_then(|value| decode_entry(&value)),
+            Err(e) => {
+                tracing::warn!("Chain account cache unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn store(&self, address: &str, data: Option<&[u8]>, ttl: u64) {
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            conn.set_ex::<_, _, ()>(cache_key(address), encode_entry(data), ttl).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to cache account {}: {}", address, e);
+        }
+    }
+}
+
+fn cache_key(address: &str) -> String {
+    format!("{}{}", CACHE_KEY_PREFIX, address)
+}
+
+/// Cache entry: JSON `null` for a missing account, otherwise the base64 account data
+fn encode_entry(data: Option<&[u8]>) -> String {
+    serde_json::to_string(&data.map(|data| BASE64.encode(data))).unwrap_or_default()
+}
+
+fn decode_entry(value: &str) -> Option<Option<Vec<u8>>> {
+    match serde_json::from_str::<Option<String>>(value).ok()? {
+        Some(data) => BASE64.decode(data).ok().map(Some),
+        None => Some(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_distinguish_missing_accounts() {
+        assert_eq!(decode_entry(&encode_entry(Some(&[1, 2, 3]))), Some(Some(vec![1, 2, 3])));
+        assert_eq!(decode_entry(&encode_entry(Some(&[]))), Some(Some(vec![])));
+        assert_eq!(decode_entry(&encode_entry(None)), Some(None));
+        assert_eq!(decode_entry("not json"), None);
+    }
+}
@@ -0,0 +1,128 @@
+// Client reading ingestion records readings on-chain through. With the `chain` feature it
+// is `BlockchainService`, building oracle instructions from the program crate; without it,
+// a mock that accepts readings in memory, so ingestion and the meter simulator build and
+// test without the Solana program crates.
+
+#[cfg(feature = "chain")]
+pub use oracle::MeterReadingInput;
+
+#[cfg(feature = "chain")]
+pub use crate::services::blockchain::BlockchainService as ChainClient;
+
+#[cfg(not(feature = "chain"))]
+pub use mock::{ChainClient, MeterReadingInput};
+
+#[cfg(feature = "chain")]
+impl ChainClient {
+    pub fn from_state(state: &crate::AppState) -> Self {
+        state.blockchain_service.clone()
+    }
+}
+
+#[cfg(not(feature = "chain"))]
+mod mock {
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    use crate::error::Result;
+    use crate::services::blockchain::ReadingSubmission;
+    use crate::services::transaction::Pubkey;
+    use crate::services::tx_signer::TxSigner;
+    use crate::utils::validation::MAX_METER_ID_LEN;
+    use crate::AppState;
+
+    /// Reading as the oracle program's `submit_meter_readings_batch` takes it
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct MeterReadingInput {
+        pub meter_id: String,
+        pub energy_produced: u64,
+        pub energy_consumed: u64,
+        pub reading_timestamp: i64,
+    }
+
+    /// Stand-in for the oracle calls of `BlockchainService`
+    ///
+    /// Every call is one made-up transaction recording all of its readings, except those
+    /// the oracle would reject for their meter ID length. Clones share what was recorded.
+    #[derive(Debug, Clone, Default)]
+    pub struct ChainClient {
+        recorded: Arc<Mutex<Vec<MeterReadingInput>>>,
+        transactions: Arc<Mutex<u64>>,
+    }
+
+    impl ChainClient {
+        /// A fresh mock; nothing is shared with the gateway's state
+        pub fn from_state(_state: &AppState) -> Self {
+            Self::default()
+        }
+
+        /// Readings recorded so far, in submission order
+        pub fn recorded(&self) -> Vec<MeterReadingInput> {
+            self.recorded.lock().unwrap_or_else(PoisonError::into_inner).clone()
+        }
+
+        pub async fn submit_meter_readings_batch(
+            &self,
+            _program_id: Pubkey,
+            _authority: &dyn TxSigner,
+            _fee_payer: Option<&dyn TxSigner>,
+            readings: &[MeterReadingInput],
+        ) -> Result<Vec<ReadingSubmission>> {
+            let signature = {
+                let mut transactions = self.transactions.lock().unwrap_or_else(PoisonError::into_inner);
+                *transactions += 1;
+                bs58::encode(transactions.to_le_bytes().repeat(8)).into_string()
+            };
+
+            let mut recorded = self.recorded.lock().unwrap_or_else(PoisonError::into_inner);
+            Ok(readings
+                .iter()
+                .map(|reading| {
+                    let outcome = if reading.meter_id.len() > MAX_METER_ID_LEN {
+                        Err(format!("Meter ID is longer than {} bytes", MAX_METER_ID_LEN))
+                    } else {
+                        recorded.push(reading.clone());
+                        Ok(signature.clone())
+                    };
+                    ReadingSubmission::new(reading, outcome)
+                })
+                .collect())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::services::tx_signer::LocalSigner;
+        use ed25519_dalek::SigningKey;
+
+        fn reading(meter_id: &str) -> MeterReadingInput {
+            MeterReadingInput {
+                meter_id: meter_id.to_string(),
+                energy_produced: 1_500,
+                energy_consumed: 900,
+                reading_timestamp: 1_700_000_000,
+            }
+        }
+
+        #[tokio::test]
+        async fn test_mock_records_readings_the_oracle_accepts() {
+            let chain = ChainClient::default();
+            let signer = LocalSigner::new(SigningKey::from_bytes(&[1; 32]));
+            let readings = [reading("MTR-001"), reading(&"M".repeat(MAX_METER_ID_LEN + 1))];
+
+            let results = chain
+                .submit_meter_readings_batch(Pubkey([3; 32]), &signer, None, &readings)
+                .await
+                .unwrap();
+            assert!(results[0].signature.is_some());
+            assert!(results[1].signature.is_none() && results[1].error.is_some());
+            assert_eq!(chain.clone().recorded(), vec![readings[0].clone()]);
+
+            let again = chain
+                .submit_meter_readings_batch(Pubkey([3; 32]), &signer, None, &readings[..1])
+                .await
+                .unwrap();
+            assert_ne!(again[0].signature, results[0].signature);
+        }
+    }
+}
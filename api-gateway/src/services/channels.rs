@@ -0,0 +1,504 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signer, SigningKey};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::error::{ApiError, Result};
+use crate::models::channel::{BalanceChannel, BalanceChannelUpdate};
+use crate::services::blockchain::BlockchainService;
+use crate::services::fee_payers::{sign_transaction, FeePayerPool};
+use crate::services::fees::{FeeOperation, FeeStrategy};
+use crate::services::transaction::{
+    anchor_instruction, decode_signature, ed25519_verify_instruction, instructions_sysvar, serialize_transaction,
+    verify_signature, Instruction, Message, Pubkey, SIGNATURE_LENGTH,
+};
+use crate::services::tx_signer::LocalSigner;
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+pub const CHANNEL_COLUMNS: &str = "id, user_id, participant_wallet, channel_address, nonce, balance, \
+    signed_nonce, signed_balance, checkpointed_nonce, checkpoint_tx_signature, status, created_at, updated_at";
+
+pub const UPDATE_COLUMNS: &str = "channel_id, nonce, delta, balance, interval_start, interval_end, \
+    operator_signature, participant_signature, signed_at, created_at";
+
+/// Channel state carrying the participant's signature
+struct SignedState {
+    nonce: u64,
+    balance: i64,
+    participant_signature: [u8; SIGNATURE_LENGTH],
+}
+
+/// Trading program instruction that takes a signed channel state
+#[derive(Debug, Clone, Copy)]
+enum StateSubmission {
+    Checkpoint,
+    Dispute,
+}
+
+/// Ed25519 check of the participant's signature followed by a trading program
+/// `checkpoint_channel` or `dispute_channel` submitted by the operator
+fn submit_state_instructions(
+    program_id: Pubkey,
+    submission: StateSubmission,
+    channel: Pubkey,
+    participant: Pubkey,
+    operator: Pubkey,
+    state: &SignedState,
+) -> Vec<Instruction> {
+    let message = trading::channel_state_message(&channel.into(), state.nonce, state.balance);
+
+    let accounts = trading::accounts::SubmitChannelState {
+        channel: channel.into(),
+        submitter: operator.into(),
+        instructions: instructions_sysvar().into(),
+    };
+    let (nonce, balance) = (state.nonce, state.balance);
+    let submit = match submission {
+        StateSubmission::Checkpoint => {
+            anchor_instruction(program_id, accounts, trading::instruction::CheckpointChannel { nonce, balance })
+        }
+        StateSubmission::Dispute => {
+            anchor_instruction(program_id, accounts, trading::instruction::DisputeChannel { nonce, balance })
+        }
+    };
+
+    vec![
+        ed25519_verify_instruction(&participant, &message, &state.participant_signature),
+        submit,
+    ]
+}
+
+/// Channel operator key from `CHANNEL_OPERATOR_KEY`, if configured
+pub(crate) fn channel_operator(config: &Config) -> Result<Option<SigningKey>> {
+    let Some(key) = config.channel_operator_key.as_deref() else {
+        return Ok(None);
+    };
+    let seed: [u8; 32] = bs58::decode(key)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ApiError::Configuration("CHANNEL_OPERATOR_KEY must be a base58 32-byte seed".to_string()))?;
+    Ok(Some(SigningKey::from_bytes(&seed)))
+}
+
+/// Accumulates signed per-interval balance updates off-chain and checkpoints them on-chain
+///
+/// The gateway countersigns each update as the channel operator (the market authority);
+/// once the participant signs it too, the state can be checkpointed or used in a dispute.
+#[derive(Clone)]
+pub struct ChannelService {
+    db: PgPool,
+    chain: BlockchainService,
+    program_id: Pubkey,
+    operator: Option<SigningKey>,
+    fee_payers: FeePayerPool,
+    fees: FeeStrategy,
+    clock: SharedClock,
+}
+
+impl ChannelService {
+    pub fn new(
+        db: PgPool,
+        chain: BlockchainService,
+        program_id: Pubkey,
+        operator: Option<SigningKey>,
+        fee_payers: FeePayerPool,
+        fees: FeeStrategy,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            db,
+            chain,
+            program_id,
+            operator,
+            fee_payers,
+            fees,
+            clock,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let program_id = Pubkey::from_str(&state.config.trading_program_id)
+            .map_err(|e| ApiError::Configuration(format!("Invalid TRADING_PROGRAM_ID: {}", e)))?;
+
+        Ok(Self::new(
+            state.db.clone(),
+            state.blockchain_service.clone(),
+            program_id,
+            channel_operator(&state.config)?,
+            state.fee_payers.clone(),
+            FeeStrategy::from_state(state),
+            state.clock.clone(),
+        ))
+    }
+
+    fn operator(&self) -> Result<&SigningKey> {
+        self.operator
+            .as_ref()
+            .ok_or_else(|| ApiError::Configuration("CHANNEL_OPERATOR_KEY is not configured".to_string()))
+    }
+
+    /// Record the channel the user opened on-chain with their registered wallet
+    pub async fn open(&self, user_id: Uuid) -> Result<BalanceChannel> {
+        let wallet: Option<String> = sqlx::query_scalar("SELECT wallet_address FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound("User not found".to_string()))?;
+        let wallet = wallet.ok_or_else(|| ApiError::BadRequest("Register a wallet address first".to_string()))?;
+
+        let participant = Pubkey::from_str(&wallet).map_err(ApiError::BadRequest)?;
+        let (channel_address, _) = Pubkey::find_program_address(&[b"channel", &participant.0], &self.program_id)
+            .ok_or_else(|| ApiError::Internal("No channel address for wallet".to_string()))?;
+
+        let query = format!(
+            "INSERT INTO balance_channels (user_id, participant_wallet, channel_address) VALUES ($1, $2, $3)
+             ON CONFLICT (participant_wallet) DO NOTHING
+             RETURNING {}",
+            CHANNEL_COLUMNS
+        );
+
+        let channel = sqlx::query_as::<_, BalanceChannel>(&query)
+            .bind(user_id)
+            .bind(&wallet)
+            .bind(channel_address.to_string())
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::Conflict(format!("A channel for {} already exists", wallet)))?;
+
+        tracing::info!("Balance channel {} opened for {}", channel.channel_address, wallet);
+        Ok(channel)
+    }
+
+    /// Apply an interval's balance change and countersign the resulting state
+    pub async fn record_update(
+        &self,
+        channel_id: Uuid,
+        delta: i64,
+        interval_start: DateTime<Utc>,
+        interval_end: DateTime<Utc>,
+    ) -> Result<BalanceChannelUpdate> {
+        let operator = self.operator()?;
+        if interval_end <= interval_start {
+            return Err(ApiError::Validation("Interval must end after it starts".to_string()));
+        }
+
+        let mut tx = self.db.begin().await?;
+
+        let query = format!("SELECT {} FROM balance_channels WHERE id = $1 FOR UPDATE", CHANNEL_COLUMNS);
+        let channel = sqlx::query_as::<_, BalanceChannel>(&query)
+            .bind(channel_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Channel {} not found", channel_id)))?;
+
+        if channel.status != BalanceChannel::OPEN {
+            return Err(ApiError::Conflict(format!("Channel {} is {}", channel_id, channel.status)));
+        }
+
+        let nonce = channel.nonce + 1;
+        let balance = channel
+            .balance
+            .checked_add(delta)
+            .ok_or_else(|| ApiError::Validation("Channel balance overflow".to_string()))?;
+
+        let channel_address = Pubkey::from_str(&channel.channel_address).map_err(ApiError::Internal)?;
+        let signature = operator.sign(&trading::channel_state_message(&channel_address.into(), nonce as u64, balance));
+
+        let query = format!(
+            "INSERT INTO balance_channel_updates (channel_id, nonce, delta, balance, interval_start, interval_end, \
+             operator_signature) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING {}",
+            UPDATE_COLUMNS
+        );
+        let update = sqlx::query_as::<_, BalanceChannelUpdate>(&query)
+            .bind(channel_id)
+            .bind(nonce)
+            .bind(delta)
+            .bind(balance)
+            .bind(interval_start)
+            .bind(interval_end)
+            .bind(bs58::encode(signature.to_bytes()).into_string())
+            .fetch_one(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE balance_channels SET nonce = $2, balance = $3 WHERE id = $1")
+            .bind(channel_id)
+            .bind(nonce)
+            .bind(balance)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        metrics::counter!("balance_channel_updates_total").increment(1);
+        Ok(update)
+    }
+
+    /// Store the participant's signature over a countersigned state
+    pub async fn sign_update(&self, user_id: Uuid, nonce: i64, signature: &str) -> Result<BalanceChannelUpdate> {
+        let channel = self.load_for_user(user_id).await?;
+        if channel.status == BalanceChannel::CLOSED {
+            return Err(ApiError::Conflict(format!("Channel {} is closed", channel.id)));
+        }
+        let update = self.load_update(channel.id, nonce).await?;
+
+        let participant = Pubkey::from_str(&channel.participant_wallet).map_err(ApiError::Internal)?;
+        let channel_address = Pubkey::from_str(&channel.channel_address).map_err(ApiError::Internal)?;
+        let message = trading::channel_state_message(&channel_address.into(), update.nonce as u64, update.balance);
+        let valid = decode_signature(signature)
+            .map(|bytes| verify_signature(&participant, &message, &bytes))
+            .unwrap_or(false);
+        if !valid {
+            return Err(ApiError::BadRequest("Signature does not match the channel state".to_string()));
+        }
+
+        let mut tx = self.db.begin().await?;
+
+        let query = format!(
+            "UPDATE balance_channel_updates SET participant_signature = $3, signed_at = $4
+             WHERE channel_id = $1 AND nonce = $2 RETURNING {}",
+            UPDATE_COLUMNS
+        );
+        let update = sqlx::query_as::<_, BalanceChannelUpdate>(&query)
+            .bind(channel.id)
+            .bind(nonce)
+            .bind(signature)
+            .bind(self.clock.now())
+            .fetch_one(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "UPDATE balance_channels SET signed_nonce = $2, signed_balance = $3 WHERE id = $1 AND signed_nonce < $2",
+        )
+        .bind(channel.id)
+        .bind(update.nonce)
+        .bind(update.balance)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(update)
+    }
+
+    pub async fn load(&self, channel_id: Uuid) -> Result<BalanceChannel> {
+        let query = format!("SELECT {} FROM balance_channels WHERE id = $1", CHANNEL_COLUMNS);
+
+        sqlx::query_as::<_, BalanceChannel>(&query)
+            .bind(channel_id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Channel {} not found", channel_id)))
+    }
+
+    pub async fn load_for_user(&self, user_id: Uuid) -> Result<BalanceChannel> {
+        let query = format!("SELECT {} FROM balance_channels WHERE user_id = $1", CHANNEL_COLUMNS);
+
+        sqlx::query_as::<_, BalanceChannel>(&query)
+            .bind(user_id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound("No balance channel for this user".to_string()))
+    }
+
+    async fn load_update(&self, channel_id: Uuid, nonce: i64) -> Result<BalanceChannelUpdate> {
+        let query = format!(
+            "SELECT {} FROM balance_channel_updates WHERE channel_id = $1 AND nonce = $2",
+            UPDATE_COLUMNS
+        );
+
+        sqlx::query_as::<_, BalanceChannelUpdate>(&query)
+            .bind(channel_id)
+            .bind(nonce)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Channel update {} not found", nonce)))
+    }
+
+    /// Checkpoint channels with fully signed states newer than their last checkpoint
+    pub async fn checkpoint_pending(&self) -> Result<usize> {
+        let query = format!(
+            "SELECT {} FROM balance_channels WHERE status = $1 AND signed_nonce > checkpointed_nonce",
+            CHANNEL_COLUMNS
+        );
+        let channels = sqlx::query_as::<_, BalanceChannel>(&query)
+            .bind(BalanceChannel::OPEN)
+            .fetch_all(&self.db)
+            .await?;
+
+        let mut checkpointed = 0;
+        for channel in channels {
+            match self.submit_state(&channel, StateSubmission::Checkpoint).await {
+                Ok(tx_signature) => {
+                    sqlx::query(
+                        "UPDATE balance_channels SET checkpointed_nonce = $2, checkpoint_tx_signature = $3 WHERE id = $1",
+                    )
+                    .bind(channel.id)
+                    .bind(channel.signed_nonce)
+                    .bind(&tx_signature)
+                    .execute(&self.db)
+                    .await?;
+                    checkpointed += 1;
+                }
+                Err(e) => tracing::error!("Checkpoint of channel {} failed: {}", channel.channel_address, e),
+            }
+        }
+
+        metrics::counter!("balance_channel_checkpoints_total").increment(checkpointed as u64);
+        Ok(checkpointed)
+    }
+
+    /// Start the dispute window on-chain with the latest state both parties signed
+    pub async fn dispute(&self, channel_id: Uuid) -> Result<BalanceChannel> {
+        let channel = self.load(channel_id).await?;
+        if channel.status != BalanceChannel::OPEN {
+            return Err(ApiError::Conflict(format!("Channel {} is {}", channel_id, channel.status)));
+        }
+
+        let tx_signature = self.submit_state(&channel, StateSubmission::Dispute).await?;
+        tracing::warn!(
+            "Disputed channel {} at nonce {} in {}",
+            channel.channel_address,
+            channel.signed_nonce,
+            tx_signature
+        );
+
+        sqlx::query("UPDATE balance_channels SET status = $2 WHERE id = $1")
+            .bind(channel_id)
+            .bind(BalanceChannel::DISPUTED)
+            .execute(&self.db)
+            .await?;
+
+        self.load(channel_id).await
+    }
+
+    /// Submit the channel's latest fully signed state to the trading program
+    async fn submit_state(&self, channel: &BalanceChannel, submission: StateSubmission) -> Result<String> {
+        let operator = self.operator()?;
+        if channel.signed_nonce == 0 {
+            return Err(ApiError::Conflict(format!(
+                "Channel {} has no state signed by its participant",
+                channel.id
+            )));
+        }
+
+        let update = self.load_update(channel.id, channel.signed_nonce).await?;
+        let participant_signature = update
+            .participant_signature
+            .as_deref()
+            .and_then(decode_signature)
+            .ok_or_else(|| ApiError::Internal(format!("Channel update {} is not signed", update.nonce)))?;
+
+        let operator_key = Pubkey(operator.verifying_key().to_bytes());
+        let instructions = submit_state_instructions(
+            self.program_id,
+            submission,
+            Pubkey::from_str(&channel.channel_address).map_err(ApiError::Internal)?,
+            Pubkey::from_str(&channel.participant_wallet).map_err(ApiError::Internal)?,
+            operator_key,
+            &SignedState {
+                nonce: update.nonce as u64,
+                balance: update.balance,
+                participant_signature,
+            },
+        );
+
+        let instructions = self.fees.with_compute_budget(FeeOperation::ChannelState, &instructions).await;
+
+        let blockhash = self.chain.recent_blockhash().await?;
+
+        let fee_payer = self.fee_payers.next();
+        let payer_key = fee_payer.as_ref().map_or(operator_key, |payer| payer.pubkey());
+        let message = Message::new(&instructions, payer_key, blockhash).map_err(ApiError::Internal)?;
+        let bytes = message.serialize();
+        let operator = LocalSigner::new(operator.clone());
+        let signatures = sign_transaction(message.signers(), &bytes, &operator, fee_payer.as_deref()).await?;
+
+        self.chain.send_transaction(&serialize_transaction(&signatures, &bytes)).await
+    }
+
+    /// Checkpoint pending channels every `interval`
+    pub fn spawn(self, interval: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match self.checkpoint_pending().await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Checkpointed {} balance channels", count),
+                    Err(e) => tracing::error!("Balance channel checkpointing failed: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::transaction::anchor_discriminator;
+
+    #[test]
+    fn test_state_message_layout() {
+        let channel = Pubkey([3; 32]);
+        let message = trading::channel_state_message(&channel.into(), 5, -2);
+
+        assert_eq!(&message[..21], b"gridtokenx:channel:v1");
+        assert_eq!(&message[21..53], &[3; 32]);
+        assert_eq!(&message[53..61], &5u64.to_le_bytes());
+        assert_eq!(&message[61..], &(-2i64).to_le_bytes());
+    }
+
+    #[test]
+    fn test_checkpoint_instructions_verify_participant_signature() {
+        let participant = SigningKey::from_bytes(&[1; 32]);
+        let participant_key = Pubkey(participant.verifying_key().to_bytes());
+        let (channel, operator, program_id) = (Pubkey([2; 32]), Pubkey([4; 32]), Pubkey([9; 32]));
+        let signature = participant.sign(&trading::channel_state_message(&channel.into(), 7, 1500)).to_bytes();
+
+        let instructions = submit_state_instructions(
+            program_id,
+            StateSubmission::Checkpoint,
+            channel,
+            participant_key,
+            operator,
+            &SignedState {
+                nonce: 7,
+                balance: 1500,
+                participant_signature: signature,
+            },
+        );
+
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].program_id, crate::services::transaction::ed25519_program_id());
+        assert_eq!(&instructions[0].data[16..48], &participant_key.0);
+
+        let checkpoint = &instructions[1];
+        assert_eq!(checkpoint.program_id, program_id);
+        assert_eq!(&checkpoint.data[..8], &anchor_discriminator("checkpoint_channel"));
+        assert_eq!(&checkpoint.data[8..16], &7u64.to_le_bytes());
+        assert_eq!(&checkpoint.data[16..], &1500i64.to_le_bytes());
+        assert_eq!(checkpoint.accounts[0].pubkey, channel);
+        assert!(checkpoint.accounts[1].is_signer && checkpoint.accounts[1].pubkey == operator);
+        assert_eq!(checkpoint.accounts[2].pubkey, instructions_sysvar());
+
+        let dispute = submit_state_instructions(
+            program_id,
+            StateSubmission::Dispute,
+            channel,
+            participant_key,
+            operator,
+            &SignedState {
+                nonce: 7,
+                balance: 1500,
+                participant_signature: signature,
+            },
+        );
+        assert_eq!(&dispute[1].data[..8], &anchor_discriminator("dispute_channel"));
+        assert_eq!(&dispute[1].data[8..], &checkpoint.data[8..]);
+    }
+}
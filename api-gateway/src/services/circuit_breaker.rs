@@ -0,0 +1,322 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::Config;
+use crate::error::{ApiError, Result};
+use crate::utils::clock::SharedClock;
+
+/// When a [`CircuitBreaker`] opens and how it recovers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BreakerSettings {
+    /// Share of failed calls in the window, from 0 to 1, at which the circuit opens
+    pub failure_rate: f64,
+    /// Most recent calls the failure rate is measured over
+    pub window: usize,
+    /// Calls the window must hold before the failure rate counts
+    pub min_calls: usize,
+    /// How long an open circuit fails calls fast before letting probes through
+    pub open_for: Duration,
+    /// Probes that must succeed in a row for a half-open circuit to close
+    pub probes: usize,
+}
+
+impl BreakerSettings {
+    /// Settings of the Solana RPC circuit, from the `SOLANA_RPC_BREAKER_*` variables
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            failure_rate: config.rpc_breaker_failure_rate.clamp(0.0, 1.0),
+            window: config.rpc_breaker_window.max(1),
+            min_calls: config.rpc_breaker_min_calls.max(1),
+            open_for: Duration::seconds(config.rpc_breaker_open_secs as i64),
+            probes: config.rpc_breaker_probes.max(1),
+        }
+    }
+}
+
+impl Default for BreakerSettings {
+    fn default() -> Self {
+        Self {
+            failure_rate: 0.5,
+            window: 20,
+            min_calls: 10,
+            open_for: Duration::seconds(30),
+            probes: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through and their outcomes are counted
+    Closed,
+    /// Calls fail fast until `open_for` has passed
+    Open,
+    /// A few probe calls go through to test whether the dependency recovered
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+
+    /// Value of the `circuit_breaker_state` gauge
+    fn gauge(&self) -> f64 {
+        match self {
+            Self::Closed => 0.0,
+            Self::HalfOpen => 1.0,
+            Self::Open => 2.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Circuit {
+    state: CircuitState,
+    /// Outcomes of the most recent calls while closed, `true` for a failure
+    outcomes: VecDeque<bool>,
+    opened_at: DateTime<Utc>,
+    probes_in_flight: usize,
+    probes_succeeded: usize,
+}
+
+/// Failure-rate circuit breaker around calls to a flaky dependency
+///
+/// Once enough of the recent calls fail, the circuit opens and calls fail fast with
+/// [`ApiError::Unavailable`] instead of waiting on timeouts. After `open_for` it lets a few
+/// probes through: if they all succeed the circuit closes, and the first failure opens it
+/// again. Clones share the same circuit.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    name: &'static str,
+    settings: BreakerSettings,
+    circuit: Arc<Mutex<Circuit>>,
+    clock: SharedClock,
+}
+
+/// Permission to make one call through a [`CircuitBreaker`], to be settled with its outcome
+///
+/// A permit dropped without an outcome, as when the call is cancelled, frees its probe slot
+/// without counting.
+#[must_use]
+pub struct Permit {
+    breaker: CircuitBreaker,
+    probe: bool,
+    settled: bool,
+}
+
+impl Permit {
+    pub fn record(mut self, failed: bool) {
+        self.settled = true;
+        self.breaker.record(self.probe, failed);
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if self.probe && !self.settled {
+            let mut circuit = self.breaker.lock();
+            circuit.probes_in_flight = circuit.probes_in_flight.saturating_sub(1);
+        }
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, settings: BreakerSettings, clock: SharedClock) -> Self {
+        metrics::gauge!("circuit_breaker_state", "breaker" => name).set(CircuitState::Closed.gauge());
+        Self {
+            name,
+            settings,
+            circuit: Arc::new(Mutex::new(Circuit {
+                state: CircuitState::Closed,
+                outcomes: VecDeque::with_capacity(settings.window),
+                opened_at: clock.now(),
+                probes_in_flight: 0,
+                probes_succeeded: 0,
+            })),
+            clock,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Circuit> {
+        self.circuit.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Current state, half-open once an open circuit has waited `open_for`
+    pub fn state(&self) -> CircuitState {
+        let mut circuit = self.lock();
+        self.half_open_if_due(&mut circuit);
+        circuit.state
+    }
+
+    /// When an open circuit starts letting probes through, `None` unless it is open
+    pub fn retry_at(&self) -> Option<DateTime<Utc>> {
+        let mut circuit = self.lock();
+        self.half_open_if_due(&mut circuit);
+        (circuit.state == CircuitState::Open).then(|| circuit.opened_at + self.settings.open_for)
+    }
+
+    /// Permit for one call, or `ApiError::Unavailable` while the circuit is open or every
+    /// probe slot of a half-open circuit is taken
+    pub fn acquire(&self) -> Result<Permit> {
+        let mut circuit = self.lock();
+        self.half_open_if_due(&mut circuit);
+        let probe = match circuit.state {
+            CircuitState::Closed => false,
+            CircuitState::HalfOpen if circuit.probes_in_flight + circuit.probes_succeeded < self.settings.probes => {
+                circuit.probes_in_flight += 1;
+                true
+            }
+            CircuitState::HalfOpen => {
+                return Err(ApiError::Unavailable(format!("{} is recovering; probes are in flight", self.name)))
+            }
+            CircuitState::Open => {
+                return Err(ApiError::Unavailable(format!(
+                    "{} is failing; calls are paused until {}",
+                    self.name,
+                    (circuit.opened_at + self.settings.open_for).to_rfc3339()
+                )))
+            }
+        };
+        Ok(Permit {
+            breaker: self.clone(),
+            probe,
+            settled: false,
+        })
+    }
+
+    fn record(&self, probe: bool, failed: bool) {
+        let mut circuit = self.lock();
+        if probe {
+            circuit.probes_in_flight = circuit.probes_in_flight.saturating_sub(1);
+            // Another probe may already have reopened the circuit
+            if circuit.state != CircuitState::HalfOpen {
+                return;
+            }
+            if failed {
+                self.transition(&mut circuit, CircuitState::Open);
+            } else {
+                circuit.probes_succeeded += 1;
+                if circuit.probes_succeeded >= self.settings.probes {
+                    self.transition(&mut circuit, CircuitState::Closed);
+                }
+            }
+            return;
+        }
+
+        // Calls started before the circuit opened do not count against the next window
+        if circuit.state != CircuitState::Closed {
+            return;
+        }
+        circuit.outcomes.push_back(failed);
+        while circuit.outcomes.len() > self.settings.window {
+            circuit.outcomes.pop_front();
+        }
+        let failures = circuit.outcomes.iter().filter(|failed| **failed).count();
+        if circuit.outcomes.len() >= self.settings.min_calls
+            && failures as f64 >= self.settings.failure_rate * circuit.outcomes.len() as f64
+        {
+            self.transition(&mut circuit, CircuitState::Open);
+        }
+    }
+
+    fn half_open_if_due(&self, circuit: &mut Circuit) {
+        if circuit.state == CircuitState::Open && self.clock.now() >= circuit.opened_at + self.settings.open_for {
+            self.transition(circuit, CircuitState::HalfOpen);
+        }
+    }
+
+    fn transition(&self, circuit: &mut Circuit, state: CircuitState) {
+        match state {
+            CircuitState::Open => {
+                tracing::warn!(
+                    "{} circuit opened; failing calls fast for {}s",
+                    self.name,
+                    self.settings.open_for.num_seconds()
+                );
+                circuit.opened_at = self.clock.now();
+            }
+            CircuitState::HalfOpen => tracing::info!("{} circuit half-open; probing", self.name),
+            CircuitState::Closed => tracing::info!("{} circuit closed", self.name),
+        }
+        circuit.state = state;
+        circuit.outcomes.clear();
+        circuit.probes_succeeded = 0;
+        metrics::gauge!("circuit_breaker_state", "breaker" => self.name).set(state.gauge());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::SimulatedClock;
+    use chrono::TimeZone;
+
+    fn breaker() -> (CircuitBreaker, SimulatedClock) {
+        let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2024, 9, 23, 12, 0, 0).unwrap());
+        let settings = BreakerSettings {
+            failure_rate: 0.5,
+            window: 4,
+            min_calls: 4,
+            open_for: Duration::seconds(30),
+            probes: 2,
+        };
+        (CircuitBreaker::new("Solana RPC", settings, clock.shared()), clock)
+    }
+
+    fn call(breaker: &CircuitBreaker, failed: bool) {
+        breaker.acquire().unwrap().record(failed);
+    }
+
+    #[test]
+    fn test_opens_at_failure_rate_and_fails_fast() {
+        let (breaker, _) = breaker();
+        // Too few calls for the failure rate to count
+        call(&breaker, true);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        call(&breaker, false);
+        call(&breaker, false);
+        call(&breaker, false);
+        call(&breaker, true);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        call(&breaker, true);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        assert!(matches!(breaker.acquire(), Err(ApiError::Unavailable(_))));
+        assert!(breaker.retry_at().is_some());
+    }
+
+    #[test]
+    fn test_half_open_probes_close_or_reopen() {
+        let (breaker, clock) = breaker();
+        for _ in 0..4 {
+            call(&breaker, true);
+        }
+        clock.advance(Duration::seconds(30));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        let first = breaker.acquire().unwrap();
+        let second = breaker.acquire().unwrap();
+        assert!(breaker.acquire().is_err());
+        first.record(false);
+        // A cancelled probe frees its slot
+        drop(second);
+        call(&breaker, false);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        for _ in 0..4 {
+            call(&breaker, true);
+        }
+        clock.advance(Duration::seconds(30));
+        call(&breaker, true);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert_eq!(breaker.retry_at(), Some(clock.shared().now() + Duration::seconds(30)));
+    }
+}
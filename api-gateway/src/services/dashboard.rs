@@ -0,0 +1,181 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::error::Result;
+use crate::models::dashboard::{BatteryState, DashboardAggregates, LiveDashboard};
+use crate::services::order_book::OrderBookSnapshot;
+use crate::utils::clock::SharedClock;
+
+/// Readings newer than this count as "current" generation and consumption
+const LIVE_WINDOW_MINUTES: i64 = 15;
+
+#[derive(Debug, Default)]
+struct Published {
+    refreshed_at: Option<DateTime<Utc>>,
+    aggregates: DashboardAggregates,
+}
+
+/// In-memory campus aggregates for the live dashboard
+///
+/// A background task refreshes the aggregates from the database; the dashboard endpoint
+/// combines them with the order book mirror without any I/O.
+pub struct DashboardMirror {
+    published: ArcSwap<Published>,
+    clock: SharedClock,
+    stale_after: chrono::Duration,
+}
+
+impl DashboardMirror {
+    pub fn new(clock: SharedClock, stale_after: Duration) -> Self {
+        Self {
+            published: ArcSwap::from_pointee(Published::default()),
+            clock,
+            stale_after: chrono::Duration::from_std(stale_after).unwrap_or(chrono::Duration::MAX),
+        }
+    }
+
+    /// Current dashboard, lock-free
+    pub fn live(&self, order_book: &OrderBookSnapshot) -> LiveDashboard {
+        let published = self.published.load();
+        let now = self.clock.now();
+
+        LiveDashboard {
+            aggregates: published.aggregates.clone(),
+            active_buy_orders: order_book.buy_orders.len(),
+            active_sell_orders: order_book.sell_orders.len(),
+            refreshed_at: published.refreshed_at,
+            stale: published
+                .refreshed_at
+                .is_none_or(|refreshed_at| now - refreshed_at > self.stale_after),
+            generated_at: now,
+        }
+    }
+
+    fn publish(&self, aggregates: DashboardAggregates) {
+        self.published.store(Arc::new(Published {
+            refreshed_at: Some(self.clock.now()),
+            aggregates,
+        }));
+    }
+
+    pub async fn refresh(&self, db: &PgPool) -> Result<()> {
+        let now = self.clock.now();
+        let window_start = now - chrono::Duration::minutes(LIVE_WINDOW_MINUTES);
+        let midnight = now.date_naive().and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+
+        let (generation_kwh, consumption_kwh): (f64, f64) = sqlx::query_as(
+            "SELECT COALESCE(SUM(energy_generated), 0)::FLOAT8, COALESCE(SUM(energy_consumed), 0)::FLOAT8
+             FROM energy_readings WHERE timestamp > $1 AND timestamp <= $2",
+        )
+        .bind(window_start)
+        .bind(now)
+        .fetch_one(db)
+        .await?;
+
+        let (meters, discharged_kwh, charged_kwh): (i64, f64, f64) = sqlx::query_as(
+            "SELECT COUNT(DISTINCT meter_id), COALESCE(SUM(energy_generated), 0)::FLOAT8,
+                    COALESCE(SUM(energy_consumed), 0)::FLOAT8
+             FROM energy_readings
+             WHERE timestamp > $1 AND timestamp <= $2 AND metadata->>'device_type' = 'battery'",
+        )
+        .bind(window_start)
+        .bind(now)
+        .fetch_one(db)
+        .await?;
+
+        let last_clearing: Option<(f64, Option<DateTime<Utc>>)> = sqlx::query_as(
+            "SELECT price_per_kwh::FLOAT8, filled_at FROM trading_orders
+             WHERE filled_at IS NOT NULL ORDER BY filled_at DESC LIMIT 1",
+        )
+        .fetch_optional(db)
+        .await?;
+
+        let (ercs_issued_today, erc_energy_issued_today): (i64, i64) = sqlx::query_as(
+            "SELECT COUNT(*), COALESCE(SUM(energy_amount), 0)::BIGINT FROM erc_certificates WHERE issued_at >= $1",
+        )
+        .bind(midnight)
+        .fetch_one(db)
+        .await?;
+
+        self.publish(DashboardAggregates {
+            generation_kwh,
+            consumption_kwh,
+            battery: BatteryState {
+                meters,
+                charged_kwh,
+                discharged_kwh,
+                mode: battery_mode(charged_kwh, discharged_kwh).to_string(),
+            },
+            last_clearing_price: last_clearing.map(|(price, _)| price),
+            last_cleared_at: last_clearing.and_then(|(_, at)| at),
+            ercs_issued_today,
+            erc_energy_issued_today,
+        });
+        Ok(())
+    }
+
+    /// Refresh the aggregates every `interval`
+    pub fn spawn(self: &Arc<Self>, db: PgPool, interval: Duration) {
+        let mirror = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = mirror.refresh(&db).await {
+                    tracing::error!("Dashboard refresh failed: {}", e);
+                }
+            }
+        });
+    }
+}
+
+fn battery_mode(charged_kwh: f64, discharged_kwh: f64) -> &'static str {
+    if charged_kwh > discharged_kwh {
+        "charging"
+    } else if discharged_kwh > charged_kwh {
+        "discharging"
+    } else {
+        "idle"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::SimulatedClock;
+
+    #[test]
+    fn test_live_dashboard_flags_stale_aggregates() {
+        let clock = SimulatedClock::new(Utc::now());
+        let mirror = DashboardMirror::new(clock.shared(), Duration::from_secs(30));
+        let order_book = OrderBookSnapshot::default();
+
+        let live = mirror.live(&order_book);
+        assert!(live.stale);
+        assert!(live.refreshed_at.is_none());
+
+        mirror.publish(DashboardAggregates {
+            ercs_issued_today: 3,
+            ..Default::default()
+        });
+        let live = mirror.live(&order_book);
+        assert!(!live.stale);
+        assert_eq!(live.aggregates.ercs_issued_today, 3);
+
+        clock.advance(chrono::Duration::seconds(31));
+        let live = mirror.live(&order_book);
+        assert!(live.stale);
+        assert_eq!(live.generated_at - live.refreshed_at.unwrap(), chrono::Duration::seconds(31));
+    }
+
+    #[test]
+    fn test_battery_mode() {
+        assert_eq!(battery_mode(2.0, 1.0), "charging");
+        assert_eq!(battery_mode(0.5, 1.0), "discharging");
+        assert_eq!(battery_mode(0.0, 0.0), "idle");
+    }
+}
@@ -0,0 +1,387 @@
+use governance::CouncilAction;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::auth::tenant::TenantScope;
+use crate::database::schema::types::ErcStatus;
+use crate::error::{ApiError, Result};
+use crate::models::dispute::{Dispute, DisputeDetail, DisputeEvidence, EvidenceRequest, OpenDisputeRequest};
+use crate::models::tx_job::TxJob;
+use crate::services::tx_queue::{enqueue_with, TxOperation};
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+pub const DISPUTE_COLUMNS: &str = "id, subject_type, subject_id, opened_by, reason, status, resolution, resolved_by, \
+    resolved_at, challenge_job_id, resolution_job_id, created_at, updated_at";
+
+pub const EVIDENCE_COLUMNS: &str = "id, dispute_id, submitted_by, description, url, sha256, created_at";
+
+/// Tenant of a dispute's opener; for `disputes` rows aliased `d`
+const DISPUTE_TENANT: &str = "(SELECT u.tenant_id FROM users u WHERE u.id = d.opened_by)";
+
+/// Most disputes returned by one listing
+pub const MAX_LIST_LIMIT: i64 = 500;
+
+/// Most evidence items attached to one dispute
+pub const MAX_EVIDENCE: i64 = 20;
+
+/// Longest evidence description
+const MAX_DESCRIPTION_LEN: usize = 2_000;
+
+/// Reasons and resolutions of certificate disputes are recorded on-chain, so every dispute
+/// keeps to the governance program's limit
+pub fn validate_text(field: &str, text: &str) -> Result<()> {
+    if text.trim().is_empty() || text.len() > CouncilAction::MAX_REASON_LEN {
+        return Err(ApiError::BadRequest(format!(
+            "{} must be 1-{} bytes",
+            field,
+            CouncilAction::MAX_REASON_LEN
+        )));
+    }
+    Ok(())
+}
+
+pub fn validate_evidence(evidence: &EvidenceRequest) -> Result<()> {
+    if evidence.description.trim().is_empty() || evidence.description.len() > MAX_DESCRIPTION_LEN {
+        return Err(ApiError::BadRequest(format!(
+            "Evidence description must be 1-{} bytes",
+            MAX_DESCRIPTION_LEN
+        )));
+    }
+    if let Some(url) = &evidence.url {
+        let parsed = reqwest::Url::parse(url).map_err(|e| ApiError::BadRequest(format!("Invalid evidence URL: {}", e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(ApiError::BadRequest("Evidence URL must be http or https".to_string()));
+        }
+    }
+    if let Some(sha256) = &evidence.sha256 {
+        if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ApiError::BadRequest("Evidence sha256 must be 64 hex digits".to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// What resolving a certificate dispute does on-chain, given its challenge job's status
+///
+/// A confirmed challenge must be resolved on-chain; one that failed never froze the
+/// certificate, so the dispute is resolved off-chain only.
+fn needs_resolution_job(challenge_status: &str) -> Result<bool> {
+    match challenge_status {
+        TxJob::CONFIRMED => Ok(true),
+        TxJob::FAILED => Ok(false),
+        _ => Err(ApiError::Conflict(
+            "The certificate's challenge has not landed on-chain yet".to_string(),
+        )),
+    }
+}
+
+/// Prosumer disputes over readings and certificates, and their resolution
+///
+/// Certificate disputes are mirrored on-chain: opening one queues `challenge_erc`, and
+/// resolving it queues `resolve_challenge`, which revokes the certificate when upheld.
+#[derive(Clone)]
+pub struct DisputeService {
+    db: PgPool,
+    clock: SharedClock,
+}
+
+impl DisputeService {
+    pub fn new(db: PgPool, clock: SharedClock) -> Self {
+        Self { db, clock }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone(), state.clock.clone())
+    }
+
+    /// Open a dispute over a reading or certificate visible in `scope`
+    pub async fn open(&self, request: &OpenDisputeRequest, opened_by: Uuid, scope: &TenantScope) -> Result<DisputeDetail> {
+        validate_text("Reason", &request.reason)?;
+        if request.evidence.len() as i64 > MAX_EVIDENCE {
+            return Err(ApiError::BadRequest(format!("At most {} evidence items per dispute", MAX_EVIDENCE)));
+        }
+        for evidence in &request.evidence {
+            validate_evidence(evidence)?;
+        }
+
+        match request.subject_type.as_str() {
+            Dispute::READING => self.require_reading(&request.subject_id, scope).await?,
+            Dispute::CERTIFICATE => self.require_certificate(&request.subject_id, scope).await?,
+            _ => {
+                return Err(ApiError::BadRequest(
+                    "subject_type must be reading or certificate".to_string(),
+                ))
+            }
+        }
+
+        let mut tx = self.db.begin().await?;
+        let query = format!(
+            "INSERT INTO disputes (subject_type, subject_id, opened_by, reason, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $5)
+             ON CONFLICT (subject_type, subject_id) WHERE status = 'open' DO NOTHING
+             RETURNING {}",
+            DISPUTE_COLUMNS
+        );
+        let mut dispute = sqlx::query_as::<_, Dispute>(&query)
+            .bind(&request.subject_type)
+            .bind(&request.subject_id)
+            .bind(opened_by)
+            .bind(request.reason.trim())
+            .bind(self.clock.now())
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| {
+                ApiError::Conflict(format!(
+                    "The {} already has an open dispute",
+                    request.subject_type
+                ))
+            })?;
+
+        if dispute.subject_type == Dispute::CERTIFICATE {
+            let operation = TxOperation::ChallengeErc {
+                certificate_id: dispute.subject_id.clone(),
+                dispute_id: dispute.id,
+                reason: dispute.reason.clone(),
+            };
+            let job = enqueue_with(&mut *tx, &operation, Some(opened_by)).await?;
+            sqlx::query("UPDATE disputes SET challenge_job_id = $2 WHERE id = $1")
+                .bind(dispute.id)
+                .bind(job.id)
+                .execute(&mut *tx)
+                .await?;
+            dispute.challenge_job_id = Some(job.id);
+        }
+
+        let mut evidence = Vec::with_capacity(request.evidence.len());
+        for item in &request.evidence {
+            evidence.push(self.insert_evidence(&mut tx, dispute.id, opened_by, item).await?);
+        }
+        tx.commit().await?;
+
+        tracing::info!("User {} opened dispute {} over {} {}", opened_by, dispute.id, dispute.subject_type, dispute.subject_id);
+        Ok(DisputeDetail { dispute, evidence })
+    }
+
+    async fn require_reading(&self, reading_id: &str, scope: &TenantScope) -> Result<()> {
+        let not_found = || ApiError::NotFound(format!("Reading {} not found", reading_id));
+        let id = Uuid::parse_str(reading_id).map_err(|_| not_found())?;
+        let meter_id: String = sqlx::query_scalar("SELECT meter_id FROM energy_readings WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(not_found)?;
+        if !scope.sees_meter(&self.db, &meter_id).await? {
+            return Err(not_found());
+        }
+        Ok(())
+    }
+
+    async fn require_certificate(&self, certificate_id: &str, scope: &TenantScope) -> Result<()> {
+        scope.require_certificate(&self.db, certificate_id).await?;
+        let status: ErcStatus = sqlx::query_scalar("SELECT status FROM erc_certificates WHERE certificate_id = $1")
+            .bind(certificate_id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("ERC certificate {} not found", certificate_id)))?;
+        if !matches!(status, ErcStatus::Valid | ErcStatus::Pending | ErcStatus::Expired) {
+            return Err(ApiError::Conflict(format!(
+                "ERC certificate {} is {} and cannot be disputed",
+                certificate_id, status
+            )));
+        }
+        Ok(())
+    }
+
+    async fn insert_evidence(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        dispute_id: Uuid,
+        submitted_by: Uuid,
+        evidence: &EvidenceRequest,
+    ) -> Result<DisputeEvidence> {
+        let query = format!(
+            "INSERT INTO dispute_evidence (dispute_id, submitted_by, description, url, sha256, created_at)
+             VALUES ($1, $2, $3, $4, LOWER($5), $6)
+             RETURNING {}",
+            EVIDENCE_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, DisputeEvidence>(&query)
+            .bind(dispute_id)
+            .bind(submitted_by)
+            .bind(evidence.description.trim())
+            .bind(&evidence.url)
+            .bind(&evidence.sha256)
+            .bind(self.clock.now())
+            .fetch_one(&mut **tx)
+            .await?)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<DisputeDetail> {
+        let query = format!("SELECT {} FROM disputes WHERE id = $1", DISPUTE_COLUMNS);
+        let dispute = sqlx::query_as::<_, Dispute>(&query)
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Dispute {} not found", id)))?;
+
+        let query = format!(
+            "SELECT {} FROM dispute_evidence WHERE dispute_id = $1 ORDER BY created_at, id",
+            EVIDENCE_COLUMNS
+        );
+        let evidence = sqlx::query_as::<_, DisputeEvidence>(&query)
+            .bind(id)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(DisputeDetail { dispute, evidence })
+    }
+
+    /// Disputes newest first, optionally only those opened by one user or in one status
+    pub async fn list(
+        &self,
+        opened_by: Option<Uuid>,
+        status: Option<&str>,
+        scope: &TenantScope,
+        limit: i64,
+    ) -> Result<Vec<Dispute>> {
+        let query = format!(
+            "SELECT {} FROM disputes d
+             WHERE {} AND ($3::uuid IS NULL OR opened_by = $3) AND ($4::varchar IS NULL OR status = $4)
+             ORDER BY created_at DESC, id LIMIT $5",
+            DISPUTE_COLUMNS,
+            TenantScope::condition(DISPUTE_TENANT, 1)
+        );
+        Ok(sqlx::query_as::<_, Dispute>(&query)
+            .bind(scope.is_campus())
+            .bind(scope.tenant())
+            .bind(opened_by)
+            .bind(status)
+            .bind(limit.clamp(1, MAX_LIST_LIMIT))
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Attach evidence to an open dispute
+    pub async fn add_evidence(&self, id: Uuid, submitted_by: Uuid, evidence: &EvidenceRequest) -> Result<DisputeEvidence> {
+        validate_evidence(evidence)?;
+
+        let mut tx = self.db.begin().await?;
+        let status: String = sqlx::query_scalar("SELECT status FROM disputes WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Dispute {} not found", id)))?;
+        if status != Dispute::OPEN {
+            return Err(ApiError::Conflict(format!("Dispute {} is already {}", id, status)));
+        }
+        let attached: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM dispute_evidence WHERE dispute_id = $1")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if attached >= MAX_EVIDENCE {
+            return Err(ApiError::Conflict(format!("At most {} evidence items per dispute", MAX_EVIDENCE)));
+        }
+
+        let evidence = self.insert_evidence(&mut tx, id, submitted_by, evidence).await?;
+        tx.commit().await?;
+        Ok(evidence)
+    }
+
+    /// Uphold or dismiss an open dispute, queueing `resolve_challenge` for a certificate
+    /// whose challenge landed
+    pub async fn resolve(&self, id: Uuid, upheld: bool, resolution: &str, resolved_by: Uuid) -> Result<Dispute> {
+        validate_text("Resolution", resolution)?;
+
+        let mut tx = self.db.begin().await?;
+        let query = format!("SELECT {} FROM disputes WHERE id = $1 FOR UPDATE", DISPUTE_COLUMNS);
+        let dispute = sqlx::query_as::<_, Dispute>(&query)
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Dispute {} not found", id)))?;
+        if dispute.status != Dispute::OPEN {
+            return Err(ApiError::Conflict(format!("Dispute {} is already {}", id, dispute.status)));
+        }
+
+        let mut resolution_job_id = None;
+        if let Some(challenge_job_id) = dispute.challenge_job_id {
+            let challenge_status: String = sqlx::query_scalar("SELECT status FROM tx_jobs WHERE id = $1")
+                .bind(challenge_job_id)
+                .fetch_one(&mut *tx)
+                .await?;
+            if needs_resolution_job(&challenge_status)? {
+                let operation = TxOperation::ResolveChallenge {
+                    certificate_id: dispute.subject_id.clone(),
+                    dispute_id: dispute.id,
+                    upheld,
+                    resolution: resolution.trim().to_string(),
+                };
+                resolution_job_id = Some(enqueue_with(&mut *tx, &operation, Some(resolved_by)).await?.id);
+            }
+        }
+
+        let query = format!(
+            "UPDATE disputes SET status = $2, resolution = $3, resolved_by = $4, resolved_at = $5,
+                 resolution_job_id = $6
+             WHERE id = $1
+             RETURNING {}",
+            DISPUTE_COLUMNS
+        );
+        let dispute = sqlx::query_as::<_, Dispute>(&query)
+            .bind(id)
+            .bind(if upheld { Dispute::UPHELD } else { Dispute::DISMISSED })
+            .bind(resolution.trim())
+            .bind(resolved_by)
+            .bind(self.clock.now())
+            .bind(resolution_job_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        tracing::info!("Dispute {} {} by {}", dispute.id, dispute.status, resolved_by);
+        Ok(dispute)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evidence(url: Option<&str>, sha256: Option<&str>) -> EvidenceRequest {
+        EvidenceRequest {
+            description: "Photo of the meter display".to_string(),
+            url: url.map(str::to_string),
+            sha256: sha256.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_text_fits_on_chain() {
+        assert!(validate_text("Reason", "Reading is twice the inverter capacity").is_ok());
+        assert!(validate_text("Reason", "  ").is_err());
+        assert!(validate_text("Reason", &"x".repeat(CouncilAction::MAX_REASON_LEN)).is_ok());
+        assert!(validate_text("Reason", &"x".repeat(CouncilAction::MAX_REASON_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_evidence_validation() {
+        assert!(validate_evidence(&evidence(None, None)).is_ok());
+        assert!(validate_evidence(&evidence(Some("https://files.example/meter.jpg"), Some(&"aB".repeat(32)))).is_ok());
+        assert!(validate_evidence(&evidence(Some("ftp://files.example/meter.jpg"), None)).is_err());
+        assert!(validate_evidence(&evidence(Some("not a url"), None)).is_err());
+        assert!(validate_evidence(&evidence(None, Some("abc"))).is_err());
+        assert!(validate_evidence(&evidence(None, Some(&"zz".repeat(32)))).is_err());
+
+        let mut empty = evidence(None, None);
+        empty.description = String::new();
+        assert!(validate_evidence(&empty).is_err());
+    }
+
+    #[test]
+    fn test_resolution_follows_challenge_job() {
+        assert!(needs_resolution_job(TxJob::CONFIRMED).unwrap());
+        assert!(!needs_resolution_job(TxJob::FAILED).unwrap());
+        assert!(matches!(needs_resolution_job(TxJob::QUEUED), Err(ApiError::Conflict(_))));
+        assert!(matches!(needs_resolution_job(TxJob::SUBMITTED), Err(ApiError::Conflict(_))));
+    }
+}
@@ -0,0 +1,309 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::error::{ApiError, Result};
+
+/// IEC 62056-47 wrapper header: version, source wPort, destination wPort and APDU length
+const WRAPPER_VERSION: u16 = 1;
+const WRAPPER_HEADER_LEN: usize = 8;
+
+/// Public client SAP, which may read registers without authentication
+const PUBLIC_CLIENT: u16 = 0x10;
+
+/// AARQ for the logical name referencing context without ciphering or authentication,
+/// proposing only the GET service and a 65535-byte receive PDU
+const AARQ: [u8; 31] = [
+    0x60, 0x1D, // AARQ
+    0xA1, 0x09, 0x06, 0x07, 0x60, 0x85, 0x74, 0x05, 0x08, 0x01, 0x01, // LN, no ciphering
+    0xBE, 0x10, 0x04, 0x0E, // user-information: xDLMS InitiateRequest
+    0x01, 0x00, 0x00, 0x00, 0x06, // no dedicated key, DLMS version 6
+    0x5F, 0x1F, 0x04, 0x00, 0x00, 0x00, 0x10, // conformance: get
+    0xFF, 0xFF, // max receive PDU size
+];
+
+/// Release request with reason `normal`
+const RLRQ: [u8; 5] = [0x62, 0x03, 0x80, 0x01, 0x00];
+
+const AARE_TAG: u8 = 0x61;
+const ASSOCIATION_RESULT_TAG: u8 = 0xA2;
+const GET_REQUEST_NORMAL: [u8; 2] = [0xC0, 0x01];
+const GET_RESPONSE_NORMAL: [u8; 2] = [0xC4, 0x01];
+/// Invoke id 1, confirmed, high priority
+const INVOKE_ID_AND_PRIORITY: u8 = 0xC1;
+
+/// COSEM `Register` interface class and its attributes
+const REGISTER_CLASS: u16 = 3;
+const VALUE_ATTRIBUTE: u8 = 2;
+const SCALER_UNIT_ATTRIBUTE: u8 = 3;
+
+/// Unit code of active energy in watt-hours
+const UNIT_WATT_HOUR: u8 = 30;
+
+/// OBIS code naming a COSEM object, e.g. `1.0.1.8.0.255` for total imported active energy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Obis(pub [u8; 6]);
+
+impl FromStr for Obis {
+    type Err = String;
+
+    /// Accepts dotted (`1.0.1.8.0.255`) and IEC (`1-0:1.8.0.255`) notation
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let groups = s
+            .split(['.', '-', ':'])
+            .map(|group| group.trim().parse::<u8>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| format!("Invalid OBIS code {}", s))?;
+        groups
+            .try_into()
+            .map(Self)
+            .map_err(|_| format!("OBIS code {} must have six groups", s))
+    }
+}
+
+impl fmt::Display for Obis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(f, "{}.{}.{}.{}.{}.{}", a, b, c, d, e, g)
+    }
+}
+
+fn invalid(reason: &str) -> ApiError {
+    ApiError::ExternalService(format!("Invalid DLMS response: {}", reason))
+}
+
+/// Wrap `apdu` for the TCP transport
+fn wrap(server: u16, apdu: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(WRAPPER_HEADER_LEN + apdu.len());
+    frame.extend_from_slice(&WRAPPER_VERSION.to_be_bytes());
+    frame.extend_from_slice(&PUBLIC_CLIENT.to_be_bytes());
+    frame.extend_from_slice(&server.to_be_bytes());
+    frame.extend_from_slice(&(apdu.len() as u16).to_be_bytes());
+    frame.extend_from_slice(apdu);
+    frame
+}
+
+/// Tag, contents and remainder of the BER element at the start of `bytes`
+fn ber_element(bytes: &[u8]) -> Result<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = bytes.split_first().ok_or_else(|| invalid("truncated element"))?;
+    let (&first, rest) = rest.split_first().ok_or_else(|| invalid("truncated length"))?;
+    let (len, rest) = match first {
+        0x81 => {
+            let (&len, rest) = rest.split_first().ok_or_else(|| invalid("truncated length"))?;
+            (usize::from(len), rest)
+        }
+        0x82 if rest.len() >= 2 => (usize::from(u16::from_be_bytes([rest[0], rest[1]])), &rest[2..]),
+        len if len < 0x80 => (usize::from(len), rest),
+        _ => return Err(invalid("unsupported length")),
+    };
+    if rest.len() < len {
+        return Err(invalid("truncated element"));
+    }
+    Ok((tag, &rest[..len], &rest[len..]))
+}
+
+/// Check that an AARE accepts the association
+fn check_aare(apdu: &[u8]) -> Result<()> {
+    let (tag, mut fields, _) = ber_element(apdu)?;
+    if tag != AARE_TAG {
+        return Err(invalid("expected an AARE"));
+    }
+    while !fields.is_empty() {
+        let (tag, contents, rest) = ber_element(fields)?;
+        if tag == ASSOCIATION_RESULT_TAG {
+            // INTEGER { accepted (0), rejected-permanent (1), rejected-transient (2) }
+            return match contents {
+                [0x02, 0x01, 0x00] => Ok(()),
+                [0x02, 0x01, result] => Err(ApiError::ExternalService(format!(
+                    "DLMS association rejected with result {}",
+                    result
+                ))),
+                _ => Err(invalid("malformed association result")),
+            };
+        }
+        fields = rest;
+    }
+    Err(invalid("AARE without an association result"))
+}
+
+/// GET-Request-Normal for attribute `attribute` of the register `obis`
+fn get_request(obis: &Obis, attribute: u8) -> Vec<u8> {
+    let mut apdu = GET_REQUEST_NORMAL.to_vec();
+    apdu.push(INVOKE_ID_AND_PRIORITY);
+    apdu.extend_from_slice(&REGISTER_CLASS.to_be_bytes());
+    apdu.extend_from_slice(&obis.0);
+    apdu.push(attribute);
+    // No selective access
+    apdu.push(0x00);
+    apdu
+}
+
+/// Data of a GET-Response-Normal, failing on a data access error
+fn get_response_data(apdu: &[u8]) -> Result<&[u8]> {
+    if apdu.len() < 4 || apdu[..2] != GET_RESPONSE_NORMAL {
+        return Err(invalid("expected a GET response"));
+    }
+    match apdu[3] {
+        0x00 => Ok(&apdu[4..]),
+        0x01 => Err(ApiError::ExternalService(format!(
+            "DLMS data access error {}",
+            apdu.get(4).copied().unwrap_or_default()
+        ))),
+        _ => Err(invalid("unknown GET result")),
+    }
+}
+
+/// Integer value of an A-XDR encoded integer of any width
+fn decode_integer(data: &[u8]) -> Result<i128> {
+    let (&tag, value) = data.split_first().ok_or_else(|| invalid("empty data"))?;
+    let bytes = |len: usize| value.get(..len).ok_or_else(|| invalid("truncated integer"));
+    Ok(match tag {
+        0x0F => i128::from(bytes(1)?[0] as i8),
+        0x11 | 0x16 => i128::from(bytes(1)?[0]),
+        0x10 => i128::from(i16::from_be_bytes(bytes(2)?.try_into().unwrap())),
+        0x12 => i128::from(u16::from_be_bytes(bytes(2)?.try_into().unwrap())),
+        0x05 => i128::from(i32::from_be_bytes(bytes(4)?.try_into().unwrap())),
+        0x06 => i128::from(u32::from_be_bytes(bytes(4)?.try_into().unwrap())),
+        0x14 => i128::from(i64::from_be_bytes(bytes(8)?.try_into().unwrap())),
+        0x15 => i128::from(u64::from_be_bytes(bytes(8)?.try_into().unwrap())),
+        _ => return Err(invalid("register value is not an integer")),
+    })
+}
+
+/// Scaler and unit of a register's `scaler_unit` structure
+fn decode_scaler_unit(data: &[u8]) -> Result<(i8, u8)> {
+    match data {
+        [0x02, 0x02, 0x0F, scaler, 0x16, unit, ..] => Ok((*scaler as i8, *unit)),
+        _ => Err(invalid("malformed scaler_unit")),
+    }
+}
+
+/// Energy register value in kWh
+fn to_kwh(value: i128, scaler: i8, unit: u8) -> Result<f64> {
+    if unit != UNIT_WATT_HOUR {
+        return Err(ApiError::ExternalService(format!(
+            "DLMS register unit {} is not an energy unit (Wh)",
+            unit
+        )));
+    }
+    Ok(value as f64 * 10f64.powi(i32::from(scaler)) / 1000.0)
+}
+
+/// DLMS/COSEM client over the TCP wrapper, associated as the public client
+pub struct DlmsClient {
+    stream: TcpStream,
+    server: u16,
+    timeout: Duration,
+}
+
+impl DlmsClient {
+    /// Connect to the logical device `server` and open an association
+    pub async fn connect(host: &str, port: u16, server: u16, timeout: Duration) -> Result<Self> {
+        let stream = tokio::time::timeout(timeout, TcpStream::connect((host, port)))
+            .await
+            .map_err(|_| ApiError::ExternalService(format!("DLMS connection to {}:{} timed out", host, port)))?
+            .map_err(|e| ApiError::ExternalService(format!("DLMS connection to {}:{} failed: {}", host, port, e)))?;
+        let mut client = Self { stream, server, timeout };
+        let aare = client.exchange(&AARQ).await?;
+        check_aare(&aare)?;
+        Ok(client)
+    }
+
+    async fn exchange(&mut self, apdu: &[u8]) -> Result<Vec<u8>> {
+        let frame = wrap(self.server, apdu);
+        tokio::time::timeout(self.timeout, async {
+            self.stream.write_all(&frame).await?;
+            let mut header = [0; WRAPPER_HEADER_LEN];
+            self.stream.read_exact(&mut header).await?;
+            if header[..2] != WRAPPER_VERSION.to_be_bytes() {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "unknown wrapper version"));
+            }
+            let mut apdu = vec![0; usize::from(u16::from_be_bytes([header[6], header[7]]))];
+            self.stream.read_exact(&mut apdu).await?;
+            Ok(apdu)
+        })
+        .await
+        .map_err(|_| ApiError::ExternalService("DLMS request timed out".to_string()))?
+        .map_err(|e| ApiError::ExternalService(format!("DLMS request failed: {}", e)))
+    }
+
+    async fn get(&mut self, obis: &Obis, attribute: u8) -> Result<Vec<u8>> {
+        let response = self.exchange(&get_request(obis, attribute)).await?;
+        get_response_data(&response).map(<[u8]>::to_vec)
+    }
+
+    /// Energy register `obis` in kWh, scaled by its `scaler_unit`
+    pub async fn read_energy_kwh(&mut self, obis: &Obis) -> Result<f64> {
+        let (scaler, unit) = decode_scaler_unit(&self.get(obis, SCALER_UNIT_ATTRIBUTE).await?)?;
+        let value = decode_integer(&self.get(obis, VALUE_ATTRIBUTE).await?)?;
+        to_kwh(value, scaler, unit)
+    }
+
+    /// Release the association; the meter closes it anyway when the connection drops
+    pub async fn release(mut self) {
+        if let Err(e) = self.exchange(&RLRQ).await {
+            tracing::debug!("DLMS release failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_obis_notation() {
+        let obis: Obis = "1.0.1.8.0.255".parse().unwrap();
+        assert_eq!(obis, Obis([1, 0, 1, 8, 0, 255]));
+        assert_eq!("1-0:2.8.0.255".parse::<Obis>().unwrap(), Obis([1, 0, 2, 8, 0, 255]));
+        assert_eq!(obis.to_string(), "1.0.1.8.0.255");
+        assert!("1.0.1.8.0".parse::<Obis>().is_err());
+        assert!("1.0.1.8.0.256".parse::<Obis>().is_err());
+    }
+
+    #[test]
+    fn test_request_frames() {
+        assert_eq!(
+            wrap(1, &get_request(&Obis([1, 0, 1, 8, 0, 255]), VALUE_ATTRIBUTE)),
+            [
+                0x00, 0x01, 0x00, 0x10, 0x00, 0x01, 0x00, 0x0D, // wrapper
+                0xC0, 0x01, 0xC1, 0x00, 0x03, 0x01, 0x00, 0x01, 0x08, 0x00, 0xFF, 0x02, 0x00,
+            ]
+        );
+        assert_eq!(AARQ.len(), usize::from(AARQ[1]) + 2);
+    }
+
+    #[test]
+    fn test_association_result() {
+        let accepted = [
+            0x61, 0x0C, 0xA1, 0x03, 0x06, 0x01, 0x00, 0xA2, 0x03, 0x02, 0x01, 0x00, 0xA3, 0x00,
+        ];
+        assert!(check_aare(&accepted).is_ok());
+
+        let mut rejected = accepted;
+        rejected[11] = 0x01;
+        assert!(check_aare(&rejected).unwrap_err().to_string().contains("rejected"));
+        assert!(check_aare(&[0x62, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_register_decoding() {
+        let value = get_response_data(&[0xC4, 0x01, 0xC1, 0x00, 0x06, 0x00, 0x01, 0xE2, 0x40]).unwrap();
+        assert_eq!(decode_integer(value).unwrap(), 123_456);
+        assert_eq!(decode_integer(&[0x10, 0xFF, 0xFE]).unwrap(), -2);
+        assert_eq!(decode_integer(&[0x15, 0, 0, 0, 0, 0, 0, 0x01, 0x00]).unwrap(), 256);
+        assert!(decode_integer(&[0x09, 0x01, 0x00]).is_err());
+
+        let scaler_unit = get_response_data(&[0xC4, 0x01, 0xC1, 0x00, 0x02, 0x02, 0x0F, 0x01, 0x16, 0x1E]).unwrap();
+        let (scaler, unit) = decode_scaler_unit(scaler_unit).unwrap();
+        assert_eq!((scaler, unit), (1, UNIT_WATT_HOUR));
+        assert_eq!(to_kwh(123_456, scaler, unit).unwrap(), 1234.56);
+        assert_eq!(to_kwh(5, 3, UNIT_WATT_HOUR).unwrap(), 5.0);
+        assert!(to_kwh(5, 0, 27).is_err());
+
+        assert!(get_response_data(&[0xC4, 0x01, 0xC1, 0x01, 0x04]).is_err());
+    }
+}
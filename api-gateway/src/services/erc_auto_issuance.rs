@@ -0,0 +1,342 @@
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::tenant::{TenantScope, CERTIFICATE_TENANT};
+use crate::error::{ApiError, Result};
+use crate::models::energy::MeterGeneration;
+use crate::models::erc::ErcAutoIssuance;
+use crate::services::scheduler::{spawn_cron, CronSchedule};
+use crate::services::timeseries::TimeseriesStore;
+use crate::services::tx_queue::{enqueue_with, TxOperation};
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+pub const AUTO_ISSUANCE_COLUMNS: &str = "certificate_id, user_id, renewable_source, energy_amount, generated_from, \
+    generated_to, net_generation_kwh, provenance, tx_job_id, created_at";
+
+/// Most issuances returned by one history request
+pub const MAX_HISTORY_LIMIT: i64 = 500;
+
+/// Hours readings are given to arrive from polled and buffering meters before they are certified
+const SETTLE_HOURS: i64 = 1;
+
+/// Uncertified generation of one prosumer from meters of one renewable source
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct IssuanceAccount {
+    user_id: Uuid,
+    /// Meter type of the prosumer's solar or wind meters, certified as that source
+    renewable_source: String,
+    meter_ids: Vec<String>,
+    /// Start of the first uncertified hour
+    generated_from: DateTime<Utc>,
+    carried_kwh: f64,
+    /// Whether a certificate was issued before; first certificates count from the meters' provisioning
+    opened: bool,
+}
+
+/// Whole kWh to certify and the fraction to carry, once `carried_kwh` plus the window's net
+/// generation reaches `threshold_kwh`
+fn certifiable(carried_kwh: f64, generation: &[MeterGeneration], threshold_kwh: f64) -> Option<(u64, f64, f64)> {
+    let total = carried_kwh + generation.iter().map(|meter| meter.net_kwh).sum::<f64>();
+    if total < threshold_kwh.max(1.0) {
+        return None;
+    }
+    let energy = total.floor();
+    Some((energy as u64, total - energy, total))
+}
+
+/// End of the last hour whose readings are considered complete at `now`
+fn settled_through(now: DateTime<Utc>) -> DateTime<Utc> {
+    let hour = now.duration_trunc(Duration::hours(1)).unwrap_or(now);
+    hour - Duration::hours(SETTLE_HOURS)
+}
+
+/// Certificate ID, the same for every replica certifying an account through the same hour
+fn certificate_id(user_id: Uuid, renewable_source: &str, through: DateTime<Utc>) -> String {
+    format!(
+        "AUTO-{}-{}-{}",
+        renewable_source.to_uppercase(),
+        user_id.simple(),
+        through.format("%Y%m%d%H")
+    )
+}
+
+/// On-chain `validation_data` pointing back at the window and meters behind a certificate
+fn validation_data(from: DateTime<Utc>, through: DateTime<Utc>, generation: &[MeterGeneration], total_kwh: f64) -> String {
+    format!(
+        "auto-issuance;window={}/{};meters={};readings={};net_kwh={:.3}",
+        from.format("%Y-%m-%dT%H:%MZ"),
+        through.format("%Y-%m-%dT%H:%MZ"),
+        generation.len(),
+        generation.iter().map(|meter| meter.reading_count).sum::<i64>(),
+        total_kwh
+    )
+}
+
+/// Issues ERCs from prosumers' validated net generation without an authority request
+///
+/// Readings held in quarantine are not in TimescaleDB and so are not certified; approved
+/// later for an hour already certified, they are not certified either.
+#[derive(Clone)]
+pub struct AutoIssuer {
+    db: PgPool,
+    timeseries: TimeseriesStore,
+    threshold_kwh: f64,
+    clock: SharedClock,
+}
+
+impl AutoIssuer {
+    pub fn new(db: PgPool, timeseries: TimeseriesStore, threshold_kwh: f64, clock: SharedClock) -> Self {
+        Self {
+            db,
+            timeseries,
+            threshold_kwh,
+            clock,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let threshold_kwh = state.config.erc_auto_issuance_threshold_kwh;
+        if !threshold_kwh.is_finite() || threshold_kwh < 1.0 {
+            return Err(ApiError::Configuration(
+                "ERC_AUTO_ISSUANCE_THRESHOLD_KWH must be at least 1 kWh".to_string(),
+            ));
+        }
+
+        Ok(Self::new(
+            state.db.clone(),
+            TimeseriesStore::from_state(state),
+            threshold_kwh,
+            state.clock.clone(),
+        ))
+    }
+
+    /// Automatically issued certificates visible in `scope`, newest first
+    pub async fn list(&self, user_id: Option<Uuid>, scope: &TenantScope, limit: i64) -> Result<Vec<ErcAutoIssuance>> {
+        let query = format!(
+            "SELECT {} FROM erc_auto_issuances c
+             WHERE ($1::uuid IS NULL OR user_id = $1) AND {}
+             ORDER BY created_at DESC
+             LIMIT $4",
+            AUTO_ISSUANCE_COLUMNS,
+            TenantScope::condition(CERTIFICATE_TENANT, 2)
+        );
+        Ok(sqlx::query_as::<_, ErcAutoIssuance>(&query)
+            .bind(user_id)
+            .bind(scope.is_campus())
+            .bind(scope.tenant())
+            .bind(limit.clamp(1, MAX_HISTORY_LIMIT))
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Issue a certificate to every prosumer whose net generation certified through the last
+    /// settled hour before `now` has crossed the threshold, returning those issued
+    ///
+    /// Runs are idempotent: replicas and manual runs reaching the same hour issue each
+    /// certificate once.
+    pub async fn run(&self, now: DateTime<Utc>) -> Result<Vec<ErcAutoIssuance>> {
+        let through = settled_through(now);
+        let mut issued = Vec::new();
+        for account in self.accounts().await? {
+            if account.generated_from >= through {
+                continue;
+            }
+            match self.issue_due(&account, through).await {
+                Ok(Some(issuance)) => issued.push(issuance),
+                Ok(None) => {}
+                Err(e) => tracing::error!(
+                    "Automatic ERC issuance for {} ({}) failed: {}",
+                    account.user_id,
+                    account.renewable_source,
+                    e
+                ),
+            }
+        }
+
+        tracing::info!("Automatic ERC issuance through {} issued {} certificates", through, issued.len());
+        Ok(issued)
+    }
+
+    async fn accounts(&self) -> Result<Vec<IssuanceAccount>> {
+        Ok(sqlx::query_as::<_, IssuanceAccount>(
+            "SELECT m.user_id, m.meter_type AS renewable_source,
+                    array_agg(m.meter_id::text ORDER BY m.meter_id) AS meter_ids,
+                    COALESCE(a.issued_through, date_trunc('hour', MIN(m.created_at))) AS generated_from,
+                    COALESCE(a.carried_kwh, 0) AS carried_kwh,
+                    a.issued_through IS NOT NULL AS opened
+             FROM meters m
+             LEFT JOIN erc_issuance_accounts a ON a.user_id = m.user_id AND a.renewable_source = m.meter_type
+             WHERE m.meter_type IN ('solar', 'wind')
+             GROUP BY m.user_id, m.meter_type, a.issued_through, a.carried_kwh",
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    /// Queue `issue_erc` for an account whose generation up to `through` crossed the threshold
+    async fn issue_due(&self, account: &IssuanceAccount, through: DateTime<Utc>) -> Result<Option<ErcAutoIssuance>> {
+        let generation = self
+            .timeseries
+            .net_generation(&account.meter_ids, account.generated_from, through)
+            .await?;
+        let Some((energy_amount, carried_kwh, total_kwh)) =
+            certifiable(account.carried_kwh, &generation, self.threshold_kwh)
+        else {
+            return Ok(None);
+        };
+
+        let certificate_id = certificate_id(account.user_id, &account.renewable_source, through);
+        let mut tx = self.db.begin().await?;
+
+        // Advance the account only from where this run read it, so a replica racing over the
+        // same hours issues nothing
+        let advanced = sqlx::query(
+            "INSERT INTO erc_issuance_accounts (user_id, renewable_source, issued_through, carried_kwh)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (user_id, renewable_source) DO UPDATE
+                 SET issued_through = EXCLUDED.issued_through, carried_kwh = EXCLUDED.carried_kwh
+                 WHERE erc_issuance_accounts.issued_through = $5",
+        )
+        .bind(account.user_id)
+        .bind(&account.renewable_source)
+        .bind(through)
+        .bind(carried_kwh)
+        .bind(account.opened.then_some(account.generated_from))
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if advanced == 0 {
+            tracing::debug!("ERC {} already issued by another replica", certificate_id);
+            return Ok(None);
+        }
+
+        let operation = TxOperation::IssueErc {
+            certificate_id: certificate_id.clone(),
+            energy_amount,
+            renewable_source: account.renewable_source.clone(),
+            validation_data: validation_data(account.generated_from, through, &generation, total_kwh),
+            source_readings: Vec::new(),
+        };
+        let job = enqueue_with(&mut *tx, &operation, None).await?;
+
+        let provenance: Vec<_> = generation
+            .iter()
+            .map(|meter| json!({ "meter_id": meter.meter_id, "net_kwh": meter.net_kwh, "reading_count": meter.reading_count }))
+            .collect();
+        let query = format!(
+            "INSERT INTO erc_auto_issuances (certificate_id, user_id, renewable_source, energy_amount,
+                 generated_from, generated_to, net_generation_kwh, provenance, tx_job_id, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             RETURNING {}",
+            AUTO_ISSUANCE_COLUMNS
+        );
+        let issuance = sqlx::query_as::<_, ErcAutoIssuance>(&query)
+            .bind(&certificate_id)
+            .bind(account.user_id)
+            .bind(&account.renewable_source)
+            .bind(energy_amount as i64)
+            .bind(account.generated_from)
+            .bind(through)
+            .bind(total_kwh)
+            .bind(json!(provenance))
+            .bind(job.id)
+            .bind(self.clock.now())
+            .fetch_one(&mut *tx)
+            .await?;
+
+        // Certificates belong to their prosumer's building or faculty
+        sqlx::query(
+            "INSERT INTO erc_certificate_tenants (certificate_id, tenant_id)
+             SELECT $1, tenant_id FROM users WHERE id = $2 AND tenant_id IS NOT NULL
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(&certificate_id)
+        .bind(account.user_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            "Queued automatic ERC {} for {} kWh of {} generation by {} (job {})",
+            certificate_id,
+            energy_amount,
+            account.renewable_source,
+            account.user_id,
+            job.id
+        );
+        metrics::counter!("erc_auto_issuances_total", "source" => account.renewable_source.clone()).increment(1);
+        metrics::counter!("erc_auto_issued_kwh_total", "source" => account.renewable_source.clone())
+            .increment(energy_amount);
+        Ok(Some(issuance))
+    }
+
+    /// Issue due certificates at every time matching `schedule`
+    pub fn spawn(self, schedule: CronSchedule) {
+        let clock = self.clock.clone();
+        spawn_cron("erc_auto_issuance", schedule, clock, move |scheduled_for| {
+            let issuer = self.clone();
+            async move { issuer.run(scheduled_for).await.map(|_| ()) }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    use crate::utils::validation::{MAX_CERTIFICATE_ID_LEN, MAX_VALIDATION_DATA_LEN};
+
+    fn meter(meter_id: &str, net_kwh: f64, reading_count: i64) -> MeterGeneration {
+        MeterGeneration {
+            meter_id: meter_id.to_string(),
+            net_kwh,
+            reading_count,
+        }
+    }
+
+    #[test]
+    fn test_generation_is_certified_in_whole_kwh_past_the_threshold() {
+        let generation = [meter("SM-1", 60.25, 96), meter("SM-2", 39.5, 96)];
+        assert_eq!(certifiable(0.0, &generation, 100.0), None);
+
+        let (energy, carried, total) = certifiable(0.5, &generation, 100.0).unwrap();
+        assert_eq!(energy, 100);
+        assert!((carried - 0.25).abs() < 1e-9);
+        assert!((total - 100.25).abs() < 1e-9);
+
+        // Certificates are never issued for less than a kWh
+        assert_eq!(certifiable(0.0, &[meter("SM-1", 0.9, 4)], 0.5), None);
+    }
+
+    #[test]
+    fn test_only_settled_hours_are_certified() {
+        let now = Utc.with_ymd_and_hms(2024, 10, 1, 12, 5, 0).unwrap();
+        assert_eq!(settled_through(now), Utc.with_ymd_and_hms(2024, 10, 1, 11, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_certificate_references_fit_on_chain() {
+        let user_id = Uuid::new_v4();
+        let through = Utc.with_ymd_and_hms(2024, 10, 1, 11, 0, 0).unwrap();
+
+        let id = certificate_id(user_id, "solar", through);
+        assert_eq!(id, format!("AUTO-SOLAR-{}-2024100111", user_id.simple()));
+        assert!(id.len() <= MAX_CERTIFICATE_ID_LEN);
+
+        let data = validation_data(
+            through - Duration::days(30),
+            through,
+            &[meter("SM-1", 60.25, 96), meter("SM-2", 39.5, 90)],
+            100.25,
+        );
+        assert_eq!(
+            data,
+            "auto-issuance;window=2024-09-01T11:00Z/2024-10-01T11:00Z;meters=2;readings=186;net_kwh=100.250"
+        );
+        assert!(data.len() <= MAX_VALIDATION_DATA_LEN);
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::Stream;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tokio::sync::watch;
+
+use crate::database::schema::types::ErcStatus;
+use crate::error::Result;
+use crate::models::erc::ErcEvent;
+use crate::utils::clock::SharedClock;
+
+/// Postgres channel `record_erc_certificate_event` announces recorded event IDs on
+pub const ERC_EVENT_CHANNEL: &str = "erc_certificate_events";
+
+const ERC_EVENT_COLUMNS: &str = "id, certificate_id, event_type, status, authority, energy_amount, slot, occurred_at";
+
+/// Events read per query while catching a stream up
+const EVENT_PAGE_SIZE: i64 = 100;
+
+/// Wait before a stream retries after its database read failed
+const STREAM_RETRY: Duration = Duration::from_secs(5);
+
+/// Certificate lifecycle events recorded in `erc_certificate_events`, streamed as they arrive
+///
+/// The `erc_certificates` trigger records issuance, validation, revocation and on-chain
+/// expiry; `record_lapsed` adds expiries of certificates nobody called `expire_erc` for.
+/// Every stream reads the table itself, so one that fell behind or resumed after a
+/// disconnect catches up from its last event ID.
+pub struct ErcEventFeed {
+    db: PgPool,
+    recorded: watch::Sender<()>,
+    clock: SharedClock,
+}
+
+impl ErcEventFeed {
+    pub fn new(db: PgPool, clock: SharedClock) -> Self {
+        let (recorded, _) = watch::channel(());
+        Self { db, recorded, clock }
+    }
+
+    /// ID of the latest recorded event, 0 before the first
+    pub async fn latest_id(&self) -> Result<i64> {
+        Ok(sqlx::query_scalar("SELECT COALESCE(MAX(id), 0) FROM erc_certificate_events")
+            .fetch_one(&self.db)
+            .await?)
+    }
+
+    /// Up to `limit` events recorded after `after`, oldest first
+    pub async fn events_after(&self, after: i64, limit: i64) -> Result<Vec<ErcEvent>> {
+        let query = format!(
+            "SELECT {} FROM erc_certificate_events WHERE id > $1 ORDER BY id LIMIT $2",
+            ERC_EVENT_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, ErcEvent>(&query)
+            .bind(after)
+            .bind(limit)
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Record expiry of valid certificates past `expires_at` that were never expired on-chain,
+    /// returning how many were recorded
+    pub async fn record_lapsed(&self) -> Result<u64> {
+        let recorded = sqlx::query(
+            "INSERT INTO erc_certificate_events (certificate_id, event_type, status, authority, energy_amount, occurred_at)
+             SELECT certificate_id, $1, $2, authority, energy_amount, expires_at FROM erc_certificates c
+             WHERE status = $3 AND expires_at <= $4
+               AND NOT EXISTS (
+                   SELECT 1 FROM erc_certificate_events e
+                   WHERE e.certificate_id = c.certificate_id AND e.event_type = $1
+               )
+             ORDER BY expires_at
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(ErcEvent::EXPIRED)
+        .bind(ErcStatus::Expired)
+        .bind(ErcStatus::Valid)
+        .bind(self.clock.now())
+        .execute(&self.db)
+        .await?
+        .rows_affected();
+
+        if recorded > 0 {
+            sqlx::query("SELECT pg_notify($1, '')")
+                .bind(ERC_EVENT_CHANNEL)
+                .execute(&self.db)
+                .await?;
+        }
+        Ok(recorded)
+    }
+
+    /// Wake streams when events are recorded, and record lapsed expiries every `sweep_interval`
+    pub fn spawn(self: &Arc<Self>, sweep_interval: Duration) {
+        let feed = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = feed.listen().await {
+                    tracing::error!("ERC event listener failed: {}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+
+        let feed = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(sweep_interval);
+            loop {
+                interval.tick().await;
+                match feed.record_lapsed().await {
+                    Ok(0) => {}
+                    Ok(recorded) => tracing::info!("Recorded {} lapsed ERC certificate expiries", recorded),
+                    Err(e) => tracing::error!("ERC expiry sweep failed: {}", e),
+                }
+            }
+        });
+    }
+
+    async fn listen(&self) -> Result<()> {
+        let mut listener = PgListener::connect_with(&self.db).await?;
+        listener.listen(ERC_EVENT_CHANNEL).await?;
+
+        // Events recorded while disconnected were not announced
+        self.recorded.send_replace(());
+        tracing::info!("ERC event feed listening on {}", ERC_EVENT_CHANNEL);
+
+        loop {
+            listener.recv().await?;
+            self.recorded.send_replace(());
+        }
+    }
+
+    /// Events recorded after `after`, as they are recorded; ends only when the feed is dropped
+    pub fn stream(self: &Arc<Self>, after: i64) -> impl Stream<Item = ErcEvent> + Send + 'static {
+        let cursor = EventCursor {
+            feed: Arc::clone(self),
+            recorded: self.recorded.subscribe(),
+            after,
+            pending: VecDeque::new(),
+        };
+
+        futures::stream::unfold(cursor, |mut cursor| async move {
+            loop {
+                if let Some(event) = cursor.pending.pop_front() {
+                    return Some((event, cursor));
+                }
+
+                // Marked seen before reading, so an event recorded meanwhile wakes the next wait
+                cursor.recorded.borrow_and_update();
+                match cursor.feed.events_after(cursor.after, EVENT_PAGE_SIZE).await {
+                    Ok(events) if !events.is_empty() => {
+                        cursor.after = events.last().map_or(cursor.after, |event| event.id);
+                        cursor.pending.extend(events);
+                    }
+                    Ok(_) => {
+                        if cursor.recorded.changed().await.is_err() {
+                            return None;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("ERC event stream read failed: {}", e);
+                        tokio::time::sleep(STREAM_RETRY).await;
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Position of one stream in the event table
+struct EventCursor {
+    feed: Arc<ErcEventFeed>,
+    recorded: watch::Receiver<()>,
+    after: i64,
+    pending: VecDeque<ErcEvent>,
+}
@@ -0,0 +1,478 @@
+use std::str::FromStr;
+
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use chrono::{DateTime, Datelike, Utc};
+use governance::RenewableSource;
+use uuid::Uuid;
+
+use crate::error::{ApiError, Result};
+use crate::models::erc::ErcTransaction;
+use crate::services::blockchain::BlockchainService;
+use crate::services::chain_cache::ChainCache;
+use crate::services::fees::{FeeOperation, FeeStrategy};
+use crate::services::fee_payers::{sign_transaction, FeePayerPool};
+use crate::services::gateway_signer::GatewaySigner;
+use crate::services::transaction::{anchor_instruction, serialize_transaction, AccountMeta, Instruction, Message, Pubkey};
+use crate::services::tx_signer::TxSigner;
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+/// Certificate to issue through the governance program's `issue_erc`
+#[derive(Debug, Clone)]
+pub struct IssueErcParams {
+    pub certificate_id: String,
+    pub energy_amount: u64,
+    pub renewable_source: RenewableSource,
+    pub validation_data: String,
+    /// Oracle meter reading PDAs backing the certificate
+    pub source_readings: Vec<Pubkey>,
+}
+
+/// UTC month as `YYYYMM`, selecting the governance program's month index PDA
+fn month_period(now: DateTime<Utc>) -> u32 {
+    now.year() as u32 * 100 + now.month()
+}
+
+/// Issues and validates ERC certificates as the Engineering Department authority
+#[derive(Clone)]
+pub struct ErcIssuer {
+    chain: BlockchainService,
+    cache: ChainCache,
+    program_id: Pubkey,
+    signer: GatewaySigner,
+    fee_payers: FeePayerPool,
+    fees: FeeStrategy,
+    clock: SharedClock,
+}
+
+impl ErcIssuer {
+    pub fn new(
+        chain: BlockchainService,
+        cache: ChainCache,
+        program_id: Pubkey,
+        signer: GatewaySigner,
+        fee_payers: FeePayerPool,
+        fees: FeeStrategy,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            chain,
+            cache,
+            program_id,
+            signer,
+            fee_payers,
+            fees,
+            clock,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let program_id = Pubkey::from_str(&state.config.governance_program_id)
+            .map_err(|e| ApiError::Configuration(format!("Invalid GOVERNANCE_PROGRAM_ID: {}", e)))?;
+
+        Ok(Self::new(
+            state.blockchain_service.clone(),
+            ChainCache::from_state(state),
+            program_id,
+            state.signer.clone(),
+            state.fee_payers.clone(),
+            FeeStrategy::from_state(state),
+            state.clock.clone(),
+        ))
+    }
+
+    fn address(&self, seeds: &[&[u8]]) -> Result<Pubkey> {
+        Pubkey::find_program_address(seeds, &self.program_id)
+            .map(|(address, _)| address)
+            .ok_or_else(|| ApiError::Internal("No program address for seeds".to_string()))
+    }
+
+    pub fn certificate_address(&self, certificate_id: &str) -> Result<Pubkey> {
+        self.address(&[b"erc_certificate", certificate_id.as_bytes()])
+    }
+
+    fn issue_instruction(&self, authority: Pubkey, params: IssueErcParams, period: u32) -> Result<Instruction> {
+        let accounts = governance::accounts::IssueErc {
+            poa_config: self.address(&[b"poa_config"])?.into(),
+            erc_certificate: self.certificate_address(&params.certificate_id)?.into(),
+            treasury: self.address(&[b"treasury"])?.into(),
+            source_index: self
+                .address(&[b"erc_source_index", &params.renewable_source.index_seed()])?
+                .into(),
+            month_index: self.address(&[b"erc_month_index", &period.to_le_bytes()])?.into(),
+            // Gateway issuance carries no meter attestation
+            meter_account: None,
+            instructions: None,
+            authority: authority.into(),
+            system_program: anchor_lang::system_program::ID,
+        };
+        let mut instruction = anchor_instruction(
+            self.program_id,
+            accounts,
+            governance::instruction::IssueErc {
+                certificate_id: params.certificate_id,
+                energy_amount: params.energy_amount,
+                renewable_source: params.renewable_source,
+                validation_data: params.validation_data,
+                period,
+            },
+        );
+        // Each read-only source reading is followed by the writable PDA claiming its energy
+        for reading in params.source_readings {
+            instruction.accounts.push(AccountMeta {
+                pubkey: reading,
+                is_signer: false,
+                is_writable: false,
+            });
+            instruction.accounts.push(AccountMeta {
+                pubkey: self.address(&[b"reading_claim", &reading.0])?,
+                is_signer: false,
+                is_writable: true,
+            });
+        }
+        Ok(instruction)
+    }
+
+    fn validate_instruction(&self, authority: Pubkey, certificate_id: &str, recipient: Pubkey) -> Result<(Instruction, Pubkey)> {
+        let erc_certificate = self.certificate_address(certificate_id)?;
+        let nft_mint = self.address(&[b"erc_mint", &erc_certificate.0])?;
+        let recipient_token_account = get_associated_token_address_with_program_id(
+            &recipient.into(),
+            &nft_mint.into(),
+            &anchor_spl::token_2022::ID,
+        );
+
+        let accounts = governance::accounts::ValidateErc {
+            poa_config: self.address(&[b"poa_config"])?.into(),
+            erc_certificate: erc_certificate.into(),
+            nft_mint: nft_mint.into(),
+            recipient: recipient.into(),
+            recipient_token_account,
+            authority: authority.into(),
+            token_program: anchor_spl::token_2022::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: anchor_lang::system_program::ID,
+        };
+        let instruction = anchor_instruction(self.program_id, accounts, governance::instruction::ValidateErcForTrading {});
+        Ok((instruction, nft_mint))
+    }
+
+    fn challenge_instruction(
+        &self,
+        authority: Pubkey,
+        certificate_id: &str,
+        dispute_id: Uuid,
+        reason: &str,
+    ) -> Result<Instruction> {
+        let accounts = governance::accounts::ChallengeErc {
+            poa_config: self.address(&[b"poa_config"])?.into(),
+            erc_certificate: self.certificate_address(certificate_id)?.into(),
+            authority: authority.into(),
+        };
+        Ok(anchor_instruction(
+            self.program_id,
+            accounts,
+            governance::instruction::ChallengeErc {
+                dispute_id: dispute_id.into_bytes(),
+                reason: reason.to_string(),
+            },
+        ))
+    }
+
+    /// `holder_token_account` holds the certificate token burned when the challenge is upheld
+    fn resolve_challenge_instruction(
+        &self,
+        authority: Pubkey,
+        certificate_id: &str,
+        dispute_id: Uuid,
+        upheld: bool,
+        resolution: &str,
+        holder_token_account: Option<Pubkey>,
+    ) -> Result<Instruction> {
+        let erc_certificate = self.certificate_address(certificate_id)?;
+        let accounts = governance::accounts::RevokeErc {
+            poa_config: self.address(&[b"poa_config"])?.into(),
+            erc_certificate: erc_certificate.into(),
+            nft_mint: self.address(&[b"erc_mint", &erc_certificate.0])?.into(),
+            holder_token_account: holder_token_account.map(Into::into),
+            token_program: holder_token_account.map(|_| anchor_spl::token_2022::ID),
+            authority: authority.into(),
+        };
+        Ok(anchor_instruction(
+            self.program_id,
+            accounts,
+            governance::instruction::ResolveChallenge {
+                dispute_id: dispute_id.into_bytes(),
+                upheld,
+                resolution: resolution.to_string(),
+            },
+        ))
+    }
+
+    /// Submit `issue_erc` for a new certificate
+    pub async fn issue(&self, params: IssueErcParams) -> Result<ErcTransaction> {
+        let authority = self.signer.lease().await?;
+        let certificate_id = params.certificate_id.clone();
+        let certificate_address = self.certificate_address(&certificate_id)?;
+        let instruction = self.issue_instruction(
+            authority.pubkey(),
+            params,
+            month_period(self.clock.now()),
+        )?;
+
+        let signature = self.submit(authority.as_ref(), &[instruction]).await?;
+        tracing::info!("Issued ERC {} in {}", certificate_id, signature);
+        self.invalidate_cached(certificate_address).await?;
+
+        Ok(ErcTransaction {
+            certificate_id,
+            certificate_address: certificate_address.to_string(),
+            signature,
+            nft_mint: None,
+        })
+    }
+
+    /// Submit `validate_erc_for_trading`, minting the certificate token to `recipient`
+    pub async fn validate(&self, certificate_id: &str, recipient: Pubkey) -> Result<ErcTransaction> {
+        let authority = self.signer.lease().await?;
+        let (instruction, nft_mint) =
+            self.validate_instruction(authority.pubkey(), certificate_id, recipient)?;
+
+        let signature = self.submit(authority.as_ref(), &[instruction]).await?;
+        tracing::info!("Validated ERC {} for trading in {}", certificate_id, signature);
+        let certificate_address = self.certificate_address(certificate_id)?;
+        self.invalidate_cached(certificate_address).await?;
+
+        Ok(ErcTransaction {
+            certificate_id: certificate_id.to_string(),
+            certificate_address: certificate_address.to_string(),
+            signature,
+            nft_mint: Some(nft_mint.to_string()),
+        })
+    }
+
+    /// Submit `challenge_erc`, freezing the certificate while `dispute_id` is open
+    pub async fn challenge(&self, certificate_id: &str, dispute_id: Uuid, reason: &str) -> Result<ErcTransaction> {
+        let authority = self.signer.lease().await?;
+        let instruction = self.challenge_instruction(authority.pubkey(), certificate_id, dispute_id, reason)?;
+
+        let signature = self.submit(authority.as_ref(), &[instruction]).await?;
+        tracing::info!("Challenged ERC {} for dispute {} in {}", certificate_id, dispute_id, signature);
+        let certificate_address = self.certificate_address(certificate_id)?;
+        self.invalidate_cached(certificate_address).await?;
+
+        Ok(ErcTransaction {
+            certificate_id: certificate_id.to_string(),
+            certificate_address: certificate_address.to_string(),
+            signature,
+            nft_mint: None,
+        })
+    }
+
+    /// Submit `resolve_challenge`, revoking the certificate and burning its token if the
+    /// dispute was upheld
+    pub async fn resolve_challenge(
+        &self,
+        certificate_id: &str,
+        dispute_id: Uuid,
+        upheld: bool,
+        resolution: &str,
+    ) -> Result<ErcTransaction> {
+        let certificate_address = self.certificate_address(certificate_id)?;
+        let nft_mint = self.address(&[b"erc_mint", &certificate_address.0])?;
+        let holder_token_account = if upheld {
+            self.chain
+                .token_holder_account(&nft_mint.to_string())
+                .await?
+                .map(|account| Pubkey::from_str(&account).map_err(ApiError::Blockchain))
+                .transpose()?
+        } else {
+            None
+        };
+
+        let authority = self.signer.lease().await?;
+        let instruction = self.resolve_challenge_instruction(
+            authority.pubkey(),
+            certificate_id,
+            dispute_id,
+            upheld,
+            resolution,
+            holder_token_account,
+        )?;
+
+        let signature = self.submit(authority.as_ref(), &[instruction]).await?;
+        tracing::info!(
+            "Resolved challenge of ERC {} for dispute {} (upheld: {}) in {}",
+            certificate_id,
+            dispute_id,
+            upheld,
+            signature
+        );
+        self.invalidate_cached(certificate_address).await?;
+
+        Ok(ErcTransaction {
+            certificate_id: certificate_id.to_string(),
+            certificate_address: certificate_address.to_string(),
+            signature,
+            nft_mint: holder_token_account.map(|_| nft_mint.to_string()),
+        })
+    }
+
+    /// Drop cached reads of the certificate and the PoA config, whose counters changed
+    async fn invalidate_cached(&self, certificate_address: Pubkey) -> Result<()> {
+        let poa_config = self.address(&[b"poa_config"])?;
+        self.cache
+            .invalidate(&[certificate_address.to_string(), poa_config.to_string()])
+            .await;
+        Ok(())
+    }
+
+    /// Sign `instructions` as the authority, paid for by the next fee payer in the pool or,
+    /// without one, by the authority
+    async fn submit(&self, authority: &dyn TxSigner, instructions: &[Instruction]) -> Result<String> {
+        let instructions = self.fees.with_compute_budget(FeeOperation::ErcIssuance, instructions).await;
+        let blockhash = self.chain.recent_blockhash().await?;
+
+        let fee_payer = self.fee_payers.next();
+        let payer_key = fee_payer.as_ref().map_or_else(|| authority.pubkey(), |payer| payer.pubkey());
+        let message = Message::new(&instructions, payer_key, blockhash).map_err(ApiError::Internal)?;
+        let bytes = message.serialize();
+        let signatures = sign_transaction(message.signers(), &bytes, authority, fee_payer.as_deref()).await?;
+
+        self.chain.send_transaction(&serialize_transaction(&signatures, &bytes)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::database::redis_pool::RedisPool;
+    use crate::services::fees::FeeCeilings;
+    use crate::services::transaction::anchor_discriminator;
+    use crate::utils::clock::SimulatedClock;
+
+    fn issuer() -> ErcIssuer {
+        ErcIssuer::new(
+            BlockchainService::new("http://localhost:8899").unwrap(),
+            ChainCache::new(
+                RedisPool::new(redis::Client::open("redis://localhost").unwrap(), 1, Duration::from_secs(1)),
+                Default::default(),
+            ),
+            Pubkey([7; 32]),
+            GatewaySigner::local(None, SimulatedClock::new(Utc::now()).shared()),
+            FeePayerPool::new(Vec::new()),
+            FeeStrategy::new(
+                BlockchainService::new("http://localhost:8899").unwrap(),
+                FeeCeilings {
+                    erc_issuance: 0,
+                    governance: 0,
+                    channel_state: 0,
+                },
+            ),
+            SimulatedClock::new(Utc::now()).shared(),
+        )
+    }
+
+    #[test]
+    fn test_issue_instruction_layout() {
+        let issuer = issuer();
+        let authority = Pubkey([1; 32]);
+        let reading = Pubkey([2; 32]);
+        let instruction = issuer
+            .issue_instruction(
+                authority,
+                IssueErcParams {
+                    certificate_id: "ERC-7".to_string(),
+                    energy_amount: 250,
+                    renewable_source: RenewableSource::from_name("Wind"),
+                    validation_data: String::new(),
+                    source_readings: vec![reading],
+                },
+                202410,
+            )
+            .unwrap();
+
+        assert_eq!(&instruction.data[..8], &anchor_discriminator("issue_erc"));
+        assert_eq!(&instruction.data[8..12], &5u32.to_le_bytes());
+        assert_eq!(&instruction.data[12..17], b"ERC-7");
+        assert_eq!(&instruction.data[17..25], &250u64.to_le_bytes());
+        assert_eq!(instruction.data[25], 1); // wind
+        assert_eq!(&instruction.data[30..], &202410u32.to_le_bytes());
+
+        assert_eq!(instruction.accounts.len(), 11);
+        assert_eq!(instruction.accounts[1].pubkey, issuer.certificate_address("ERC-7").unwrap());
+        assert!(instruction.accounts[1].is_writable);
+        // Omitted meter attestation accounts are passed as the declared program ID
+        assert_eq!(instruction.accounts[5].pubkey, governance::ID.into());
+        assert_eq!(instruction.accounts[6].pubkey, governance::ID.into());
+        assert!(instruction.accounts[7].is_signer && instruction.accounts[7].pubkey == authority);
+        assert_eq!(
+            instruction.accounts[9],
+            AccountMeta { pubkey: reading, is_signer: false, is_writable: false }
+        );
+        assert_eq!(
+            instruction.accounts[10],
+            AccountMeta {
+                pubkey: issuer.address(&[b"reading_claim", &reading.0]).unwrap(),
+                is_signer: false,
+                is_writable: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_instruction_derives_certificate_token_accounts() {
+        let issuer = issuer();
+        let (instruction, nft_mint) = issuer.validate_instruction(Pubkey([1; 32]), "ERC-7", Pubkey([3; 32])).unwrap();
+        let certificate = issuer.certificate_address("ERC-7").unwrap();
+
+        assert_eq!(instruction.data, anchor_discriminator("validate_erc_for_trading"));
+        assert_eq!(nft_mint, Pubkey::find_program_address(&[b"erc_mint", &certificate.0], &Pubkey([7; 32])).unwrap().0);
+        assert_eq!(instruction.accounts[2].pubkey, nft_mint);
+        assert_eq!(instruction.accounts[3].pubkey, Pubkey([3; 32]));
+        assert!(instruction.accounts[4].is_writable);
+    }
+
+    #[test]
+    fn test_challenge_instructions_carry_dispute() {
+        let issuer = issuer();
+        let dispute = Uuid::from_bytes([9; 16]);
+        let certificate = issuer.certificate_address("ERC-7").unwrap();
+
+        let challenge = issuer.challenge_instruction(Pubkey([1; 32]), "ERC-7", dispute, "meter tampering").unwrap();
+        assert_eq!(&challenge.data[..8], &anchor_discriminator("challenge_erc"));
+        assert_eq!(&challenge.data[8..24], &[9; 16]);
+        assert_eq!(&challenge.data[28..], b"meter tampering");
+        assert_eq!(challenge.accounts[1].pubkey, certificate);
+        assert_eq!(challenge.accounts.len(), 3);
+        assert!(challenge.accounts[2].is_signer && !challenge.accounts[2].is_writable);
+
+        let holder = Pubkey([4; 32]);
+        let upheld = issuer
+            .resolve_challenge_instruction(Pubkey([1; 32]), "ERC-7", dispute, true, "confirmed", Some(holder))
+            .unwrap();
+        assert_eq!(&upheld.data[..8], &anchor_discriminator("resolve_challenge"));
+        assert_eq!(&upheld.data[8..24], &[9; 16]);
+        assert_eq!(upheld.data[24], 1);
+        assert_eq!(upheld.accounts[3].pubkey, holder);
+        assert_eq!(upheld.accounts[4].pubkey, anchor_spl::token_2022::ID.into());
+
+        // Without a holder the optional token accounts are passed as the declared program ID
+        let dismissed = issuer
+            .resolve_challenge_instruction(Pubkey([1; 32]), "ERC-7", dispute, false, "", None)
+            .unwrap();
+        assert_eq!(dismissed.data[24], 0);
+        assert_eq!(dismissed.accounts[3].pubkey, governance::ID.into());
+        assert_eq!(dismissed.accounts[4].pubkey, governance::ID.into());
+    }
+
+    #[test]
+    fn test_month_period_and_sources() {
+        let now = DateTime::parse_from_rfc3339("2024-10-31T23:59:59Z").unwrap().with_timezone(&Utc);
+        assert_eq!(month_period(now), 202410);
+        assert_eq!(RenewableSource::from_name("solar"), RenewableSource::Solar);
+        assert_eq!(RenewableSource::from_name("geothermal"), RenewableSource::Other("geothermal".to_string()));
+    }
+}
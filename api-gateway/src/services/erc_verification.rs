@@ -0,0 +1,322 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signer, SigningKey};
+use reqwest::Url;
+
+use crate::database::schema::types::ErcStatus;
+use crate::error::{ApiError, Result};
+use crate::models::erc::{ErcVerification, ErcVerificationLink};
+use crate::services::blockchain::BlockchainService;
+use crate::services::certificates::effective_status;
+use crate::services::chain_cache::{CachedAccount, ChainCache};
+use crate::services::transaction::{anchor_account_discriminator, verify_signature, Pubkey, SIGNATURE_LENGTH};
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+/// Domain prefix of signed certificate verification links
+const VERIFICATION_DOMAIN: &[u8] = b"gridtokenx:erc-verify:v1";
+
+/// Certificate fields read from the governance program's `ErcCertificate` account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnChainCertificate {
+    pub renewable_source: String,
+    pub certificate_id: String,
+    pub energy_amount: u64,
+    pub issued_at: i64,
+    pub expires_at: Option<i64>,
+    pub status: ErcStatus,
+}
+
+/// Decode an `ErcCertificate` account: source enum, certificate ID, authority, amount,
+/// validation data, issue time, optional expiry and status, in Borsh order
+pub fn parse_erc_certificate(data: &[u8]) -> Option<OnChainCertificate> {
+    let mut reader = BorshReader { data, offset: 0 };
+    if reader.take(8)? != anchor_account_discriminator("ErcCertificate") {
+        return None;
+    }
+
+    let renewable_source = match reader.u8()? {
+        0 => "solar".to_string(),
+        1 => "wind".to_string(),
+        2 => "biomass".to_string(),
+        3 => "hydro".to_string(),
+        4 => reader.string()?,
+        _ => return None,
+    };
+    let certificate_id = reader.string()?;
+    reader.take(32)?; // authority
+    let energy_amount = reader.u64()?;
+    reader.string()?; // validation data
+    let issued_at = reader.i64()?;
+    let expires_at = match reader.u8()? {
+        0 => None,
+        1 => Some(reader.i64()?),
+        _ => return None,
+    };
+    // Statuses added to the program later decode as `Unknown` rather than failing
+    let status = ErcStatus::from_code(reader.u8()?);
+
+    Some(OnChainCertificate {
+        renewable_source,
+        certificate_id,
+        energy_amount,
+        issued_at,
+        expires_at,
+        status,
+    })
+}
+
+struct BorshReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> BorshReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.offset..self.offset.checked_add(len)?)?;
+        self.offset += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn i64(&mut self) -> Option<i64> {
+        Some(i64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = u32::from_le_bytes(self.take(4)?.try_into().ok()?) as usize;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+}
+
+fn link_message(certificate_id: &str) -> Vec<u8> {
+    [VERIFICATION_DOMAIN, certificate_id.as_bytes()].concat()
+}
+
+/// Signs verification links for printed certificates and checks them against the chain
+#[derive(Clone)]
+pub struct CertificateVerifier {
+    chain: BlockchainService,
+    cache: ChainCache,
+    program_id: Pubkey,
+    signing_key: Option<SigningKey>,
+    public_base_url: String,
+    clock: SharedClock,
+}
+
+impl CertificateVerifier {
+    pub fn new(
+        chain: BlockchainService,
+        cache: ChainCache,
+        program_id: Pubkey,
+        signing_key: Option<SigningKey>,
+        public_base_url: String,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            chain,
+            cache,
+            program_id,
+            signing_key,
+            public_base_url,
+            clock,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let program_id = Pubkey::from_str(&state.config.governance_program_id)
+            .map_err(|e| ApiError::Configuration(format!("Invalid GOVERNANCE_PROGRAM_ID: {}", e)))?;
+
+        let signing_key = match state.config.certificate_signing_key.as_deref() {
+            Some(key) => {
+                let seed: [u8; 32] = bs58::decode(key)
+                    .into_vec()
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| {
+                        ApiError::Configuration("CERTIFICATE_SIGNING_KEY must be a base58 32-byte seed".to_string())
+                    })?;
+                Some(SigningKey::from_bytes(&seed))
+            }
+            None => None,
+        };
+
+        Ok(Self::new(
+            state.blockchain_service.clone(),
+            ChainCache::from_state(state),
+            program_id,
+            signing_key,
+            state.config.public_base_url.clone(),
+            state.clock.clone(),
+        ))
+    }
+
+    /// Signed URL to print as the certificate's QR code
+    pub fn link(&self, certificate_id: &str) -> Result<ErcVerificationLink> {
+        let key = self
+            .signing_key
+            .as_ref()
+            .ok_or_else(|| ApiError::Configuration("CERTIFICATE_SIGNING_KEY is not configured".to_string()))?;
+        let signature = bs58::encode(key.sign(&link_message(certificate_id)).to_bytes()).into_string();
+
+        let invalid_base = || ApiError::Configuration(format!("Invalid PUBLIC_BASE_URL: {}", self.public_base_url));
+        let mut url = Url::parse(&self.public_base_url).map_err(|_| invalid_base())?;
+        url.path_segments_mut()
+            .map_err(|_| invalid_base())?
+            .pop_if_empty()
+            .extend(["verify", "erc", certificate_id]);
+        url.query_pairs_mut().append_pair("sig", &signature);
+
+        Ok(ErcVerificationLink {
+            certificate_id: certificate_id.to_string(),
+            url: url.to_string(),
+            signature,
+        })
+    }
+
+    fn signature_valid(&self, certificate_id: &str, signature: &str) -> bool {
+        let Some(key) = &self.signing_key else {
+            return false;
+        };
+        let Some(signature) = bs58::decode(signature)
+            .into_vec()
+            .ok()
+            .filter(|bytes| bytes.len() == SIGNATURE_LENGTH)
+        else {
+            return false;
+        };
+
+        verify_signature(
+            &Pubkey(key.verifying_key().to_bytes()),
+            &link_message(certificate_id),
+            &signature,
+        )
+    }
+
+    /// Check the link signature, then the certificate's current on-chain state
+    ///
+    /// The chain is only queried for genuine links, so the endpoint cannot be used as an
+    /// open RPC proxy.
+    pub async fn verify(&self, certificate_id: &str, signature: &str) -> Result<ErcVerification> {
+        let now = self.clock.now();
+        let mut verification = ErcVerification {
+            certificate_id: certificate_id.to_string(),
+            verified: false,
+            signature_valid: self.signature_valid(certificate_id, signature),
+            status: None,
+            renewable_source: None,
+            energy_amount: None,
+            issued_at: None,
+            expires_at: None,
+            account_address: None,
+            checked_at: now,
+        };
+        if !verification.signature_valid {
+            return Ok(verification);
+        }
+
+        let (address, _) =
+            Pubkey::find_program_address(&[b"erc_certificate", certificate_id.as_bytes()], &self.program_id)
+                .ok_or_else(|| ApiError::Internal("No certificate address for ID".to_string()))?;
+        verification.account_address = Some(address.to_string());
+
+        let Some(data) = self
+            .cache
+            .get_account_data(&self.chain, &address.to_string(), CachedAccount::Certificate)
+            .await?
+        else {
+            verification.status = Some("not_found".to_string());
+            return Ok(verification);
+        };
+        let certificate = parse_erc_certificate(&data)
+            .filter(|certificate| certificate.certificate_id == certificate_id)
+            .ok_or_else(|| ApiError::Blockchain(format!("Account {} is not an ERC certificate", address)))?;
+
+        let expires_at = certificate.expires_at.and_then(|t| DateTime::<Utc>::from_timestamp(t, 0));
+        let status = effective_status(certificate.status, expires_at, now);
+
+        verification.verified = status == ErcStatus::Valid;
+        verification.status = Some(status.to_string());
+        verification.renewable_source = Some(certificate.renewable_source);
+        verification.energy_amount = Some(certificate.energy_amount);
+        verification.issued_at = DateTime::<Utc>::from_timestamp(certificate.issued_at, 0);
+        verification.expires_at = expires_at;
+        Ok(verification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn certificate_account(source: &[u8], status: u8, expires_at: Option<i64>) -> Vec<u8> {
+        let mut data = anchor_account_discriminator("ErcCertificate").to_vec();
+        data.extend_from_slice(source);
+        data.extend_from_slice(&6u32.to_le_bytes());
+        data.extend_from_slice(b"ERC-42");
+        data.extend_from_slice(&[9; 32]);
+        data.extend_from_slice(&1500u64.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        match expires_at {
+            Some(expiry) => {
+                data.push(1);
+                data.extend_from_slice(&expiry.to_le_bytes());
+            }
+            None => data.push(0),
+        }
+        data.push(status);
+        data.extend_from_slice(&[0; 64]); // trailing fields are ignored
+        data
+    }
+
+    #[test]
+    fn test_parse_erc_certificate() {
+        let certificate = parse_erc_certificate(&certificate_account(&[1], 2, Some(1_800_000_000))).unwrap();
+        assert_eq!(
+            certificate,
+            OnChainCertificate {
+                renewable_source: "wind".to_string(),
+                certificate_id: "ERC-42".to_string(),
+                energy_amount: 1500,
+                issued_at: 1_700_000_000,
+                expires_at: Some(1_800_000_000),
+                status: ErcStatus::Revoked,
+            }
+        );
+
+        let mut other = vec![4];
+        other.extend_from_slice(&10u32.to_le_bytes());
+        other.extend_from_slice(b"geothermal");
+        let certificate = parse_erc_certificate(&certificate_account(&other, 0, None)).unwrap();
+        assert_eq!(certificate.renewable_source, "geothermal");
+        assert_eq!(certificate.expires_at, None);
+        assert_eq!(certificate.status, ErcStatus::Valid);
+
+        let newer = parse_erc_certificate(&certificate_account(&[0], 9, None)).unwrap();
+        assert_eq!(newer.status, ErcStatus::Unknown(9));
+
+        let mut wrong_account = certificate_account(&[0], 0, None);
+        wrong_account[0] ^= 1;
+        assert!(parse_erc_certificate(&wrong_account).is_none());
+        assert!(parse_erc_certificate(&certificate_account(&[0], 0, None)[..30]).is_none());
+    }
+
+    #[test]
+    fn test_link_signature_binds_certificate_id() {
+        let key = SigningKey::from_bytes(&[5; 32]);
+        let signature = key.sign(&link_message("ERC-1")).to_bytes();
+        let pubkey = Pubkey(key.verifying_key().to_bytes());
+
+        assert!(verify_signature(&pubkey, &link_message("ERC-1"), &signature));
+        assert!(!verify_signature(&pubkey, &link_message("ERC-2"), &signature));
+    }
+}
@@ -0,0 +1,214 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use sqlx::{Executor, PgPool, Postgres};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::database::schema::types::OrderSide;
+use crate::error::{ApiError, Result};
+
+/// Postgres channel gateway events are announced on, by the gateway and by database triggers
+pub const EVENT_CHANNEL: &str = "gateway_events";
+
+/// Events a lagging subscriber can fall behind by before it skips some
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Something that happened to a user's orders, certificates, meters or statements
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GatewayEvent {
+    /// The matching engine filled the rest of an order
+    OrderFilled {
+        order_id: Uuid,
+        user_id: Uuid,
+        side: OrderSide,
+        energy_amount: Decimal,
+        filled_at: DateTime<Utc>,
+    },
+    /// A certificate auto-issued for the user's net generation landed on-chain
+    CertificateIssued {
+        certificate_id: String,
+        user_id: Uuid,
+        energy_amount: i64,
+        issued_at: DateTime<Utc>,
+    },
+    /// An active meter stopped reporting
+    MeterOffline {
+        meter_id: String,
+        user_id: Uuid,
+        /// `None` if the meter never reported
+        last_reading_at: Option<DateTime<Utc>>,
+        offline_since: DateTime<Utc>,
+    },
+    /// A billing period closed with a statement for the user
+    SettlementReady {
+        statement_id: Uuid,
+        billing_period_id: Uuid,
+        user_id: Uuid,
+        net_amount: Decimal,
+    },
+}
+
+impl GatewayEvent {
+    pub const ORDER_FILLED: &'static str = "order_filled";
+    pub const CERTIFICATE_ISSUED: &'static str = "certificate_issued";
+    pub const METER_OFFLINE: &'static str = "meter_offline";
+    pub const SETTLEMENT_READY: &'static str = "settlement_ready";
+    pub const EVENT_TYPES: [&'static str; 4] = [
+        Self::ORDER_FILLED,
+        Self::CERTIFICATE_ISSUED,
+        Self::METER_OFFLINE,
+        Self::SETTLEMENT_READY,
+    ];
+
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::OrderFilled { .. } => Self::ORDER_FILLED,
+            Self::CertificateIssued { .. } => Self::CERTIFICATE_ISSUED,
+            Self::MeterOffline { .. } => Self::METER_OFFLINE,
+            Self::SettlementReady { .. } => Self::SETTLEMENT_READY,
+        }
+    }
+
+    /// User the event concerns
+    pub fn user_id(&self) -> Uuid {
+        match self {
+            Self::OrderFilled { user_id, .. }
+            | Self::CertificateIssued { user_id, .. }
+            | Self::MeterOffline { user_id, .. }
+            | Self::SettlementReady { user_id, .. } => *user_id,
+        }
+    }
+
+    /// Identifies this occurrence of the event, however many replicas hear it
+    pub fn key(&self) -> String {
+        match self {
+            Self::OrderFilled { order_id, .. } => format!("{}:{}", Self::ORDER_FILLED, order_id),
+            Self::CertificateIssued { certificate_id, .. } => {
+                format!("{}:{}", Self::CERTIFICATE_ISSUED, certificate_id)
+            }
+            Self::MeterOffline {
+                meter_id,
+                offline_since,
+                ..
+            } => format!("{}:{}:{}", Self::METER_OFFLINE, meter_id, offline_since.timestamp()),
+            Self::SettlementReady { statement_id, .. } => format!("{}:{}", Self::SETTLEMENT_READY, statement_id),
+        }
+    }
+}
+
+/// Announce `event` on [`EVENT_CHANNEL`]
+///
+/// Run inside the transaction recording what happened, so the event is delivered once it
+/// commits and never for a rolled back change.
+pub async fn publish<'e, E>(executor: E, event: &GatewayEvent) -> Result<()>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let payload = serde_json::to_string(event)
+        .map_err(|e| ApiError::Internal(format!("Failed to serialize {} event: {}", event.event_type(), e)))?;
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(EVENT_CHANNEL)
+        .bind(payload)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Gateway events announced by any replica, fanned out to this replica's subscribers
+///
+/// Events announced while the listener is reconnecting are not replayed.
+pub struct EventBus {
+    db: PgPool,
+    sender: broadcast::Sender<GatewayEvent>,
+}
+
+impl EventBus {
+    pub fn new(db: PgPool) -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self { db, sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<GatewayEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Keep listening on [`EVENT_CHANNEL`], reconnecting after failures
+    pub fn spawn(self: &Arc<Self>) {
+        let bus = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = bus.listen().await {
+                    tracing::error!("Gateway event listener failed: {}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    async fn listen(&self) -> Result<()> {
+        let mut listener = PgListener::connect_with(&self.db).await?;
+        listener.listen(EVENT_CHANNEL).await?;
+        tracing::info!("Gateway event bus listening on {}", EVENT_CHANNEL);
+
+        loop {
+            let notification = listener.recv().await?;
+            match serde_json::from_str::<GatewayEvent>(notification.payload()) {
+                // Nobody subscribed yet is not an error
+                Ok(event) => {
+                    let _ = self.sender.send(event);
+                }
+                Err(e) => tracing::warn!("Ignoring malformed gateway event {}: {}", notification.payload(), e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_trigger_payload_parses() {
+        // As built by the publish_certificate_issued trigger
+        let payload = r#"{"event" : "certificate_issued", "certificate_id" : "ERC-7", "user_id" : "00000000-0000-0000-0000-000000000007", "energy_amount" : 1500, "issued_at" : "2024-09-23T12:00:00+00:00"}"#;
+        let event: GatewayEvent = serde_json::from_str(payload).unwrap();
+
+        assert_eq!(event.event_type(), GatewayEvent::CERTIFICATE_ISSUED);
+        assert_eq!(event.user_id(), Uuid::from_u128(7));
+        assert_eq!(event.key(), "certificate_issued:ERC-7");
+    }
+
+    #[test]
+    fn test_events_round_trip() {
+        let event = GatewayEvent::MeterOffline {
+            meter_id: "MTR-1".to_string(),
+            user_id: Uuid::from_u128(1),
+            last_reading_at: None,
+            offline_since: Utc.with_ymd_and_hms(2024, 9, 23, 12, 0, 0).unwrap(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], GatewayEvent::METER_OFFLINE);
+
+        let parsed: GatewayEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.key(), event.key());
+        assert_eq!(parsed.key(), "meter_offline:MTR-1:1727092800");
+    }
+
+    #[test]
+    fn test_meter_offline_key_changes_per_outage() {
+        let outage = |hour| GatewayEvent::MeterOffline {
+            meter_id: "MTR-1".to_string(),
+            user_id: Uuid::from_u128(1),
+            last_reading_at: None,
+            offline_since: Utc.with_ymd_and_hms(2024, 9, 23, hour, 0, 0).unwrap(),
+        };
+        assert_ne!(outage(12).key(), outage(13).key());
+    }
+}
@@ -0,0 +1,188 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::error::{ApiError, Result};
+use crate::services::blockchain::BlockchainService;
+use crate::services::gateway_signer::read_keypair;
+use crate::services::transaction::Pubkey;
+use crate::services::tx_signer::{LocalSigner, SharedSigner, TxSigner};
+
+struct FeePayer {
+    signer: SharedSigner,
+    funded: AtomicBool,
+}
+
+/// Keypairs that pay for gateway transactions in turn
+///
+/// Spreading fees over several payers keeps concurrent submissions from contending on a
+/// single fee payer account. Payers whose balance drops below `MIN_SIGNER_BALANCE_LAMPORTS`
+/// are skipped until they are topped up; with no funded payer the authority pays itself.
+#[derive(Clone)]
+pub struct FeePayerPool {
+    payers: Arc<Vec<FeePayer>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl FeePayerPool {
+    pub fn new(signers: Vec<SharedSigner>) -> Self {
+        let payers = signers
+            .into_iter()
+            .map(|signer| FeePayer {
+                signer,
+                funded: AtomicBool::new(true),
+            })
+            .collect();
+        Self {
+            payers: Arc::new(payers),
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Pool of the keypair files in `FEE_PAYER_KEYPAIR_PATHS`
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let signers = config
+            .fee_payer_keypair_paths
+            .iter()
+            .map(|path| Ok(Arc::new(LocalSigner::new(read_keypair(Path::new(path))?)) as SharedSigner))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::new(signers))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.payers.is_empty()
+    }
+
+    pub fn pubkeys(&self) -> Vec<Pubkey> {
+        self.payers.iter().map(|payer| payer.signer.pubkey()).collect()
+    }
+
+    /// Next funded payer in round-robin order, if any
+    pub fn next(&self) -> Option<SharedSigner> {
+        let len = self.payers.len();
+        (0..len).find_map(|_| {
+            let payer = &self.payers[self.next.fetch_add(1, Ordering::Relaxed) % len];
+            payer.funded.load(Ordering::Relaxed).then(|| payer.signer.clone())
+        })
+    }
+
+    fn set_funded(&self, index: usize, balance: u64, minimum: u64) {
+        let payer = &self.payers[index];
+        let funded = balance >= minimum;
+        if payer.funded.swap(funded, Ordering::Relaxed) != funded {
+            if funded {
+                tracing::info!("Fee payer {} is funded again with {} lamports", payer.signer.pubkey(), balance);
+            } else {
+                tracing::warn!(
+                    "Fee payer {} holds {} lamports, below the {} required; skipping it",
+                    payer.signer.pubkey(),
+                    balance,
+                    minimum
+                );
+            }
+        }
+    }
+
+    /// Check every payer's balance, publishing it and skipping payers below `minimum`
+    pub async fn refresh_balances(&self, chain: &BlockchainService, minimum: u64) {
+        for (index, payer) in self.payers.iter().enumerate() {
+            let address = payer.signer.pubkey().to_string();
+            match chain.get_balance(&address).await {
+                Ok(balance) => {
+                    metrics::gauge!("fee_payer_balance_lamports", "payer" => address).set(balance as f64);
+                    self.set_funded(index, balance, minimum);
+                }
+                Err(e) => tracing::warn!("Failed to read fee payer {} balance: {}", address, e),
+            }
+        }
+    }
+
+    pub fn spawn_balance_monitor(self, chain: BlockchainService, minimum: u64, interval: Duration) {
+        if self.is_empty() {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                self.refresh_balances(&chain, minimum).await;
+            }
+        });
+    }
+}
+
+/// Signatures over `message` for each of its required `signers`, in order, from the
+/// authority and the fee payer paying for it
+pub async fn sign_transaction(
+    signers: &[Pubkey],
+    message: &[u8],
+    authority: &dyn TxSigner,
+    fee_payer: Option<&dyn TxSigner>,
+) -> Result<Vec<[u8; 64]>> {
+    let mut signatures = Vec::with_capacity(signers.len());
+    for signer in signers {
+        let signature = match fee_payer {
+            Some(payer) if payer.pubkey() == *signer => payer.sign_message(message).await?,
+            _ if authority.pubkey() == *signer => authority.sign_message(message).await?,
+            _ => return Err(ApiError::Internal(format!("No key for required signer {}", signer))),
+        };
+        signatures.push(signature);
+    }
+    Ok(signatures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+
+    use crate::services::transaction::{AccountMeta, Instruction, Message};
+
+    fn signer(seed: u8) -> SharedSigner {
+        Arc::new(LocalSigner::new(SigningKey::from_bytes(&[seed; 32])))
+    }
+
+    #[test]
+    fn test_round_robin_skips_unfunded_payers() {
+        let pool = FeePayerPool::new(vec![signer(1), signer(2), signer(3)]);
+        let keys = pool.pubkeys();
+        let picked: Vec<Pubkey> = (0..4).map(|_| pool.next().unwrap().pubkey()).collect();
+        assert_eq!(picked, vec![keys[0], keys[1], keys[2], keys[0]]);
+
+        pool.set_funded(1, 10, 100);
+        let picked: Vec<Pubkey> = (0..3).map(|_| pool.next().unwrap().pubkey()).collect();
+        assert_eq!(picked, vec![keys[2], keys[0], keys[2]]);
+
+        pool.set_funded(0, 10, 100);
+        pool.set_funded(2, 99, 100);
+        assert!(pool.next().is_none());
+        pool.set_funded(2, 100, 100);
+        assert_eq!(pool.next().unwrap().pubkey(), keys[2]);
+
+        assert!(FeePayerPool::new(Vec::new()).next().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_signatures_follow_message_signers() {
+        let authority = signer(1);
+        let payer = signer(2);
+        let instruction = Instruction {
+            program_id: Pubkey([9; 32]),
+            accounts: vec![AccountMeta { pubkey: authority.pubkey(), is_signer: true, is_writable: false }],
+            data: Vec::new(),
+        };
+        let message = Message::new(&[instruction], payer.pubkey(), [0; 32]).unwrap();
+        assert_eq!(message.signers(), [payer.pubkey(), authority.pubkey()]);
+
+        let bytes = message.serialize();
+        let signatures = sign_transaction(message.signers(), &bytes, authority.as_ref(), Some(payer.as_ref()))
+            .await
+            .unwrap();
+        assert_eq!(signatures[0], payer.sign_message(&bytes).await.unwrap());
+        assert_eq!(signatures[1], authority.sign_message(&bytes).await.unwrap());
+
+        assert!(sign_transaction(message.signers(), &bytes, authority.as_ref(), None).await.is_err());
+    }
+}
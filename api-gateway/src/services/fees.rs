@@ -0,0 +1,232 @@
+use std::str::FromStr;
+
+use crate::error::Result;
+use crate::services::blockchain::BlockchainService;
+use crate::services::transaction::{Instruction, Pubkey};
+use crate::AppState;
+
+/// Compute units the runtime grants each instruction when a transaction sets no limit
+const UNITS_PER_INSTRUCTION: u32 = 200_000;
+
+/// Highest compute unit limit a transaction may request
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Percentile of recent prioritization fees a transaction bids, out of 100
+const FEE_PERCENTILE: usize = 75;
+
+/// Most accounts `getRecentPrioritizationFees` accepts
+const MAX_FEE_ACCOUNTS: usize = 128;
+
+const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
+pub fn compute_budget_program_id() -> Pubkey {
+    Pubkey::from_str("ComputeBudget111111111111111111111111111111").expect("valid program address")
+}
+
+/// Compute budget `SetComputeUnitLimit`
+pub fn set_compute_unit_limit_instruction(units: u32) -> Instruction {
+    let mut data = vec![2];
+    data.extend_from_slice(&units.to_le_bytes());
+    Instruction {
+        program_id: compute_budget_program_id(),
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+/// Compute budget `SetComputeUnitPrice`, in micro-lamports per compute unit
+pub fn set_compute_unit_price_instruction(micro_lamports: u64) -> Instruction {
+    let mut data = vec![3];
+    data.extend_from_slice(&micro_lamports.to_le_bytes());
+    Instruction {
+        program_id: compute_budget_program_id(),
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+/// Transactions the gateway signs and pays for, each with its own fee ceiling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeOperation {
+    ErcIssuance,
+    Governance,
+    ChannelState,
+}
+
+impl FeeOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ErcIssuance => "erc_issuance",
+            Self::Governance => "governance",
+            Self::ChannelState => "channel_state",
+        }
+    }
+}
+
+/// Most each operation pays in priority fees, in lamports per transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeCeilings {
+    pub erc_issuance: u64,
+    pub governance: u64,
+    pub channel_state: u64,
+}
+
+impl FeeCeilings {
+    pub fn for_operation(&self, operation: FeeOperation) -> u64 {
+        match operation {
+            FeeOperation::ErcIssuance => self.erc_issuance,
+            FeeOperation::Governance => self.governance,
+            FeeOperation::ChannelState => self.channel_state,
+        }
+    }
+}
+
+/// Compute unit limit covering `instructions` as the runtime would without a limit
+fn compute_unit_limit(instructions: &[Instruction]) -> u32 {
+    (instructions.len() as u32)
+        .saturating_mul(UNITS_PER_INSTRUCTION)
+        .min(MAX_COMPUTE_UNIT_LIMIT)
+}
+
+/// `FEE_PERCENTILE`th of `fees`, zero when there are none
+fn fee_percentile(mut fees: Vec<u64>) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let index = (fees.len() * FEE_PERCENTILE).div_ceil(100).saturating_sub(1);
+    fees[index.min(fees.len() - 1)]
+}
+
+/// Unit price bidding `estimate`, lowered so `units` cost at most `ceiling` lamports
+fn capped_unit_price(estimate: u64, ceiling: u64, units: u32) -> u64 {
+    let max_price = ceiling.saturating_mul(MICRO_LAMPORTS_PER_LAMPORT) / u64::from(units.max(1));
+    estimate.min(max_price)
+}
+
+/// Writable accounts of `instructions`, whose recent fees set the price of writing them
+fn writable_accounts(instructions: &[Instruction]) -> Vec<String> {
+    let mut accounts: Vec<Pubkey> = instructions
+        .iter()
+        .flat_map(|instruction| &instruction.accounts)
+        .filter(|meta| meta.is_writable)
+        .map(|meta| meta.pubkey)
+        .collect();
+    accounts.sort();
+    accounts.dedup();
+    accounts.truncate(MAX_FEE_ACCOUNTS);
+    accounts.iter().map(Pubkey::to_string).collect()
+}
+
+/// Prices gateway transactions from recent prioritization fees so they land under congestion
+#[derive(Clone)]
+pub struct FeeStrategy {
+    chain: BlockchainService,
+    ceilings: FeeCeilings,
+}
+
+impl FeeStrategy {
+    pub fn new(chain: BlockchainService, ceilings: FeeCeilings) -> Self {
+        Self { chain, ceilings }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(
+            state.blockchain_service.clone(),
+            FeeCeilings {
+                erc_issuance: state.config.erc_priority_fee_ceiling,
+                governance: state.config.governance_priority_fee_ceiling,
+                channel_state: state.config.channel_priority_fee_ceiling,
+            },
+        )
+    }
+
+    /// Recent prioritization fee for writing the accounts of `instructions`, in micro-lamports
+    /// per compute unit
+    pub async fn estimate_unit_price(&self, instructions: &[Instruction]) -> Result<u64> {
+        let fees = self
+            .chain
+            .get_recent_prioritization_fees(&writable_accounts(instructions))
+            .await?;
+        Ok(fee_percentile(fees.into_iter().map(|fee| fee.prioritization_fee).collect()))
+    }
+
+    /// `instructions` preceded by compute budget instructions pricing them for `operation`
+    ///
+    /// When fees cannot be estimated the transaction is sent without a priority fee.
+    pub async fn with_compute_budget(&self, operation: FeeOperation, instructions: &[Instruction]) -> Vec<Instruction> {
+        let units = compute_unit_limit(instructions);
+        let estimate = match self.estimate_unit_price(instructions).await {
+            Ok(estimate) => estimate,
+            Err(e) => {
+                tracing::warn!("Priority fee estimation failed for {}: {}", operation.as_str(), e);
+                0
+            }
+        };
+        let unit_price = capped_unit_price(estimate, self.ceilings.for_operation(operation), units);
+        metrics::histogram!("priority_fee_micro_lamports", "operation" => operation.as_str())
+            .record(unit_price as f64);
+
+        let mut budgeted = Vec::with_capacity(instructions.len() + 2);
+        budgeted.push(set_compute_unit_limit_instruction(units));
+        if unit_price > 0 {
+            budgeted.push(set_compute_unit_price_instruction(unit_price));
+        }
+        budgeted.extend_from_slice(instructions);
+        budgeted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::transaction::AccountMeta;
+
+    fn instruction(accounts: Vec<AccountMeta>) -> Instruction {
+        Instruction {
+            program_id: Pubkey([9; 32]),
+            accounts,
+            data: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_compute_budget_instruction_data() {
+        let limit = set_compute_unit_limit_instruction(300_000);
+        assert_eq!(limit.program_id.to_string(), "ComputeBudget111111111111111111111111111111");
+        assert_eq!(limit.data, [2, 0xe0, 0x93, 0x04, 0x00]);
+
+        let price = set_compute_unit_price_instruction(1_000);
+        assert_eq!(price.data, [3, 0xe8, 0x03, 0, 0, 0, 0, 0, 0]);
+        assert!(price.accounts.is_empty());
+    }
+
+    #[test]
+    fn test_fee_percentile() {
+        assert_eq!(fee_percentile(Vec::new()), 0);
+        assert_eq!(fee_percentile(vec![500]), 500);
+        assert_eq!(fee_percentile(vec![0, 400, 100, 300, 200, 0, 0, 1_000]), 300);
+    }
+
+    #[test]
+    fn test_unit_price_respects_ceiling() {
+        // 10,000 lamports over 200,000 units allows 50,000 micro-lamports per unit
+        assert_eq!(capped_unit_price(20_000, 10_000, 200_000), 20_000);
+        assert_eq!(capped_unit_price(80_000, 10_000, 200_000), 50_000);
+        assert_eq!(capped_unit_price(80_000, 0, 200_000), 0);
+    }
+
+    #[test]
+    fn test_limits_and_fee_accounts() {
+        let writable = AccountMeta { pubkey: Pubkey([1; 32]), is_signer: false, is_writable: true };
+        let readonly = AccountMeta { pubkey: Pubkey([2; 32]), is_signer: false, is_writable: false };
+        let instructions = vec![
+            instruction(vec![writable.clone(), readonly]),
+            instruction(vec![writable.clone()]),
+        ];
+
+        assert_eq!(compute_unit_limit(&instructions), 400_000);
+        assert_eq!(compute_unit_limit(&vec![instruction(Vec::new()); 10]), MAX_COMPUTE_UNIT_LIMIT);
+        assert_eq!(writable_accounts(&instructions), vec![writable.pubkey.to_string()]);
+    }
+}
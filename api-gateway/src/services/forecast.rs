@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, DurationRound, Timelike, Utc};
+use serde::Deserialize;
+
+use crate::error::{ApiError, Result};
+use crate::models::energy::{EnergyForecast, EnergyPoint, ForecastPoint};
+use crate::services::timeseries::{EnergyBucket, TimeseriesStore};
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+/// Days of hourly history a forecast is fitted to
+const HISTORY_DAYS: i64 = 14;
+
+/// Furthest ahead a forecast reaches, in hours
+const MAX_HORIZON_HOURS: i64 = 7 * 24;
+
+/// Weight of each new day in the exponentially smoothed profile
+const SMOOTHING_ALPHA: f64 = 0.3;
+
+/// How a forecast extrapolates a meter's daily cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastMethod {
+    /// Each hour repeats the latest day with a reading at that hour
+    SeasonalNaive,
+    /// Each hour of day is exponentially smoothed across days, favouring recent ones
+    #[default]
+    ExponentialSmoothing,
+}
+
+impl ForecastMethod {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::SeasonalNaive => "seasonal_naive",
+            Self::ExponentialSmoothing => "exponential_smoothing",
+        }
+    }
+}
+
+/// Hours in a horizon such as `24h` or `2d`
+pub fn parse_horizon(horizon: &str) -> Result<i64> {
+    let invalid = || ApiError::BadRequest(format!("Horizon {:?} is not a number of hours (24h) or days (2d)", horizon));
+    let horizon = horizon.trim();
+    let hours = match horizon.char_indices().last() {
+        Some((unit, 'h')) => horizon[..unit].parse::<i64>().map_err(|_| invalid())?,
+        Some((unit, 'd')) => horizon[..unit].parse::<i64>().map_err(|_| invalid())? * 24,
+        _ => return Err(invalid()),
+    };
+    if !(1..=MAX_HORIZON_HOURS).contains(&hours) {
+        return Err(ApiError::BadRequest(format!(
+            "Horizon must be between 1h and {}h",
+            MAX_HORIZON_HOURS
+        )));
+    }
+    Ok(hours)
+}
+
+/// Hourly generation and consumption for the `hours` starting at `start`, from hourly `history`
+/// in time order
+pub fn forecast(method: ForecastMethod, history: &[EnergyPoint], start: DateTime<Utc>, hours: i64) -> Vec<ForecastPoint> {
+    // Expected (generated, consumed) at each hour of day
+    let profile: HashMap<u32, (f64, f64)> = match method {
+        ForecastMethod::SeasonalNaive => history
+            .iter()
+            .map(|point| (point.bucket.hour(), (point.energy_generated, point.energy_consumed)))
+            .collect(),
+        ForecastMethod::ExponentialSmoothing => {
+            let mut levels: HashMap<u32, (f64, f64)> = HashMap::new();
+            for point in history {
+                levels
+                    .entry(point.bucket.hour())
+                    .and_modify(|(generated, consumed)| {
+                        *generated += SMOOTHING_ALPHA * (point.energy_generated - *generated);
+                        *consumed += SMOOTHING_ALPHA * (point.energy_consumed - *consumed);
+                    })
+                    .or_insert((point.energy_generated, point.energy_consumed));
+            }
+            levels
+        }
+    };
+
+    (0..hours)
+        .map(|offset| {
+            let hour = start + Duration::hours(offset);
+            let (generated, consumed) = profile.get(&hour.hour()).copied().unwrap_or_default();
+            ForecastPoint {
+                hour,
+                energy_generated: generated.max(0.0),
+                energy_consumed: consumed.max(0.0),
+            }
+        })
+        .collect()
+}
+
+/// Short-term per-meter forecasts from the TimescaleDB hourly rollup
+#[derive(Clone)]
+pub struct ForecastService {
+    store: TimeseriesStore,
+    clock: SharedClock,
+}
+
+impl ForecastService {
+    pub fn new(store: TimeseriesStore, clock: SharedClock) -> Self {
+        Self { store, clock }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(TimeseriesStore::from_state(state), state.clock.clone())
+    }
+
+    /// Forecast `meter_id` for the `hours` from the start of the current hour
+    pub async fn forecast(&self, meter_id: &str, method: ForecastMethod, hours: i64) -> Result<EnergyForecast> {
+        let now = self.clock.now();
+        let start = now
+            .duration_trunc(Duration::hours(1))
+            .map_err(|e| ApiError::Internal(format!("Cannot round {} to the hour: {}", now, e)))?;
+        let history = self
+            .store
+            .energy_series(meter_id, EnergyBucket::Hour, start - Duration::days(HISTORY_DAYS), start)
+            .await?;
+        if history.is_empty() {
+            return Err(ApiError::NotFound(format!(
+                "Meter {} has no readings in the last {} days to forecast from",
+                meter_id, HISTORY_DAYS
+            )));
+        }
+
+        Ok(EnergyForecast {
+            meter_id: meter_id.to_string(),
+            method: method.as_str().to_string(),
+            generated_at: now,
+            history_hours: history.len(),
+            points: forecast(method, &history, start, hours),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn point(day: u32, hour: u32, generated: f64, consumed: f64) -> EnergyPoint {
+        EnergyPoint {
+            bucket: Utc.with_ymd_and_hms(2024, 9, day, hour, 0, 0).unwrap(),
+            energy_generated: generated,
+            energy_consumed: consumed,
+            reading_count: 4,
+        }
+    }
+
+    #[test]
+    fn test_horizon_parses_hours_and_days() {
+        assert_eq!(parse_horizon("24h").unwrap(), 24);
+        assert_eq!(parse_horizon("2d").unwrap(), 48);
+        assert!(parse_horizon("0h").is_err());
+        assert!(parse_horizon("8d").is_err());
+        assert!(parse_horizon("24").is_err());
+        assert!(parse_horizon("h").is_err());
+    }
+
+    #[test]
+    fn test_seasonal_naive_repeats_the_latest_day() {
+        let history = [point(1, 12, 4.0, 1.0), point(2, 12, 6.0, 2.0), point(2, 13, 5.0, 1.5)];
+        let start = Utc.with_ymd_and_hms(2024, 9, 3, 12, 0, 0).unwrap();
+
+        let points = forecast(ForecastMethod::SeasonalNaive, &history, start, 3);
+
+        assert_eq!(points.len(), 3);
+        assert_eq!((points[0].energy_generated, points[0].energy_consumed), (6.0, 2.0));
+        assert_eq!(points[1].energy_generated, 5.0);
+        // No history at 14:00
+        assert_eq!(points[2].hour, start + Duration::hours(2));
+        assert_eq!(points[2].energy_generated, 0.0);
+    }
+
+    #[test]
+    fn test_exponential_smoothing_favours_recent_days() {
+        let history = [point(1, 12, 10.0, 2.0), point(2, 12, 0.0, 2.0)];
+        let start = Utc.with_ymd_and_hms(2024, 9, 3, 12, 0, 0).unwrap();
+
+        let points = forecast(ForecastMethod::ExponentialSmoothing, &history, start, 1);
+
+        assert!((points[0].energy_generated - 7.0).abs() < 1e-9);
+        assert!((points[0].energy_consumed - 2.0).abs() < 1e-9);
+    }
+}
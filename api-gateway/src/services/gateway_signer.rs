@@ -0,0 +1,259 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ed25519_dalek::SigningKey;
+use serde::Serialize;
+use tokio::sync::{OwnedRwLockReadGuard, RwLock};
+
+use crate::config::Config;
+use crate::error::{ApiError, Result};
+use crate::services::transaction::Pubkey;
+use crate::services::tx_signer::{AwsCredentials, KmsSigner, LocalSigner, SharedSigner, VaultTransitSigner};
+use crate::utils::clock::SharedClock;
+
+/// Signer held while a transaction signed with it is built and submitted
+pub type SignerLease = OwnedRwLockReadGuard<Option<SharedSigner>, SharedSigner>;
+
+/// Governance authority key from `GOVERNANCE_AUTHORITY_KEY`, if configured
+fn governance_authority(config: &Config) -> Result<Option<SigningKey>> {
+    let Some(key) = config.governance_authority_key.as_deref() else {
+        return Ok(None);
+    };
+    let seed: [u8; 32] = bs58::decode(key)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ApiError::Configuration("GOVERNANCE_AUTHORITY_KEY must be a base58 32-byte seed".to_string()))?;
+    Ok(Some(SigningKey::from_bytes(&seed)))
+}
+
+/// Key from a Solana CLI keypair file: a JSON array of the 32-byte secret key followed by
+/// the 32-byte public key
+fn parse_keypair(contents: &str) -> Result<SigningKey> {
+    let bytes: Vec<u8> = serde_json::from_str(contents)
+        .map_err(|e| ApiError::Configuration(format!("Keypair file is not a JSON byte array: {}", e)))?;
+    let bytes: [u8; 64] = bytes
+        .try_into()
+        .map_err(|_| ApiError::Configuration("Keypair file must hold 64 bytes".to_string()))?;
+    SigningKey::from_keypair_bytes(&bytes)
+        .map_err(|_| ApiError::Configuration("Keypair file public key does not match its secret key".to_string()))
+}
+
+pub(crate) fn read_keypair(path: &Path) -> Result<SigningKey> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| ApiError::Configuration(format!("Failed to read keypair {}: {}", path.display(), e)))?;
+    parse_keypair(&contents)
+}
+
+/// Public keys before and after a rotation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyRotation {
+    pub previous: Option<String>,
+    pub current: String,
+}
+
+fn required(value: &Option<String>, name: &str) -> Result<String> {
+    value
+        .clone()
+        .ok_or_else(|| ApiError::Configuration(format!("{} is required by SIGNER_BACKEND", name)))
+}
+
+/// Where the gateway signing key lives, chosen by `SIGNER_BACKEND`
+#[derive(Clone)]
+pub enum SignerBackend {
+    /// `GOVERNANCE_AUTHORITY_KEY`, fixed for the life of the process
+    Seed(Option<SigningKey>),
+    /// Solana CLI keypair file at `API_GATEWAY_KEYPAIR_PATH`
+    KeypairFile(PathBuf),
+    /// Vault transit key `VAULT_TRANSIT_KEY`
+    Vault {
+        addr: String,
+        token: String,
+        mount: String,
+        key_name: String,
+    },
+    /// AWS KMS key `KMS_KEY_ID`
+    Kms {
+        region: String,
+        key_id: String,
+        credentials: AwsCredentials,
+    },
+}
+
+impl SignerBackend {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        match config.signer_backend.as_str() {
+            "local" => match config.api_gateway_keypair_path.as_deref() {
+                Some(path) => Ok(Self::KeypairFile(PathBuf::from(path))),
+                None => Ok(Self::Seed(governance_authority(config)?)),
+            },
+            "vault" => Ok(Self::Vault {
+                addr: required(&config.vault_addr, "VAULT_ADDR")?,
+                token: required(&config.vault_token, "VAULT_TOKEN")?,
+                mount: config.vault_transit_mount.clone(),
+                key_name: required(&config.vault_transit_key, "VAULT_TRANSIT_KEY")?,
+            }),
+            "kms" => Ok(Self::Kms {
+                region: required(&config.aws_region, "AWS_REGION")?,
+                key_id: required(&config.kms_key_id, "KMS_KEY_ID")?,
+                credentials: AwsCredentials {
+                    access_key_id: required(&config.aws_access_key_id, "AWS_ACCESS_KEY_ID")?,
+                    secret_access_key: required(&config.aws_secret_access_key, "AWS_SECRET_ACCESS_KEY")?,
+                    session_token: config.aws_session_token.clone(),
+                },
+            }),
+            other => Err(ApiError::Configuration(format!(
+                "SIGNER_BACKEND must be local, vault or kms, not {}",
+                other
+            ))),
+        }
+    }
+
+    /// Signer for the backend's current key
+    async fn load(&self, clock: &SharedClock) -> Result<Option<SharedSigner>> {
+        let signer: SharedSigner = match self {
+            Self::Seed(key) => return Ok(key.clone().map(|key| Arc::new(LocalSigner::new(key)) as SharedSigner)),
+            Self::KeypairFile(path) => Arc::new(LocalSigner::new(read_keypair(path)?)),
+            Self::Vault {
+                addr,
+                token,
+                mount,
+                key_name,
+            } => Arc::new(VaultTransitSigner::connect(addr, token, mount, key_name).await?),
+            Self::Kms {
+                region,
+                key_id,
+                credentials,
+            } => Arc::new(KmsSigner::connect(region, key_id, credentials.clone(), clock.clone()).await?),
+        };
+        Ok(Some(signer))
+    }
+}
+
+/// The gateway's signing authority for ERC and governance transactions, rotatable at runtime
+///
+/// Transactions lease the signer while they are signed and submitted, and a rotation waits
+/// for outstanding leases: in-flight transactions drain under the old key and every later
+/// one is signed with the new key.
+#[derive(Clone)]
+pub struct GatewaySigner {
+    backend: SignerBackend,
+    signer: Arc<RwLock<Option<SharedSigner>>>,
+    clock: SharedClock,
+}
+
+impl GatewaySigner {
+    pub fn new(backend: SignerBackend, signer: Option<SharedSigner>, clock: SharedClock) -> Self {
+        Self {
+            backend,
+            signer: Arc::new(RwLock::new(signer)),
+            clock,
+        }
+    }
+
+    /// Signer holding `key` in memory, which cannot be reloaded
+    pub fn local(key: Option<SigningKey>, clock: SharedClock) -> Self {
+        let signer = key.clone().map(|key| Arc::new(LocalSigner::new(key)) as SharedSigner);
+        Self::new(SignerBackend::Seed(key), signer, clock)
+    }
+
+    /// Connect to the configured backend and load its key
+    pub async fn from_config(config: &Config, clock: SharedClock) -> Result<Self> {
+        let backend = SignerBackend::from_config(config)?;
+        let signer = backend.load(&clock).await?;
+        Ok(Self::new(backend, signer, clock))
+    }
+
+    /// Lease the current signer, delaying rotations until the lease is dropped
+    pub async fn lease(&self) -> Result<SignerLease> {
+        OwnedRwLockReadGuard::try_map(self.signer.clone().read_owned().await, Option::as_ref).map_err(|_| {
+            ApiError::Configuration("API_GATEWAY_KEYPAIR_PATH or GOVERNANCE_AUTHORITY_KEY is not configured".to_string())
+        })
+    }
+
+    /// Current public key, if a key is configured
+    pub async fn pubkey(&self) -> Option<Pubkey> {
+        self.signer.read().await.as_ref().map(|signer| signer.pubkey())
+    }
+
+    /// Load the backend's current key again and switch to it: a new keypair file, or the
+    /// latest version of a Vault or KMS key
+    pub async fn reload(&self) -> Result<KeyRotation> {
+        if let SignerBackend::Seed(_) = self.backend {
+            return Err(ApiError::Configuration(
+                "Set API_GATEWAY_KEYPAIR_PATH or a remote SIGNER_BACKEND to rotate the gateway signer".to_string(),
+            ));
+        }
+        let signer = self
+            .backend
+            .load(&self.clock)
+            .await?
+            .ok_or_else(|| ApiError::Configuration("Signer backend returned no key".to_string()))?;
+        Ok(self.rotate(signer).await)
+    }
+
+    async fn rotate(&self, signer: SharedSigner) -> KeyRotation {
+        let current = signer.pubkey().to_string();
+        let previous = self
+            .signer
+            .write()
+            .await
+            .replace(signer)
+            .map(|signer| signer.pubkey().to_string());
+        KeyRotation { previous, current }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::utils::clock::SystemClock;
+
+    fn keypair_json(key: &SigningKey) -> String {
+        serde_json::to_string(&key.to_keypair_bytes().to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_keypair_file() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        assert_eq!(parse_keypair(&keypair_json(&key)).unwrap(), key);
+
+        let mut mismatched = key.to_keypair_bytes();
+        mismatched[40] ^= 1;
+        assert!(parse_keypair(&serde_json::to_string(&mismatched.to_vec()).unwrap()).is_err());
+        assert!(parse_keypair("[1, 2, 3]").is_err());
+        assert!(parse_keypair("not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rotation_waits_for_leases() {
+        let old = SigningKey::from_bytes(&[1; 32]);
+        let new = SigningKey::from_bytes(&[2; 32]);
+        let old_pubkey = Pubkey(old.verifying_key().to_bytes());
+        let signer = GatewaySigner::local(Some(old), SystemClock::shared());
+
+        let lease = signer.lease().await.unwrap();
+        let rotating = tokio::spawn({
+            let signer = signer.clone();
+            async move { signer.rotate(Arc::new(LocalSigner::new(new))).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!rotating.is_finished());
+        assert_eq!(lease.pubkey(), old_pubkey);
+        drop(lease);
+
+        let rotation = rotating.await.unwrap();
+        assert_eq!(rotation.previous, Some(old_pubkey.to_string()));
+        assert_eq!(signer.pubkey().await, Some(Pubkey(SigningKey::from_bytes(&[2; 32]).verifying_key().to_bytes())));
+    }
+
+    #[tokio::test]
+    async fn test_reload_requires_keypair_path() {
+        let signer = GatewaySigner::local(None, SystemClock::shared());
+        assert!(signer.lease().await.is_err());
+        assert!(signer.reload().await.is_err());
+        assert_eq!(signer.pubkey().await, None);
+    }
+}
@@ -0,0 +1,282 @@
+use std::str::FromStr;
+
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::error::{ApiError, Result};
+use crate::models::governance::GovernanceTransaction;
+use crate::services::blockchain::{BlockchainService, TransactionLifetime};
+use crate::services::chain_cache::ChainCache;
+use crate::services::fees::{FeeOperation, FeeStrategy};
+use crate::services::fee_payers::{sign_transaction, FeePayerPool};
+use crate::services::gateway_signer::GatewaySigner;
+use crate::services::transaction::{anchor_instruction, serialize_transaction, Instruction, Message, Pubkey};
+use crate::services::tx_signer::TxSigner;
+use crate::AppState;
+
+/// Administrative governance instruction submitted by the Engineering Department authority
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GovernanceAction {
+    Pause,
+    Unpause,
+    Maintenance {
+        enabled: bool,
+    },
+    Limits {
+        min_energy_amount: u64,
+        max_erc_amount: u64,
+        erc_validity_period: i64,
+    },
+}
+
+impl GovernanceAction {
+    /// Governance program instruction name, recorded in the audit log
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Pause => "emergency_pause",
+            Self::Unpause => "emergency_unpause",
+            Self::Maintenance { .. } => "set_maintenance_mode",
+            Self::Limits { .. } => "update_erc_limits",
+        }
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        match self {
+            Self::Pause | Self::Unpause => json!({}),
+            Self::Maintenance { enabled } => json!({ "maintenance_enabled": enabled }),
+            Self::Limits {
+                min_energy_amount,
+                max_erc_amount,
+                erc_validity_period,
+            } => json!({
+                "min_energy_amount": min_energy_amount,
+                "max_erc_amount": max_erc_amount,
+                "erc_validity_period": erc_validity_period,
+            }),
+        }
+    }
+}
+
+/// Instruction for `action`, signed by the PoA authority
+fn action_instruction(program_id: Pubkey, authority: Pubkey, action: &GovernanceAction) -> Result<Instruction> {
+    let (poa_config, _) = Pubkey::find_program_address(&[b"poa_config"], &program_id)
+        .ok_or_else(|| ApiError::Internal("No PoA config address".to_string()))?;
+    let poa_config = poa_config.into();
+    let authority = authority.into();
+
+    let instruction = match action {
+        GovernanceAction::Pause => anchor_instruction(
+            program_id,
+            governance::accounts::EmergencyControl { poa_config, authority },
+            governance::instruction::EmergencyPause {},
+        ),
+        GovernanceAction::Unpause => anchor_instruction(
+            program_id,
+            governance::accounts::EmergencyControl { poa_config, authority },
+            governance::instruction::EmergencyUnpause {},
+        ),
+        GovernanceAction::Maintenance { enabled } => anchor_instruction(
+            program_id,
+            governance::accounts::UpdateGovernanceConfig { poa_config, authority },
+            governance::instruction::SetMaintenanceMode {
+                maintenance_enabled: *enabled,
+            },
+        ),
+        GovernanceAction::Limits {
+            min_energy_amount,
+            max_erc_amount,
+            erc_validity_period,
+        } => anchor_instruction(
+            program_id,
+            governance::accounts::UpdateGovernanceConfig { poa_config, authority },
+            governance::instruction::UpdateErcLimits {
+                min_energy_amount: *min_energy_amount,
+                max_erc_amount: *max_erc_amount,
+                erc_validity_period: *erc_validity_period,
+            },
+        ),
+    };
+    Ok(instruction)
+}
+
+/// Submits administrative governance instructions and keeps an audit trail of them
+#[derive(Clone)]
+pub struct GovernanceAdmin {
+    db: sqlx::PgPool,
+    chain: BlockchainService,
+    cache: ChainCache,
+    program_id: Pubkey,
+    signer: GatewaySigner,
+    fee_payers: FeePayerPool,
+    nonce_account: Option<Pubkey>,
+    fees: FeeStrategy,
+}
+
+impl GovernanceAdmin {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        db: sqlx::PgPool,
+        chain: BlockchainService,
+        cache: ChainCache,
+        program_id: Pubkey,
+        signer: GatewaySigner,
+        fee_payers: FeePayerPool,
+        nonce_account: Option<Pubkey>,
+        fees: FeeStrategy,
+    ) -> Self {
+        Self {
+            db,
+            chain,
+            cache,
+            program_id,
+            signer,
+            fee_payers,
+            nonce_account,
+            fees,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let program_id = Pubkey::from_str(&state.config.governance_program_id)
+            .map_err(|e| ApiError::Configuration(format!("Invalid GOVERNANCE_PROGRAM_ID: {}", e)))?;
+
+        Ok(Self::new(
+            state.db.clone(),
+            state.blockchain_service.clone(),
+            ChainCache::from_state(state),
+            program_id,
+            state.signer.clone(),
+            state.fee_payers.clone(),
+            state
+                .config
+                .governance_nonce_account
+                .as_deref()
+                .map(|account| {
+                    Pubkey::from_str(account)
+                        .map_err(|e| ApiError::Configuration(format!("Invalid GOVERNANCE_NONCE_ACCOUNT: {}", e)))
+                })
+                .transpose()?,
+            FeeStrategy::from_state(state),
+        ))
+    }
+
+    /// Submit `action` on behalf of `user_id`, recording the outcome whether or not it lands
+    pub async fn execute(&self, user_id: Uuid, action: GovernanceAction) -> Result<GovernanceTransaction> {
+        let authority = self.signer.lease().await?;
+        let instruction = action_instruction(self.program_id, authority.pubkey(), &action)?;
+
+        let result = self.submit(authority.as_ref(), &[instruction]).await;
+        let (signature, error) = match &result {
+            Ok(signature) => (Some(signature.as_str()), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let audit_id = self.record(user_id, &action, signature, error.as_deref()).await?;
+
+        let signature = result?;
+        if let Some((poa_config, _)) = Pubkey::find_program_address(&[b"poa_config"], &self.program_id) {
+            self.cache.invalidate(&[poa_config.to_string()]).await;
+        }
+        tracing::info!("User {} submitted governance {} in {}", user_id, action.name(), signature);
+        Ok(GovernanceTransaction {
+            audit_id,
+            action: action.name().to_string(),
+            signature,
+        })
+    }
+
+    async fn record(
+        &self,
+        user_id: Uuid,
+        action: &GovernanceAction,
+        signature: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<Uuid> {
+        let (id,): (Uuid,) = sqlx::query_as(
+            "INSERT INTO governance_actions (user_id, action, parameters, tx_signature, error_message)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING id",
+        )
+        .bind(user_id)
+        .bind(action.name())
+        .bind(action.parameters())
+        .bind(signature)
+        .bind(error)
+        .fetch_one(&self.db)
+        .await?;
+        Ok(id)
+    }
+
+    /// Durable nonce lifetime when `GOVERNANCE_NONCE_ACCOUNT` is set, otherwise the latest blockhash
+    async fn lifetime(&self, authority: Pubkey) -> Result<TransactionLifetime> {
+        let Some(account) = self.nonce_account else {
+            return self.chain.latest_lifetime().await;
+        };
+        let lifetime = self.chain.nonce_lifetime(account).await?.ok_or_else(|| {
+            ApiError::Configuration(format!(
+                "GOVERNANCE_NONCE_ACCOUNT {} is not an initialized nonce account",
+                account
+            ))
+        })?;
+        if let TransactionLifetime::Nonce { state, .. } = &lifetime {
+            if state.authority != authority {
+                return Err(ApiError::Configuration(format!(
+                    "GOVERNANCE_NONCE_ACCOUNT {} is not controlled by the governance authority",
+                    account
+                )));
+            }
+        }
+        Ok(lifetime)
+    }
+
+    /// Sign `instructions` as the authority, paid for by the next fee payer in the pool or,
+    /// without one, by the authority
+    async fn submit(&self, authority: &dyn TxSigner, instructions: &[Instruction]) -> Result<String> {
+        let authority_key = authority.pubkey();
+        let lifetime = self.lifetime(authority_key).await?;
+        let instructions = self.fees.with_compute_budget(FeeOperation::Governance, instructions).await;
+
+        let fee_payer = self.fee_payers.next();
+        let payer_key = fee_payer.as_ref().map_or(authority_key, |payer| payer.pubkey());
+        let message = Message::new(&lifetime.instructions(&instructions), payer_key, lifetime.recent_blockhash())
+            .map_err(ApiError::Internal)?;
+        let bytes = message.serialize();
+        let signatures = sign_transaction(message.signers(), &bytes, authority, fee_payer.as_deref()).await?;
+
+        self.chain.send_transaction(&serialize_transaction(&signatures, &bytes)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::transaction::anchor_discriminator;
+
+    #[test]
+    fn test_action_instructions() {
+        let program_id = Pubkey([7; 32]);
+        let authority = Pubkey([1; 32]);
+        let (poa_config, _) = Pubkey::find_program_address(&[b"poa_config"], &program_id).unwrap();
+
+        let pause = action_instruction(program_id, authority, &GovernanceAction::Pause).unwrap();
+        assert_eq!(pause.data, anchor_discriminator("emergency_pause"));
+        assert_eq!(pause.accounts[0].pubkey, poa_config);
+        assert!(pause.accounts[0].is_writable);
+        assert!(pause.accounts[1].is_signer && pause.accounts[1].pubkey == authority);
+
+        let maintenance =
+            action_instruction(program_id, authority, &GovernanceAction::Maintenance { enabled: true }).unwrap();
+        assert_eq!(&maintenance.data[..8], &anchor_discriminator("set_maintenance_mode"));
+        assert_eq!(&maintenance.data[8..], &[1]);
+
+        let limits = GovernanceAction::Limits {
+            min_energy_amount: 100,
+            max_erc_amount: 5_000,
+            erc_validity_period: 86_400,
+        };
+        let instruction = action_instruction(program_id, authority, &limits).unwrap();
+        assert_eq!(&instruction.data[..8], &anchor_discriminator("update_erc_limits"));
+        assert_eq!(&instruction.data[8..16], &100u64.to_le_bytes());
+        assert_eq!(&instruction.data[24..], &86_400i64.to_le_bytes());
+        assert_eq!(limits.parameters()["max_erc_amount"], 5_000);
+    }
+}
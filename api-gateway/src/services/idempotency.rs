@@ -0,0 +1,165 @@
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+use crate::database::redis_pool::RedisPool;
+use crate::AppState;
+
+const KEY_PREFIX: &str = "idempotency:";
+
+/// Seconds a key stays claimed while its first request runs, past the request timeout
+const IN_FLIGHT_TTL: u64 = 60;
+
+/// What is stored under an idempotency key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum IdempotencyRecord {
+    /// The first request with this key is still being handled
+    InFlight { fingerprint: String },
+    /// The first request finished with this response, replayed to retries
+    Completed {
+        fingerprint: String,
+        status: u16,
+        content_type: Option<String>,
+        /// Base64 response body
+        body: String,
+    },
+}
+
+impl IdempotencyRecord {
+    pub fn fingerprint(&self) -> &str {
+        match self {
+            Self::InFlight { fingerprint } | Self::Completed { fingerprint, .. } => fingerprint,
+        }
+    }
+}
+
+/// Result of claiming a key for a request
+#[derive(Debug, PartialEq, Eq)]
+pub enum Claim {
+    /// No earlier request used the key; this one should run
+    Acquired,
+    /// An earlier request used the key
+    Existing(IdempotencyRecord),
+}
+
+/// `Idempotency-Key` records in Redis, scoped per caller so keys of different clients never collide
+#[derive(Clone)]
+pub struct IdempotencyStore {
+    redis: RedisPool,
+    ttl: u64,
+}
+
+impl IdempotencyStore {
+    pub fn new(redis: RedisPool, ttl: u64) -> Self {
+        Self { redis, ttl }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.redis.clone(), state.config.idempotency_key_ttl)
+    }
+
+    /// Atomically claim `key` within `scope`, or return what an earlier request stored
+    pub async fn claim(&self, scope: &str, key: &str, fingerprint: &str) -> redis::RedisResult<Claim> {
+        let redis_key = redis_key(scope, key);
+        let in_flight = encode(&IdempotencyRecord::InFlight {
+            fingerprint: fingerprint.to_string(),
+        });
+        let mut conn = self.redis.get().await?;
+
+        // A record can expire between the two commands, so try the claim again once
+        for _ in 0..2 {
+            let claimed: Option<String> = redis::cmd("SET")
+                .arg(&redis_key)
+                .arg(&in_flight)
+                .arg("NX")
+                .arg("EX")
+                .arg(IN_FLIGHT_TTL)
+                .query_async(&mut conn)
+                .await?;
+            if claimed.is_some() {
+                return Ok(Claim::Acquired);
+            }
+
+            let existing: Option<String> = conn.get(&redis_key).await?;
+            if let Some(record) = existing.as_deref().and_then(decode) {
+                return Ok(Claim::Existing(record));
+            }
+        }
+
+        Ok(Claim::Existing(IdempotencyRecord::InFlight {
+            fingerprint: fingerprint.to_string(),
+        }))
+    }
+
+    /// Store the response of a claimed request for `IDEMPOTENCY_KEY_TTL` seconds
+    pub async fn complete(&self, scope: &str, key: &str, record: &IdempotencyRecord) {
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            conn.set_ex::<_, _, ()>(redis_key(scope, key), encode(record), self.ttl).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to store response for idempotency key {}: {}", key, e);
+        }
+    }
+
+    /// Drop a claim so a retry runs the request again
+    pub async fn release(&self, scope: &str, key: &str) {
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            conn.del::<_, ()>(redis_key(scope, key)).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to release idempotency key {}: {}", key, e);
+        }
+    }
+}
+
+fn redis_key(scope: &str, key: &str) -> String {
+    format!("{}{}:{}", KEY_PREFIX, scope, key)
+}
+
+fn encode(record: &IdempotencyRecord) -> String {
+    serde_json::to_string(record).expect("idempotency records serialize")
+}
+
+fn decode(value: &str) -> Option<IdempotencyRecord> {
+    serde_json::from_str(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_round_trip() {
+        let records = [
+            IdempotencyRecord::InFlight {
+                fingerprint: "abc".to_string(),
+            },
+            IdempotencyRecord::Completed {
+                fingerprint: "abc".to_string(),
+                status: 200,
+                content_type: Some("application/json".to_string()),
+                body: "e30=".to_string(),
+            },
+        ];
+        for record in records {
+            assert_eq!(decode(&encode(&record)), Some(record.clone()));
+            assert_eq!(record.fingerprint(), "abc");
+        }
+        assert_eq!(decode("not json"), None);
+    }
+
+    #[test]
+    fn test_keys_are_scoped_per_caller() {
+        assert_ne!(
+            redis_key("api-key:6f1c", "reading-1"),
+            redis_key("api-key:9a2d", "reading-1")
+        );
+        assert_eq!(redis_key("user:42", "reading-1"), "idempotency:user:42:reading-1");
+    }
+}
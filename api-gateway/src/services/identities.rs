@@ -0,0 +1,194 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::auth::oidc::{GroupRoles, IdTokenClaims};
+use crate::auth::password::PasswordService;
+use crate::error::{ApiError, Result};
+use crate::AppState;
+
+/// Department of accounts created at first sign-on when the provider sends none
+const DEFAULT_DEPARTMENT: &str = "Engineering Administration";
+
+/// Username of an account created at first sign-on: the provider's preferred username, or
+/// the local part of the user's email
+fn new_username(claims: &IdTokenClaims) -> Option<String> {
+    claims
+        .preferred_username
+        .as_deref()
+        .or_else(|| claims.email.as_deref().and_then(|email| email.split('@').next()))
+        .map(str::trim)
+        .filter(|username| (3..=50).contains(&username.chars().count()))
+        .map(str::to_string)
+}
+
+/// Provider accounts linked to gateway users
+#[derive(Clone)]
+pub struct IdentityStore {
+    db: PgPool,
+}
+
+impl IdentityStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone())
+    }
+
+    /// The user a verified provider account signs in as, with the roles its groups map to
+    ///
+    /// An unlinked account is linked to the user with its verified email, or to a new user
+    /// whose password can never be used. The provider is authoritative for every mapped role,
+    /// so mapped roles the user's groups no longer grant are withdrawn.
+    pub async fn sign_in(&self, claims: &IdTokenClaims, groups: &[String], mapping: &GroupRoles) -> Result<Uuid> {
+        let mut tx = self.db.begin().await?;
+        let linked: Option<Uuid> = sqlx::query_scalar(
+            "UPDATE user_identities SET email = $3, groups = $4, last_login_at = NOW()
+             WHERE issuer = $1 AND subject = $2
+             RETURNING user_id",
+        )
+        .bind(&claims.iss)
+        .bind(&claims.sub)
+        .bind(&claims.email)
+        .bind(groups)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let user_id = match linked {
+            Some(user_id) => user_id,
+            None => {
+                let user_id = match self.user_with_email(&mut tx, claims).await? {
+                    Some(user_id) => user_id,
+                    None => create_user(&mut tx, claims).await?,
+                };
+                sqlx::query(
+                    "INSERT INTO user_identities (issuer, subject, user_id, email, groups) VALUES ($1, $2, $3, $4, $5)",
+                )
+                .bind(&claims.iss)
+                .bind(&claims.sub)
+                .bind(user_id)
+                .bind(&claims.email)
+                .bind(groups)
+                .execute(&mut *tx)
+                .await?;
+                tracing::info!("Linked {} account {} to user {}", claims.iss, claims.sub, user_id);
+                user_id
+            }
+        };
+
+        let active = sqlx::query_scalar!("SELECT is_active FROM users WHERE id = $1 FOR UPDATE", user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !active {
+            return Err(ApiError::Authorization(format!("User {} is deactivated", user_id)));
+        }
+        sync_roles(&mut tx, user_id, groups, mapping).await?;
+        tx.commit().await?;
+
+        Ok(user_id)
+    }
+
+    /// Existing user owning the account's email, when the provider has verified it
+    async fn user_with_email(&self, conn: &mut PgConnection, claims: &IdTokenClaims) -> Result<Option<Uuid>> {
+        let Some(email) = claims.email.as_deref().filter(|_| claims.email_verified) else {
+            return Ok(None);
+        };
+        Ok(sqlx::query_scalar!("SELECT id FROM users WHERE lower(email) = lower($1)", email)
+            .fetch_optional(conn)
+            .await?)
+    }
+}
+
+async fn create_user(conn: &mut PgConnection, claims: &IdTokenClaims) -> Result<Uuid> {
+    let email = claims
+        .email
+        .as_deref()
+        .ok_or_else(|| ApiError::BadRequest("Identity provider did not share an email address".to_string()))?;
+    let username = new_username(claims)
+        .ok_or_else(|| ApiError::BadRequest("Identity provider did not share a usable username".to_string()))?;
+    let department = claims
+        .extra
+        .get("department")
+        .and_then(|department| department.as_str())
+        .unwrap_or(DEFAULT_DEPARTMENT);
+    // Single sign-on users never learn this password, so only the provider can sign them in
+    let password_hash = PasswordService::hash_password(&PasswordService::generate_temporary_password())?;
+
+    sqlx::query_scalar(
+        "INSERT INTO users (username, email, password_hash, department, first_name, last_name)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id",
+    )
+    .bind(&username)
+    .bind(email)
+    .bind(&password_hash)
+    .bind(department)
+    .bind(claims.given_name.as_deref().unwrap_or(&username))
+    .bind(claims.family_name.as_deref().unwrap_or_default())
+    .fetch_one(conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db_error) if db_error.is_unique_violation() => ApiError::Conflict(format!(
+            "Username {} or email {} belongs to another account",
+            username, email
+        )),
+        _ => e.into(),
+    })
+}
+
+/// Set the account role and assigned roles the user's groups map to
+async fn sync_roles(conn: &mut PgConnection, user_id: Uuid, groups: &[String], mapping: &GroupRoles) -> Result<()> {
+    if let Some(role) = mapping.account_role(groups) {
+        sqlx::query("UPDATE users SET role = ($2)::user_role WHERE id = $1 AND role <> ($2)::user_role")
+            .bind(user_id)
+            .bind(role)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    let mapped = mapping.mapped();
+    let granted = mapping.granted(groups);
+    sqlx::query(
+        "DELETE FROM user_roles ur USING roles r
+         WHERE ur.role_id = r.id AND ur.user_id = $1 AND r.name = ANY($2) AND r.name <> ALL($3)",
+    )
+    .bind(user_id)
+    .bind(&mapped)
+    .bind(&granted)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(
+        "INSERT INTO user_roles (user_id, role_id)
+         SELECT $1, id FROM roles WHERE name = ANY($2)
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(user_id)
+    .bind(&granted)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(preferred_username: Option<&str>, email: Option<&str>) -> IdTokenClaims {
+        serde_json::from_value(serde_json::json!({
+            "iss": "https://sso.example.ac.th",
+            "sub": "u-1",
+            "preferred_username": preferred_username,
+            "email": email,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_new_usernames() {
+        assert_eq!(new_username(&claims(Some("somchai.k"), Some("sk@example.ac.th"))).as_deref(), Some("somchai.k"));
+        assert_eq!(new_username(&claims(None, Some("sk01@example.ac.th"))).as_deref(), Some("sk01"));
+        assert_eq!(new_username(&claims(Some("ab"), None)), None);
+        assert_eq!(new_username(&claims(None, None)), None);
+    }
+}
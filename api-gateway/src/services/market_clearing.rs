@@ -0,0 +1,185 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{ApiError, Result};
+use crate::handlers::blockchain::singleton_address;
+use crate::models::market_clearing::MarketClearingRun;
+use crate::services::blockchain::BlockchainService;
+use crate::services::fee_payers::FeePayerPool;
+use crate::services::gateway_signer::GatewaySigner;
+use crate::services::scheduler::{spawn_cron, CronSchedule};
+use crate::services::transaction::Pubkey;
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+pub const RUN_COLUMNS: &str =
+    "id, trigger, scheduled_for, status, signature, error, triggered_by, created_at, completed_at";
+
+/// Most runs returned by one history request
+pub const MAX_HISTORY_LIMIT: i64 = 500;
+
+/// Triggers the oracle's market clearing and records every run
+#[derive(Clone)]
+pub struct MarketClearingService {
+    db: PgPool,
+    chain: BlockchainService,
+    oracle_program_id: Pubkey,
+    governance_program_id: String,
+    signer: GatewaySigner,
+    fee_payers: FeePayerPool,
+    clock: SharedClock,
+}
+
+impl MarketClearingService {
+    pub fn new(
+        db: PgPool,
+        chain: BlockchainService,
+        oracle_program_id: Pubkey,
+        governance_program_id: String,
+        signer: GatewaySigner,
+        fee_payers: FeePayerPool,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            db,
+            chain,
+            oracle_program_id,
+            governance_program_id,
+            signer,
+            fee_payers,
+            clock,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let oracle_program_id = Pubkey::from_str(&state.config.oracle_program_id)
+            .map_err(|e| ApiError::Configuration(format!("Invalid ORACLE_PROGRAM_ID: {}", e)))?;
+
+        Ok(Self::new(
+            state.db.clone(),
+            state.blockchain_service.clone(),
+            oracle_program_id,
+            state.config.governance_program_id.clone(),
+            state.signer.clone(),
+            state.fee_payers.clone(),
+            state.clock.clone(),
+        ))
+    }
+
+    /// Most recent runs, newest first
+    pub async fn list(&self, limit: i64) -> Result<Vec<MarketClearingRun>> {
+        let query = format!(
+            "SELECT {} FROM market_clearing_runs ORDER BY created_at DESC LIMIT $1",
+            RUN_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, MarketClearingRun>(&query)
+            .bind(limit.clamp(1, MAX_HISTORY_LIMIT))
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Trigger clearing unless governance is paused, returning the recorded run
+    ///
+    /// A scheduled run is claimed per `scheduled_for`, so when several replicas reach the
+    /// same boundary only the first triggers it and the rest get `None`.
+    pub async fn run(
+        &self,
+        trigger: &str,
+        scheduled_for: DateTime<Utc>,
+        triggered_by: Option<Uuid>,
+    ) -> Result<Option<MarketClearingRun>> {
+        let query = format!(
+            "INSERT INTO market_clearing_runs (trigger, scheduled_for, triggered_by, status)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (scheduled_for) WHERE trigger = 'scheduled' DO NOTHING
+             RETURNING {}",
+            RUN_COLUMNS
+        );
+        let Some(run) = sqlx::query_as::<_, MarketClearingRun>(&query)
+            .bind(trigger)
+            .bind(scheduled_for)
+            .bind(triggered_by)
+            .bind(MarketClearingRun::RUNNING)
+            .fetch_optional(&self.db)
+            .await?
+        else {
+            tracing::debug!("Market clearing for {} already claimed by another replica", scheduled_for);
+            return Ok(None);
+        };
+
+        let (status, signature, error) = match self.pause_flags().await {
+            Ok(0) => match self.submit().await {
+                Ok(signature) => (MarketClearingRun::SUBMITTED, Some(signature), None),
+                Err(e) => (MarketClearingRun::FAILED, None, Some(e.to_string())),
+            },
+            Ok(flags) => (
+                MarketClearingRun::SKIPPED,
+                None,
+                Some(format!("Governance is paused (flags {:#04b})", flags)),
+            ),
+            Err(e) => (
+                MarketClearingRun::FAILED,
+                None,
+                Some(format!("Could not read governance pause state: {}", e)),
+            ),
+        };
+        match &error {
+            Some(reason) => tracing::warn!("Market clearing run {} {}: {}", run.id, status, reason),
+            None => tracing::info!("Market clearing run {} submitted", run.id),
+        }
+        metrics::counter!("market_clearing_runs_total", "trigger" => trigger.to_string(), "status" => status)
+            .increment(1);
+
+        let query = format!(
+            "UPDATE market_clearing_runs SET status = $2, signature = $3, error = $4, completed_at = $5
+             WHERE id = $1
+             RETURNING {}",
+            RUN_COLUMNS
+        );
+        Ok(Some(
+            sqlx::query_as::<_, MarketClearingRun>(&query)
+                .bind(run.id)
+                .bind(status)
+                .bind(signature)
+                .bind(error)
+                .bind(self.clock.now())
+                .fetch_one(&self.db)
+                .await?,
+        ))
+    }
+
+    /// Governance pause flags in force now; an uninitialized governance program pauses nothing
+    async fn pause_flags(&self) -> Result<u8> {
+        let address = singleton_address(&self.governance_program_id, b"poa_config")?;
+        let poa_config = self
+            .chain
+            .get_anchor_account::<governance::PoAConfig>(&address)
+            .await?;
+        Ok(poa_config.map_or(0, |config| config.active_pause_flags(self.clock.now().timestamp())))
+    }
+
+    async fn submit(&self) -> Result<String> {
+        let authority = self.signer.lease().await?;
+        let fee_payer = self.fee_payers.next();
+        self.chain
+            .trigger_market_clearing(self.oracle_program_id, authority.as_ref(), fee_payer.as_deref())
+            .await
+    }
+
+    /// Trigger clearing at every boundary of `schedule`
+    pub fn spawn(self, schedule: CronSchedule) {
+        let clock = self.clock.clone();
+        spawn_cron("market_clearing", schedule, clock, move |scheduled_for| {
+            let service = self.clone();
+            async move {
+                service
+                    .run(MarketClearingRun::SCHEDULED, scheduled_for, None)
+                    .await
+                    .map(|_| ())
+            }
+        });
+    }
+}
@@ -0,0 +1,255 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use sqlx::types::BigDecimal;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::services::order_book::{OrderBookChange, OrderBookMirror, OrderBookSnapshot};
+use crate::services::program_logs::{ProgramEvent, ProgramLogSubscriber};
+use crate::utils::decimal::decimal;
+
+/// Postgres channel the matching engine announces committed trades on
+pub const TRADE_CHANNEL: &str = "trade_executions";
+
+/// Updates buffered per client before slow clients are resynchronized with a snapshot
+const UPDATE_CHANNEL_CAPACITY: usize = 1024;
+
+/// Trade executed by the matching engine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradePrint {
+    pub execution_id: Uuid,
+    pub energy_amount: Decimal,
+    pub price_per_kwh: Decimal,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// Message streamed to `/ws/market` clients
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MarketUpdate {
+    /// Whole order book and the last trade, replacing whatever the client holds
+    Snapshot {
+        order_book: OrderBookSnapshot,
+        last_trade: Option<TradePrint>,
+    },
+    /// Order book delta; deltas at or below the snapshot's version are already in it
+    OrderBook(OrderBookChange),
+    Trade(TradePrint),
+    /// On-chain market clearing result
+    Cleared {
+        signature: String,
+        epoch: u64,
+        clearing_price: u64,
+        total_volume: u64,
+        trades: u64,
+        timestamp: i64,
+    },
+}
+
+/// Fans order book deltas, trades and clearing results out to market data clients
+///
+/// Trades arrive over Postgres notifications, so every gateway replica streams them, not
+/// only the one leading the matching engine.
+pub struct MarketFeed {
+    sender: broadcast::Sender<MarketUpdate>,
+    last_trade: Mutex<Option<TradePrint>>,
+}
+
+impl Default for MarketFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarketFeed {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+        Self {
+            sender,
+            last_trade: Mutex::new(None),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<MarketUpdate> {
+        self.sender.subscribe()
+    }
+
+    pub fn last_trade(&self) -> Option<TradePrint> {
+        self.last_trade.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Snapshot a client starts from, or resynchronizes with after missing updates
+    pub fn snapshot(&self, order_book: &OrderBookMirror) -> MarketUpdate {
+        MarketUpdate::Snapshot {
+            order_book: order_book.snapshot().as_ref().clone(),
+            last_trade: self.last_trade(),
+        }
+    }
+
+    fn publish(&self, update: MarketUpdate) {
+        if let MarketUpdate::Trade(trade) = &update {
+            let mut last_trade = self.last_trade.lock().unwrap_or_else(|e| e.into_inner());
+            // Notifications from concurrent commits may arrive out of order
+            if last_trade.as_ref().is_none_or(|last| last.executed_at <= trade.executed_at) {
+                *last_trade = Some(trade.clone());
+            }
+        }
+        // No receivers is fine; no client is connected
+        let _ = self.sender.send(update);
+    }
+
+    /// Forward order book changes, trade notifications and clearing events to clients
+    pub fn spawn(self: &Arc<Self>, db: PgPool, order_book: Arc<OrderBookMirror>, program_events: &ProgramLogSubscriber) {
+        let feed = Arc::clone(self);
+        let mut changes = order_book.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let change = match changes.recv().await {
+                    Ok(change) => change,
+                    // Clients reload the book rather than apply deltas with a gap
+                    Err(broadcast::error::RecvError::Lagged(_)) => OrderBookChange::Reset {
+                        version: order_book.snapshot().version,
+                    },
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                feed.publish(MarketUpdate::OrderBook(change));
+            }
+        });
+
+        let feed = Arc::clone(self);
+        let mut events = program_events.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let notice = match events.recv().await {
+                    Ok(notice) => notice,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!("Market feed skipped {} program events", missed);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if let ProgramEvent::MarketCleared {
+                    epoch,
+                    clearing_price,
+                    total_volume,
+                    trades,
+                    timestamp,
+                    ..
+                } = notice.event
+                {
+                    feed.publish(MarketUpdate::Cleared {
+                        signature: notice.signature,
+                        epoch,
+                        clearing_price,
+                        total_volume,
+                        trades,
+                        timestamp,
+                    });
+                }
+            }
+        });
+
+        let feed = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = feed.listen(&db).await {
+                    tracing::error!("Market feed trade listener failed: {}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    async fn listen(&self, db: &PgPool) -> Result<()> {
+        let mut listener = PgListener::connect_with(db).await?;
+        listener.listen(TRADE_CHANNEL).await?;
+
+        // Trades committed while disconnected were not delivered
+        if let Some(trade) = load_last_trade(db).await? {
+            self.publish(MarketUpdate::Trade(trade));
+        }
+        tracing::info!("Market feed listening on {}", TRADE_CHANNEL);
+
+        loop {
+            let notification = listener.recv().await?;
+            match serde_json::from_str::<TradePrint>(notification.payload()) {
+                Ok(trade) => self.publish(MarketUpdate::Trade(trade)),
+                Err(e) => tracing::warn!("Ignoring malformed trade notification: {}", e),
+            }
+        }
+    }
+}
+
+async fn load_last_trade(db: &PgPool) -> Result<Option<TradePrint>> {
+    let row: Option<(Uuid, BigDecimal, BigDecimal, DateTime<Utc>)> = sqlx::query_as(
+        "SELECT id, energy_amount, price_per_kwh, executed_at FROM trade_executions
+         ORDER BY executed_at DESC LIMIT 1",
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|(execution_id, energy_amount, price_per_kwh, executed_at)| TradePrint {
+        execution_id,
+        energy_amount: decimal(&energy_amount),
+        price_per_kwh: decimal(&price_per_kwh),
+        executed_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn trade(minute: u32) -> TradePrint {
+        TradePrint {
+            execution_id: Uuid::nil(),
+            energy_amount: Decimal::new(25, 1),
+            price_per_kwh: Decimal::new(4, 0),
+            executed_at: Utc.with_ymd_and_hms(2024, 9, 23, 12, minute, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_updates_carry_their_type_tag() {
+        let change = MarketUpdate::OrderBook(OrderBookChange::Remove {
+            version: 7,
+            order_id: Uuid::nil(),
+        });
+        assert_eq!(
+            serde_json::to_value(&change).unwrap(),
+            json!({ "type": "order_book", "change": "remove", "version": 7, "order_id": Uuid::nil() })
+        );
+
+        let value = serde_json::to_value(MarketUpdate::Trade(trade(0))).unwrap();
+        assert_eq!(value["type"], "trade");
+        assert_eq!(serde_json::from_value::<TradePrint>(value).unwrap(), trade(0));
+    }
+
+    #[tokio::test]
+    async fn test_last_trade_ignores_late_notifications() {
+        let feed = MarketFeed::new();
+        let mut updates = feed.subscribe();
+
+        feed.publish(MarketUpdate::Trade(trade(5)));
+        feed.publish(MarketUpdate::Trade(trade(3)));
+        assert_eq!(feed.last_trade(), Some(trade(5)));
+
+        // Both are still streamed
+        assert!(matches!(updates.recv().await.unwrap(), MarketUpdate::Trade(t) if t == trade(5)));
+        assert!(matches!(updates.recv().await.unwrap(), MarketUpdate::Trade(t) if t == trade(3)));
+
+        let MarketUpdate::Snapshot { last_trade, .. } = feed.snapshot(&OrderBookMirror::new()) else {
+            unreachable!()
+        };
+        assert_eq!(last_trade, Some(trade(5)));
+    }
+}
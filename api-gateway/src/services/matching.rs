@@ -0,0 +1,719 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::PgListener;
+use sqlx::types::BigDecimal;
+use sqlx::{PgPool, Postgres};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::database::schema::types::{OrderSide, OrderStatus};
+use crate::error::{ApiError, Result};
+use crate::models::trading::SettlementTrade;
+use crate::models::tx_job::TxJob;
+use crate::services::event_bus::{self, GatewayEvent};
+use crate::services::market_feed::{TradePrint, TRADE_CHANNEL};
+use crate::services::order_book::ORDER_BOOK_CHANNEL;
+use crate::services::pricing::{PriceBand, PricingService};
+use crate::services::scheduler::{spawn_cron, CronSchedule};
+use crate::services::settlement::{chunk_trades, MarketTokens, Settler};
+use crate::services::tx_queue::{enqueue_with, TxOperation};
+use crate::utils::clock::SharedClock;
+use crate::utils::decimal::{big_decimal, decimal};
+use crate::AppState;
+
+/// Session advisory lock held by the one gateway replica running the engine ("matching")
+const MATCHING_LOCK_KEY: i64 = 0x6d61_7463_6869_6e67;
+
+/// How often a standby replica tries to take over, and the leader checks it still holds the lock
+const LEADER_RETRY: Duration = Duration::from_secs(5);
+const LEADER_HEARTBEAT: Duration = Duration::from_secs(10);
+
+/// When crossing orders are matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchingMode {
+    /// As soon as an order arrives, at the price of the order that was resting on the book
+    Continuous,
+    /// At each epoch boundary, all at the uniform price the trading program's own clearing uses
+    Epoch,
+}
+
+impl FromStr for MatchingMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> std::result::Result<Self, Self::Err> {
+        match mode.trim().to_ascii_lowercase().as_str() {
+            "continuous" => Ok(Self::Continuous),
+            "epoch" => Ok(Self::Epoch),
+            _ => Err(format!("Unknown matching mode {:?}; expected continuous or epoch", mode)),
+        }
+    }
+}
+
+/// Open order as the engine holds it, with what is left to fill
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct OpenOrderRow {
+    id: Uuid,
+    user_id: Uuid,
+    wallet_address: String,
+    side: OrderSide,
+    price_per_kwh: BigDecimal,
+    remaining: BigDecimal,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookOrder {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub wallet: String,
+    pub is_buy: bool,
+    pub price: Decimal,
+    pub remaining: Decimal,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<OpenOrderRow> for BookOrder {
+    fn from(row: OpenOrderRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            wallet: row.wallet_address,
+            is_buy: matches!(row.side, OrderSide::Buy),
+            price: decimal(&row.price_per_kwh),
+            remaining: decimal(&row.remaining),
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+        }
+    }
+}
+
+/// Quantity matched between a buy and a sell order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub buy_order_id: Uuid,
+    pub sell_order_id: Uuid,
+    pub buyer_id: Uuid,
+    pub seller_id: Uuid,
+    pub buyer_wallet: String,
+    pub seller_wallet: String,
+    pub quantity: Decimal,
+    pub price: Decimal,
+}
+
+/// Order as a fill left it
+#[derive(sqlx::FromRow)]
+struct FilledOrder {
+    user_id: Uuid,
+    side: OrderSide,
+    energy_amount: BigDecimal,
+    /// Whether the fill completed the order
+    completed: bool,
+}
+
+/// Open orders in price-time priority
+#[derive(Debug, Clone, Default)]
+pub struct MatchingBook {
+    /// Highest price first
+    bids: Vec<BookOrder>,
+    /// Lowest price first
+    asks: Vec<BookOrder>,
+}
+
+impl MatchingBook {
+    pub fn from_orders(orders: impl IntoIterator<Item = BookOrder>) -> Self {
+        let mut book = Self::default();
+        for order in orders {
+            book.side_mut(order.is_buy).push(order);
+        }
+        book.sort();
+        book
+    }
+
+    pub fn len(&self) -> usize {
+        self.bids.len() + self.asks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Insert `order`, replacing any earlier version of it
+    pub fn upsert(&mut self, order: BookOrder) {
+        self.remove(order.id);
+        self.side_mut(order.is_buy).push(order);
+        self.sort();
+    }
+
+    pub fn remove(&mut self, order_id: Uuid) {
+        self.bids.retain(|order| order.id != order_id);
+        self.asks.retain(|order| order.id != order_id);
+    }
+
+    fn side_mut(&mut self, is_buy: bool) -> &mut Vec<BookOrder> {
+        if is_buy {
+            &mut self.bids
+        } else {
+            &mut self.asks
+        }
+    }
+
+    fn sort(&mut self) {
+        self.bids
+            .sort_by(|a, b| b.price.cmp(&a.price).then(a.created_at.cmp(&b.created_at)));
+        self.asks
+            .sort_by(|a, b| a.price.cmp(&b.price).then(a.created_at.cmp(&b.created_at)));
+    }
+
+    /// Match crossing orders, returning the fills and the book left once they are applied
+    ///
+    /// Orders expired at `now` are dropped without matching. Fills clear within `band`: bids
+    /// below its floor and asks above its ceiling stay on the book unmatched.
+    pub fn matched(&self, mode: MatchingMode, band: &PriceBand, now: DateTime<Utc>) -> (Vec<Fill>, MatchingBook) {
+        let live = |order: &BookOrder| order.expires_at.is_none_or(|expires_at| expires_at > now);
+        let mut bids: Vec<BookOrder> = self.bids.iter().filter(|order| live(order)).cloned().collect();
+        let mut asks: Vec<BookOrder> = self.asks.iter().filter(|order| live(order)).cloned().collect();
+
+        let mut fills = Vec::new();
+        let mut marginal = None;
+        let (mut b, mut a) = (0, 0);
+        while b < bids.len() && a < asks.len() {
+            let (bid, ask) = (&bids[b], &asks[a]);
+            // Bids only get cheaper and asks dearer further down the book
+            if bid.price < ask.price || !band.admits_bid(bid.price) || !band.admits_ask(ask.price) {
+                break;
+            }
+
+            let quantity = bid.remaining.min(ask.remaining);
+            let resting_price = if bid.created_at <= ask.created_at { bid.price } else { ask.price };
+            fills.push(Fill {
+                buy_order_id: bid.id,
+                sell_order_id: ask.id,
+                buyer_id: bid.user_id,
+                seller_id: ask.user_id,
+                buyer_wallet: bid.wallet.clone(),
+                seller_wallet: ask.wallet.clone(),
+                quantity,
+                price: band.clamp(resting_price),
+            });
+            marginal = Some((bid.price, ask.price));
+
+            bids[b].remaining -= quantity;
+            asks[a].remaining -= quantity;
+            if bids[b].remaining.is_zero() {
+                b += 1;
+            }
+            if asks[a].remaining.is_zero() {
+                a += 1;
+            }
+        }
+
+        // Every matched bid is at or above the marginal bid and every matched ask at or
+        // below the marginal ask, so the midpoint is acceptable to all of them. The band
+        // overlaps the marginal spread, so clamping keeps it so.
+        if let (MatchingMode::Epoch, Some((bid_price, ask_price))) = (mode, marginal) {
+            let price = band.clamp(ask_price + (bid_price - ask_price) / Decimal::TWO);
+            fills.iter_mut().for_each(|fill| fill.price = price);
+        }
+
+        bids.retain(|order| !order.remaining.is_zero());
+        asks.retain(|order| !order.remaining.is_zero());
+        (fills, MatchingBook { bids, asks })
+    }
+
+    /// Drop the fills `tokens` cannot settle, as `matched` left them on this book
+    ///
+    /// An order still on the book gets a dropped fill's quantity back. One the fill exhausted
+    /// stays off it: what it has left rounds to nothing in token base units at that price.
+    pub fn retain_settleable(&mut self, fills: &mut Vec<Fill>, tokens: &MarketTokens) {
+        fills.retain(|fill| {
+            if tokens.amounts(fill.quantity, fill.price).is_some() {
+                return true;
+            }
+            tracing::warn!(
+                "Dropping fill of {} kWh between orders {} and {}: too small to settle in token base units",
+                fill.quantity,
+                fill.buy_order_id,
+                fill.sell_order_id
+            );
+            let orders = self.bids.iter_mut().filter(|order| order.id == fill.buy_order_id);
+            let orders = orders.chain(self.asks.iter_mut().filter(|order| order.id == fill.sell_order_id));
+            orders.for_each(|order| order.remaining += fill.quantity);
+            false
+        });
+    }
+}
+
+/// Open orders of active users with a registered wallet and a confirmed on-chain commitment,
+/// or just `order_id` if it is one of them
+///
+/// Orders of users without a wallet, or who were deactivated, stay open but cannot be settled,
+/// so they are not matched; nor are orders until the trading program holds their commitment.
+async fn load_open_orders(db: &PgPool, now: DateTime<Utc>, order_id: Option<Uuid>) -> Result<Vec<BookOrder>> {
+    let rows = sqlx::query_as::<_, OpenOrderRow>(
+        "SELECT o.id, o.user_id, u.wallet_address, o.side, o.price_per_kwh,
+                o.energy_amount - o.filled_amount AS remaining, o.created_at, o.expires_at
+         FROM trading_orders o
+         JOIN users u ON u.id = o.user_id
+         JOIN tx_jobs j ON j.id = o.commitment_job_id
+         WHERE o.status IN ($1, $2)
+           AND j.status = $5
+           AND o.filled_amount < o.energy_amount
+           AND o.price_per_kwh IS NOT NULL
+           AND u.wallet_address IS NOT NULL
+           AND u.is_active
+           AND (o.expires_at IS NULL OR o.expires_at > $3)
+           AND ($4::uuid IS NULL OR o.id = $4)
+         ORDER BY o.created_at",
+    )
+    .bind(OrderStatus::Pending)
+    .bind(OrderStatus::Active)
+    .bind(now)
+    .bind(order_id)
+    .bind(TxJob::CONFIRMED)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(BookOrder::from).collect())
+}
+
+/// Book and market held by the replica leading the engine
+struct Leader {
+    book: MatchingBook,
+    tokens: MarketTokens,
+}
+
+/// Off-chain matching of API orders, settled on-chain in compact trading program batches
+///
+/// One gateway replica leads at a time, holding a Postgres advisory lock. The leader keeps
+/// the open orders in memory, fed by the `trading_orders` change notifications, and persists
+/// each round of fills, the orders they fill and the settlement jobs settling them in one
+/// database transaction before applying them to its book. A new leader, after a crash or
+/// failover, rebuilds the book by replaying the persisted open orders.
+pub struct MatchingEngine {
+    db: PgPool,
+    settler: Settler,
+    mode: MatchingMode,
+    pricing: PricingService,
+    leader: Mutex<Option<Leader>>,
+    clock: SharedClock,
+}
+
+impl MatchingEngine {
+    pub fn new(db: PgPool, settler: Settler, mode: MatchingMode, clock: SharedClock) -> Self {
+        Self {
+            pricing: PricingService::new(db.clone(), clock.clone()),
+            db,
+            settler,
+            mode,
+            leader: Mutex::new(None),
+            clock,
+        }
+    }
+
+    pub fn from_state(state: &AppState, mode: MatchingMode) -> Result<Self> {
+        Ok(Self::new(
+            state.db.clone(),
+            Settler::from_state(state)?,
+            mode,
+            state.clock.clone(),
+        ))
+    }
+
+    /// Lead the engine whenever this replica holds the lock, matching at each boundary of
+    /// `epoch_schedule` in epoch mode
+    pub fn spawn(self: Arc<Self>, epoch_schedule: Option<CronSchedule>) {
+        let engine = Arc::clone(&self);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = engine.lead().await {
+                    tracing::error!("Matching engine stopped leading: {}", e);
+                }
+                engine.leader.lock().await.take();
+                tokio::time::sleep(LEADER_RETRY).await;
+            }
+        });
+
+        if let Some(schedule) = epoch_schedule {
+            let clock = self.clock.clone();
+            spawn_cron("order_matching", schedule, clock, move |_| {
+                let engine = Arc::clone(&self);
+                async move { engine.match_book().await.map(|_| ()) }
+            });
+        }
+    }
+
+    /// Run the engine while holding the advisory lock; returns at once when another replica holds it
+    async fn lead(&self) -> Result<()> {
+        let mut lock = self.db.acquire().await?;
+        let acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+            .bind(MATCHING_LOCK_KEY)
+            .fetch_one(&mut *lock)
+            .await?;
+        if !acquired {
+            return Ok(());
+        }
+
+        tracing::info!("Matching engine leading in {:?} mode", self.mode);
+        let result = self.run(&mut lock).await;
+        // Closing rather than returning the connection to the pool releases the lock
+        if let Err(e) = lock.close().await {
+            tracing::warn!("Could not close the matching engine lock connection: {}", e);
+        }
+        result
+    }
+
+    async fn run(&self, lock: &mut PoolConnection<Postgres>) -> Result<()> {
+        let mut listener = PgListener::connect_with(&self.db).await?;
+        listener.listen(ORDER_BOOK_CHANNEL).await?;
+
+        // Orders placed while no replica was leading were not delivered
+        self.recover().await?;
+
+        let mut heartbeat = tokio::time::interval(LEADER_HEARTBEAT);
+        loop {
+            tokio::select! {
+                notification = listener.recv() => {
+                    let notification = notification?;
+                    match Uuid::parse_str(notification.payload()) {
+                        Ok(order_id) => self.sync_order(order_id).await?,
+                        Err(_) => tracing::warn!("Ignoring malformed order book notification: {}", notification.payload()),
+                    }
+                }
+                _ = heartbeat.tick() => {
+                    sqlx::query("SELECT 1").execute(&mut **lock).await?;
+                }
+            }
+        }
+    }
+
+    /// Rebuild the book from the persisted open orders
+    async fn recover(&self) -> Result<()> {
+        let tokens = self.settler.market_tokens().await?;
+        let mut leader = self.leader.lock().await;
+        let book = MatchingBook::from_orders(load_open_orders(&self.db, self.clock.now(), None).await?);
+        tracing::info!("Matching engine recovered {} open orders", book.len());
+        *leader = Some(Leader { book, tokens });
+
+        if self.mode == MatchingMode::Continuous {
+            if let Some(leader) = leader.as_mut() {
+                self.match_leader(leader).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply a changed order to the book, matching it at once in continuous mode
+    async fn sync_order(&self, order_id: Uuid) -> Result<()> {
+        let mut leader = self.leader.lock().await;
+        let Some(leader) = leader.as_mut() else {
+            return Ok(());
+        };
+
+        match load_open_orders(&self.db, self.clock.now(), Some(order_id)).await?.pop() {
+            Some(order) => leader.book.upsert(order),
+            None => leader.book.remove(order_id),
+        }
+        if self.mode == MatchingMode::Continuous {
+            self.match_leader(leader).await?;
+        }
+        Ok(())
+    }
+
+    /// Match the book now, returning the number of fills; nothing happens on a standby replica
+    pub async fn match_book(&self) -> Result<usize> {
+        match self.leader.lock().await.as_mut() {
+            Some(leader) => self.match_leader(leader).await,
+            None => Ok(0),
+        }
+    }
+
+    async fn match_leader(&self, leader: &mut Leader) -> Result<usize> {
+        if leader.book.is_empty() {
+            return Ok(0);
+        }
+
+        let now = self.clock.now();
+        let band = self.pricing.band_at(now).await?;
+        let (mut fills, mut book) = leader.book.matched(self.mode, &band, now);
+        book.retain_settleable(&mut fills, &leader.tokens);
+        if !fills.is_empty() {
+            self.persist(&fills, &leader.tokens, now).await?;
+            tracing::info!("Matching engine filled {} trades", fills.len());
+            metrics::counter!("matching_engine_trades_total").increment(fills.len() as u64);
+        }
+        leader.book = book;
+        Ok(fills.len())
+    }
+
+    /// Record `fills`, fill their orders and queue their settlement, all or nothing
+    ///
+    /// Every fill must be settleable, as `MatchingBook::retain_settleable` leaves them.
+    async fn persist(&self, fills: &[Fill], tokens: &MarketTokens, now: DateTime<Utc>) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        let mut executions = Vec::new();
+        let mut trades: Vec<SettlementTrade> = Vec::new();
+
+        for fill in fills {
+            let execution_id = Uuid::new_v4();
+            let (energy_amount, payment_amount) = tokens
+                .amounts(fill.quantity, fill.price)
+                .ok_or_else(|| ApiError::Internal(format!("Fill of {} kWh cannot be settled", fill.quantity)))?;
+            let trade = SettlementTrade {
+                buy_order: fill.buy_order_id,
+                sell_order: fill.sell_order_id,
+                buyer: fill.buyer_wallet.clone(),
+                seller: fill.seller_wallet.clone(),
+                energy_amount,
+                payment_amount,
+            };
+            sqlx::query(
+                "INSERT INTO trade_executions (
+                    id, buy_order_id, sell_order_id, buyer_id, seller_id, energy_amount, price_per_kwh,
+                    total_price, executed_at
+                 ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            )
+            .bind(execution_id)
+            .bind(fill.buy_order_id)
+            .bind(fill.sell_order_id)
+            .bind(fill.buyer_id)
+            .bind(fill.seller_id)
+            .bind(big_decimal(fill.quantity))
+            .bind(big_decimal(fill.price))
+            .bind(big_decimal(fill.quantity * fill.price))
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+            // Delivered to the market feed of every replica once the transaction commits
+            let print = TradePrint {
+                execution_id,
+                energy_amount: fill.quantity,
+                price_per_kwh: fill.price,
+                executed_at: now,
+            };
+            let payload = serde_json::to_string(&print)
+                .map_err(|e| ApiError::Internal(format!("Failed to serialize trade {}: {}", execution_id, e)))?;
+            sqlx::query("SELECT pg_notify($1, $2)")
+                .bind(TRADE_CHANNEL)
+                .bind(payload)
+                .execute(&mut *tx)
+                .await?;
+
+            for order_id in [fill.buy_order_id, fill.sell_order_id] {
+                // Orders cancelled or filled since the book last saw them are not filled again
+                let order = sqlx::query_as::<_, FilledOrder>(
+                    "UPDATE trading_orders SET
+                        filled_amount = filled_amount + $2,
+                        status = CASE WHEN filled_amount + $2 >= energy_amount THEN $3 ELSE $4 END,
+                        filled_at = CASE WHEN filled_amount + $2 >= energy_amount THEN $5 ELSE filled_at END
+                     WHERE id = $1 AND status IN ($6, $4) AND filled_amount + $2 <= energy_amount
+                     RETURNING user_id, side, energy_amount, status = $3 AS completed",
+                )
+                .bind(order_id)
+                .bind(big_decimal(fill.quantity))
+                .bind(OrderStatus::Filled)
+                .bind(OrderStatus::Active)
+                .bind(now)
+                .bind(OrderStatus::Pending)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| ApiError::Conflict(format!("Order {} is no longer open for matching", order_id)))?;
+
+                if order.completed {
+                    let event = GatewayEvent::OrderFilled {
+                        order_id,
+                        user_id: order.user_id,
+                        side: order.side,
+                        energy_amount: decimal(&order.energy_amount),
+                        filled_at: now,
+                    };
+                    event_bus::publish(&mut *tx, &event).await?;
+                }
+            }
+
+            executions.push(execution_id);
+            trades.push(trade);
+        }
+
+        for chunk in chunk_trades(self.settler.program_id(), tokens, &trades)? {
+            let batch: i64 = sqlx::query_scalar("SELECT nextval('settlement_batch_seq')")
+                .fetch_one(&mut *tx)
+                .await?;
+            let operation = TxOperation::SettleTrades {
+                batch: batch as u64,
+                trades: trades[chunk.clone()].to_vec(),
+            };
+            let job = enqueue_with(&mut *tx, &operation, None).await?;
+            sqlx::query("UPDATE trade_executions SET settlement_batch = $2, settlement_job_id = $3 WHERE id = ANY($1)")
+                .bind(&executions[chunk])
+                .bind(batch)
+                .bind(job.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::transaction::Pubkey;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 9, 23, 12, 0, 0).unwrap()
+    }
+
+    fn order(is_buy: bool, price: i64, quantity: i64, age_secs: i64) -> BookOrder {
+        BookOrder {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            wallet: "11111111111111111111111111111111".to_string(),
+            is_buy,
+            price: Decimal::new(price, 0),
+            remaining: Decimal::new(quantity, 0),
+            created_at: now() - chrono::Duration::seconds(age_secs),
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn test_continuous_matching_trades_at_the_resting_price() {
+        let resting_ask = order(false, 5, 10, 60);
+        let cheaper_ask = order(false, 4, 3, 30);
+        let bid = order(true, 6, 8, 0);
+        let book = MatchingBook::from_orders(vec![resting_ask.clone(), cheaper_ask.clone(), bid.clone()]);
+
+        let (fills, remaining) = book.matched(MatchingMode::Continuous, &PriceBand::default(), now());
+
+        assert_eq!(fills.len(), 2);
+        assert_eq!((fills[0].sell_order_id, fills[0].quantity, fills[0].price), (cheaper_ask.id, Decimal::new(3, 0), Decimal::new(4, 0)));
+        assert_eq!((fills[1].sell_order_id, fills[1].quantity, fills[1].price), (resting_ask.id, Decimal::new(5, 0), Decimal::new(5, 0)));
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining.asks[0].id, resting_ask.id);
+        assert_eq!(remaining.asks[0].remaining, Decimal::new(5, 0));
+        assert!(remaining.bids.is_empty());
+    }
+
+    #[test]
+    fn test_epoch_matching_uses_one_midpoint_price() {
+        let book = MatchingBook::from_orders(vec![
+            order(true, 10, 5, 10),
+            order(true, 7, 5, 20),
+            order(false, 4, 5, 30),
+            order(false, 6, 5, 40),
+        ]);
+
+        let (fills, remaining) = book.matched(MatchingMode::Epoch, &PriceBand::default(), now());
+
+        assert_eq!(fills.len(), 2);
+        // Marginal pair is the 7 bid and the 6 ask
+        assert!(fills.iter().all(|fill| fill.price == Decimal::new(65, 1)));
+        assert!(remaining.is_empty());
+    }
+
+    #[test]
+    fn test_expired_and_uncrossed_orders_are_not_matched() {
+        let mut expired_ask = order(false, 1, 5, 30);
+        expired_ask.expires_at = Some(now());
+        let ask = order(false, 8, 5, 20);
+        let bid = order(true, 7, 5, 10);
+        let book = MatchingBook::from_orders(vec![expired_ask.clone(), ask, bid]);
+
+        let (fills, remaining) = book.matched(MatchingMode::Continuous, &PriceBand::default(), now());
+
+        assert!(fills.is_empty());
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining.asks.iter().all(|order| order.id != expired_ask.id));
+    }
+
+    #[test]
+    fn test_price_band_bounds_matching_and_fill_prices() {
+        let band = PriceBand {
+            floor: Some(Decimal::new(3, 0)),
+            ceiling: Some(Decimal::new(6, 0)),
+        };
+        let cheap_ask = order(false, 1, 10, 40);
+        let dear_ask = order(false, 7, 5, 30);
+        let bid = order(true, 9, 5, 20);
+        let low_bid = order(true, 2, 5, 10);
+        let book = MatchingBook::from_orders(vec![cheap_ask.clone(), dear_ask.clone(), bid.clone(), low_bid.clone()]);
+
+        let (fills, remaining) = book.matched(MatchingMode::Continuous, &band, now());
+
+        // The resting ask's price is raised to the floor
+        assert_eq!(fills.len(), 1);
+        assert_eq!((fills[0].buy_order_id, fills[0].sell_order_id), (bid.id, cheap_ask.id));
+        assert_eq!(fills[0].price, Decimal::new(3, 0));
+        // The low bid still crosses the cheap ask, but only below the floor
+        assert_eq!(remaining.len(), 3);
+        assert_eq!(remaining.bids[0].id, low_bid.id);
+        assert_eq!((remaining.asks[0].id, remaining.asks[0].remaining), (cheap_ask.id, Decimal::new(5, 0)));
+        assert_eq!(remaining.asks[1].id, dear_ask.id);
+
+        let book = MatchingBook::from_orders(vec![order(true, 10, 5, 10), order(false, 8, 5, 20)]);
+        let (fills, _) = book.matched(MatchingMode::Epoch, &band, now());
+        assert_eq!(fills.len(), 0);
+
+        let book = MatchingBook::from_orders(vec![order(true, 10, 5, 10), order(false, 4, 5, 20)]);
+        let (fills, _) = book.matched(MatchingMode::Epoch, &band, now());
+        assert_eq!(fills[0].price, Decimal::new(6, 0));
+    }
+
+    #[test]
+    fn test_upsert_replaces_an_order() {
+        let bid = order(true, 5, 10, 0);
+        let mut book = MatchingBook::from_orders(vec![bid.clone()]);
+
+        let mut partially_filled = bid.clone();
+        partially_filled.remaining = Decimal::new(4, 0);
+        book.upsert(partially_filled);
+        assert_eq!(book.len(), 1);
+        assert_eq!(book.bids[0].remaining, Decimal::new(4, 0));
+
+        book.remove(bid.id);
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn test_fills_too_small_to_settle_are_dropped() {
+        let tokens = MarketTokens {
+            energy_mint: Pubkey::new([7; 32]),
+            energy_decimals: 3,
+            payment_mint: Pubkey::new([8; 32]),
+            payment_decimals: 6,
+        };
+        let mut dust_ask = order(false, 4, 0, 30);
+        dust_ask.remaining = Decimal::new(1, 4);
+        let ask = order(false, 5, 2, 20);
+        let bid = order(true, 6, 5, 10);
+        let book = MatchingBook::from_orders(vec![dust_ask.clone(), ask.clone(), bid.clone()]);
+
+        let (mut fills, mut remaining) = book.matched(MatchingMode::Continuous, &PriceBand::default(), now());
+        assert_eq!(fills.len(), 2);
+        remaining.retain_settleable(&mut fills, &tokens);
+
+        // 0.0001 kWh is below the energy mint's 0.001 base unit
+        assert_eq!(fills.len(), 1);
+        assert_eq!((fills[0].sell_order_id, fills[0].quantity), (ask.id, Decimal::new(2, 0)));
+        assert_eq!(remaining.len(), 1);
+        assert_eq!((remaining.bids[0].id, remaining.bids[0].remaining), (bid.id, Decimal::new(3, 0)));
+    }
+
+    #[test]
+    fn test_matching_mode_parses() {
+        assert_eq!("Continuous".parse::<MatchingMode>(), Ok(MatchingMode::Continuous));
+        assert_eq!(" epoch ".parse::<MatchingMode>(), Ok(MatchingMode::Epoch));
+        assert!("auction".parse::<MatchingMode>().is_err());
+    }
+}
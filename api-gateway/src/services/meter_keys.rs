@@ -0,0 +1,216 @@
+use std::str::FromStr;
+
+use ed25519_dalek::VerifyingKey;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{ApiError, Result};
+use crate::models::energy::{EnergyReadingSubmission, Meter, MeterSigningKey};
+use crate::services::transaction::{verify_signature, Pubkey};
+use crate::AppState;
+
+/// Domain prefix of the message a meter signs for each reading
+const READING_DOMAIN: &[u8] = b"gridtokenx:meter-reading:v1";
+
+const KEY_COLUMNS: &str = "meter_id, public_key, registered_by, created_at, updated_at";
+
+/// Bytes a meter signs for a reading: the domain, the meter ID as a u32 length and UTF-8
+/// bytes, then the timestamp in Unix milliseconds and the generated and consumed Wh, all
+/// little-endian `i64`
+pub fn reading_message(reading: &EnergyReadingSubmission) -> Vec<u8> {
+    let wh = |kwh: f64| (kwh * 1000.0).round() as i64;
+    let mut message = READING_DOMAIN.to_vec();
+    message.extend_from_slice(&(reading.meter_id.len() as u32).to_le_bytes());
+    message.extend_from_slice(reading.meter_id.as_bytes());
+    message.extend_from_slice(&reading.timestamp.timestamp_millis().to_le_bytes());
+    message.extend_from_slice(&wh(reading.energy_generated).to_le_bytes());
+    message.extend_from_slice(&wh(reading.energy_consumed).to_le_bytes());
+    message
+}
+
+/// Registered meter signing keys, checked against every pushed reading
+///
+/// Once a meter has a key, its readings must be signed with it. Meters without one are
+/// only accepted while `REQUIRE_METER_SIGNATURES` is off.
+#[derive(Clone)]
+pub struct MeterKeyStore {
+    db: PgPool,
+    required: bool,
+}
+
+impl MeterKeyStore {
+    pub fn new(db: PgPool, required: bool) -> Self {
+        Self { db, required }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone(), state.config.require_meter_signatures)
+    }
+
+    pub async fn list(&self) -> Result<Vec<MeterSigningKey>> {
+        let query = format!("SELECT {} FROM meter_signing_keys ORDER BY meter_id", KEY_COLUMNS);
+        Ok(sqlx::query_as::<_, MeterSigningKey>(&query).fetch_all(&self.db).await?)
+    }
+
+    /// Register or rotate the key an assigned meter signs its readings with
+    ///
+    /// Provisioned meters are issued their keys by the gateway and are left to
+    /// `MeterProvisioning`, which keeps the registry program in step.
+    pub async fn register(&self, meter_id: &str, public_key: &str, registered_by: Uuid) -> Result<MeterSigningKey> {
+        let key = Pubkey::from_str(public_key)
+            .map_err(|e| ApiError::BadRequest(format!("Invalid public_key: {}", e)))?;
+        if VerifyingKey::from_bytes(&key.0).is_err() {
+            return Err(ApiError::BadRequest("public_key is not an ed25519 public key".to_string()));
+        }
+
+        let provisioned: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM meters WHERE meter_id = $1)")
+            .bind(meter_id)
+            .fetch_one(&self.db)
+            .await?;
+        if provisioned {
+            return Err(ApiError::Conflict(format!(
+                "Meter {} is provisioned; rotate its key through its credentials instead",
+                meter_id
+            )));
+        }
+
+        let assigned: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM meter_assignments WHERE meter_id = $1 AND is_active)")
+                .bind(meter_id)
+                .fetch_one(&self.db)
+                .await?;
+        if !assigned {
+            return Err(ApiError::NotFound(format!("Meter {} is not assigned to a user", meter_id)));
+        }
+
+        let query = format!(
+            "INSERT INTO meter_signing_keys (meter_id, public_key, registered_by) VALUES ($1, $2, $3)
+             ON CONFLICT (meter_id) DO UPDATE SET
+                 public_key = EXCLUDED.public_key, registered_by = EXCLUDED.registered_by
+             RETURNING {}",
+            KEY_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, MeterSigningKey>(&query)
+            .bind(meter_id)
+            .bind(key.to_string())
+            .bind(registered_by)
+            .fetch_one(&self.db)
+            .await?)
+    }
+
+    pub async fn remove(&self, meter_id: &str) -> Result<()> {
+        let removed = sqlx::query("DELETE FROM meter_signing_keys WHERE meter_id = $1")
+            .bind(meter_id)
+            .execute(&self.db)
+            .await?
+            .rows_affected();
+        if removed == 0 {
+            return Err(ApiError::NotFound(format!("Meter {} has no signing key", meter_id)));
+        }
+        Ok(())
+    }
+
+    /// Reject a pushed reading unless it is signed by its meter's registered key, or is from a
+    /// decommissioned meter
+    pub async fn verify(&self, reading: &EnergyReadingSubmission) -> Result<()> {
+        let (public_key, status): (Option<String>, Option<String>) = sqlx::query_as(
+            "SELECT (SELECT public_key FROM meter_signing_keys WHERE meter_id = $1),
+                 (SELECT status FROM meters WHERE meter_id = $1)",
+        )
+        .bind(&reading.meter_id)
+        .fetch_one(&self.db)
+        .await?;
+        if status.as_deref() == Some(Meter::DECOMMISSIONED) {
+            return Err(ApiError::Unauthorized(format!("Meter {} has been decommissioned", reading.meter_id)));
+        }
+        let Some(public_key) = public_key else {
+            if self.required {
+                return Err(ApiError::Unauthorized(format!(
+                    "Meter {} has no registered signing key",
+                    reading.meter_id
+                )));
+            }
+            return Ok(());
+        };
+
+        let signature = reading
+            .meter_signature
+            .as_deref()
+            .ok_or_else(|| ApiError::Unauthorized(format!("Readings from meter {} must be signed", reading.meter_id)))?;
+        if !signed_by(&public_key, reading, signature) {
+            return Err(ApiError::Unauthorized(format!(
+                "Reading signature does not match meter {}'s registered key",
+                reading.meter_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Whether base58 `signature` over the reading is by base58 `public_key`
+fn signed_by(public_key: &str, reading: &EnergyReadingSubmission, signature: &str) -> bool {
+    let Ok(key) = Pubkey::from_str(public_key) else {
+        return false;
+    };
+    let Ok(signature) = bs58::decode(signature).into_vec() else {
+        return false;
+    };
+    verify_signature(&key, &reading_message(reading), &signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn reading() -> EnergyReadingSubmission {
+        EnergyReadingSubmission {
+            meter_id: "MTR-1".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 9, 23, 12, 0, 0).unwrap(),
+            energy_generated: 2.5,
+            energy_consumed: 1.25,
+            solar_irradiance: None,
+            temperature: None,
+            engineering_authority_signature: "engineering".to_string(),
+            metadata: None,
+            meter_signature: None,
+        }
+    }
+
+    #[test]
+    fn test_reading_message_layout() {
+        let message = reading_message(&reading());
+        let (domain, rest) = message.split_at(READING_DOMAIN.len());
+        assert_eq!(domain, READING_DOMAIN);
+        assert_eq!(rest[..4], 5u32.to_le_bytes());
+        assert_eq!(&rest[4..9], b"MTR-1");
+        assert_eq!(rest[9..17], 1_727_092_800_000i64.to_le_bytes());
+        assert_eq!(rest[17..25], 2_500i64.to_le_bytes());
+        assert_eq!(rest[25..], 1_250i64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_signatures_cover_the_reading() {
+        let meter = SigningKey::from_bytes(&[7; 32]);
+        let public_key = bs58::encode(meter.verifying_key().to_bytes()).into_string();
+        let signature = bs58::encode(meter.sign(&reading_message(&reading())).to_bytes()).into_string();
+        assert!(signed_by(&public_key, &reading(), &signature));
+
+        // A spoofed meter ID or altered energy no longer matches
+        let spoofed = EnergyReadingSubmission {
+            meter_id: "MTR-2".to_string(),
+            ..reading()
+        };
+        assert!(!signed_by(&public_key, &spoofed, &signature));
+        let inflated = EnergyReadingSubmission {
+            energy_generated: 25.0,
+            ..reading()
+        };
+        assert!(!signed_by(&public_key, &inflated, &signature));
+
+        let other = bs58::encode(SigningKey::from_bytes(&[8; 32]).verifying_key().to_bytes()).into_string();
+        assert!(!signed_by(&other, &reading(), &signature));
+        assert!(!signed_by(&public_key, &reading(), "not-base58!"));
+    }
+}
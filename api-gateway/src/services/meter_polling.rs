@@ -0,0 +1,398 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::error::{ApiError, Result};
+use crate::models::energy::{EnergyMetadata, EnergyReadingSubmission};
+use crate::models::meter_polling::MeterPollingConfig;
+use crate::services::dlms::{DlmsClient, Obis};
+use crate::services::modbus::ModbusClient;
+use crate::services::readings::ReadingStore;
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+pub const POLLING_COLUMNS: &str = "meter_id, protocol, host, port, unit_id, produced_register, consumed_register, \
+     register_scale, poll_interval_seconds, enabled, last_produced_kwh, last_consumed_kwh, last_polled_at, \
+     last_error, next_poll_at, created_at, updated_at";
+
+/// Most meters claimed in one poller pass
+const POLL_BATCH: i64 = 500;
+
+/// Polling settings submitted for a meter
+#[derive(Debug, Clone, Deserialize)]
+pub struct MeterPollingRequest {
+    /// modbus_tcp or dlms_cosem
+    pub protocol: String,
+    pub host: String,
+    /// Defaults to 502 for Modbus-TCP and 4059 for DLMS/COSEM
+    pub port: Option<u16>,
+    pub unit_id: Option<u16>,
+    pub produced_register: String,
+    pub consumed_register: String,
+    pub register_scale: Option<f64>,
+    pub poll_interval_seconds: Option<u32>,
+    pub enabled: Option<bool>,
+}
+
+/// Where a meter's cumulative energy counters are read from
+#[derive(Debug, Clone, PartialEq)]
+enum MeterSource {
+    Modbus { produced: u16, consumed: u16, scale: f64 },
+    Dlms { produced: Obis, consumed: Obis },
+}
+
+impl MeterSource {
+    fn parse(protocol: &str, produced: &str, consumed: &str, scale: f64) -> std::result::Result<Self, String> {
+        match protocol {
+            MeterPollingConfig::MODBUS_TCP => {
+                let register = |value: &str| {
+                    value
+                        .trim()
+                        .parse::<u16>()
+                        .map_err(|_| format!("Invalid Modbus register address {}", value))
+                };
+                if !(scale.is_finite() && scale > 0.0) {
+                    return Err("register_scale must be positive".to_string());
+                }
+                Ok(Self::Modbus {
+                    produced: register(produced)?,
+                    consumed: register(consumed)?,
+                    scale,
+                })
+            }
+            MeterPollingConfig::DLMS_COSEM => Ok(Self::Dlms {
+                produced: produced.parse()?,
+                consumed: consumed.parse()?,
+            }),
+            other => Err(format!("Protocol must be modbus_tcp or dlms_cosem, not {}", other)),
+        }
+    }
+
+    fn from_config(config: &MeterPollingConfig) -> Result<Self> {
+        Self::parse(
+            &config.protocol,
+            &config.produced_register,
+            &config.consumed_register,
+            config.register_scale,
+        )
+        .map_err(ApiError::Configuration)
+    }
+}
+
+/// Cumulative energy counters of a meter, in kWh
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MeterCounters {
+    produced_kwh: f64,
+    consumed_kwh: f64,
+}
+
+/// Energy over the interval between two counter reads; `None` for the first read or when
+/// a counter went backwards, e.g. after a meter replacement or register rollover
+fn interval_energy(previous: Option<MeterCounters>, current: MeterCounters) -> Option<MeterCounters> {
+    let previous = previous?;
+    let produced_kwh = current.produced_kwh - previous.produced_kwh;
+    let consumed_kwh = current.consumed_kwh - previous.consumed_kwh;
+    if produced_kwh < 0.0 || consumed_kwh < 0.0 {
+        return None;
+    }
+    Some(MeterCounters {
+        produced_kwh,
+        consumed_kwh,
+    })
+}
+
+async fn read_counters(config: &MeterPollingConfig, timeout: Duration) -> Result<MeterCounters> {
+    let port = u16::try_from(config.port).map_err(|_| ApiError::Configuration("Invalid meter port".to_string()))?;
+    let unit_id =
+        u16::try_from(config.unit_id).map_err(|_| ApiError::Configuration("Invalid meter unit id".to_string()))?;
+
+    match MeterSource::from_config(config)? {
+        MeterSource::Modbus { produced, consumed, scale } => {
+            let unit_id =
+                u8::try_from(unit_id).map_err(|_| ApiError::Configuration("Modbus unit id must be 0-255".to_string()))?;
+            let mut client = ModbusClient::connect(&config.host, port, unit_id, timeout).await?;
+            Ok(MeterCounters {
+                produced_kwh: f64::from(client.read_u32(produced).await?) * scale,
+                consumed_kwh: f64::from(client.read_u32(consumed).await?) * scale,
+            })
+        }
+        MeterSource::Dlms { produced, consumed } => {
+            let mut client = DlmsClient::connect(&config.host, port, unit_id, timeout).await?;
+            let counters = MeterCounters {
+                produced_kwh: client.read_energy_kwh(&produced).await?,
+                consumed_kwh: client.read_energy_kwh(&consumed).await?,
+            };
+            client.release().await;
+            Ok(counters)
+        }
+    }
+}
+
+/// Reads meters without push capability on their configured schedule and records the
+/// energy since the previous read as a reading
+#[derive(Clone)]
+pub struct MeterPoller {
+    db: PgPool,
+    readings: ReadingStore,
+    clock: SharedClock,
+    timeout: Duration,
+    concurrency: usize,
+}
+
+impl MeterPoller {
+    pub fn new(db: PgPool, readings: ReadingStore, clock: SharedClock, timeout: Duration, concurrency: usize) -> Self {
+        Self {
+            db,
+            readings,
+            clock,
+            timeout,
+            concurrency: concurrency.max(1),
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(
+            state.db.clone(),
+            ReadingStore::from_state(state),
+            state.clock.clone(),
+            Duration::from_secs(state.config.meter_poll_timeout),
+            state.config.meter_poll_concurrency,
+        )
+    }
+
+    pub async fn list(&self) -> Result<Vec<MeterPollingConfig>> {
+        let query = format!("SELECT {} FROM meter_polling_configs ORDER BY meter_id", POLLING_COLUMNS);
+        Ok(sqlx::query_as::<_, MeterPollingConfig>(&query)
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Create or replace a meter's polling settings; a changed source starts a new baseline
+    pub async fn configure(&self, meter_id: &str, request: MeterPollingRequest) -> Result<MeterPollingConfig> {
+        let register_scale = request.register_scale.unwrap_or(0.001);
+        MeterSource::parse(
+            &request.protocol,
+            &request.produced_register,
+            &request.consumed_register,
+            register_scale,
+        )
+        .map_err(ApiError::BadRequest)?;
+        if request.host.trim().is_empty() {
+            return Err(ApiError::BadRequest("host is required".to_string()));
+        }
+        let port = request.port.unwrap_or(match request.protocol.as_str() {
+            MeterPollingConfig::DLMS_COSEM => 4059,
+            _ => 502,
+        });
+        let poll_interval = i32::try_from(request.poll_interval_seconds.unwrap_or(900).max(1)).unwrap_or(i32::MAX);
+
+        let registered: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM meter_assignments WHERE meter_id = $1 AND is_active)")
+                .bind(meter_id)
+                .fetch_one(&self.db)
+                .await?;
+        if !registered {
+            return Err(ApiError::NotFound(format!("Meter {} is not assigned to a user", meter_id)));
+        }
+
+        let query = format!(
+            "INSERT INTO meter_polling_configs (meter_id, protocol, host, port, unit_id, produced_register,
+                 consumed_register, register_scale, poll_interval_seconds, enabled)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             ON CONFLICT (meter_id) DO UPDATE SET
+                 protocol = EXCLUDED.protocol, host = EXCLUDED.host, port = EXCLUDED.port,
+                 unit_id = EXCLUDED.unit_id, produced_register = EXCLUDED.produced_register,
+                 consumed_register = EXCLUDED.consumed_register, register_scale = EXCLUDED.register_scale,
+                 poll_interval_seconds = EXCLUDED.poll_interval_seconds, enabled = EXCLUDED.enabled,
+                 last_produced_kwh = NULL, last_consumed_kwh = NULL, last_error = NULL, next_poll_at = NOW()
+             RETURNING {}",
+            POLLING_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, MeterPollingConfig>(&query)
+            .bind(meter_id)
+            .bind(&request.protocol)
+            .bind(request.host.trim())
+            .bind(i32::from(port))
+            .bind(i32::from(request.unit_id.unwrap_or(1)))
+            .bind(&request.produced_register)
+            .bind(&request.consumed_register)
+            .bind(register_scale)
+            .bind(poll_interval)
+            .bind(request.enabled.unwrap_or(true))
+            .fetch_one(&self.db)
+            .await?)
+    }
+
+    pub async fn remove(&self, meter_id: &str) -> Result<()> {
+        let removed = sqlx::query("DELETE FROM meter_polling_configs WHERE meter_id = $1")
+            .bind(meter_id)
+            .execute(&self.db)
+            .await?
+            .rows_affected();
+        if removed == 0 {
+            return Err(ApiError::NotFound(format!("Meter {} is not polled", meter_id)));
+        }
+        Ok(())
+    }
+
+    /// Claim the meters due for a read, scheduling their next one
+    async fn claim_due(&self, now: DateTime<Utc>) -> Result<Vec<MeterPollingConfig>> {
+        let query = format!(
+            "UPDATE meter_polling_configs
+             SET next_poll_at = $1 + make_interval(secs => poll_interval_seconds)
+             WHERE meter_id IN (
+                 SELECT meter_id FROM meter_polling_configs
+                 WHERE enabled AND next_poll_at <= $1
+                 ORDER BY next_poll_at
+                 LIMIT $2
+                 FOR UPDATE SKIP LOCKED
+             )
+             RETURNING {}",
+            POLLING_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, MeterPollingConfig>(&query)
+            .bind(now)
+            .bind(POLL_BATCH)
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Read every due meter, returning how many were read
+    pub async fn poll_due(&self) -> Result<usize> {
+        let due = self.claim_due(self.clock.now()).await?;
+        let count = due.len();
+        futures::stream::iter(due)
+            .for_each_concurrent(self.concurrency, |config| async move {
+                if let Err(e) = self.poll(&config).await {
+                    tracing::error!("Failed to record poll of meter {}: {}", config.meter_id, e);
+                }
+            })
+            .await;
+        Ok(count)
+    }
+
+    async fn poll(&self, config: &MeterPollingConfig) -> Result<()> {
+        let now = self.clock.now();
+        let current = match read_counters(config, self.timeout).await {
+            Ok(current) => current,
+            Err(e) => {
+                metrics::counter!("meter_polls_total", "protocol" => config.protocol.clone(), "outcome" => "failure")
+                    .increment(1);
+                tracing::warn!("Polling meter {} over {} failed: {}", config.meter_id, config.protocol, e);
+                sqlx::query("UPDATE meter_polling_configs SET last_error = $2 WHERE meter_id = $1")
+                    .bind(&config.meter_id)
+                    .bind(e.to_string())
+                    .execute(&self.db)
+                    .await?;
+                return Ok(());
+            }
+        };
+        metrics::counter!("meter_polls_total", "protocol" => config.protocol.clone(), "outcome" => "success")
+            .increment(1);
+
+        let previous = config
+            .last_produced_kwh
+            .zip(config.last_consumed_kwh)
+            .map(|(produced_kwh, consumed_kwh)| MeterCounters {
+                produced_kwh,
+                consumed_kwh,
+            });
+        match interval_energy(previous, current) {
+            Some(energy) => {
+                let reading = EnergyReadingSubmission {
+                    meter_id: config.meter_id.clone(),
+                    timestamp: now,
+                    energy_generated: energy.produced_kwh,
+                    energy_consumed: energy.consumed_kwh,
+                    solar_irradiance: None,
+                    temperature: None,
+                    engineering_authority_signature: String::new(),
+                    metadata: Some(EnergyMetadata {
+                        location: format!("{}:{}", config.host, config.port),
+                        device_type: config.protocol.clone(),
+                        weather_conditions: None,
+                    }),
+                    // Read by the gateway itself rather than pushed by the meter
+                    meter_signature: None,
+                };
+                self.readings.record(&reading, now).await?;
+            }
+            None if previous.is_some() => {
+                tracing::warn!("Meter {} counters went backwards; starting a new baseline", config.meter_id)
+            }
+            None => {}
+        }
+
+        sqlx::query(
+            "UPDATE meter_polling_configs
+             SET last_produced_kwh = $2, last_consumed_kwh = $3, last_polled_at = $4, last_error = NULL
+             WHERE meter_id = $1",
+        )
+        .bind(&config.meter_id)
+        .bind(current.produced_kwh)
+        .bind(current.consumed_kwh)
+        .bind(now)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    pub fn spawn(self, interval: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.poll_due().await {
+                    tracing::error!("Meter polling pass failed: {}", e);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counters(produced_kwh: f64, consumed_kwh: f64) -> MeterCounters {
+        MeterCounters {
+            produced_kwh,
+            consumed_kwh,
+        }
+    }
+
+    #[test]
+    fn test_interval_energy() {
+        assert_eq!(interval_energy(None, counters(10.0, 5.0)), None);
+        assert_eq!(
+            interval_energy(Some(counters(10.0, 5.0)), counters(12.5, 5.0)),
+            Some(counters(2.5, 0.0))
+        );
+        assert_eq!(interval_energy(Some(counters(10.0, 5.0)), counters(0.5, 6.0)), None);
+    }
+
+    #[test]
+    fn test_meter_sources() {
+        assert_eq!(
+            MeterSource::parse("modbus_tcp", "0", "2", 0.01),
+            Ok(MeterSource::Modbus {
+                produced: 0,
+                consumed: 2,
+                scale: 0.01
+            })
+        );
+        assert!(MeterSource::parse("modbus_tcp", "70000", "2", 0.01).is_err());
+        assert!(MeterSource::parse("modbus_tcp", "0", "2", 0.0).is_err());
+        assert_eq!(
+            MeterSource::parse("dlms_cosem", "1.0.2.8.0.255", "1-0:1.8.0.255", 0.001),
+            Ok(MeterSource::Dlms {
+                produced: Obis([1, 0, 2, 8, 0, 255]),
+                consumed: Obis([1, 0, 1, 8, 0, 255]),
+            })
+        );
+        assert!(MeterSource::parse("iec_62056_21", "0", "2", 0.001).is_err());
+    }
+}
@@ -0,0 +1,392 @@
+use ed25519_dalek::SigningKey;
+use rand::RngCore;
+use serde::Deserialize;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::error::{ApiError, Result};
+use crate::models::energy::{Meter, MeterCredentials};
+use crate::models::tx_job::TxJob;
+use crate::services::prosumers::lock_trading_wallet;
+use crate::services::tx_queue::{enqueue_with, TxOperation};
+use crate::utils::clock::SharedClock;
+use crate::utils::validation::{MAX_BUILDING_LEN, MAX_ZONE_LEN};
+use crate::AppState;
+
+/// Meter with its inverter capacity, signing key, registry transaction status and latest reading
+const METER_SELECT: &str = "SELECT m.meter_id, m.user_id, m.meter_type, m.building, m.zone, i.capacity_kw,
+        m.sampling_interval_seconds, m.status, k.public_key, m.registry_job_id, j.status AS registry_status,
+        (SELECT MAX(er.timestamp) FROM energy_readings er WHERE er.meter_id = m.meter_id) AS last_reading_at,
+        m.registered_by, m.decommissioned_at, m.created_at, m.updated_at
+     FROM meters m
+     LEFT JOIN meter_inverters i ON i.meter_id = m.meter_id
+     LEFT JOIN meter_signing_keys k ON k.meter_id = m.meter_id
+     LEFT JOIN tx_jobs j ON j.id = m.registry_job_id";
+
+/// Most meters returned by one listing
+const MAX_METER_LIMIT: i64 = 500;
+
+/// Longest meter ID, as stored by the gateway
+const MAX_METER_ID_LEN: usize = 20;
+/// Longest sampling interval, in seconds
+const MAX_SAMPLING_INTERVAL: u32 = 24 * 60 * 60;
+
+/// Meter submitted for provisioning
+#[derive(Debug, Clone, Deserialize)]
+pub struct MeterRegistrationRequest {
+    pub meter_id: String,
+    /// User the meter is assigned to, who must be registered on-chain with a wallet
+    pub user_id: Uuid,
+    /// solar, wind, battery or grid
+    pub meter_type: String,
+    pub building: String,
+    #[serde(default)]
+    pub zone: String,
+    /// Rated inverter output generation is screened against
+    pub capacity_kw: f64,
+    /// Defaults to 900
+    pub sampling_interval_seconds: Option<u32>,
+}
+
+impl MeterRegistrationRequest {
+    fn validate(&self) -> std::result::Result<(), String> {
+        if self.meter_id.trim().is_empty() || self.meter_id.len() > MAX_METER_ID_LEN {
+            return Err(format!("meter_id must be 1 to {} characters", MAX_METER_ID_LEN));
+        }
+        if self.building.trim().is_empty() || self.building.len() > MAX_BUILDING_LEN {
+            return Err(format!("building must be 1 to {} bytes", MAX_BUILDING_LEN));
+        }
+        if self.zone.len() > MAX_ZONE_LEN {
+            return Err(format!("zone must be at most {} bytes", MAX_ZONE_LEN));
+        }
+        if !Meter::TYPES.contains(&self.meter_type.as_str()) {
+            return Err(format!(
+                "meter_type must be solar, wind, battery or grid, not {}",
+                self.meter_type
+            ));
+        }
+        if !self.capacity_kw.is_finite() || self.capacity_kw <= 0.0 {
+            return Err("capacity_kw must be a positive number".to_string());
+        }
+        if !(1..=MAX_SAMPLING_INTERVAL).contains(&self.sampling_interval()) {
+            return Err(format!(
+                "sampling_interval_seconds must be between 1 and {}",
+                MAX_SAMPLING_INTERVAL
+            ));
+        }
+        Ok(())
+    }
+
+    fn sampling_interval(&self) -> u32 {
+        self.sampling_interval_seconds.unwrap_or(900)
+    }
+}
+
+/// New meter signing key, as its base58 seed and public key
+fn generate_key() -> (String, String) {
+    let mut seed = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut seed);
+    let public_key = SigningKey::from_bytes(&seed).verifying_key();
+    (
+        bs58::encode(seed).into_string(),
+        bs58::encode(public_key.to_bytes()).into_string(),
+    )
+}
+
+/// Provisions meters: registers them with the gateway's reading validation and, through the
+/// transaction queue, the registry program, and issues and revokes their signing keys
+#[derive(Clone)]
+pub struct MeterProvisioning {
+    db: PgPool,
+    clock: SharedClock,
+}
+
+impl MeterProvisioning {
+    pub fn new(db: PgPool, clock: SharedClock) -> Self {
+        Self { db, clock }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone(), state.clock.clone())
+    }
+
+    pub async fn list(&self, status: Option<&str>, limit: i64) -> Result<Vec<Meter>> {
+        let query = format!(
+            "{} WHERE $1::varchar IS NULL OR m.status = $1 ORDER BY m.meter_id LIMIT $2",
+            METER_SELECT
+        );
+        Ok(sqlx::query_as::<_, Meter>(&query)
+            .bind(status)
+            .bind(limit.clamp(1, MAX_METER_LIMIT))
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    pub async fn get(&self, meter_id: &str) -> Result<Meter> {
+        let query = format!("{} WHERE m.meter_id = $1", METER_SELECT);
+        sqlx::query_as::<_, Meter>(&query)
+            .bind(meter_id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Meter {} is not provisioned", meter_id)))
+    }
+
+    /// Register a meter, assign it to its user and issue its first signing key
+    pub async fn register(&self, request: MeterRegistrationRequest, registered_by: Uuid) -> Result<MeterCredentials> {
+        request.validate().map_err(ApiError::BadRequest)?;
+        let meter_id = request.meter_id.trim().to_string();
+
+        let mut tx = self.db.begin().await?;
+        let owner = lock_trading_wallet(&mut tx, request.user_id).await?;
+
+        let inserted = sqlx::query(
+            "INSERT INTO meters (meter_id, user_id, meter_type, building, zone, sampling_interval_seconds, registered_by)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT (meter_id) DO NOTHING",
+        )
+        .bind(&meter_id)
+        .bind(request.user_id)
+        .bind(&request.meter_type)
+        .bind(&request.building)
+        .bind(&request.zone)
+        .bind(request.sampling_interval() as i32)
+        .bind(registered_by)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if inserted == 0 {
+            return Err(ApiError::Conflict(format!("Meter {} is already provisioned", meter_id)));
+        }
+
+        let assignee: Option<Uuid> =
+            sqlx::query_scalar("SELECT user_id FROM meter_assignments WHERE meter_id = $1 AND is_active")
+                .bind(&meter_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        match assignee {
+            Some(user_id) if user_id != request.user_id => {
+                return Err(ApiError::Conflict(format!("Meter {} is assigned to another user", meter_id)));
+            }
+            Some(_) => {}
+            None => {
+                sqlx::query("INSERT INTO meter_assignments (user_id, meter_id, building) VALUES ($1, $2, $3)")
+                    .bind(request.user_id)
+                    .bind(&meter_id)
+                    .bind(&request.building)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        sqlx::query(
+            "INSERT INTO meter_inverters (meter_id, capacity_kw) VALUES ($1, $2)
+             ON CONFLICT (meter_id) DO UPDATE SET capacity_kw = EXCLUDED.capacity_kw",
+        )
+        .bind(&meter_id)
+        .bind(request.capacity_kw)
+        .execute(&mut *tx)
+        .await?;
+
+        let (secret_key, public_key) = generate_key();
+        store_key(&mut tx, &meter_id, &public_key, registered_by).await?;
+        let operation = TxOperation::RegisterMeter {
+            meter_id: meter_id.clone(),
+            owner,
+            meter_type: request.meter_type,
+            building: request.building,
+            zone: request.zone,
+            meter_pubkey: public_key,
+        };
+        queue_registry_job(&mut tx, &meter_id, &operation, registered_by).await?;
+        tx.commit().await?;
+
+        tracing::info!("Provisioned meter {}", meter_id);
+        Ok(MeterCredentials {
+            secret_key,
+            meter: self.get(&meter_id).await?,
+        })
+    }
+
+    /// Issue a meter a new signing key, revoking the previous one
+    pub async fn rotate_credentials(&self, meter_id: &str, issued_by: Uuid) -> Result<MeterCredentials> {
+        let mut tx = self.db.begin().await?;
+        lock_active(&mut tx, meter_id).await?;
+
+        let (secret_key, public_key) = generate_key();
+        store_key(&mut tx, meter_id, &public_key, issued_by).await?;
+        let operation = TxOperation::RotateMeterKey {
+            meter_id: meter_id.to_string(),
+            meter_pubkey: public_key,
+        };
+        queue_registry_job(&mut tx, meter_id, &operation, issued_by).await?;
+        tx.commit().await?;
+
+        tracing::info!("Rotated signing key of meter {}", meter_id);
+        Ok(MeterCredentials {
+            secret_key,
+            meter: self.get(meter_id).await?,
+        })
+    }
+
+    /// Retire a meter for good: revoke its key, end its assignment and stop polling it
+    pub async fn decommission(&self, meter_id: &str, decommissioned_by: Uuid) -> Result<Meter> {
+        let mut tx = self.db.begin().await?;
+        lock_active(&mut tx, meter_id).await?;
+        let now = self.clock.now();
+
+        sqlx::query("UPDATE meters SET status = $2, decommissioned_at = $3 WHERE meter_id = $1")
+            .bind(meter_id)
+            .bind(Meter::DECOMMISSIONED)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM meter_signing_keys WHERE meter_id = $1")
+            .bind(meter_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE meter_assignments SET is_active = FALSE, deactivated_at = $2 WHERE meter_id = $1 AND is_active")
+            .bind(meter_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM meter_polling_configs WHERE meter_id = $1")
+            .bind(meter_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let operation = TxOperation::DecommissionMeter {
+            meter_id: meter_id.to_string(),
+        };
+        queue_registry_job(&mut tx, meter_id, &operation, decommissioned_by).await?;
+        tx.commit().await?;
+
+        tracing::info!("Decommissioned meter {}", meter_id);
+        self.get(meter_id).await
+    }
+}
+
+/// Lock an active meter whose previous registry transaction has completed, as later
+/// transactions depend on its registry account
+async fn lock_active(tx: &mut Transaction<'_, Postgres>, meter_id: &str) -> Result<()> {
+    let meter: Option<(String, Option<String>)> = sqlx::query_as(
+        "SELECT m.status, j.status FROM meters m
+         LEFT JOIN tx_jobs j ON j.id = m.registry_job_id
+         WHERE m.meter_id = $1
+         FOR UPDATE OF m",
+    )
+    .bind(meter_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    match meter {
+        None => Err(ApiError::NotFound(format!("Meter {} is not provisioned", meter_id))),
+        Some((status, _)) if status == Meter::DECOMMISSIONED => {
+            Err(ApiError::Conflict(format!("Meter {} has been decommissioned", meter_id)))
+        }
+        Some((_, Some(job))) if job == TxJob::QUEUED || job == TxJob::SUBMITTED => Err(ApiError::Conflict(format!(
+            "Meter {}'s registry transaction is still pending",
+            meter_id
+        ))),
+        Some(_) => Ok(()),
+    }
+}
+
+async fn store_key(tx: &mut Transaction<'_, Postgres>, meter_id: &str, public_key: &str, by: Uuid) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO meter_signing_keys (meter_id, public_key, registered_by) VALUES ($1, $2, $3)
+         ON CONFLICT (meter_id) DO UPDATE SET
+             public_key = EXCLUDED.public_key, registered_by = EXCLUDED.registered_by",
+    )
+    .bind(meter_id)
+    .bind(public_key)
+    .bind(by)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn queue_registry_job(
+    tx: &mut Transaction<'_, Postgres>,
+    meter_id: &str,
+    operation: &TxOperation,
+    by: Uuid,
+) -> Result<()> {
+    let job = enqueue_with(&mut **tx, operation, Some(by)).await?;
+    sqlx::query("UPDATE meters SET registry_job_id = $2 WHERE meter_id = $1")
+        .bind(meter_id)
+        .bind(job.id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "chain")]
+    use crate::services::meter_registry::parse_meter_type;
+    use ed25519_dalek::VerifyingKey;
+
+    fn request() -> MeterRegistrationRequest {
+        MeterRegistrationRequest {
+            meter_id: "MTR-1".to_string(),
+            user_id: Uuid::nil(),
+            meter_type: "solar".to_string(),
+            building: "Engineering 4".to_string(),
+            zone: "north".to_string(),
+            capacity_kw: 5.0,
+            sampling_interval_seconds: None,
+        }
+    }
+
+    #[test]
+    fn test_registration_validation() {
+        assert!(request().validate().is_ok());
+        assert_eq!(request().sampling_interval(), 900);
+        for invalid in [
+            MeterRegistrationRequest {
+                meter_id: "MTR-000000000000000001".to_string(),
+                ..request()
+            },
+            MeterRegistrationRequest {
+                building: " ".to_string(),
+                ..request()
+            },
+            MeterRegistrationRequest {
+                zone: "z".repeat(33),
+                ..request()
+            },
+            MeterRegistrationRequest {
+                meter_type: "nuclear".to_string(),
+                ..request()
+            },
+            MeterRegistrationRequest {
+                capacity_kw: 0.0,
+                ..request()
+            },
+            MeterRegistrationRequest {
+                sampling_interval_seconds: Some(0),
+                ..request()
+            },
+        ] {
+            assert!(invalid.validate().is_err(), "{:?} should be invalid", invalid);
+        }
+        // Every gateway meter type is one the registry program knows
+        #[cfg(feature = "chain")]
+        for meter_type in Meter::TYPES {
+            assert!(parse_meter_type(meter_type).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_generated_key_pairs_match() {
+        let (secret_key, public_key) = generate_key();
+        let seed: [u8; 32] = bs58::decode(&secret_key).into_vec().unwrap().try_into().unwrap();
+        let public_key: [u8; 32] = bs58::decode(&public_key).into_vec().unwrap().try_into().unwrap();
+        assert_eq!(
+            SigningKey::from_bytes(&seed).verifying_key(),
+            VerifyingKey::from_bytes(&public_key).unwrap()
+        );
+        assert_ne!(generate_key().0, secret_key);
+    }
+}
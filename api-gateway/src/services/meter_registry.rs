@@ -0,0 +1,183 @@
+use std::str::FromStr;
+
+use registry::{MeterStatus, MeterType};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ApiError, Result};
+use crate::services::blockchain::BlockchainService;
+use crate::services::fee_payers::{sign_transaction, FeePayerPool};
+use crate::services::gateway_signer::GatewaySigner;
+use crate::services::transaction::{anchor_instruction, serialize_transaction, Instruction, Message, Pubkey};
+use crate::AppState;
+
+/// Registry program meter type from its gateway name
+pub fn parse_meter_type(name: &str) -> Result<MeterType> {
+    match name {
+        "solar" => Ok(MeterType::Solar),
+        "wind" => Ok(MeterType::Wind),
+        "battery" => Ok(MeterType::Battery),
+        "grid" => Ok(MeterType::Grid),
+        other => Err(ApiError::BadRequest(format!(
+            "Meter type must be solar, wind, battery or grid, not {}",
+            other
+        ))),
+    }
+}
+
+/// Meter to register through the registry program's `register_meter`
+#[derive(Clone)]
+pub struct RegisterMeterParams {
+    pub meter_id: String,
+    pub owner: Pubkey,
+    pub meter_type: MeterType,
+    pub building: String,
+    pub zone: String,
+    pub meter_pubkey: Pubkey,
+}
+
+/// Outcome of a registry program transaction, stored as its transaction job's result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeterTransaction {
+    pub meter_id: String,
+    /// Meter PDA in the registry program
+    pub meter_address: String,
+    pub signature: String,
+}
+
+/// Registers, rotates the keys of and decommissions meters as the registry authority
+#[derive(Clone)]
+pub struct MeterRegistrar {
+    chain: BlockchainService,
+    program_id: Pubkey,
+    signer: GatewaySigner,
+    fee_payers: FeePayerPool,
+}
+
+impl MeterRegistrar {
+    pub fn new(chain: BlockchainService, program_id: Pubkey, signer: GatewaySigner, fee_payers: FeePayerPool) -> Self {
+        Self {
+            chain,
+            program_id,
+            signer,
+            fee_payers,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let program_id = Pubkey::from_str(&state.config.registry_program_id)
+            .map_err(|e| ApiError::Configuration(format!("Invalid REGISTRY_PROGRAM_ID: {}", e)))?;
+
+        Ok(Self::new(
+            state.blockchain_service.clone(),
+            program_id,
+            state.signer.clone(),
+            state.fee_payers.clone(),
+        ))
+    }
+
+    fn address(&self, seeds: &[&[u8]]) -> Result<Pubkey> {
+        Pubkey::find_program_address(seeds, &self.program_id)
+            .map(|(address, _)| address)
+            .ok_or_else(|| ApiError::Internal("No program address for seeds".to_string()))
+    }
+
+    pub fn meter_address(&self, meter_id: &str) -> Result<Pubkey> {
+        self.address(&[b"meter", meter_id.as_bytes()])
+    }
+
+    fn register_instruction(&self, authority: Pubkey, params: RegisterMeterParams) -> Result<Instruction> {
+        let accounts = registry::accounts::RegisterMeter {
+            registry: self.address(&[b"registry"])?.into(),
+            user_account: self.address(&[b"user", &params.owner.0])?.into(),
+            meter_account: self.meter_address(&params.meter_id)?.into(),
+            authority: authority.into(),
+            system_program: anchor_lang::system_program::ID,
+        };
+        Ok(anchor_instruction(
+            self.program_id,
+            accounts,
+            registry::instruction::RegisterMeter {
+                meter_id: params.meter_id,
+                meter_type: params.meter_type,
+                building: params.building,
+                zone: params.zone,
+                meter_pubkey: params.meter_pubkey.into(),
+            },
+        ))
+    }
+
+    fn rotate_key_instruction(&self, authority: Pubkey, meter_id: &str, meter_pubkey: Pubkey) -> Result<Instruction> {
+        let accounts = registry::accounts::RotateMeterKey {
+            registry: self.address(&[b"registry"])?.into(),
+            meter_account: self.meter_address(meter_id)?.into(),
+            authority: authority.into(),
+        };
+        Ok(anchor_instruction(
+            self.program_id,
+            accounts,
+            registry::instruction::RotateMeterKey {
+                meter_pubkey: meter_pubkey.into(),
+            },
+        ))
+    }
+
+    fn decommission_instruction(&self, authority: Pubkey, meter_id: &str) -> Result<Instruction> {
+        let accounts = registry::accounts::UpdateMeterStatus {
+            registry: self.address(&[b"registry"])?.into(),
+            meter_account: self.meter_address(meter_id)?.into(),
+            authority: authority.into(),
+        };
+        Ok(anchor_instruction(
+            self.program_id,
+            accounts,
+            registry::instruction::UpdateMeterStatus {
+                new_status: MeterStatus::Decommissioned,
+            },
+        ))
+    }
+
+    /// Submit `register_meter` for a newly provisioned meter
+    pub async fn register(&self, params: RegisterMeterParams) -> Result<MeterTransaction> {
+        let meter_id = params.meter_id.clone();
+        self.submit(&meter_id, |authority| self.register_instruction(authority, params))
+            .await
+    }
+
+    /// Submit `rotate_meter_key` for a meter's newly issued key
+    pub async fn rotate_key(&self, meter_id: &str, meter_pubkey: Pubkey) -> Result<MeterTransaction> {
+        self.submit(meter_id, |authority| self.rotate_key_instruction(authority, meter_id, meter_pubkey))
+            .await
+    }
+
+    /// Submit `update_meter_status` retiring a meter for good
+    pub async fn decommission(&self, meter_id: &str) -> Result<MeterTransaction> {
+        self.submit(meter_id, |authority| self.decommission_instruction(authority, meter_id))
+            .await
+    }
+
+    /// Sign the instruction built for the leased authority, paid for by the next fee payer in
+    /// the pool or, without one, by the authority
+    async fn submit(
+        &self,
+        meter_id: &str,
+        instruction: impl FnOnce(Pubkey) -> Result<Instruction>,
+    ) -> Result<MeterTransaction> {
+        let authority = self.signer.lease().await?;
+        let fee_payer = self.fee_payers.next();
+        let instructions = [instruction(authority.pubkey())?];
+
+        let blockhash = self.chain.recent_blockhash().await?;
+        let payer_key = fee_payer.as_ref().map_or_else(|| authority.pubkey(), |payer| payer.pubkey());
+        let message = Message::new(&instructions, payer_key, blockhash).map_err(ApiError::Internal)?;
+        let bytes = message.serialize();
+        let signatures = sign_transaction(message.signers(), &bytes, authority.as_ref(), fee_payer.as_deref()).await?;
+        let signature = self.chain.send_transaction(&serialize_transaction(&signatures, &bytes)).await?;
+
+        tracing::info!("Registry transaction for meter {} submitted in {}", meter_id, signature);
+        Ok(MeterTransaction {
+            meter_id: meter_id.to_string(),
+            meter_address: self.meter_address(meter_id)?.to_string(),
+            signature,
+        })
+    }
+}
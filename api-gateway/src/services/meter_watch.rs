@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::models::energy::Meter;
+use crate::services::event_bus::{self, GatewayEvent};
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+#[derive(Debug, sqlx::FromRow)]
+struct OfflineMeter {
+    meter_id: String,
+    user_id: Uuid,
+    last_reading_at: Option<DateTime<Utc>>,
+    offline_since: DateTime<Utc>,
+}
+
+/// Announces active meters that stopped reporting as [`GatewayEvent::MeterOffline`]
+///
+/// A meter is offline once `missed_intervals` of its sampling intervals pass without a
+/// reading. It is announced once per outage: `meters.offline_since` marks it until a newer
+/// reading arrives.
+#[derive(Clone)]
+pub struct MeterWatch {
+    db: PgPool,
+    clock: SharedClock,
+    missed_intervals: i32,
+}
+
+impl MeterWatch {
+    pub fn new(db: PgPool, clock: SharedClock, missed_intervals: i32) -> Self {
+        Self {
+            db,
+            clock,
+            missed_intervals: missed_intervals.max(1),
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(
+            state.db.clone(),
+            state.clock.clone(),
+            state.config.meter_offline_missed_intervals,
+        )
+    }
+
+    /// Clear meters that reported again and announce newly offline ones, returning how many went offline
+    pub async fn sweep(&self) -> Result<usize> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query(
+            "UPDATE meters m SET offline_since = NULL
+             WHERE offline_since IS NOT NULL
+               AND (status <> $1 OR EXISTS (
+                   SELECT 1 FROM energy_readings r WHERE r.meter_id = m.meter_id AND r.timestamp > m.offline_since
+               ))",
+        )
+        .bind(Meter::ACTIVE)
+        .execute(&mut *tx)
+        .await?;
+
+        // Meters that never reported count from their registration
+        let offline = sqlx::query_as::<_, OfflineMeter>(
+            "WITH latest AS (
+                 SELECT m.meter_id,
+                        (SELECT MAX(r.timestamp) FROM energy_readings r WHERE r.meter_id = m.meter_id) AS last_reading_at
+                 FROM meters m
+                 WHERE m.status = $1 AND m.offline_since IS NULL
+             )
+             UPDATE meters m SET offline_since = COALESCE(l.last_reading_at, m.created_at)
+             FROM latest l
+             WHERE m.meter_id = l.meter_id AND m.offline_since IS NULL
+               AND COALESCE(l.last_reading_at, m.created_at)
+                   < $2 - make_interval(secs => m.sampling_interval_seconds::double precision * $3)
+             RETURNING m.meter_id, m.user_id, l.last_reading_at, m.offline_since",
+        )
+        .bind(Meter::ACTIVE)
+        .bind(self.clock.now())
+        .bind(f64::from(self.missed_intervals))
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for meter in &offline {
+            let event = GatewayEvent::MeterOffline {
+                meter_id: meter.meter_id.clone(),
+                user_id: meter.user_id,
+                last_reading_at: meter.last_reading_at,
+                offline_since: meter.offline_since,
+            };
+            event_bus::publish(&mut *tx, &event).await?;
+        }
+
+        tx.commit().await?;
+        Ok(offline.len())
+    }
+
+    /// Sweep every `interval`
+    pub fn spawn(self, interval: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                match self.sweep().await {
+                    Ok(0) => {}
+                    Ok(offline) => tracing::warn!("{} meters went offline", offline),
+                    Err(e) => tracing::error!("Offline meter sweep failed: {}", e),
+                }
+            }
+        });
+    }
+}
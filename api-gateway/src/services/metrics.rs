@@ -0,0 +1,204 @@
+use std::time::{Duration, Instant};
+
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+use sqlx::PgPool;
+
+use crate::database::redis_pool::RedisPool;
+use crate::database::schema::types::OrderStatus;
+use crate::error::Result;
+use crate::models::signing::SigningSession;
+use crate::models::tx_job::TxJob;
+
+/// Latency buckets in seconds for HTTP handlers and Solana RPC calls
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Install the global Prometheus recorder that every `metrics::` macro reports to
+pub fn install_recorder() -> std::result::Result<PrometheusHandle, BuildError> {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_duration_seconds".to_string()), LATENCY_BUCKETS)?
+        .install_recorder()
+}
+
+/// Count a read of one of the Redis caches, for hit rate alerts
+pub fn record_cache_lookup(cache: &'static str, hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    metrics::counter!("cache_lookups_total", "cache" => cache, "result" => result).increment(1);
+}
+
+/// Record how long a caller waited for a pooled connection
+pub fn record_pool_wait(pool: &'static str, waited: Duration) {
+    metrics::histogram!("db_pool_acquire_duration_seconds", "pool" => pool).record(waited.as_secs_f64());
+}
+
+/// Count a caller refused because the pool had no free connection within its acquire timeout
+pub fn record_pool_timeout(pool: &'static str) {
+    metrics::counter!("db_pool_timeouts_total", "pool" => pool).increment(1);
+}
+
+/// Connections of one pool, sampled into `db_pool_connections` gauges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolUsage {
+    pub pool: &'static str,
+    pub in_use: u32,
+    pub idle: u32,
+    pub max: u32,
+}
+
+impl PoolUsage {
+    pub fn postgres(pool: &'static str, db: &PgPool) -> Self {
+        let idle = db.num_idle() as u32;
+        Self {
+            pool,
+            in_use: db.size().saturating_sub(idle),
+            idle,
+            max: db.options().get_max_connections(),
+        }
+    }
+
+    pub fn redis(redis: &RedisPool) -> Self {
+        let in_use = redis.in_use() as u32;
+        Self {
+            pool: "redis",
+            in_use,
+            idle: redis.size() as u32 - in_use,
+            max: redis.size() as u32,
+        }
+    }
+
+    pub fn publish(&self) {
+        metrics::gauge!("db_pool_connections", "pool" => self.pool, "state" => "in_use").set(f64::from(self.in_use));
+        metrics::gauge!("db_pool_connections", "pool" => self.pool, "state" => "idle").set(f64::from(self.idle));
+        metrics::gauge!("db_pool_max_connections", "pool" => self.pool).set(f64::from(self.max));
+    }
+
+    /// Publish pool usage every `interval`
+    ///
+    /// Queries take connections inside sqlx, so Postgres wait times come from a probe checkout
+    /// each sample; Redis checkouts record their own.
+    pub fn spawn(postgres: Vec<(&'static str, PgPool)>, redis: RedisPool, interval: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                for (pool, db) in &postgres {
+                    let started = Instant::now();
+                    match db.acquire().await {
+                        Ok(_) => record_pool_wait(pool, started.elapsed()),
+                        Err(sqlx::Error::PoolTimedOut) => record_pool_timeout(pool),
+                        Err(e) => tracing::warn!("{} pool probe failed: {}", pool, e),
+                    }
+                    Self::postgres(pool, db).publish();
+                }
+                Self::redis(&redis).publish();
+            }
+        });
+    }
+}
+
+/// Work waiting in Postgres, sampled into `queue_depth` gauges
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueueDepths {
+    pub pending_transactions: i64,
+    pub collecting_signing_sessions: i64,
+    pub submitting_signing_sessions: i64,
+    pub open_orders: i64,
+    pub queued_tx_jobs: i64,
+}
+
+impl QueueDepths {
+    pub async fn sample(db: &PgPool) -> Result<Self> {
+        let (pending_transactions, collecting, submitting, open_orders, queued_tx_jobs): (i64, i64, i64, i64, i64) =
+            sqlx::query_as(
+                "SELECT
+                    (SELECT COUNT(*) FROM blockchain_transactions WHERE status = 'pending'),
+                    (SELECT COUNT(*) FROM signing_sessions WHERE status = $1),
+                    (SELECT COUNT(*) FROM signing_sessions WHERE status = $2),
+                    (SELECT COUNT(*) FROM trading_orders WHERE status IN ($3, $4)),
+                    (SELECT COUNT(*) FROM tx_jobs WHERE status = $5)",
+            )
+            .bind(SigningSession::COLLECTING)
+            .bind(SigningSession::SUBMITTING)
+            .bind(OrderStatus::Pending)
+            .bind(OrderStatus::Active)
+            .bind(TxJob::QUEUED)
+            .fetch_one(db)
+            .await?;
+
+        Ok(Self {
+            pending_transactions,
+            collecting_signing_sessions: collecting,
+            submitting_signing_sessions: submitting,
+            open_orders,
+            queued_tx_jobs,
+        })
+    }
+
+    fn gauges(&self) -> [(&'static str, i64); 5] {
+        [
+            ("pending_transactions", self.pending_transactions),
+            ("collecting_signing_sessions", self.collecting_signing_sessions),
+            ("submitting_signing_sessions", self.submitting_signing_sessions),
+            ("open_orders", self.open_orders),
+            ("queued_tx_jobs", self.queued_tx_jobs),
+        ]
+    }
+
+    pub fn publish(&self) {
+        for (queue, depth) in self.gauges() {
+            metrics::gauge!("queue_depth", "queue" => queue).set(depth as f64);
+        }
+    }
+
+    /// Sample and publish queue depths every `interval`
+    pub fn spawn(db: PgPool, interval: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                match Self::sample(&db).await {
+                    Ok(depths) => depths.publish(),
+                    Err(e) => tracing::error!("Queue depth sampling failed: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_histograms_render_buckets() {
+        let recorder = PrometheusBuilder::new()
+            .set_buckets_for_metric(Matcher::Suffix("_duration_seconds".to_string()), LATENCY_BUCKETS)
+            .unwrap()
+            .build_recorder();
+        let handle = recorder.handle();
+
+        metrics::with_local_recorder(&recorder, || {
+            metrics::histogram!("solana_rpc_duration_seconds", "method" => "getSlot").record(0.02);
+            record_cache_lookup("chain", true);
+            QueueDepths {
+                open_orders: 3,
+                ..Default::default()
+            }
+            .publish();
+            record_pool_wait("postgres", Duration::from_millis(3));
+            PoolUsage {
+                pool: "postgres",
+                in_use: 4,
+                idle: 1,
+                max: 50,
+            }
+            .publish();
+        });
+
+        let rendered = handle.render();
+        assert!(rendered.contains(r#"solana_rpc_duration_seconds_bucket{method="getSlot",le="0.025"} 1"#));
+        assert!(rendered.contains(r#"cache_lookups_total{cache="chain",result="hit"} 1"#));
+        assert!(rendered.contains(r#"queue_depth{queue="open_orders"} 3"#));
+        assert!(rendered.contains(r#"db_pool_acquire_duration_seconds_bucket{pool="postgres",le="0.005"} 1"#));
+        assert!(rendered.contains(r#"db_pool_connections{pool="postgres",state="in_use"} 4"#));
+    }
+}
@@ -1,2 +1,74 @@
 // Business logic services
-// Authentication, blockchain client, trading engine, etc.
\ No newline at end of file
+// Authentication, blockchain client, trading engine, etc.
+
+pub mod audit_log;
+pub mod backfill;
+#[cfg(feature = "payments")]
+pub mod billing;
+pub mod billing_export;
+pub mod blockchain;
+pub mod carbon;
+pub mod certificates;
+pub mod chain_cache;
+pub mod chain_client;
+#[cfg(feature = "payments")]
+pub mod channels;
+pub mod circuit_breaker;
+pub mod dashboard;
+#[cfg(feature = "chain")]
+pub mod disputes;
+pub mod dlms;
+pub mod erc_auto_issuance;
+pub mod erc_events;
+#[cfg(feature = "chain")]
+pub mod erc_issuance;
+#[cfg(feature = "chain")]
+pub mod erc_verification;
+pub mod event_bus;
+pub mod fee_payers;
+pub mod fees;
+pub mod forecast;
+pub mod gateway_signer;
+#[cfg(feature = "chain")]
+pub mod governance_admin;
+pub mod idempotency;
+pub mod identities;
+#[cfg(feature = "chain")]
+pub mod market_clearing;
+pub mod market_feed;
+#[cfg(feature = "chain")]
+pub mod matching;
+pub mod meter_keys;
+pub mod meter_polling;
+pub mod meter_provisioning;
+#[cfg(feature = "chain")]
+pub mod meter_registry;
+pub mod meter_watch;
+pub mod metrics;
+pub mod modbus;
+#[cfg(feature = "mqtt")]
+pub mod mqtt_readings;
+pub mod notifications;
+pub mod order_book;
+#[cfg(feature = "chain")]
+pub mod price_oracle;
+pub mod pricing;
+pub mod program_errors;
+pub mod program_logs;
+pub mod prosumers;
+pub mod reading_import;
+pub mod readings;
+pub mod reports;
+pub mod scheduler;
+#[cfg(feature = "chain")]
+pub mod settlement;
+pub mod signing;
+pub mod simulation;
+pub mod tenants;
+pub mod timeseries;
+pub mod transaction;
+pub mod tx_queue;
+pub mod tx_signer;
+#[cfg(feature = "chain")]
+pub mod tx_worker;
+pub mod webhooks;
@@ -0,0 +1,170 @@
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::error::{ApiError, Result};
+
+const READ_HOLDING_REGISTERS: u8 = 0x03;
+
+/// MBAP header: transaction id, protocol id, length and unit id
+const HEADER_LEN: usize = 7;
+
+/// Longest PDU a Modbus frame carries
+const MAX_PDU_LEN: usize = 253;
+
+fn exception_name(code: u8) -> &'static str {
+    match code {
+        0x01 => "illegal function",
+        0x02 => "illegal data address",
+        0x03 => "illegal data value",
+        0x04 => "server device failure",
+        0x06 => "server device busy",
+        0x0A => "gateway path unavailable",
+        0x0B => "gateway target device failed to respond",
+        _ => "unknown exception",
+    }
+}
+
+/// `Read Holding Registers` request for `count` registers from `address`
+fn read_request(transaction_id: u16, unit_id: u8, address: u16, count: u16) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(HEADER_LEN + 5);
+    frame.extend_from_slice(&transaction_id.to_be_bytes());
+    frame.extend_from_slice(&0u16.to_be_bytes());
+    frame.extend_from_slice(&6u16.to_be_bytes());
+    frame.push(unit_id);
+    frame.push(READ_HOLDING_REGISTERS);
+    frame.extend_from_slice(&address.to_be_bytes());
+    frame.extend_from_slice(&count.to_be_bytes());
+    frame
+}
+
+/// Register values from the response frame to a `read_request`
+fn parse_response(frame: &[u8], transaction_id: u16, unit_id: u8, count: u16) -> Result<Vec<u16>> {
+    let invalid = |reason: &str| ApiError::ExternalService(format!("Invalid Modbus response: {}", reason));
+    if frame.len() < HEADER_LEN + 2 {
+        return Err(invalid("frame too short"));
+    }
+    if frame[0..2] != transaction_id.to_be_bytes() || frame[6] != unit_id {
+        return Err(invalid("transaction or unit id mismatch"));
+    }
+
+    let pdu = &frame[HEADER_LEN..];
+    if pdu[0] == READ_HOLDING_REGISTERS | 0x80 {
+        return Err(ApiError::ExternalService(format!(
+            "Modbus exception {:#04x}: {}",
+            pdu[1],
+            exception_name(pdu[1])
+        )));
+    }
+    if pdu[0] != READ_HOLDING_REGISTERS {
+        return Err(invalid("unexpected function code"));
+    }
+
+    let data = &pdu[2..];
+    if usize::from(pdu[1]) != usize::from(count) * 2 || data.len() != usize::from(count) * 2 {
+        return Err(invalid("register count mismatch"));
+    }
+    Ok(data
+        .chunks_exact(2)
+        .map(|word| u16::from_be_bytes([word[0], word[1]]))
+        .collect())
+}
+
+/// Modbus-TCP client reading a meter's energy counters
+pub struct ModbusClient {
+    stream: TcpStream,
+    unit_id: u8,
+    next_transaction: u16,
+    timeout: Duration,
+}
+
+impl ModbusClient {
+    pub async fn connect(host: &str, port: u16, unit_id: u8, timeout: Duration) -> Result<Self> {
+        let stream = tokio::time::timeout(timeout, TcpStream::connect((host, port)))
+            .await
+            .map_err(|_| ApiError::ExternalService(format!("Modbus connection to {}:{} timed out", host, port)))?
+            .map_err(|e| ApiError::ExternalService(format!("Modbus connection to {}:{} failed: {}", host, port, e)))?;
+        Ok(Self {
+            stream,
+            unit_id,
+            next_transaction: 0,
+            timeout,
+        })
+    }
+
+    pub async fn read_holding_registers(&mut self, address: u16, count: u16) -> Result<Vec<u16>> {
+        self.next_transaction = self.next_transaction.wrapping_add(1);
+        let transaction_id = self.next_transaction;
+        let request = read_request(transaction_id, self.unit_id, address, count);
+
+        let frame = tokio::time::timeout(self.timeout, async {
+            self.stream.write_all(&request).await?;
+            let mut frame = vec![0; HEADER_LEN];
+            self.stream.read_exact(&mut frame).await?;
+            // The length field counts the unit id and the PDU
+            let pdu_len = usize::from(u16::from_be_bytes([frame[4], frame[5]])).saturating_sub(1);
+            if pdu_len == 0 || pdu_len > MAX_PDU_LEN {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "bad MBAP length"));
+            }
+            frame.resize(HEADER_LEN + pdu_len, 0);
+            self.stream.read_exact(&mut frame[HEADER_LEN..]).await?;
+            Ok(frame)
+        })
+        .await
+        .map_err(|_| ApiError::ExternalService("Modbus request timed out".to_string()))?
+        .map_err(|e| ApiError::ExternalService(format!("Modbus request failed: {}", e)))?;
+
+        parse_response(&frame, transaction_id, self.unit_id, count)
+    }
+
+    /// Unsigned 32-bit counter held in two registers, high word first
+    pub async fn read_u32(&mut self, address: u16) -> Result<u32> {
+        let words = self.read_holding_registers(address, 2).await?;
+        Ok(u32::from(words[0]) << 16 | u32::from(words[1]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request_frame() {
+        assert_eq!(
+            read_request(0x0102, 1, 0x006B, 2),
+            [0x01, 0x02, 0x00, 0x00, 0x00, 0x06, 0x01, 0x03, 0x00, 0x6B, 0x00, 0x02]
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let frame = [0x01, 0x02, 0x00, 0x00, 0x00, 0x07, 0x01, 0x03, 0x04, 0x00, 0x01, 0x86, 0xA0];
+        assert_eq!(parse_response(&frame, 0x0102, 1, 2).unwrap(), [0x0001, 0x86A0]);
+
+        assert!(parse_response(&frame, 0x0103, 1, 2).is_err());
+        assert!(parse_response(&frame, 0x0102, 2, 2).is_err());
+        assert!(parse_response(&frame, 0x0102, 1, 3).is_err());
+
+        let exception = [0x01, 0x02, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02];
+        let error = parse_response(&exception, 0x0102, 1, 2).unwrap_err();
+        assert!(error.to_string().contains("illegal data address"));
+    }
+
+    #[tokio::test]
+    async fn test_reads_counter_from_server() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 12];
+            socket.read_exact(&mut request).await.unwrap();
+            let mut response = request[..4].to_vec();
+            response.extend_from_slice(&[0x00, 0x07, request[6], 0x03, 0x04, 0x00, 0x01, 0x86, 0xA0]);
+            socket.write_all(&response).await.unwrap();
+        });
+
+        let mut client = ModbusClient::connect("127.0.0.1", port, 5, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(client.read_u32(0x0010).await.unwrap(), 100_000);
+    }
+}
@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use rumqttc::{AsyncClient, Event, EventLoop, MqttOptions, Packet, Publish, QoS};
+use validator::Validate;
+
+use crate::error::{ApiError, Result};
+use crate::models::energy::EnergyReadingSubmission;
+use crate::services::readings::ReadingStore;
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+/// Wait before polling the broker connection again after it failed
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Meter id a reading was published for: the level of `topic` under the single `+` of `filter`
+fn topic_meter_id<'a>(filter: &str, topic: &'a str) -> Option<&'a str> {
+    let filter: Vec<&str> = filter.split('/').collect();
+    let levels: Vec<&str> = topic.split('/').collect();
+    if filter.len() != levels.len() || filter.iter().filter(|level| **level == "+").count() != 1 {
+        return None;
+    }
+
+    let mut meter_id = None;
+    for (expected, level) in filter.iter().zip(&levels) {
+        match *expected {
+            "+" if !level.is_empty() => meter_id = Some(*level),
+            expected if expected == *level => {}
+            _ => return None,
+        }
+    }
+    meter_id
+}
+
+/// Takes signed readings meters publish to an MQTT broker, as if pushed to the API
+///
+/// The payload is an [`EnergyReadingSubmission`] for the meter named in the topic, checked
+/// against the meter's key like any other pushed reading.
+#[derive(Clone)]
+pub struct MqttReadingSubscriber {
+    readings: ReadingStore,
+    clock: SharedClock,
+    broker_url: String,
+    topic: String,
+}
+
+impl MqttReadingSubscriber {
+    pub fn new(readings: ReadingStore, clock: SharedClock, broker_url: String, topic: String) -> Self {
+        Self {
+            readings,
+            clock,
+            broker_url,
+            topic,
+        }
+    }
+
+    /// `None` when no broker is configured
+    pub fn from_state(state: &AppState) -> Option<Self> {
+        let broker_url = state.config.mqtt_broker_url.clone()?;
+        Some(Self::new(
+            ReadingStore::from_state(state),
+            state.clock.clone(),
+            broker_url,
+            state.config.mqtt_reading_topic.clone(),
+        ))
+    }
+
+    /// Record the reading in `publish`, returning the meter it was for
+    async fn handle(&self, publish: &Publish) -> Result<String> {
+        let meter_id = topic_meter_id(&self.topic, &publish.topic)
+            .ok_or_else(|| ApiError::BadRequest(format!("Topic {} names no meter", publish.topic)))?;
+        let reading: EnergyReadingSubmission = serde_json::from_slice(&publish.payload)
+            .map_err(|e| ApiError::BadRequest(format!("Invalid reading payload: {}", e)))?;
+        reading
+            .validate()
+            .map_err(|e| ApiError::BadRequest(format!("Invalid reading: {}", e)))?;
+        if reading.meter_id != meter_id {
+            return Err(ApiError::BadRequest(format!(
+                "Reading for meter {} published on the topic of meter {}",
+                reading.meter_id, meter_id
+            )));
+        }
+
+        self.readings.submit(&reading, self.clock.now()).await?;
+        Ok(reading.meter_id)
+    }
+
+    async fn run(self, client: AsyncClient, mut events: EventLoop) {
+        loop {
+            match events.poll().await {
+                // Subscribe on every connection, as the broker may not have kept the session
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    if let Err(e) = client.subscribe(self.topic.clone(), QoS::AtLeastOnce).await {
+                        tracing::error!("Failed to subscribe to {}: {}", self.topic, e);
+                    }
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    let outcome = match self.handle(&publish).await {
+                        Ok(meter_id) => {
+                            tracing::debug!("Recorded MQTT reading of meter {}", meter_id);
+                            "success"
+                        }
+                        Err(e) => {
+                            tracing::warn!("Rejected MQTT reading on {}: {}", publish.topic, e);
+                            "failure"
+                        }
+                    };
+                    metrics::counter!("mqtt_readings_total", "outcome" => outcome).increment(1);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!("MQTT broker connection failed: {}", e);
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    }
+
+    pub fn spawn(self) -> Result<()> {
+        let options = MqttOptions::parse_url(self.broker_url.as_str())
+            .map_err(|e| ApiError::Configuration(format!("Invalid MQTT_BROKER_URL: {}", e)))?;
+        let (client, events) = AsyncClient::new(options, 64);
+        tokio::spawn(self.run(client, events));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_meter_id() {
+        assert_eq!(topic_meter_id("meters/+/readings", "meters/MTR-7/readings"), Some("MTR-7"));
+        assert_eq!(topic_meter_id("campus/+/energy", "campus/MTR-7/energy"), Some("MTR-7"));
+        assert_eq!(topic_meter_id("meters/+/readings", "meters/MTR-7/status"), None);
+        assert_eq!(topic_meter_id("meters/+/readings", "meters//readings"), None);
+        assert_eq!(topic_meter_id("meters/+/readings", "meters/a/b/readings"), None);
+        assert_eq!(topic_meter_id("meters/readings", "meters/readings"), None);
+    }
+}
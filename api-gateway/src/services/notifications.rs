@@ -0,0 +1,656 @@
+use std::time::Duration;
+
+use lettre::address::Address;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::database::queue;
+use crate::database::schema::types::OrderSide;
+use crate::error::{ApiError, Result};
+use crate::models::notification::{Notification, NotificationPreference, NotificationPreferenceRequest};
+use crate::services::event_bus::GatewayEvent;
+use crate::utils::backoff;
+use crate::utils::clock::SharedClock;
+use crate::utils::html;
+use crate::AppState;
+
+/// Email handed to the notification relay
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailNotification<'a> {
+    pub to: &'a [String],
+    pub subject: &'a str,
+    pub html: &'a str,
+}
+
+/// Delivers notifications through the configured HTTP relay
+///
+/// The relay (e.g. the campus mail gateway) accepts JSON emails; without one configured,
+/// notifications are only logged.
+#[derive(Debug, Clone)]
+pub struct NotificationService {
+    http: reqwest::Client,
+    webhook_url: Option<String>,
+}
+
+impl NotificationService {
+    pub fn new(webhook_url: Option<String>) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| ApiError::Configuration(format!("Failed to build notification client: {}", e)))?;
+
+        Ok(Self { http, webhook_url })
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.webhook_url.is_some()
+    }
+
+    pub async fn send_email(&self, email: &EmailNotification<'_>) -> Result<()> {
+        let Some(url) = &self.webhook_url else {
+            tracing::info!(
+                "No notification relay configured; not sending \"{}\" to {}",
+                email.subject,
+                email.to.join(", ")
+            );
+            return Ok(());
+        };
+
+        let response = self
+            .http
+            .post(url)
+            .json(email)
+            .send()
+            .await
+            .map_err(|e| ApiError::ExternalService(format!("Notification relay request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(ApiError::ExternalService(format!(
+                "Notification relay returned {}",
+                response.status()
+            )));
+        }
+
+        tracing::info!("Sent \"{}\" to {}", email.subject, email.to.join(", "));
+        Ok(())
+    }
+}
+
+pub const PREFERENCE_COLUMNS: &str = "user_id, channel, enabled, destination, event_types, created_at, updated_at";
+pub const NOTIFICATION_COLUMNS: &str = "id, user_id, event_type, event_key, channel, subject, body, status, attempts, \
+    last_error, next_attempt_at, sent_at, created_at, updated_at";
+
+/// Alerts claimed per dispatcher pass
+const BATCH_SIZE: i64 = 50;
+/// Time a claimed alert is hidden from other dispatchers while it is sent
+const CLAIM_LEASE: Duration = Duration::from_secs(60);
+/// Time SMTP and LINE Notify have to accept an alert
+const SEND_TIMEOUT: Duration = Duration::from_secs(15);
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(30);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(3600);
+/// Characters of a failure kept with an alert
+const MAX_ERROR_LEN: usize = 512;
+const MAX_DESTINATION_LEN: usize = 255;
+/// Most alerts returned by one history request
+pub const MAX_HISTORY_LIMIT: i64 = 500;
+
+/// Subject and plain-text body of the alert for `event`
+fn render(event: &GatewayEvent) -> (String, String) {
+    match event {
+        GatewayEvent::OrderFilled {
+            order_id,
+            side,
+            energy_amount,
+            filled_at,
+            ..
+        } => {
+            let side = match side {
+                OrderSide::Buy => "buy",
+                OrderSide::Sell => "sell",
+            };
+            (
+                format!("Your {} order was filled", side),
+                format!(
+                    "Your {} order {} for {} kWh was filled at {}.",
+                    side,
+                    order_id,
+                    energy_amount.normalize(),
+                    filled_at.format("%Y-%m-%d %H:%M UTC")
+                ),
+            )
+        }
+        GatewayEvent::CertificateIssued {
+            certificate_id,
+            energy_amount,
+            issued_at,
+            ..
+        } => (
+            format!("Certificate {} issued", certificate_id),
+            format!(
+                "Renewable energy certificate {} for {} kWh of your generation was issued at {}.",
+                certificate_id,
+                energy_amount,
+                issued_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+        ),
+        GatewayEvent::MeterOffline {
+            meter_id,
+            last_reading_at,
+            ..
+        } => (
+            format!("Meter {} is offline", meter_id),
+            match last_reading_at {
+                Some(at) => format!(
+                    "Meter {} has not reported since {}. Check its power and network connection.",
+                    meter_id,
+                    at.format("%Y-%m-%d %H:%M UTC")
+                ),
+                None => format!(
+                    "Meter {} has not reported since it was registered. Check its power and network connection.",
+                    meter_id
+                ),
+            },
+        ),
+        GatewayEvent::SettlementReady {
+            statement_id,
+            net_amount,
+            ..
+        } => (
+            "Your settlement statement is ready".to_string(),
+            format!(
+                "Settlement statement {} is ready, with a net amount of {} THB.",
+                statement_id,
+                net_amount.normalize()
+            ),
+        ),
+    }
+}
+
+fn validate_preference(request: &NotificationPreferenceRequest) -> Result<()> {
+    if !NotificationPreference::CHANNELS.contains(&request.channel.as_str()) {
+        return Err(ApiError::Validation(format!(
+            "Unknown channel {}; expected one of {}",
+            request.channel,
+            NotificationPreference::CHANNELS.join(", ")
+        )));
+    }
+    if let Some(destination) = &request.destination {
+        if destination.trim().is_empty() || destination.len() > MAX_DESTINATION_LEN {
+            return Err(ApiError::Validation(format!(
+                "Destination must be 1 to {} characters",
+                MAX_DESTINATION_LEN
+            )));
+        }
+        if request.channel == NotificationPreference::EMAIL && destination.trim().parse::<Address>().is_err() {
+            return Err(ApiError::Validation(format!("Invalid email address {}", destination)));
+        }
+    }
+    if let Some(event_types) = &request.event_types {
+        if let Some(unknown) = event_types
+            .iter()
+            .find(|event_type| !GatewayEvent::EVENT_TYPES.contains(&event_type.as_str()))
+        {
+            return Err(ApiError::Validation(format!(
+                "Unknown event type {}; expected one of {}",
+                unknown,
+                GatewayEvent::EVENT_TYPES.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// LINE Notify tokens are secrets; only their last characters are shown back
+fn masked(mut preference: NotificationPreference) -> NotificationPreference {
+    if preference.channel == NotificationPreference::LINE {
+        preference.destination = preference.destination.map(|token| {
+            let tail: String = token.chars().rev().take(4).collect::<Vec<_>>().into_iter().rev().collect();
+            format!("****{}", tail)
+        });
+    }
+    preference
+}
+
+/// Users' alert preferences and the alerts queued for them
+#[derive(Clone)]
+pub struct NotificationStore {
+    db: PgPool,
+}
+
+impl NotificationStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone())
+    }
+
+    /// Channels the user configured; without an email row, alerts go to the account's address
+    pub async fn preferences(&self, user_id: Uuid) -> Result<Vec<NotificationPreference>> {
+        let query = format!(
+            "SELECT {} FROM notification_preferences WHERE user_id = $1 ORDER BY channel",
+            PREFERENCE_COLUMNS
+        );
+        let preferences = sqlx::query_as::<_, NotificationPreference>(&query)
+            .bind(user_id)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(preferences.into_iter().map(masked).collect())
+    }
+
+    /// Create or replace the user's preference for a channel
+    ///
+    /// An email preference without a destination uses the account's address; a LINE
+    /// preference without one keeps the token it already has.
+    pub async fn set_preference(
+        &self,
+        user_id: Uuid,
+        request: &NotificationPreferenceRequest,
+    ) -> Result<NotificationPreference> {
+        validate_preference(request)?;
+        let destination = request.destination.as_deref().map(str::trim);
+        let event_types = request
+            .event_types
+            .clone()
+            .unwrap_or_else(|| GatewayEvent::EVENT_TYPES.iter().map(|e| e.to_string()).collect());
+
+        if request.channel == NotificationPreference::LINE && destination.is_none() {
+            let configured: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM notification_preferences WHERE user_id = $1 AND channel = $2)",
+            )
+            .bind(user_id)
+            .bind(NotificationPreference::LINE)
+            .fetch_one(&self.db)
+            .await?;
+            if !configured {
+                return Err(ApiError::Validation("A LINE Notify token is required".to_string()));
+            }
+        }
+
+        let query = format!(
+            "INSERT INTO notification_preferences (user_id, channel, enabled, destination, event_types)
+             VALUES ($1, $2, $3, CASE WHEN $2 = $6 THEN COALESCE($4, (
+                 SELECT destination FROM notification_preferences WHERE user_id = $1 AND channel = $2
+             )) ELSE $4 END, $5)
+             ON CONFLICT (user_id, channel) DO UPDATE SET
+                 enabled = EXCLUDED.enabled,
+                 destination = EXCLUDED.destination,
+                 event_types = EXCLUDED.event_types
+             RETURNING {}",
+            PREFERENCE_COLUMNS
+        );
+        let preference = sqlx::query_as::<_, NotificationPreference>(&query)
+            .bind(user_id)
+            .bind(&request.channel)
+            .bind(request.enabled)
+            .bind(destination)
+            .bind(&event_types)
+            .bind(NotificationPreference::LINE)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(masked(preference))
+    }
+
+    /// The user's alerts with their delivery status, newest first
+    pub async fn history(&self, user_id: Uuid, status: Option<&str>, limit: i64) -> Result<Vec<Notification>> {
+        let query = format!(
+            "SELECT {} FROM notifications
+             WHERE user_id = $1 AND ($2::text IS NULL OR status = $2)
+             ORDER BY created_at DESC
+             LIMIT $3",
+            NOTIFICATION_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, Notification>(&query)
+            .bind(user_id)
+            .bind(status)
+            .bind(limit.clamp(1, MAX_HISTORY_LIMIT))
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Queue an alert about `event` on each of its user's channels that wants it, returning how many were queued
+    ///
+    /// Every replica hears every event; the event key lets only the first queue it.
+    pub async fn queue(&self, event: &GatewayEvent) -> Result<u64> {
+        let (subject, body) = render(event);
+        Ok(sqlx::query(
+            "INSERT INTO notifications (user_id, event_type, event_key, channel, subject, body)
+             SELECT u.id, $2, $3, c.channel, $4, $5
+             FROM users u
+             CROSS JOIN (VALUES ($6), ($7)) AS c(channel)
+             LEFT JOIN notification_preferences p ON p.user_id = u.id AND p.channel = c.channel
+             WHERE u.id = $1 AND u.is_active
+               AND CASE WHEN p.user_id IS NULL THEN c.channel = $6 AND u.email IS NOT NULL
+                        ELSE p.enabled AND $2 = ANY(p.event_types) AND COALESCE(p.destination, u.email) IS NOT NULL
+                   END
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(event.user_id())
+        .bind(event.event_type())
+        .bind(event.key())
+        .bind(subject)
+        .bind(body)
+        .bind(NotificationPreference::EMAIL)
+        .bind(NotificationPreference::LINE)
+        .execute(&self.db)
+        .await?
+        .rows_affected())
+    }
+
+    /// Queue alerts for gateway events as they arrive
+    pub fn spawn_events(self, mut events: broadcast::Receiver<GatewayEvent>) {
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!("Notification queue skipped {} gateway events", missed);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if let Err(e) = self.queue(&event).await {
+                    tracing::error!("Failed to queue notifications for {}: {}", event.key(), e);
+                }
+            }
+        });
+    }
+}
+
+/// Sends queued alerts by email and LINE Notify, retrying failures with backoff
+pub struct NotificationDispatcher {
+    db: PgPool,
+    clock: SharedClock,
+    http: reqwest::Client,
+    /// Falls back to the notification relay when no SMTP server is configured
+    smtp: Option<AsyncSmtpTransport<Tokio1Executor>>,
+    from: Mailbox,
+    relay: NotificationService,
+    line_notify_url: String,
+    max_attempts: i32,
+}
+
+impl NotificationDispatcher {
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let config = &state.config;
+        let smtp = config
+            .smtp_url
+            .as_deref()
+            .map(|url| {
+                AsyncSmtpTransport::<Tokio1Executor>::from_url(url)
+                    .map(|builder| builder.timeout(Some(SEND_TIMEOUT)).build())
+                    .map_err(|e| ApiError::Configuration(format!("Invalid SMTP_URL: {}", e)))
+            })
+            .transpose()?;
+        let from = config
+            .smtp_from
+            .parse::<Mailbox>()
+            .map_err(|e| ApiError::Configuration(format!("Invalid SMTP_FROM: {}", e)))?;
+        let http = reqwest::Client::builder()
+            .timeout(SEND_TIMEOUT)
+            .build()
+            .map_err(|e| ApiError::Configuration(format!("Failed to build LINE Notify client: {}", e)))?;
+
+        Ok(Self {
+            db: state.db.clone(),
+            clock: state.clock.clone(),
+            http,
+            smtp,
+            from,
+            relay: NotificationService::new(config.notification_webhook_url.clone())?,
+            line_notify_url: config.line_notify_url.clone(),
+            max_attempts: config.notification_max_attempts.max(1),
+        })
+    }
+
+    /// Send every due alert, returning how many were claimed
+    pub async fn process_due(&self) -> Result<usize> {
+        let notifications: Vec<Notification> = queue::claim_due(
+            &self.db,
+            "notifications",
+            NOTIFICATION_COLUMNS,
+            &[Notification::PENDING],
+            CLAIM_LEASE,
+            BATCH_SIZE,
+        )
+        .await?;
+
+        let count = notifications.len();
+        futures::future::join_all(notifications.iter().map(|notification| async move {
+            // The lease expires and the alert is picked up again
+            if let Err(e) = self.deliver(notification).await {
+                tracing::error!("Notification {} could not be recorded: {}", notification.id, e);
+            }
+        }))
+        .await;
+        Ok(count)
+    }
+
+    async fn deliver(&self, notification: &Notification) -> Result<()> {
+        let destination: Option<String> = sqlx::query_scalar(
+            "SELECT COALESCE(p.destination, u.email) FROM users u
+             LEFT JOIN notification_preferences p ON p.user_id = u.id AND p.channel = $2
+             WHERE u.id = $1",
+        )
+        .bind(notification.user_id)
+        .bind(&notification.channel)
+        .fetch_optional(&self.db)
+        .await?
+        .flatten();
+
+        let attempts = notification.attempts + 1;
+        let (outcome, permanent) = match &destination {
+            Some(destination) => (self.send(notification, destination).await, false),
+            None => (Err("No destination for this channel".to_string()), true),
+        };
+
+        let Err(error) = outcome else {
+            metrics::counter!(
+                "notifications_total",
+                "channel" => notification.channel.clone(),
+                "outcome" => "sent"
+            )
+            .increment(1);
+            sqlx::query(
+                "UPDATE notifications SET status = $2, attempts = $3, last_error = NULL, sent_at = $4 WHERE id = $1",
+            )
+            .bind(notification.id)
+            .bind(Notification::SENT)
+            .bind(attempts)
+            .bind(self.clock.now())
+            .execute(&self.db)
+            .await?;
+            return Ok(());
+        };
+        let error: String = error.chars().take(MAX_ERROR_LEN).collect();
+
+        if !permanent && attempts < self.max_attempts {
+            let delay = backoff::retry_delay(attempts, INITIAL_RETRY_DELAY, MAX_RETRY_DELAY);
+            tracing::warn!(
+                "Notification {} by {} attempt {} failed, retrying in {}s: {}",
+                notification.id,
+                notification.channel,
+                attempts,
+                delay.as_secs(),
+                error
+            );
+            sqlx::query(
+                "UPDATE notifications SET attempts = $2, last_error = $3,
+                    next_attempt_at = NOW() + make_interval(secs => $4)
+                 WHERE id = $1",
+            )
+            .bind(notification.id)
+            .bind(attempts)
+            .bind(&error)
+            .bind(delay.as_secs_f64())
+            .execute(&self.db)
+            .await?;
+            return Ok(());
+        }
+
+        metrics::counter!(
+            "notifications_total",
+            "channel" => notification.channel.clone(),
+            "outcome" => "failed"
+        )
+        .increment(1);
+        tracing::error!(
+            "Notification {} by {} failed after {} attempts: {}",
+            notification.id,
+            notification.channel,
+            attempts,
+            error
+        );
+        sqlx::query("UPDATE notifications SET status = $2, attempts = $3, last_error = $4 WHERE id = $1")
+            .bind(notification.id)
+            .bind(Notification::FAILED)
+            .bind(attempts)
+            .bind(&error)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn send(&self, notification: &Notification, destination: &str) -> std::result::Result<(), String> {
+        if notification.channel == NotificationPreference::LINE {
+            return self.send_line(notification, destination).await;
+        }
+
+        let Some(smtp) = &self.smtp else {
+            if !self.relay.is_configured() {
+                return Err("No SMTP server or notification relay configured".to_string());
+            }
+            let html = format!("<p>{}</p>", html::escape(&notification.body));
+            let email = EmailNotification {
+                to: &[destination.to_string()],
+                subject: &notification.subject,
+                html: &html,
+            };
+            return self.relay.send_email(&email).await.map_err(|e| e.to_string());
+        };
+
+        let to = destination
+            .parse::<Mailbox>()
+            .map_err(|e| format!("Invalid email address {}: {}", destination, e))?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(&notification.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(notification.body.clone())
+            .map_err(|e| format!("Failed to build email: {}", e))?;
+        smtp.send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("SMTP delivery failed: {}", e))
+    }
+
+    async fn send_line(&self, notification: &Notification, token: &str) -> std::result::Result<(), String> {
+        let message = format!("\n{}\n{}", notification.subject, notification.body);
+        let response = self
+            .http
+            .post(&self.line_notify_url)
+            .bearer_auth(token)
+            .form(&[("message", message)])
+            .send()
+            .await
+            .map_err(|e| format!("LINE Notify request failed: {}", e))?;
+
+        if response.status().is_success() {
+            return Ok(());
+        }
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        Err(format!("LINE Notify returned {}: {}", status, text))
+    }
+
+    /// Send due alerts every `interval`
+    pub fn spawn(self, interval: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.process_due().await {
+                    tracing::error!("Notification dispatcher pass failed: {}", e);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn request(channel: &str, destination: Option<&str>, event_types: Option<&[&str]>) -> NotificationPreferenceRequest {
+        NotificationPreferenceRequest {
+            channel: channel.to_string(),
+            enabled: true,
+            destination: destination.map(str::to_string),
+            event_types: event_types.map(|types| types.iter().map(|e| e.to_string()).collect()),
+        }
+    }
+
+    #[test]
+    fn test_alerts_render_per_event() {
+        let filled_at = Utc.with_ymd_and_hms(2024, 9, 23, 12, 30, 0).unwrap();
+        let (subject, body) = render(&GatewayEvent::OrderFilled {
+            order_id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            side: OrderSide::Sell,
+            energy_amount: Decimal::from_str("12.50000000").unwrap(),
+            filled_at,
+        });
+        assert_eq!(subject, "Your sell order was filled");
+        assert!(body.contains("12.5 kWh"));
+        assert!(body.contains("2024-09-23 12:30 UTC"));
+
+        let (subject, body) = render(&GatewayEvent::MeterOffline {
+            meter_id: "MTR-1".to_string(),
+            user_id: Uuid::nil(),
+            last_reading_at: None,
+            offline_since: filled_at,
+        });
+        assert_eq!(subject, "Meter MTR-1 is offline");
+        assert!(body.contains("since it was registered"));
+    }
+
+    #[test]
+    fn test_preference_validation() {
+        assert!(validate_preference(&request("email", None, None)).is_ok());
+        assert!(validate_preference(&request("email", Some("prosumer@utcc.ac.th"), Some(&["meter_offline"]))).is_ok());
+        assert!(validate_preference(&request("line", Some("line-token"), None)).is_ok());
+        assert!(validate_preference(&request("sms", None, None)).is_err());
+        assert!(validate_preference(&request("email", Some("not an address"), None)).is_err());
+        assert!(validate_preference(&request("line", Some("  "), None)).is_err());
+        assert!(validate_preference(&request("email", None, Some(&["OrderFilled"]))).is_err());
+    }
+
+    #[test]
+    fn test_line_tokens_are_masked() {
+        let preference = |channel: &str, destination: &str| NotificationPreference {
+            user_id: Uuid::nil(),
+            channel: channel.to_string(),
+            enabled: true,
+            destination: Some(destination.to_string()),
+            event_types: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        assert_eq!(masked(preference("line", "abcdefgh1234")).destination.as_deref(), Some("****1234"));
+        assert_eq!(
+            masked(preference("email", "prosumer@utcc.ac.th")).destination.as_deref(),
+            Some("prosumer@utcc.ac.th")
+        );
+    }
+}
@@ -0,0 +1,421 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tokio::sync::{broadcast, Mutex};
+use uuid::Uuid;
+
+use crate::database::schema::types::{OrderSide, OrderStatus};
+use crate::error::Result;
+use crate::models::trading::{MarketData, OrderBook, TradingOrder, TradingOrderDb};
+use crate::utils::clock::{SharedClock, SystemClock};
+
+/// Postgres channel the `trading_orders` trigger publishes changed order IDs on
+pub const ORDER_BOOK_CHANNEL: &str = "order_book_changes";
+
+/// Changes buffered per subscriber before slow consumers start missing them
+const CHANGE_CHANNEL_CAPACITY: usize = 1024;
+
+const OPEN_ORDER_COLUMNS: &str = "id, user_id, order_type, side, energy_amount, price_per_kwh, filled_amount, \
+    status, expires_at, created_at, filled_at";
+
+/// Immutable order book published to readers
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrderBookSnapshot {
+    /// Incremented on every published change
+    pub version: u64,
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Highest price first
+    pub buy_orders: Vec<TradingOrder>,
+    /// Lowest price first
+    pub sell_orders: Vec<TradingOrder>,
+}
+
+impl OrderBookSnapshot {
+    fn from_orders(orders: Vec<TradingOrder>, version: u64, now: DateTime<Utc>) -> Self {
+        let mut snapshot = OrderBookSnapshot {
+            version,
+            updated_at: Some(now),
+            ..Default::default()
+        };
+
+        for order in orders.into_iter().filter(is_open) {
+            snapshot.side_mut(&order.side).push(order);
+        }
+        snapshot.sort();
+
+        snapshot
+    }
+
+    /// Copy of this snapshot with `order` inserted, replaced, or removed when no longer open
+    fn with_order(&self, order: TradingOrder, now: DateTime<Utc>) -> Self {
+        let mut next = self.without_order(order.id, now);
+        if is_open(&order) {
+            next.side_mut(&order.side).push(order);
+            next.sort();
+        }
+        next
+    }
+
+    fn without_order(&self, order_id: Uuid, now: DateTime<Utc>) -> Self {
+        OrderBookSnapshot {
+            version: self.version + 1,
+            updated_at: Some(now),
+            buy_orders: self.buy_orders.iter().filter(|o| o.id != order_id).cloned().collect(),
+            sell_orders: self.sell_orders.iter().filter(|o| o.id != order_id).cloned().collect(),
+        }
+    }
+
+    /// Copy of this snapshot with only the best `depth` orders of each side
+    pub fn top(&self, depth: usize) -> Self {
+        OrderBookSnapshot {
+            version: self.version,
+            updated_at: self.updated_at,
+            buy_orders: self.buy_orders.iter().take(depth).cloned().collect(),
+            sell_orders: self.sell_orders.iter().take(depth).cloned().collect(),
+        }
+    }
+
+    fn side_mut(&mut self, side: &OrderSide) -> &mut Vec<TradingOrder> {
+        match side {
+            OrderSide::Buy => &mut self.buy_orders,
+            OrderSide::Sell => &mut self.sell_orders,
+        }
+    }
+
+    fn sort(&mut self) {
+        // Price-time priority
+        self.buy_orders
+            .sort_by(|a, b| b.price_per_kwh.cmp(&a.price_per_kwh).then(a.created_at.cmp(&b.created_at)));
+        self.sell_orders
+            .sort_by(|a, b| a.price_per_kwh.cmp(&b.price_per_kwh).then(a.created_at.cmp(&b.created_at)));
+    }
+
+    /// Order contents, ignoring version and publish time
+    fn fingerprint(&self) -> Vec<(Uuid, String, String, String)> {
+        self.buy_orders
+            .iter()
+            .chain(self.sell_orders.iter())
+            .map(|o| {
+                (
+                    o.id,
+                    o.energy_amount.normalize().to_string(),
+                    o.filled_amount.normalize().to_string(),
+                    o.price_per_kwh.normalize().to_string(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Change published with each new order book snapshot
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum OrderBookChange {
+    /// An order was added to the book or updated while still open
+    Upsert { version: u64, order: TradingOrder },
+    /// An order left the book: filled, cancelled or expired
+    Remove { version: u64, order_id: Uuid },
+    /// The book was rebuilt from the database; subscribers should reload the snapshot
+    Reset { version: u64 },
+}
+
+fn is_open(order: &TradingOrder) -> bool {
+    matches!(order.status, OrderStatus::Pending | OrderStatus::Active) && order.filled_amount < order.energy_amount
+}
+
+/// In-memory mirror of open trading orders
+///
+/// Readers load the current snapshot without locking; writers serialize on a mutex and
+/// publish a new snapshot per change, announcing it to `subscribe`rs.
+pub struct OrderBookMirror {
+    snapshot: ArcSwap<OrderBookSnapshot>,
+    writer: Mutex<()>,
+    changes: broadcast::Sender<OrderBookChange>,
+    clock: SharedClock,
+}
+
+impl Default for OrderBookMirror {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderBookMirror {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock::shared())
+    }
+
+    /// Mirror stamping published snapshots with `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
+        let (changes, _) = broadcast::channel(CHANGE_CHANNEL_CAPACITY);
+        Self {
+            snapshot: ArcSwap::from_pointee(OrderBookSnapshot::default()),
+            writer: Mutex::new(()),
+            changes,
+            clock,
+        }
+    }
+
+    /// Changes published after this call, in version order
+    pub fn subscribe(&self) -> broadcast::Receiver<OrderBookChange> {
+        self.changes.subscribe()
+    }
+
+    fn publish(&self, next: OrderBookSnapshot, change: OrderBookChange) {
+        self.snapshot.store(Arc::new(next));
+        // No receivers is fine; nobody is streaming the book
+        let _ = self.changes.send(change);
+    }
+
+    /// Current order book, lock-free
+    pub fn snapshot(&self) -> Arc<OrderBookSnapshot> {
+        self.snapshot.load_full()
+    }
+
+    /// Current epoch and order book, as served by the REST and gRPC market endpoints
+    pub fn market_data(&self, now: DateTime<Utc>) -> MarketData {
+        // Get current epoch information (for now, use simple hour-based epochs)
+        let current_epoch = (now.timestamp() / 3600) as u64; // 1-hour epochs
+        let epoch_start = DateTime::from_timestamp(current_epoch as i64 * 3600, 0).unwrap();
+        let epoch_end = epoch_start + chrono::Duration::hours(1);
+
+        // Trade data arrives in Phase 4
+        let snapshot = self.snapshot();
+        MarketData {
+            current_epoch,
+            epoch_start_time: epoch_start,
+            epoch_end_time: epoch_end,
+            status: "active".to_string(),
+            order_book_version: snapshot.version,
+            order_book: OrderBook {
+                sell_orders: snapshot.sell_orders.clone(),
+                buy_orders: snapshot.buy_orders.clone(),
+            },
+            recent_trades: vec![],
+        }
+    }
+
+    /// Re-read a changed order from the database and publish it
+    pub async fn refresh_order(&self, db: &PgPool, order_id: Uuid) -> Result<()> {
+        let _guard = self.writer.lock().await;
+
+        let query = format!("SELECT {} FROM trading_orders WHERE id = $1", OPEN_ORDER_COLUMNS);
+        let order = sqlx::query_as::<_, TradingOrderDb>(&query)
+            .bind(order_id)
+            .fetch_optional(db)
+            .await?;
+
+        let current = self.snapshot.load();
+        let (next, change) = match order.map(TradingOrder::from) {
+            Some(order) if is_open(&order) => {
+                let next = current.with_order(order.clone(), self.clock.now());
+                let version = next.version;
+                (next, OrderBookChange::Upsert { version, order })
+            }
+            _ => {
+                let next = current.without_order(order_id, self.clock.now());
+                let version = next.version;
+                (next, OrderBookChange::Remove { version, order_id })
+            }
+        };
+        self.publish(next, change);
+
+        Ok(())
+    }
+
+    /// Replace the mirror with the open orders currently in the database
+    pub async fn rebuild(&self, db: &PgPool) -> Result<()> {
+        let _guard = self.writer.lock().await;
+        let rebuilt = load_open_orders(db, self.snapshot.load().version + 1, self.clock.now()).await?;
+        let version = rebuilt.version;
+        self.publish(rebuilt, OrderBookChange::Reset { version });
+        Ok(())
+    }
+
+    /// Compare the mirror with the database and rebuild it on divergence
+    ///
+    /// Returns whether the mirror had diverged.
+    pub async fn check_consistency(&self, db: &PgPool) -> Result<bool> {
+        // Holding the writer lock keeps notifications from interleaving with the comparison
+        let _guard = self.writer.lock().await;
+
+        let current = self.snapshot.load_full();
+        let expected = load_open_orders(db, current.version + 1, self.clock.now()).await?;
+
+        if expected.fingerprint() == current.fingerprint() {
+            return Ok(false);
+        }
+
+        tracing::warn!(
+            "Order book mirror diverged (mirror: {} orders, database: {} orders); rebuilding",
+            current.buy_orders.len() + current.sell_orders.len(),
+            expected.buy_orders.len() + expected.sell_orders.len()
+        );
+        metrics::counter!("order_book_rebuilds_total").increment(1);
+        let version = expected.version;
+        self.publish(expected, OrderBookChange::Reset { version });
+
+        Ok(true)
+    }
+
+    /// Keep the mirror up to date: apply order change notifications and periodically verify it
+    pub fn spawn(self: &Arc<Self>, db: PgPool, check_interval: Duration) {
+        let mirror = Arc::clone(self);
+        let listener_db = db.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = mirror.listen(&listener_db).await {
+                    tracing::error!("Order book listener failed: {}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+
+        let mirror = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check_interval);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = mirror.check_consistency(&db).await {
+                    tracing::error!("Order book consistency check failed: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn listen(&self, db: &PgPool) -> Result<()> {
+        let mut listener = PgListener::connect_with(db).await?;
+        listener.listen(ORDER_BOOK_CHANNEL).await?;
+
+        // Changes made while disconnected were not delivered
+        self.rebuild(db).await?;
+        tracing::info!("Order book mirror listening on {}", ORDER_BOOK_CHANNEL);
+
+        loop {
+            let notification = listener.recv().await?;
+            match Uuid::parse_str(notification.payload()) {
+                Ok(order_id) => self.refresh_order(db, order_id).await?,
+                Err(_) => tracing::warn!("Ignoring malformed order book notification: {}", notification.payload()),
+            }
+        }
+    }
+}
+
+async fn load_open_orders(db: &PgPool, version: u64, now: DateTime<Utc>) -> Result<OrderBookSnapshot> {
+    let query = format!(
+        "SELECT {} FROM trading_orders WHERE status IN ($1, $2)",
+        OPEN_ORDER_COLUMNS
+    );
+    let orders = sqlx::query_as::<_, TradingOrderDb>(&query)
+        .bind(OrderStatus::Pending)
+        .bind(OrderStatus::Active)
+        .fetch_all(db)
+        .await?;
+
+    Ok(OrderBookSnapshot::from_orders(
+        orders.into_iter().map(TradingOrder::from).collect(),
+        version,
+        now,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::types::OrderType;
+    use chrono::TimeZone;
+    use rust_decimal::Decimal;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 9, 23, 12, 0, 0).unwrap()
+    }
+
+    fn order(side: OrderSide, price: i64, age_secs: i64) -> TradingOrder {
+        TradingOrder {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            order_type: OrderType::Limit,
+            side,
+            energy_amount: Decimal::new(10, 0),
+            price_per_kwh: Decimal::new(price, 0),
+            filled_amount: Decimal::ZERO,
+            status: OrderStatus::Active,
+            expires_at: None,
+            created_at: now() - chrono::Duration::seconds(age_secs),
+            filled_at: None,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_uses_price_time_priority() {
+        let older_bid = order(OrderSide::Buy, 5, 60);
+        let newer_bid = order(OrderSide::Buy, 5, 10);
+        let best_bid = order(OrderSide::Buy, 6, 0);
+        let ask = order(OrderSide::Sell, 7, 0);
+        let best_ask = order(OrderSide::Sell, 6, 0);
+
+        let snapshot = OrderBookSnapshot::from_orders(
+            vec![newer_bid.clone(), ask.clone(), older_bid.clone(), best_ask.clone(), best_bid.clone()],
+            1,
+            now(),
+        );
+
+        let bids: Vec<Uuid> = snapshot.buy_orders.iter().map(|o| o.id).collect();
+        let asks: Vec<Uuid> = snapshot.sell_orders.iter().map(|o| o.id).collect();
+        assert_eq!(bids, vec![best_bid.id, older_bid.id, newer_bid.id]);
+        assert_eq!(asks, vec![best_ask.id, ask.id]);
+    }
+
+    #[test]
+    fn test_with_order_replaces_and_removes_closed_orders() {
+        let bid = order(OrderSide::Buy, 5, 0);
+        let snapshot = OrderBookSnapshot::from_orders(vec![bid.clone()], 1, now());
+
+        let mut partially_filled = bid.clone();
+        partially_filled.filled_amount = Decimal::new(4, 0);
+        let later = now() + chrono::Duration::seconds(30);
+        let updated = snapshot.with_order(partially_filled, later);
+        assert_eq!(updated.version, 2);
+        assert_eq!(updated.updated_at, Some(later));
+        assert_eq!(updated.buy_orders.len(), 1);
+        assert_eq!(updated.buy_orders[0].filled_amount, Decimal::new(4, 0));
+
+        let mut filled = bid;
+        filled.status = OrderStatus::Filled;
+        let updated = updated.with_order(filled, later);
+        assert!(updated.buy_orders.is_empty());
+    }
+
+    #[test]
+    fn test_top_keeps_the_best_orders_of_each_side() {
+        let best_bid = order(OrderSide::Buy, 6, 0);
+        let best_ask = order(OrderSide::Sell, 7, 0);
+        let snapshot = OrderBookSnapshot::from_orders(
+            vec![order(OrderSide::Buy, 5, 0), best_bid.clone(), order(OrderSide::Sell, 8, 0), best_ask.clone()],
+            3,
+            now(),
+        );
+
+        let top = snapshot.top(1);
+        assert_eq!(top.version, 3);
+        assert_eq!(top.buy_orders.iter().map(|o| o.id).collect::<Vec<_>>(), vec![best_bid.id]);
+        assert_eq!(top.sell_orders.iter().map(|o| o.id).collect::<Vec<_>>(), vec![best_ask.id]);
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_version() {
+        let orders = vec![order(OrderSide::Buy, 5, 0), order(OrderSide::Sell, 6, 0)];
+        let a = OrderBookSnapshot::from_orders(orders.clone(), 1, now());
+        let b = OrderBookSnapshot::from_orders(orders, 7, now());
+        assert_eq!(a.fingerprint(), b.fingerprint());
+
+        let c = a.without_order(a.buy_orders[0].id, now());
+        assert_ne!(a.fingerprint(), c.fingerprint());
+    }
+}
@@ -0,0 +1,539 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{ApiError, Result};
+use crate::models::pricing::{GridPrice, GridPriceDb, GridPriceIngestion};
+use crate::services::audit_log::sha256_hex;
+use crate::services::blockchain::BlockchainService;
+use crate::services::fee_payers::{sign_transaction, FeePayerPool};
+use crate::services::gateway_signer::GatewaySigner;
+use crate::services::scheduler::{spawn_cron, CronSchedule};
+use crate::services::settlement::{to_base_units, Settler};
+use crate::services::transaction::{
+    anchor_instruction, serialize_transaction, verify_signature, Instruction, Message, Pubkey,
+};
+use crate::services::tx_queue::{enqueue_with, TxOperation};
+use crate::utils::clock::SharedClock;
+use crate::utils::decimal::big_decimal;
+use crate::AppState;
+
+pub const GRID_PRICE_COLUMNS: &str = "time, tariff, price_per_kwh, currency, published_at, snapshot_hash, \
+    payload, signature, public_key, source_url, tx_job_id, ingested_at";
+
+/// Transaction advisory lock taken while queueing a grid price for the oracle program ("gridpric")
+const PUBLISH_LOCK_KEY: i64 = 0x6772_6964_7072_6963;
+
+/// Tolerated skew between the tariff publisher's clock and the gateway's, as the oracle program allows
+const MAX_CLOCK_DRIFT_SECS: i64 = 5 * 60;
+
+/// Most grid prices returned by one history request
+pub const MAX_HISTORY_LIMIT: i64 = 1_000;
+
+/// Snapshot as the tariff source serves it: the payload exactly as signed, and its signature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedSnapshot {
+    /// JSON `TariffSnapshot`
+    pub payload: String,
+    /// Base58 ed25519 signature over the payload bytes
+    pub signature: String,
+}
+
+/// Grid tariff in force from `effective_at`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TariffSnapshot {
+    pub tariff: String,
+    pub price_per_kwh: Decimal,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    pub effective_at: DateTime<Utc>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+fn default_currency() -> String {
+    "THB".to_string()
+}
+
+/// Tariff in `snapshot` once its signature by `public_key` and its contents check out
+pub fn verify_snapshot(snapshot: &SignedSnapshot, public_key: &Pubkey, now: DateTime<Utc>) -> Result<TariffSnapshot> {
+    let signature = bs58::decode(&snapshot.signature)
+        .into_vec()
+        .map_err(|e| ApiError::ExternalService(format!("Tariff snapshot signature is not base58: {}", e)))?;
+    if !verify_signature(public_key, snapshot.payload.as_bytes(), &signature) {
+        return Err(ApiError::ExternalService(
+            "Tariff snapshot is not signed by the configured publisher key".to_string(),
+        ));
+    }
+
+    let tariff: TariffSnapshot = serde_json::from_str(&snapshot.payload)
+        .map_err(|e| ApiError::ExternalService(format!("Invalid tariff snapshot: {}", e)))?;
+    if tariff.tariff.is_empty() || tariff.tariff.len() > 64 || tariff.currency.len() > 8 {
+        return Err(ApiError::ExternalService(
+            "Tariff snapshot names a tariff or currency that is empty or too long".to_string(),
+        ));
+    }
+    if tariff.price_per_kwh <= Decimal::ZERO {
+        return Err(ApiError::ExternalService(format!(
+            "Tariff snapshot price {} is not positive",
+            tariff.price_per_kwh
+        )));
+    }
+    if tariff.effective_at > now + chrono::Duration::seconds(MAX_CLOCK_DRIFT_SECS) {
+        return Err(ApiError::ExternalService(format!(
+            "Tariff snapshot takes effect in the future, at {}",
+            tariff.effective_at
+        )));
+    }
+    Ok(tariff)
+}
+
+/// SHA-256 hex digest as the 32 bytes the oracle program records
+fn digest_bytes(hex: &str) -> Result<[u8; 32]> {
+    let mut bytes = [0u8; 32];
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(ApiError::BadRequest(format!("{} is not a SHA-256 hex digest", hex)));
+    }
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)
+            .map_err(|_| ApiError::BadRequest(format!("{} is not a SHA-256 hex digest", hex)))?;
+    }
+    Ok(bytes)
+}
+
+/// Source of signed tariff snapshots
+#[derive(Clone)]
+struct TariffSource {
+    url: String,
+    public_key: Pubkey,
+}
+
+/// Ingests the official grid tariff from signed snapshots, keeps its history in TimescaleDB
+/// and queues each new price for the oracle program, where market clearing takes it as a bound
+#[derive(Clone)]
+pub struct PriceOracle {
+    db: PgPool,
+    timescale: PgPool,
+    http: reqwest::Client,
+    source: Option<TariffSource>,
+    clock: SharedClock,
+}
+
+impl PriceOracle {
+    pub fn new(
+        db: PgPool,
+        timescale: PgPool,
+        source_url: Option<String>,
+        public_key: Option<&str>,
+        clock: SharedClock,
+    ) -> Result<Self> {
+        let source = match source_url {
+            Some(url) => {
+                let public_key = public_key.ok_or_else(|| {
+                    ApiError::Configuration("PRICE_ORACLE_URL is set without PRICE_ORACLE_PUBLIC_KEY".to_string())
+                })?;
+                let public_key = Pubkey::from_str(public_key)
+                    .map_err(|e| ApiError::Configuration(format!("Invalid PRICE_ORACLE_PUBLIC_KEY: {}", e)))?;
+                Some(TariffSource { url, public_key })
+            }
+            None => None,
+        };
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| ApiError::Configuration(format!("Failed to build price oracle client: {}", e)))?;
+
+        Ok(Self {
+            db,
+            timescale,
+            http,
+            source,
+            clock,
+        })
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        Self::new(
+            state.db.clone(),
+            state.timescale_db.clone(),
+            state.config.price_oracle_url.clone(),
+            state.config.price_oracle_public_key.as_deref(),
+            state.clock.clone(),
+        )
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.source.is_some()
+    }
+
+    /// Most recently effective grid price
+    pub async fn latest(&self) -> Result<Option<GridPrice>> {
+        let query = format!("SELECT {} FROM grid_prices ORDER BY time DESC LIMIT 1", GRID_PRICE_COLUMNS);
+        let price = sqlx::query_as::<_, GridPriceDb>(&query)
+            .fetch_optional(&self.timescale)
+            .await?;
+        Ok(price.map(GridPrice::from))
+    }
+
+    /// Grid prices that took effect in `[from, to)`, newest first
+    pub async fn history(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>, limit: i64) -> Result<Vec<GridPrice>> {
+        let query = format!(
+            "SELECT {} FROM grid_prices
+             WHERE ($1::timestamptz IS NULL OR time >= $1) AND ($2::timestamptz IS NULL OR time < $2)
+             ORDER BY time DESC
+             LIMIT $3",
+            GRID_PRICE_COLUMNS
+        );
+        let prices = sqlx::query_as::<_, GridPriceDb>(&query)
+            .bind(from)
+            .bind(to)
+            .bind(limit.clamp(1, MAX_HISTORY_LIMIT))
+            .fetch_all(&self.timescale)
+            .await?;
+        Ok(prices.into_iter().map(GridPrice::from).collect())
+    }
+
+    /// Fetch the current snapshot, record it if it is new, and queue the latest price for the
+    /// oracle program unless it already is
+    ///
+    /// A snapshot taking effect before the latest recorded one is ignored, so replaying an
+    /// old signed snapshot cannot roll the price back.
+    pub async fn ingest(&self) -> Result<GridPriceIngestion> {
+        let source = self
+            .source
+            .as_ref()
+            .ok_or_else(|| ApiError::Configuration("PRICE_ORACLE_URL is not set".to_string()))?;
+
+        let snapshot = self.fetch(source).await?;
+        let tariff = match verify_snapshot(&snapshot, &source.public_key, self.clock.now()) {
+            Ok(tariff) => tariff,
+            Err(e) => {
+                metrics::counter!("price_oracle_snapshots_total", "outcome" => "rejected").increment(1);
+                return Err(e);
+            }
+        };
+
+        let latest = self.latest().await?;
+        let ingested = match &latest {
+            Some(latest) if tariff.effective_at <= latest.effective_at => {
+                if tariff.effective_at < latest.effective_at {
+                    tracing::warn!(
+                        "Ignoring tariff snapshot effective {} before the latest grid price at {}",
+                        tariff.effective_at,
+                        latest.effective_at
+                    );
+                }
+                false
+            }
+            _ => self.record(source, &snapshot, &tariff).await?,
+        };
+        metrics::counter!("price_oracle_snapshots_total", "outcome" => if ingested { "ingested" } else { "unchanged" })
+            .increment(1);
+
+        let mut latest = self
+            .latest()
+            .await?
+            .ok_or_else(|| ApiError::Internal("No grid price recorded after ingestion".to_string()))?;
+        if latest.tx_job_id.is_none() {
+            latest.tx_job_id = self.publish(&latest).await?;
+        }
+        Ok(GridPriceIngestion { ingested, latest })
+    }
+
+    async fn fetch(&self, source: &TariffSource) -> Result<SignedSnapshot> {
+        let response = self
+            .http
+            .get(&source.url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| ApiError::ExternalService(format!("Tariff source request failed: {}", e)))?;
+        response
+            .json::<SignedSnapshot>()
+            .await
+            .map_err(|e| ApiError::ExternalService(format!("Invalid tariff source response: {}", e)))
+    }
+
+    /// Record a verified snapshot; `false` if it already was
+    async fn record(&self, source: &TariffSource, snapshot: &SignedSnapshot, tariff: &TariffSnapshot) -> Result<bool> {
+        let snapshot_hash = sha256_hex(snapshot.payload.as_bytes());
+        let recorded = sqlx::query(
+            "INSERT INTO grid_prices (
+                time, tariff, price_per_kwh, currency, published_at, snapshot_hash, payload, signature,
+                public_key, source_url
+             ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             ON CONFLICT (snapshot_hash, time) DO NOTHING",
+        )
+        .bind(tariff.effective_at)
+        .bind(&tariff.tariff)
+        .bind(big_decimal(tariff.price_per_kwh))
+        .bind(&tariff.currency)
+        .bind(tariff.published_at)
+        .bind(&snapshot_hash)
+        .bind(&snapshot.payload)
+        .bind(&snapshot.signature)
+        .bind(source.public_key.to_string())
+        .bind(&source.url)
+        .execute(&self.timescale)
+        .await?
+        .rows_affected();
+
+        if recorded > 0 {
+            tracing::info!(
+                "Grid tariff {} at {} {}/kWh effective {}",
+                tariff.tariff,
+                tariff.price_per_kwh,
+                tariff.currency,
+                tariff.effective_at
+            );
+        }
+        Ok(recorded > 0)
+    }
+
+    /// Queue `price` for the oracle program, returning its job, or the job another replica
+    /// queued first
+    ///
+    /// The job only exists once the price records it, as both happen under the lock before
+    /// the queueing transaction commits.
+    async fn publish(&self, price: &GridPrice) -> Result<Option<Uuid>> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(PUBLISH_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+
+        let queued: Option<Uuid> = sqlx::query_scalar(
+            "SELECT tx_job_id FROM grid_prices WHERE snapshot_hash = $1 AND time = $2",
+        )
+        .bind(&price.snapshot_hash)
+        .bind(price.effective_at)
+        .fetch_one(&self.timescale)
+        .await?;
+        if queued.is_some() {
+            return Ok(queued);
+        }
+
+        let operation = TxOperation::PublishGridPrice {
+            price_per_kwh: price.price_per_kwh,
+            effective_at: price.effective_at.timestamp(),
+            snapshot_hash: price.snapshot_hash.clone(),
+        };
+        let job = enqueue_with(&mut *tx, &operation, None).await?;
+        sqlx::query("UPDATE grid_prices SET tx_job_id = $3 WHERE snapshot_hash = $1 AND time = $2")
+            .bind(&price.snapshot_hash)
+            .bind(price.effective_at)
+            .bind(job.id)
+            .execute(&self.timescale)
+            .await?;
+        tx.commit().await?;
+        Ok(Some(job.id))
+    }
+
+    /// Fetch the tariff at every boundary of `schedule`
+    pub fn spawn(self, schedule: CronSchedule) {
+        let clock = self.clock.clone();
+        spawn_cron("price_oracle", schedule, clock, move |_| {
+            let oracle = self.clone();
+            async move { oracle.ingest().await.map(|_| ()) }
+        });
+    }
+}
+
+/// Outcome of an oracle program `submit_grid_price`, stored as its transaction job's result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridPriceTransaction {
+    pub price_per_kwh: Decimal,
+    /// Price as the trading program prices orders: payment base units per energy base unit
+    pub unit_price: u64,
+    pub effective_at: i64,
+    /// Grid price PDA in the oracle program
+    pub grid_price_address: String,
+    pub signature: String,
+}
+
+/// Publishes grid prices to the oracle program as an authorized gateway
+#[derive(Clone)]
+pub struct GridPricePublisher {
+    chain: BlockchainService,
+    program_id: Pubkey,
+    settler: Settler,
+    signer: GatewaySigner,
+    fee_payers: FeePayerPool,
+}
+
+impl GridPricePublisher {
+    pub fn new(
+        chain: BlockchainService,
+        program_id: Pubkey,
+        settler: Settler,
+        signer: GatewaySigner,
+        fee_payers: FeePayerPool,
+    ) -> Self {
+        Self {
+            chain,
+            program_id,
+            settler,
+            signer,
+            fee_payers,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let program_id = Pubkey::from_str(&state.config.oracle_program_id)
+            .map_err(|e| ApiError::Configuration(format!("Invalid ORACLE_PROGRAM_ID: {}", e)))?;
+
+        Ok(Self::new(
+            state.blockchain_service.clone(),
+            program_id,
+            Settler::from_state(state)?,
+            state.signer.clone(),
+            state.fee_payers.clone(),
+        ))
+    }
+
+    fn address(&self, seeds: &[&[u8]]) -> Result<Pubkey> {
+        Pubkey::find_program_address(seeds, &self.program_id)
+            .map(|(address, _)| address)
+            .ok_or_else(|| ApiError::Internal("No program address for seeds".to_string()))
+    }
+
+    fn submit_instruction(&self, authority: Pubkey, unit_price: u64, effective_at: i64, snapshot_hash: [u8; 32]) -> Result<Instruction> {
+        let accounts = oracle::accounts::SubmitGridPrice {
+            oracle_data: self.address(&[b"oracle_data"])?.into(),
+            grid_price: self.address(&[b"grid_price"])?.into(),
+            authority: authority.into(),
+            system_program: anchor_lang::system_program::ID,
+        };
+        Ok(anchor_instruction(
+            self.program_id,
+            accounts,
+            oracle::instruction::SubmitGridPrice {
+                price_per_kwh: unit_price,
+                effective_at,
+                snapshot_hash,
+            },
+        ))
+    }
+
+    /// Submit `submit_grid_price` with `price_per_kwh` in the trading market's units, paid for
+    /// by the next fee payer in the pool or, without one, by the authority
+    pub async fn publish(&self, price_per_kwh: Decimal, effective_at: i64, snapshot_hash: &str) -> Result<GridPriceTransaction> {
+        let snapshot_hash = digest_bytes(snapshot_hash)?;
+        let tokens = self.settler.market_tokens().await?;
+        let unit_price = unit_price(price_per_kwh, tokens.energy_decimals, tokens.payment_decimals)
+            .filter(|unit_price| *unit_price > 0)
+            .ok_or_else(|| {
+                ApiError::BadRequest(format!(
+                    "Grid price {} per kWh is not representable in the trading market's token units",
+                    price_per_kwh
+                ))
+            })?;
+
+        let authority = self.signer.lease().await?;
+        let fee_payer = self.fee_payers.next();
+        let instructions = [self.submit_instruction(authority.pubkey(), unit_price, effective_at, snapshot_hash)?];
+
+        let blockhash = self.chain.recent_blockhash().await?;
+        let payer_key = fee_payer.as_ref().map_or_else(|| authority.pubkey(), |payer| payer.pubkey());
+        let message = Message::new(&instructions, payer_key, blockhash).map_err(ApiError::Internal)?;
+        let bytes = message.serialize();
+        let signatures = sign_transaction(message.signers(), &bytes, authority.as_ref(), fee_payer.as_deref()).await?;
+        let signature = self.chain.send_transaction(&serialize_transaction(&signatures, &bytes)).await?;
+
+        tracing::info!("Grid price {} per kWh published in {}", price_per_kwh, signature);
+        Ok(GridPriceTransaction {
+            price_per_kwh,
+            unit_price,
+            effective_at,
+            grid_price_address: self.address(&[b"grid_price"])?.to_string(),
+            signature,
+        })
+    }
+}
+
+/// `price` per kWh as the trading program prices orders, in payment base units per energy
+/// base unit, rounded down
+pub fn unit_price(price: Decimal, energy_decimals: u8, payment_decimals: u8) -> Option<u64> {
+    let energy_scale = Decimal::from(10u64.checked_pow(energy_decimals as u32)?);
+    to_base_units(price.checked_div(energy_scale)?, payment_decimals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 9, 23, 12, 0, 0).unwrap()
+    }
+
+    fn signed(key: &SigningKey, payload: &str) -> SignedSnapshot {
+        SignedSnapshot {
+            payload: payload.to_string(),
+            signature: bs58::encode(key.sign(payload.as_bytes()).to_bytes()).into_string(),
+        }
+    }
+
+    fn public_key(key: &SigningKey) -> Pubkey {
+        Pubkey(key.verifying_key().to_bytes())
+    }
+
+    const PAYLOAD: &str = r#"{"tariff":"PEA-TOU-1.1.3","price_per_kwh":5.7982,"effective_at":"2024-09-23T02:00:00Z"}"#;
+
+    #[test]
+    fn test_verify_snapshot_accepts_the_publishers_signature() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+
+        let tariff = verify_snapshot(&signed(&key, PAYLOAD), &public_key(&key), now()).unwrap();
+
+        assert_eq!(tariff.tariff, "PEA-TOU-1.1.3");
+        assert_eq!(tariff.price_per_kwh, Decimal::new(57982, 4));
+        assert_eq!(tariff.currency, "THB");
+        assert_eq!(tariff.effective_at, Utc.with_ymd_and_hms(2024, 9, 23, 2, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_verify_snapshot_rejects_other_keys_and_tampered_payloads() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let other = SigningKey::from_bytes(&[8; 32]);
+
+        assert!(verify_snapshot(&signed(&other, PAYLOAD), &public_key(&key), now()).is_err());
+
+        let mut tampered = signed(&key, PAYLOAD);
+        tampered.payload = tampered.payload.replace("5.7982", "0.5");
+        assert!(verify_snapshot(&tampered, &public_key(&key), now()).is_err());
+    }
+
+    #[test]
+    fn test_verify_snapshot_rejects_future_and_non_positive_prices() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+
+        let future = r#"{"tariff":"PEA","price_per_kwh":5.0,"effective_at":"2024-09-23T12:10:00Z"}"#;
+        assert!(verify_snapshot(&signed(&key, future), &public_key(&key), now()).is_err());
+
+        let free = r#"{"tariff":"PEA","price_per_kwh":0,"effective_at":"2024-09-23T02:00:00Z"}"#;
+        assert!(verify_snapshot(&signed(&key, free), &public_key(&key), now()).is_err());
+    }
+
+    #[test]
+    fn test_unit_price_scales_to_base_units() {
+        // 5.7982 THB per kWh in 6-decimal payment tokens per whole, milli- and nano-kWh
+        assert_eq!(unit_price(Decimal::new(57982, 4), 0, 6), Some(5_798_200));
+        assert_eq!(unit_price(Decimal::new(57982, 4), 3, 6), Some(5_798));
+        assert_eq!(unit_price(Decimal::new(57982, 4), 9, 6), Some(0));
+    }
+
+    #[test]
+    fn test_digest_bytes_parses_hex() {
+        let digest = sha256_hex(PAYLOAD.as_bytes());
+        let bytes = digest_bytes(&digest).unwrap();
+        assert_eq!(bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<String>(), digest);
+        assert!(digest_bytes("abc").is_err());
+        assert!(digest_bytes(&"zz".repeat(32)).is_err());
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+use crate::error::{ApiError, Result};
+use crate::models::pricing::{CurrentPrice, TouHoliday, TouPeriod, TouPeriodDb, TouPeriodRequest};
+use crate::utils::clock::SharedClock;
+use crate::utils::decimal::big_decimal;
+use crate::AppState;
+
+pub const PERIOD_COLUMNS: &str = "id, name, weekdays, start_time, end_time, applies_on_holidays, grid_rate, \
+    price_floor, price_ceiling, priority, updated_at";
+
+/// Offset of campus local time (Asia/Bangkok) from UTC; Thailand observes no daylight saving
+const CAMPUS_UTC_OFFSET_SECS: i32 = 7 * 60 * 60;
+
+/// How far ahead the end of the period in force is looked for; every period repeats weekly
+const NEXT_CHANGE_HORIZON_DAYS: i64 = 8;
+
+pub fn campus_offset() -> FixedOffset {
+    FixedOffset::east_opt(CAMPUS_UTC_OFFSET_SECS).expect("offset is within a day")
+}
+
+/// Prices P2P trades may clear at; unbounded by default
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PriceBand {
+    pub floor: Option<Decimal>,
+    pub ceiling: Option<Decimal>,
+}
+
+impl PriceBand {
+    /// A bid below the floor cannot trade at any admissible price
+    pub fn admits_bid(&self, price: Decimal) -> bool {
+        self.floor.is_none_or(|floor| price >= floor)
+    }
+
+    /// An ask above the ceiling cannot trade at any admissible price
+    pub fn admits_ask(&self, price: Decimal) -> bool {
+        self.ceiling.is_none_or(|ceiling| price <= ceiling)
+    }
+
+    pub fn clamp(&self, price: Decimal) -> Decimal {
+        let price = self.floor.map_or(price, |floor| price.max(floor));
+        self.ceiling.map_or(price, |ceiling| price.min(ceiling))
+    }
+}
+
+impl From<&TouPeriod> for PriceBand {
+    fn from(period: &TouPeriod) -> Self {
+        Self {
+            floor: period.price_floor,
+            ceiling: period.price_ceiling,
+        }
+    }
+}
+
+pub fn validate_period(id: &str, request: &TouPeriodRequest) -> Result<()> {
+    if id.is_empty() || id.len() > 32 || !id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        return Err(ApiError::BadRequest(
+            "Period ID must be 1-32 lowercase letters, digits or underscores".to_string(),
+        ));
+    }
+    if request.name.trim().is_empty() || request.name.len() > 255 {
+        return Err(ApiError::BadRequest("Period name must be 1-255 characters".to_string()));
+    }
+    if request.weekdays.is_empty() || request.weekdays.iter().any(|day| !(1..=7).contains(day)) {
+        return Err(ApiError::BadRequest(
+            "Weekdays must be ISO weekdays, 1 = Monday .. 7 = Sunday".to_string(),
+        ));
+    }
+    let rates = [Some(request.grid_rate), request.price_floor, request.price_ceiling];
+    if rates.into_iter().flatten().any(|rate| rate.is_sign_negative()) {
+        return Err(ApiError::BadRequest("Rates and price bounds cannot be negative".to_string()));
+    }
+    if let (Some(floor), Some(ceiling)) = (request.price_floor, request.price_ceiling) {
+        if floor > ceiling {
+            return Err(ApiError::BadRequest("price_floor cannot be above price_ceiling".to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Time-of-use periods and public holidays, resolving which period is in force when
+#[derive(Debug, Clone, Default)]
+pub struct TouSchedule {
+    /// Highest priority first
+    periods: Vec<TouPeriod>,
+    holidays: HashSet<NaiveDate>,
+}
+
+impl TouSchedule {
+    pub fn new(mut periods: Vec<TouPeriod>, holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        periods.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.id.cmp(&b.id)));
+        Self {
+            periods,
+            holidays: holidays.into_iter().collect(),
+        }
+    }
+
+    fn local(at: DateTime<Utc>) -> NaiveDateTime {
+        at.with_timezone(&campus_offset()).naive_local()
+    }
+
+    fn applies_on(&self, period: &TouPeriod, day: NaiveDate) -> bool {
+        period.weekdays.contains(&(day.weekday().number_from_monday() as i16))
+            && (period.applies_on_holidays || !self.holidays.contains(&day))
+    }
+
+    fn covers(&self, period: &TouPeriod, local: NaiveDateTime) -> bool {
+        let (day, time) = (local.date(), local.time());
+        if period.start_time < period.end_time {
+            (period.start_time..period.end_time).contains(&time) && self.applies_on(period, day)
+        } else {
+            // Runs past midnight: the part after midnight belongs to the day the period started
+            (time >= period.start_time && self.applies_on(period, day))
+                || (time < period.end_time && day.pred_opt().is_some_and(|day| self.applies_on(period, day)))
+        }
+    }
+
+    fn period_at_local(&self, local: NaiveDateTime) -> Option<&TouPeriod> {
+        self.periods.iter().find(|period| self.covers(period, local))
+    }
+
+    pub fn period_at(&self, at: DateTime<Utc>) -> Option<&TouPeriod> {
+        self.period_at_local(Self::local(at))
+    }
+
+    pub fn is_holiday(&self, at: DateTime<Utc>) -> bool {
+        self.holidays.contains(&Self::local(at).date())
+    }
+
+    pub fn band_at(&self, at: DateTime<Utc>) -> PriceBand {
+        self.period_at(at).map(PriceBand::from).unwrap_or_default()
+    }
+
+    /// When the period in force at `at` gives way to another, if it does within a week
+    pub fn next_change(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local = Self::local(at);
+        let current = self.period_at_local(local).map(|period| period.id.as_str());
+
+        // Periods only start or end at their own times, or at midnight when the day changes
+        let times: Vec<NaiveTime> = self
+            .periods
+            .iter()
+            .flat_map(|period| [period.start_time, period.end_time])
+            .chain([NaiveTime::MIN])
+            .collect();
+        let mut boundaries: Vec<NaiveDateTime> = (0..=NEXT_CHANGE_HORIZON_DAYS)
+            .map(|days| local.date() + Duration::days(days))
+            .flat_map(|day| times.iter().map(move |time| day.and_time(*time)))
+            .filter(|boundary| *boundary > local)
+            .collect();
+        boundaries.sort();
+        boundaries.dedup();
+
+        boundaries
+            .into_iter()
+            .find(|boundary| self.period_at_local(*boundary).map(|period| period.id.as_str()) != current)
+            .and_then(|boundary| campus_offset().from_local_datetime(&boundary).single())
+            .map(|boundary| boundary.with_timezone(&Utc))
+    }
+}
+
+/// Time-of-use tariff periods bounding the prices the matching engine clears at
+#[derive(Clone)]
+pub struct PricingService {
+    db: PgPool,
+    clock: SharedClock,
+}
+
+impl PricingService {
+    pub fn new(db: PgPool, clock: SharedClock) -> Self {
+        Self { db, clock }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone(), state.clock.clone())
+    }
+
+    pub async fn periods(&self) -> Result<Vec<TouPeriod>> {
+        let query = format!("SELECT {} FROM tou_periods ORDER BY priority DESC, id", PERIOD_COLUMNS);
+        let periods = sqlx::query_as::<_, TouPeriodDb>(&query).fetch_all(&self.db).await?;
+        Ok(periods.into_iter().map(TouPeriod::from).collect())
+    }
+
+    /// Holidays on or after `from`
+    pub async fn holidays(&self, from: NaiveDate) -> Result<Vec<TouHoliday>> {
+        Ok(sqlx::query_as::<_, TouHoliday>("SELECT day, name FROM tou_holidays WHERE day >= $1 ORDER BY day")
+            .bind(from)
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Schedule from `at` onwards, with the holidays that can still affect it
+    pub async fn schedule(&self, at: DateTime<Utc>) -> Result<TouSchedule> {
+        let from = at.with_timezone(&campus_offset()).date_naive() - Duration::days(1);
+        let holidays = self.holidays(from).await?;
+        Ok(TouSchedule::new(self.periods().await?, holidays.into_iter().map(|holiday| holiday.day)))
+    }
+
+    /// Price bounds in force at `at`; unbounded when no period covers it
+    pub async fn band_at(&self, at: DateTime<Utc>) -> Result<PriceBand> {
+        Ok(self.schedule(at).await?.band_at(at))
+    }
+
+    pub async fn current(&self) -> Result<CurrentPrice> {
+        let at = self.clock.now();
+        let schedule = self.schedule(at).await?;
+        let period = schedule
+            .period_at(at)
+            .cloned()
+            .ok_or_else(|| ApiError::NotFound("No time-of-use period is in force".to_string()))?;
+        Ok(CurrentPrice {
+            at,
+            is_holiday: schedule.is_holiday(at),
+            ends_at: schedule.next_change(at),
+            period,
+        })
+    }
+
+    pub async fn set_period(&self, id: &str, request: &TouPeriodRequest) -> Result<TouPeriod> {
+        validate_period(id, request)?;
+        let mut weekdays = request.weekdays.clone();
+        weekdays.sort_unstable();
+        weekdays.dedup();
+
+        let query = format!(
+            "INSERT INTO tou_periods (
+                id, name, weekdays, start_time, end_time, applies_on_holidays, grid_rate,
+                price_floor, price_ceiling, priority
+             ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             ON CONFLICT (id) DO UPDATE SET
+                 name = EXCLUDED.name,
+                 weekdays = EXCLUDED.weekdays,
+                 start_time = EXCLUDED.start_time,
+                 end_time = EXCLUDED.end_time,
+                 applies_on_holidays = EXCLUDED.applies_on_holidays,
+                 grid_rate = EXCLUDED.grid_rate,
+                 price_floor = EXCLUDED.price_floor,
+                 price_ceiling = EXCLUDED.price_ceiling,
+                 priority = EXCLUDED.priority
+             RETURNING {}",
+            PERIOD_COLUMNS
+        );
+        let period = sqlx::query_as::<_, TouPeriodDb>(&query)
+            .bind(id)
+            .bind(request.name.trim())
+            .bind(weekdays)
+            .bind(request.start_time)
+            .bind(request.end_time)
+            .bind(request.applies_on_holidays)
+            .bind(big_decimal(request.grid_rate))
+            .bind(request.price_floor.map(big_decimal))
+            .bind(request.price_ceiling.map(big_decimal))
+            .bind(request.priority)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(period.into())
+    }
+
+    pub async fn delete_period(&self, id: &str) -> Result<()> {
+        let deleted = sqlx::query("DELETE FROM tou_periods WHERE id = $1")
+            .bind(id)
+            .execute(&self.db)
+            .await?
+            .rows_affected();
+        if deleted == 0 {
+            return Err(ApiError::NotFound(format!("Time-of-use period {} not found", id)));
+        }
+        Ok(())
+    }
+
+    pub async fn set_holiday(&self, day: NaiveDate, name: &str) -> Result<TouHoliday> {
+        if name.trim().is_empty() || name.len() > 255 {
+            return Err(ApiError::BadRequest("Holiday name must be 1-255 characters".to_string()));
+        }
+        Ok(sqlx::query_as::<_, TouHoliday>(
+            "INSERT INTO tou_holidays (day, name) VALUES ($1, $2)
+             ON CONFLICT (day) DO UPDATE SET name = EXCLUDED.name
+             RETURNING day, name",
+        )
+        .bind(day)
+        .bind(name.trim())
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    pub async fn delete_holiday(&self, day: NaiveDate) -> Result<()> {
+        let deleted = sqlx::query("DELETE FROM tou_holidays WHERE day = $1")
+            .bind(day)
+            .execute(&self.db)
+            .await?
+            .rows_affected();
+        if deleted == 0 {
+            return Err(ApiError::NotFound(format!("Holiday {} not found", day)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn period(id: &str, weekdays: &[i16], start: (u32, u32), end: (u32, u32), holidays: bool, priority: i32) -> TouPeriod {
+        TouPeriod {
+            id: id.to_string(),
+            name: id.to_string(),
+            weekdays: weekdays.to_vec(),
+            start_time: NaiveTime::from_hms_opt(start.0, start.1, 0).unwrap(),
+            end_time: NaiveTime::from_hms_opt(end.0, end.1, 0).unwrap(),
+            applies_on_holidays: holidays,
+            grid_rate: Decimal::new(26369, 4),
+            price_floor: Some(Decimal::new(22, 1)),
+            price_ceiling: Some(Decimal::new(26369, 4)),
+            priority,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// The seeded PEA TOU schedule
+    fn schedule(holidays: &[NaiveDate]) -> TouSchedule {
+        let mut peak = period("peak", &[1, 2, 3, 4, 5], (9, 0), (22, 0), false, 1);
+        peak.price_ceiling = Some(Decimal::new(57982, 4));
+        let off_peak = period("off_peak", &[1, 2, 3, 4, 5, 6, 7], (0, 0), (0, 0), true, 0);
+        TouSchedule::new(vec![off_peak, peak], holidays.iter().copied())
+    }
+
+    /// Campus local time on a day of September 2024; the 23rd is a Monday
+    fn local(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        campus_offset()
+            .with_ymd_and_hms(2024, 9, day, hour, minute, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn period_id(schedule: &TouSchedule, at: DateTime<Utc>) -> Option<&str> {
+        schedule.period_at(at).map(|period| period.id.as_str())
+    }
+
+    #[test]
+    fn test_peak_covers_working_day_hours_in_campus_time() {
+        let schedule = schedule(&[]);
+
+        assert_eq!(period_id(&schedule, local(23, 8, 59)), Some("off_peak"));
+        assert_eq!(period_id(&schedule, local(23, 9, 0)), Some("peak"));
+        assert_eq!(period_id(&schedule, local(23, 21, 59)), Some("peak"));
+        assert_eq!(period_id(&schedule, local(23, 22, 0)), Some("off_peak"));
+        // Saturday
+        assert_eq!(period_id(&schedule, local(28, 12, 0)), Some("off_peak"));
+        // 02:00 UTC is already 09:00 on campus
+        assert_eq!(
+            period_id(&schedule, Utc.with_ymd_and_hms(2024, 9, 23, 2, 0, 0).unwrap()),
+            Some("peak")
+        );
+    }
+
+    #[test]
+    fn test_holidays_are_off_peak_all_day() {
+        let holiday = NaiveDate::from_ymd_opt(2024, 9, 24).unwrap();
+        let schedule = schedule(&[holiday]);
+
+        assert!(schedule.is_holiday(local(24, 12, 0)));
+        assert_eq!(period_id(&schedule, local(24, 12, 0)), Some("off_peak"));
+        assert_eq!(period_id(&schedule, local(25, 12, 0)), Some("peak"));
+        assert_eq!(schedule.band_at(local(24, 12, 0)).ceiling, Some(Decimal::new(26369, 4)));
+    }
+
+    #[test]
+    fn test_overnight_periods_belong_to_the_day_they_start() {
+        // Friday night into Saturday morning only
+        let night = period("night", &[5], (22, 0), (6, 0), false, 2);
+        let schedule = TouSchedule::new(vec![night], []);
+
+        assert_eq!(period_id(&schedule, local(27, 23, 0)), Some("night"));
+        assert_eq!(period_id(&schedule, local(28, 5, 59)), Some("night"));
+        assert_eq!(period_id(&schedule, local(28, 6, 0)), None);
+        assert_eq!(period_id(&schedule, local(28, 23, 0)), None);
+        assert_eq!(schedule.band_at(local(28, 23, 0)), PriceBand::default());
+    }
+
+    #[test]
+    fn test_next_change_skips_boundaries_that_keep_the_period() {
+        let schedule = schedule(&[]);
+
+        assert_eq!(schedule.next_change(local(23, 12, 0)), Some(local(23, 22, 0)));
+        // Monday night's off-peak runs on through midnight until Tuesday's peak
+        assert_eq!(schedule.next_change(local(23, 23, 0)), Some(local(24, 9, 0)));
+        // Friday night through the weekend
+        assert_eq!(schedule.next_change(local(27, 22, 0)), Some(local(30, 9, 0)));
+        assert_eq!(TouSchedule::new(vec![], []).next_change(local(23, 12, 0)), None);
+    }
+
+    #[test]
+    fn test_price_band_admits_and_clamps() {
+        let band = PriceBand {
+            floor: Some(Decimal::new(2, 0)),
+            ceiling: Some(Decimal::new(5, 0)),
+        };
+
+        assert!(band.admits_bid(Decimal::new(2, 0)));
+        assert!(!band.admits_bid(Decimal::new(19, 1)));
+        assert!(band.admits_ask(Decimal::new(5, 0)));
+        assert!(!band.admits_ask(Decimal::new(51, 1)));
+        assert_eq!(band.clamp(Decimal::new(1, 0)), Decimal::new(2, 0));
+        assert_eq!(band.clamp(Decimal::new(6, 0)), Decimal::new(5, 0));
+        assert_eq!(PriceBand::default().clamp(Decimal::new(6, 0)), Decimal::new(6, 0));
+    }
+}
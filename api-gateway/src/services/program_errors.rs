@@ -0,0 +1,187 @@
+use serde_json::Value;
+
+use crate::error::BlockchainError;
+
+/// Custom errors of one Anchor program, generated by `build.rs` from its `#[error_code]` enum
+pub struct ProgramErrors {
+    /// Directory of the program under `anchor/programs`
+    pub program: &'static str,
+    /// ID from the program's `declare_id!`
+    pub program_id: &'static str,
+    /// Name and message of each error, numbered from `ANCHOR_ERROR_OFFSET`
+    pub errors: &'static [(&'static str, &'static str)],
+}
+
+include!(concat!(env!("OUT_DIR"), "/program_errors.rs"));
+
+/// Number of the first custom error of every Anchor program
+const ANCHOR_ERROR_OFFSET: u64 = 6000;
+
+/// Anchor errors the GridTokenX programs raise while paused or in maintenance
+const PAUSED_ERRORS: &[&str] = &[
+    "SystemPaused",
+    "IssuancePaused",
+    "ValidationPaused",
+    "ConfigUpdatesPaused",
+    "MaintenanceMode",
+];
+
+/// Error name and message from an Anchor error log line, e.g.
+/// `Program log: AnchorError occurred. Error Code: SystemPaused. Error Number: 6003. Error Message: System is currently paused.`
+fn parse_anchor_error(log: &str) -> Option<(&str, &str)> {
+    let (_, rest) = log.split_once("AnchorError")?;
+    let (_, rest) = rest.split_once("Error Code: ")?;
+    let (code, rest) = rest.split_once(". Error Number: ")?;
+    let (_, message) = rest.split_once(". Error Message: ")?;
+    Some((code, message.strip_suffix('.').unwrap_or(message)))
+}
+
+fn classify(code: &str, message: &str) -> BlockchainError {
+    let (code, message) = (code.to_string(), message.to_string());
+    if PAUSED_ERRORS.contains(&code.as_str()) {
+        BlockchainError::Paused { code, message }
+    } else if code.starts_with("Unauthorized") {
+        BlockchainError::Unauthorized { code, message }
+    } else {
+        BlockchainError::Rejected { code, message }
+    }
+}
+
+/// Custom program error number in an `InstructionError` transaction error
+fn custom_error(err: &Value) -> Option<u64> {
+    err.get("InstructionError")?.get(1)?.get("Custom")?.as_u64()
+}
+
+/// Name and message of custom error `number` of the program matching `is_program`
+fn lookup(is_program: impl Fn(&ProgramErrors) -> bool, number: u64) -> Option<(&'static str, &'static str)> {
+    let index = usize::try_from(number.checked_sub(ANCHOR_ERROR_OFFSET)?).ok()?;
+    PROGRAM_ERRORS.iter().find(|program| is_program(program))?.errors.get(index).copied()
+}
+
+/// ID of the program whose failure the runtime logged, e.g.
+/// `Program Dy8JFn95L1E7NoUkXbFQtW1kGR7Ja21CkNcirNgv4ghe failed: custom program error: 0x1783`
+fn failed_program(logs: &[String]) -> Option<&str> {
+    logs.iter().rev().find_map(|log| {
+        let (program, _) = log.strip_prefix("Program ")?.split_once(" failed: custom program error")?;
+        Some(program)
+    })
+}
+
+/// Typed error for custom error `number` of a program found by `is_program`
+fn custom_program_error(is_program: impl Fn(&ProgramErrors) -> bool, number: u64) -> BlockchainError {
+    match lookup(is_program, number) {
+        Some((code, message)) => classify(code, message),
+        None => BlockchainError::Rejected {
+            code: format!("Custom({})", number),
+            message: format!("Program rejected the transaction with error {}", number),
+        },
+    }
+}
+
+/// Typed error for a simulation that failed with transaction error `err`, using the
+/// Anchor error the failing program logged when there is one
+pub fn decode_simulation_error(err: &Value, logs: &[String]) -> BlockchainError {
+    if let Some((code, message)) = logs.iter().rev().find_map(|log| parse_anchor_error(log)) {
+        return classify(code, message);
+    }
+
+    if err.as_str() == Some("InsufficientFundsForFee") || err.get("InsufficientFundsForRent").is_some() {
+        return BlockchainError::InsufficientFunds;
+    }
+    let program_id = failed_program(logs);
+    match custom_error(err) {
+        Some(number) => custom_program_error(|program| Some(program.program_id) == program_id, number),
+        None => BlockchainError::Failed(err.to_string()),
+    }
+}
+
+/// Typed error for a transaction that failed on-chain with `err`, whose logs are no longer at
+/// hand, decoding custom errors against `program`, the `anchor/programs` directory it targeted
+pub fn decode_transaction_error(err: &Value, program: &str) -> BlockchainError {
+    match custom_error(err) {
+        Some(number) => custom_program_error(|errors| errors.program == program, number),
+        None => decode_simulation_error(err, &[]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn logs(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_anchor_errors_are_typed() {
+        let err = json!({ "InstructionError": [1, { "Custom": 6003 }] });
+        let paused = logs(&[
+            "Program Dy8JFn95L1E7NoUkXbFQtW1kGR7Ja21CkNcirNgv4ghe invoke [1]",
+            "Program log: Instruction: IssueErc",
+            "Program log: AnchorError thrown in programs/governance/src/lib.rs:412. Error Code: SystemPaused. \
+             Error Number: 6003. Error Message: System is currently paused.",
+        ]);
+        assert_eq!(
+            decode_simulation_error(&err, &paused),
+            BlockchainError::Paused {
+                code: "SystemPaused".to_string(),
+                message: "System is currently paused".to_string(),
+            }
+        );
+
+        let below = logs(&["Program log: AnchorError occurred. Error Code: BelowMinimumEnergy. Error Number: 6012. \
+             Error Message: Energy amount below minimum required."]);
+        let error = decode_simulation_error(&err, &below);
+        assert_eq!(error.code(), Some("BelowMinimumEnergy"));
+        assert_eq!(error.to_string(), "Energy amount below minimum required");
+        assert!(matches!(error, BlockchainError::Rejected { .. }));
+
+        let unauthorized = logs(&["Program log: AnchorError caused by account: poa_config. Error Code: \
+             UnauthorizedAuthority. Error Number: 6000. Error Message: Unauthorized authority."]);
+        assert!(matches!(
+            decode_simulation_error(&err, &unauthorized),
+            BlockchainError::Unauthorized { .. }
+        ));
+    }
+
+    #[test]
+    fn test_errors_without_anchor_logs() {
+        assert_eq!(
+            decode_simulation_error(&json!("InsufficientFundsForFee"), &[]),
+            BlockchainError::InsufficientFunds
+        );
+        assert_eq!(
+            decode_simulation_error(&json!({ "InstructionError": [0, { "Custom": 1 }] }), &[]).code(),
+            Some("Custom(1)")
+        );
+        assert!(matches!(
+            decode_simulation_error(&json!("BlockhashNotFound"), &[]),
+            BlockchainError::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn test_custom_error_numbers_are_named() {
+        let governance = PROGRAM_ERRORS.iter().find(|program| program.program == "governance").unwrap();
+        let number = ANCHOR_ERROR_OFFSET
+            + governance.errors.iter().position(|(code, _)| *code == "ErcExpired").unwrap() as u64;
+        let err = json!({ "InstructionError": [0, { "Custom": number }] });
+
+        let failed = logs(&[&format!(
+            "Program {} failed: custom program error: {:#x}",
+            governance.program_id, number
+        )]);
+        let error = decode_simulation_error(&err, &failed);
+        assert_eq!(error.code(), Some("ErcExpired"));
+        assert_eq!(error.to_string(), "ERC certificate has expired");
+        assert_eq!(error.stable_code(), "ERC_EXPIRED");
+
+        assert_eq!(decode_transaction_error(&err, "governance").code(), Some("ErcExpired"));
+        assert_eq!(decode_transaction_error(&err, "unknown").code(), Some(format!("Custom({})", number).as_str()));
+        assert_eq!(
+            decode_transaction_error(&json!({ "InstructionError": [0, { "Custom": 1 }] }), "governance").code(),
+            Some("Custom(1)")
+        );
+    }
+}
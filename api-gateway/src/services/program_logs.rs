@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[cfg(feature = "chain")]
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+use crate::config::Config;
+use crate::error::{ApiError, Result};
+use crate::services::transaction::Pubkey;
+
+/// Events buffered per subscriber before slow consumers start missing them
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Prefix of the log line carrying an `emit!`ted Anchor event
+const EVENT_LOG_PREFIX: &str = "Program data: ";
+
+/// Program whose events the subscriber decodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventSource {
+    Governance,
+    Trading,
+}
+
+/// Anchor event decoded from program logs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgramEvent {
+    ErcIssued {
+        certificate_id: String,
+        authority: String,
+        energy_amount: u64,
+        renewable_source: String,
+        fee: u64,
+        timestamp: i64,
+    },
+    ErcValidatedForTrading {
+        certificate_id: String,
+        authority: String,
+        nft_mint: String,
+        recipient: String,
+        timestamp: i64,
+    },
+    MarketCleared {
+        epoch: u64,
+        clearing_price: u64,
+        total_volume: u64,
+        trades: u64,
+        cleared_by: String,
+        timestamp: i64,
+    },
+}
+
+/// Event together with the transaction that emitted it
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramEventNotice {
+    pub signature: String,
+    pub slot: u64,
+    pub event: ProgramEvent,
+}
+
+#[cfg(feature = "chain")]
+pub fn renewable_source_name(source: governance::RenewableSource) -> String {
+    match source {
+        governance::RenewableSource::Solar => "solar".to_string(),
+        governance::RenewableSource::Wind => "wind".to_string(),
+        governance::RenewableSource::Biomass => "biomass".to_string(),
+        governance::RenewableSource::Hydro => "hydro".to_string(),
+        governance::RenewableSource::Other(name) => name,
+    }
+}
+
+/// Decode event data (discriminator then Borsh fields) emitted by `source`
+#[cfg(feature = "chain")]
+pub fn decode_event(source: EventSource, data: &[u8]) -> Option<ProgramEvent> {
+    let discriminator = data.get(..8)?;
+    let mut fields = &data[8..];
+
+    let event = match source {
+        EventSource::Governance if discriminator == governance::ErcIssued::DISCRIMINATOR => {
+            let event = governance::ErcIssued::deserialize(&mut fields).ok()?;
+            ProgramEvent::ErcIssued {
+                certificate_id: event.certificate_id,
+                authority: event.authority.to_string(),
+                energy_amount: event.energy_amount,
+                renewable_source: renewable_source_name(event.renewable_source),
+                fee: event.fee,
+                timestamp: event.timestamp,
+            }
+        }
+        EventSource::Governance if discriminator == governance::ErcValidatedForTrading::DISCRIMINATOR => {
+            let event = governance::ErcValidatedForTrading::deserialize(&mut fields).ok()?;
+            ProgramEvent::ErcValidatedForTrading {
+                certificate_id: event.certificate_id,
+                authority: event.authority.to_string(),
+                nft_mint: event.nft_mint.to_string(),
+                recipient: event.recipient.to_string(),
+                timestamp: event.timestamp,
+            }
+        }
+        EventSource::Trading if discriminator == trading::MarketCleared::DISCRIMINATOR => {
+            let event = trading::MarketCleared::deserialize(&mut fields).ok()?;
+            ProgramEvent::MarketCleared {
+                epoch: event.epoch,
+                clearing_price: event.clearing_price,
+                total_volume: event.total_volume,
+                trades: event.trades,
+                cleared_by: event.cleared_by.to_string(),
+                timestamp: event.timestamp,
+            }
+        }
+        _ => return None,
+    };
+    Some(event)
+}
+
+/// Without the program crates there are no event layouts to decode against
+#[cfg(not(feature = "chain"))]
+pub fn decode_event(_source: EventSource, _data: &[u8]) -> Option<ProgramEvent> {
+    None
+}
+
+/// Decode the events `program_id` emitted in a transaction's logs
+///
+/// Tracks the invocation stack so events logged by other programs, including ones it
+/// calls through CPI, are not attributed to it.
+pub fn decode_logs(source: EventSource, program_id: &Pubkey, logs: &[String]) -> Vec<ProgramEvent> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = line.strip_prefix(EVENT_LOG_PREFIX) {
+            if stack.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            match BASE64.decode(data).ok().and_then(|data| decode_event(source, &data)) {
+                Some(event) => events.push(event),
+                None => tracing::debug!("Skipping undecoded {:?} event data", source),
+            }
+        } else if let Some((program, tail)) = rest.split_once(' ') {
+            if tail.starts_with("invoke [") {
+                stack.push(program);
+            } else if tail == "success" || tail.starts_with("failed") {
+                stack.pop();
+            }
+        }
+    }
+    events
+}
+
+/// `logsNotification` payload from the RPC websocket
+#[derive(Debug, Deserialize)]
+struct LogsNotification {
+    params: LogsNotificationParams,
+}
+
+#[derive(Debug, Deserialize)]
+struct LogsNotificationParams {
+    subscription: u64,
+    result: LogsResult,
+}
+
+#[derive(Debug, Deserialize)]
+struct LogsResult {
+    context: LogsContext,
+    value: LogsValue,
+}
+
+#[derive(Debug, Deserialize)]
+struct LogsContext {
+    slot: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct LogsValue {
+    signature: String,
+    err: Option<serde_json::Value>,
+    logs: Vec<String>,
+}
+
+/// Reply to a `logsSubscribe` request, carrying the subscription ID
+#[derive(Debug, Deserialize)]
+struct SubscribeResponse {
+    id: usize,
+    result: u64,
+}
+
+/// Subscribes to program logs over `SOLANA_WS_URL` and publishes decoded Anchor events
+///
+/// Consumers call `subscribe` for a receiver; events emitted while the websocket is
+/// reconnecting are not replayed.
+pub struct ProgramLogSubscriber {
+    ws_url: String,
+    programs: Vec<(EventSource, Pubkey)>,
+    sender: broadcast::Sender<ProgramEventNotice>,
+}
+
+impl ProgramLogSubscriber {
+    pub fn new(ws_url: String, programs: Vec<(EventSource, Pubkey)>) -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            ws_url,
+            programs,
+            sender,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let program = |name: &str, value: &str| {
+            Pubkey::from_str(value).map_err(|e| ApiError::Configuration(format!("Invalid {}: {}", name, e)))
+        };
+
+        Ok(Self::new(
+            config.solana_ws_url.clone(),
+            vec![
+                (
+                    EventSource::Governance,
+                    program("GOVERNANCE_PROGRAM_ID", &config.governance_program_id)?,
+                ),
+                (
+                    EventSource::Trading,
+                    program("TRADING_PROGRAM_ID", &config.trading_program_id)?,
+                ),
+            ],
+        ))
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ProgramEventNotice> {
+        self.sender.subscribe()
+    }
+
+    /// Keep the log subscriptions open, reconnecting after failures
+    pub fn spawn(self: &std::sync::Arc<Self>) {
+        let subscriber = std::sync::Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = subscriber.listen().await {
+                    tracing::error!("Program log subscription failed: {}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    async fn listen(&self) -> Result<()> {
+        let ws_error = |e: tokio_tungstenite::tungstenite::Error| ApiError::Blockchain(format!("Websocket error: {}", e));
+        let (mut socket, _) = tokio_tungstenite::connect_async(self.ws_url.as_str()).await.map_err(ws_error)?;
+
+        for (id, (_, program_id)) in self.programs.iter().enumerate() {
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "logsSubscribe",
+                "params": [{ "mentions": [program_id.to_string()] }, { "commitment": "confirmed" }],
+            });
+            socket.send(WsMessage::Text(request.to_string())).await.map_err(ws_error)?;
+        }
+        tracing::info!("Subscribed to program logs at {}", self.ws_url);
+
+        // Subscription ID assigned by the node -> index into `programs`
+        let mut subscriptions: HashMap<u64, usize> = HashMap::new();
+        while let Some(message) = socket.next().await {
+            match message.map_err(ws_error)? {
+                WsMessage::Text(text) => self.handle_message(&text, &mut subscriptions),
+                WsMessage::Ping(payload) => socket.send(WsMessage::Pong(payload)).await.map_err(ws_error)?,
+                WsMessage::Close(_) => break,
+                _ => {}
+            }
+        }
+        Err(ApiError::Blockchain("Program log websocket closed".to_string()))
+    }
+
+    fn handle_message(&self, text: &str, subscriptions: &mut HashMap<u64, usize>) {
+        if let Ok(response) = serde_json::from_str::<SubscribeResponse>(text) {
+            subscriptions.insert(response.result, response.id);
+            return;
+        }
+        let Ok(notification) = serde_json::from_str::<LogsNotification>(text) else {
+            tracing::debug!("Ignoring websocket message: {}", text);
+            return;
+        };
+
+        let params = notification.params;
+        let Some(&(source, program_id)) = subscriptions
+            .get(&params.subscription)
+            .and_then(|&index| self.programs.get(index))
+        else {
+            return;
+        };
+        // Failed transactions are rolled back, so their events never happened
+        if params.result.value.err.is_some() {
+            return;
+        }
+
+        for event in decode_logs(source, &program_id, &params.result.value.logs) {
+            // No receivers is fine; events are only for whoever is listening
+            let _ = self.sender.send(ProgramEventNotice {
+                signature: params.result.value.signature.clone(),
+                slot: params.result.context.slot,
+                event,
+            });
+        }
+    }
+}
+
+#[cfg(all(test, feature = "chain"))]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+
+    fn data_line(event: &impl Event) -> String {
+        format!("{}{}", EVENT_LOG_PREFIX, BASE64.encode(event.data()))
+    }
+
+    #[test]
+    fn test_decode_logs_attributes_events_to_the_emitting_program() {
+        let governance_id = Pubkey([1; 32]);
+        let trading_id = Pubkey([2; 32]);
+        let issued = governance::ErcIssued {
+            certificate_id: "ERC-9".to_string(),
+            authority: Pubkey([3; 32]).into(),
+            energy_amount: 400,
+            renewable_source: governance::RenewableSource::Solar,
+            fee: 5_000,
+            attested_by: None,
+            timestamp: 1_700_000_000,
+        };
+        let cleared = trading::MarketCleared {
+            epoch: 12,
+            clearing_price: 3_500,
+            total_volume: 900,
+            trades: 4,
+            cleared_by: Pubkey([4; 32]).into(),
+            timestamp: 1_700_000_100,
+        };
+
+        // Trading clears the market, then calls governance, which emits its own event
+        let logs = vec![
+            format!("Program {} invoke [1]", trading_id),
+            "Program log: Instruction: ClearMarket".to_string(),
+            format!("Program {} invoke [2]", governance_id),
+            data_line(&issued),
+            format!("Program {} success", governance_id),
+            data_line(&cleared),
+            format!("Program {} consumed 5000 of 200000 compute units", trading_id),
+            format!("Program {} success", trading_id),
+        ];
+
+        let trading_events = decode_logs(EventSource::Trading, &trading_id, &logs);
+        assert_eq!(
+            trading_events,
+            vec![ProgramEvent::MarketCleared {
+                epoch: 12,
+                clearing_price: 3_500,
+                total_volume: 900,
+                trades: 4,
+                cleared_by: Pubkey([4; 32]).to_string(),
+                timestamp: 1_700_000_100,
+            }]
+        );
+
+        let governance_events = decode_logs(EventSource::Governance, &governance_id, &logs);
+        assert_eq!(governance_events.len(), 1);
+        assert!(matches!(
+            &governance_events[0],
+            ProgramEvent::ErcIssued { certificate_id, renewable_source, fee: 5_000, .. }
+                if certificate_id == "ERC-9" && renewable_source == "solar"
+        ));
+    }
+
+    #[test]
+    fn test_handle_message_publishes_successful_transactions() {
+        let program_id = Pubkey([1; 32]);
+        let subscriber = ProgramLogSubscriber::new(String::new(), vec![(EventSource::Governance, program_id)]);
+        let mut receiver = subscriber.subscribe();
+        let mut subscriptions = HashMap::new();
+
+        subscriber.handle_message(r#"{"jsonrpc":"2.0","result":77,"id":0}"#, &mut subscriptions);
+        assert_eq!(subscriptions.get(&77), Some(&0));
+
+        let validated = governance::ErcValidatedForTrading {
+            certificate_id: "ERC-9".to_string(),
+            authority: Pubkey([3; 32]).into(),
+            nft_mint: Pubkey([5; 32]).into(),
+            recipient: Pubkey([6; 32]).into(),
+            timestamp: 1_700_000_000,
+        };
+        let notification = |err: serde_json::Value| {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "logsNotification",
+                "params": {
+                    "subscription": 77,
+                    "result": {
+                        "context": { "slot": 321 },
+                        "value": {
+                            "signature": "sig",
+                            "err": err,
+                            "logs": [
+                                format!("Program {} invoke [1]", program_id),
+                                data_line(&validated),
+                                format!("Program {} success", program_id),
+                            ],
+                        },
+                    },
+                },
+            })
+            .to_string()
+        };
+
+        subscriber.handle_message(&notification(json!({ "InstructionError": [0, "Custom"] })), &mut subscriptions);
+        subscriber.handle_message(&notification(serde_json::Value::Null), &mut subscriptions);
+
+        let notice = receiver.try_recv().unwrap();
+        assert_eq!((notice.signature.as_str(), notice.slot), ("sig", 321));
+        assert!(matches!(notice.event, ProgramEvent::ErcValidatedForTrading { .. }));
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,293 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::auth::password::PasswordService;
+use crate::database::schema::types::OrderStatus;
+use crate::error::{ApiError, Result};
+use crate::models::user::ProsumerProfile;
+use crate::services::transaction::Pubkey;
+use crate::AppState;
+
+/// Profile with the meters actively assigned to the user
+const PROFILE_SELECT: &str = "SELECT u.id, u.username, u.email, u.first_name, u.last_name, u.university_id,
+        u.role::text AS role, u.department, u.wallet_address, u.blockchain_registered, u.is_active,
+        ARRAY(SELECT ma.meter_id::text FROM meter_assignments ma WHERE ma.user_id = u.id AND ma.is_active
+              ORDER BY ma.meter_id) AS meters,
+        u.created_at, u.updated_at
+     FROM users u";
+
+/// Most profiles returned by one listing
+const MAX_PROSUMER_LIMIT: i64 = 500;
+
+/// Roles a prosumer account may hold; administrators are managed through roles
+const PROSUMER_ROLES: [&str; 2] = ["student", "faculty"];
+
+/// Prosumer account created by an administrator
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateProsumerRequest {
+    #[validate(length(min = 3, max = 50))]
+    pub username: String,
+    #[validate(email)]
+    pub email: String,
+    #[validate(length(min = 8, max = 128))]
+    pub password: String,
+    #[validate(length(min = 1, max = 100))]
+    pub first_name: String,
+    #[validate(length(min = 1, max = 100))]
+    pub last_name: String,
+    /// Student or staff ID
+    #[validate(length(min = 1, max = 20))]
+    pub university_id: String,
+    #[validate(length(min = 1, max = 100))]
+    pub department: String,
+    /// student (default) or faculty
+    pub role: Option<String>,
+    pub wallet_address: Option<String>,
+}
+
+/// Profile fields to change; omitted fields are kept
+#[derive(Debug, Default, Deserialize, Serialize, Validate)]
+pub struct UpdateProsumerRequest {
+    #[validate(email)]
+    pub email: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub first_name: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub last_name: Option<String>,
+    #[validate(length(min = 1, max = 20))]
+    pub university_id: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub department: Option<String>,
+    /// student or faculty
+    pub role: Option<String>,
+    pub wallet_address: Option<String>,
+}
+
+fn check_role(role: &str) -> Result<()> {
+    if !PROSUMER_ROLES.contains(&role) {
+        return Err(ApiError::BadRequest(format!("role must be student or faculty, not {}", role)));
+    }
+    Ok(())
+}
+
+fn check_wallet(wallet_address: &str) -> Result<()> {
+    Pubkey::from_str(wallet_address)
+        .map(|_| ())
+        .map_err(|e| ApiError::BadRequest(format!("Invalid wallet_address: {}", e)))
+}
+
+/// Profile field guarded by a `users` unique constraint such as `users_university_id_key`
+fn unique_field(constraint: &str) -> &str {
+    constraint
+        .strip_prefix("users_")
+        .and_then(|field| field.strip_suffix("_key"))
+        .unwrap_or(constraint)
+}
+
+/// Unique violations as conflicts naming the field that is already taken
+fn conflict_on_taken(error: sqlx::Error) -> ApiError {
+    if let sqlx::Error::Database(db_error) = &error {
+        if db_error.is_unique_violation() {
+            let field = db_error.constraint().map(unique_field).unwrap_or("value");
+            return ApiError::Conflict(format!("That {} is already registered", field));
+        }
+    }
+    error.into()
+}
+
+/// Wallet of an active user, locking the user for the rest of the transaction
+///
+/// Orders and meters are only accepted for prosumers who can settle on-chain.
+pub async fn lock_trading_wallet(conn: &mut PgConnection, user_id: Uuid) -> Result<String> {
+    let user: Option<(Option<String>, bool)> =
+        sqlx::query_as("SELECT wallet_address, is_active FROM users WHERE id = $1 FOR UPDATE")
+            .bind(user_id)
+            .fetch_optional(conn)
+            .await?;
+    match user {
+        None => Err(ApiError::NotFound(format!("User {} not found", user_id))),
+        Some((_, false)) => Err(ApiError::Authorization(format!("User {} is deactivated", user_id))),
+        Some((None, true)) => Err(ApiError::BadRequest(format!("User {} has no registered wallet", user_id))),
+        Some((Some(wallet), true)) => Ok(wallet),
+    }
+}
+
+/// Prosumer accounts: who owns which meters and which wallet their trades settle to
+#[derive(Clone)]
+pub struct ProsumerStore {
+    db: PgPool,
+}
+
+impl ProsumerStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone())
+    }
+
+    /// Profiles by username, optionally only those matching `search` in their name, username,
+    /// email or university ID
+    pub async fn list(&self, search: Option<&str>, limit: i64, offset: i64) -> Result<Vec<ProsumerProfile>> {
+        let query = format!(
+            "{} WHERE $1::text IS NULL
+                 OR concat_ws(' ', u.username, u.email, u.first_name, u.last_name, u.university_id) ILIKE '%' || $1 || '%'
+             ORDER BY u.username
+             LIMIT $2 OFFSET $3",
+            PROFILE_SELECT
+        );
+        Ok(sqlx::query_as::<_, ProsumerProfile>(&query)
+            .bind(search)
+            .bind(limit.clamp(1, MAX_PROSUMER_LIMIT))
+            .bind(offset.max(0))
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    pub async fn get(&self, user_id: Uuid) -> Result<ProsumerProfile> {
+        let query = format!("{} WHERE u.id = $1", PROFILE_SELECT);
+        sqlx::query_as::<_, ProsumerProfile>(&query)
+            .bind(user_id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("User {} not found", user_id)))
+    }
+
+    pub async fn create(&self, request: CreateProsumerRequest) -> Result<ProsumerProfile> {
+        request
+            .validate()
+            .map_err(|e| ApiError::BadRequest(format!("Validation error: {}", e)))?;
+        let role = request.role.as_deref().unwrap_or("student");
+        check_role(role)?;
+        if let Some(wallet_address) = &request.wallet_address {
+            check_wallet(wallet_address)?;
+        }
+        PasswordService::validate_password_strength(&request.password)?;
+        let password_hash = PasswordService::hash_password(&request.password)?;
+
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (username, email, password_hash, role, department, first_name, last_name,
+                 university_id, wallet_address)
+             VALUES ($1, $2, $3, ($4)::user_role, $5, $6, $7, $8, $9)
+             RETURNING id",
+        )
+        .bind(&request.username)
+        .bind(&request.email)
+        .bind(&password_hash)
+        .bind(role)
+        .bind(&request.department)
+        .bind(&request.first_name)
+        .bind(&request.last_name)
+        .bind(&request.university_id)
+        .bind(&request.wallet_address)
+        .fetch_one(&self.db)
+        .await
+        .map_err(conflict_on_taken)?;
+
+        self.get(user_id).await
+    }
+
+    pub async fn update(&self, user_id: Uuid, request: &UpdateProsumerRequest) -> Result<ProsumerProfile> {
+        request
+            .validate()
+            .map_err(|e| ApiError::BadRequest(format!("Validation error: {}", e)))?;
+        if let Some(role) = &request.role {
+            check_role(role)?;
+        }
+        if let Some(wallet_address) = &request.wallet_address {
+            check_wallet(wallet_address)?;
+        }
+
+        let updated = sqlx::query(
+            "UPDATE users SET
+                 email = COALESCE($2, email),
+                 first_name = COALESCE($3, first_name),
+                 last_name = COALESCE($4, last_name),
+                 university_id = COALESCE($5, university_id),
+                 department = COALESCE($6, department),
+                 role = COALESCE(($7::text)::user_role, role),
+                 wallet_address = COALESCE($8, wallet_address),
+                 updated_at = NOW()
+             WHERE id = $1 AND role <> 'admin'",
+        )
+        .bind(user_id)
+        .bind(&request.email)
+        .bind(&request.first_name)
+        .bind(&request.last_name)
+        .bind(&request.university_id)
+        .bind(&request.department)
+        .bind(&request.role)
+        .bind(&request.wallet_address)
+        .execute(&self.db)
+        .await
+        .map_err(conflict_on_taken)?
+        .rows_affected();
+        if updated == 0 {
+            return Err(ApiError::NotFound(format!("Prosumer {} not found", user_id)));
+        }
+
+        self.get(user_id).await
+    }
+
+    /// Deactivate a prosumer who no longer has open orders or assigned meters
+    ///
+    /// Accounts are kept so their trades, readings and statements still resolve to an owner.
+    pub async fn deactivate(&self, user_id: Uuid) -> Result<ProsumerProfile> {
+        let mut tx = self.db.begin().await?;
+        // Updating first locks the user against concurrent order placements
+        let deactivated = sqlx::query("UPDATE users SET is_active = FALSE, updated_at = NOW() WHERE id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if deactivated == 0 {
+            return Err(ApiError::NotFound(format!("User {} not found", user_id)));
+        }
+
+        let (open_orders, meters): (i64, i64) = sqlx::query_as(
+            "SELECT
+                 (SELECT COUNT(*) FROM trading_orders WHERE user_id = $1 AND status IN ($2, $3)),
+                 (SELECT COUNT(*) FROM meter_assignments WHERE user_id = $1 AND is_active)",
+        )
+        .bind(user_id)
+        .bind(OrderStatus::Pending)
+        .bind(OrderStatus::Active)
+        .fetch_one(&mut *tx)
+        .await?;
+        if open_orders > 0 || meters > 0 {
+            return Err(ApiError::Conflict(format!(
+                "User {} still has {} open orders and {} assigned meters",
+                user_id, open_orders, meters
+            )));
+        }
+        tx.commit().await?;
+
+        self.get(user_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_constraints_name_their_field() {
+        assert_eq!(unique_field("users_university_id_key"), "university_id");
+        assert_eq!(unique_field("users_wallet_address_key"), "wallet_address");
+        assert_eq!(unique_field("idx_other"), "idx_other");
+    }
+
+    #[test]
+    fn test_prosumer_roles_and_wallets() {
+        assert!(check_role("student").is_ok());
+        assert!(check_role("faculty").is_ok());
+        assert!(check_role("admin").is_err());
+        assert!(check_wallet("42LoRKPphBBdvaCDx2ZjNuZFqzXuJziiiNXyiV6FhBY5").is_ok());
+        assert!(check_wallet("not-a-wallet").is_err());
+    }
+}
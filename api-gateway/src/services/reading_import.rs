@@ -0,0 +1,458 @@
+use std::str::FromStr;
+
+use axum::body::Body;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ApiError, Result};
+use crate::services::blockchain::ReadingSubmission;
+use crate::services::chain_client::{ChainClient, MeterReadingInput};
+use crate::services::fee_payers::FeePayerPool;
+use crate::services::gateway_signer::GatewaySigner;
+use crate::services::readings::ReadingStore;
+use crate::services::timeseries::MeterSample;
+use crate::services::transaction::Pubkey;
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+/// Valid rows stored (and optionally sent on-chain) together
+const IMPORT_BATCH: usize = 1_000;
+
+/// Most row and on-chain errors listed in an import summary; the rest are only counted
+const MAX_REPORTED_ERRORS: usize = 1_000;
+
+/// Longest meter ID `energy_readings` stores
+const MAX_METER_ID_LEN: usize = 20;
+
+/// Largest energy value `energy_readings` stores, in kWh (`DECIMAL(10, 4)`)
+const MAX_ENERGY_KWH: f64 = 999_999.999_9;
+
+/// Upload formats of a reading import
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ImportFormat {
+    pub fn from_content_type(content_type: Option<&str>) -> Result<Self> {
+        let media_type = content_type
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase());
+        match media_type.as_deref() {
+            Some("text/csv") => Ok(Self::Csv),
+            Some("application/x-ndjson" | "application/jsonl") => Ok(Self::Ndjson),
+            _ => Err(ApiError::BadRequest(
+                "Content-Type must be text/csv or application/x-ndjson".to_string(),
+            )),
+        }
+    }
+}
+
+/// One uploaded reading; CSV headers name the same columns
+#[derive(Debug, Deserialize)]
+struct ImportRow {
+    meter_id: String,
+    timestamp: DateTime<Utc>,
+    energy_generated: f64,
+    energy_consumed: f64,
+    solar_irradiance: Option<f64>,
+    temperature: Option<f64>,
+}
+
+/// Why an uploaded row was not imported, by 1-based line number
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RowError {
+    pub line: usize,
+    pub error: String,
+}
+
+/// Outcome of sending imported readings to the oracle program
+#[derive(Debug, Default, Serialize)]
+pub struct OnChainBackfill {
+    pub recorded: usize,
+    pub failed: usize,
+    /// Readings the oracle did not record, up to the report limit
+    pub failures: Vec<ReadingSubmission>,
+}
+
+/// Outcome of a reading import
+#[derive(Debug, Default, Serialize)]
+pub struct ImportSummary {
+    pub rows: usize,
+    pub imported: usize,
+    /// Valid rows failing reading screening, held for review and not sent on-chain
+    pub quarantined: usize,
+    pub rejected: usize,
+    /// Rejected rows, up to the report limit
+    pub errors: Vec<RowError>,
+    pub on_chain: Option<OnChainBackfill>,
+}
+
+/// Fields of a CSV line, honouring double-quoted fields with `""` escapes
+fn csv_fields(line: &str) -> std::result::Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("Unterminated quoted field".to_string());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+fn parse_number(column: &str, value: &str) -> std::result::Result<f64, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} is not a number: {}", column, value))
+}
+
+fn parse_optional(column: &str, value: Option<&String>) -> std::result::Result<Option<f64>, String> {
+    match value.map(|value| value.trim()) {
+        None | Some("") => Ok(None),
+        Some(value) => parse_number(column, value).map(Some),
+    }
+}
+
+fn check_energy(column: &str, value: f64) -> std::result::Result<(), String> {
+    if !value.is_finite() || !(0.0..=MAX_ENERGY_KWH).contains(&value) {
+        return Err(format!("{} must be between 0 and {} kWh", column, MAX_ENERGY_KWH));
+    }
+    Ok(())
+}
+
+/// Parses an upload line by line, validating each row
+struct RowParser {
+    format: ImportFormat,
+    /// CSV header columns, once read
+    columns: Option<Vec<String>>,
+    now: DateTime<Utc>,
+}
+
+impl RowParser {
+    fn new(format: ImportFormat, now: DateTime<Utc>) -> Self {
+        Self {
+            format,
+            columns: None,
+            now,
+        }
+    }
+
+    /// Reading on `line`, or `None` for blank lines and the CSV header
+    fn parse_line(&mut self, line: &str) -> Option<std::result::Result<MeterSample, String>> {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            return None;
+        }
+        let row = match self.format {
+            ImportFormat::Ndjson => serde_json::from_str::<ImportRow>(line).map_err(|e| e.to_string()),
+            ImportFormat::Csv => match &self.columns {
+                None => {
+                    return match csv_fields(line) {
+                        Ok(columns) => {
+                            self.columns = Some(columns.into_iter().map(|c| c.trim().to_string()).collect());
+                            None
+                        }
+                        Err(e) => Some(Err(e)),
+                    };
+                }
+                Some(columns) => Self::csv_row(columns, line),
+            },
+        };
+        Some(row.and_then(|row| self.validate(row)))
+    }
+
+    fn csv_row(columns: &[String], line: &str) -> std::result::Result<ImportRow, String> {
+        let fields = csv_fields(line)?;
+        if fields.len() != columns.len() {
+            return Err(format!("Expected {} fields, found {}", columns.len(), fields.len()));
+        }
+        let field = |name: &str| columns.iter().position(|column| column == name).map(|index| &fields[index]);
+        let required = |name: &str| field(name).ok_or_else(|| format!("Missing column {}", name));
+
+        Ok(ImportRow {
+            meter_id: required("meter_id")?.trim().to_string(),
+            timestamp: DateTime::parse_from_rfc3339(required("timestamp")?.trim())
+                .map_err(|e| format!("timestamp is not RFC 3339: {}", e))?
+                .with_timezone(&Utc),
+            energy_generated: parse_number("energy_generated", required("energy_generated")?)?,
+            energy_consumed: parse_number("energy_consumed", required("energy_consumed")?)?,
+            solar_irradiance: parse_optional("solar_irradiance", field("solar_irradiance"))?,
+            temperature: parse_optional("temperature", field("temperature"))?,
+        })
+    }
+
+    fn validate(&self, row: ImportRow) -> std::result::Result<MeterSample, String> {
+        if row.meter_id.is_empty() || row.meter_id.len() > MAX_METER_ID_LEN {
+            return Err(format!("meter_id must be 1 to {} characters", MAX_METER_ID_LEN));
+        }
+        if row.timestamp > self.now {
+            return Err("timestamp is in the future".to_string());
+        }
+        check_energy("energy_generated", row.energy_generated)?;
+        check_energy("energy_consumed", row.energy_consumed)?;
+        Ok(MeterSample {
+            meter_id: row.meter_id,
+            time: row.timestamp,
+            energy_generated: row.energy_generated,
+            energy_consumed: row.energy_consumed,
+            irradiance: row.solar_irradiance,
+            temperature: row.temperature,
+        })
+    }
+}
+
+/// Oracle reading for an imported sample, in the whole kWh the programs count
+pub fn oracle_reading(sample: &MeterSample) -> MeterReadingInput {
+    MeterReadingInput {
+        meter_id: sample.meter_id.clone(),
+        energy_produced: sample.energy_generated.round() as u64,
+        energy_consumed: sample.energy_consumed.round() as u64,
+        reading_timestamp: sample.time.timestamp(),
+    }
+}
+
+/// Imports historical readings from CSV or NDJSON uploads
+#[derive(Clone)]
+pub struct ReadingImporter {
+    readings: ReadingStore,
+    chain: ChainClient,
+    oracle_program_id: Pubkey,
+    signer: GatewaySigner,
+    fee_payers: FeePayerPool,
+    clock: SharedClock,
+}
+
+impl ReadingImporter {
+    pub fn new(
+        readings: ReadingStore,
+        chain: ChainClient,
+        oracle_program_id: Pubkey,
+        signer: GatewaySigner,
+        fee_payers: FeePayerPool,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            readings,
+            chain,
+            oracle_program_id,
+            signer,
+            fee_payers,
+            clock,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let oracle_program_id = Pubkey::from_str(&state.config.oracle_program_id)
+            .map_err(|e| ApiError::Configuration(format!("Invalid ORACLE_PROGRAM_ID: {}", e)))?;
+
+        Ok(Self::new(
+            ReadingStore::from_state(state),
+            ChainClient::from_state(state),
+            oracle_program_id,
+            state.signer.clone(),
+            state.fee_payers.clone(),
+            state.clock.clone(),
+        ))
+    }
+
+    /// Validate and store the readings in `body` as it streams in, in batches
+    ///
+    /// With `on_chain`, each stored batch is also recorded by the oracle program. The
+    /// oracle rejects readings older than its `max_reading_age`, so seeding older history
+    /// needs that limit raised first.
+    pub async fn import(&self, format: ImportFormat, body: Body, on_chain: bool) -> Result<ImportSummary> {
+        let now = self.clock.now();
+        let mut parser = RowParser::new(format, now);
+        let mut summary = ImportSummary {
+            on_chain: on_chain.then(OnChainBackfill::default),
+            ..Default::default()
+        };
+        let mut batch = Vec::with_capacity(IMPORT_BATCH);
+        let mut buffer = Vec::new();
+        let mut line_number = 0;
+        let mut stream = body.into_data_stream();
+
+        loop {
+            let chunk = stream
+                .next()
+                .await
+                .transpose()
+                .map_err(|e| ApiError::BadRequest(format!("Failed to read upload: {}", e)))?;
+            let finished = chunk.is_none();
+            match chunk {
+                Some(chunk) => buffer.extend_from_slice(&chunk),
+                // A final line without a trailing newline
+                None if !buffer.is_empty() => buffer.push(b'\n'),
+                None => {}
+            }
+
+            let mut consumed = 0;
+            while let Some(end) = buffer[consumed..].iter().position(|&b| b == b'\n') {
+                line_number += 1;
+                let line = std::str::from_utf8(&buffer[consumed..consumed + end])
+                    .map_err(|_| "Line is not valid UTF-8".to_string());
+                consumed += end + 1;
+
+                match line.map(|line| parser.parse_line(line)) {
+                    Ok(None) => continue,
+                    Ok(Some(Ok(sample))) => batch.push(sample),
+                    Ok(Some(Err(error))) | Err(error) => Self::reject(&mut summary, line_number, error),
+                }
+                summary.rows += 1;
+                if batch.len() == IMPORT_BATCH {
+                    self.flush(&mut batch, &mut summary, now).await?;
+                }
+            }
+            buffer.drain(..consumed);
+
+            if finished {
+                break;
+            }
+        }
+        self.flush(&mut batch, &mut summary, now).await?;
+
+        if format == ImportFormat::Csv && parser.columns.is_none() {
+            return Err(ApiError::BadRequest("CSV upload has no header row".to_string()));
+        }
+        Ok(summary)
+    }
+
+    fn reject(summary: &mut ImportSummary, line: usize, error: String) {
+        summary.rejected += 1;
+        if summary.errors.len() < MAX_REPORTED_ERRORS {
+            summary.errors.push(RowError { line, error });
+        }
+    }
+
+    async fn flush(&self, batch: &mut Vec<MeterSample>, summary: &mut ImportSummary, now: DateTime<Utc>) -> Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let recorded = self.readings.record_samples(batch, now).await?;
+        summary.imported += recorded.stored as usize;
+        summary.quarantined += recorded.quarantined;
+
+        if let Some(backfill) = summary.on_chain.as_mut().filter(|_| !recorded.accepted.is_empty()) {
+            let readings: Vec<MeterReadingInput> = recorded.accepted.iter().map(oracle_reading).collect();
+            let authority = self.signer.lease().await?;
+            let fee_payer = self.fee_payers.next();
+            let results = self
+                .chain
+                .submit_meter_readings_batch(self.oracle_program_id, authority.as_ref(), fee_payer.as_deref(), &readings)
+                .await?;
+            for result in results {
+                if result.error.is_none() {
+                    backfill.recorded += 1;
+                    continue;
+                }
+                backfill.failed += 1;
+                if backfill.failures.len() < MAX_REPORTED_ERRORS {
+                    backfill.failures.push(result);
+                }
+            }
+        }
+        batch.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 9, 1, 0, 0, 0).unwrap()
+    }
+
+    fn parse_all(parser: &mut RowParser, upload: &str) -> Vec<std::result::Result<MeterSample, String>> {
+        upload.lines().filter_map(|line| parser.parse_line(line)).collect()
+    }
+
+    #[test]
+    fn test_csv_fields() {
+        assert_eq!(csv_fields("a,b,,c").unwrap(), ["a", "b", "", "c"]);
+        assert_eq!(csv_fields(r#""Dorm A, 3F",1"#).unwrap(), ["Dorm A, 3F", "1"]);
+        assert_eq!(csv_fields(r#""say ""hi""",x"#).unwrap(), [r#"say "hi""#, "x"]);
+        assert!(csv_fields(r#""open,1"#).is_err());
+    }
+
+    #[test]
+    fn test_csv_rows_follow_the_header() {
+        let mut parser = RowParser::new(ImportFormat::Csv, now());
+        let rows = parse_all(
+            &mut parser,
+            "timestamp,meter_id,energy_consumed,energy_generated,temperature\r\n\
+             2023-09-01T00:15:00Z,METER-001,1.25,3.5,\r\n\
+             \r\n\
+             2023-09-01T00:30:00Z,METER-001,abc,3.5,31.2\n\
+             2025-01-01T00:00:00Z,METER-001,1,1,\n\
+             2023-09-01T00:45:00Z,METER-001,1\n",
+        );
+
+        let sample = rows[0].as_ref().unwrap();
+        assert_eq!(sample.meter_id, "METER-001");
+        assert_eq!(sample.energy_generated, 3.5);
+        assert_eq!(sample.energy_consumed, 1.25);
+        assert_eq!(sample.temperature, None);
+        assert!(rows[1].as_ref().unwrap_err().contains("energy_consumed"));
+        assert!(rows[2].as_ref().unwrap_err().contains("future"));
+        assert!(rows[3].as_ref().unwrap_err().contains("Expected 5 fields"));
+        assert_eq!(rows.len(), 4);
+    }
+
+    #[test]
+    fn test_ndjson_rows() {
+        let mut parser = RowParser::new(ImportFormat::Ndjson, now());
+        let rows = parse_all(
+            &mut parser,
+            r#"{"meter_id":"METER-002","timestamp":"2023-09-01T00:15:00Z","energy_generated":2.0,"energy_consumed":0.5}
+{"meter_id":"METER-002","timestamp":"2023-09-01T00:30:00Z","energy_generated":-1.0,"energy_consumed":0.5}
+{"meter_id":"METER-TOO-LONG-FOR-THE-TABLE","timestamp":"2023-09-01T00:30:00Z","energy_generated":1.0,"energy_consumed":0.5}
+not json"#,
+        );
+        assert_eq!(rows[0].as_ref().unwrap().energy_generated, 2.0);
+        assert!(rows[1].as_ref().unwrap_err().contains("energy_generated"));
+        assert!(rows[2].as_ref().unwrap_err().contains("meter_id"));
+        assert!(rows[3].is_err());
+    }
+
+    #[test]
+    fn test_format_from_content_type() {
+        assert_eq!(ImportFormat::from_content_type(Some("text/csv; charset=utf-8")).unwrap(), ImportFormat::Csv);
+        assert_eq!(ImportFormat::from_content_type(Some("application/x-ndjson")).unwrap(), ImportFormat::Ndjson);
+        assert!(ImportFormat::from_content_type(Some("application/json")).is_err());
+        assert!(ImportFormat::from_content_type(None).is_err());
+    }
+
+    #[test]
+    fn test_oracle_readings_use_whole_kwh() {
+        let sample = MeterSample {
+            meter_id: "METER-001".to_string(),
+            time: now(),
+            energy_generated: 12.6,
+            energy_consumed: 0.4,
+            irradiance: None,
+            temperature: None,
+        };
+        let reading = oracle_reading(&sample);
+        assert_eq!((reading.energy_produced, reading.energy_consumed), (13, 0));
+        assert_eq!(reading.reading_timestamp, now().timestamp());
+    }
+}
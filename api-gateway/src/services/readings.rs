@@ -0,0 +1,673 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::database::schema::types::ReadingStatus;
+use crate::error::{ApiError, Result};
+use crate::models::energy::{EnergyReadingSubmission, QuarantinedReading};
+use crate::models::webhook::WebhookSubscription;
+use crate::services::meter_keys::MeterKeyStore;
+use crate::services::timeseries::{MeterSample, TimeseriesStore};
+use crate::services::webhooks::WebhookStore;
+use crate::utils::decimal::big_decimal_from_f64;
+use crate::AppState;
+
+/// Quarantined readings returned by a review queue request at most
+const MAX_QUARANTINE_LIMIT: i64 = 500;
+
+const QUARANTINE_COLUMNS: &str = "id, meter_id, timestamp, energy_generated, energy_consumed, solar_irradiance, \
+     temperature, metadata, meter_signature, reasons, status, reading_id, reviewed_by, review_note, reviewed_at, created_at";
+
+/// Why a stored reading is implausible, if it is
+fn anomaly(sample: &MeterSample, max_kwh: f64) -> Option<&'static str> {
+    let energy = [sample.energy_generated, sample.energy_consumed];
+    if energy.iter().any(|kwh| !kwh.is_finite()) {
+        Some("non_finite_energy")
+    } else if energy.iter().any(|kwh| *kwh < 0.0) {
+        Some("negative_energy")
+    } else if energy.iter().any(|kwh| *kwh > max_kwh) {
+        Some("energy_above_ceiling")
+    } else {
+        None
+    }
+}
+
+/// Checks a reading has to pass to be stored rather than quarantined
+#[derive(Debug, Clone)]
+pub struct ScreeningRules {
+    /// Generated or consumed kWh in one reading above which it is implausible
+    pub max_kwh: f64,
+    /// Largest change in generated or consumed kWh from the meter's previous reading
+    pub max_delta_kwh: f64,
+    /// Identical non-zero readings in a row, this one included, taken to mean the meter is stuck
+    pub stuck_readings: usize,
+    /// How far ahead of the gateway's clock a reading may be timestamped
+    pub max_clock_skew: Duration,
+}
+
+/// What screening knows about a meter
+#[derive(Debug, Default)]
+struct MeterHistory {
+    /// Rated inverter output, when registered
+    capacity_kw: Option<f64>,
+    /// Interval a provisioned meter reports at
+    sampling_interval: Option<Duration>,
+    /// Latest stored readings, oldest first
+    recent: VecDeque<MeterSample>,
+}
+
+/// Checks each of `samples` fails, in order; samples that pass extend `history` for later ones
+fn screen(
+    rules: &ScreeningRules,
+    samples: &[MeterSample],
+    history: &mut HashMap<String, MeterHistory>,
+    now: DateTime<Utc>,
+) -> Vec<Vec<&'static str>> {
+    let kept = rules.stuck_readings.saturating_sub(1).max(1);
+    let mut order: Vec<usize> = (0..samples.len()).collect();
+    order.sort_by_key(|&i| samples[i].time);
+
+    let mut reasons = vec![Vec::new(); samples.len()];
+    for i in order {
+        let sample = &samples[i];
+        let failed = &mut reasons[i];
+        failed.extend(anomaly(sample, rules.max_kwh));
+        if sample.time > now + rules.max_clock_skew {
+            failed.push("future_timestamp");
+        }
+
+        let meter = history.entry(sample.meter_id.clone()).or_default();
+        let previous = meter.recent.back();
+        if let Some(capacity_kw) = meter.capacity_kw {
+            // Without an earlier reading the interval is the meter's sampling interval, or an hour
+            let hours = previous
+                .map(|previous| (sample.time - previous.time).num_seconds() as f64 / 3600.0)
+                .filter(|hours| *hours > 0.0)
+                .unwrap_or_else(|| meter.sampling_interval.unwrap_or(Duration::hours(1)).num_seconds() as f64 / 3600.0);
+            if sample.energy_generated > capacity_kw * hours {
+                failed.push("above_inverter_capacity");
+            }
+        }
+        if let Some(previous) = previous {
+            if (sample.energy_generated - previous.energy_generated).abs() > rules.max_delta_kwh
+                || (sample.energy_consumed - previous.energy_consumed).abs() > rules.max_delta_kwh
+            {
+                failed.push("impossible_delta");
+            }
+        }
+        let energy = (sample.energy_generated, sample.energy_consumed);
+        if rules.stuck_readings > 1
+            && energy != (0.0, 0.0)
+            && meter.recent.len() + 1 >= rules.stuck_readings
+            && meter
+                .recent
+                .iter()
+                .rev()
+                .take(rules.stuck_readings - 1)
+                .all(|previous| (previous.energy_generated, previous.energy_consumed) == energy)
+        {
+            failed.push("stuck_value");
+        }
+
+        if failed.is_empty() {
+            meter.recent.push_back(sample.clone());
+            if meter.recent.len() > kept {
+                meter.recent.pop_front();
+            }
+        }
+    }
+    reasons
+}
+
+/// Outcome of recording one reading
+#[derive(Debug, Clone, Copy)]
+pub struct RecordedReading {
+    /// Stored reading, or the quarantine entry holding it
+    pub id: Uuid,
+    /// submitted or quarantined
+    pub status: ReadingStatus,
+}
+
+/// Outcome of recording many readings
+#[derive(Debug, Default)]
+pub struct RecordedSamples {
+    pub stored: u64,
+    pub quarantined: usize,
+    /// Samples that passed screening, in the order given
+    pub accepted: Vec<MeterSample>,
+}
+
+/// Review decision on a quarantined reading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewDecision {
+    /// Store the reading as if it had passed screening
+    Approve,
+    Reject,
+}
+
+/// Stores meter readings, whether pushed to the API or polled from the meter
+///
+/// Readings failing the [`ScreeningRules`] are quarantined for review instead, so they
+/// never reach the charts, billing or the oracle program unless an operator approves them.
+#[derive(Clone)]
+pub struct ReadingStore {
+    db: PgPool,
+    timeseries: TimeseriesStore,
+    webhooks: WebhookStore,
+    keys: MeterKeyStore,
+    rules: ScreeningRules,
+}
+
+impl ReadingStore {
+    pub fn new(
+        db: PgPool,
+        timeseries: TimeseriesStore,
+        webhooks: WebhookStore,
+        keys: MeterKeyStore,
+        rules: ScreeningRules,
+    ) -> Self {
+        Self {
+            db,
+            timeseries,
+            webhooks,
+            keys,
+            rules,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(
+            state.db.clone(),
+            TimeseriesStore::from_state(state),
+            WebhookStore::from_state(state),
+            MeterKeyStore::from_state(state),
+            ScreeningRules {
+                max_kwh: state.config.reading_anomaly_max_kwh,
+                max_delta_kwh: state.config.reading_max_delta_kwh,
+                stuck_readings: state.config.reading_stuck_count,
+                max_clock_skew: Duration::seconds(state.config.reading_max_clock_skew),
+            },
+        )
+    }
+
+    /// Accept a reading a meter pushed to the API or gRPC surface, signed by the meter's key
+    ///
+    /// The meter's signature is stored with the reading; the oracle program's
+    /// `MeterReadingInput` has no field for it yet, so it is not forwarded on-chain.
+    pub async fn submit(&self, payload: &EnergyReadingSubmission, now: DateTime<Utc>) -> Result<RecordedReading> {
+        // Validate engineering authority signature (for Phase 3)
+        if payload.engineering_authority_signature.is_empty() {
+            return Err(ApiError::BadRequest("Engineering authority signature required".to_string()));
+        }
+        self.keys.verify(payload).await?;
+
+        // TODO: In Phase 4, trigger blockchain submission for verified readings
+        self.record(payload, now).await
+    }
+
+    /// Insert `payload` into `energy_readings` and the TimescaleDB hypertable, or quarantine it
+    pub async fn record(&self, payload: &EnergyReadingSubmission, now: DateTime<Utc>) -> Result<RecordedReading> {
+        let sample = MeterSample::from(payload);
+        let metadata_json = payload.metadata.as_ref().map(|m| serde_json::to_value(m).unwrap());
+
+        let reasons = self.screen(std::slice::from_ref(&sample), now).await?.remove(0);
+        if !reasons.is_empty() {
+            let id = Uuid::new_v4();
+            sqlx::query(
+                "INSERT INTO quarantined_readings (id, meter_id, timestamp, energy_generated, energy_consumed,
+                     solar_irradiance, temperature, metadata, meter_signature, reasons, created_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+            )
+            .bind(id)
+            .bind(&sample.meter_id)
+            .bind(sample.time)
+            .bind(sample.energy_generated)
+            .bind(sample.energy_consumed)
+            .bind(sample.irradiance)
+            .bind(sample.temperature)
+            .bind(metadata_json)
+            .bind(&payload.meter_signature)
+            .bind(&reasons)
+            .bind(now)
+            .execute(&self.db)
+            .await?;
+            self.report_anomalies(&[(id, &sample, reasons)]).await;
+            return Ok(RecordedReading {
+                id,
+                status: ReadingStatus::Quarantined,
+            });
+        }
+
+        let reading_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO energy_readings (
+                 id, meter_id, timestamp, energy_generated, energy_consumed,
+                 solar_irradiance, temperature, metadata, meter_signature, created_at
+             ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(reading_id)
+        .bind(&payload.meter_id)
+        .bind(payload.timestamp)
+        .bind(big_decimal_from_f64(payload.energy_generated))
+        .bind(big_decimal_from_f64(payload.energy_consumed))
+        .bind(payload.solar_irradiance.map(big_decimal_from_f64))
+        .bind(payload.temperature.map(big_decimal_from_f64))
+        .bind(metadata_json)
+        .bind(&payload.meter_signature)
+        .bind(now)
+        .execute(&self.db)
+        .instrument(tracing::info_span!("insert_energy_reading"))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to insert energy reading: {}", e);
+            ApiError::Database(e)
+        })?;
+
+        // The relational row is authoritative; a missed chart sample is only logged
+        if let Err(e) = self.timeseries.ingest(std::slice::from_ref(&sample)).await {
+            tracing::warn!("Failed to write reading {} to TimescaleDB: {}", reading_id, e);
+        }
+        Ok(RecordedReading {
+            id: reading_id,
+            status: ReadingStatus::Submitted,
+        })
+    }
+
+    /// Insert many readings with one statement per table, quarantining those failing screening
+    pub async fn record_samples(&self, samples: &[MeterSample], now: DateTime<Utc>) -> Result<RecordedSamples> {
+        if samples.is_empty() {
+            return Ok(RecordedSamples::default());
+        }
+
+        let mut recorded = RecordedSamples::default();
+        let mut quarantined = Vec::new();
+        for (sample, reasons) in samples.iter().zip(self.screen(samples, now).await?) {
+            if reasons.is_empty() {
+                recorded.accepted.push(sample.clone());
+            } else {
+                quarantined.push((Uuid::new_v4(), sample, reasons));
+            }
+        }
+        recorded.quarantined = quarantined.len();
+
+        if !quarantined.is_empty() {
+            let ids: Vec<Uuid> = quarantined.iter().map(|(id, _, _)| *id).collect();
+            let meter_ids: Vec<&str> = quarantined.iter().map(|(_, s, _)| s.meter_id.as_str()).collect();
+            let times: Vec<DateTime<Utc>> = quarantined.iter().map(|(_, s, _)| s.time).collect();
+            let generated: Vec<f64> = quarantined.iter().map(|(_, s, _)| s.energy_generated).collect();
+            let consumed: Vec<f64> = quarantined.iter().map(|(_, s, _)| s.energy_consumed).collect();
+            let irradiance: Vec<Option<f64>> = quarantined.iter().map(|(_, s, _)| s.irradiance).collect();
+            let temperature: Vec<Option<f64>> = quarantined.iter().map(|(_, s, _)| s.temperature).collect();
+            // Reason names contain no commas, and Postgres has no ragged arrays to bind
+            let reasons: Vec<String> = quarantined.iter().map(|(_, _, r)| r.join(",")).collect();
+
+            sqlx::query(
+                "INSERT INTO quarantined_readings (id, meter_id, timestamp, energy_generated, energy_consumed,
+                     solar_irradiance, temperature, reasons, created_at)
+                 SELECT id, meter_id, time, generated, consumed, irradiance, temperature,
+                     string_to_array(reasons, ','), $9
+                 FROM UNNEST($1::uuid[], $2::varchar[], $3::timestamptz[], $4::float8[], $5::float8[],
+                     $6::float8[], $7::float8[], $8::text[])
+                     AS reading(id, meter_id, time, generated, consumed, irradiance, temperature, reasons)",
+            )
+            .bind(&ids)
+            .bind(&meter_ids)
+            .bind(&times)
+            .bind(&generated)
+            .bind(&consumed)
+            .bind(&irradiance)
+            .bind(&temperature)
+            .bind(&reasons)
+            .bind(now)
+            .execute(&self.db)
+            .instrument(tracing::info_span!("quarantine_energy_readings", samples = quarantined.len()))
+            .await?;
+            self.report_anomalies(&quarantined).await;
+        }
+
+        let samples = &recorded.accepted;
+        if samples.is_empty() {
+            return Ok(recorded);
+        }
+        let meter_ids: Vec<&str> = samples.iter().map(|s| s.meter_id.as_str()).collect();
+        let times: Vec<DateTime<Utc>> = samples.iter().map(|s| s.time).collect();
+        let generated: Vec<f64> = samples.iter().map(|s| s.energy_generated).collect();
+        let consumed: Vec<f64> = samples.iter().map(|s| s.energy_consumed).collect();
+        let irradiance: Vec<Option<f64>> = samples.iter().map(|s| s.irradiance).collect();
+        let temperature: Vec<Option<f64>> = samples.iter().map(|s| s.temperature).collect();
+
+        recorded.stored = sqlx::query(
+            "INSERT INTO energy_readings (meter_id, timestamp, energy_generated, energy_consumed,
+                 solar_irradiance, temperature, created_at)
+             SELECT meter_id, time, generated::numeric, consumed::numeric, irradiance::numeric, temperature::numeric, $7
+             FROM UNNEST($1::varchar[], $2::timestamptz[], $3::float8[], $4::float8[], $5::float8[], $6::float8[])
+                 AS reading(meter_id, time, generated, consumed, irradiance, temperature)",
+        )
+        .bind(&meter_ids)
+        .bind(&times)
+        .bind(&generated)
+        .bind(&consumed)
+        .bind(&irradiance)
+        .bind(&temperature)
+        .bind(now)
+        .execute(&self.db)
+        .instrument(tracing::info_span!("insert_energy_readings", samples = samples.len()))
+        .await?
+        .rows_affected();
+
+        if let Err(e) = self.timeseries.ingest(samples).await {
+            tracing::warn!("Failed to write {} readings to TimescaleDB: {}", samples.len(), e);
+        }
+        Ok(recorded)
+    }
+
+    /// Checks each of `samples` fails, against the stored readings preceding them
+    async fn screen(&self, samples: &[MeterSample], now: DateTime<Utc>) -> Result<Vec<Vec<&'static str>>> {
+        let mut first: HashMap<&str, DateTime<Utc>> = HashMap::new();
+        for sample in samples {
+            first
+                .entry(sample.meter_id.as_str())
+                .and_modify(|time| *time = (*time).min(sample.time))
+                .or_insert(sample.time);
+        }
+        let (meter_ids, times): (Vec<&str>, Vec<DateTime<Utc>>) = first.into_iter().unzip();
+
+        let recent: Vec<(String, DateTime<Utc>, f64, f64)> = sqlx::query_as(
+            "SELECT meter_id, timestamp, energy_generated, energy_consumed
+             FROM (
+                 SELECT er.meter_id, er.timestamp, er.energy_generated::float8 AS energy_generated,
+                     er.energy_consumed::float8 AS energy_consumed,
+                     row_number() OVER (PARTITION BY er.meter_id ORDER BY er.timestamp DESC) AS recency
+                 FROM UNNEST($1::varchar[], $2::timestamptz[]) AS batch(meter_id, first_time)
+                 JOIN energy_readings er ON er.meter_id = batch.meter_id AND er.timestamp < batch.first_time
+             ) readings
+             WHERE recency <= $3
+             ORDER BY meter_id, timestamp",
+        )
+        .bind(&meter_ids)
+        .bind(&times)
+        .bind(self.rules.stuck_readings.saturating_sub(1).max(1) as i64)
+        .fetch_all(&self.db)
+        .await?;
+        let installations: Vec<(String, Option<f64>, Option<i32>)> = sqlx::query_as(
+            "SELECT COALESCE(i.meter_id, m.meter_id), i.capacity_kw, m.sampling_interval_seconds
+             FROM meter_inverters i
+             FULL JOIN meters m ON m.meter_id = i.meter_id
+             WHERE COALESCE(i.meter_id, m.meter_id) = ANY($1)",
+        )
+        .bind(&meter_ids)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut history: HashMap<String, MeterHistory> = HashMap::new();
+        for (meter_id, capacity_kw, sampling_interval) in installations {
+            let meter = history.entry(meter_id).or_default();
+            meter.capacity_kw = capacity_kw;
+            meter.sampling_interval = sampling_interval.map(|seconds| Duration::seconds(seconds.into()));
+        }
+        for (meter_id, time, energy_generated, energy_consumed) in recent {
+            history.entry(meter_id.clone()).or_default().recent.push_back(MeterSample {
+                meter_id,
+                time,
+                energy_generated,
+                energy_consumed,
+                irradiance: None,
+                temperature: None,
+            });
+        }
+        Ok(screen(&self.rules, samples, &mut history, now))
+    }
+
+    /// Queue a `reading_anomaly` webhook for each quarantined reading
+    async fn report_anomalies(&self, quarantined: &[(Uuid, &MeterSample, Vec<&'static str>)]) {
+        for (id, sample, reasons) in quarantined {
+            tracing::warn!(
+                "Quarantined reading from meter {} at {}: {}",
+                sample.meter_id,
+                sample.time,
+                reasons.join(", ")
+            );
+            let data = json!({
+                "meter_id": sample.meter_id,
+                "timestamp": sample.time,
+                "energy_generated": sample.energy_generated,
+                "energy_consumed": sample.energy_consumed,
+                "reason": reasons[0],
+                "reasons": reasons,
+                "quarantine_id": id,
+            });
+            // The reading is held either way; a missed notification is only logged
+            if let Err(e) = self.webhooks.publish(WebhookSubscription::READING_ANOMALY, &data).await {
+                tracing::error!("Failed to queue reading anomaly webhooks for {}: {}", sample.meter_id, e);
+            }
+        }
+    }
+
+    /// Register the rated inverter output of a meter's installation
+    pub async fn set_inverter_capacity(&self, meter_id: &str, capacity_kw: f64) -> Result<()> {
+        if !capacity_kw.is_finite() || capacity_kw <= 0.0 {
+            return Err(ApiError::BadRequest("capacity_kw must be a positive number".to_string()));
+        }
+        sqlx::query(
+            "INSERT INTO meter_inverters (meter_id, capacity_kw) VALUES ($1, $2)
+             ON CONFLICT (meter_id) DO UPDATE SET capacity_kw = EXCLUDED.capacity_kw",
+        )
+        .bind(meter_id)
+        .bind(capacity_kw)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Quarantined readings, newest first
+    pub async fn quarantined(&self, status: Option<&str>, limit: i64) -> Result<Vec<QuarantinedReading>> {
+        let query = format!(
+            "SELECT {} FROM quarantined_readings
+             WHERE ($1::varchar IS NULL OR status = $1)
+             ORDER BY created_at DESC
+             LIMIT $2",
+            QUARANTINE_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, QuarantinedReading>(&query)
+            .bind(status)
+            .bind(limit.clamp(1, MAX_QUARANTINE_LIMIT))
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Settle a pending quarantined reading; approved ones are stored like any other reading
+    pub async fn review(
+        &self,
+        id: Uuid,
+        decision: ReviewDecision,
+        reviewer: Uuid,
+        note: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<QuarantinedReading> {
+        let mut tx = self.db.begin().await?;
+        let query = format!("SELECT {} FROM quarantined_readings WHERE id = $1 FOR UPDATE", QUARANTINE_COLUMNS);
+        let reading = sqlx::query_as::<_, QuarantinedReading>(&query)
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Quarantined reading {} not found", id)))?;
+        if reading.status != QuarantinedReading::PENDING {
+            return Err(ApiError::Conflict(format!(
+                "Quarantined reading {} was already {}",
+                id, reading.status
+            )));
+        }
+
+        let (status, reading_id) = match decision {
+            ReviewDecision::Approve => {
+                let reading_id: Uuid = sqlx::query_scalar(
+                    "INSERT INTO energy_readings (meter_id, timestamp, energy_generated, energy_consumed,
+                         solar_irradiance, temperature, metadata, meter_signature, created_at)
+                     SELECT meter_id, timestamp, energy_generated::numeric, energy_consumed::numeric,
+                         solar_irradiance::numeric, temperature::numeric, metadata, meter_signature, $2
+                     FROM quarantined_readings WHERE id = $1
+                     RETURNING id",
+                )
+                .bind(id)
+                .bind(now)
+                .fetch_one(&mut *tx)
+                .await?;
+                (QuarantinedReading::APPROVED, Some(reading_id))
+            }
+            ReviewDecision::Reject => (QuarantinedReading::REJECTED, None),
+        };
+        let query = format!(
+            "UPDATE quarantined_readings
+             SET status = $2, reading_id = $3, reviewed_by = $4, review_note = $5, reviewed_at = $6
+             WHERE id = $1
+             RETURNING {}",
+            QUARANTINE_COLUMNS
+        );
+        let reviewed = sqlx::query_as::<_, QuarantinedReading>(&query)
+            .bind(id)
+            .bind(status)
+            .bind(reading_id)
+            .bind(reviewer)
+            .bind(note)
+            .bind(now)
+            .fetch_one(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        if decision == ReviewDecision::Approve {
+            let sample = MeterSample {
+                meter_id: reviewed.meter_id.clone(),
+                time: reviewed.timestamp,
+                energy_generated: reviewed.energy_generated,
+                energy_consumed: reviewed.energy_consumed,
+                irradiance: reviewed.solar_irradiance,
+                temperature: reviewed.temperature,
+            };
+            if let Err(e) = self.timeseries.ingest(std::slice::from_ref(&sample)).await {
+                tracing::warn!("Failed to write approved reading {} to TimescaleDB: {}", id, e);
+            }
+        }
+        Ok(reviewed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample(energy_generated: f64, energy_consumed: f64) -> MeterSample {
+        MeterSample {
+            meter_id: "MTR-1".to_string(),
+            time: Utc.with_ymd_and_hms(2024, 9, 23, 12, 0, 0).unwrap(),
+            energy_generated,
+            energy_consumed,
+            irradiance: None,
+            temperature: None,
+        }
+    }
+
+    fn at(minute: i64, energy_generated: f64, energy_consumed: f64) -> MeterSample {
+        MeterSample {
+            time: sample(0.0, 0.0).time + Duration::minutes(minute),
+            ..sample(energy_generated, energy_consumed)
+        }
+    }
+
+    fn rules() -> ScreeningRules {
+        ScreeningRules {
+            max_kwh: 100.0,
+            max_delta_kwh: 20.0,
+            stuck_readings: 3,
+            max_clock_skew: Duration::minutes(5),
+        }
+    }
+
+    #[test]
+    fn test_anomalies() {
+        assert_eq!(anomaly(&sample(12.5, 3.0), 100.0), None);
+        assert_eq!(anomaly(&sample(100.0, 0.0), 100.0), None);
+        assert_eq!(anomaly(&sample(-0.5, 3.0), 100.0), Some("negative_energy"));
+        assert_eq!(anomaly(&sample(12.5, 250.0), 100.0), Some("energy_above_ceiling"));
+        assert_eq!(anomaly(&sample(f64::NAN, 3.0), 100.0), Some("non_finite_energy"));
+    }
+
+    #[test]
+    fn test_screening_flags_future_and_capacity() {
+        let now = sample(0.0, 0.0).time;
+        let mut history = HashMap::new();
+        history.insert(
+            "MTR-1".to_string(),
+            MeterHistory {
+                capacity_kw: Some(4.0),
+                sampling_interval: None,
+                recent: VecDeque::from([at(-15, 0.8, 0.5)]),
+            },
+        );
+        let samples = [at(0, 1.5, 0.5), at(15, 0.9, 0.6), at(30, 50.0, 0.5)];
+
+        let reasons = screen(&rules(), &samples, &mut history, now);
+
+        // 4 kW over 15 minutes is at most 1 kWh
+        assert_eq!(reasons[0], ["above_inverter_capacity"]);
+        assert_eq!(reasons[1], ["future_timestamp"]);
+        assert_eq!(reasons[2], ["future_timestamp", "above_inverter_capacity", "impossible_delta"]);
+    }
+
+    #[test]
+    fn test_first_reading_is_judged_over_the_sampling_interval() {
+        let now = at(120, 0.0, 0.0).time;
+        let meter = |sampling_interval| MeterHistory {
+            capacity_kw: Some(4.0),
+            sampling_interval,
+            recent: VecDeque::new(),
+        };
+        let samples = [at(0, 1.5, 0.5)];
+
+        let mut provisioned = HashMap::from([("MTR-1".to_string(), meter(Some(Duration::minutes(15))))]);
+        assert_eq!(screen(&rules(), &samples, &mut provisioned, now)[0], ["above_inverter_capacity"]);
+        // An unprovisioned meter is given an hour
+        let mut unprovisioned = HashMap::from([("MTR-1".to_string(), meter(None))]);
+        assert!(screen(&rules(), &samples, &mut unprovisioned, now)[0].is_empty());
+    }
+
+    #[test]
+    fn test_screening_compares_against_accepted_readings_in_time_order() {
+        let now = at(120, 0.0, 0.0).time;
+        let mut history = HashMap::new();
+        // Out of order; the spike is judged against 0:00, and 0:30 against 0:15 rather than the spike
+        let samples = [at(30, 3.0, 1.0), at(0, 2.0, 1.0), at(15, 40.0, 1.0)];
+
+        let reasons = screen(&rules(), &samples, &mut history, now);
+
+        assert!(reasons[0].is_empty());
+        assert!(reasons[1].is_empty());
+        assert_eq!(reasons[2], ["impossible_delta"]);
+        assert_eq!(history["MTR-1"].recent.len(), 2);
+    }
+
+    #[test]
+    fn test_screening_detects_stuck_meters() {
+        let now = at(120, 0.0, 0.0).time;
+        let mut history = HashMap::new();
+        let samples = [
+            at(0, 2.0, 1.0),
+            at(15, 2.0, 1.0),
+            at(30, 2.0, 1.0),
+            at(45, 0.0, 0.0),
+            at(60, 0.0, 0.0),
+            at(75, 0.0, 0.0),
+        ];
+
+        let reasons = screen(&rules(), &samples, &mut history, now);
+
+        assert!(reasons[..2].iter().all(Vec::is_empty));
+        assert_eq!(reasons[2], ["stuck_value"]);
+        // No generation or consumption at all is plausible for an idle meter at night
+        assert!(reasons[3..].iter().all(Vec::is_empty));
+    }
+}
@@ -0,0 +1,537 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::database::schema::types::OrderStatus;
+use crate::error::{ApiError, Result};
+use crate::models::report::{
+    ChainReconciliation, EnergyBalance, FeeSpend, ProgramFeeSpend, QueueHealth, ReconciliationReport,
+    ReconciliationSummary, TransactionMismatch,
+};
+use crate::models::signing::SigningSession;
+use crate::services::blockchain::{BlockchainService, SignatureStatus};
+use crate::services::notifications::{EmailNotification, NotificationService};
+use crate::services::order_book::OrderBookMirror;
+use crate::services::scheduler::{spawn_daily, DailySchedule};
+use crate::utils::clock::SharedClock;
+use crate::utils::html::escape;
+use crate::AppState;
+
+/// Transactions compared against the chain per report
+const MAX_RECONCILED_TRANSACTIONS: i64 = 1000;
+/// `getSignatureStatuses` accepts at most 256 signatures per call
+const SIGNATURE_STATUS_BATCH: usize = 256;
+
+pub const REPORT_COLUMNS: &str = "id, report_date, period_start, period_end, status, summary, recipients, \
+    delivered_at, error_message, created_at, updated_at";
+
+/// Builds, archives and delivers the nightly reconciliation report
+#[derive(Clone)]
+pub struct ReportService {
+    db: PgPool,
+    chain: BlockchainService,
+    order_book: Arc<OrderBookMirror>,
+    notifier: NotificationService,
+    recipients: Vec<String>,
+    clock: SharedClock,
+}
+
+impl ReportService {
+    pub fn new(
+        db: PgPool,
+        chain: BlockchainService,
+        order_book: Arc<OrderBookMirror>,
+        notifier: NotificationService,
+        recipients: Vec<String>,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            db,
+            chain,
+            order_book,
+            notifier,
+            recipients,
+            clock,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        Ok(Self::new(
+            state.db.clone(),
+            state.blockchain_service.clone(),
+            Arc::clone(&state.order_book),
+            NotificationService::new(state.config.notification_webhook_url.clone())?,
+            state.config.report_recipients.clone(),
+            state.clock.clone(),
+        ))
+    }
+
+    /// Generate the nightly report at `schedule`
+    pub fn spawn(self, schedule: DailySchedule) {
+        let clock = self.clock.clone();
+        spawn_daily("reconciliation_report", schedule, clock, move || {
+            let service = self.clone();
+            async move { service.run(false).await.map(|_| ()) }
+        });
+    }
+
+    /// Report on the 24 hours ending now
+    ///
+    /// Unless `replace` is set, an existing report for today is kept and `None` returned, so
+    /// replicas running the same schedule produce a single report.
+    pub async fn run(&self, replace: bool) -> Result<Option<ReconciliationReport>> {
+        let period_end = self.clock.now();
+        let period_start = period_end - Duration::days(1);
+        let report_date = period_end.date_naive();
+
+        let Some(report_id) = self.claim(report_date, period_start, period_end, replace).await? else {
+            tracing::info!("Reconciliation report for {} already exists", report_date);
+            return Ok(None);
+        };
+
+        let summary = match self.collect(period_start, period_end).await {
+            Ok(summary) => summary,
+            Err(e) => {
+                sqlx::query("UPDATE reconciliation_reports SET status = $2, error_message = $3 WHERE id = $1")
+                    .bind(report_id)
+                    .bind(ReconciliationReport::FAILED)
+                    .bind(e.to_string())
+                    .execute(&self.db)
+                    .await?;
+                return Err(e);
+            }
+        };
+
+        let status = if summary.needs_attention() {
+            ReconciliationReport::ATTENTION
+        } else {
+            ReconciliationReport::OK
+        };
+        let html = render_html(report_date, period_start, period_end, &summary);
+
+        sqlx::query("UPDATE reconciliation_reports SET status = $2, summary = $3, html = $4 WHERE id = $1")
+            .bind(report_id)
+            .bind(status)
+            .bind(Json(&summary))
+            .bind(&html)
+            .execute(&self.db)
+            .await?;
+
+        self.deliver(report_id, report_date, status, &html).await?;
+
+        Ok(Some(self.load(report_id).await?))
+    }
+
+    pub async fn load(&self, report_id: Uuid) -> Result<ReconciliationReport> {
+        let query = format!("SELECT {} FROM reconciliation_reports WHERE id = $1", REPORT_COLUMNS);
+
+        sqlx::query_as::<_, ReconciliationReport>(&query)
+            .bind(report_id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Report {} not found", report_id)))
+    }
+
+    async fn claim(
+        &self,
+        report_date: NaiveDate,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        replace: bool,
+    ) -> Result<Option<Uuid>> {
+        let on_conflict = if replace {
+            "DO UPDATE SET period_start = EXCLUDED.period_start, period_end = EXCLUDED.period_end, \
+             status = EXCLUDED.status, summary = NULL, html = NULL, recipients = EXCLUDED.recipients, \
+             delivered_at = NULL, error_message = NULL"
+        } else {
+            "DO NOTHING"
+        };
+
+        let query = format!(
+            "INSERT INTO reconciliation_reports (report_date, period_start, period_end, status, recipients) \
+             VALUES ($1, $2, $3, $4, $5) ON CONFLICT (report_date) {} RETURNING id",
+            on_conflict
+        );
+
+        Ok(sqlx::query_scalar(&query)
+            .bind(report_date)
+            .bind(period_start)
+            .bind(period_end)
+            .bind(ReconciliationReport::GENERATING)
+            .bind(Json(&self.recipients))
+            .fetch_optional(&self.db)
+            .await?)
+    }
+
+    async fn collect(&self, period_start: DateTime<Utc>, period_end: DateTime<Utc>) -> Result<ReconciliationSummary> {
+        Ok(ReconciliationSummary {
+            chain: self.reconcile_chain(period_start, period_end).await?,
+            energy: self.energy_balance(period_start, period_end).await?,
+            queues: self.queue_health(period_start, period_end).await?,
+            fees: self.fee_spend(period_start, period_end).await?,
+        })
+    }
+
+    async fn reconcile_chain(
+        &self,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<ChainReconciliation> {
+        let transactions: Vec<(String, String)> = sqlx::query_as(
+            "SELECT signature, status FROM blockchain_transactions
+             WHERE submitted_at >= $1 AND submitted_at < $2
+             ORDER BY submitted_at LIMIT $3",
+        )
+        .bind(period_start)
+        .bind(period_end)
+        .bind(MAX_RECONCILED_TRANSACTIONS)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut reconciliation = ChainReconciliation::default();
+
+        for batch in transactions.chunks(SIGNATURE_STATUS_BATCH) {
+            let signatures: Vec<String> = batch.iter().map(|(signature, _)| signature.clone()).collect();
+            let statuses = match self.chain.get_signature_statuses(&signatures).await {
+                Ok(statuses) => statuses,
+                Err(e) => {
+                    tracing::warn!("Chain reconciliation stopped: {}", e);
+                    reconciliation.rpc_error = Some(e.to_string());
+                    break;
+                }
+            };
+
+            for ((signature, db_status), status) in batch.iter().zip(statuses) {
+                let chain_status = chain_status(status.as_ref());
+                reconciliation.checked += 1;
+
+                if is_consistent(db_status, chain_status) {
+                    reconciliation.matched += 1;
+                } else {
+                    reconciliation.mismatches.push(TransactionMismatch {
+                        signature: signature.clone(),
+                        db_status: db_status.clone(),
+                        chain_status: chain_status.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(reconciliation)
+    }
+
+    async fn energy_balance(&self, period_start: DateTime<Utc>, period_end: DateTime<Utc>) -> Result<EnergyBalance> {
+        let (readings, total_generated, total_consumed, invalid_readings): (i64, f64, f64, i64) = sqlx::query_as(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(energy_generated), 0)::float8,
+                    COALESCE(SUM(energy_consumed), 0)::float8,
+                    COUNT(*) FILTER (WHERE energy_generated < 0 OR energy_consumed < 0)
+             FROM energy_readings
+             WHERE timestamp >= $1 AND timestamp < $2",
+        )
+        .bind(period_start)
+        .bind(period_end)
+        .fetch_one(&self.db)
+        .await?;
+
+        let silent_meters: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT ma.meter_id FROM meter_assignments ma
+             WHERE ma.is_active = true AND NOT EXISTS (
+                 SELECT 1 FROM energy_readings er
+                 WHERE er.meter_id = ma.meter_id AND er.timestamp >= $1 AND er.timestamp < $2
+             )
+             ORDER BY ma.meter_id",
+        )
+        .bind(period_start)
+        .bind(period_end)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(EnergyBalance {
+            readings,
+            total_generated,
+            total_consumed,
+            net_energy: total_generated - total_consumed,
+            invalid_readings,
+            silent_meters,
+        })
+    }
+
+    async fn queue_health(&self, period_start: DateTime<Utc>, period_end: DateTime<Utc>) -> Result<QueueHealth> {
+        let (stale_pending_transactions, collecting, stuck, failed, open_orders): (i64, i64, i64, i64, i64) =
+            sqlx::query_as(
+                "SELECT
+                    (SELECT COUNT(*) FROM blockchain_transactions
+                     WHERE status = 'pending' AND submitted_at < $2 - INTERVAL '1 hour'),
+                    (SELECT COUNT(*) FROM signing_sessions WHERE status = $3),
+                    (SELECT COUNT(*) FROM signing_sessions
+                     WHERE status = $4 AND updated_at < $2 - INTERVAL '10 minutes'),
+                    (SELECT COUNT(*) FROM signing_sessions
+                     WHERE status = $5 AND updated_at >= $1 AND updated_at < $2),
+                    (SELECT COUNT(*) FROM trading_orders WHERE status IN ($6, $7))",
+            )
+            .bind(period_start)
+            .bind(period_end)
+            .bind(SigningSession::COLLECTING)
+            .bind(SigningSession::SUBMITTING)
+            .bind(SigningSession::FAILED)
+            .bind(OrderStatus::Pending)
+            .bind(OrderStatus::Active)
+            .fetch_one(&self.db)
+            .await?;
+
+        let order_book_diverged = self.order_book.check_consistency(&self.db).await?;
+
+        Ok(QueueHealth {
+            stale_pending_transactions,
+            collecting_signing_sessions: collecting,
+            stuck_signing_sessions: stuck,
+            failed_signing_sessions: failed,
+            open_orders,
+            order_book_diverged,
+        })
+    }
+
+    async fn fee_spend(&self, period_start: DateTime<Utc>, period_end: DateTime<Utc>) -> Result<FeeSpend> {
+        let by_program = sqlx::query_as::<_, ProgramFeeSpend>(
+            "SELECT program_id, COUNT(*) AS transactions, COALESCE(SUM(fee), 0)::bigint AS lamports
+             FROM blockchain_transactions
+             WHERE submitted_at >= $1 AND submitted_at < $2
+             GROUP BY program_id
+             ORDER BY lamports DESC, program_id",
+        )
+        .bind(period_start)
+        .bind(period_end)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(FeeSpend {
+            transactions: by_program.iter().map(|p| p.transactions).sum(),
+            total_lamports: by_program.iter().map(|p| p.lamports).sum(),
+            by_program,
+        })
+    }
+
+    async fn deliver(&self, report_id: Uuid, report_date: NaiveDate, status: &str, html: &str) -> Result<()> {
+        if self.recipients.is_empty() || !self.notifier.is_configured() {
+            tracing::info!("Reconciliation report {} archived without delivery", report_date);
+            return Ok(());
+        }
+
+        let subject = format!("GridTokenX reconciliation report {} ({})", report_date, status);
+        let email = EmailNotification {
+            to: &self.recipients,
+            subject: &subject,
+            html,
+        };
+
+        match self.notifier.send_email(&email).await {
+            Ok(()) => {
+                sqlx::query("UPDATE reconciliation_reports SET delivered_at = $2 WHERE id = $1")
+                    .bind(report_id)
+                    .bind(self.clock.now())
+                    .execute(&self.db)
+                    .await?;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to deliver reconciliation report {}: {}", report_date, e);
+                sqlx::query("UPDATE reconciliation_reports SET error_message = $2 WHERE id = $1")
+                    .bind(report_id)
+                    .bind(e.to_string())
+                    .execute(&self.db)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn chain_status(status: Option<&SignatureStatus>) -> &str {
+    match status {
+        None => "not_found",
+        Some(status) if status.err.is_some() => "failed",
+        Some(status) => status.confirmation_status.as_deref().unwrap_or("processed"),
+    }
+}
+
+/// Whether the database status agrees with what the chain reports
+fn is_consistent(db_status: &str, chain_status: &str) -> bool {
+    match chain_status {
+        "failed" => db_status == "failed",
+        "confirmed" | "finalized" => db_status == "confirmed",
+        "processed" => db_status == "pending",
+        // Never landed, or dropped before processing
+        "not_found" => db_status == "pending" || db_status == "failed",
+        _ => false,
+    }
+}
+
+fn render_html(
+    report_date: NaiveDate,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+    summary: &ReconciliationSummary,
+) -> String {
+    let mut html = String::new();
+    let status = if summary.needs_attention() { "Needs attention" } else { "OK" };
+
+    // Writing to a String cannot fail
+    let _ = write!(
+        html,
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Reconciliation report {date}</title>\
+         <style>body{{font-family:sans-serif}}table{{border-collapse:collapse}}\
+         td,th{{border:1px solid #ccc;padding:4px 8px;text-align:left}}</style></head><body>\
+         <h1>Reconciliation report {date}</h1><p>Period: {start} &ndash; {end}<br>Status: <strong>{status}</strong></p>",
+        date = report_date,
+        start = period_start.format("%Y-%m-%d %H:%M UTC"),
+        end = period_end.format("%Y-%m-%d %H:%M UTC"),
+        status = status,
+    );
+
+    let chain = &summary.chain;
+    let _ = write!(
+        html,
+        "<h2>Chain reconciliation</h2><p>{} transactions checked, {} matched, {} mismatched.</p>",
+        chain.checked,
+        chain.matched,
+        chain.mismatches.len()
+    );
+    if let Some(error) = &chain.rpc_error {
+        let _ = write!(html, "<p><strong>RPC error:</strong> {}</p>", escape(error));
+    }
+    if !chain.mismatches.is_empty() {
+        html.push_str("<table><tr><th>Signature</th><th>Database</th><th>Chain</th></tr>");
+        for mismatch in &chain.mismatches {
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&mismatch.signature),
+                escape(&mismatch.db_status),
+                escape(&mismatch.chain_status)
+            );
+        }
+        html.push_str("</table>");
+    }
+
+    let energy = &summary.energy;
+    let _ = write!(
+        html,
+        "<h2>Energy balance</h2><table>\
+         <tr><th>Readings</th><td>{}</td></tr>\
+         <tr><th>Generated (kWh)</th><td>{:.3}</td></tr>\
+         <tr><th>Consumed (kWh)</th><td>{:.3}</td></tr>\
+         <tr><th>Net (kWh)</th><td>{:.3}</td></tr>\
+         <tr><th>Invalid readings</th><td>{}</td></tr>\
+         <tr><th>Silent meters</th><td>{}</td></tr></table>",
+        energy.readings,
+        energy.total_generated,
+        energy.total_consumed,
+        energy.net_energy,
+        energy.invalid_readings,
+        if energy.silent_meters.is_empty() {
+            "none".to_string()
+        } else {
+            escape(&energy.silent_meters.join(", "))
+        }
+    );
+
+    let queues = &summary.queues;
+    let _ = write!(
+        html,
+        "<h2>Queue health</h2><table>\
+         <tr><th>Transactions pending over 1 hour</th><td>{}</td></tr>\
+         <tr><th>Signing sessions collecting</th><td>{}</td></tr>\
+         <tr><th>Signing sessions stuck submitting</th><td>{}</td></tr>\
+         <tr><th>Signing sessions failed</th><td>{}</td></tr>\
+         <tr><th>Open orders</th><td>{}</td></tr>\
+         <tr><th>Order book mirror</th><td>{}</td></tr></table>",
+        queues.stale_pending_transactions,
+        queues.collecting_signing_sessions,
+        queues.stuck_signing_sessions,
+        queues.failed_signing_sessions,
+        queues.open_orders,
+        if queues.order_book_diverged { "diverged, rebuilt" } else { "consistent" }
+    );
+
+    let fees = &summary.fees;
+    let _ = write!(
+        html,
+        "<h2>Fee spend</h2><p>{} transactions, {} lamports ({:.6} SOL).</p>",
+        fees.transactions,
+        fees.total_lamports,
+        fees.total_lamports as f64 / 1_000_000_000.0
+    );
+    if !fees.by_program.is_empty() {
+        html.push_str("<table><tr><th>Program</th><th>Transactions</th><th>Lamports</th></tr>");
+        for program in &fees.by_program {
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&program.program_id),
+                program.transactions,
+                program.lamports
+            );
+        }
+        html.push_str("</table>");
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> ReconciliationSummary {
+        ReconciliationSummary {
+            chain: ChainReconciliation::default(),
+            energy: EnergyBalance::default(),
+            queues: QueueHealth::default(),
+            fees: FeeSpend::default(),
+        }
+    }
+
+    #[test]
+    fn test_chain_status_consistency() {
+        assert!(is_consistent("confirmed", "finalized"));
+        assert!(is_consistent("pending", "processed"));
+        assert!(is_consistent("failed", "failed"));
+        assert!(is_consistent("pending", "not_found"));
+
+        assert!(!is_consistent("pending", "finalized"));
+        assert!(!is_consistent("confirmed", "failed"));
+        assert!(!is_consistent("confirmed", "not_found"));
+    }
+
+    #[test]
+    fn test_needs_attention() {
+        assert!(!summary().needs_attention());
+
+        let mut diverged = summary();
+        diverged.queues.order_book_diverged = true;
+        assert!(diverged.needs_attention());
+
+        let mut silent = summary();
+        silent.energy.silent_meters.push("METER-001".to_string());
+        assert!(silent.needs_attention());
+    }
+
+    #[test]
+    fn test_render_html_escapes_values() {
+        let mut report = summary();
+        report.chain.rpc_error = Some("<script>".to_string());
+        let date = NaiveDate::from_ymd_opt(2024, 9, 23).unwrap();
+        let now = Utc::now();
+
+        let html = render_html(date, now - Duration::days(1), now, &report);
+        assert!(html.contains("Reconciliation report 2024-09-23"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+}
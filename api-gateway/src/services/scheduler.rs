@@ -0,0 +1,194 @@
+use std::future::Future;
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+
+use crate::utils::clock::SharedClock;
+
+/// Time of day (UTC) at which a daily job runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailySchedule {
+    time: NaiveTime,
+}
+
+impl DailySchedule {
+    pub fn at(hour: u32, minute: u32) -> Option<Self> {
+        NaiveTime::from_hms_opt(hour, minute, 0).map(|time| Self { time })
+    }
+
+    /// First scheduled time strictly after `now`
+    pub fn next_run_after(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let today = now.date_naive().and_time(self.time).and_utc();
+        if today > now {
+            today
+        } else {
+            today + Duration::days(1)
+        }
+    }
+}
+
+/// UTC times matching a cron expression with a leading seconds field,
+/// e.g. `0 */15 * * * *` for every quarter hour
+#[derive(Debug, Clone)]
+pub struct CronSchedule {
+    schedule: cron::Schedule,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        cron::Schedule::from_str(expression.trim())
+            .map(|schedule| Self { schedule })
+            .map_err(|e| format!("Invalid cron expression {:?}: {}", expression, e))
+    }
+
+    /// First scheduled time strictly after `now`; `None` once the schedule is exhausted
+    pub fn next_run_after(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.schedule.after(&now).next()
+    }
+}
+
+/// Run `job` with each time `next_run_after` yields until it yields none, logging failures
+fn spawn_scheduled<N, F, Fut>(name: &'static str, clock: SharedClock, next_run_after: N, mut job: F)
+where
+    N: Fn(DateTime<Utc>) -> Option<DateTime<Utc>> + Send + 'static,
+    F: FnMut(DateTime<Utc>) -> Fut + Send + 'static,
+    Fut: Future<Output = crate::error::Result<()>> + Send,
+{
+    tokio::spawn(async move {
+        loop {
+            let now = clock.now();
+            let Some(next_run) = next_run_after(now) else {
+                tracing::warn!("Job {} has no further scheduled runs", name);
+                break;
+            };
+            tracing::info!("Job {} scheduled for {}", name, next_run);
+
+            let wait = (next_run - now).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+
+            tracing::info!("Running job {}", name);
+            match job(next_run).await {
+                Ok(()) => tracing::info!("Job {} completed", name),
+                Err(e) => tracing::error!("Job {} failed: {}", name, e),
+            }
+        }
+    });
+}
+
+/// Run `job` once a day at `schedule`, logging failures
+///
+/// Jobs running on several gateway replicas must make their own work idempotent.
+pub fn spawn_daily<F, Fut>(name: &'static str, schedule: DailySchedule, clock: SharedClock, mut job: F)
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = crate::error::Result<()>> + Send,
+{
+    spawn_scheduled(name, clock, move |now| Some(schedule.next_run_after(now)), move |_| job());
+}
+
+/// Run `job` at every time matching `schedule`, passing it the scheduled time
+///
+/// As with [`spawn_daily`], replicas each run the job; the scheduled time lets it claim
+/// each run once.
+pub fn spawn_cron<F, Fut>(name: &'static str, schedule: CronSchedule, clock: SharedClock, job: F)
+where
+    F: FnMut(DateTime<Utc>) -> Fut + Send + 'static,
+    Fut: Future<Output = crate::error::Result<()>> + Send,
+{
+    spawn_scheduled(name, clock, move |now| schedule.next_run_after(now), job);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::{Clock, SimulatedClock};
+    use chrono::TimeZone;
+    use std::sync::{Arc, Mutex};
+
+    /// Move the simulated wall clock forward, then let tokio's paused timer catch up
+    async fn fast_forward(clock: &SimulatedClock, by: Duration) {
+        clock.advance(by);
+        tokio::time::sleep(by.to_std().unwrap()).await;
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[test]
+    fn test_next_run_is_later_today_or_tomorrow() {
+        let schedule = DailySchedule::at(1, 30).unwrap();
+
+        let before = Utc.with_ymd_and_hms(2024, 9, 23, 0, 15, 0).unwrap();
+        assert_eq!(
+            schedule.next_run_after(before),
+            Utc.with_ymd_and_hms(2024, 9, 23, 1, 30, 0).unwrap()
+        );
+
+        let exactly = Utc.with_ymd_and_hms(2024, 9, 23, 1, 30, 0).unwrap();
+        assert_eq!(
+            schedule.next_run_after(exactly),
+            Utc.with_ymd_and_hms(2024, 9, 24, 1, 30, 0).unwrap()
+        );
+
+        let after = Utc.with_ymd_and_hms(2024, 9, 30, 23, 0, 0).unwrap();
+        assert_eq!(
+            schedule.next_run_after(after),
+            Utc.with_ymd_and_hms(2024, 10, 1, 1, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_schedule_is_rejected() {
+        assert!(DailySchedule::at(24, 0).is_none());
+        assert!(DailySchedule::at(0, 60).is_none());
+        assert!(CronSchedule::parse("every quarter hour").is_err());
+        assert!(CronSchedule::parse("0 61 * * * *").is_err());
+    }
+
+    #[test]
+    fn test_cron_runs_at_next_matching_time() {
+        let schedule = CronSchedule::parse("0 */15 * * * *").unwrap();
+
+        let between = Utc.with_ymd_and_hms(2024, 9, 23, 10, 7, 30).unwrap();
+        assert_eq!(
+            schedule.next_run_after(between),
+            Some(Utc.with_ymd_and_hms(2024, 9, 23, 10, 15, 0).unwrap())
+        );
+
+        let boundary = Utc.with_ymd_and_hms(2024, 9, 23, 23, 45, 0).unwrap();
+        assert_eq!(
+            schedule.next_run_after(boundary),
+            Some(Utc.with_ymd_and_hms(2024, 9, 24, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_daily_job_runs_on_simulated_clock() {
+        tokio::time::pause();
+
+        let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2024, 9, 23, 1, 0, 0).unwrap());
+        let runs = Arc::new(Mutex::new(Vec::new()));
+
+        let job_clock = clock.clone();
+        let job_runs = Arc::clone(&runs);
+        spawn_daily("test_job", DailySchedule::at(1, 30).unwrap(), clock.shared(), move || {
+            job_runs.lock().unwrap().push(job_clock.now());
+            async { Ok(()) }
+        });
+        tokio::task::yield_now().await;
+
+        fast_forward(&clock, Duration::minutes(29)).await;
+        assert!(runs.lock().unwrap().is_empty());
+
+        fast_forward(&clock, Duration::minutes(1)).await;
+        fast_forward(&clock, Duration::hours(23)).await;
+        assert_eq!(
+            *runs.lock().unwrap(),
+            vec![Utc.with_ymd_and_hms(2024, 9, 23, 1, 30, 0).unwrap()]
+        );
+
+        fast_forward(&clock, Duration::hours(1)).await;
+        assert_eq!(runs.lock().unwrap().len(), 2);
+        assert_eq!(runs.lock().unwrap()[1], Utc.with_ymd_and_hms(2024, 9, 24, 1, 30, 0).unwrap());
+    }
+}
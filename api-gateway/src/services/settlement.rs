@@ -0,0 +1,501 @@
+use std::ops::Range;
+use std::str::FromStr;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database::schema::types::OrderSide;
+use crate::error::{ApiError, Result};
+use crate::models::trading::SettlementTrade;
+use crate::services::blockchain::{transaction_size, BlockchainService, MAX_TRANSACTION_SIZE};
+use crate::services::fee_payers::{sign_transaction, FeePayerPool};
+use crate::services::fees::set_compute_unit_limit_instruction;
+use crate::services::gateway_signer::GatewaySigner;
+use crate::services::transaction::{
+    anchor_instruction, ed25519_verify_instruction, instructions_sysvar, serialize_transaction, AccountMeta, Instruction,
+    Message, Pubkey, SIGNATURE_LENGTH,
+};
+use crate::AppState;
+
+/// Compute units of a settlement batch besides its trades, which create the batch record,
+/// and of each trade, which makes up to four token transfers
+const BATCH_BASE_UNITS: u32 = 30_000;
+const UNITS_PER_TRADE: u32 = 25_000;
+
+/// Mints the trading market settles in, with the decimals kWh and prices are scaled by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketTokens {
+    pub energy_mint: Pubkey,
+    pub energy_decimals: u8,
+    pub payment_mint: Pubkey,
+    pub payment_decimals: u8,
+}
+
+impl MarketTokens {
+    /// Energy and payment base units a trade of `quantity` kWh at `price` per kWh settles,
+    /// rounded down; `None` when either rounds to nothing or overflows
+    ///
+    /// The payment is for the energy delivered, so that the trade settles within the
+    /// commitments of both its orders.
+    pub fn amounts(&self, quantity: Decimal, price: Decimal) -> Option<(u64, u64)> {
+        let energy_amount = to_base_units(quantity, self.energy_decimals)?;
+        let delivered = Decimal::from(energy_amount).checked_div(scale(self.energy_decimals)?)?;
+        let payment_amount = to_base_units(delivered.checked_mul(price)?, self.payment_decimals)?;
+        (energy_amount > 0 && payment_amount > 0).then_some((energy_amount, payment_amount))
+    }
+
+    /// Price per kWh and quantity in base units an order of `quantity` kWh at `price`
+    /// commits to; `None` when either rounds to nothing or overflows
+    ///
+    /// Quantities round down, buy prices up and sell prices down, so that every trade the
+    /// engine matches for the order settles within its commitment.
+    pub fn commitment(&self, side: &OrderSide, quantity: Decimal, price: Decimal) -> Option<(u64, u64)> {
+        let price = price.checked_mul(scale(self.payment_decimals)?)?;
+        let price_per_kwh = match side {
+            OrderSide::Buy => price.ceil(),
+            OrderSide::Sell => price.trunc(),
+        }
+        .to_u64()?;
+        let quantity = to_base_units(quantity, self.energy_decimals)?;
+        (price_per_kwh > 0 && quantity > 0).then_some((price_per_kwh, quantity))
+    }
+}
+
+fn scale(decimals: u8) -> Option<Decimal> {
+    Some(Decimal::from(10u64.checked_pow(decimals as u32)?))
+}
+
+/// `value` in base units of a token with `decimals`, rounded down
+pub fn to_base_units(value: Decimal, decimals: u8) -> Option<u64> {
+    value.checked_mul(scale(decimals)?)?.trunc().to_u64()
+}
+
+/// Order commitment its owner signed, as the gateway received it
+#[derive(Debug, Clone)]
+pub struct CommitOrderParams {
+    pub order_id: Uuid,
+    pub owner: Pubkey,
+    pub side: OrderSide,
+    pub energy_amount: Decimal,
+    pub price_per_kwh: Decimal,
+    pub signature: [u8; SIGNATURE_LENGTH],
+}
+
+/// Outcome of submitting an order commitment, stored as its transaction job's result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitmentTransaction {
+    pub order_id: Uuid,
+    pub commitment: String,
+    pub signature: String,
+}
+
+/// Outcome of submitting a settlement batch, stored as its transaction job's result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementTransaction {
+    pub batch: u64,
+    pub trades: usize,
+    pub signature: String,
+}
+
+fn program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Result<Pubkey> {
+    Pubkey::find_program_address(seeds, program_id)
+        .map(|(address, _)| address)
+        .ok_or_else(|| ApiError::Internal("No program address for seeds".to_string()))
+}
+
+fn wallet(address: &str) -> Result<Pubkey> {
+    Pubkey::from_str(address).map_err(|e| ApiError::Internal(format!("Invalid wallet {}: {}", address, e)))
+}
+
+/// `OrderCommitment` of `owner`'s order `order_id`
+pub fn commitment_address(program_id: &Pubkey, owner: &Pubkey, order_id: Uuid) -> Result<Pubkey> {
+    program_address(&[b"order_commitment", &owner.0, order_id.as_bytes()], program_id)
+}
+
+/// Ed25519 check of the owner's signature and `commit_order` recording `order`'s commitment
+pub fn commitment_instructions(
+    program_id: Pubkey,
+    authority: Pubkey,
+    tokens: &MarketTokens,
+    order: &CommitOrderParams,
+) -> Result<Vec<Instruction>> {
+    let (price_per_kwh, quantity) = tokens
+        .commitment(&order.side, order.energy_amount, order.price_per_kwh)
+        .ok_or_else(|| ApiError::BadRequest(format!("Order {} is too small to commit in token base units", order.order_id)))?;
+    let side = match order.side {
+        OrderSide::Buy => trading::OrderType::Buy,
+        OrderSide::Sell => trading::OrderType::Sell,
+    };
+    let order_id = *order.order_id.as_bytes();
+    let message = trading::order_commitment_message(&order_id, &side, price_per_kwh, quantity);
+
+    let accounts = trading::accounts::CommitOrder {
+        market: program_address(&[b"market"], &program_id)?.into(),
+        poa_config: program_address(&[b"poa_config"], &governance::ID.into())?.into(),
+        commitment: commitment_address(&program_id, &order.owner, order.order_id)?.into(),
+        owner: order.owner.into(),
+        authority: authority.into(),
+        instructions: instructions_sysvar().into(),
+        system_program: anchor_lang::system_program::ID,
+    };
+    Ok(vec![
+        ed25519_verify_instruction(&order.owner, &message, &order.signature),
+        anchor_instruction(
+            program_id,
+            accounts,
+            trading::instruction::CommitOrder {
+                order_id,
+                side,
+                price_per_kwh,
+                quantity,
+            },
+        ),
+    ])
+}
+
+/// Compute budget and `settle_offchain_batch` instructions settling `trades` as `batch`
+///
+/// Each participant's associated energy and payment token accounts and each order's
+/// commitment are listed once and referred to by index.
+pub fn settlement_instructions(
+    program_id: Pubkey,
+    authority: Pubkey,
+    tokens: &MarketTokens,
+    batch: u64,
+    trades: &[SettlementTrade],
+) -> Result<Vec<Instruction>> {
+    let mut listed: Vec<Pubkey> = Vec::new();
+    let mut index_of = |account: Pubkey| -> Result<u8> {
+        let index = match listed.iter().position(|known| *known == account) {
+            Some(index) => index,
+            None => {
+                listed.push(account);
+                listed.len() - 1
+            }
+        };
+        u8::try_from(index).map_err(|_| ApiError::Internal("Too many settlement accounts".to_string()))
+    };
+    let token_account = |owner: &str, mint: Pubkey| -> Result<Pubkey> {
+        Ok(get_associated_token_address(&wallet(owner)?.into(), &mint.into()).into())
+    };
+    let commitment = |owner: &str, order_id: Uuid| commitment_address(&program_id, &wallet(owner)?, order_id);
+
+    let mut offchain_trades = Vec::with_capacity(trades.len());
+    for trade in trades {
+        offchain_trades.push(trading::OffchainTrade {
+            buyer_energy: index_of(token_account(&trade.buyer, tokens.energy_mint)?)?,
+            buyer_payment: index_of(token_account(&trade.buyer, tokens.payment_mint)?)?,
+            seller_energy: index_of(token_account(&trade.seller, tokens.energy_mint)?)?,
+            seller_payment: index_of(token_account(&trade.seller, tokens.payment_mint)?)?,
+            energy_amount: trade.energy_amount,
+            payment_amount: trade.payment_amount,
+            buy_commitment: index_of(commitment(&trade.buyer, trade.buy_order)?)?,
+            sell_commitment: index_of(commitment(&trade.seller, trade.sell_order)?)?,
+        });
+    }
+
+    let accounts = trading::accounts::SettleOffchainBatch {
+        market: program_address(&[b"market"], &program_id)?.into(),
+        poa_config: program_address(&[b"poa_config"], &governance::ID.into())?.into(),
+        settlement_batch: program_address(&[b"settlement_batch", &batch.to_le_bytes()], &program_id)?.into(),
+        energy_mint: tokens.energy_mint.into(),
+        authority: authority.into(),
+        fee_vault: program_address(&[trading::FeePool::Treasury.vault_seed()], &program_id)?.into(),
+        maintenance_vault: program_address(&[trading::FeePool::Maintenance.vault_seed()], &program_id)?.into(),
+        token_program: anchor_spl::token::ID,
+        system_program: anchor_lang::system_program::ID,
+    };
+    let mut instruction = anchor_instruction(
+        program_id,
+        accounts,
+        trading::instruction::SettleOffchainBatch {
+            batch,
+            trades: offchain_trades,
+        },
+    );
+    instruction.accounts.extend(listed.into_iter().map(|pubkey| AccountMeta {
+        pubkey,
+        is_signer: false,
+        is_writable: true,
+    }));
+
+    let units = BATCH_BASE_UNITS + UNITS_PER_TRADE * trades.len() as u32;
+    Ok(vec![set_compute_unit_limit_instruction(units), instruction])
+}
+
+/// Split `trades` into the longest runs that fit one settlement transaction
+///
+/// Sized as if a separate fee payer signs, so the runs fit whichever way the batch is paid for.
+pub fn chunk_trades(program_id: Pubkey, tokens: &MarketTokens, trades: &[SettlementTrade]) -> Result<Vec<Range<usize>>> {
+    let authority = Pubkey::new([1; 32]);
+    let fee_payer = Pubkey::new([2; 32]);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < trades.len() {
+        let mut end = start + 1;
+        while end < trades.len() {
+            let instructions = settlement_instructions(program_id, authority, tokens, u64::MAX, &trades[start..=end])?;
+            if transaction_size(&instructions, fee_payer)? > MAX_TRANSACTION_SIZE {
+                break;
+            }
+            end += 1;
+        }
+        chunks.push(start..end);
+        start = end;
+    }
+    Ok(chunks)
+}
+
+/// Submits settlement batches of off-chain matched trades as the market's clearing authority
+#[derive(Clone)]
+pub struct Settler {
+    chain: BlockchainService,
+    program_id: Pubkey,
+    signer: GatewaySigner,
+    fee_payers: FeePayerPool,
+}
+
+impl Settler {
+    pub fn new(chain: BlockchainService, program_id: Pubkey, signer: GatewaySigner, fee_payers: FeePayerPool) -> Self {
+        Self {
+            chain,
+            program_id,
+            signer,
+            fee_payers,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let program_id = Pubkey::from_str(&state.config.trading_program_id)
+            .map_err(|e| ApiError::Configuration(format!("Invalid TRADING_PROGRAM_ID: {}", e)))?;
+
+        Ok(Self::new(
+            state.blockchain_service.clone(),
+            program_id,
+            state.signer.clone(),
+            state.fee_payers.clone(),
+        ))
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    /// The initialized trading market
+    pub async fn market(&self) -> Result<trading::Market> {
+        let address = program_address(&[b"market"], &self.program_id)?.to_string();
+        self.chain
+            .get_anchor_account::<trading::Market>(&address)
+            .await?
+            .ok_or_else(|| ApiError::NotFound("Trading market is not initialized".to_string()))
+    }
+
+    /// Mints and decimals of the initialized trading market
+    pub async fn market_tokens(&self) -> Result<MarketTokens> {
+        self.tokens(&self.market().await?).await
+    }
+
+    /// Mints and decimals `market` settles in
+    pub async fn tokens(&self, market: &trading::Market) -> Result<MarketTokens> {
+        let decimals = |mint: anchor_lang::prelude::Pubkey| async move {
+            self.chain
+                .get_anchor_account::<anchor_spl::token::Mint>(&mint.to_string())
+                .await?
+                .map(|mint| mint.decimals)
+                .ok_or_else(|| ApiError::NotFound(format!("Mint {} does not exist", mint)))
+        };
+        Ok(MarketTokens {
+            energy_mint: market.energy_mint.into(),
+            energy_decimals: decimals(market.energy_mint).await?,
+            payment_mint: market.payment_mint.into(),
+            payment_decimals: decimals(market.payment_mint).await?,
+        })
+    }
+
+    /// Record of `batch` once the trading program has settled it
+    pub async fn settled_batch(&self, batch: u64) -> Result<Option<trading::SettlementBatch>> {
+        let address = program_address(&[b"settlement_batch", &batch.to_le_bytes()], &self.program_id)?.to_string();
+        self.chain.get_anchor_account::<trading::SettlementBatch>(&address).await
+    }
+
+    /// Submit `settle_offchain_batch` for `trades`
+    pub async fn settle(&self, batch: u64, trades: &[SettlementTrade]) -> Result<SettlementTransaction> {
+        let tokens = self.market_tokens().await?;
+        let signature = self
+            .submit(|authority| settlement_instructions(self.program_id, authority, &tokens, batch, trades))
+            .await?;
+
+        tracing::info!("Settlement batch {} of {} trades submitted in {}", batch, trades.len(), signature);
+        Ok(SettlementTransaction {
+            batch,
+            trades: trades.len(),
+            signature,
+        })
+    }
+
+    /// Submit `commit_order` recording `order`'s signed commitment
+    pub async fn commit(&self, order: &CommitOrderParams) -> Result<CommitmentTransaction> {
+        let tokens = self.market_tokens().await?;
+        let signature = self
+            .submit(|authority| commitment_instructions(self.program_id, authority, &tokens, order))
+            .await?;
+
+        tracing::info!("Commitment of order {} submitted in {}", order.order_id, signature);
+        Ok(CommitmentTransaction {
+            order_id: order.order_id,
+            commitment: commitment_address(&self.program_id, &order.owner, order.order_id)?.to_string(),
+            signature,
+        })
+    }
+
+    /// Sign `instructions`, built for the clearing authority's key, and send them
+    async fn submit(&self, instructions: impl FnOnce(Pubkey) -> Result<Vec<Instruction>>) -> Result<String> {
+        let authority = self.signer.lease().await?;
+        let fee_payer = self.fee_payers.next();
+        let instructions = instructions(authority.pubkey())?;
+
+        let blockhash = self.chain.recent_blockhash().await?;
+        let payer_key = fee_payer.as_ref().map_or_else(|| authority.pubkey(), |payer| payer.pubkey());
+        let message = Message::new(&instructions, payer_key, blockhash).map_err(ApiError::Internal)?;
+        let bytes = message.serialize();
+        let signatures = sign_transaction(message.signers(), &bytes, authority.as_ref(), fee_payer.as_deref()).await?;
+        self.chain.send_transaction(&serialize_transaction(&signatures, &bytes)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens() -> MarketTokens {
+        MarketTokens {
+            energy_mint: Pubkey::new([7; 32]),
+            energy_decimals: 3,
+            payment_mint: Pubkey::new([8; 32]),
+            payment_decimals: 6,
+        }
+    }
+
+    fn participant(seed: u8) -> String {
+        Pubkey::new([seed; 32]).to_string()
+    }
+
+    fn trade(buyer: u8, seller: u8, quantity: Decimal, price: Decimal) -> SettlementTrade {
+        let (energy_amount, payment_amount) = tokens().amounts(quantity, price).unwrap();
+        SettlementTrade {
+            buy_order: Uuid::new_v4(),
+            sell_order: Uuid::new_v4(),
+            buyer: participant(buyer),
+            seller: participant(seller),
+            energy_amount,
+            payment_amount,
+        }
+    }
+
+    #[test]
+    fn test_trades_are_scaled_to_base_units_rounding_down() {
+        // Paid for the 12.345 kWh delivered
+        assert_eq!(
+            tokens().amounts(Decimal::new(123_456, 4), Decimal::new(35, 1)),
+            Some((12_345, 43_207_500))
+        );
+
+        assert_eq!(tokens().amounts(Decimal::new(1, 4), Decimal::ONE), None);
+        assert_eq!(to_base_units(Decimal::new(-1, 0), 0), None);
+    }
+
+    #[test]
+    fn test_matched_trades_settle_within_their_commitments() {
+        let (quantity, price) = (Decimal::new(123_456, 4), Decimal::new(35_000_005, 7));
+        let commitment = |side: OrderSide| {
+            let (price_per_kwh, quantity) = tokens().commitment(&side, quantity, price).unwrap();
+            trading::OrderCommitment {
+                owner: Default::default(),
+                order_id: [0; 16],
+                side: match side {
+                    OrderSide::Buy => trading::OrderType::Buy,
+                    OrderSide::Sell => trading::OrderType::Sell,
+                },
+                price_per_kwh,
+                quantity,
+                filled: 0,
+                committed_at: 0,
+                bump: 0,
+            }
+        };
+        let (mut buy, mut sell) = (commitment(OrderSide::Buy), commitment(OrderSide::Sell));
+        assert_eq!((buy.price_per_kwh, sell.price_per_kwh), (3_500_001, 3_500_000));
+        assert_eq!(buy.quantity, 12_345);
+
+        // Both orders filled in two parts at exactly their limit price
+        for part in [Decimal::new(50_001, 4), Decimal::new(73_455, 4)] {
+            let (energy_amount, payment_amount) = tokens().amounts(part, price).unwrap();
+            buy.fill(energy_amount, payment_amount, tokens().energy_decimals).unwrap();
+            sell.fill(energy_amount, payment_amount, tokens().energy_decimals).unwrap();
+        }
+        assert_eq!(buy.filled, 12_345);
+    }
+
+    #[test]
+    fn test_participants_accounts_are_listed_once() {
+        let program_id = Pubkey::new([3; 32]);
+        let trades = vec![
+            trade(10, 11, Decimal::ONE, Decimal::ONE),
+            trade(10, 12, Decimal::ONE, Decimal::ONE),
+        ];
+        let instructions = settlement_instructions(program_id, Pubkey::new([1; 32]), &tokens(), 5, &trades).unwrap();
+        let settle = &instructions[1];
+
+        // Nine fixed accounts, two for each of the three participants and one for each of
+        // the four orders
+        assert_eq!(settle.accounts.len(), 9 + 6 + 4);
+        assert!(settle.accounts[9..].iter().all(|meta| meta.is_writable && !meta.is_signer));
+        let commitment = commitment_address(&program_id, &Pubkey::new([10; 32]), trades[1].buy_order).unwrap();
+        assert!(settle.accounts.iter().any(|meta| meta.pubkey == commitment));
+    }
+
+    #[test]
+    fn test_commitment_is_checked_before_it_is_recorded() {
+        let program_id = Pubkey::new([3; 32]);
+        let order = CommitOrderParams {
+            order_id: Uuid::new_v4(),
+            owner: Pubkey::new([10; 32]),
+            side: OrderSide::Buy,
+            energy_amount: Decimal::new(12_500, 3),
+            price_per_kwh: Decimal::new(35, 1),
+            signature: [7; SIGNATURE_LENGTH],
+        };
+        let instructions = commitment_instructions(program_id, Pubkey::new([1; 32]), &tokens(), &order).unwrap();
+
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].program_id, crate::services::transaction::ed25519_program_id());
+        let message = trading::order_commitment_message(order.order_id.as_bytes(), &trading::OrderType::Buy, 3_500_000, 12_500);
+        assert!(instructions[0].data.ends_with(&message));
+        assert_eq!(instructions[1].accounts[2].pubkey, commitment_address(&program_id, &order.owner, order.order_id).unwrap());
+
+        let dust = CommitOrderParams {
+            energy_amount: Decimal::new(1, 4),
+            ..order
+        };
+        assert!(commitment_instructions(program_id, Pubkey::new([1; 32]), &tokens(), &dust).is_err());
+    }
+
+    #[test]
+    fn test_chunks_fit_one_transaction() {
+        let program_id = Pubkey::new([3; 32]);
+        let trades: Vec<SettlementTrade> = (0..20).map(|i| trade(20 + i, 60 + i, Decimal::ONE, Decimal::ONE)).collect();
+
+        let chunks = chunk_trades(program_id, &tokens(), &trades).unwrap();
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.first().unwrap().start, 0);
+        assert_eq!(chunks.last().unwrap().end, trades.len());
+        for chunk in chunks {
+            let instructions =
+                settlement_instructions(program_id, Pubkey::new([1; 32]), &tokens(), 9, &trades[chunk]).unwrap();
+            assert!(transaction_size(&instructions, Pubkey::new([2; 32])).unwrap() <= MAX_TRANSACTION_SIZE);
+        }
+    }
+}
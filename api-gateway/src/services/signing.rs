@@ -0,0 +1,312 @@
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::Duration;
+use serde_json::json;
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{ApiError, Result};
+use crate::models::signing::SigningSession;
+use crate::services::blockchain::{BlockchainService, TransactionLifetime};
+use crate::services::transaction::{
+    serialize_transaction, verify_signature, Instruction, Message, Pubkey, SIGNATURE_LENGTH,
+};
+use crate::utils::clock::SharedClock;
+
+/// Recent blockhashes stay valid for 150 blocks (~60-90 seconds)
+const BLOCKHASH_SESSION_LIFETIME_SECS: i64 = 90;
+
+const SESSION_COLUMNS: &str = "id, created_by, description, message, required_signers, signatures, \
+    recent_blockhash, last_valid_block_height, nonce_account, status, tx_signature, error_message, \
+    expires_at, created_at, updated_at";
+
+/// Transaction to be signed by several parties
+#[derive(Debug, Clone)]
+pub struct NewSigningSession {
+    pub instructions: Vec<Instruction>,
+    pub fee_payer: Pubkey,
+    /// Durable nonce account; sessions without one expire with their blockhash
+    pub nonce_account: Option<Pubkey>,
+    pub description: Option<String>,
+}
+
+/// Builds transactions, collects partial signatures and submits once every party has signed
+#[derive(Clone)]
+pub struct SigningCoordinator {
+    db: PgPool,
+    chain: BlockchainService,
+    nonce_session_ttl: Duration,
+    clock: SharedClock,
+}
+
+impl SigningCoordinator {
+    pub fn new(db: PgPool, chain: BlockchainService, nonce_session_ttl_secs: u64, clock: SharedClock) -> Self {
+        Self {
+            db,
+            chain,
+            nonce_session_ttl: Duration::seconds(nonce_session_ttl_secs as i64),
+            clock,
+        }
+    }
+
+    pub async fn create_session(&self, created_by: Uuid, request: NewSigningSession) -> Result<SigningSession> {
+        let lifetime = match request.nonce_account {
+            Some(nonce_account) => self.chain.nonce_lifetime(nonce_account).await?.ok_or_else(|| {
+                ApiError::BadRequest(format!("Account {} is not an initialized nonce account", nonce_account))
+            })?,
+            None => self.chain.latest_lifetime().await?,
+        };
+        let (last_valid_block_height, expires_at) = match &lifetime {
+            TransactionLifetime::Nonce { .. } => (None, self.clock.now() + self.nonce_session_ttl),
+            TransactionLifetime::Blockhash {
+                last_valid_block_height,
+                ..
+            } => (
+                Some(*last_valid_block_height as i64),
+                self.clock.now() + Duration::seconds(BLOCKHASH_SESSION_LIFETIME_SECS),
+            ),
+        };
+        let recent_blockhash = lifetime.recent_blockhash();
+
+        let message = Message::new(&lifetime.instructions(&request.instructions), request.fee_payer, recent_blockhash)
+            .map_err(ApiError::BadRequest)?;
+        let required_signers: Vec<String> = message.signers().iter().map(|k| k.to_string()).collect();
+
+        let query = format!(
+            "INSERT INTO signing_sessions (created_by, description, message, required_signers, recent_blockhash, \
+             last_valid_block_height, nonce_account, expires_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING {}",
+            SESSION_COLUMNS
+        );
+
+        let session = sqlx::query_as::<_, SigningSession>(&query)
+            .bind(created_by)
+            .bind(&request.description)
+            .bind(BASE64.encode(message.serialize()))
+            .bind(Json(&required_signers))
+            .bind(Pubkey::new(recent_blockhash).to_string())
+            .bind(last_valid_block_height)
+            .bind(request.nonce_account.map(|k| k.to_string()))
+            .bind(expires_at)
+            .fetch_one(&self.db)
+            .await?;
+
+        tracing::info!(
+            "Created signing session {} requiring {} signatures",
+            session.id,
+            required_signers.len()
+        );
+
+        Ok(session)
+    }
+
+    /// Load a session, expiring it first if its blockhash or nonce window has passed
+    pub async fn get_session(&self, session_id: Uuid) -> Result<SigningSession> {
+        let session = self.load(session_id).await?;
+        self.expire_if_stale(session).await
+    }
+
+    /// Record a party's signature; submits the transaction once all signatures are present
+    pub async fn add_signature(&self, session_id: Uuid, signer: &str, signature: &str) -> Result<SigningSession> {
+        let session = self.get_session(session_id).await?;
+
+        if session.status != SigningSession::COLLECTING {
+            return Err(ApiError::Conflict(format!(
+                "Signing session {} is {}",
+                session_id, session.status
+            )));
+        }
+
+        if !session.required_signers.iter().any(|s| s == signer) {
+            return Err(ApiError::BadRequest(format!("{} is not a required signer", signer)));
+        }
+
+        let signer_key = Pubkey::from_str(signer).map_err(ApiError::BadRequest)?;
+        let signature_bytes = bs58::decode(signature)
+            .into_vec()
+            .map_err(|_| ApiError::BadRequest("Signature must be base58 encoded".to_string()))?;
+        let message = decode_message(&session)?;
+
+        if !verify_signature(&signer_key, &message, &signature_bytes) {
+            return Err(ApiError::BadRequest(format!("Invalid signature for {}", signer)));
+        }
+
+        // Merge atomically so concurrent signers do not overwrite each other
+        let query = format!(
+            "UPDATE signing_sessions SET signatures = signatures || jsonb_build_object($2::text, $3::text) \
+             WHERE id = $1 AND status = $4 RETURNING {}",
+            SESSION_COLUMNS
+        );
+        let session = sqlx::query_as::<_, SigningSession>(&query)
+            .bind(session_id)
+            .bind(signer)
+            .bind(signature)
+            .bind(SigningSession::COLLECTING)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::Conflict(format!("Signing session {} is no longer collecting", session_id)))?;
+
+        tracing::info!("Signing session {} signed by {}", session_id, signer);
+
+        if session.missing_signers().is_empty() {
+            return self.submit(session_id).await;
+        }
+
+        Ok(session)
+    }
+
+    async fn submit(&self, session_id: Uuid) -> Result<SigningSession> {
+        // Claim the session so only one request submits it
+        let Some(session) = self
+            .transition(session_id, SigningSession::COLLECTING, SigningSession::SUBMITTING, None, None)
+            .await?
+        else {
+            return self.load(session_id).await;
+        };
+
+        let message = decode_message(&session)?;
+        let signatures = session
+            .required_signers
+            .iter()
+            .map(|signer| {
+                let bytes = session
+                    .signatures
+                    .get(signer)
+                    .and_then(|s| bs58::decode(s).into_vec().ok())
+                    .ok_or_else(|| ApiError::Internal(format!("Missing signature for {}", signer)))?;
+                <[u8; SIGNATURE_LENGTH]>::try_from(bytes.as_slice())
+                    .map_err(|_| ApiError::Internal(format!("Malformed signature for {}", signer)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let transaction = serialize_transaction(&signatures, &message);
+
+        let session = match self.chain.send_transaction(&transaction).await {
+            Ok(tx_signature) => {
+                tracing::info!("Signing session {} submitted as {}", session_id, tx_signature);
+                self.transition(
+                    session_id,
+                    SigningSession::SUBMITTING,
+                    SigningSession::SUBMITTED,
+                    Some(tx_signature),
+                    None,
+                )
+                .await?
+            }
+            Err(e) => {
+                tracing::warn!("Signing session {} failed to submit: {}", session_id, e);
+                self.transition(
+                    session_id,
+                    SigningSession::SUBMITTING,
+                    SigningSession::FAILED,
+                    None,
+                    Some(e.to_string()),
+                )
+                .await?
+            }
+        };
+
+        let session = session.ok_or_else(|| ApiError::Internal(format!("Signing session {} vanished", session_id)))?;
+        self.notify_parties(&session).await;
+
+        Ok(session)
+    }
+
+    async fn expire_if_stale(&self, session: SigningSession) -> Result<SigningSession> {
+        if session.status != SigningSession::COLLECTING {
+            return Ok(session);
+        }
+
+        let stale = match session.last_valid_block_height {
+            // Blockhash sessions live exactly as long as the cluster accepts the blockhash
+            Some(_) => !self.chain.is_blockhash_valid(&session.recent_blockhash).await?,
+            None => self.clock.now() >= session.expires_at,
+        };
+
+        if !stale {
+            return Ok(session);
+        }
+
+        match self
+            .transition(session.id, SigningSession::COLLECTING, SigningSession::EXPIRED, None, None)
+            .await?
+        {
+            Some(expired) => {
+                tracing::info!("Signing session {} expired", expired.id);
+                self.notify_parties(&expired).await;
+                Ok(expired)
+            }
+            None => self.load(session.id).await,
+        }
+    }
+
+    /// Move a session between states; `None` if it was not in the expected state
+    async fn transition(
+        &self,
+        session_id: Uuid,
+        from: &str,
+        to: &str,
+        tx_signature: Option<String>,
+        error_message: Option<String>,
+    ) -> Result<Option<SigningSession>> {
+        let query = format!(
+            "UPDATE signing_sessions SET status = $3, tx_signature = COALESCE($4, tx_signature), \
+             error_message = COALESCE($5, error_message) \
+             WHERE id = $1 AND status = $2 RETURNING {}",
+            SESSION_COLUMNS
+        );
+
+        Ok(sqlx::query_as::<_, SigningSession>(&query)
+            .bind(session_id)
+            .bind(from)
+            .bind(to)
+            .bind(tx_signature)
+            .bind(error_message)
+            .fetch_optional(&self.db)
+            .await?)
+    }
+
+    async fn load(&self, session_id: Uuid) -> Result<SigningSession> {
+        let query = format!("SELECT {} FROM signing_sessions WHERE id = $1", SESSION_COLUMNS);
+
+        sqlx::query_as::<_, SigningSession>(&query)
+            .bind(session_id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Signing session {} not found", session_id)))
+    }
+
+    /// Record the outcome in the activity log of the creator and every signing party
+    async fn notify_parties(&self, session: &SigningSession) {
+        let details = json!({
+            "session_id": session.id,
+            "status": session.status,
+            "tx_signature": session.tx_signature,
+            "error": session.error_message,
+        });
+
+        let result = sqlx::query(
+            "INSERT INTO user_activities (user_id, action, details)
+             SELECT id, $3, $4 FROM users
+             WHERE id = $1 OR wallet_address = ANY(SELECT jsonb_array_elements_text($2))",
+        )
+        .bind(session.created_by)
+        .bind(&session.required_signers)
+        .bind(format!("signing_session_{}", session.status))
+        .bind(details)
+        .execute(&self.db)
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to notify parties of signing session {}: {}", session.id, e);
+        }
+    }
+}
+
+fn decode_message(session: &SigningSession) -> Result<Vec<u8>> {
+    BASE64
+        .decode(&session.message)
+        .map_err(|e| ApiError::Internal(format!("Corrupt signing session message: {}", e)))
+}
@@ -0,0 +1,470 @@
+use std::f64::consts::PI;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Timelike, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use crate::error::{ApiError, Result};
+use crate::services::chain_client::{ChainClient, MeterReadingInput};
+use crate::services::fee_payers::FeePayerPool;
+use crate::services::gateway_signer::GatewaySigner;
+use crate::services::reading_import::oracle_reading;
+use crate::services::readings::ReadingStore;
+use crate::services::timeseries::MeterSample;
+use crate::services::transaction::Pubkey;
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+/// Most virtual meters one run simulates
+pub const MAX_SIMULATED_METERS: u32 = 10_000;
+
+/// Offset of campus local time (Asia/Bangkok) from UTC, which the profiles follow
+const LOCAL_UTC_OFFSET_HOURS: f64 = 7.0;
+
+/// Share of virtual meters with rooftop solar; the rest only consume
+const SOLAR_SHARE: f64 = 0.6;
+
+/// Load-test run of virtual meters, as requested from the admin API or the command line
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SimulationRequest {
+    pub meters: u32,
+    /// Seconds between rounds, each sending one reading per meter (default 1)
+    pub tick_seconds: Option<f64>,
+    /// Seconds of profile each reading covers (default 900, a 15-minute meter interval)
+    pub reading_interval_seconds: Option<u32>,
+    /// Stop after this many seconds; without it the run continues until stopped
+    pub duration_seconds: Option<u64>,
+    /// Also record every round through the oracle program
+    pub on_chain: Option<bool>,
+    /// Seed of the meters' installations and noise, for repeatable runs (default 0)
+    pub seed: Option<u64>,
+}
+
+/// Validated simulation parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulationSettings {
+    pub meters: u32,
+    pub tick: Duration,
+    pub reading_interval: chrono::Duration,
+    pub duration: Option<Duration>,
+    pub on_chain: bool,
+    pub seed: u64,
+}
+
+impl SimulationSettings {
+    pub fn from_request(request: &SimulationRequest) -> Result<Self> {
+        if request.meters == 0 || request.meters > MAX_SIMULATED_METERS {
+            return Err(ApiError::BadRequest(format!(
+                "meters must be between 1 and {}",
+                MAX_SIMULATED_METERS
+            )));
+        }
+        let tick_seconds = request.tick_seconds.unwrap_or(1.0);
+        if !tick_seconds.is_finite() || tick_seconds < 0.01 {
+            return Err(ApiError::BadRequest("tick_seconds must be at least 0.01".to_string()));
+        }
+        let reading_interval = request.reading_interval_seconds.unwrap_or(900);
+        if reading_interval == 0 {
+            return Err(ApiError::BadRequest("reading_interval_seconds must be positive".to_string()));
+        }
+        Ok(Self {
+            meters: request.meters,
+            tick: Duration::from_secs_f64(tick_seconds),
+            reading_interval: chrono::Duration::seconds(reading_interval.into()),
+            duration: request.duration_seconds.map(Duration::from_secs),
+            on_chain: request.on_chain.unwrap_or(false),
+            seed: request.seed.unwrap_or(0),
+        })
+    }
+
+    /// Readings per second the run offers the pipeline
+    pub fn offered_rate(&self) -> f64 {
+        f64::from(self.meters) / self.tick.as_secs_f64()
+    }
+}
+
+/// Simulation requested on the command line: `--simulate-meters N [--simulate-on-chain]`
+pub fn request_from_args(args: impl IntoIterator<Item = String>) -> std::result::Result<Option<SimulationRequest>, String> {
+    let mut request: Option<SimulationRequest> = None;
+    let mut on_chain = false;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        match flag.as_str() {
+            "--simulate-meters" => {
+                let value = inline
+                    .or_else(|| args.next())
+                    .ok_or_else(|| "--simulate-meters needs a meter count".to_string())?;
+                let meters = u32::from_str(&value).map_err(|e| format!("Invalid --simulate-meters: {}", e))?;
+                request = Some(SimulationRequest {
+                    meters,
+                    ..Default::default()
+                });
+            }
+            "--simulate-on-chain" => on_chain = true,
+            _ => {}
+        }
+    }
+    if on_chain && request.is_none() {
+        return Err("--simulate-on-chain needs --simulate-meters".to_string());
+    }
+    Ok(request.map(|request| SimulationRequest {
+        on_chain: Some(on_chain),
+        ..request
+    }))
+}
+
+/// Installation of one virtual meter
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualMeter {
+    pub meter_id: String,
+    /// Rated rooftop solar output, 0 for consumers
+    pub solar_kw: f64,
+    /// Average household or room load
+    pub base_load_kw: f64,
+}
+
+/// `count` virtual meters, the same ones for the same seed
+pub fn virtual_meters(count: u32, seed: u64) -> Vec<VirtualMeter> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (1..=count)
+        .map(|i| VirtualMeter {
+            meter_id: format!("SIM-{:05}", i),
+            solar_kw: if rng.gen_bool(SOLAR_SHARE) {
+                (rng.gen_range(3.0..10.0_f64) * 10.0).round() / 10.0
+            } else {
+                0.0
+            },
+            base_load_kw: rng.gen_range(0.3..2.0),
+        })
+        .collect()
+}
+
+fn local_hour(time: DateTime<Utc>) -> f64 {
+    let hour = f64::from(time.hour()) + f64::from(time.minute()) / 60.0 + f64::from(time.second()) / 3600.0;
+    (hour + LOCAL_UTC_OFFSET_HOURS).rem_euclid(24.0)
+}
+
+/// Clear-sky share of peak irradiance at `hour`, a bell between 06:00 and 18:00 local time
+fn daylight(hour: f64) -> f64 {
+    if (6.0..18.0).contains(&hour) {
+        (PI * (hour - 6.0) / 12.0).sin().powf(1.5)
+    } else {
+        0.0
+    }
+}
+
+/// Load relative to base: a morning and a larger evening peak
+fn load_shape(hour: f64) -> f64 {
+    1.0 + 0.6 * (-(hour - 8.0).powi(2) / 2.0).exp() + 0.9 * (-(hour - 19.0).powi(2) / 4.0).exp()
+}
+
+/// Round to the 4 decimals `energy_readings` stores
+fn kwh(value: f64) -> f64 {
+    (value * 10_000.0).round() / 10_000.0
+}
+
+/// Reading of `meter` stamped `time`, covering the `interval` of profile ending at `profile_time`
+pub fn sample(
+    meter: &VirtualMeter,
+    time: DateTime<Utc>,
+    profile_time: DateTime<Utc>,
+    interval: chrono::Duration,
+    rng: &mut impl Rng,
+) -> MeterSample {
+    let hours = interval.num_seconds() as f64 / 3600.0;
+    // Profiles are taken at the middle of the interval
+    let hour = local_hour(profile_time - interval / 2);
+    let sun = daylight(hour);
+    let clouds = rng.gen_range(0.7..1.0);
+    let noise = rng.gen_range(0.9..1.1);
+
+    MeterSample {
+        meter_id: meter.meter_id.clone(),
+        time,
+        energy_generated: kwh(meter.solar_kw * sun * clouds * hours),
+        energy_consumed: kwh(meter.base_load_kw * load_shape(hour) * noise * hours),
+        irradiance: Some((1_000.0 * sun * clouds).round()),
+        temperature: Some(((27.0 + 6.0 * sun) * 10.0).round() / 10.0),
+    }
+}
+
+/// Progress and throughput of a simulation run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SimulationStats {
+    pub running: bool,
+    pub meters: u32,
+    pub on_chain: bool,
+    /// Readings per second offered to the pipeline
+    pub offered_rate: f64,
+    pub started_at: Option<DateTime<Utc>>,
+    pub stopped_at: Option<DateTime<Utc>>,
+    pub rounds: u64,
+    pub generated: u64,
+    pub stored: u64,
+    pub quarantined: u64,
+    pub on_chain_recorded: u64,
+    pub on_chain_failed: u64,
+    /// Stored readings per second since the start
+    pub stored_rate: f64,
+    /// Readings recorded on-chain per second since the start
+    pub on_chain_rate: f64,
+    /// Wall time of the latest round through ingestion and, with `on_chain`, the oracle
+    pub last_round_ms: u64,
+    pub errors: u64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct ActiveRun {
+    stats: Arc<Mutex<SimulationStats>>,
+    task: Option<JoinHandle<()>>,
+}
+
+/// The gateway's one simulation run, shared by every request
+#[derive(Clone, Default)]
+pub struct SimulationRuns {
+    current: Arc<Mutex<ActiveRun>>,
+}
+
+impl SimulationRuns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Drives virtual meters through reading ingestion and, optionally, the oracle program
+///
+/// Each round stores one reading per meter through the same screening and batch insert
+/// as imports and polled meters, then records the accepted ones with
+/// `submit_meter_readings_batch`. Rounds that take longer than the tick delay the next,
+/// so the stored and on-chain rates show where the pipeline saturates.
+#[derive(Clone)]
+pub struct Simulator {
+    readings: ReadingStore,
+    chain: ChainClient,
+    oracle_program_id: Pubkey,
+    signer: GatewaySigner,
+    fee_payers: FeePayerPool,
+    clock: SharedClock,
+    runs: SimulationRuns,
+}
+
+impl Simulator {
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        let oracle_program_id = Pubkey::from_str(&state.config.oracle_program_id)
+            .map_err(|e| ApiError::Configuration(format!("Invalid ORACLE_PROGRAM_ID: {}", e)))?;
+
+        Ok(Self {
+            readings: ReadingStore::from_state(state),
+            chain: ChainClient::from_state(state),
+            oracle_program_id,
+            signer: state.signer.clone(),
+            fee_payers: state.fee_payers.clone(),
+            clock: state.clock.clone(),
+            runs: state.simulation.clone(),
+        })
+    }
+
+    /// Stats of the current or latest run
+    pub fn status(&self) -> SimulationStats {
+        let run = self.runs.current.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = run.stats.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        stats
+    }
+
+    /// Start a run, unless one is already running
+    pub fn start(&self, settings: SimulationSettings) -> Result<SimulationStats> {
+        let mut run = self.runs.current.lock().unwrap_or_else(|e| e.into_inner());
+        if run.task.as_ref().is_some_and(|task| !task.is_finished()) {
+            return Err(ApiError::Conflict("A simulation is already running".to_string()));
+        }
+
+        let stats = Arc::new(Mutex::new(SimulationStats {
+            running: true,
+            meters: settings.meters,
+            on_chain: settings.on_chain,
+            offered_rate: settings.offered_rate(),
+            started_at: Some(self.clock.now()),
+            ..Default::default()
+        }));
+        let simulator = self.clone();
+        let task_stats = Arc::clone(&stats);
+        run.task = Some(tokio::spawn(async move {
+            simulator.run(settings, &task_stats).await;
+            finish(&task_stats, simulator.clock.now());
+        }));
+        run.stats = stats;
+
+        tracing::info!(
+            "Simulating {} meters at {:.1} readings/s{}",
+            settings.meters,
+            settings.offered_rate(),
+            if settings.on_chain { " through the oracle program" } else { "" }
+        );
+        let stats = run.stats.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        Ok(stats)
+    }
+
+    /// Stop the running simulation, returning its final stats
+    pub fn stop(&self) -> Result<SimulationStats> {
+        let run = self.runs.current.lock().unwrap_or_else(|e| e.into_inner());
+        match run.task.as_ref().filter(|task| !task.is_finished()) {
+            Some(task) => task.abort(),
+            None => return Err(ApiError::NotFound("No simulation is running".to_string())),
+        }
+        finish(&run.stats, self.clock.now());
+        let stats = run.stats.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        Ok(stats)
+    }
+
+    async fn run(&self, settings: SimulationSettings, stats: &Mutex<SimulationStats>) {
+        let mut meters: Vec<(VirtualMeter, StdRng)> = virtual_meters(settings.meters, settings.seed)
+            .into_iter()
+            .enumerate()
+            .map(|(i, meter)| (meter, StdRng::seed_from_u64(settings.seed ^ (i as u64 + 1))))
+            .collect();
+        let started = Instant::now();
+        let mut profile_time = self.clock.now();
+        let mut interval = tokio::time::interval(settings.tick);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            if settings.duration.is_some_and(|duration| started.elapsed() >= duration) {
+                break;
+            }
+            profile_time += settings.reading_interval;
+
+            let round_started = Instant::now();
+            let now = self.clock.now();
+            let samples: Vec<MeterSample> = meters
+                .iter_mut()
+                .map(|(meter, rng)| sample(meter, now, profile_time, settings.reading_interval, rng))
+                .collect();
+            let outcome = self.round(&samples, settings.on_chain, now).await;
+
+            let mut stats = stats.lock().unwrap_or_else(|e| e.into_inner());
+            stats.rounds += 1;
+            stats.generated += samples.len() as u64;
+            match outcome {
+                Ok(round) => {
+                    stats.stored += round.stored;
+                    stats.quarantined += round.quarantined;
+                    stats.on_chain_recorded += round.on_chain_recorded;
+                    stats.on_chain_failed += round.on_chain_failed;
+                }
+                Err(e) => {
+                    tracing::warn!("Simulation round failed: {}", e);
+                    stats.errors += 1;
+                    stats.last_error = Some(e.to_string());
+                }
+            }
+            let elapsed = started.elapsed().as_secs_f64().max(f64::EPSILON);
+            stats.stored_rate = stats.stored as f64 / elapsed;
+            stats.on_chain_rate = stats.on_chain_recorded as f64 / elapsed;
+            stats.last_round_ms = round_started.elapsed().as_millis() as u64;
+            metrics::counter!("simulation_readings_total").increment(samples.len() as u64);
+        }
+    }
+
+    async fn round(&self, samples: &[MeterSample], on_chain: bool, now: DateTime<Utc>) -> Result<RoundOutcome> {
+        let recorded = self.readings.record_samples(samples, now).await?;
+        let mut outcome = RoundOutcome {
+            stored: recorded.stored,
+            quarantined: recorded.quarantined as u64,
+            ..Default::default()
+        };
+        if !on_chain || recorded.accepted.is_empty() {
+            return Ok(outcome);
+        }
+
+        let readings: Vec<MeterReadingInput> = recorded.accepted.iter().map(oracle_reading).collect();
+        let authority = self.signer.lease().await?;
+        let fee_payer = self.fee_payers.next();
+        let results = self
+            .chain
+            .submit_meter_readings_batch(self.oracle_program_id, authority.as_ref(), fee_payer.as_deref(), &readings)
+            .await?;
+        for result in results {
+            match result.error {
+                None => outcome.on_chain_recorded += 1,
+                Some(_) => outcome.on_chain_failed += 1,
+            }
+        }
+        Ok(outcome)
+    }
+}
+
+#[derive(Debug, Default)]
+struct RoundOutcome {
+    stored: u64,
+    quarantined: u64,
+    on_chain_recorded: u64,
+    on_chain_failed: u64,
+}
+
+fn finish(stats: &Mutex<SimulationStats>, now: DateTime<Utc>) {
+    let mut stats = stats.lock().unwrap_or_else(|e| e.into_inner());
+    if stats.running {
+        stats.running = false;
+        stats.stopped_at = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_request_from_args() {
+        assert!(request_from_args(args("--port 8080")).unwrap().is_none());
+
+        let request = request_from_args(args("--simulate-meters 200")).unwrap().unwrap();
+        assert_eq!((request.meters, request.on_chain), (200, Some(false)));
+        let request = request_from_args(args("--simulate-on-chain --simulate-meters=50")).unwrap().unwrap();
+        assert_eq!((request.meters, request.on_chain), (50, Some(true)));
+
+        assert!(request_from_args(args("--simulate-meters")).is_err());
+        assert!(request_from_args(args("--simulate-on-chain")).is_err());
+
+        let settings = SimulationSettings::from_request(&request).unwrap();
+        assert_eq!(settings.offered_rate(), 50.0);
+        assert!(SimulationSettings::from_request(&SimulationRequest::default()).is_err());
+    }
+
+    #[test]
+    fn test_profiles_follow_the_campus_day() {
+        let meters = virtual_meters(20, 7);
+        assert_eq!(meters, virtual_meters(20, 7));
+        assert_eq!(meters[0].meter_id, "SIM-00001");
+        let meter = meters.iter().find(|meter| meter.solar_kw > 0.0).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let interval = chrono::Duration::minutes(15);
+        // 12:15 and 00:15 in Bangkok
+        let noon = Utc.with_ymd_and_hms(2024, 9, 23, 5, 15, 0).unwrap();
+        let midnight = Utc.with_ymd_and_hms(2024, 9, 23, 17, 15, 0).unwrap();
+
+        let day = sample(meter, noon, noon, interval, &mut rng);
+        assert!(day.energy_generated > 0.5 * meter.solar_kw * 0.25);
+        assert!(day.energy_generated <= meter.solar_kw * 0.25);
+        let night = sample(meter, midnight, midnight, interval, &mut rng);
+        assert_eq!(night.energy_generated, 0.0);
+        assert!(night.energy_consumed > 0.0);
+        assert_eq!(night.time, midnight);
+    }
+}
@@ -0,0 +1,346 @@
+use redis::AsyncCommands;
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::api_keys::MAX_RATE_LIMIT_PER_MINUTE;
+use crate::database::redis_pool::RedisPool;
+use crate::database::schema::types::OrderStatus;
+use crate::error::{ApiError, Result};
+use crate::models::tenant::{Tenant, TenantUsage};
+use crate::services::metrics::record_cache_lookup;
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+const TENANT_COLUMNS: &str = "id, name, kind, requests_per_minute, created_at, updated_at";
+
+const RATE_KEY_PREFIX: &str = "tenants:rate:";
+
+const LIMIT_CACHE_PREFIX: &str = "tenants:limit:";
+
+#[derive(Debug, Deserialize)]
+pub struct TenantRequest {
+    /// Short lowercase identifier carried in tokens, e.g. `eng` or `bldg-12`
+    pub id: String,
+    pub name: String,
+    /// faculty or building
+    pub kind: String,
+    /// Defaults to `TENANT_RATE_LIMIT_PER_MINUTE`
+    pub requests_per_minute: Option<i32>,
+}
+
+fn validate_id(id: &str) -> Result<()> {
+    let valid = !id.is_empty()
+        && id.len() <= 32
+        && id.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::Validation(
+            "Tenant ID must be 1-32 lowercase letters, digits, - or _".to_string(),
+        ))
+    }
+}
+
+pub fn validate_rate_limit(requests_per_minute: Option<i32>) -> Result<()> {
+    match requests_per_minute {
+        Some(limit) if !(1..=MAX_RATE_LIMIT_PER_MINUTE).contains(&limit) => Err(ApiError::Validation(format!(
+            "requests_per_minute must be between 1 and {}",
+            MAX_RATE_LIMIT_PER_MINUTE
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn validate_request(request: &TenantRequest) -> Result<()> {
+    validate_id(&request.id)?;
+    if request.name.trim().is_empty() || request.name.len() > 255 {
+        return Err(ApiError::Validation("Tenant name must be 1-255 characters".to_string()));
+    }
+    if !Tenant::KINDS.contains(&request.kind.as_str()) {
+        return Err(ApiError::Validation(format!(
+            "Unknown tenant kind {}; expected one of {}",
+            request.kind,
+            Tenant::KINDS.join(", ")
+        )));
+    }
+    validate_rate_limit(request.requests_per_minute)
+}
+
+/// Buildings and faculties in Postgres, with their shared request budgets counted in Redis
+#[derive(Clone)]
+pub struct TenantStore {
+    db: PgPool,
+    redis: RedisPool,
+    clock: SharedClock,
+    default_limit: i32,
+    cache_ttl: u64,
+}
+
+impl TenantStore {
+    pub fn new(db: PgPool, redis: RedisPool, clock: SharedClock, default_limit: i32, cache_ttl_secs: u64) -> Self {
+        Self {
+            db,
+            redis,
+            clock,
+            default_limit,
+            cache_ttl: cache_ttl_secs,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(
+            state.db.clone(),
+            state.redis.clone(),
+            state.clock.clone(),
+            state.config.tenant_rate_limit_per_minute,
+            state.config.permission_cache_ttl,
+        )
+    }
+
+    pub async fn list(&self) -> Result<Vec<Tenant>> {
+        let query = format!("SELECT {} FROM tenants ORDER BY kind, id", TENANT_COLUMNS);
+        Ok(sqlx::query_as::<_, Tenant>(&query).fetch_all(&self.db).await?)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Tenant> {
+        let query = format!("SELECT {} FROM tenants WHERE id = $1", TENANT_COLUMNS);
+        sqlx::query_as::<_, Tenant>(&query)
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Tenant {} not found", id)))
+    }
+
+    pub async fn create(&self, request: &TenantRequest) -> Result<Tenant> {
+        validate_request(request)?;
+        let query = format!(
+            "INSERT INTO tenants (id, name, kind, requests_per_minute) VALUES ($1, $2, $3, $4)
+             RETURNING {}",
+            TENANT_COLUMNS
+        );
+        sqlx::query_as::<_, Tenant>(&query)
+            .bind(&request.id)
+            .bind(request.name.trim())
+            .bind(&request.kind)
+            .bind(request.requests_per_minute)
+            .fetch_one(&self.db)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+                    ApiError::Conflict(format!("Tenant {} already exists", request.id))
+                }
+                e => e.into(),
+            })
+    }
+
+    /// Change a tenant's request budget; `None` returns it to the gateway default
+    pub async fn set_rate_limit(&self, id: &str, requests_per_minute: Option<i32>) -> Result<Tenant> {
+        validate_rate_limit(requests_per_minute)?;
+        let query = format!(
+            "UPDATE tenants SET requests_per_minute = $2 WHERE id = $1 RETURNING {}",
+            TENANT_COLUMNS
+        );
+        let tenant = sqlx::query_as::<_, Tenant>(&query)
+            .bind(id)
+            .bind(requests_per_minute)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Tenant {} not found", id)))?;
+
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            conn.del::<_, ()>(limit_cache_key(id)).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to invalidate cached rate limit of tenant {}: {}", id, e);
+        }
+        Ok(tenant)
+    }
+
+    /// Move a user into a tenant, or out of every tenant; takes effect at their next sign-in
+    /// or token refresh
+    pub async fn assign_user(&self, user_id: Uuid, tenant: Option<&str>) -> Result<()> {
+        if let Some(tenant) = tenant {
+            self.get(tenant).await?;
+        }
+        let updated = sqlx::query("UPDATE users SET tenant_id = $2 WHERE id = $1")
+            .bind(user_id)
+            .bind(tenant)
+            .execute(&self.db)
+            .await?
+            .rows_affected();
+        if updated == 0 {
+            return Err(ApiError::NotFound(format!("User {} not found", user_id)));
+        }
+        Ok(())
+    }
+
+    /// Move a meter, and so its readings, into a tenant or out of every tenant
+    pub async fn assign_meter(&self, meter_id: &str, tenant: Option<&str>) -> Result<()> {
+        if let Some(tenant) = tenant {
+            self.get(tenant).await?;
+        }
+        let updated = sqlx::query("UPDATE meters SET tenant_id = $2 WHERE meter_id = $1")
+            .bind(meter_id)
+            .bind(tenant)
+            .execute(&self.db)
+            .await?
+            .rows_affected();
+        if updated == 0 {
+            return Err(ApiError::NotFound(format!("Meter {} not found", meter_id)));
+        }
+        Ok(())
+    }
+
+    /// What a tenant holds and the requests it made this minute
+    pub async fn usage(&self, tenant: &Tenant) -> Result<TenantUsage> {
+        let since = self.clock.now() - chrono::Duration::hours(24);
+        let mut usage = sqlx::query_as::<_, TenantUsage>(
+            "SELECT $1::varchar AS tenant_id,
+                    (SELECT COUNT(*) FROM users WHERE tenant_id = $1) AS users,
+                    (SELECT COUNT(*) FROM meters WHERE tenant_id = $1) AS meters,
+                    (SELECT COUNT(*) FROM energy_readings r
+                     JOIN meters m ON m.meter_id = r.meter_id
+                     WHERE m.tenant_id = $1 AND r.timestamp >= $2) AS readings_24h,
+                    (SELECT COUNT(*) FROM trading_orders o
+                     JOIN users u ON u.id = o.user_id
+                     WHERE u.tenant_id = $1 AND o.status IN ($3, $4)) AS open_orders,
+                    (SELECT COUNT(*) FROM erc_certificate_tenants WHERE tenant_id = $1) AS certificates",
+        )
+        .bind(&tenant.id)
+        .bind(since)
+        .bind(OrderStatus::Pending)
+        .bind(OrderStatus::Active)
+        .fetch_one(&self.db)
+        .await?;
+
+        usage.requests_per_minute = tenant.requests_per_minute.unwrap_or(self.default_limit);
+        let key = rate_key(&tenant.id, self.clock.now().timestamp());
+        let counted = async {
+            let mut conn = self.redis.get().await?;
+            conn.get::<_, Option<i64>>(key).await
+        }
+        .await;
+        match counted {
+            Ok(count) => usage.requests_this_minute = count.unwrap_or(0),
+            Err(e) => tracing::warn!("Tenant request counter unavailable: {}", e),
+        }
+        Ok(usage)
+    }
+
+    /// Requests per minute `tenant` may make
+    async fn rate_limit(&self, tenant: &str) -> Result<i32> {
+        let cached = async {
+            let mut conn = self.redis.get().await?;
+            conn.get::<_, Option<i32>>(limit_cache_key(tenant)).await
+        }
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("Tenant rate limit cache unavailable: {}", e);
+            None
+        });
+        record_cache_lookup("tenant_limits", cached.is_some());
+        if let Some(limit) = cached {
+            return Ok(limit);
+        }
+
+        let limit = sqlx::query_scalar::<_, Option<i32>>("SELECT requests_per_minute FROM tenants WHERE id = $1")
+            .bind(tenant)
+            .fetch_optional(&self.db)
+            .await?
+            .flatten()
+            .unwrap_or(self.default_limit);
+
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            conn.set_ex::<_, _, ()>(limit_cache_key(tenant), limit, self.cache_ttl).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to cache rate limit of tenant {}: {}", tenant, e);
+        }
+        Ok(limit)
+    }
+
+    /// Count a request against the tenant's per-minute budget
+    ///
+    /// Like API key limits this is best effort: requests are admitted when Redis is unavailable.
+    pub async fn check_rate_limit(&self, tenant: &str) -> Result<()> {
+        metrics::counter!("tenant_requests_total", "tenant" => tenant.to_string()).increment(1);
+
+        let limit = self.rate_limit(tenant).await?;
+        let key = rate_key(tenant, self.clock.now().timestamp());
+        let result = async {
+            let mut conn = self.redis.get().await?;
+            redis::pipe()
+                .atomic()
+                .incr(&key, 1)
+                .expire(&key, 60)
+                .ignore()
+                .query_async::<_, (i64,)>(&mut conn)
+                .await
+        }
+        .await;
+
+        match result {
+            Ok((count,)) if count > i64::from(limit) => {
+                if count == i64::from(limit) + 1 {
+                    tracing::warn!("Tenant {} exceeded {} requests per minute", tenant, limit);
+                }
+                metrics::counter!("tenant_requests_rejected_total", "tenant" => tenant.to_string()).increment(1);
+                Err(ApiError::RateLimit)
+            }
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::warn!("Tenant rate limiter unavailable: {}", e);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Redis counter for a tenant's requests in the minute containing `timestamp`
+fn rate_key(tenant: &str, timestamp: i64) -> String {
+    format!("{}{}:{}", RATE_KEY_PREFIX, tenant, timestamp.div_euclid(60))
+}
+
+fn limit_cache_key(tenant: &str) -> String {
+    format!("{}{}", LIMIT_CACHE_PREFIX, tenant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: &str, kind: &str, requests_per_minute: Option<i32>) -> TenantRequest {
+        TenantRequest {
+            id: id.to_string(),
+            name: "Faculty of Engineering".to_string(),
+            kind: kind.to_string(),
+            requests_per_minute,
+        }
+    }
+
+    #[test]
+    fn test_validate_request() {
+        assert!(validate_request(&request("eng", "faculty", None)).is_ok());
+        assert!(validate_request(&request("bldg-12", "building", Some(1_200))).is_ok());
+
+        assert!(validate_request(&request("Eng", "faculty", None)).is_err());
+        assert!(validate_request(&request("-eng", "faculty", None)).is_err());
+        assert!(validate_request(&request(&"e".repeat(33), "faculty", None)).is_err());
+        assert!(validate_request(&request("eng", "department", None)).is_err());
+        assert!(validate_request(&request("eng", "faculty", Some(0))).is_err());
+    }
+
+    #[test]
+    fn test_rate_keys_roll_over_each_minute() {
+        assert_eq!(rate_key("eng", 119), "tenants:rate:eng:1");
+        assert_eq!(rate_key("eng", 120), "tenants:rate:eng:2");
+    }
+}
@@ -0,0 +1,193 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::error::{ApiError, Result};
+use crate::models::energy::{EnergyPoint, EnergyReadingSubmission, MeterGeneration};
+use crate::AppState;
+
+/// Width of the per-meter energy buckets served to charts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum EnergyBucket {
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[default]
+    #[serde(rename = "1h")]
+    Hour,
+    #[serde(rename = "1d")]
+    Day,
+}
+
+impl EnergyBucket {
+    /// Most buckets one chart query may span
+    const MAX_BUCKETS: i64 = 2_000;
+
+    /// Continuous aggregate holding this bucket width
+    fn view(self) -> &'static str {
+        match self {
+            Self::FifteenMinutes => "meter_energy_15m",
+            Self::Hour => "meter_energy_hourly",
+            Self::Day => "meter_energy_daily",
+        }
+    }
+
+    fn seconds(self) -> i64 {
+        match self {
+            Self::FifteenMinutes => 15 * 60,
+            Self::Hour => 60 * 60,
+            Self::Day => 24 * 60 * 60,
+        }
+    }
+
+    /// Reject ranges that are inverted or would return an unreasonable number of buckets
+    pub fn check_range(self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<()> {
+        if end <= start {
+            return Err(ApiError::BadRequest("end_time must be after start_time".to_string()));
+        }
+        if (end - start).num_seconds() / self.seconds() > Self::MAX_BUCKETS {
+            return Err(ApiError::BadRequest(format!(
+                "Range spans more than {} buckets; use a wider bucket",
+                Self::MAX_BUCKETS
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Meter reading as written to the TimescaleDB hypertable
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeterSample {
+    pub meter_id: String,
+    pub time: DateTime<Utc>,
+    pub energy_generated: f64,
+    pub energy_consumed: f64,
+    pub irradiance: Option<f64>,
+    pub temperature: Option<f64>,
+}
+
+impl From<&EnergyReadingSubmission> for MeterSample {
+    fn from(reading: &EnergyReadingSubmission) -> Self {
+        Self {
+            meter_id: reading.meter_id.clone(),
+            time: reading.timestamp,
+            energy_generated: reading.energy_generated,
+            energy_consumed: reading.energy_consumed,
+            irradiance: reading.solar_irradiance,
+            temperature: reading.temperature,
+        }
+    }
+}
+
+/// Meter readings in the TimescaleDB `energy_readings` hypertable and its energy rollups
+#[derive(Clone)]
+pub struct TimeseriesStore {
+    db: sqlx::PgPool,
+}
+
+impl TimeseriesStore {
+    pub fn new(db: sqlx::PgPool) -> Self {
+        Self { db }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.timescale_db.clone())
+    }
+
+    /// Write a batch of readings in one statement
+    #[tracing::instrument(name = "timescale_ingest", skip_all, fields(samples = samples.len()))]
+    pub async fn ingest(&self, samples: &[MeterSample]) -> Result<u64> {
+        if samples.is_empty() {
+            return Ok(0);
+        }
+
+        let meter_ids: Vec<&str> = samples.iter().map(|s| s.meter_id.as_str()).collect();
+        let times: Vec<DateTime<Utc>> = samples.iter().map(|s| s.time).collect();
+        let generated: Vec<f64> = samples.iter().map(|s| s.energy_generated).collect();
+        let consumed: Vec<f64> = samples.iter().map(|s| s.energy_consumed).collect();
+        let irradiance: Vec<Option<f64>> = samples.iter().map(|s| s.irradiance).collect();
+        let temperature: Vec<Option<f64>> = samples.iter().map(|s| s.temperature).collect();
+
+        let result = sqlx::query(
+            "INSERT INTO energy_readings (meter_id, time, energy_generated, energy_consumed, irradiance, temperature)
+             SELECT * FROM UNNEST($1::varchar[], $2::timestamptz[], $3::float8[], $4::float8[], $5::float8[], $6::float8[])",
+        )
+        .bind(&meter_ids)
+        .bind(&times)
+        .bind(&generated)
+        .bind(&consumed)
+        .bind(&irradiance)
+        .bind(&temperature)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Energy generated and consumed by `meter_id` per bucket, oldest first
+    #[tracing::instrument(name = "timescale_energy_series", skip(self))]
+    pub async fn energy_series(
+        &self,
+        meter_id: &str,
+        bucket: EnergyBucket,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<EnergyPoint>> {
+        bucket.check_range(start, end)?;
+
+        let query = format!(
+            "SELECT bucket, energy_generated::float8 AS energy_generated, \
+             energy_consumed::float8 AS energy_consumed, reading_count \
+             FROM {} WHERE meter_id = $1 AND bucket >= $2 AND bucket < $3 \
+             ORDER BY bucket",
+            bucket.view()
+        );
+        let points = sqlx::query_as::<_, EnergyPoint>(&query)
+            .bind(meter_id)
+            .bind(start)
+            .bind(end)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(points)
+    }
+
+    /// Net generation of each of `meter_ids` with readings in `[start, end)`, by meter ID
+    ///
+    /// Whole hours only: `start` and `end` are expected on hour boundaries.
+    #[tracing::instrument(name = "timescale_net_generation", skip(self))]
+    pub async fn net_generation(
+        &self,
+        meter_ids: &[String],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<MeterGeneration>> {
+        let generation = sqlx::query_as::<_, MeterGeneration>(
+            "SELECT meter_id, SUM(GREATEST(energy_generated - energy_consumed, 0))::float8 AS net_kwh, \
+             SUM(reading_count)::int8 AS reading_count \
+             FROM meter_energy_hourly WHERE meter_id = ANY($1) AND bucket >= $2 AND bucket < $3 \
+             GROUP BY meter_id ORDER BY meter_id",
+        )
+        .bind(meter_ids)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(generation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_bucket_names_and_ranges() {
+        let bucket: EnergyBucket = serde_json::from_str("\"15m\"").unwrap();
+        assert_eq!(bucket.view(), "meter_energy_15m");
+        assert!(serde_json::from_str::<EnergyBucket>("\"5m\"").is_err());
+
+        let start = Utc::now();
+        assert!(EnergyBucket::Hour.check_range(start, start + Duration::days(30)).is_ok());
+        assert!(EnergyBucket::Hour.check_range(start, start - Duration::hours(1)).is_err());
+        assert!(EnergyBucket::FifteenMinutes.check_range(start, start + Duration::days(30)).is_err());
+        assert!(EnergyBucket::Day.check_range(start, start + Duration::days(365 * 5)).is_ok());
+    }
+}
@@ -0,0 +1,514 @@
+// Minimal Solana transaction encoding for transactions built by the gateway.
+// Implements the legacy message wire format, which is all the gateway needs to
+// collect signatures and submit through JSON-RPC without pulling in the Solana SDK.
+// Instructions to the GridTokenX programs are built from the program crates' generated
+// types so their layout cannot drift from the on-chain code.
+
+use std::fmt;
+use std::str::FromStr;
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+
+pub const SIGNATURE_LENGTH: usize = 64;
+
+/// Base58-encoded 32-byte account address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pubkey(pub [u8; 32]);
+
+impl Pubkey {
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Pubkey(bytes)
+    }
+
+    pub fn to_bytes(self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Program derived address for `seeds`: the first bump, from 255 down, that lands off the curve
+    pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Option<(Pubkey, u8)> {
+        (0..=u8::MAX).rev().find_map(|bump| {
+            let mut hasher = Sha256::new();
+            for seed in seeds {
+                hasher.update(seed);
+            }
+            hasher.update([bump]);
+            hasher.update(program_id.0);
+            hasher.update(b"ProgramDerivedAddress");
+            let address: [u8; 32] = hasher.finalize().into();
+
+            // Valid ed25519 points could have a private key, so they are skipped
+            VerifyingKey::from_bytes(&address).is_err().then_some((Pubkey(address), bump))
+        })
+    }
+}
+
+impl FromStr for Pubkey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = bs58::decode(s)
+            .into_vec()
+            .map_err(|_| format!("Invalid base58 public key: {}", s))?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| format!("Invalid public key length: {}", s))?;
+        Ok(Pubkey(bytes))
+    }
+}
+
+#[cfg(feature = "chain")]
+impl From<anchor_lang::prelude::Pubkey> for Pubkey {
+    fn from(pubkey: anchor_lang::prelude::Pubkey) -> Self {
+        Pubkey(pubkey.to_bytes())
+    }
+}
+
+#[cfg(feature = "chain")]
+impl From<Pubkey> for anchor_lang::prelude::Pubkey {
+    fn from(pubkey: Pubkey) -> Self {
+        anchor_lang::prelude::Pubkey::new_from_array(pubkey.0)
+    }
+}
+
+impl fmt::Display for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", bs58::encode(self.0).into_string())
+    }
+}
+
+pub const SYSTEM_PROGRAM_ID: Pubkey = Pubkey([0; 32]);
+
+/// `SysvarRecentB1ockHashes11111111111111111111`, required by `AdvanceNonceAccount`
+pub fn recent_blockhashes_sysvar() -> Pubkey {
+    Pubkey::from_str("SysvarRecentB1ockHashes11111111111111111111").expect("valid sysvar address")
+}
+
+/// `Sysvar1nstructions1111111111111111111111111`, used by programs to inspect sibling instructions
+pub fn instructions_sysvar() -> Pubkey {
+    Pubkey::from_str("Sysvar1nstructions1111111111111111111111111").expect("valid sysvar address")
+}
+
+pub fn ed25519_program_id() -> Pubkey {
+    Pubkey::from_str("Ed25519SigVerify111111111111111111111111111").expect("valid program address")
+}
+
+/// First 8 bytes of an Anchor instruction's data; tests check generated instruction
+/// data against it
+#[cfg(test)]
+pub fn anchor_discriminator(instruction_name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("global:{}", instruction_name).as_bytes());
+    hash[..8].try_into().expect("sha256 output is 32 bytes")
+}
+
+/// First 8 bytes of an Anchor account's data
+pub fn anchor_account_discriminator(account_name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("account:{}", account_name).as_bytes());
+    hash[..8].try_into().expect("sha256 output is 32 bytes")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+/// Instruction to an Anchor program from its crate's generated `accounts::*` and
+/// `instruction::*` types
+#[cfg(feature = "chain")]
+pub fn anchor_instruction(
+    program_id: Pubkey,
+    accounts: impl anchor_lang::ToAccountMetas,
+    data: impl anchor_lang::InstructionData,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: accounts
+            .to_account_metas(None)
+            .into_iter()
+            .map(|meta| AccountMeta {
+                pubkey: meta.pubkey.into(),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: data.data(),
+    }
+}
+
+/// System program `AdvanceNonceAccount`; must be the first instruction of a durable nonce transaction
+pub fn advance_nonce_instruction(nonce_account: Pubkey, nonce_authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: SYSTEM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta { pubkey: nonce_account, is_signer: false, is_writable: true },
+            AccountMeta { pubkey: recent_blockhashes_sysvar(), is_signer: false, is_writable: false },
+            AccountMeta { pubkey: nonce_authority, is_signer: true, is_writable: false },
+        ],
+        data: 4u32.to_le_bytes().to_vec(),
+    }
+}
+
+/// Base58 ed25519 signature, if `signature` is one
+pub fn decode_signature(signature: &str) -> Option<[u8; SIGNATURE_LENGTH]> {
+    bs58::decode(signature).into_vec().ok()?.try_into().ok()
+}
+
+/// Ed25519 program instruction verifying one signature carried in its own data
+pub fn ed25519_verify_instruction(pubkey: &Pubkey, message: &[u8], signature: &[u8; SIGNATURE_LENGTH]) -> Instruction {
+    // Header: signature count, padding, then offsets of the signature, key and message,
+    // each paired with an instruction index (u16::MAX meaning this instruction)
+    const DATA_START: u16 = 16;
+    let public_key_offset = DATA_START;
+    let signature_offset = public_key_offset + 32;
+    let message_offset = signature_offset + SIGNATURE_LENGTH as u16;
+
+    let mut data = Vec::with_capacity(message_offset as usize + message.len());
+    data.extend_from_slice(&[1, 0]);
+    for value in [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(&pubkey.0);
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program_id(),
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+/// Initialized durable nonce account state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonceState {
+    pub authority: Pubkey,
+    pub nonce: [u8; 32],
+}
+
+/// Parse system program nonce account data (`Versions` -> `State::Initialized`)
+pub fn parse_nonce_account(data: &[u8]) -> Option<NonceState> {
+    if data.len() < 72 {
+        return None;
+    }
+
+    let state = u32::from_le_bytes(data[4..8].try_into().ok()?);
+    if state != 1 {
+        return None;
+    }
+
+    Some(NonceState {
+        authority: Pubkey(data[8..40].try_into().ok()?),
+        nonce: data[40..72].try_into().ok()?,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageHeader {
+    pub num_required_signatures: u8,
+    pub num_readonly_signed_accounts: u8,
+    pub num_readonly_unsigned_accounts: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledInstruction {
+    pub program_id_index: u8,
+    pub accounts: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+/// Legacy transaction message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub header: MessageHeader,
+    pub account_keys: Vec<Pubkey>,
+    pub recent_blockhash: [u8; 32],
+    pub instructions: Vec<CompiledInstruction>,
+}
+
+impl Message {
+    /// Compile instructions into a message paid for by `fee_payer`
+    ///
+    /// Accounts are deduplicated (merging signer/writable flags) and ordered as the runtime
+    /// expects: fee payer, writable signers, read-only signers, writable and read-only non-signers.
+    pub fn new(instructions: &[Instruction], fee_payer: Pubkey, recent_blockhash: [u8; 32]) -> Result<Self, String> {
+        let mut metas: Vec<AccountMeta> = vec![AccountMeta {
+            pubkey: fee_payer,
+            is_signer: true,
+            is_writable: true,
+        }];
+
+        let mut add_meta = |meta: AccountMeta| match metas.iter_mut().find(|m| m.pubkey == meta.pubkey) {
+            Some(existing) => {
+                existing.is_signer |= meta.is_signer;
+                existing.is_writable |= meta.is_writable;
+            }
+            None => metas.push(meta),
+        };
+
+        for instruction in instructions {
+            for meta in &instruction.accounts {
+                add_meta(meta.clone());
+            }
+            add_meta(AccountMeta {
+                pubkey: instruction.program_id,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        // Stable sort keeps first-appearance order within each category
+        metas.sort_by_key(|m| (m.pubkey != fee_payer, !m.is_signer, !m.is_writable));
+
+        if metas.len() > u8::MAX as usize + 1 {
+            return Err(format!("Too many accounts in transaction: {}", metas.len()));
+        }
+
+        let count = |pred: fn(&AccountMeta) -> bool| metas.iter().filter(|m| pred(m)).count() as u8;
+        let header = MessageHeader {
+            num_required_signatures: count(|m| m.is_signer),
+            num_readonly_signed_accounts: count(|m| m.is_signer && !m.is_writable),
+            num_readonly_unsigned_accounts: count(|m| !m.is_signer && !m.is_writable),
+        };
+
+        let account_keys: Vec<Pubkey> = metas.iter().map(|m| m.pubkey).collect();
+        let index_of = |key: &Pubkey| account_keys.iter().position(|k| k == key).unwrap_or_default() as u8;
+
+        let instructions = instructions
+            .iter()
+            .map(|instruction| CompiledInstruction {
+                program_id_index: index_of(&instruction.program_id),
+                accounts: instruction.accounts.iter().map(|m| index_of(&m.pubkey)).collect(),
+                data: instruction.data.clone(),
+            })
+            .collect();
+
+        Ok(Message {
+            header,
+            account_keys,
+            recent_blockhash,
+            instructions,
+        })
+    }
+
+    /// Accounts that must sign, in signature order
+    pub fn signers(&self) -> &[Pubkey] {
+        &self.account_keys[..self.header.num_required_signatures as usize]
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = vec![
+            self.header.num_required_signatures,
+            self.header.num_readonly_signed_accounts,
+            self.header.num_readonly_unsigned_accounts,
+        ];
+
+        encode_length(&mut buf, self.account_keys.len());
+        for key in &self.account_keys {
+            buf.extend_from_slice(&key.0);
+        }
+
+        buf.extend_from_slice(&self.recent_blockhash);
+
+        encode_length(&mut buf, self.instructions.len());
+        for instruction in &self.instructions {
+            buf.push(instruction.program_id_index);
+            encode_length(&mut buf, instruction.accounts.len());
+            buf.extend_from_slice(&instruction.accounts);
+            encode_length(&mut buf, instruction.data.len());
+            buf.extend_from_slice(&instruction.data);
+        }
+
+        buf
+    }
+}
+
+/// Compact-u16 ("shortvec") length prefix
+pub fn encode_length(buf: &mut Vec<u8>, len: usize) {
+    let mut remaining = len;
+    loop {
+        let mut byte = (remaining & 0x7f) as u8;
+        remaining >>= 7;
+        if remaining == 0 {
+            buf.push(byte);
+            return;
+        }
+        byte |= 0x80;
+        buf.push(byte);
+    }
+}
+
+/// Wire-format transaction: signatures in signer order followed by the serialized message
+pub fn serialize_transaction(signatures: &[[u8; SIGNATURE_LENGTH]], message: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(1 + signatures.len() * SIGNATURE_LENGTH + message.len());
+    encode_length(&mut buf, signatures.len());
+    for signature in signatures {
+        buf.extend_from_slice(signature);
+    }
+    buf.extend_from_slice(message);
+    buf
+}
+
+/// Verify an ed25519 signature over a serialized message
+pub fn verify_signature(pubkey: &Pubkey, message: &[u8], signature: &[u8]) -> bool {
+    let Ok(key) = VerifyingKey::from_bytes(&pubkey.0) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_slice(signature) else {
+        return false;
+    };
+
+    key.verify_strict(message, &signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn key(byte: u8) -> Pubkey {
+        Pubkey([byte; 32])
+    }
+
+    #[test]
+    fn test_shortvec_encoding() {
+        let encode = |len| {
+            let mut buf = Vec::new();
+            encode_length(&mut buf, len);
+            buf
+        };
+
+        assert_eq!(encode(0), vec![0x00]);
+        assert_eq!(encode(127), vec![0x7f]);
+        assert_eq!(encode(128), vec![0x80, 0x01]);
+        assert_eq!(encode(16384), vec![0x80, 0x80, 0x01]);
+    }
+
+    #[test]
+    fn test_message_orders_and_deduplicates_accounts() {
+        let program = key(9);
+        let instruction = Instruction {
+            program_id: program,
+            accounts: vec![
+                AccountMeta { pubkey: key(3), is_signer: false, is_writable: false },
+                AccountMeta { pubkey: key(2), is_signer: true, is_writable: false },
+                AccountMeta { pubkey: key(4), is_signer: false, is_writable: true },
+                AccountMeta { pubkey: key(3), is_signer: false, is_writable: true },
+                AccountMeta { pubkey: key(1), is_signer: true, is_writable: false },
+            ],
+            data: vec![7],
+        };
+
+        let message = Message::new(&[instruction], key(1), [5; 32]).unwrap();
+
+        assert_eq!(message.account_keys, vec![key(1), key(2), key(3), key(4), program]);
+        assert_eq!(
+            message.header,
+            MessageHeader {
+                num_required_signatures: 2,
+                num_readonly_signed_accounts: 1,
+                num_readonly_unsigned_accounts: 1,
+            }
+        );
+        assert_eq!(message.signers(), &[key(1), key(2)]);
+        assert_eq!(message.instructions[0].program_id_index, 4);
+        assert_eq!(message.instructions[0].accounts, vec![2, 1, 3, 2, 0]);
+
+        let bytes = message.serialize();
+        assert_eq!(&bytes[..4], &[2, 1, 1, 5]);
+        assert_eq!(bytes.len(), 3 + 1 + 5 * 32 + 32 + 1 + (1 + 1 + 5 + 1 + 1));
+    }
+
+    #[test]
+    fn test_signature_verification() {
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let pubkey = Pubkey(signing_key.verifying_key().to_bytes());
+        let message = b"message bytes";
+        let signature = signing_key.sign(message).to_bytes();
+
+        assert!(verify_signature(&pubkey, message, &signature));
+        assert!(!verify_signature(&pubkey, b"other bytes", &signature));
+        assert!(!verify_signature(&key(1), message, &signature));
+
+        let tx = serialize_transaction(&[signature], message);
+        assert_eq!(tx[0], 1);
+        assert_eq!(&tx[1..65], &signature);
+    }
+
+    #[test]
+    fn test_parse_nonce_account() {
+        let mut data = vec![0u8; 80];
+        data[0..4].copy_from_slice(&1u32.to_le_bytes());
+        data[4..8].copy_from_slice(&1u32.to_le_bytes());
+        data[8..40].copy_from_slice(&[3; 32]);
+        data[40..72].copy_from_slice(&[4; 32]);
+
+        let state = parse_nonce_account(&data).unwrap();
+        assert_eq!(state.authority, key(3));
+        assert_eq!(state.nonce, [4; 32]);
+
+        data[4..8].copy_from_slice(&0u32.to_le_bytes());
+        assert!(parse_nonce_account(&data).is_none());
+    }
+
+    #[test]
+    fn test_advance_nonce_instruction_layout() {
+        let instruction = advance_nonce_instruction(key(1), key(2));
+        assert_eq!(instruction.program_id.to_string(), "11111111111111111111111111111111");
+        assert_eq!(instruction.data, vec![4, 0, 0, 0]);
+        assert!(instruction.accounts[2].is_signer);
+    }
+
+    #[test]
+    fn test_find_program_address_matches_solana() {
+        let program = Pubkey::from_str("dS3zvp95PFVrNNBfZDXn78QL5MvhUqDCFR4rn8z9Jgh").unwrap();
+
+        let (market, bump) = Pubkey::find_program_address(&[b"market"], &program).unwrap();
+        assert_eq!(market.to_string(), "65YBHnrEZjJE8ou5mRVZj6WCe2DkGrbvZvLS3t5Un48h");
+        assert_eq!(bump, 255);
+
+        // Bump 255 lands on the curve for this participant
+        let (channel, bump) = Pubkey::find_program_address(&[b"channel", &[7; 32]], &program).unwrap();
+        assert_eq!(channel.to_string(), "H6K1dQjCWFXHc3crrHPShsLud5zepbDHh99KVECTbZfF");
+        assert_eq!(bump, 254);
+    }
+
+    #[test]
+    fn test_anchor_discriminator() {
+        assert_eq!(anchor_discriminator("initialize"), [175, 175, 109, 31, 13, 152, 155, 237]);
+    }
+
+    #[test]
+    fn test_ed25519_verify_instruction_layout() {
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let signer = Pubkey(signing_key.verifying_key().to_bytes());
+        let signature = signing_key.sign(b"state").to_bytes();
+
+        let instruction = ed25519_verify_instruction(&signer, b"state", &signature);
+        let data = &instruction.data;
+        let read = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as usize;
+
+        assert_eq!(instruction.program_id.to_string(), "Ed25519SigVerify111111111111111111111111111");
+        assert_eq!(data[0], 1);
+        assert_eq!(&data[read(2)..read(2) + SIGNATURE_LENGTH], &signature);
+        assert_eq!(&data[read(6)..read(6) + 32], &signer.0);
+        assert_eq!(&data[read(10)..read(10) + read(12)], b"state");
+        assert_eq!([read(4), read(8), read(14)], [u16::MAX as usize; 3]);
+    }
+}
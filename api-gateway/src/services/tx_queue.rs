@@ -0,0 +1,216 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::database::schema::types::OrderSide;
+use crate::error::{ApiError, Result};
+use crate::models::trading::SettlementTrade;
+use crate::models::tx_job::TxJob;
+use crate::AppState;
+
+pub const TX_JOB_COLUMNS: &str = "id, operation, payload, status, attempts, signature, result, error_code, \
+    error_message, submitted_at, completed_at, created_by, created_at, updated_at";
+
+/// Chain operation a job performs, stored as the job's `operation` and `payload`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "operation", content = "payload", rename_all = "snake_case")]
+pub enum TxOperation {
+    IssueErc {
+        certificate_id: String,
+        energy_amount: u64,
+        renewable_source: String,
+        validation_data: String,
+        source_readings: Vec<String>,
+    },
+    ValidateErc {
+        certificate_id: String,
+        recipient: String,
+    },
+    /// Freeze a certificate while a dispute over it is open
+    ChallengeErc {
+        certificate_id: String,
+        dispute_id: Uuid,
+        reason: String,
+    },
+    /// Close a certificate's challenge, revoking it if the dispute was upheld
+    ResolveChallenge {
+        certificate_id: String,
+        dispute_id: Uuid,
+        upheld: bool,
+        resolution: String,
+    },
+    /// Trades matched off chain, settled as one trading program batch
+    SettleTrades {
+        batch: u64,
+        trades: Vec<SettlementTrade>,
+    },
+    /// Owner-signed limits of an off-chain order, recorded before the order is matched
+    CommitOrder {
+        order_id: Uuid,
+        owner: String,
+        side: OrderSide,
+        energy_amount: Decimal,
+        price_per_kwh: Decimal,
+        signature: String,
+    },
+    RegisterMeter {
+        meter_id: String,
+        owner: String,
+        meter_type: String,
+        building: String,
+        zone: String,
+        meter_pubkey: String,
+    },
+    RotateMeterKey {
+        meter_id: String,
+        meter_pubkey: String,
+    },
+    DecommissionMeter {
+        meter_id: String,
+    },
+    /// Official grid tariff for the oracle program, from a verified snapshot
+    PublishGridPrice {
+        price_per_kwh: Decimal,
+        effective_at: i64,
+        snapshot_hash: String,
+    },
+}
+
+impl TxOperation {
+    /// Program the operation's transaction calls, as its `anchor/programs` directory
+    #[cfg(any(test, feature = "chain"))]
+    pub(crate) fn program(&self) -> &'static str {
+        match self {
+            TxOperation::IssueErc { .. }
+            | TxOperation::ValidateErc { .. }
+            | TxOperation::ChallengeErc { .. }
+            | TxOperation::ResolveChallenge { .. } => "governance",
+            TxOperation::SettleTrades { .. } | TxOperation::CommitOrder { .. } => "trading",
+            TxOperation::RegisterMeter { .. }
+            | TxOperation::RotateMeterKey { .. }
+            | TxOperation::DecommissionMeter { .. } => "registry",
+            TxOperation::PublishGridPrice { .. } => "oracle",
+        }
+    }
+
+    fn to_columns(&self) -> (String, Value) {
+        let mut value = serde_json::to_value(self).expect("transaction operations serialize");
+        let operation = value["operation"].as_str().unwrap_or_default().to_string();
+        (operation, value["payload"].take())
+    }
+
+    #[cfg(any(test, feature = "chain"))]
+    pub(crate) fn from_columns(operation: &str, payload: &Value) -> Result<Self> {
+        serde_json::from_value(serde_json::json!({ "operation": operation, "payload": payload }))
+            .map_err(|e| ApiError::Internal(format!("Invalid {} job payload: {}", operation, e)))
+    }
+}
+
+/// Postgres-backed queue of chain operations, enqueued by API handlers
+#[derive(Clone)]
+pub struct TxQueue {
+    db: PgPool,
+}
+
+impl TxQueue {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone())
+    }
+
+    /// Queue `operation` for the worker, returning the job to poll
+    pub async fn enqueue(&self, operation: &TxOperation, created_by: Uuid) -> Result<TxJob> {
+        enqueue_with(&self.db, operation, Some(created_by)).await
+    }
+
+    pub async fn load(&self, id: Uuid) -> Result<TxJob> {
+        let query = format!("SELECT {} FROM tx_jobs WHERE id = $1", TX_JOB_COLUMNS);
+
+        sqlx::query_as::<_, TxJob>(&query)
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Transaction job {} not found", id)))
+    }
+}
+
+/// Queue `operation` through `executor`, so that a job queued inside a database transaction
+/// only exists once that transaction commits
+pub async fn enqueue_with<'e>(
+    executor: impl PgExecutor<'e>,
+    operation: &TxOperation,
+    created_by: Option<Uuid>,
+) -> Result<TxJob> {
+    let (name, payload) = operation.to_columns();
+    let query = format!(
+        "INSERT INTO tx_jobs (operation, payload, created_by) VALUES ($1, $2, $3) RETURNING {}",
+        TX_JOB_COLUMNS
+    );
+
+    let job = sqlx::query_as::<_, TxJob>(&query)
+        .bind(&name)
+        .bind(payload)
+        .bind(created_by)
+        .fetch_one(executor)
+        .await?;
+
+    tracing::info!("Queued {} transaction job {}", name, job.id);
+    Ok(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operations_round_trip_through_columns() {
+        let operation = TxOperation::ValidateErc {
+            certificate_id: "ERC-7".to_string(),
+            recipient: "11111111111111111111111111111111".to_string(),
+        };
+        let (name, payload) = operation.to_columns();
+
+        assert_eq!(name, "validate_erc");
+        assert_eq!(payload["certificate_id"], "ERC-7");
+        assert_eq!(TxOperation::from_columns(&name, &payload).unwrap(), operation);
+        assert!(TxOperation::from_columns("issue_erc", &payload).is_err());
+
+        let operation = TxOperation::PublishGridPrice {
+            price_per_kwh: Decimal::new(57982, 4),
+            effective_at: 1_727_056_800,
+            snapshot_hash: "ab".repeat(32),
+        };
+        let (name, payload) = operation.to_columns();
+        assert_eq!(name, "publish_grid_price");
+        assert_eq!(TxOperation::from_columns(&name, &payload).unwrap(), operation);
+
+        let operation = TxOperation::ResolveChallenge {
+            certificate_id: "ERC-7".to_string(),
+            dispute_id: Uuid::nil(),
+            upheld: true,
+            resolution: "Meter was tampered with".to_string(),
+        };
+        let (name, payload) = operation.to_columns();
+        assert_eq!(name, "resolve_challenge");
+        assert_eq!(operation.program(), "governance");
+        assert_eq!(TxOperation::from_columns(&name, &payload).unwrap(), operation);
+
+        let operation = TxOperation::CommitOrder {
+            order_id: Uuid::nil(),
+            owner: "11111111111111111111111111111111".to_string(),
+            side: OrderSide::Sell,
+            energy_amount: Decimal::new(12_500, 3),
+            price_per_kwh: Decimal::new(35, 1),
+            signature: "1".repeat(64),
+        };
+        let (name, payload) = operation.to_columns();
+        assert_eq!(name, "commit_order");
+        assert_eq!(operation.program(), "trading");
+        assert_eq!(TxOperation::from_columns(&name, &payload).unwrap(), operation);
+    }
+}
@@ -0,0 +1,427 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::error::{ApiError, Result};
+use crate::services::transaction::Pubkey;
+use crate::utils::clock::SharedClock;
+
+/// Signs transaction messages as the gateway authority
+///
+/// Remote implementations keep the key in Vault or KMS, so it never sits on the gateway host.
+#[async_trait]
+pub trait TxSigner: Send + Sync {
+    fn pubkey(&self) -> Pubkey;
+
+    /// ed25519 signature over a serialized transaction message
+    async fn sign_message(&self, message: &[u8]) -> Result<[u8; 64]>;
+}
+
+pub type SharedSigner = Arc<dyn TxSigner>;
+
+/// `signature` as a 64-byte ed25519 signature by `pubkey` over `message`
+///
+/// Remote signatures are checked before they are put in a transaction, so a signer that
+/// switched keys fails here rather than on-chain.
+fn verified_signature(pubkey: &Pubkey, message: &[u8], signature: &[u8]) -> Result<[u8; 64]> {
+    let signature: [u8; 64] = signature
+        .try_into()
+        .map_err(|_| ApiError::ExternalService("Signer returned a signature that is not 64 bytes".to_string()))?;
+    VerifyingKey::from_bytes(&pubkey.0)
+        .and_then(|key| key.verify(message, &Signature::from_bytes(&signature)))
+        .map_err(|_| ApiError::ExternalService(format!("Signer returned a signature not made by {}", pubkey)))?;
+    Ok(signature)
+}
+
+/// Key held in gateway memory, from a keypair file or `GOVERNANCE_AUTHORITY_KEY`
+pub struct LocalSigner(SigningKey);
+
+impl LocalSigner {
+    pub fn new(key: SigningKey) -> Self {
+        Self(key)
+    }
+}
+
+#[async_trait]
+impl TxSigner for LocalSigner {
+    fn pubkey(&self) -> Pubkey {
+        Pubkey(self.0.verifying_key().to_bytes())
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<[u8; 64]> {
+        Ok(self.0.sign(message).to_bytes())
+    }
+}
+
+/// Public key of the latest version of a transit key, from `GET /v1/<mount>/keys/<name>`
+fn vault_public_key(response: &Value) -> Result<Pubkey> {
+    let data = &response["data"];
+    if data["type"].as_str() != Some("ed25519") {
+        return Err(ApiError::Configuration("Vault transit key must be of type ed25519".to_string()));
+    }
+    let version = data["latest_version"].as_u64().unwrap_or(1).to_string();
+    data["keys"][&version]["public_key"]
+        .as_str()
+        .and_then(|key| BASE64.decode(key).ok())
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .map(Pubkey)
+        .ok_or_else(|| ApiError::ExternalService("Vault returned no ed25519 public key".to_string()))
+}
+
+/// Signature bytes from a transit signature such as `vault:v1:<base64>`
+fn vault_signature(signature: &str) -> Result<Vec<u8>> {
+    signature
+        .rsplit_once(':')
+        .filter(|(prefix, _)| prefix.starts_with("vault:v"))
+        .and_then(|(_, encoded)| BASE64.decode(encoded).ok())
+        .ok_or_else(|| ApiError::ExternalService("Vault returned a malformed signature".to_string()))
+}
+
+/// ed25519 key in a HashiCorp Vault transit secrets engine
+pub struct VaultTransitSigner {
+    http: reqwest::Client,
+    /// `<VAULT_ADDR>/v1/<mount>`
+    base_url: String,
+    token: String,
+    key_name: String,
+    pubkey: Pubkey,
+}
+
+impl VaultTransitSigner {
+    /// Read the public key of the transit key's latest version
+    pub async fn connect(addr: &str, token: &str, mount: &str, key_name: &str) -> Result<Self> {
+        let http = reqwest::Client::new();
+        let base_url = format!("{}/v1/{}", addr.trim_end_matches('/'), mount.trim_matches('/'));
+        let response: Value = http
+            .get(format!("{}/keys/{}", base_url, key_name))
+            .header("X-Vault-Token", token)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| ApiError::ExternalService(format!("Vault key lookup failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| ApiError::ExternalService(format!("Invalid Vault key response: {}", e)))?;
+
+        Ok(Self {
+            pubkey: vault_public_key(&response)?,
+            http,
+            base_url,
+            token: token.to_string(),
+            key_name: key_name.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl TxSigner for VaultTransitSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<[u8; 64]> {
+        let response: Value = self
+            .http
+            .post(format!("{}/sign/{}", self.base_url, self.key_name))
+            .header("X-Vault-Token", &self.token)
+            .json(&json!({ "input": BASE64.encode(message) }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| ApiError::ExternalService(format!("Vault signing failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| ApiError::ExternalService(format!("Invalid Vault signing response: {}", e)))?;
+
+        let signature = response["data"]["signature"]
+            .as_str()
+            .ok_or_else(|| ApiError::ExternalService("Vault returned no signature".to_string()))?;
+        verified_signature(&self.pubkey, message, &vault_signature(signature)?)
+    }
+}
+
+/// DER prefix of an Ed25519 SubjectPublicKeyInfo, followed by the 32-byte key
+const ED25519_SPKI_PREFIX: [u8; 12] = [0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00];
+
+fn ed25519_from_spki(der: &[u8]) -> Result<Pubkey> {
+    der.strip_prefix(&ED25519_SPKI_PREFIX[..])
+        .and_then(|key| <[u8; 32]>::try_from(key).ok())
+        .map(Pubkey)
+        .ok_or_else(|| ApiError::Configuration("KMS key must have key spec ECC_NIST_EDWARDS25519".to_string()))
+}
+
+/// AWS credentials used to sign KMS requests
+#[derive(Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+const SHA256_BLOCK_BYTES: usize = 64;
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; SHA256_BLOCK_BYTES];
+    if key.len() > SHA256_BLOCK_BYTES {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner = Sha256::new()
+        .chain_update(block.map(|byte| byte ^ 0x36))
+        .chain_update(data)
+        .finalize();
+    Sha256::new()
+        .chain_update(block.map(|byte| byte ^ 0x5c))
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Signature Version 4 key for `date` (`YYYYMMDD`)
+fn sigv4_signing_key(secret_access_key: &str, date: &str, region: &str, service: &str) -> [u8; 32] {
+    let key = hmac_sha256(format!("AWS4{}", secret_access_key).as_bytes(), date.as_bytes());
+    let key = hmac_sha256(&key, region.as_bytes());
+    let key = hmac_sha256(&key, service.as_bytes());
+    hmac_sha256(&key, b"aws4_request")
+}
+
+/// `Authorization` header for a Signature Version 4 `POST /` with the given headers, which
+/// must be lowercase, sorted and include `host` and `x-amz-date`
+fn sigv4_authorization(
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    amz_date: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> String {
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "POST\n/\n\n{}\n{}\n{:x}",
+        canonical_headers,
+        signed_headers,
+        Sha256::digest(body)
+    );
+
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{:x}",
+        amz_date,
+        scope,
+        Sha256::digest(canonical_request.as_bytes())
+    );
+    let signature = hmac_sha256(
+        &sigv4_signing_key(&credentials.secret_access_key, date, region, service),
+        string_to_sign.as_bytes(),
+    );
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id,
+        scope,
+        signed_headers,
+        hex(&signature)
+    )
+}
+
+/// Ed25519 key (`ECC_NIST_EDWARDS25519`) in AWS KMS, optionally backed by CloudHSM
+pub struct KmsSigner {
+    http: reqwest::Client,
+    region: String,
+    key_id: String,
+    credentials: AwsCredentials,
+    pubkey: Pubkey,
+    clock: SharedClock,
+}
+
+impl KmsSigner {
+    /// Read the key's public key from KMS
+    pub async fn connect(region: &str, key_id: &str, credentials: AwsCredentials, clock: SharedClock) -> Result<Self> {
+        let mut signer = Self {
+            http: reqwest::Client::new(),
+            region: region.to_string(),
+            key_id: key_id.to_string(),
+            credentials,
+            pubkey: Pubkey([0; 32]),
+            clock,
+        };
+        let response = signer.call("GetPublicKey", json!({ "KeyId": key_id })).await?;
+        let der = response["PublicKey"]
+            .as_str()
+            .and_then(|key| BASE64.decode(key).ok())
+            .ok_or_else(|| ApiError::ExternalService("KMS returned no public key".to_string()))?;
+        signer.pubkey = ed25519_from_spki(&der)?;
+        Ok(signer)
+    }
+
+    /// Call a KMS JSON API action such as `Sign`
+    async fn call(&self, action: &str, request: Value) -> Result<Value> {
+        let host = format!("kms.{}.amazonaws.com", self.region);
+        let amz_date = self.clock.now().format("%Y%m%dT%H%M%SZ").to_string();
+        let target = format!("TrentService.{}", action);
+        let body = request.to_string();
+
+        let mut headers = vec![
+            ("content-type", "application/x-amz-json-1.1"),
+            ("host", host.as_str()),
+            ("x-amz-date", amz_date.as_str()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.as_str()));
+        }
+        headers.push(("x-amz-target", target.as_str()));
+        let authorization = sigv4_authorization(
+            &self.credentials,
+            &self.region,
+            "kms",
+            &amz_date,
+            &headers,
+            body.as_bytes(),
+        );
+
+        let mut http_request = self
+            .http
+            .post(format!("https://{}/", host))
+            .header("Authorization", authorization)
+            .body(body);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            http_request = http_request.header(*name, *value);
+        }
+
+        let response = http_request
+            .send()
+            .await
+            .map_err(|e| ApiError::ExternalService(format!("KMS {} failed: {}", action, e)))?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| ApiError::ExternalService(format!("Invalid KMS {} response: {}", action, e)))?;
+        if !status.is_success() {
+            return Err(ApiError::ExternalService(format!(
+                "KMS {} failed with {}: {}",
+                action,
+                status,
+                body["message"].as_str().or(body["Message"].as_str()).unwrap_or("unknown error")
+            )));
+        }
+        Ok(body)
+    }
+}
+
+#[async_trait]
+impl TxSigner for KmsSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<[u8; 64]> {
+        let response = self
+            .call(
+                "Sign",
+                json!({
+                    "KeyId": self.key_id,
+                    "Message": BASE64.encode(message),
+                    "MessageType": "RAW",
+                    "SigningAlgorithm": "ED25519_SHA_512",
+                }),
+            )
+            .await?;
+
+        let signature = response["Signature"]
+            .as_str()
+            .and_then(|signature| BASE64.decode(signature).ok())
+            .ok_or_else(|| ApiError::ExternalService("KMS returned no signature".to_string()))?;
+        verified_signature(&self.pubkey, message, &signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_signatures_are_verified() {
+        let key = SigningKey::from_bytes(&[5; 32]);
+        let signer = LocalSigner::new(key.clone());
+        let signature = signer.sign_message(b"message").await.unwrap();
+
+        assert_eq!(verified_signature(&signer.pubkey(), b"message", &signature).unwrap(), signature);
+        assert!(verified_signature(&signer.pubkey(), b"other message", &signature).is_err());
+        assert!(verified_signature(&Pubkey([9; 32]), b"message", &signature).is_err());
+        assert!(verified_signature(&signer.pubkey(), b"message", &signature[..63]).is_err());
+    }
+
+    #[test]
+    fn test_vault_responses() {
+        let key = SigningKey::from_bytes(&[5; 32]).verifying_key().to_bytes();
+        let response = json!({
+            "data": {
+                "type": "ed25519",
+                "latest_version": 2,
+                "keys": {
+                    "1": { "public_key": BASE64.encode([1; 32]) },
+                    "2": { "public_key": BASE64.encode(key) },
+                },
+            },
+        });
+        assert_eq!(vault_public_key(&response).unwrap(), Pubkey(key));
+        assert!(vault_public_key(&json!({ "data": { "type": "ecdsa-p256" } })).is_err());
+
+        assert_eq!(vault_signature(&format!("vault:v2:{}", BASE64.encode([7; 64]))).unwrap(), vec![7; 64]);
+        assert!(vault_signature("not-a-signature").is_err());
+    }
+
+    #[test]
+    fn test_kms_public_key() {
+        let mut der = ED25519_SPKI_PREFIX.to_vec();
+        der.extend_from_slice(&[3; 32]);
+        assert_eq!(ed25519_from_spki(&der).unwrap(), Pubkey([3; 32]));
+        assert!(ed25519_from_spki(&der[1..]).is_err());
+    }
+
+    #[test]
+    fn test_sigv4_signing() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Signing key example from the AWS Signature Version 4 documentation
+        assert_eq!(
+            hex(&sigv4_signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20150830", "us-east-1", "iam")),
+            "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9"
+        );
+
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        };
+        let authorization = sigv4_authorization(
+            &credentials,
+            "eu-west-1",
+            "kms",
+            "20240923T101500Z",
+            &[("host", "kms.eu-west-1.amazonaws.com"), ("x-amz-date", "20240923T101500Z")],
+            b"{}",
+        );
+        assert!(authorization.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20240923/eu-west-1/kms/aws4_request, SignedHeaders=host;x-amz-date, Signature="
+        ));
+    }
+}
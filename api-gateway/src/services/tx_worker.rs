@@ -0,0 +1,469 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use governance::RenewableSource;
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::database::queue;
+use crate::error::{ApiError, BlockchainError, Result};
+use crate::models::tx_job::TxJob;
+use crate::services::blockchain::{BlockchainService, SignatureStatus};
+use crate::services::erc_issuance::{ErcIssuer, IssueErcParams};
+use crate::services::meter_registry::{parse_meter_type, MeterRegistrar, MeterTransaction, RegisterMeterParams};
+use crate::services::price_oracle::GridPricePublisher;
+use crate::services::program_errors::decode_transaction_error;
+use crate::services::settlement::{CommitOrderParams, Settler};
+use crate::services::transaction::{decode_signature, Pubkey};
+use crate::services::tx_queue::{TxOperation, TX_JOB_COLUMNS};
+use crate::utils::backoff;
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+/// Jobs claimed per worker pass
+const BATCH_SIZE: i64 = 20;
+/// Time a claimed job is hidden from other workers while it is processed
+const CLAIM_LEASE: Duration = Duration::from_secs(60);
+/// Delay between confirmation checks of a submitted job
+const CONFIRMATION_POLL: Duration = Duration::from_secs(2);
+/// Seconds after which a submission the cluster has not seen has an expired blockhash
+const CONFIRMATION_TIMEOUT_SECS: i64 = 90;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(2);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+/// Where a submitted job stands on-chain
+#[derive(Debug, PartialEq)]
+enum Confirmation {
+    Confirmed,
+    Failed(BlockchainError),
+    Pending,
+    /// Never seen by the cluster before its blockhash expired, so it can be resubmitted
+    Expired,
+}
+
+/// `program` is the `anchor/programs` directory of the program the job calls, used to name
+/// its custom errors
+fn confirmation(status: Option<&SignatureStatus>, expired: bool, program: &str) -> Confirmation {
+    match status {
+        Some(SignatureStatus { err: Some(err), .. }) => Confirmation::Failed(decode_transaction_error(err, program)),
+        Some(status) if matches!(status.confirmation_status.as_deref(), Some("confirmed" | "finalized")) => {
+            Confirmation::Confirmed
+        }
+        None if expired => Confirmation::Expired,
+        _ => Confirmation::Pending,
+    }
+}
+
+/// Whether a failed submission may succeed when it is retried
+fn is_retryable(error: &ApiError) -> bool {
+    match error {
+        ApiError::Chain(error) => matches!(
+            error,
+            BlockchainError::Paused { .. } | BlockchainError::InsufficientFunds | BlockchainError::Failed(_)
+        ),
+        ApiError::Blockchain(_)
+        | ApiError::ExternalService(_)
+        | ApiError::Unavailable(_)
+        | ApiError::Database(_)
+        | ApiError::Redis(_) => true,
+        _ => false,
+    }
+}
+
+fn error_code(error: &ApiError) -> Option<String> {
+    match error {
+        ApiError::Chain(error) => Some(error.stable_code()),
+        _ => None,
+    }
+}
+
+/// Signature and stored result of a registry program job
+fn meter_result(transaction: MeterTransaction) -> Result<(String, Value)> {
+    let result = serde_json::to_value(&transaction).map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok((transaction.signature, result))
+}
+
+/// Signs, submits and confirms queued jobs, retrying transient failures with backoff
+///
+/// Jobs are claimed with `SKIP LOCKED` and a lease, so several gateway instances can run
+/// workers against the same queue.
+#[derive(Clone)]
+pub struct TxWorker {
+    db: PgPool,
+    chain: BlockchainService,
+    issuer: ErcIssuer,
+    settler: Settler,
+    registrar: MeterRegistrar,
+    oracle: GridPricePublisher,
+    max_attempts: i32,
+    clock: SharedClock,
+}
+
+impl TxWorker {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        db: PgPool,
+        chain: BlockchainService,
+        issuer: ErcIssuer,
+        settler: Settler,
+        registrar: MeterRegistrar,
+        oracle: GridPricePublisher,
+        max_attempts: i32,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            db,
+            chain,
+            issuer,
+            settler,
+            registrar,
+            oracle,
+            max_attempts: max_attempts.max(1),
+            clock,
+        }
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        Ok(Self::new(
+            state.db.clone(),
+            state.blockchain_service.clone(),
+            ErcIssuer::from_state(state)?,
+            Settler::from_state(state)?,
+            MeterRegistrar::from_state(state)?,
+            GridPricePublisher::from_state(state)?,
+            state.config.tx_job_max_attempts,
+            state.clock.clone(),
+        ))
+    }
+
+    /// Advance every due job, returning how many were claimed
+    ///
+    /// While the RPC circuit breaker is open no job is claimed, so queued submissions wait
+    /// for the node to recover without using up their attempts.
+    pub async fn process_due(&self) -> Result<usize> {
+        if let Some(retry_at) = self.chain.circuit_breaker().retry_at() {
+            tracing::debug!("Solana RPC circuit open; holding transaction jobs until {}", retry_at);
+            return Ok(0);
+        }
+
+        let jobs: Vec<TxJob> = queue::claim_due(
+            &self.db,
+            "tx_jobs",
+            TX_JOB_COLUMNS,
+            &[TxJob::QUEUED, TxJob::SUBMITTED],
+            CLAIM_LEASE,
+            BATCH_SIZE,
+        )
+        .await?;
+
+        for job in &jobs {
+            let advanced = if job.status == TxJob::SUBMITTED {
+                self.confirm(job).await
+            } else {
+                self.submit(job).await
+            };
+            // The lease expires and the job is picked up again
+            if let Err(e) = advanced {
+                tracing::error!("Transaction job {} could not be advanced: {}", job.id, e);
+            }
+        }
+        Ok(jobs.len())
+    }
+
+    async fn execute(&self, operation: TxOperation) -> Result<(String, Value)> {
+        let transaction = match operation {
+            TxOperation::IssueErc {
+                certificate_id,
+                energy_amount,
+                renewable_source,
+                validation_data,
+                source_readings,
+            } => {
+                let params = IssueErcParams {
+                    certificate_id,
+                    energy_amount,
+                    renewable_source: RenewableSource::from_name(&renewable_source),
+                    validation_data,
+                    source_readings: source_readings
+                        .iter()
+                        .map(|reading| Pubkey::from_str(reading).map_err(ApiError::BadRequest))
+                        .collect::<Result<Vec<_>>>()?,
+                };
+                self.issuer.issue(params).await?
+            }
+            TxOperation::ValidateErc {
+                certificate_id,
+                recipient,
+            } => {
+                let recipient = Pubkey::from_str(&recipient).map_err(ApiError::BadRequest)?;
+                self.issuer.validate(&certificate_id, recipient).await?
+            }
+            TxOperation::ChallengeErc {
+                certificate_id,
+                dispute_id,
+                reason,
+            } => self.issuer.challenge(&certificate_id, dispute_id, &reason).await?,
+            TxOperation::ResolveChallenge {
+                certificate_id,
+                dispute_id,
+                upheld,
+                resolution,
+            } => {
+                self.issuer
+                    .resolve_challenge(&certificate_id, dispute_id, upheld, &resolution)
+                    .await?
+            }
+            TxOperation::SettleTrades { batch, trades } => {
+                let settlement = self.settler.settle(batch, &trades).await?;
+                let result = serde_json::to_value(&settlement).map_err(|e| ApiError::Internal(e.to_string()))?;
+                return Ok((settlement.signature, result));
+            }
+            TxOperation::CommitOrder {
+                order_id,
+                owner,
+                side,
+                energy_amount,
+                price_per_kwh,
+                signature,
+            } => {
+                let order = CommitOrderParams {
+                    order_id,
+                    owner: Pubkey::from_str(&owner).map_err(ApiError::BadRequest)?,
+                    side,
+                    energy_amount,
+                    price_per_kwh,
+                    signature: decode_signature(&signature)
+                        .ok_or_else(|| ApiError::BadRequest(format!("Invalid commitment signature for order {}", order_id)))?,
+                };
+                let commitment = self.settler.commit(&order).await?;
+                let result = serde_json::to_value(&commitment).map_err(|e| ApiError::Internal(e.to_string()))?;
+                return Ok((commitment.signature, result));
+            }
+            TxOperation::RegisterMeter {
+                meter_id,
+                owner,
+                meter_type,
+                building,
+                zone,
+                meter_pubkey,
+            } => {
+                let params = RegisterMeterParams {
+                    meter_id,
+                    owner: Pubkey::from_str(&owner).map_err(ApiError::BadRequest)?,
+                    meter_type: parse_meter_type(&meter_type)?,
+                    building,
+                    zone,
+                    meter_pubkey: Pubkey::from_str(&meter_pubkey).map_err(ApiError::BadRequest)?,
+                };
+                return meter_result(self.registrar.register(params).await?);
+            }
+            TxOperation::RotateMeterKey { meter_id, meter_pubkey } => {
+                let meter_pubkey = Pubkey::from_str(&meter_pubkey).map_err(ApiError::BadRequest)?;
+                return meter_result(self.registrar.rotate_key(&meter_id, meter_pubkey).await?);
+            }
+            TxOperation::DecommissionMeter { meter_id } => {
+                return meter_result(self.registrar.decommission(&meter_id).await?);
+            }
+            TxOperation::PublishGridPrice {
+                price_per_kwh,
+                effective_at,
+                snapshot_hash,
+            } => {
+                let transaction = self.oracle.publish(price_per_kwh, effective_at, &snapshot_hash).await?;
+                let result = serde_json::to_value(&transaction).map_err(|e| ApiError::Internal(e.to_string()))?;
+                return Ok((transaction.signature, result));
+            }
+        };
+
+        let result = serde_json::to_value(&transaction).map_err(|e| ApiError::Internal(e.to_string()))?;
+        Ok((transaction.signature, result))
+    }
+
+    async fn submit(&self, job: &TxJob) -> Result<()> {
+        let attempts = job.attempts + 1;
+        let outcome = match TxOperation::from_columns(&job.operation, &job.payload) {
+            Ok(operation) => self.execute(operation).await,
+            Err(e) => Err(e),
+        };
+
+        match outcome {
+            Ok((signature, result)) => {
+                sqlx::query(
+                    "UPDATE tx_jobs SET status = $2, attempts = $3, signature = $4, result = $5, submitted_at = $6,
+                        error_code = NULL, error_message = NULL, next_attempt_at = NOW() + make_interval(secs => $7)
+                     WHERE id = $1",
+                )
+                .bind(job.id)
+                .bind(TxJob::SUBMITTED)
+                .bind(attempts)
+                .bind(&signature)
+                .bind(result)
+                .bind(self.clock.now())
+                .bind(CONFIRMATION_POLL.as_secs_f64())
+                .execute(&self.db)
+                .await?;
+
+                tracing::info!("Transaction job {} submitted in {}", job.id, signature);
+                Ok(())
+            }
+            // Refused by the circuit breaker without reaching the node, so not an attempt
+            Err(e @ ApiError::Unavailable(_)) => {
+                tracing::info!("Transaction job {} held while the Solana RPC recovers: {}", job.id, e);
+                self.retry(job.id, job.attempts, INITIAL_RETRY_DELAY, &e).await
+            }
+            Err(e) if is_retryable(&e) && attempts < self.max_attempts => {
+                let delay = backoff::retry_delay(attempts, INITIAL_RETRY_DELAY, MAX_RETRY_DELAY);
+                tracing::warn!(
+                    "Transaction job {} attempt {} failed, retrying in {}s: {}",
+                    job.id,
+                    attempts,
+                    delay.as_secs(),
+                    e
+                );
+                self.retry(job.id, attempts, delay, &e).await
+            }
+            Err(e) => self.fail(job, attempts, &e).await,
+        }
+    }
+
+    async fn confirm(&self, job: &TxJob) -> Result<()> {
+        let statuses = match &job.signature {
+            Some(signature) => self.chain.get_signature_statuses(std::slice::from_ref(signature)).await?,
+            None => Vec::new(),
+        };
+        let expired = job.submitted_at.is_none_or(|submitted_at| {
+            self.clock.now() - submitted_at > chrono::Duration::seconds(CONFIRMATION_TIMEOUT_SECS)
+        });
+
+        let program = TxOperation::from_columns(&job.operation, &job.payload)
+            .map(|operation| operation.program())
+            .unwrap_or_default();
+
+        match confirmation(statuses.first().and_then(Option::as_ref), expired, program) {
+            Confirmation::Confirmed => {
+                sqlx::query("UPDATE tx_jobs SET status = $2, completed_at = $3 WHERE id = $1")
+                    .bind(job.id)
+                    .bind(TxJob::CONFIRMED)
+                    .bind(self.clock.now())
+                    .execute(&self.db)
+                    .await?;
+                tracing::info!("Transaction job {} confirmed", job.id);
+                Ok(())
+            }
+            Confirmation::Failed(error) => self.fail(job, job.attempts, &ApiError::Chain(error)).await,
+            Confirmation::Pending => {
+                sqlx::query("UPDATE tx_jobs SET next_attempt_at = NOW() + make_interval(secs => $2) WHERE id = $1")
+                    .bind(job.id)
+                    .bind(CONFIRMATION_POLL.as_secs_f64())
+                    .execute(&self.db)
+                    .await?;
+                Ok(())
+            }
+            Confirmation::Expired if job.attempts < self.max_attempts => {
+                tracing::warn!("Transaction job {} expired unconfirmed, resubmitting", job.id);
+                let error = ApiError::Chain(BlockchainError::Failed("Submission expired unconfirmed".to_string()));
+                self.retry(job.id, job.attempts, Duration::ZERO, &error).await
+            }
+            Confirmation::Expired => {
+                let error = ApiError::Chain(BlockchainError::Failed(
+                    "Transaction was not confirmed before its blockhash expired".to_string(),
+                ));
+                self.fail(job, job.attempts, &error).await
+            }
+        }
+    }
+
+    /// Put a job back in the queue for another submission after `delay`
+    async fn retry(&self, id: Uuid, attempts: i32, delay: Duration, error: &ApiError) -> Result<()> {
+        sqlx::query(
+            "UPDATE tx_jobs SET status = $2, attempts = $3, error_code = $4, error_message = $5,
+                next_attempt_at = NOW() + make_interval(secs => $6)
+             WHERE id = $1",
+        )
+        .bind(id)
+        .bind(TxJob::QUEUED)
+        .bind(attempts)
+        .bind(error_code(error))
+        .bind(error.to_string())
+        .bind(delay.as_secs_f64())
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn fail(&self, job: &TxJob, attempts: i32, error: &ApiError) -> Result<()> {
+        sqlx::query(
+            "UPDATE tx_jobs SET status = $2, attempts = $3, error_code = $4, error_message = $5, completed_at = $6
+             WHERE id = $1",
+        )
+        .bind(job.id)
+        .bind(TxJob::FAILED)
+        .bind(attempts)
+        .bind(error_code(error))
+        .bind(error.to_string())
+        .bind(self.clock.now())
+        .execute(&self.db)
+        .await?;
+
+        tracing::error!("Transaction job {} ({}) failed: {}", job.id, job.operation, error);
+        Ok(())
+    }
+
+    /// Process due jobs every `interval`
+    pub fn spawn(self, interval: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.process_due().await {
+                    tracing::error!("Transaction queue pass failed: {}", e);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn status(confirmation_status: &str, err: Option<Value>) -> SignatureStatus {
+        SignatureStatus {
+            slot: 42,
+            confirmation_status: Some(confirmation_status.to_string()),
+            err,
+        }
+    }
+
+    #[test]
+    fn test_confirmation() {
+        assert_eq!(confirmation(Some(&status("finalized", None)), false, "governance"), Confirmation::Confirmed);
+        assert_eq!(confirmation(Some(&status("confirmed", None)), true, "governance"), Confirmation::Confirmed);
+        assert_eq!(confirmation(Some(&status("processed", None)), true, "governance"), Confirmation::Pending);
+        assert_eq!(confirmation(None, false, "governance"), Confirmation::Pending);
+        assert_eq!(confirmation(None, true, "governance"), Confirmation::Expired);
+
+        let failed = status("confirmed", Some(json!({ "InstructionError": [0, { "Custom": 6012 }] })));
+        match confirmation(Some(&failed), false, "governance") {
+            Confirmation::Failed(error @ BlockchainError::Rejected { .. }) => {
+                assert_eq!(error.code(), Some("BelowMinimumEnergy"));
+                assert_eq!(error_code(&ApiError::Chain(error)).as_deref(), Some("BELOW_MINIMUM_ENERGY"));
+            }
+            other => panic!("unexpected confirmation: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_only_transient_errors_are_retried() {
+        assert!(is_retryable(&ApiError::Blockchain("connection reset".to_string())));
+        assert!(is_retryable(&ApiError::Chain(BlockchainError::InsufficientFunds)));
+        assert!(is_retryable(&ApiError::Unavailable("Solana RPC is failing".to_string())));
+        assert!(!is_retryable(&ApiError::Chain(BlockchainError::Rejected {
+            code: "BelowMinimumEnergy".to_string(),
+            message: "Energy amount below minimum required".to_string(),
+        })));
+        assert!(!is_retryable(&ApiError::Configuration("GOVERNANCE_AUTHORITY_KEY is not configured".to_string())));
+    }
+}
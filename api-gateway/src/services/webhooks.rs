@@ -0,0 +1,564 @@
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::database::queue;
+use crate::error::{ApiError, Result};
+use crate::models::webhook::{WebhookDelivery, WebhookSubscription};
+use crate::services::program_logs::{ProgramEvent, ProgramEventNotice};
+use crate::utils::backoff;
+use crate::utils::clock::SharedClock;
+use crate::AppState;
+
+pub const SUBSCRIPTION_COLUMNS: &str =
+    "id, url, event_types, secret, description, active, created_by, created_at, updated_at";
+pub const DELIVERY_COLUMNS: &str = "id, subscription_id, event_type, payload, status, attempts, response_status, \
+    last_error, next_attempt_at, delivered_at, created_at, updated_at";
+
+/// Header carrying `t=<unix seconds>,v1=<hex HMAC-SHA256 of "<t>.<body>">`
+pub const SIGNATURE_HEADER: &str = "X-GridTokenX-Signature";
+pub const EVENT_HEADER: &str = "X-GridTokenX-Event";
+pub const DELIVERY_HEADER: &str = "X-GridTokenX-Delivery";
+
+/// Deliveries claimed per dispatcher pass
+const BATCH_SIZE: i64 = 50;
+/// Time a claimed delivery is hidden from other dispatchers while it is attempted
+const CLAIM_LEASE: Duration = Duration::from_secs(60);
+/// Time an endpoint has to answer a delivery
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(10);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(3600);
+/// Characters of an endpoint's response kept with a failed attempt
+const MAX_ERROR_LEN: usize = 512;
+/// Most deliveries returned by one history request
+pub const MAX_HISTORY_LIMIT: i64 = 500;
+
+/// Webhook registration submitted by an administrator
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookSubscriptionRequest {
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub description: Option<String>,
+}
+
+/// HMAC-SHA256 of `message` under `secret`, hex encoded
+fn hmac_hex(secret: &[u8], message: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Value of [`SIGNATURE_HEADER`] for `body` sent at `timestamp`
+///
+/// Receivers recompute the HMAC over `"<t>.<body>"` with their subscription secret and
+/// reject stale timestamps to stop replays.
+pub fn signature_header(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+    format!("t={},v1={}", timestamp, hmac_hex(secret.as_bytes(), &message))
+}
+
+fn generate_secret() -> String {
+    let mut buf = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut buf);
+    format!("whsec_{}", buf.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+}
+
+fn validate_request(request: &WebhookSubscriptionRequest) -> Result<()> {
+    let url = reqwest::Url::parse(request.url.trim())
+        .map_err(|_| ApiError::Validation(format!("Invalid webhook URL {}", request.url)))?;
+    if !matches!(url.scheme(), "https" | "http") || url.host_str().is_none() {
+        return Err(ApiError::Validation("Webhook URL must be an http(s) URL".to_string()));
+    }
+    if request.event_types.is_empty() {
+        return Err(ApiError::Validation("At least one event type is required".to_string()));
+    }
+    if let Some(unknown) = request
+        .event_types
+        .iter()
+        .find(|event_type| !WebhookSubscription::EVENT_TYPES.contains(&event_type.as_str()))
+    {
+        return Err(ApiError::Validation(format!(
+            "Unknown event type {}; expected one of {}",
+            unknown,
+            WebhookSubscription::EVENT_TYPES.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Webhook event type a decoded program event is delivered as, if any
+fn program_event_type(event: &ProgramEvent) -> Option<&'static str> {
+    match event {
+        ProgramEvent::ErcIssued { .. } => Some(WebhookSubscription::ERC_ISSUED),
+        ProgramEvent::MarketCleared { .. } => Some(WebhookSubscription::MARKET_CLEARED),
+        ProgramEvent::ErcValidatedForTrading { .. } => None,
+    }
+}
+
+/// Body POSTed to the endpoint for `delivery`
+fn delivery_body(delivery: &WebhookDelivery) -> Value {
+    json!({
+        "id": delivery.id,
+        "event": delivery.event_type,
+        "created_at": delivery.created_at,
+        "data": delivery.payload,
+    })
+}
+
+/// Webhook subscriptions and the deliveries queued for them
+#[derive(Clone)]
+pub struct WebhookStore {
+    db: PgPool,
+}
+
+impl WebhookStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone())
+    }
+
+    pub async fn list(&self) -> Result<Vec<WebhookSubscription>> {
+        let query = format!(
+            "SELECT {} FROM webhook_subscriptions ORDER BY created_at DESC",
+            SUBSCRIPTION_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, WebhookSubscription>(&query)
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Register an endpoint, returning it with its freshly generated signing secret
+    pub async fn create(
+        &self,
+        request: &WebhookSubscriptionRequest,
+        created_by: Uuid,
+    ) -> Result<WebhookSubscription> {
+        validate_request(request)?;
+        let mut event_types = request.event_types.clone();
+        event_types.sort();
+        event_types.dedup();
+
+        let query = format!(
+            "INSERT INTO webhook_subscriptions (url, event_types, secret, description, created_by)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING {}",
+            SUBSCRIPTION_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, WebhookSubscription>(&query)
+            .bind(request.url.trim())
+            .bind(&event_types)
+            .bind(generate_secret())
+            .bind(request.description.as_deref().map(str::trim).filter(|d| !d.is_empty()))
+            .bind(created_by)
+            .fetch_one(&self.db)
+            .await?)
+    }
+
+    /// Remove a subscription together with its delivery history
+    pub async fn remove(&self, id: Uuid) -> Result<()> {
+        let removed = sqlx::query("DELETE FROM webhook_subscriptions WHERE id = $1")
+            .bind(id)
+            .execute(&self.db)
+            .await?
+            .rows_affected();
+        if removed == 0 {
+            return Err(ApiError::NotFound(format!("Webhook subscription {} not found", id)));
+        }
+        Ok(())
+    }
+
+    /// Most recent deliveries to a subscription, optionally only those in `status`
+    pub async fn deliveries(
+        &self,
+        subscription_id: Uuid,
+        status: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM webhook_subscriptions WHERE id = $1)")
+            .bind(subscription_id)
+            .fetch_one(&self.db)
+            .await?;
+        if !exists {
+            return Err(ApiError::NotFound(format!("Webhook subscription {} not found", subscription_id)));
+        }
+
+        let query = format!(
+            "SELECT {} FROM webhook_deliveries
+             WHERE subscription_id = $1 AND ($2::varchar IS NULL OR status = $2)
+             ORDER BY created_at DESC
+             LIMIT $3",
+            DELIVERY_COLUMNS
+        );
+        Ok(sqlx::query_as::<_, WebhookDelivery>(&query)
+            .bind(subscription_id)
+            .bind(status)
+            .bind(limit.clamp(1, MAX_HISTORY_LIMIT))
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Queue a dead-lettered delivery for another round of attempts
+    pub async fn redeliver(&self, delivery_id: Uuid) -> Result<WebhookDelivery> {
+        let query = format!(
+            "UPDATE webhook_deliveries SET status = $2, attempts = 0, next_attempt_at = NOW()
+             WHERE id = $1 AND status = $3
+             RETURNING {}",
+            DELIVERY_COLUMNS
+        );
+        sqlx::query_as::<_, WebhookDelivery>(&query)
+            .bind(delivery_id)
+            .bind(WebhookDelivery::PENDING)
+            .bind(WebhookDelivery::DEAD_LETTERED)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("No dead-lettered webhook delivery {}", delivery_id)))
+    }
+
+    /// Queue `data` for every active subscription to `event_type`, returning how many were queued
+    pub async fn publish(&self, event_type: &str, data: &Value) -> Result<u64> {
+        Ok(sqlx::query(
+            "INSERT INTO webhook_deliveries (subscription_id, event_type, payload)
+             SELECT id, $1, $2 FROM webhook_subscriptions WHERE active AND $1 = ANY(event_types)",
+        )
+        .bind(event_type)
+        .bind(data)
+        .execute(&self.db)
+        .await?
+        .rows_affected())
+    }
+
+    /// Queue the governance and trading events subscribers can receive as they are decoded
+    pub fn spawn_program_events(self, mut events: broadcast::Receiver<ProgramEventNotice>) {
+        tokio::spawn(async move {
+            loop {
+                let notice = match events.recv().await {
+                    Ok(notice) => notice,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!("Webhook publisher skipped {} program events", missed);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let Some(event_type) = program_event_type(&notice.event) else {
+                    continue;
+                };
+                let data = match serde_json::to_value(&notice) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        tracing::error!("Failed to serialize program event {}: {}", notice.signature, e);
+                        continue;
+                    }
+                };
+                if let Err(e) = self.publish(event_type, &data).await {
+                    tracing::error!("Failed to queue {} webhooks for {}: {}", event_type, notice.signature, e);
+                }
+            }
+        });
+    }
+}
+
+/// Outcome of one delivery attempt
+#[derive(Debug)]
+struct Attempt {
+    response_status: Option<i32>,
+    error: Option<String>,
+}
+
+/// Sends queued deliveries, retrying failures with backoff until they are dead-lettered
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    db: PgPool,
+    http: reqwest::Client,
+    clock: SharedClock,
+    max_attempts: i32,
+}
+
+impl WebhookDispatcher {
+    pub fn new(db: PgPool, clock: SharedClock, max_attempts: i32) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| ApiError::Configuration(format!("Failed to build webhook client: {}", e)))?;
+
+        Ok(Self {
+            db,
+            http,
+            clock,
+            max_attempts: max_attempts.max(1),
+        })
+    }
+
+    pub fn from_state(state: &AppState) -> Result<Self> {
+        Self::new(state.db.clone(), state.clock.clone(), state.config.webhook_max_attempts)
+    }
+
+    /// Attempt every due delivery, returning how many were claimed
+    pub async fn process_due(&self) -> Result<usize> {
+        let deliveries: Vec<WebhookDelivery> = queue::claim_due(
+            &self.db,
+            "webhook_deliveries",
+            DELIVERY_COLUMNS,
+            &[WebhookDelivery::PENDING],
+            CLAIM_LEASE,
+            BATCH_SIZE,
+        )
+        .await?;
+
+        let count = deliveries.len();
+        futures::future::join_all(deliveries.iter().map(|delivery| async move {
+            // The lease expires and the delivery is picked up again
+            if let Err(e) = self.deliver(delivery).await {
+                tracing::error!("Webhook delivery {} could not be recorded: {}", delivery.id, e);
+            }
+        }))
+        .await;
+        Ok(count)
+    }
+
+    async fn deliver(&self, delivery: &WebhookDelivery) -> Result<()> {
+        let subscription = sqlx::query_as::<_, WebhookSubscription>(&format!(
+            "SELECT {} FROM webhook_subscriptions WHERE id = $1",
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(delivery.subscription_id)
+        .fetch_one(&self.db)
+        .await?;
+
+        let attempts = delivery.attempts + 1;
+        let attempt = if subscription.active {
+            self.send(&subscription, delivery).await
+        } else {
+            Attempt {
+                response_status: None,
+                error: Some("Subscription is inactive".to_string()),
+            }
+        };
+
+        let Some(error) = attempt.error else {
+            metrics::counter!(
+                "webhook_deliveries_total",
+                "event" => delivery.event_type.clone(),
+                "outcome" => "delivered"
+            )
+            .increment(1);
+            sqlx::query(
+                "UPDATE webhook_deliveries SET status = $2, attempts = $3, response_status = $4, last_error = NULL,
+                    delivered_at = $5
+                 WHERE id = $1",
+            )
+            .bind(delivery.id)
+            .bind(WebhookDelivery::DELIVERED)
+            .bind(attempts)
+            .bind(attempt.response_status)
+            .bind(self.clock.now())
+            .execute(&self.db)
+            .await?;
+            return Ok(());
+        };
+
+        if subscription.active && attempts < self.max_attempts {
+            let delay = backoff::retry_delay(attempts, INITIAL_RETRY_DELAY, MAX_RETRY_DELAY);
+            tracing::warn!(
+                "Webhook delivery {} to {} attempt {} failed, retrying in {}s: {}",
+                delivery.id,
+                subscription.url,
+                attempts,
+                delay.as_secs(),
+                error
+            );
+            sqlx::query(
+                "UPDATE webhook_deliveries SET attempts = $2, response_status = $3, last_error = $4,
+                    next_attempt_at = NOW() + make_interval(secs => $5)
+                 WHERE id = $1",
+            )
+            .bind(delivery.id)
+            .bind(attempts)
+            .bind(attempt.response_status)
+            .bind(&error)
+            .bind(delay.as_secs_f64())
+            .execute(&self.db)
+            .await?;
+            return Ok(());
+        }
+
+        metrics::counter!(
+            "webhook_deliveries_total",
+            "event" => delivery.event_type.clone(),
+            "outcome" => "dead_lettered"
+        )
+        .increment(1);
+        tracing::error!(
+            "Webhook delivery {} to {} dead-lettered after {} attempts: {}",
+            delivery.id,
+            subscription.url,
+            attempts,
+            error
+        );
+        sqlx::query(
+            "UPDATE webhook_deliveries SET status = $2, attempts = $3, response_status = $4, last_error = $5
+             WHERE id = $1",
+        )
+        .bind(delivery.id)
+        .bind(WebhookDelivery::DEAD_LETTERED)
+        .bind(attempts)
+        .bind(attempt.response_status)
+        .bind(&error)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn send(&self, subscription: &WebhookSubscription, delivery: &WebhookDelivery) -> Attempt {
+        let body = delivery_body(delivery).to_string();
+        let signature = signature_header(&subscription.secret, self.clock.now().timestamp(), body.as_bytes());
+
+        let response = self
+            .http
+            .post(&subscription.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .body(body)
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => Attempt {
+                response_status: Some(i32::from(response.status().as_u16())),
+                error: None,
+            },
+            Ok(response) => {
+                let status = response.status();
+                let text: String = response.text().await.unwrap_or_default().chars().take(MAX_ERROR_LEN).collect();
+                Attempt {
+                    response_status: Some(i32::from(status.as_u16())),
+                    error: Some(format!("Endpoint returned {}: {}", status, text)),
+                }
+            }
+            Err(e) => Attempt {
+                response_status: None,
+                error: Some(format!("Request failed: {}", e)),
+            },
+        }
+    }
+
+    /// Attempt due deliveries every `interval`
+    pub fn spawn(self, interval: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.process_due().await {
+                    tracing::error!("Webhook dispatcher pass failed: {}", e);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn request(url: &str, event_types: &[&str]) -> WebhookSubscriptionRequest {
+        WebhookSubscriptionRequest {
+            url: url.to_string(),
+            event_types: event_types.iter().map(|e| e.to_string()).collect(),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_hmac_matches_rfc_4231() {
+        assert_eq!(
+            hmac_hex(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_signature_covers_timestamp_and_body() {
+        let header = signature_header("whsec_test", 1_700_000_000, br#"{"event":"erc_issued"}"#);
+        let expected = hmac_hex(b"whsec_test", br#"1700000000.{"event":"erc_issued"}"#);
+
+        assert_eq!(header, format!("t=1700000000,v1={}", expected));
+        assert_ne!(header, signature_header("whsec_test", 1_700_000_001, br#"{"event":"erc_issued"}"#));
+        assert_ne!(header, signature_header("whsec_other", 1_700_000_000, br#"{"event":"erc_issued"}"#));
+    }
+
+    #[test]
+    fn test_generated_secrets_are_unique() {
+        let secret = generate_secret();
+        assert_eq!(secret.len(), "whsec_".len() + 64);
+        assert_ne!(secret, generate_secret());
+    }
+
+    #[test]
+    fn test_subscription_validation() {
+        assert!(validate_request(&request("https://example.com/hooks", &["erc_issued", "reading_anomaly"])).is_ok());
+        assert!(validate_request(&request("ftp://example.com/hooks", &["erc_issued"])).is_err());
+        assert!(validate_request(&request("not a url", &["erc_issued"])).is_err());
+        assert!(validate_request(&request("https://example.com/hooks", &[])).is_err());
+        assert!(validate_request(&request("https://example.com/hooks", &["ErcIssued"])).is_err());
+    }
+
+    #[test]
+    fn test_program_events_map_to_webhook_events() {
+        let cleared = ProgramEvent::MarketCleared {
+            epoch: 7,
+            clearing_price: 3_500,
+            total_volume: 120,
+            trades: 4,
+            cleared_by: "11111111111111111111111111111111".to_string(),
+            timestamp: 1_700_000_000,
+        };
+        let validated = ProgramEvent::ErcValidatedForTrading {
+            certificate_id: "ERC-7".to_string(),
+            authority: "11111111111111111111111111111111".to_string(),
+            nft_mint: "11111111111111111111111111111111".to_string(),
+            recipient: "11111111111111111111111111111111".to_string(),
+            timestamp: 1_700_000_000,
+        };
+
+        assert_eq!(program_event_type(&cleared), Some(WebhookSubscription::MARKET_CLEARED));
+        assert_eq!(program_event_type(&validated), None);
+    }
+
+    #[test]
+    fn test_delivery_body_wraps_payload() {
+        let created_at = Utc.with_ymd_and_hms(2024, 9, 23, 12, 0, 0).unwrap();
+        let delivery = WebhookDelivery {
+            id: Uuid::nil(),
+            subscription_id: Uuid::nil(),
+            event_type: WebhookSubscription::READING_ANOMALY.to_string(),
+            payload: json!({ "meter_id": "MTR-1" }),
+            status: WebhookDelivery::PENDING.to_string(),
+            attempts: 0,
+            response_status: None,
+            last_error: None,
+            next_attempt_at: created_at,
+            delivered_at: None,
+            created_at,
+            updated_at: created_at,
+        };
+        let body = delivery_body(&delivery);
+
+        assert_eq!(body["event"], "reading_anomaly");
+        assert_eq!(body["data"]["meter_id"], "MTR-1");
+        assert_eq!(body["id"], Uuid::nil().to_string());
+    }
+}
@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+/// Delay before attempt `attempts + 1`: `initial` after the first attempt, doubling after
+/// each further one up to `max`
+pub fn retry_delay(attempts: i32, initial: Duration, max: Duration) -> Duration {
+    let doublings = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    initial.saturating_mul(1 << doublings).min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        let (initial, max) = (Duration::from_secs(10), Duration::from_secs(3600));
+        assert_eq!(retry_delay(0, initial, max), initial);
+        assert_eq!(retry_delay(1, initial, max), initial);
+        assert_eq!(retry_delay(4, initial, max), Duration::from_secs(80));
+        assert_eq!(retry_delay(30, initial, max), max);
+        assert_eq!(retry_delay(i32::MAX, Duration::MAX, max), max);
+    }
+}
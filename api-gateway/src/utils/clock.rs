@@ -0,0 +1,128 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+
+/// Source of wall-clock time for services
+///
+/// Services read the time through an injected clock instead of `Utc::now()`, so tests
+/// can fast-forward deterministically with `SimulatedClock` (tests and the `test-utils`
+/// feature only).
+pub trait Clock: Send + Sync + Debug {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+/// First day of the month `date` falls in, and of the month after it
+pub fn month_of(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start = date.with_day(1).expect("every month has a first day");
+    let end = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    }
+    .expect("first day of the next month exists");
+    (start, end)
+}
+
+/// The real system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    pub fn shared() -> SharedClock {
+        Arc::new(SystemClock)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+pub use simulated::SimulatedClock;
+
+#[cfg(any(test, feature = "test-utils"))]
+mod simulated {
+    use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+    use chrono::{DateTime, Duration, Utc};
+
+    use super::{Clock, SharedClock};
+
+    /// Clock that only moves when told to; clones share the same time
+    #[derive(Debug, Clone)]
+    pub struct SimulatedClock {
+        now: Arc<Mutex<DateTime<Utc>>>,
+    }
+
+    impl SimulatedClock {
+        pub fn new(start: DateTime<Utc>) -> Self {
+            Self {
+                now: Arc::new(Mutex::new(start)),
+            }
+        }
+
+        pub fn shared(&self) -> SharedClock {
+            Arc::new(self.clone())
+        }
+
+        pub fn advance(&self, by: Duration) {
+            *self.time() += by;
+        }
+
+        pub fn set(&self, to: DateTime<Utc>) {
+            *self.time() = to;
+        }
+
+        /// The time stays usable after a test panicked while holding the lock
+        fn time(&self) -> MutexGuard<'_, DateTime<Utc>> {
+            self.now.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+
+    impl Clock for SimulatedClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.time()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_simulated_clock_moves_only_when_advanced() {
+        let start = Utc.with_ymd_and_hms(2024, 9, 23, 12, 0, 0).unwrap();
+        let clock = SimulatedClock::new(start);
+        let shared = clock.shared();
+
+        assert_eq!(shared.now(), start);
+        assert_eq!(shared.now(), start);
+
+        clock.advance(Duration::hours(25));
+        assert_eq!(shared.now(), start + Duration::hours(25));
+
+        clock.set(start);
+        assert_eq!(shared.now(), start);
+    }
+
+    #[test]
+    fn test_month_of_wraps_the_year() {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        assert_eq!(month_of(date(2024, 12, 31)), (date(2024, 12, 1), date(2025, 1, 1)));
+        assert_eq!(month_of(date(2024, 2, 29)), (date(2024, 2, 1), date(2024, 3, 1)));
+    }
+
+    #[test]
+    fn test_system_clock_tracks_utc_now() {
+        let before = Utc::now();
+        let now = SystemClock.now();
+        assert!(now >= before && now <= Utc::now());
+    }
+}
@@ -0,0 +1,35 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+
+use crate::error::{ApiError, Result};
+
+/// Opaque keyset cursor: the sort time and unique key of the last row on a page
+pub fn encode(time: DateTime<Utc>, key: &str) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}|{}", time.to_rfc3339(), key))
+}
+
+pub fn decode(cursor: &str) -> Result<(DateTime<Utc>, String)> {
+    let invalid = || ApiError::BadRequest("Invalid cursor".to_string());
+    let decoded = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+    let (time, key) = decoded.split_once('|').ok_or_else(invalid)?;
+    let time = DateTime::parse_from_rfc3339(time).map_err(|_| invalid())?;
+    Ok((time.with_timezone(&Utc), key.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_cursors_round_trip() {
+        let time = Utc.with_ymd_and_hms(2024, 9, 1, 8, 15, 0).unwrap();
+        let cursor = encode(time, "ERC-2024-0001");
+        assert_eq!(decode(&cursor).unwrap(), (time, "ERC-2024-0001".to_string()));
+
+        assert!(decode("not a cursor").is_err());
+        assert!(decode(&URL_SAFE_NO_PAD.encode("no separator")).is_err());
+    }
+}
@@ -0,0 +1,40 @@
+// Conversions between `Decimal`, used by API and domain types, and `BigDecimal`, which
+// sqlx maps NUMERIC columns to.
+
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use sqlx::types::BigDecimal;
+
+/// `value` as a `Decimal`, or zero if it is out of `Decimal`'s range
+pub fn decimal(value: &BigDecimal) -> Decimal {
+    Decimal::from_str(&value.to_string()).unwrap_or_default()
+}
+
+pub fn big_decimal(value: Decimal) -> BigDecimal {
+    BigDecimal::from_str(&value.to_string()).unwrap_or_default()
+}
+
+/// `value` as a `BigDecimal`, or zero if it is not finite
+pub fn big_decimal_from_f64(value: f64) -> BigDecimal {
+    BigDecimal::from_str(&value.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trips_numeric_values() {
+        let value = Decimal::from_str("1234.56789").unwrap();
+        assert_eq!(big_decimal(value), BigDecimal::from_str("1234.56789").unwrap());
+        assert_eq!(decimal(&big_decimal(value)), value);
+        assert_eq!(decimal(&BigDecimal::from_str("1e40").unwrap()), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_converts_readings() {
+        assert_eq!(big_decimal_from_f64(12.5), BigDecimal::from_str("12.5").unwrap());
+        assert_eq!(big_decimal_from_f64(f64::NAN), BigDecimal::default());
+    }
+}
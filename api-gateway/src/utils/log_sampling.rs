@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Metadata};
+use tracing_subscriber::layer::{Context, Filter};
+
+/// Field name that groups events for sampling (e.g. `warn!(event_key = %meter_id, ...)`).
+/// Events without it are grouped by callsite.
+pub const EVENT_KEY_FIELD: &str = "event_key";
+
+/// Sampling policy: admit the first `first_n` events per window, then 1 in `then_one_in`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SamplingPolicy {
+    pub first_n: u64,
+    pub then_one_in: u64,
+    pub window: Duration,
+}
+
+/// Policy applied to every event whose target starts with `module`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SamplingRule {
+    pub module: String,
+    pub policy: SamplingPolicy,
+}
+
+/// Log sampling configuration parsed from `LOG_SAMPLING`
+///
+/// Format: `<module>=<first_n>:<then_one_in>[:<window_secs>]`, comma separated.
+/// `*` sets the default policy for modules without a rule, e.g.
+/// `api_gateway::handlers::meters=20:100:60,*=1000:10`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogSamplingConfig {
+    pub rules: Vec<SamplingRule>,
+    pub default_policy: Option<SamplingPolicy>,
+}
+
+impl LogSamplingConfig {
+    const DEFAULT_WINDOW_SECS: u64 = 60;
+
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut config = LogSamplingConfig::default();
+
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (module, policy) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid log sampling rule: {}", entry))?;
+
+            let parts: Vec<&str> = policy.split(':').collect();
+            if parts.len() < 2 || parts.len() > 3 {
+                return Err(format!("Invalid log sampling policy: {}", policy));
+            }
+
+            let parse_part = |value: &str| {
+                value
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|_| format!("Invalid log sampling value: {}", value))
+            };
+
+            let first_n = parse_part(parts[0])?;
+            let then_one_in = parse_part(parts[1])?;
+            let window_secs = match parts.get(2) {
+                Some(value) => parse_part(value)?,
+                None => Self::DEFAULT_WINDOW_SECS,
+            };
+
+            if window_secs == 0 {
+                return Err(format!("Log sampling window must be positive: {}", entry));
+            }
+
+            let policy = SamplingPolicy {
+                first_n,
+                then_one_in,
+                window: Duration::from_secs(window_secs),
+            };
+
+            match module.trim() {
+                "*" => config.default_policy = Some(policy),
+                module => config.rules.push(SamplingRule {
+                    module: module.to_string(),
+                    policy,
+                }),
+            }
+        }
+
+        // Longest prefix wins when rules overlap
+        config.rules.sort_by_key(|rule| std::cmp::Reverse(rule.module.len()));
+
+        Ok(config)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.default_policy.is_none()
+    }
+
+    /// Find the rule name and policy applying to an event target
+    fn policy_for<'a>(&'a self, target: &str) -> Option<(&'a str, SamplingPolicy)> {
+        self.rules
+            .iter()
+            .find(|rule| target.starts_with(rule.module.as_str()))
+            .map(|rule| (rule.module.as_str(), rule.policy))
+            .or_else(|| self.default_policy.map(|policy| ("*", policy)))
+    }
+}
+
+#[derive(Debug)]
+struct WindowCounter {
+    window_start: Instant,
+    seen: u64,
+}
+
+/// Per-module, per-event-key sampler
+#[derive(Debug)]
+pub struct LogSampler {
+    config: LogSamplingConfig,
+    counters: Mutex<HashMap<(String, String), WindowCounter>>,
+}
+
+impl LogSampler {
+    /// Upper bound on tracked keys so a flood of unique keys cannot grow memory unbounded
+    const MAX_TRACKED_KEYS: usize = 10_000;
+
+    pub fn new(config: LogSamplingConfig) -> Self {
+        Self {
+            config,
+            counters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Decide whether an event should be emitted, recording suppressed events as metrics
+    pub fn admit(&self, target: &str, key: &str, now: Instant) -> bool {
+        let Some((module, policy)) = self.config.policy_for(target) else {
+            return true;
+        };
+
+        let mut counters = match self.counters.lock() {
+            Ok(counters) => counters,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if counters.len() >= Self::MAX_TRACKED_KEYS {
+            counters.retain(|_, counter| now.duration_since(counter.window_start) < policy.window);
+        }
+
+        let counter = counters
+            .entry((module.to_string(), key.to_string()))
+            .or_insert(WindowCounter {
+                window_start: now,
+                seen: 0,
+            });
+
+        if now.duration_since(counter.window_start) >= policy.window {
+            counter.window_start = now;
+            counter.seen = 0;
+        }
+
+        counter.seen += 1;
+
+        let admitted = counter.seen <= policy.first_n
+            || (policy.then_one_in > 0 && (counter.seen - policy.first_n) % policy.then_one_in == 0);
+
+        if !admitted {
+            metrics::counter!("log_events_suppressed_total", "module" => module.to_string())
+                .increment(1);
+        }
+
+        admitted
+    }
+}
+
+/// `tracing_subscriber` per-layer filter that applies a [`LogSampler`] to events
+pub struct SamplingFilter {
+    sampler: LogSampler,
+}
+
+impl SamplingFilter {
+    pub fn new(config: LogSamplingConfig) -> Self {
+        Self {
+            sampler: LogSampler::new(config),
+        }
+    }
+}
+
+impl<S> Filter<S> for SamplingFilter {
+    fn enabled(&self, _meta: &Metadata<'_>, _cx: &Context<'_, S>) -> bool {
+        true
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, _cx: &Context<'_, S>) -> bool {
+        let metadata = event.metadata();
+
+        let mut visitor = EventKeyVisitor(None);
+        event.record(&mut visitor);
+
+        let key = visitor.0.unwrap_or_else(|| {
+            format!(
+                "{}:{}",
+                metadata.file().unwrap_or(metadata.name()),
+                metadata.line().unwrap_or(0)
+            )
+        });
+
+        self.sampler.admit(metadata.target(), &key, Instant::now())
+    }
+}
+
+struct EventKeyVisitor(Option<String>);
+
+impl Visit for EventKeyVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == EVENT_KEY_FIELD {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == EVENT_KEY_FIELD {
+            self.0 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sampling_spec() {
+        let config =
+            LogSamplingConfig::parse("api_gateway::handlers=5:10, api_gateway::handlers::meters=2:3:30,*=100:0")
+                .unwrap();
+
+        assert_eq!(config.rules.len(), 2);
+        assert_eq!(config.rules[0].module, "api_gateway::handlers::meters");
+        assert_eq!(config.rules[0].policy.window, Duration::from_secs(30));
+        assert_eq!(config.rules[1].policy.window, Duration::from_secs(60));
+        assert_eq!(config.default_policy.unwrap().first_n, 100);
+
+        assert!(LogSamplingConfig::parse("meters").is_err());
+        assert!(LogSamplingConfig::parse("meters=1").is_err());
+        assert!(LogSamplingConfig::parse("meters=1:2:0").is_err());
+        assert!(LogSamplingConfig::parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_first_n_then_one_in_m() {
+        let sampler = LogSampler::new(LogSamplingConfig::parse("api_gateway::handlers::meters=2:3").unwrap());
+        let now = Instant::now();
+
+        let admitted: Vec<bool> = (0..8)
+            .map(|_| sampler.admit("api_gateway::handlers::meters", "meter-1", now))
+            .collect();
+
+        assert_eq!(admitted, vec![true, true, false, false, true, false, false, true]);
+
+        // Other keys and unmatched modules are tracked independently
+        assert!(sampler.admit("api_gateway::handlers::meters", "meter-2", now));
+        assert!((0..10).all(|_| sampler.admit("api_gateway::handlers::trading", "x", now)));
+    }
+
+    #[test]
+    fn test_window_reset() {
+        let sampler = LogSampler::new(LogSamplingConfig::parse("*=1:0:10").unwrap());
+        let start = Instant::now();
+
+        assert!(sampler.admit("any", "key", start));
+        assert!(!sampler.admit("any", "key", start + Duration::from_secs(5)));
+        assert!(sampler.admit("any", "key", start + Duration::from_secs(11)));
+    }
+}
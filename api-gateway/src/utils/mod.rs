@@ -1,2 +1,12 @@
 // Utility functions
-// Validation, encryption, formatting, etc.
\ No newline at end of file
+// Validation, encryption, formatting, etc.
+
+pub mod backoff;
+pub mod log_sampling;
+pub mod clock;
+pub mod html;
+pub mod telemetry;
+pub mod cursor;
+pub mod decimal;
+pub mod pdf;
+pub mod validation;
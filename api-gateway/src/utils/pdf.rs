@@ -0,0 +1,154 @@
+/// A4 in points
+const PAGE_WIDTH: u32 = 595;
+const PAGE_HEIGHT: u32 = 842;
+const MARGIN: u32 = 50;
+const FONT_SIZE: u32 = 9;
+const LEADING: u32 = 12;
+
+/// Lines of text that fit on one page
+pub const LINES_PER_PAGE: usize = ((PAGE_HEIGHT - 2 * MARGIN) / LEADING) as usize;
+
+/// Characters of 9pt Courier that fit between the margins
+pub const LINE_WIDTH: usize = 91;
+
+/// Text-only PDF set in Courier, so columns padded with spaces stay aligned
+///
+/// Only printable ASCII is rendered; other characters are replaced with `?`.
+#[derive(Debug, Default)]
+pub struct PdfDocument {
+    pages: Vec<Vec<String>>,
+}
+
+impl PdfDocument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a line, starting a new page when the current one is full
+    pub fn line(&mut self, text: impl Into<String>) {
+        match self.pages.last_mut() {
+            Some(page) if page.len() < LINES_PER_PAGE => page.push(text.into()),
+            _ => self.pages.push(vec![text.into()]),
+        }
+    }
+
+    /// Start the next line on a new page
+    pub fn page_break(&mut self) {
+        self.pages.push(Vec::new());
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let empty = [Vec::new()];
+        let pages = if self.pages.is_empty() { &empty[..] } else { &self.pages[..] };
+
+        // 1 catalog, 2 page tree, 3 font, then each page followed by its content stream
+        let kids: Vec<String> = (0..pages.len()).map(|i| format!("{} 0 R", 4 + 2 * i)).collect();
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_string(),
+        ];
+        for (i, lines) in pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> \
+                 /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                5 + 2 * i
+            ));
+
+            let mut stream = format!(
+                "BT\n/F1 {} Tf\n{} TL\n{} {} Td\n",
+                FONT_SIZE,
+                LEADING,
+                MARGIN,
+                PAGE_HEIGHT - MARGIN
+            );
+            for line in lines {
+                stream.push('(');
+                stream.push_str(&escape(line));
+                stream.push_str(") Tj T*\n");
+            }
+            stream.push_str("ET");
+            objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", stream.len(), stream));
+        }
+
+        let mut out = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+        }
+
+        let xref = out.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            table.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        table.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        ));
+        out.extend_from_slice(table.as_bytes());
+        out
+    }
+}
+
+/// `text` as the body of a PDF string literal
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\\' | '(' | ')' => format!("\\{}", c),
+            ' '..='~' => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_keeps_string_literals_balanced() {
+        assert_eq!(escape(r"VAT (7%) \ net"), r"VAT \(7%\) \\ net");
+        assert_eq!(escape("สมชาย Jaidee"), "????? Jaidee");
+    }
+
+    #[test]
+    fn test_lines_overflow_onto_new_pages() {
+        let mut document = PdfDocument::new();
+        for i in 0..LINES_PER_PAGE + 1 {
+            document.line(format!("line {}", i));
+        }
+        document.page_break();
+        document.line("next statement");
+        let pdf = String::from_utf8(document.to_bytes()).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.contains("/Count 3"));
+        assert!(pdf.ends_with("%%EOF\n"));
+    }
+
+    #[test]
+    fn test_xref_points_at_each_object() {
+        let mut document = PdfDocument::new();
+        document.line("Settlement statement");
+        let pdf = String::from_utf8(document.to_bytes()).unwrap();
+
+        let xref = pdf.find("\nxref\n").unwrap() + 1;
+        let startxref: usize = pdf.split("startxref\n").nth(1).unwrap().lines().next().unwrap().parse().unwrap();
+        assert_eq!(startxref, xref);
+
+        let offsets: Vec<usize> = pdf[xref..]
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse().unwrap())
+            .collect();
+        assert_eq!(offsets.len(), 5);
+        for (i, offset) in offsets.into_iter().enumerate() {
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj\n", i + 1)));
+        }
+    }
+}
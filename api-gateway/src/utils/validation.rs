@@ -0,0 +1,226 @@
+use axum::async_trait;
+use axum::extract::{FromRequest, Request};
+use axum::Json;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::error::{ApiError, Result};
+use crate::AppState;
+
+// Program limits are repeated here so validation builds without the `chain` feature;
+// `test_limits_match_the_programs` keeps them in step with the program crates.
+
+/// Longest meter ID a reading can carry; the oracle program seeds reading PDAs with it
+pub const MAX_METER_ID_LEN: usize = 32;
+/// Longest building and zone the registry program stores for a meter
+pub const MAX_BUILDING_LEN: usize = 64;
+pub const MAX_ZONE_LEN: usize = 32;
+/// Longest location the registry program stores for a user
+pub const MAX_LOCATION_LEN: usize = 100;
+/// ERC limits enforced by the governance program
+pub const MAX_CERTIFICATE_ID_LEN: usize = 64;
+pub const MAX_VALIDATION_DATA_LEN: usize = 252;
+pub const MAX_SOURCE_READINGS: u64 = 8;
+/// Oldest reading the oracle program accepts by default, and how far ahead of its clock one may be, in seconds
+pub const MAX_READING_AGE_SECONDS: i64 = 7 * 24 * 60 * 60;
+pub const MAX_CLOCK_DRIFT_SECONDS: i64 = 5 * 60;
+/// Longest base58 ed25519 signature
+pub const MAX_SIGNATURE_LEN: u64 = 88;
+
+/// Most energy one reading may report, in kWh; `energy_readings` holds up to DECIMAL(10, 4)
+pub const MAX_READING_KWH: f64 = 100_000.0;
+/// Highest price per kWh an order may ask, in THB
+pub const MAX_PRICE_PER_KWH: Decimal = Decimal::from_parts(1_000, 0, 0, false, 0);
+/// Decimal places orders are stored with in `trading_orders`
+pub const MAX_ORDER_SCALE: u32 = 8;
+
+fn error(code: &'static str, message: String) -> ValidationError {
+    let mut error = ValidationError::new(code);
+    error.message = Some(message.into());
+    error
+}
+
+fn byte_length(value: &str, min: usize, max: usize) -> std::result::Result<(), ValidationError> {
+    if value.len() < min || value.len() > max {
+        return Err(error("length", format!("must be {} to {} bytes", min, max)));
+    }
+    Ok(())
+}
+
+/// Meter IDs the oracle program can seed a reading PDA with
+pub fn meter_id(value: &str) -> std::result::Result<(), ValidationError> {
+    byte_length(value, 1, MAX_METER_ID_LEN)
+}
+
+pub fn location(value: &str) -> std::result::Result<(), ValidationError> {
+    byte_length(value, 0, MAX_LOCATION_LEN)
+}
+
+pub fn certificate_id(value: &str) -> std::result::Result<(), ValidationError> {
+    byte_length(value, 1, MAX_CERTIFICATE_ID_LEN)
+}
+
+pub fn validation_data(value: &str) -> std::result::Result<(), ValidationError> {
+    byte_length(value, 0, MAX_VALIDATION_DATA_LEN)
+}
+
+/// Energy a reading reports: finite and between 0 and `MAX_READING_KWH`
+pub fn reading_kwh(value: f64) -> std::result::Result<(), ValidationError> {
+    if !value.is_finite() || !(0.0..=MAX_READING_KWH).contains(&value) {
+        return Err(error("range", format!("must be between 0 and {} kWh", MAX_READING_KWH)));
+    }
+    Ok(())
+}
+
+/// Positive amount with no more decimal places than orders are stored with
+fn order_amount(value: &Decimal, max: Option<Decimal>) -> std::result::Result<(), ValidationError> {
+    if *value <= Decimal::ZERO {
+        return Err(error("range", "must be positive".to_string()));
+    }
+    if let Some(max) = max.filter(|max| value > max) {
+        return Err(error("range", format!("must be at most {}", max)));
+    }
+    if value.normalize().scale() > MAX_ORDER_SCALE {
+        return Err(error("scale", format!("must have at most {} decimal places", MAX_ORDER_SCALE)));
+    }
+    Ok(())
+}
+
+pub fn order_energy(value: &Decimal) -> std::result::Result<(), ValidationError> {
+    order_amount(value, None)
+}
+
+pub fn order_price(value: &Decimal) -> std::result::Result<(), ValidationError> {
+    order_amount(value, Some(MAX_PRICE_PER_KWH))
+}
+
+/// Reading time the oracle program accepts at `now`: no older than its default maximum reading
+/// age and no further ahead than its clock drift allowance
+pub fn reading_time(value: DateTime<Utc>, now: DateTime<Utc>) -> std::result::Result<(), ValidationError> {
+    if value > now + Duration::seconds(MAX_CLOCK_DRIFT_SECONDS) {
+        return Err(error("future", "is ahead of the current time".to_string()));
+    }
+    if value < now - Duration::seconds(MAX_READING_AGE_SECONDS) {
+        return Err(error("stale", "is too old to be recorded on-chain".to_string()));
+    }
+    Ok(())
+}
+
+/// Times such as order expiries, which must be after `now`
+pub fn future_time(value: DateTime<Utc>, now: DateTime<Utc>) -> std::result::Result<(), ValidationError> {
+    if value <= now {
+        return Err(error("past", "must be in the future".to_string()));
+    }
+    Ok(())
+}
+
+/// `error` as the only failure of `field`
+pub fn field_error(field: &'static str, error: ValidationError) -> ValidationErrors {
+    let mut errors = ValidationErrors::new();
+    errors.add(field, error);
+    errors
+}
+
+/// Request body checked before its handler runs
+///
+/// Field limits are derived with `validator`; checks against the current time, read from the
+/// gateway clock, go in `validate_at`.
+pub trait ValidateRequest: Validate {
+    fn validate_at(&self, _now: DateTime<Utc>) -> std::result::Result<(), ValidationErrors> {
+        Ok(())
+    }
+}
+
+/// Check `request` against its field limits and, at `now`, its timestamps
+pub fn check<T: ValidateRequest>(request: &T, now: DateTime<Utc>) -> Result<()> {
+    request
+        .validate()
+        .and_then(|()| request.validate_at(now))
+        .map_err(|e| ApiError::Validation(e.to_string()))
+}
+
+/// JSON body rejected with `400` unless it passes its `ValidateRequest` checks
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T> FromRequest<AppState> for ValidatedJson<T>
+where
+    T: DeserializeOwned + ValidateRequest,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &AppState) -> Result<Self> {
+        let Json(value) = Json::<T>::from_request(request, state)
+            .await
+            .map_err(|e| ApiError::BadRequest(e.body_text()))?;
+        check(&value, state.clock.now())?;
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_limits_follow_the_programs() {
+        assert!(meter_id("MTR-001_a").is_ok());
+        assert!(meter_id(&"M".repeat(MAX_METER_ID_LEN)).is_ok());
+        assert!(meter_id(&"M".repeat(MAX_METER_ID_LEN + 1)).is_err());
+        assert!(meter_id("").is_err());
+
+        // Limits are bytes on-chain, so multi-byte text reaches them sooner
+        let thai = "อาคาร".repeat(MAX_LOCATION_LEN / 15 + 1);
+        assert!(thai.chars().count() < MAX_LOCATION_LEN);
+        assert!(location(&thai).is_err());
+        assert!(certificate_id(&"C".repeat(MAX_CERTIFICATE_ID_LEN)).is_ok());
+        assert!(certificate_id(&"C".repeat(MAX_CERTIFICATE_ID_LEN + 1)).is_err());
+        assert!(validation_data("").is_ok());
+    }
+
+    #[cfg(feature = "chain")]
+    #[test]
+    fn test_limits_match_the_programs() {
+        assert_eq!(MAX_METER_ID_LEN, oracle::MeterReading::MAX_METER_ID_LEN);
+        assert_eq!(MAX_BUILDING_LEN, registry::MeterAccount::MAX_BUILDING_LEN);
+        assert_eq!(MAX_ZONE_LEN, registry::MeterAccount::MAX_ZONE_LEN);
+        assert_eq!(MAX_LOCATION_LEN, registry::UserAccount::MAX_LOCATION_LEN);
+        assert_eq!(MAX_CERTIFICATE_ID_LEN, governance::ErcCertificate::MAX_CERTIFICATE_ID_LEN);
+        assert_eq!(MAX_VALIDATION_DATA_LEN, governance::ErcCertificate::MAX_VALIDATION_DATA_LEN);
+        assert_eq!(MAX_SOURCE_READINGS, governance::ErcCertificate::MAX_SOURCE_READINGS as u64);
+        assert_eq!(MAX_READING_AGE_SECONDS, oracle::OracleData::DEFAULT_MAX_READING_AGE);
+        assert_eq!(MAX_CLOCK_DRIFT_SECONDS, oracle::OracleData::MAX_CLOCK_DRIFT);
+    }
+
+    #[test]
+    fn test_reading_energy_and_time() {
+        assert!(reading_kwh(0.0).is_ok());
+        assert!(reading_kwh(MAX_READING_KWH).is_ok());
+        assert!(reading_kwh(-0.1).is_err());
+        assert!(reading_kwh(f64::NAN).is_err());
+        assert!(reading_kwh(f64::INFINITY).is_err());
+
+        let now = Utc.with_ymd_and_hms(2024, 9, 23, 12, 0, 0).unwrap();
+        assert!(reading_time(now, now).is_ok());
+        assert!(reading_time(now + Duration::minutes(5), now).is_ok());
+        assert!(reading_time(now + Duration::minutes(6), now).is_err());
+        assert!(reading_time(now - Duration::days(7), now).is_ok());
+        assert!(reading_time(now - Duration::days(8), now).is_err());
+    }
+
+    #[test]
+    fn test_order_amounts() {
+        assert!(order_energy(&Decimal::from_str("12.5").unwrap()).is_ok());
+        assert!(order_energy(&Decimal::ZERO).is_err());
+        assert!(order_energy(&Decimal::from_str("-1").unwrap()).is_err());
+        assert!(order_energy(&Decimal::from_str("0.000000001").unwrap()).is_err());
+        assert!(order_energy(&Decimal::from_str("1.100000000").unwrap()).is_ok());
+        assert!(order_price(&MAX_PRICE_PER_KWH).is_ok());
+        assert!(order_price(&(MAX_PRICE_PER_KWH + Decimal::ONE)).is_err());
+    }
+}
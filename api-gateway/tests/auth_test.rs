@@ -1,6 +1,18 @@
 use api_gateway::{AppState, config::Config};
 use api_gateway::auth::{jwt::JwtService, jwt::ApiKeyService, Claims};
 use api_gateway::auth::password::PasswordService;
+use api_gateway::database::redis_pool::RedisPool;
+use api_gateway::database::PoolSettings;
+use api_gateway::services::blockchain::BlockchainService;
+use api_gateway::services::dashboard::DashboardMirror;
+use api_gateway::services::erc_events::ErcEventFeed;
+use api_gateway::services::fee_payers::FeePayerPool;
+use api_gateway::services::gateway_signer::GatewaySigner;
+use api_gateway::services::market_feed::MarketFeed;
+use api_gateway::services::order_book::OrderBookMirror;
+use api_gateway::services::program_logs::ProgramLogSubscriber;
+use api_gateway::services::simulation::SimulationRuns;
+use api_gateway::utils::clock::SystemClock;
 use api_gateway::handlers::auth::{LoginRequest, UpdateProfileRequest, ChangePasswordRequest};
 use api_gateway::handlers::user_management::EnhancedRegisterRequest;
 use axum::{
@@ -13,8 +25,11 @@ use axum::{
     Router,
 };
 use chrono::Utc;
+use metrics_exporter_prometheus::PrometheusBuilder;
 use serde_json::{json, Value};
 use sqlx::Row;
+use std::sync::Arc;
+use std::time::Duration;
 use tower::ServiceExt;
 use uuid::Uuid;
 
@@ -31,29 +46,49 @@ impl TestContext {
         let config = Config::from_env().expect("Failed to load test config");
         
         // Setup test database
-        let db_pool = api_gateway::database::setup_database(&config.database_url)
+        let pool_settings = PoolSettings::from_config(&config);
+        let db_pool = api_gateway::database::setup_database(&config.database_url, &pool_settings)
             .await
             .expect("Failed to setup database");
         
-        let timescale_pool = api_gateway::database::setup_timescale_database(&config.timescale_url)
+        let timescale_pool = api_gateway::database::setup_timescale_database(&config.timescale_url, &pool_settings)
             .await
             .expect("Failed to setup TimescaleDB");
         
         // Setup Redis
         let redis_client = redis::Client::open(config.redis_url.as_str())
             .expect("Failed to setup Redis");
+        let redis_pool = RedisPool::new(redis_client, config.redis_pool_size, Duration::from_millis(config.redis_acquire_timeout_ms));
         
         // Initialize auth services
         let jwt_service = JwtService::new().expect("Failed to init JWT service");
         let api_key_service = ApiKeyService::new().expect("Failed to init API key service");
+        let blockchain_service = BlockchainService::new(&config.solana_rpc_url)
+            .expect("Failed to init blockchain service");
         
         let state = AppState {
-            db: db_pool,
+            db: db_pool.clone(),
             timescale_db: timescale_pool,
-            redis: redis_client,
+            redis: redis_pool,
             config: config.clone(),
             jwt_service,
             api_key_service,
+            blockchain_service,
+            order_book: Arc::new(OrderBookMirror::new()),
+            dashboard: Arc::new(DashboardMirror::new(SystemClock::shared(), Duration::from_secs(30))),
+            program_events: Arc::new(
+                ProgramLogSubscriber::from_config(&config).expect("Failed to init program log subscriber"),
+            ),
+            market_feed: Arc::new(MarketFeed::new()),
+            erc_events: Arc::new(ErcEventFeed::new(db_pool.clone(), SystemClock::shared())),
+            simulation: SimulationRuns::new(),
+            signer: GatewaySigner::from_config(&config, SystemClock::shared())
+                .await
+                .expect("Failed to load gateway signer"),
+            fee_payers: FeePayerPool::from_config(&config).expect("Failed to load fee payers"),
+            clock: SystemClock::shared(),
+            metrics: PrometheusBuilder::new().build_recorder().handle(),
+            graphql: api_gateway::graphql::schema(),
         };
         
         // Create test user
@@ -196,7 +231,8 @@ async fn test_login_invalid_username() {
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     
     let json = extract_json(response).await;
-    assert_eq!(json["error"], "Invalid credentials");
+    assert_eq!(json["code"], "UNAUTHORIZED");
+    assert_eq!(json["detail"], "Unauthorized: Invalid credentials");
     
     ctx.cleanup().await;
 }
@@ -223,7 +259,8 @@ async fn test_login_invalid_password() {
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     
     let json = extract_json(response).await;
-    assert_eq!(json["error"], "Invalid credentials");
+    assert_eq!(json["code"], "UNAUTHORIZED");
+    assert_eq!(json["detail"], "Unauthorized: Invalid credentials");
     
     ctx.cleanup().await;
 }
@@ -251,7 +288,8 @@ async fn test_login_validation_errors() {
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     
     let json = extract_json(response).await;
-    assert!(json["error"].as_str().unwrap().contains("Validation error"));
+    assert_eq!(json["code"], "BAD_REQUEST");
+    assert!(json["detail"].as_str().unwrap().contains("Validation error"));
     
     ctx.cleanup().await;
 }
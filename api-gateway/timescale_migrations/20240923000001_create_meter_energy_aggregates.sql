@@ -0,0 +1,79 @@
+-- Meter reading hypertable and per-meter energy rollups for time-series charts
+--
+-- The table matches docker/timescaledb/init.sql, so readings from the simulators and the
+-- gateway land in the same hypertable.
+CREATE EXTENSION IF NOT EXISTS timescaledb;
+
+CREATE TABLE IF NOT EXISTS energy_readings (
+    time TIMESTAMPTZ NOT NULL,
+    meter_id VARCHAR(64) NOT NULL,
+    energy_generated DECIMAL(18, 8) DEFAULT 0,
+    energy_consumed DECIMAL(18, 8) DEFAULT 0,
+    voltage DECIMAL(10, 2),
+    current DECIMAL(10, 2),
+    power_factor DECIMAL(4, 2),
+    frequency DECIMAL(6, 2),
+    temperature DECIMAL(6, 2),
+    irradiance DECIMAL(10, 2),
+    weather_condition VARCHAR(50),
+    grid_connection_status VARCHAR(20) DEFAULT 'Connected'
+);
+
+SELECT create_hypertable('energy_readings', 'time', if_not_exists => TRUE);
+CREATE INDEX IF NOT EXISTS idx_energy_readings_meter_id_time ON energy_readings (meter_id, time DESC);
+
+-- Created WITH NO DATA so they can be built inside the migration transaction; the refresh
+-- policies below backfill them. Real-time aggregation covers the not yet materialized tail.
+CREATE MATERIALIZED VIEW IF NOT EXISTS meter_energy_15m
+WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+SELECT
+    time_bucket('15 minutes', time) AS bucket,
+    meter_id,
+    SUM(energy_generated) AS energy_generated,
+    SUM(energy_consumed) AS energy_consumed,
+    COUNT(*) AS reading_count
+FROM energy_readings
+GROUP BY bucket, meter_id
+WITH NO DATA;
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS meter_energy_hourly
+WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+SELECT
+    time_bucket('1 hour', time) AS bucket,
+    meter_id,
+    SUM(energy_generated) AS energy_generated,
+    SUM(energy_consumed) AS energy_consumed,
+    COUNT(*) AS reading_count
+FROM energy_readings
+GROUP BY bucket, meter_id
+WITH NO DATA;
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS meter_energy_daily
+WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+SELECT
+    time_bucket('1 day', time) AS bucket,
+    meter_id,
+    SUM(energy_generated) AS energy_generated,
+    SUM(energy_consumed) AS energy_consumed,
+    COUNT(*) AS reading_count
+FROM energy_readings
+GROUP BY bucket, meter_id
+WITH NO DATA;
+
+SELECT add_continuous_aggregate_policy('meter_energy_15m',
+    start_offset => INTERVAL '2 hours',
+    end_offset => INTERVAL '15 minutes',
+    schedule_interval => INTERVAL '15 minutes',
+    if_not_exists => TRUE);
+
+SELECT add_continuous_aggregate_policy('meter_energy_hourly',
+    start_offset => INTERVAL '1 day',
+    end_offset => INTERVAL '1 hour',
+    schedule_interval => INTERVAL '1 hour',
+    if_not_exists => TRUE);
+
+SELECT add_continuous_aggregate_policy('meter_energy_daily',
+    start_offset => INTERVAL '7 days',
+    end_offset => INTERVAL '1 day',
+    schedule_interval => INTERVAL '1 day',
+    if_not_exists => TRUE);
@@ -0,0 +1,20 @@
+-- History of the official grid tariff, one row per signed snapshot the price oracle ingested,
+-- with the transaction job publishing it to the oracle program
+CREATE TABLE IF NOT EXISTS grid_prices (
+    time TIMESTAMPTZ NOT NULL, -- when the tariff took effect
+    tariff VARCHAR(64) NOT NULL,
+    price_per_kwh DECIMAL(18, 8) NOT NULL, -- in currency per kWh
+    currency VARCHAR(8) NOT NULL,
+    published_at TIMESTAMPTZ, -- when the publisher signed the snapshot, if it says
+    snapshot_hash CHAR(64) NOT NULL, -- SHA-256 hex of the signed payload
+    payload TEXT NOT NULL, -- signed payload, kept so the signature can be checked again
+    signature VARCHAR(128) NOT NULL, -- base58 ed25519 signature over the payload
+    public_key VARCHAR(64) NOT NULL,
+    source_url TEXT NOT NULL,
+    tx_job_id UUID, -- NULL until queued for the oracle program
+    ingested_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT create_hypertable('grid_prices', 'time', if_not_exists => TRUE);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_grid_prices_snapshot ON grid_prices (snapshot_hash, time);
+CREATE INDEX IF NOT EXISTS idx_grid_prices_tariff_time ON grid_prices (tariff, time DESC);